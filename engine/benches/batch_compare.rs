@@ -1,5 +1,5 @@
 use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
-use match_engine::{Command, OrderBook, Side};
+use match_engine::{AccountId, Command, OrderBook, Side, TimeInForce};
 
 fn seed_book(levels: usize, base_price: u64, tick: u64, qty_per_level: u64) -> OrderBook {
     let mut ob = OrderBook::new();
@@ -15,7 +15,7 @@ fn seed_book(levels: usize, base_price: u64, tick: u64, qty_per_level: u64) -> O
 fn build_limit_cmds(n: u64, base_px: u64) -> Vec<Command> {
     let mut cmds = Vec::with_capacity(n as usize);
     for i in 0..n {
-        let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
+        let side = if i.is_multiple_of(2) { Side::Buy } else { Side::Sell };
         let cross = i % 10 < 3;
         let px = if side == Side::Buy {
             if cross { base_px + 1 } else { base_px.saturating_sub(5) }
@@ -23,7 +23,7 @@ fn build_limit_cmds(n: u64, base_px: u64) -> Vec<Command> {
             if cross { base_px.saturating_sub(1) } else { base_px + 5 }
         };
         let qty = 1 + (i % 5);
-        cmds.push(Command::Limit { seq: i, side, price: px, qty });
+        cmds.push(Command::Limit { seq: i, side, price: px, qty, tif: TimeInForce::Gtc, account: AccountId(0) });
     }
     cmds
 }
@@ -39,7 +39,7 @@ fn bench_batch_compare(c: &mut Criterion) {
                 || seed_book(200, 10_000, 1, 1_000),
                 |mut ob| {
                     for i in 0..n {
-                        let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
+                        let side = if i.is_multiple_of(2) { Side::Buy } else { Side::Sell };
                         let px = if side == Side::Buy { 9_995 } else { 10_005 };
                         let qty = 1 + (i % 5);
                         let _ = ob.submit_limit(side, black_box(px), black_box(qty));
@@ -56,7 +56,7 @@ fn bench_batch_compare(c: &mut Criterion) {
                 |mut ob| {
                     let mut trades = Vec::with_capacity(1024);
                     for i in 0..n {
-                        let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
+                        let side = if i.is_multiple_of(2) { Side::Buy } else { Side::Sell };
                         let px = if side == Side::Buy { 9_995 } else { 10_005 };
                         let qty = 1 + (i % 5);
                         let _ = ob.submit_limit_into(side, black_box(px), black_box(qty), &mut trades);
@@ -83,6 +83,19 @@ fn bench_batch_compare(c: &mut Criterion) {
                 BatchSize::LargeInput,
             );
         });
+
+        // Batch mode for trusted callers: seqs already strictly increasing, no sort or duplicate scan
+        group.bench_with_input(BenchmarkId::new("batch_presorted", orders), &orders, |b, &n| {
+            b.iter_batched(
+                || (seed_book(200, 10_000, 1, 1_000), build_limit_cmds(n, 10_000)),
+                |(mut ob, cmds)| {
+                    let mut trades = Vec::with_capacity((n as usize).min(4096));
+                    let _ = ob.process_commands_batch_presorted_unchecked(&cmds, &mut trades);
+                    black_box(trades);
+                },
+                BatchSize::LargeInput,
+            );
+        });
     }
     group.finish();
 }
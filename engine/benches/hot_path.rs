@@ -0,0 +1,62 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use match_engine::{BTreeStorage, BookStorage, LadderStorage, OrderBook, Side, SkipListStorage, SlabStorage, SoaStorage};
+
+// Inner matching loop only: books are seeded outside the timed section.
+//   full_fills    one market order sweeps `levels` levels of `per_level` 1-lot makers, every maker fully filled
+//   partial_fill  small takers nibble one large maker, every fill is the partial tail
+
+const MID: u64 = 10_000;
+
+fn seed_makers<S: BookStorage>(storage: S, levels: u64, per_level: u64, qty: u64) -> OrderBook<S> {
+    let mut ob = OrderBook::with_storage(storage);
+    for i in 1..=levels {
+        for _ in 0..per_level { let _ = ob.submit_limit(Side::Sell, MID + i, qty); }
+    }
+    ob
+}
+
+fn sweep<S: BookStorage>(ob: &mut OrderBook<S>, qty: u64) -> usize {
+    let mut trades = Vec::with_capacity(qty as usize);
+    let _ = ob.submit_market_into(Side::Buy, black_box(qty), &mut trades);
+    trades.len()
+}
+
+fn nibble<S: BookStorage>(ob: &mut OrderBook<S>, n: u64) {
+    let mut trades = Vec::with_capacity(1);
+    for _ in 0..n {
+        let _ = ob.submit_market_into(Side::Buy, black_box(1), &mut trades);
+        trades.clear();
+    }
+}
+
+macro_rules! backends {
+    ($group:expr, $id:expr, $input:expr, $setup:expr, $routine:expr) => {
+        $group.bench_with_input(BenchmarkId::new("btree", $id), $input, |b, _| b.iter_batched(|| $setup(BTreeStorage::default()), $routine, BatchSize::LargeInput));
+        $group.bench_with_input(BenchmarkId::new("ladder", $id), $input, |b, _| b.iter_batched(|| $setup(LadderStorage::with_range(9_000, 11_000)), $routine, BatchSize::LargeInput));
+        $group.bench_with_input(BenchmarkId::new("slab", $id), $input, |b, _| b.iter_batched(|| $setup(SlabStorage::default()), $routine, BatchSize::LargeInput));
+        $group.bench_with_input(BenchmarkId::new("soa", $id), $input, |b, _| b.iter_batched(|| $setup(SoaStorage::default()), $routine, BatchSize::LargeInput));
+        $group.bench_with_input(BenchmarkId::new("skiplist", $id), $input, |b, _| b.iter_batched(|| $setup(SkipListStorage::default()), $routine, BatchSize::LargeInput));
+    };
+}
+
+fn bench_full_fills(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hot_path_full_fills");
+    for &(levels, per_level) in &[(10u64, 100u64), (200, 5)] {
+        let fills = levels * per_level;
+        group.throughput(Throughput::Elements(fills));
+        let id = format!("{levels}x{per_level}");
+        backends!(group, &id, &fills, |s| seed_makers(s, levels, per_level, 1), |mut ob| sweep(&mut ob, fills));
+    }
+    group.finish();
+}
+
+fn bench_partial_fill(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hot_path_partial_fill");
+    let n = 10_000u64;
+    group.throughput(Throughput::Elements(n));
+    backends!(group, n, &n, |s| seed_makers(s, 1, 1, n + 1), |mut ob| nibble(&mut ob, n));
+    group.finish();
+}
+
+criterion_group!(benches, bench_full_fills, bench_partial_fill);
+criterion_main!(benches);
@@ -0,0 +1,100 @@
+// Deterministic counterpart of the criterion benches: each function runs once under cachegrind and
+// reports instructions, L1/LL hits, RAM accesses and estimated cycles, so changes on the submit,
+// cancel and batch paths can be compared without wall-clock noise. Setup is part of every count;
+// the `*_setup` baselines measure it alone so it can be subtracted.
+use iai::black_box;
+use match_engine::{AccountId, Command, OrderBook, OrderId, Side, TimeInForce};
+
+const LEVELS: u64 = 100;
+const OPS: u64 = 1_000;
+
+fn seeded() -> OrderBook {
+    let mut ob = OrderBook::new();
+    for i in 1..=LEVELS {
+        let _ = ob.submit_limit(Side::Buy, 10_000 - i, 100);
+        let _ = ob.submit_limit(Side::Sell, 10_000 + i, 100);
+    }
+    ob
+}
+
+fn book_setup() -> OrderBook { black_box(seeded()) }
+
+fn submit_passive() -> OrderBook {
+    let mut ob = seeded();
+    let mut trades = Vec::new();
+    for i in 0..OPS {
+        let (side, px) = if i % 2 == 0 { (Side::Buy, 10_000 - 1 - i % LEVELS) } else { (Side::Sell, 10_000 + 1 + i % LEVELS) };
+        let _ = ob.submit_limit_into(side, black_box(px), black_box(1), &mut trades);
+    }
+    ob
+}
+
+fn submit_crossing() -> OrderBook {
+    let mut ob = seeded();
+    let mut trades = Vec::with_capacity(16);
+    for i in 0..OPS {
+        let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
+        let _ = ob.submit_market_into(side, black_box(3), &mut trades);
+        trades.clear();
+    }
+    ob
+}
+
+fn cancel_setup() -> (OrderBook, Vec<OrderId>) {
+    let mut ob = seeded();
+    let ids = (0..OPS).map(|i| ob.submit_limit(Side::Buy, 10_000 - 1 - i % LEVELS, 1).unwrap().0).collect();
+    black_box((ob, ids))
+}
+
+fn cancel() -> OrderBook {
+    let (mut ob, ids) = cancel_setup();
+    // Interleave levels and queue positions rather than cancelling in arrival order
+    for i in 0..ids.len() { let _ = ob.cancel(ids[(i * 7) % ids.len()]); }
+    ob
+}
+
+fn batch_commands() -> Vec<Command> {
+    (0..OPS).map(|i| {
+        let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
+        match i % 10 {
+            0..=2 => Command::Market { seq: i, side, qty: 2, account: AccountId(0) },
+            _ => Command::Limit { seq: i, side, price: if side == Side::Buy { 10_000 - 1 - i % 20 } else { 10_000 + 1 + i % 20 }, qty: 1 + i % 5, tif: TimeInForce::Gtc, account: AccountId(0) },
+        }
+    }).collect()
+}
+
+fn batch_setup() -> (OrderBook, Vec<Command>) { black_box((seeded(), batch_commands())) }
+
+fn batch() -> Vec<u64> {
+    let (mut ob, mut cmds) = batch_setup();
+    let mut trades = Vec::with_capacity(OPS as usize);
+    let _ = ob.process_commands_batch_checked_into(&mut cmds, &mut trades);
+    trades.iter().map(|t| t.qty).collect()
+}
+
+// Inner matching loop (see benches/hot_path.rs): 1-lot makers swept whole, then one maker nibbled
+fn makers_setup() -> OrderBook {
+    let mut ob = OrderBook::new();
+    for i in 0..OPS { let _ = ob.submit_limit(Side::Sell, 10_001 + i % 10, 1); }
+    let _ = ob.submit_limit(Side::Sell, 10_011, OPS + 1);
+    black_box(ob)
+}
+
+fn full_fills() -> OrderBook {
+    let mut ob = makers_setup();
+    let mut trades = Vec::with_capacity(OPS as usize);
+    let _ = ob.submit_market_into(Side::Buy, black_box(OPS), &mut trades);
+    ob
+}
+
+fn partial_fills() -> OrderBook {
+    let mut ob = full_fills();
+    let mut trades = Vec::with_capacity(1);
+    for _ in 0..OPS {
+        let _ = ob.submit_market_into(Side::Buy, black_box(1), &mut trades);
+        trades.clear();
+    }
+    ob
+}
+
+iai::main!(book_setup, submit_passive, submit_crossing, cancel_setup, cancel, batch_setup, batch, makers_setup, full_fills, partial_fills);
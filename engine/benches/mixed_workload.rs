@@ -0,0 +1,31 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use match_engine::{OrderBook, Workload, WorkloadConfig, WorkloadMix};
+
+const OPS: u64 = 50_000;
+
+fn setup(mix: WorkloadMix) -> (OrderBook, Workload) {
+    let mut w = Workload::new(WorkloadConfig { mix, ..WorkloadConfig::default() });
+    let mut ob = OrderBook::new();
+    w.seed_book(&mut ob, 100, 50);
+    (ob, w)
+}
+
+// Throughput per mix from criterion, plus one report per mix with per-operation latency percentiles
+fn bench_mixed(c: &mut Criterion) {
+    let mixes = [("balanced", WorkloadMix::BALANCED), ("maker_heavy", WorkloadMix::MAKER_HEAVY), ("taker_heavy", WorkloadMix::TAKER_HEAVY)];
+    let mut group = c.benchmark_group("mixed_workload");
+    group.throughput(Throughput::Elements(OPS));
+    for (name, mix) in mixes {
+        let (mut ob, mut w) = setup(mix);
+        let r = w.run(&mut ob, OPS);
+        eprintln!("{}: {:.0} ops/s, {} trades, latency ns p50={} p90={} p99={} p99.9={} max={}",
+            name, r.ops_per_sec(), r.trades, r.p50_nanos, r.p90_nanos, r.p99_nanos, r.p999_nanos, r.max_nanos);
+        group.bench_with_input(BenchmarkId::from_parameter(name), &mix, |b, &mix| {
+            b.iter_batched(|| setup(mix), |(mut ob, mut w)| w.run(&mut ob, OPS), BatchSize::LargeInput);
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_mixed);
+criterion_main!(benches);
@@ -0,0 +1,110 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use match_engine::{BTreeStorage, BookStorage, LadderStorage, OrderBook, OrderId, Side, SkipListStorage, SlabStorage, SoaStorage};
+
+const MID: u64 = 10_000;
+
+// `levels` levels a side, `tick` apart around MID * tick
+fn seed<S: BookStorage>(storage: S, levels: u64, tick: u64, qty: u64) -> OrderBook<S> {
+    let mut ob = OrderBook::with_storage(storage);
+    for i in 1..=levels {
+        let _ = ob.submit_limit(Side::Buy, (MID - i) * tick, qty);
+        let _ = ob.submit_limit(Side::Sell, (MID + i) * tick, qty);
+    }
+    ob
+}
+
+// Same flow for every backend: 60% passive limits near the touch, 20% crossing limits, 10% markets, 10% cancels;
+// prices are multiples of `tick` (1 = dense book)
+fn run<S: BookStorage>(ob: &mut OrderBook<S>, n: u64, tick: u64) {
+    let mut trades = Vec::with_capacity(1024);
+    let mut live: Vec<OrderId> = Vec::with_capacity(n as usize);
+    for i in 0..n {
+        let side = if i.is_multiple_of(2) { Side::Buy } else { Side::Sell };
+        let qty = 1 + (i % 5);
+        match i % 10 {
+            0 => { let _ = ob.submit_market_into(side, black_box(qty), &mut trades); }
+            1 if !live.is_empty() => { let id = live.swap_remove((i as usize * 7) % live.len()); let _ = ob.cancel(id); }
+            2 | 3 => {
+                let px = match side { Side::Buy => (MID + 3) * tick, Side::Sell => (MID - 3) * tick };
+                let _ = ob.submit_limit_into(side, black_box(px), black_box(qty), &mut trades);
+            }
+            _ => {
+                let off = 1 + i % 20;
+                let px = match side { Side::Buy => (MID - off) * tick, Side::Sell => (MID + off) * tick };
+                if let Ok((id, r)) = ob.submit_limit_into(side, black_box(px), black_box(qty), &mut trades) {
+                    if r > 0 { live.push(id); }
+                }
+            }
+        }
+        trades.clear();
+    }
+}
+
+fn bench_storage_compare(c: &mut Criterion) {
+    let mut group = c.benchmark_group("storage_compare");
+    for &orders in &[10_000u64, 50_000u64] {
+        group.throughput(Throughput::Elements(orders));
+        group.bench_with_input(BenchmarkId::new("btree", orders), &orders, |b, &n| {
+            b.iter_batched(|| seed(BTreeStorage::default(), 200, 1, 1_000), |mut ob| run(&mut ob, n, 1), BatchSize::LargeInput);
+        });
+        group.bench_with_input(BenchmarkId::new("ladder", orders), &orders, |b, &n| {
+            b.iter_batched(|| seed(LadderStorage::with_range(9_000, 11_000), 200, 1, 1_000), |mut ob| run(&mut ob, n, 1), BatchSize::LargeInput);
+        });
+        group.bench_with_input(BenchmarkId::new("slab", orders), &orders, |b, &n| {
+            b.iter_batched(|| seed(SlabStorage::default(), 200, 1, 1_000), |mut ob| run(&mut ob, n, 1), BatchSize::LargeInput);
+        });
+        group.bench_with_input(BenchmarkId::new("soa", orders), &orders, |b, &n| {
+            b.iter_batched(|| seed(SoaStorage::default(), 200, 1, 1_000), |mut ob| run(&mut ob, n, 1), BatchSize::LargeInput);
+        });
+        group.bench_with_input(BenchmarkId::new("skiplist", orders), &orders, |b, &n| {
+            b.iter_batched(|| seed(SkipListStorage::default(), 200, 1, 1_000), |mut ob| run(&mut ob, n, 1), BatchSize::LargeInput);
+        });
+    }
+    group.finish();
+}
+
+// Crypto-style sparse book: the same flow with levels 1_000 ticks apart, so the ladder spans
+// ~200k mostly empty slots per side while the tree and skip list only hold the live levels
+fn bench_sparse(c: &mut Criterion) {
+    const TICK: u64 = 1_000;
+    let mut group = c.benchmark_group("sparse_levels");
+    let orders = 50_000u64;
+    group.throughput(Throughput::Elements(orders));
+    group.bench_function("btree", |b| {
+        b.iter_batched(|| seed(BTreeStorage::default(), 200, TICK, 1_000), |mut ob| run(&mut ob, orders, TICK), BatchSize::LargeInput);
+    });
+    group.bench_function("ladder", |b| {
+        b.iter_batched(|| seed(LadderStorage::with_range((MID - 200) * TICK, (MID + 200) * TICK), 200, TICK, 1_000), |mut ob| run(&mut ob, orders, TICK), BatchSize::LargeInput);
+    });
+    group.bench_function("skiplist", |b| {
+        b.iter_batched(|| seed(SkipListStorage::default(), 200, TICK, 1_000), |mut ob| run(&mut ob, orders, TICK), BatchSize::LargeInput);
+    });
+    group.finish();
+}
+
+// Depth aggregation over deep queues: 200 levels a side, 50 orders each
+fn bench_depth(c: &mut Criterion) {
+    fn deep<S: BookStorage>(storage: S) -> OrderBook<S> {
+        let mut ob = OrderBook::with_storage(storage);
+        for _ in 0..50 { for i in 1..=200 { let _ = ob.submit_limit(Side::Buy, 10_000 - i, 10); let _ = ob.submit_limit(Side::Sell, 10_000 + i, 10); } }
+        ob
+    }
+    let mut group = c.benchmark_group("depth_top_20");
+    let btree = deep(BTreeStorage::default());
+    group.bench_function("btree", |b| b.iter(|| black_box(btree.top_n(20))));
+    let ladder = deep(LadderStorage::with_range(9_000, 11_000));
+    group.bench_function("ladder", |b| b.iter(|| black_box(ladder.top_n(20))));
+    let slab = deep(SlabStorage::default());
+    group.bench_function("slab", |b| b.iter(|| black_box(slab.top_n(20))));
+    let soa = deep(SoaStorage::default());
+    group.bench_function("soa", |b| b.iter(|| black_box(soa.top_n(20))));
+    let skip = deep(SkipListStorage::default());
+    group.bench_function("skiplist", |b| b.iter(|| black_box(skip.top_n(20))));
+    // Same read into reused buffers
+    let (mut bids, mut asks) = (Vec::new(), Vec::new());
+    group.bench_function("soa_into", |b| b.iter(|| { soa.top_n_into(20, &mut bids, &mut asks); black_box((&bids, &asks)); }));
+    group.finish();
+}
+
+criterion_group!(benches, bench_storage_compare, bench_sparse, bench_depth);
+criterion_main!(benches);
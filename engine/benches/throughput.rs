@@ -28,7 +28,7 @@ fn bench_limit_throughput(c: &mut Criterion) {
                 |mut ob| {
                     let base = 10_000u64;
                     for i in 0..n {
-                        let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
+                        let side = if i.is_multiple_of(2) { Side::Buy } else { Side::Sell };
                         let cross = i % 10 < 3;
                         let px = match side {
                             Side::Buy => {
@@ -62,7 +62,7 @@ fn bench_market_throughput(c: &mut Criterion) {
                 || setup_book(500, 10_000, 1, 2_000),
                 |mut ob| {
                     for i in 0..n {
-                        let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
+                        let side = if i.is_multiple_of(2) { Side::Buy } else { Side::Sell };
                         let qty = 1 + (i % 10);
                         let _ = ob.submit_market(side, black_box(qty));
                     }
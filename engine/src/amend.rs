@@ -0,0 +1,54 @@
+use crate::session::SessionState;
+use crate::{faults, iceberg, market_data, BookStorage, EngineError, L3Event, OrderBook, OrderId, Trade};
+
+// Order amend (`OrderBook::amend`, `Command::Amend`): change a resting order's price and total quantity
+// (displayed plus reserve for icebergs) keeping its id and other attributes.
+//   decrease  same price, smaller quantity: modified in place and keeps its queue position; an iceberg
+//             takes the cut from its reserve first (reported as an L3 Update)
+//   increase  same price, larger quantity: the order joins the back of its level with a fresh timestamp
+//             (an L3 Delete and Add under the same id)
+//   price     any price change is handled as `reprice` at the new quantity: the order loses its time
+//             priority and trades first as taker if the new price crosses the opposite touch, unless
+//             the amend cross policy (see `post_only`) rejects or re-prices it
+// Amending to the current price and quantity is a no-op. A quantity increase is new exposure, so the
+// reduce-only cap and group risk limits are checked against the new quantity; a price change goes through
+// the checks of `reprice` (tick, cross policy, band, storage). All checks run before the order changes,
+// so a rejected amend leaves it as it was. Held stop orders cannot be amended (cancel and enter again).
+impl<S: BookStorage> OrderBook<S> {
+    // Returns the qty left resting
+    pub fn amend(&mut self, id: OrderId, new_price: u64, new_qty: u64, trades_out: &mut Vec<Trade>) -> Result<u64, EngineError> {
+        if new_qty == 0 { return Err(EngineError::Rejected("amend to zero quantity; cancel instead".into())); }
+        if self.stop_order(id).is_some() { return Err(EngineError::Rejected("held stop orders cannot be amended".into())); }
+        if self.session == SessionState::Halted { return Err(EngineError::Rejected("instrument halted".into())); }
+        if self.cancel_only { return Err(EngineError::Rejected("book in cancel-only mode".into())); }
+        let &(side, price) = self.index.get(&id.0).ok_or(EngineError::UnknownOrder)?;
+        self.check_symbol_config(|c| c.check_qty(new_qty))?;
+        let target = if new_price != price { self.move_target(side, new_price)? } else { new_price };
+        let (total, account, reduce_only) = self.storage.update(side, price, id, |o| (o.qty + o.reserve_qty, o.account, o.reduce_only)).ok_or_else(|| faults::desync(id))?;
+        if new_qty > total {
+            if reduce_only && new_qty > self.reduce_only_cap(account, side)? { return Err(EngineError::Rejected("amend would increase a reduce-only order past the position".into())); }
+            if let Some((risk, instrument)) = &self.risk { risk.check(instrument, account, side, new_qty)?; }
+        }
+        // Every check has passed; from here on only a storage desync can fail
+        if target != new_price { self.note_repriced(id, new_price, target); }
+        let new_price = target;
+
+        // Set the new quantity where the order rests; only a same-price decrease keeps its place
+        let shown = self.storage.update(side, price, id, |o| {
+            let shown = if new_qty < total { o.qty.min(new_qty) } else { iceberg::split(o.display_qty, new_qty).0 };
+            (o.qty, o.reserve_qty, o.order_qty) = (shown, new_qty - shown, o.order_qty - total + new_qty);
+            shown
+        }).ok_or_else(|| faults::desync(id))?;
+        if new_price != price { return self.move_order(id, side, price, new_price, trades_out); }
+        if new_qty < total { self.note_l3(|_| L3Event::Update { id, qty: shown }); }
+        if new_qty > total {
+            let ts = self.now();
+            if !self.storage.relocate(side, price, id, price, ts) { return Err(faults::desync(id)); }
+            let mode = self.l3_mode;
+            let owner = self.storage.update(side, price, id, |o| market_data::owner_of(mode, o)).ok_or_else(|| faults::desync(id))?;
+            self.note_l3(|_| L3Event::Delete { id });
+            self.note_l3(|_| L3Event::Add { id, side, price, qty: shown, ts, owner });
+        }
+        Ok(new_qty)
+    }
+}
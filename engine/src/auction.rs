@@ -0,0 +1,140 @@
+use crate::session::SessionState;
+use crate::{positions, AccountId, Attribution, L3Event, Owner, BookStorage, EngineError, EngineEvent, Order, OrderBook, OrderId, OrderRequest, OrderType, Side, TimeInForce, Trade, TradeConditions};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+// Opening / closing auctions. AtOpen orders (market-on-open, limit-on-open) are accepted only in
+// PreOpen and AtClose orders (market-on-close, limit-on-close) only in PreClose; they never rest
+// on the continuous book and execute solely in the uncross run by `set_session`.
+//
+// The uncross crosses auction orders together with the limit orders resting on the book at one
+// price: the one with the most executable volume, then the smallest imbalance, then closest to the
+// last trade price, then the lowest. Without any limit price the last trade price is used. Market
+// orders go first, then limits by price and time; each trade is printed at the uncross price with
+// the later of the two orders as taker. Auction orders left unexecuted expire.
+struct Interest {
+    id: OrderId,
+    side: Side,
+    price: Option<u64>, // None = market
+    qty: u64,
+    // Hidden iceberg reserve, not offered in the uncross
+    reserve: u64,
+    ts: u64,
+    account: AccountId,
+    correlation: u64,
+    in_book: bool,
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    // Auction-only orders waiting for the next uncross, in arrival order
+    pub fn auction_orders(&self) -> &[Order] { &self.auction }
+
+    pub(crate) fn enter_auction(&mut self, req: &OrderRequest) -> Result<OrderId, EngineError> {
+        let phase = match req.tif { TimeInForce::AtOpen => SessionState::PreOpen, _ => SessionState::PreClose };
+        if self.session != phase { return Err(EngineError::Rejected(format!("{:?} orders are only accepted during {:?}", req.tif, phase))); }
+        let id = self.next_order_id();
+        self.note_event(|| EngineEvent::Accepted { id, req: *req });
+        let ts = self.now();
+        self.auction.push(Order { id, side: req.side, price: req.price, qty: req.qty, order_type: req.order_type, ts, tif: req.tif, account: req.account, reduce_only: req.reduce_only, correlation: req.correlation, display_qty: 0, reserve_qty: 0, order_qty: req.qty });
+        Ok(id)
+    }
+
+    pub(crate) fn cancel_auction(&mut self, id: OrderId) -> Result<Order, EngineError> {
+        let i = self.auction.iter().position(|o| o.id == id).ok_or(EngineError::UnknownOrder)?;
+        Ok(self.auction.remove(i))
+    }
+
+    pub(crate) fn take_auction_orders(&mut self, tif: TimeInForce) -> Vec<Order> {
+        let (taken, kept) = std::mem::take(&mut self.auction).into_iter().partition(|o| o.tif == tif);
+        self.auction = kept;
+        taken
+    }
+
+    // Equilibrium price for the given interest, or None when nothing would execute
+    fn uncross_price(&self, parts: &[Interest]) -> Option<u64> {
+        let (mut mkt_buy, mut mkt_sell) = (0u64, 0u64);
+        let (mut buys, mut sells) = (BTreeMap::<u64, u64>::new(), BTreeMap::<u64, u64>::new());
+        for p in parts {
+            match (p.side, p.price) {
+                (Side::Buy, None) => mkt_buy += p.qty,
+                (Side::Sell, None) => mkt_sell += p.qty,
+                (Side::Buy, Some(px)) => *buys.entry(px).or_default() += p.qty,
+                (Side::Sell, Some(px)) => *sells.entry(px).or_default() += p.qty,
+            }
+        }
+        let mut prices: Vec<u64> = buys.keys().chain(sells.keys()).copied().collect();
+        prices.sort_unstable();
+        prices.dedup();
+        if prices.is_empty() { prices.extend(self.last_trade); }
+        let reference = self.last_trade;
+        let best = prices.into_iter().map(|px| {
+            let demand = mkt_buy + buys.range(px..).map(|(_, q)| q).sum::<u64>();
+            let supply = mkt_sell + sells.range(..=px).map(|(_, q)| q).sum::<u64>();
+            (demand.min(supply), Reverse(demand.abs_diff(supply)), Reverse(reference.map_or(0, |r| r.abs_diff(px))), Reverse(px))
+        }).max()?;
+        (best.0 > 0).then_some(best.3 .0)
+    }
+
+    pub(crate) fn uncross(&mut self, tif: TimeInForce, trades_out: &mut Vec<Trade>) {
+        let mut parts: Vec<Interest> = self.auction.iter().filter(|o| o.tif == tif).map(|o| Interest {
+            id: o.id, side: o.side, price: (o.order_type == OrderType::Limit).then_some(o.price), qty: o.qty, reserve: 0, ts: o.ts,
+            account: o.account, correlation: o.correlation, in_book: false,
+        }).collect();
+        if parts.is_empty() { return; }
+        for side in [Side::Buy, Side::Sell] {
+            self.storage.for_each_level(side, &mut |price, orders| {
+                parts.extend(orders.map(|o| Interest { id: o.id, side, price: Some(price), qty: o.qty, reserve: o.reserve_qty, ts: o.ts, account: o.account, correlation: o.correlation, in_book: true }));
+                true
+            });
+        }
+        let Some(px) = self.uncross_price(&parts) else { return };
+        let conditions = TradeConditions::AUCTION | if tif == TimeInForce::AtOpen { TradeConditions::OPENING } else { TradeConditions::CLOSING } | self.emission.conditions();
+
+        // Priority: market first, then the more aggressive limit, then time
+        let rank = |p: &Interest| match (p.side, p.price) { (_, None) => (0, 0, p.ts), (Side::Buy, Some(x)) => (1, u64::MAX - x, p.ts), (Side::Sell, Some(x)) => (1, x, p.ts) };
+        let (mut buys, mut sells): (Vec<Interest>, Vec<Interest>) = parts.into_iter().filter(|p| match (p.side, p.price) {
+            (_, None) => true,
+            (Side::Buy, Some(x)) => x >= px,
+            (Side::Sell, Some(x)) => x <= px,
+        }).partition(|p| p.side == Side::Buy);
+        buys.sort_by_key(rank);
+        sells.sort_by_key(rank);
+
+        let (mut bi, mut si, start_len) = (0, 0, trades_out.len());
+        while bi < buys.len() && si < sells.len() {
+            let (b, s) = (&buys[bi], &sells[si]);
+            let qty = b.qty.min(s.qty);
+            let (taker, maker) = if b.ts > s.ts { (b, s) } else { (s, b) };
+            let (output_seq, trade_id, maker_remaining) = (self.next_output_seq(), self.next_trade_id(), maker.qty - qty + maker.reserve);
+            trades_out.push(Trade { trade_id, taker_id: taker.id, maker_id: maker.id, price: px, qty, taker_side: taker.side, taker_correlation: taker.correlation, maker_correlation: maker.correlation, taker_account: taker.account, maker_account: maker.account, maker_remaining, maker_fully_filled: maker_remaining == 0, ts: self.ts, conditions, output_seq });
+            positions::record_fill(&mut self.positions, &self.reduce_only, &mut self.touched, taker.side, taker.account, maker.account, qty);
+            if let Some((risk, instrument)) = &self.risk { risk.record_fill(instrument, taker.side, taker.account, maker.account, qty); }
+            if self.reduce_only.contains_key(&taker.account) && !self.touched.contains(&taker.account) { self.touched.push(taker.account); }
+            let (taker_leaves, taker_id) = (taker.qty - qty + taker.reserve, taker.id);
+            self.note_fills(taker_id, taker_leaves + qty, &trades_out[trades_out.len() - 1..]);
+            buys[bi].qty -= qty;
+            sells[si].qty -= qty;
+            for p in [&buys[bi], &sells[si]] { self.apply_auction_fill(p, px, qty); }
+            if buys[bi].qty == 0 { bi += 1; }
+            if sells[si].qty == 0 { si += 1; }
+        }
+        if !self.refills.is_empty() { self.replenish(); }
+        self.note_trade_price(px);
+        self.note_trades(&trades_out[start_len..]);
+        if let Some(&account) = self.touched.first() { self.recheck_reduce_only(account); }
+    }
+
+    fn apply_auction_fill(&mut self, p: &Interest, px: u64, traded: u64) {
+        if !p.in_book {
+            if p.qty == 0 { self.auction.retain(|o| o.id != p.id); } else if let Some(o) = self.auction.iter_mut().find(|o| o.id == p.id) { o.qty = p.qty; }
+            return;
+        }
+        let price = p.price.expect("book orders are limits");
+        self.note_l3(|mode| L3Event::Execute { id: p.id, side: p.side, price: px, qty: traded, remaining: p.qty, owner: (mode == Some(Attribution::Attributed)).then_some(Owner { account: p.account, correlation: p.correlation }) });
+        if p.qty == 0 {
+            match self.storage.remove(p.side, price, p.id) { Some(o) => self.refill_or_unindex(&o), None => { self.index.remove(&p.id.0); } }
+        } else {
+            self.storage.update(p.side, price, p.id, |o| o.qty = p.qty);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{stops, AccountId, BookStorage, Order, OrderBook, OrderId, OrderRequest, Trade};
+
+// Matching budget (per instrument, off by default): a new order may trade through at most `levels`
+// price levels per command. An order that uses up its budget with liquidity still in reach pauses
+// and its remainder becomes a continuation, queued behind earlier ones; the command returns with it
+// still open (nothing rests yet). Each later command first runs one slice of the oldest
+// continuation (another `levels` levels, then back to the queue or done), so one huge sweep is
+// spread over the flow instead of stalling it; `Command::Resume` (or `resume_continuation`) runs a
+// slice on an otherwise quiet book. A finished order rests or is discarded as its type says, with a
+// fresh timestamp. Each slice is reported like an order of its own (hooks, fill report, stops).
+// Fill-or-kill orders and reprices are never sliced; a cancel takes a paused order's remainder off
+// the queue, and a session change (including end of day) first runs every continuation to the end.
+// Continuations are kept in snapshots (`BookSnapshot::continuations`, queue order) and resume after a
+// restore like any other; they are not visible to `get_order`/`order_status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Continuation {
+    pub id: OrderId,
+    // The order with its open remainder as qty
+    pub req: OrderRequest,
+    // Filled by the earlier slices
+    pub filled: u64,
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn set_match_budget(&mut self, levels: Option<u32>) { self.match_budget = levels.map(|l| l.max(1)); }
+
+    pub fn match_budget(&self) -> Option<u32> { self.match_budget }
+
+    pub fn pending_continuations(&self) -> usize { self.continuations.len() }
+
+    // One slice of the oldest continuation: (its id, qty still open after the slice)
+    pub fn resume_continuation(&mut self, trades_out: &mut Vec<Trade>) -> Option<(OrderId, u64)> {
+        let Continuation { id, req, filled } = self.continuations.pop_front()?;
+        let remaining = self.emitting(req.emission, trades_out, |ob, out| ob.execute_slice(id, req, filled, out));
+        self.trigger_stops(trades_out);
+        Some((id, remaining))
+    }
+
+    // Run every continuation to the end, ignoring the budget
+    pub(crate) fn finish_continuations(&mut self, trades_out: &mut Vec<Trade>) {
+        let budget = self.match_budget.take();
+        while self.resume_continuation(trades_out).is_some() {}
+        self.match_budget = budget;
+    }
+
+    pub(crate) fn cancel_continuation(&mut self, id: OrderId) -> Option<Order> {
+        let i = self.continuations.iter().position(|c| c.id == id)?;
+        let c = self.continuations.remove(i)?;
+        let mut o = stops::held(id, &c.req);
+        o.order_qty += c.filled;
+        Some(o)
+    }
+
+    pub(crate) fn continuation_account(&self, id: OrderId) -> Option<AccountId> {
+        self.continuations.iter().find(|c| c.id == id).map(|c| c.req.account)
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{BookStorage, EngineEvent, Order, OrderBook, OrderId, Side};
+
+// Orders the engine removed on its own as a side effect of processing something else (a session
+// transition, a halt, a position change), as opposed to a Cancel command. Recorded so consumers
+// mirroring the book by order id stay consistent; drained by the caller after each command or batch.
+// Reduce-only orders trimmed but left on the book are reported by `drain_reduce_only_updates` only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug, PartialEq, Eq)))]
+pub enum CancelReason {
+    // Day orders at the close, auction orders not executed in their uncross
+    Expired,
+    // Resting orders cancelled by a halt under `HaltAction::Cancel`
+    Halted,
+    // Reduce-only order left with nothing to reduce
+    ReduceOnly,
+    // Resting order cancelled by self-trade prevention (see `stp`)
+    SelfTrade,
+    // Fill-or-kill order that could not trade in full; it never reached the book (see `fok`)
+    Killed,
+    // Cancel held back by the minimum resting time, carried out once it was up (see `min_resting`)
+    Deferred,
+    // Stop order that failed the entry checks when it activated (see `stops`)
+    StopRejected,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug, PartialEq, Eq)))]
+pub struct ImplicitCancel {
+    pub id: OrderId,
+    pub side: Side,
+    pub price: u64,
+    // Quantity removed
+    pub qty: u64,
+    pub reason: CancelReason,
+    // Caused by a command processed as `Emission::Replay`
+    pub replay: bool,
+    pub output_seq: u64,
+}
+
+impl ImplicitCancel {
+    pub(crate) fn of(o: &Order, reason: CancelReason) -> Self { Self { id: o.id, side: o.side, price: o.price, qty: o.qty + o.reserve_qty, reason, replay: false, output_seq: 0 } }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn drain_implicit_cancels(&mut self) -> Vec<ImplicitCancel> { std::mem::take(&mut self.implicit_cancels) }
+
+    pub fn drain_implicit_cancels_into(&mut self, out: &mut Vec<ImplicitCancel>) { out.append(&mut self.implicit_cancels); }
+
+    pub(crate) fn note_cancel(&mut self, c: ImplicitCancel) {
+        let output_seq = self.next_output_seq();
+        self.implicit_cancels.push(ImplicitCancel { output_seq, ..c });
+        self.note_event(|| match c.reason {
+            CancelReason::Expired => EngineEvent::Expired { id: c.id, qty: c.qty },
+            reason => EngineEvent::Canceled { id: c.id, qty: c.qty, reason: Some(reason) },
+        });
+    }
+}
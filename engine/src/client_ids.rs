@@ -0,0 +1,104 @@
+use crate::{AccountId, BookStorage, ClientOrderId, EngineError, Order, OrderBook, OrderId, OrderRequest, Trade};
+use std::collections::VecDeque;
+
+// Client-assigned order ids (`OrderRequest::with_client_id`): an order entered with one is also known
+// by (account, client id) besides the `OrderId` the engine gives it. A client id is unique per account
+// for the session: an order reusing one is rejected with `EngineError::DuplicateClientId` before any
+// other check, even when the earlier order has since traded or been cancelled. The lookup is cleared
+// at end of day; snapshots keep it (`BookSnapshot::client_ids`, with each id's term start) and the
+// retention window, so a restored book still rejects reused ids. Stops and auction orders register like
+// any other order.
+//
+// A retention window (`set_client_id_retention`, off by default) bounds the lookup on long-running
+// books: ids are forgotten oldest first once more than `max_ids` are held or once older than
+// `max_age_micros` of the book's clock (see `clock`), and may then be used again. An id whose order is
+// still working (resting, a held stop, an auction order or a paused sweep) is never forgotten; it
+// starts a new term instead. Expiry runs as orders with client ids are entered, in amortised constant
+// time per order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClientIdRetention {
+    pub max_ids: Option<usize>,
+    pub max_age_micros: Option<u64>,
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    // Turning retention on starts every held id's term now
+    pub fn set_client_id_retention(&mut self, retention: Option<ClientIdRetention>) {
+        let now = self.clock.now_micros();
+        self.client_id_window = retention.map(|r| (r, self.client_ids.iter().map(|(&(account, client_id), &id)| (now, account, client_id, id)).collect()));
+        if let Some(window) = &mut self.client_id_window { window.1.make_contiguous().sort_by_key(|e| e.3 .0); }
+        self.expire_client_ids(self.client_ids.len());
+    }
+
+    pub fn client_id_retention(&self) -> Option<ClientIdRetention> { self.client_id_window.as_ref().map(|w| w.0) }
+
+    // Client ids currently held for duplicate detection and lookups
+    pub fn client_id_count(&self) -> usize { self.client_ids.len() }
+
+    // Engine id of the order `account` entered as `client_id` this session
+    pub fn order_id_by_client(&self, account: AccountId, client_id: ClientOrderId) -> Option<OrderId> { self.client_ids.get(&(account, client_id)).copied() }
+
+    pub fn cancel_by_client_id(&mut self, account: AccountId, client_id: ClientOrderId) -> Result<Order, EngineError> {
+        let id = self.order_id_by_client(account, client_id).ok_or(EngineError::UnknownOrder)?;
+        self.cancel(id)
+    }
+
+    // Enter `req` through `enter`, recording its client id once it is accepted
+    pub(crate) fn with_client_id(&mut self, req: OrderRequest, trades_out: &mut Vec<Trade>, enter: impl FnOnce(&mut Self, OrderRequest, &mut Vec<Trade>) -> Result<(OrderId, u64), EngineError>) -> Result<(OrderId, u64), EngineError> {
+        let Some(client_id) = req.client_id else { return enter(self, req, trades_out) };
+        if self.client_ids.contains_key(&(req.account, client_id)) { return Err(EngineError::DuplicateClientId(client_id)); }
+        let (id, remaining) = enter(self, req, trades_out)?;
+        self.client_ids.insert((req.account, client_id), id);
+        if self.client_id_window.is_some() {
+            let now = self.clock.now_micros();
+            if let Some((_, held)) = &mut self.client_id_window { held.push_back((now, req.account, client_id, id)); }
+            self.expire_client_ids(2);
+        }
+        Ok((id, remaining))
+    }
+
+    // (term start, account, client id, order id) per held id: oldest term first under a retention window,
+    // otherwise in order id order with no term
+    pub(crate) fn client_id_entries(&self) -> Vec<ClientIdEntry> {
+        if let Some((_, held)) = &self.client_id_window { return held.iter().copied().collect(); }
+        let mut entries: Vec<_> = self.client_ids.iter().map(|(&(account, client_id), &id)| (0, account, client_id, id)).collect();
+        entries.sort_by_key(|e| e.3 .0);
+        entries
+    }
+
+    pub(crate) fn restore_client_ids(&mut self, retention: Option<ClientIdRetention>, entries: &[ClientIdEntry]) {
+        self.client_ids = entries.iter().map(|&(_, account, client_id, id)| ((account, client_id), id)).collect();
+        self.client_id_window = retention.map(|r| (r, entries.iter().copied().collect()));
+    }
+
+    pub(crate) fn clear_client_ids(&mut self) {
+        self.client_ids.clear();
+        if let Some((_, held)) = &mut self.client_id_window { held.clear(); }
+    }
+
+    // Forget ids past the window, oldest first. Each id is forgotten once, and at most `renewals` ids
+    // of working orders are moved to the back per call, so an entry costs amortised constant time
+    fn expire_client_ids(&mut self, mut renewals: usize) {
+        let Some((retention, mut held)) = self.client_id_window.take() else { return };
+        let now = self.clock.now_micros();
+        while let Some(&(since, account, client_id, id)) = held.front() {
+            let over = retention.max_ids.is_some_and(|max| held.len() > max) || retention.max_age_micros.is_some_and(|age| now.saturating_sub(since) >= age);
+            if !over { break; }
+            let working = self.working(id);
+            if working && renewals == 0 { break; }
+            held.pop_front();
+            if working { held.push_back((now, account, client_id, id)); renewals -= 1; } else { self.client_ids.remove(&(account, client_id)); }
+        }
+        self.client_id_window = Some((retention, held));
+    }
+
+    fn working(&self, id: OrderId) -> bool {
+        self.index.contains_key(&id.0) || self.stop_order(id).is_some() || self.auction.iter().any(|o| o.id == id) || self.continuation_account(id).is_some()
+    }
+}
+
+pub(crate) type ClientIdWindow = (ClientIdRetention, VecDeque<ClientIdEntry>);
+
+// (term start in the book's clock, account, client id, order id)
+pub type ClientIdEntry = (u64, AccountId, ClientOrderId, OrderId);
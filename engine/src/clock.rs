@@ -0,0 +1,42 @@
+use crate::{BookStorage, OrderBook};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Wall time for book features measured in real time (see `min_resting`), injected per book with
+// `OrderBook::set_clock`; books read the system clock by default. Order `ts` stays a logical counter.
+// For replicas and journal replay to agree, drive a `ManualClock` from the sequencer's timestamps
+// rather than reading the system clock on each host.
+pub trait Clock: Send {
+    // Microseconds since a fixed epoch, never decreasing
+    fn now_micros(&self) -> u64;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_micros(&self) -> u64 { SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_micros() as u64) }
+}
+
+// Set by its owner; clones share the same time
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock(Arc<AtomicU64>);
+
+impl ManualClock {
+    pub fn new(micros: u64) -> Self { Self(Arc::new(AtomicU64::new(micros))) }
+
+    pub fn set(&self, micros: u64) { self.0.store(micros, Ordering::Release); }
+
+    pub fn advance(&self, micros: u64) { self.0.fetch_add(micros, Ordering::AcqRel); }
+}
+
+impl Clock for ManualClock {
+    fn now_micros(&self) -> u64 { self.0.load(Ordering::Acquire) }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) { self.clock = Box::new(clock); }
+
+    pub fn clock_micros(&self) -> u64 { self.clock.now_micros() }
+}
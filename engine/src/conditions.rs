@@ -0,0 +1,54 @@
+use crate::{OrderId, Trade};
+use std::ops::BitOr;
+
+// Trade condition codes, a bit set so one `Trade` type covers every execution context. The engine
+// sets OPENING / CLOSING together with AUCTION on uncross trades; the remaining codes are for
+// subsystems that report trades the continuous book never produces (negotiated blocks, quantity
+// removed by self-trade prevention instead of trading, busts of earlier trades); REPLAY marks trades
+// re-created from a replay (see `output`). Unknown bits are
+// kept as-is so codes can be added without breaking stored or forwarded trades.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug, PartialEq, Eq)))]
+pub struct TradeConditions(pub u16);
+
+impl TradeConditions {
+    pub const NONE: Self = Self(0);
+    pub const OPENING: Self = Self(1);
+    pub const CLOSING: Self = Self(1 << 1);
+    pub const AUCTION: Self = Self(1 << 2);
+    pub const BLOCK: Self = Self(1 << 3);
+    pub const STP_DECREMENT: Self = Self(1 << 4);
+    pub const BUST: Self = Self(1 << 5);
+    // Produced by a command processed as `Emission::Replay` (recovery, not live trading)
+    pub const REPLAY: Self = Self(1 << 6);
+
+    pub fn contains(self, other: Self) -> bool { self.0 & other.0 == other.0 }
+
+    pub fn is_empty(self) -> bool { self.0 == 0 }
+}
+
+impl BitOr for TradeConditions {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self { Self(self.0 | rhs.0) }
+}
+
+// Liquidity indicator for one side of a trade
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Liquidity {
+    Added,
+    Removed,
+    // Uncross trades neither add nor remove liquidity
+    Auction,
+}
+
+impl Trade {
+    // None when `id` is not a party to the trade
+    pub fn liquidity(&self, id: OrderId) -> Option<Liquidity> {
+        if id != self.taker_id && id != self.maker_id { return None; }
+        Some(if self.conditions.contains(TradeConditions::AUCTION) { Liquidity::Auction } else if id == self.maker_id { Liquidity::Added } else { Liquidity::Removed })
+    }
+
+    // The same trade reported as busted
+    pub fn bust(&self) -> Trade { Trade { conditions: self.conditions | TradeConditions::BUST, ..self.clone() } }
+}
@@ -0,0 +1,121 @@
+use crate::{BookStorage, L3Event, OrderBook, Side};
+use std::collections::HashMap;
+
+// Incremental L2 depth feed (off by default): a `DepthDelta` with a level's new aggregate displayed
+// quantity (0 = level gone) every time an order adds to, trades at, shrinks in or leaves that level,
+// in the order it happened, so consumers keep a full depth view without polling `top_n`. The deltas
+// are derived from the same changes as the L3 feed (whether or not that feed is on) and carry no
+// order detail: hidden iceberg reserve never shows, a refilled slice shows as its own delta. A
+// consumer starts from `top_n` (or `l3_snapshot`) taken right after a drain and applies the deltas
+// drained since. Suppressed output (see `output`) moves the levels without deltas; the next delta
+// at such a level carries its true total again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthDelta {
+    pub side: Side,
+    pub price: u64,
+    pub new_qty: u64,
+    pub output_seq: u64,
+}
+
+// Displayed qty per resting order and per level, as of the L3 changes seen so far
+#[derive(Debug, Default)]
+pub(crate) struct DepthFeed {
+    orders: HashMap<u64, (Side, u64, u64)>,
+    levels: HashMap<(Side, u64), u64>,
+    // L3 changes already applied (a prefix of the book's L3 buffer)
+    seen: usize,
+    deltas: Vec<DepthDelta>,
+}
+
+impl DepthFeed {
+    // Apply an L3 change as it is recorded, so its delta takes its place in the output sequence
+    pub(crate) fn record(&mut self, ev: &L3Event, output_seq: &mut u64) {
+        self.apply(ev, output_seq);
+        self.seen += 1;
+    }
+
+    fn apply(&mut self, ev: &L3Event, output_seq: &mut u64) {
+        let (id, qty) = match *ev {
+            L3Event::Add { id, side, price, qty, .. } => {
+                self.orders.insert(id.0, (side, price, qty));
+                return self.level(side, price, output_seq, |total| total + qty);
+            }
+            L3Event::Execute { id, remaining, .. } => (id, remaining),
+            L3Event::Update { id, qty } => (id, qty),
+            L3Event::Delete { id } => (id, 0),
+        };
+        let Some(o) = self.orders.get_mut(&id.0) else { return };
+        let (side, price, old) = *o;
+        if qty == 0 { self.orders.remove(&id.0); } else { o.2 = qty; }
+        self.level(side, price, output_seq, |total| total + qty - old);
+    }
+
+    fn level(&mut self, side: Side, price: u64, output_seq: &mut u64, f: impl FnOnce(u64) -> u64) {
+        let total = self.levels.entry((side, price)).or_default();
+        *total = f(*total);
+        let new_qty = *total;
+        if new_qty == 0 { self.levels.remove(&(side, price)); }
+        *output_seq += 1;
+        self.deltas.push(DepthDelta { side, price, new_qty, output_seq: *output_seq });
+    }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    // Turning the feed on starts from the book as it is; off drops undrained deltas
+    pub fn set_depth_feed(&mut self, on: bool) {
+        self.sync_depth();
+        if !on { self.depth_feed = None; return; }
+        if self.depth_feed.is_some() { return; }
+        let mut feed = DepthFeed { seen: self.l3.len(), ..DepthFeed::default() };
+        for side in [Side::Buy, Side::Sell] {
+            self.storage.for_each_level(side, &mut |price, orders| {
+                for o in orders {
+                    feed.orders.insert(o.id.0, (side, price, o.qty));
+                    *feed.levels.entry((side, price)).or_default() += o.qty;
+                }
+                true
+            });
+        }
+        self.depth_feed = Some(feed);
+    }
+
+    pub fn depth_feed(&self) -> bool { self.depth_feed.is_some() }
+
+    // Deltas since the previous drain, in the order they happened
+    pub fn drain_depth(&mut self) -> Vec<DepthDelta> {
+        let mut out = Vec::new();
+        self.drain_depth_into(&mut out);
+        out
+    }
+
+    pub fn drain_depth_into(&mut self, out: &mut Vec<DepthDelta>) {
+        self.sync_depth();
+        if let Some(feed) = &mut self.depth_feed { out.append(&mut feed.deltas); }
+    }
+
+    // L3 changes are recorded while either feed is on
+    pub(crate) fn l3_on(&self) -> bool { self.l3_mode.is_some() || self.depth_feed.is_some() }
+
+    // Bring the depth view up to the L3 buffer; without the L3 feed the buffer is only ours to clear
+    pub(crate) fn sync_depth(&mut self) {
+        let Some(feed) = &mut self.depth_feed else { return };
+        for ev in &self.l3[feed.seen..] { feed.apply(ev, &mut self.output_seq); }
+        if self.l3_mode.is_none() { self.l3.clear(); }
+        feed.seen = self.l3.len();
+    }
+
+    // The L3 buffer was emptied after a sync
+    pub(crate) fn l3_taken(&mut self) {
+        if let Some(feed) = &mut self.depth_feed { feed.seen = 0; }
+    }
+
+    // Suppressed output: keep the view in step with the book, then drop the L3 changes and deltas
+    // made since the given lengths
+    pub(crate) fn sync_depth_quietly(&mut self, l3_from: usize, deltas_from: usize) {
+        self.sync_depth();
+        self.l3.truncate(l3_from);
+        if let Some(feed) = &mut self.depth_feed { feed.deltas.truncate(deltas_from); feed.seen = self.l3.len(); }
+    }
+
+    pub(crate) fn depth_deltas_len(&self) -> usize { self.depth_feed.as_ref().map_or(0, |f| f.deltas.len()) }
+}
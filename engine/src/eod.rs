@@ -0,0 +1,57 @@
+use crate::{BookStorage, OrderBook, ReferenceValue, SessionState, SessionStats, Trade, TradeConditions};
+
+// End-of-day processing for one book (`Command::EndOfDay`, sequenced like any session change):
+//   close       move to Closed; from PreClose that runs the closing uncross (the day was
+//               configured with a closing auction phase), and every resting Day order expires
+//   settlement  closing auction price, else the session's last trade, else the settlement price
+//               injected earlier (`ReferenceValue::SettlementPrice`); it becomes the book's
+//               settlement reference, so the next session's price band starts from it
+//   statistics  the day's final `SessionStats`, after the closing auction
+//   reset       session statistics start over right away rather than at the next open
+// Reports from sequenced commands are buffered for `drain_eod_reports`; writing the end-of-day
+// snapshot is up to the caller (the ingestor worker does it after the batch).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettlementSource {
+    ClosingAuction,
+    LastTrade,
+    Reference,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Settlement {
+    pub price: u64,
+    pub source: SettlementSource,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EodReport {
+    // None when nothing traded and no settlement price was ever injected
+    pub settlement: Option<Settlement>,
+    pub stats: SessionStats,
+    pub closing_auction_trades: usize,
+    // Day and unexecuted auction orders expired by the close
+    pub expired: usize,
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn end_of_day(&mut self, trades_out: &mut Vec<Trade>) -> EodReport {
+        let mut change = if self.session == SessionState::Closed { Default::default() } else { self.set_session(SessionState::Closed) };
+        let closing = change.trades.iter().find(|t| t.conditions.contains(TradeConditions::AUCTION)).map(|t| t.price);
+        let stats = self.stats;
+        let settlement = match (closing, stats.last, self.refdata.settlement_price) {
+            (Some(price), _, _) => Some(Settlement { price, source: SettlementSource::ClosingAuction }),
+            (None, Some(price), _) => Some(Settlement { price, source: SettlementSource::LastTrade }),
+            (None, None, price) => price.map(|price| Settlement { price, source: SettlementSource::Reference }),
+        };
+        if let Some(s) = settlement { self.set_reference_value(ReferenceValue::SettlementPrice(s.price)); }
+        self.stats = SessionStats::default();
+        self.clear_client_ids();
+        let report = EodReport { settlement, stats, closing_auction_trades: change.trades.len(), expired: change.expired.len() };
+        trades_out.append(&mut change.trades);
+        report
+    }
+
+    pub fn drain_eod_reports(&mut self) -> Vec<EodReport> { std::mem::take(&mut self.eod_reports) }
+
+    pub fn drain_eod_reports_into(&mut self, out: &mut Vec<EodReport>) { out.append(&mut self.eod_reports); }
+}
@@ -0,0 +1,82 @@
+use crate::{AccountId, BookStorage, CancelReason, Command, Emission, EngineError, OrderBook, OrderId, OrderRequest, Trade, TradeConditions, TradeId};
+
+// Execution report stream (`OrderBook::set_event_sink`, off by default): every order lifecycle
+// transition is pushed to the sink as it happens, so callers need not infer it from return values.
+//   Accepted         a new order was given its id (stops when held, auction orders when queued)
+//   Rejected         a new order failed its checks (no id), or a cancel, amend, reprice or reduce
+//                    command failed (the id it named); direct calls other than new orders return
+//                    their error and are not reported
+//   PartiallyFilled  one execution of an order with `leaves` still open, for taker and maker alike
+//   Filled           the execution that completed the order
+//   Canceled         removed before it was filled: a cancel, an unfilled IOC, market or
+//                    self-trade-cancelled remainder (`reason` None / SelfTrade), or any implicit
+//                    cancel other than expiry (`reason` as in `ImplicitCancel`)
+//   Expired          day or auction order gone at its session end
+//   Triggered        a held stop activated; its executions follow as for a new order
+// STP decrements are not executions and report nothing (the taker's `leaves` still shrink). Events
+// follow the command's `Emission`: none for suppressed output. The sink runs inline on the matching
+// thread and must not block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineEvent {
+    Accepted { id: OrderId, req: OrderRequest },
+    Rejected { id: Option<OrderId>, account: AccountId, correlation: u64, reason: String },
+    PartiallyFilled { id: OrderId, trade_id: TradeId, price: u64, qty: u64, leaves: u64 },
+    Filled { id: OrderId, trade_id: TradeId, price: u64, qty: u64 },
+    Canceled { id: OrderId, qty: u64, reason: Option<CancelReason> },
+    Expired { id: OrderId, qty: u64 },
+    Triggered { id: OrderId, stop_price: u64, last_price: u64 },
+}
+
+pub trait EventSink: Send {
+    fn on_event(&mut self, ev: &EngineEvent);
+}
+
+impl<F> EventSink for F
+where
+    F: FnMut(&EngineEvent) + Send,
+{
+    fn on_event(&mut self, ev: &EngineEvent) { self(ev) }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn set_event_sink(&mut self, sink: Option<Box<dyn EventSink>>) { self.event_sink = sink; }
+
+    pub fn has_event_sink(&self) -> bool { self.event_sink.is_some() }
+
+    pub(crate) fn note_event(&mut self, ev: impl FnOnce() -> EngineEvent) {
+        if self.emission == Emission::Suppressed { return; }
+        if let Some(sink) = self.event_sink.as_mut() { sink.on_event(&ev()); }
+    }
+
+    // Fill events for `trades`, made by taker `id` with `open` qty before them
+    pub(crate) fn note_fills(&mut self, id: OrderId, mut open: u64, trades: &[Trade]) {
+        if self.event_sink.is_none() { return; }
+        for t in trades {
+            open -= t.qty.min(open);
+            if t.conditions.contains(TradeConditions::STP_DECREMENT) { continue; }
+            self.note_event(|| fill(id, t, open));
+            self.note_event(|| fill(t.maker_id, t, t.maker_remaining));
+        }
+    }
+
+    pub(crate) fn note_rejected(&mut self, id: Option<OrderId>, account: AccountId, correlation: u64, err: &EngineError) {
+        if matches!(err, EngineError::Deferred { .. }) { return; }
+        self.note_event(|| EngineEvent::Rejected { id, account, correlation, reason: err.to_string() });
+    }
+
+    // A failed command other than a new order (those are reported on entry)
+    pub(crate) fn note_command_rejected(&mut self, cmd: &Command, err: &EngineError) {
+        let (id, account) = match *cmd {
+            Command::Cancel { id, account, .. } => (Some(id), account.unwrap_or_default()),
+            Command::CancelClient { account, .. } => (None, account),
+            Command::Reprice { id, .. } | Command::Amend { id, .. } | Command::Reduce { id, .. } => (Some(id), AccountId(0)),
+            _ => return,
+        };
+        self.note_rejected(id, account, 0, err);
+    }
+}
+
+fn fill(id: OrderId, t: &Trade, leaves: u64) -> EngineEvent {
+    let (trade_id, price, qty) = (t.trade_id, t.price, t.qty);
+    if leaves == 0 { EngineEvent::Filled { id, trade_id, price, qty } } else { EngineEvent::PartiallyFilled { id, trade_id, price, qty, leaves } }
+}
@@ -0,0 +1,62 @@
+use crate::{BookStorage, EngineError, OrderBook, OrderId};
+
+// What a batch does when a command hits an internal error (`EngineError::Invariant`, e.g. the id
+// index and the price levels disagree) rather than an ordinary rejection:
+//   Resilient  skip the command and carry on with the batch (default)
+//   FailFast   put the book in cancel-only mode and stop the batch there; it stays cancel-only
+//              until an operator calls `set_cancel_only(false)`
+// Either way an `EngineFault` is recorded for alerting (`drain_faults`). Rejections (unknown ids,
+// halted books, ...) are the command's own outcome: they never stop a batch under either policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    #[default]
+    Resilient,
+    FailFast,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultAction {
+    Skipped,
+    CancelOnly,
+    // Raised outside the book (e.g. an ingestor worker whose write-ahead journal failed): the
+    // worker stopped and nothing from `seq` on was applied
+    Stopped,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineFault {
+    pub seq: u64,
+    pub error: String,
+    pub action: FaultAction,
+}
+
+impl EngineError {
+    pub fn is_internal(&self) -> bool { matches!(self, EngineError::Invariant(_)) }
+}
+
+pub(crate) fn desync(id: OrderId) -> EngineError { EngineError::Invariant(format!("order {} indexed but missing from its level", id.0)) }
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy) { self.error_policy = policy; }
+
+    pub fn error_policy(&self) -> ErrorPolicy { self.error_policy }
+
+    // Cancel-only: new orders and reprices are rejected, cancels still go through
+    pub fn set_cancel_only(&mut self, on: bool) { self.cancel_only = on; }
+
+    pub fn is_cancel_only(&self) -> bool { self.cancel_only }
+
+    pub fn drain_faults(&mut self) -> Vec<EngineFault> { std::mem::take(&mut self.faults) }
+
+    pub fn drain_faults_into(&mut self, out: &mut Vec<EngineFault>) { out.append(&mut self.faults); }
+
+    // Apply the error policy to a failed command: Ok to go on with the batch, Err to stop it
+    pub fn resolve_error(&mut self, seq: u64, err: EngineError) -> Result<(), EngineError> {
+        if !err.is_internal() { return Ok(()); }
+        let action = match self.error_policy { ErrorPolicy::Resilient => FaultAction::Skipped, ErrorPolicy::FailFast => FaultAction::CancelOnly };
+        self.faults.push(EngineFault { seq, error: err.to_string(), action });
+        if action == FaultAction::Skipped { return Ok(()); }
+        self.cancel_only = true;
+        Err(err)
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{AccountId, BookStorage, OrderBook, OrderId, OrderRequest, Side, Trade, TradeConditions};
+
+// Consolidated execution report per taker command (off by default), the way order-entry protocols
+// answer the client: one report for the whole command in addition to the raw trades.
+//   filled / notional  over the command's trades as taker (STP decrements are not fills)
+//   fills              (price, qty) per price level, in execution order
+//   remaining          qty the command did not fill: resting now, or cancelled (markets, IOC-like
+//                      outcomes, STP)
+// Commands that did not trade get no report; neither do makers or auction uncrosses. Reports follow
+// the command's `Emission` (dropped when suppressed, `replay` set on replay).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FillReport {
+    pub id: OrderId,
+    pub account: AccountId,
+    pub correlation: u64,
+    pub side: Side,
+    pub filled: u64,
+    pub remaining: u64,
+    // Sum of price * qty
+    pub notional: u128,
+    pub fills: Vec<(u64, u64)>,
+    pub replay: bool,
+    pub output_seq: u64,
+}
+
+impl FillReport {
+    pub fn avg_price(&self) -> Option<f64> { (self.filled > 0).then(|| self.notional as f64 / self.filled as f64) }
+
+    fn of(id: OrderId, req: &OrderRequest, remaining: u64, trades: &[Trade]) -> Option<Self> {
+        let mut report = FillReport { id, account: req.account, correlation: req.correlation, side: req.side, filled: 0, remaining, notional: 0, fills: Vec::new(), replay: false, output_seq: 0 };
+        for t in trades.iter().filter(|t| t.taker_id == id && !t.conditions.contains(TradeConditions::STP_DECREMENT)) {
+            report.filled += t.qty;
+            report.notional += t.price as u128 * t.qty as u128;
+            match report.fills.last_mut() {
+                Some((p, q)) if *p == t.price => *q += t.qty,
+                _ => report.fills.push((t.price, t.qty)),
+            }
+        }
+        (report.filled > 0).then_some(report)
+    }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    // Off by default; turning it off drops undrained reports
+    pub fn set_fill_reports(&mut self, on: bool) {
+        self.fill_reports_on = on;
+        if !on { self.fill_reports.clear(); }
+    }
+
+    pub fn fill_reports_enabled(&self) -> bool { self.fill_reports_on }
+
+    pub fn drain_fill_reports(&mut self) -> Vec<FillReport> { std::mem::take(&mut self.fill_reports) }
+
+    pub fn drain_fill_reports_into(&mut self, out: &mut Vec<FillReport>) { out.append(&mut self.fill_reports); }
+
+    pub(crate) fn note_fill_report(&mut self, id: OrderId, req: &OrderRequest, remaining: u64, trades: &[Trade]) {
+        if !self.fill_reports_on { return; }
+        let Some(report) = FillReport::of(id, req, remaining, trades) else { return };
+        let output_seq = self.next_output_seq();
+        self.fill_reports.push(FillReport { output_seq, ..report });
+    }
+}
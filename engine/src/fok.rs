@@ -0,0 +1,55 @@
+use crate::short_sale::uptick_allows;
+use crate::storage::crosses;
+use crate::{BookStorage, CancelReason, EngineError, ImplicitCancel, OrderBook, OrderId, OrderRequest, OrderType, Side, StpAction, TimeInForce, Trade};
+
+// Fill or kill (`TimeInForce::Fok`): the order trades its whole quantity on entry or not at all. Before
+// anything is matched the opposite side is scanned, read only, under the rules matching applies:
+//   price       levels within the limit (market orders: every level)
+//   top level   `top_level_only` counts the best level only
+//   uptick      short sales count a level only while the uptick rule allows it, tracking the trade
+//               prices the sweep itself would print
+//   self-trade  same-group makers count as filled under `StpAction::Decrement`, are skipped under
+//               `CancelResting`, and stop the scan under `CancelIncoming` / `CancelBoth`
+// A killed order keeps the id it was given, produces no trades and leaves the book untouched; it is
+// reported as an `ImplicitCancel` with `CancelReason::Killed` for its full quantity, and the submit
+// returns its whole quantity as left over.
+impl<S: BookStorage> OrderBook<S> {
+    pub fn submit_limit_fok(&mut self, side: Side, price: u64, qty: u64) -> Result<(OrderId, Vec<Trade>, u64), EngineError> {
+        let mut trades = Vec::new();
+        let (id, remaining) = self.submit_limit_fok_into(side, price, qty, &mut trades)?;
+        Ok((id, trades, remaining))
+    }
+
+    pub fn submit_limit_fok_into(&mut self, side: Side, price: u64, qty: u64, trades_out: &mut Vec<Trade>) -> Result<(OrderId, u64), EngineError> {
+        self.submit_into(OrderRequest::limit(side, price, qty).with_tif(TimeInForce::Fok), trades_out)
+    }
+
+    // Whether `req` would trade in full against the book as it is
+    pub(crate) fn fully_fillable(&self, req: &OrderRequest) -> bool {
+        let maker_side = req.side.opposite();
+        let limit = match req.order_type { OrderType::Limit => Some(req.price), OrderType::Market => None };
+        let (mut need, mut first, mut blocked) = (req.qty, None, false);
+        let (mut last, mut last_different) = (self.last_trade, self.last_different);
+        self.storage.for_each_level(maker_side, &mut |p, orders| {
+            if !crosses(maker_side, p, limit) || req.top_level_only && first.is_some_and(|f| f != p) { return false; }
+            first = Some(p);
+            if req.short_sell && self.uptick_rule && !uptick_allows(p, last, last_different) { return false; }
+            let before = need;
+            for o in orders {
+                match self.stp.filter(|_| self.stp_groups.same_group(req.account, o.account)) {
+                    Some(StpAction::CancelIncoming | StpAction::CancelBoth) => { blocked = true; return false; }
+                    Some(StpAction::CancelResting) => {}
+                    Some(StpAction::Decrement) | None => need -= need.min(o.qty + o.reserve_qty),
+                }
+                if need == 0 { return false; }
+            }
+            if need < before && last != Some(p) { (last_different, last) = (last, Some(p)); }
+            true
+        });
+        need == 0 && !blocked
+    }
+
+    pub(crate) fn kill(&mut self, id: OrderId, req: &OrderRequest) {
+        self.note_cancel(ImplicitCancel { id, side: req.side, price: req.price, qty: req.qty, reason: CancelReason::Killed, replay: false, output_seq: 0 });
+    }
+}
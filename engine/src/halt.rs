@@ -0,0 +1,28 @@
+use crate::{BookStorage, OrderBook};
+
+// What a halt (SessionState::Halted) does to the resting book. New orders are always rejected
+// while halted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HaltAction {
+    // Orders stay and the book is frozen: cancels are rejected too
+    #[default]
+    Keep,
+    // Every resting order expires on entering the halt
+    Cancel,
+    // Orders stay and may be cancelled, nothing else is accepted
+    CancelOnly,
+}
+
+// Per instrument (per book). With `reopening_auction`, resuming (Halted -> Open) moves to PreOpen
+// instead, and continuous trading starts only after the reopening uncross (PreOpen -> Open).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HaltPolicy {
+    pub resting: HaltAction,
+    pub reopening_auction: bool,
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn set_halt_policy(&mut self, policy: HaltPolicy) { self.halt_policy = policy; }
+
+    pub fn halt_policy(&self) -> HaltPolicy { self.halt_policy }
+}
@@ -0,0 +1,27 @@
+use crate::{EngineError, OrderId, OrderRequest, Trade};
+
+// Runs before matching. May adjust the request (enrichment, qty caps) or reject it with an
+// error, in which case the order gets no id and the book is left untouched.
+pub trait PreMatchHook: Send {
+    fn pre_match(&mut self, req: &mut OrderRequest) -> Result<(), EngineError>;
+}
+
+// Runs once per submitted order that produced fills, with the trades it generated.
+// Trades are already applied to the book; hooks can observe or annotate them.
+pub trait PostTradeHook: Send {
+    fn post_trade(&mut self, req: &OrderRequest, taker_id: OrderId, trades: &mut [Trade]);
+}
+
+impl<F> PreMatchHook for F
+where
+    F: FnMut(&mut OrderRequest) -> Result<(), EngineError> + Send,
+{
+    fn pre_match(&mut self, req: &mut OrderRequest) -> Result<(), EngineError> { self(req) }
+}
+
+impl<F> PostTradeHook for F
+where
+    F: FnMut(&OrderRequest, OrderId, &mut [Trade]) + Send,
+{
+    fn post_trade(&mut self, req: &OrderRequest, taker_id: OrderId, trades: &mut [Trade]) { self(req, taker_id, trades) }
+}
@@ -0,0 +1,47 @@
+use crate::{market_data, BookStorage, EngineError, L3Event, Order, OrderBook, OrderRequest, OrderType, TimeInForce};
+
+// Iceberg orders (`OrderRequest::iceberg`): a resting limit order shows `display_qty` at a time and
+// keeps the rest as a hidden `reserve_qty`. Only the displayed slice is in the book's storage, so it
+// alone matches with price-time priority and is what depth (`top_n`, `best_bid` / `best_ask`), queue
+// positions and L3 events see. A slice used up by matching is refilled from the reserve and joins the
+// back of its level with a fresh timestamp, reported as an L3 Add under the same id; an incoming order
+// keeps matching refilled slices while it crosses, so the whole reserve is reachable (fill-or-kill
+// prescans count it). Cancels and implicit cancels take the reserve along with the slice. Only the
+// displayed slice takes part in an auction uncross. On entry an iceberg takes liquidity with its full
+// quantity; icebergs must be continuous limit orders and cannot be reduce-only.
+
+// Displayed slice and hidden reserve of `qty` resting with `display` shown at a time (0 = plain order)
+pub(crate) fn split(display: u64, qty: u64) -> (u64, u64) {
+    if display == 0 { return (qty, 0); }
+    let shown = qty.min(display);
+    (shown, qty - shown)
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub(crate) fn check_iceberg(&self, req: &OrderRequest) -> Result<(), EngineError> {
+        let Some(display) = req.display_qty else { return Ok(()) };
+        if display == 0 { return Err(EngineError::Rejected("iceberg display quantity must be positive".into())); }
+        if req.order_type == OrderType::Market || matches!(req.tif, TimeInForce::AtOpen | TimeInForce::AtClose) { return Err(EngineError::Rejected("iceberg orders must be continuous limit orders".into())); }
+        if req.reduce_only { return Err(EngineError::Rejected("iceberg orders cannot be reduce-only".into())); }
+        Ok(())
+    }
+
+    // Put the slices emptied since the last call back on the book from their reserves
+    pub(crate) fn replenish(&mut self) {
+        let mut refills = std::mem::take(&mut self.refills);
+        for mut o in refills.drain(..) {
+            (o.qty, o.reserve_qty) = split(o.display_qty, o.reserve_qty);
+            o.ts = self.now();
+            self.note_l3(|mode| L3Event::Add { id: o.id, side: o.side, price: o.price, qty: o.qty, ts: o.ts, owner: market_data::owner_of(mode, &o) });
+            self.index.insert(o.id.0, (o.side, o.price));
+            self.storage.push_back(o);
+        }
+        self.refills = refills;
+    }
+
+    // A slice taken off the book outside storage matching (self-trade decrement, auction fill): queue
+    // its refill and keep the id indexed when reserve is left
+    pub(crate) fn refill_or_unindex(&mut self, o: &Order) {
+        if o.reserve_qty > 0 { self.refills.push(o.clone()); } else { self.index.remove(&o.id.0); }
+    }
+}
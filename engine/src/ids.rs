@@ -0,0 +1,66 @@
+use crate::{BookStorage, OrderBook, OrderId, TradeId};
+
+// Order and trade id generation, injected per book with `OrderBook::set_id_generator`. The book keeps
+// counting its orders and trades from 1 (the counters are what snapshots save); the generator turns
+// the n-th into the id handed out, and must give ids that grow with n.
+//   Sequential    n itself (the default): unique within the book and across its snapshots
+//   EpochPrefixed the epoch in the top 24 bits: a book restarted without its snapshot under a new
+//                 epoch (e.g. a restart counter) never repeats an id; 2^40 ids per epoch
+//   NodePrefixed  the node in the top 16 bits: books on different engines or shards given distinct
+//                 nodes never share an id; 2^48 ids per node
+// Like the clock, the generator is not part of snapshots: install it again after `from_snapshot`
+// (and before replaying a journal, whose cancels name the ids it gave).
+pub trait IdGenerator: Send {
+    fn order_id(&self, n: u64) -> OrderId;
+    fn trade_id(&self, n: u64) -> TradeId;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sequential;
+
+impl IdGenerator for Sequential {
+    fn order_id(&self, n: u64) -> OrderId { OrderId(n) }
+
+    fn trade_id(&self, n: u64) -> TradeId { TradeId(n) }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EpochPrefixed {
+    pub epoch: u32,
+}
+
+impl EpochPrefixed {
+    pub const SHIFT: u32 = 40;
+
+    fn id(&self, n: u64) -> u64 { (self.epoch as u64 & 0xFF_FFFF) << Self::SHIFT | n }
+}
+
+impl IdGenerator for EpochPrefixed {
+    fn order_id(&self, n: u64) -> OrderId { OrderId(self.id(n)) }
+
+    fn trade_id(&self, n: u64) -> TradeId { TradeId(self.id(n)) }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NodePrefixed {
+    pub node: u16,
+}
+
+impl NodePrefixed {
+    pub const SHIFT: u32 = 48;
+
+    fn id(&self, n: u64) -> u64 { (self.node as u64) << Self::SHIFT | n }
+}
+
+impl IdGenerator for NodePrefixed {
+    fn order_id(&self, n: u64) -> OrderId { OrderId(self.id(n)) }
+
+    fn trade_id(&self, n: u64) -> TradeId { TradeId(self.id(n)) }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn set_id_generator(&mut self, ids: impl IdGenerator + 'static) { self.ids = Box::new(ids); }
+
+    // Id of the most recent order, 0 before the first
+    pub fn last_order_id(&self) -> OrderId { if self.next_id == 0 { OrderId(0) } else { self.ids.order_id(self.next_id) } }
+}
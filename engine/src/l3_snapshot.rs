@@ -0,0 +1,81 @@
+use crate::market_data::{self, Attribution, L3Event, Owner};
+use crate::{BookStorage, OrderBook, OrderId, Side};
+
+// Full order-by-order (L3) view of the resting book, as the L3 feed shows it: every order per price
+// level in FIFO order, levels best first, with its displayed quantity (an iceberg's reserve stays
+// hidden) and priority timestamp. It is the starting point for an external book: take it after
+// draining the feed, then `apply` the events drained since to keep it in step. Owners are filled in
+// only for `Attribution::Attributed`. `snapshot` (`BookSnapshot`) remains the internal copy used to
+// rebuild the book itself, with hidden quantities, accounts and time in force.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct L3Order {
+    pub id: OrderId,
+    pub qty: u64,
+    pub ts: u64,
+    pub owner: Option<Owner>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct L3Level {
+    pub price: u64,
+    pub orders: Vec<L3Order>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct L3Snapshot {
+    pub bids: Vec<L3Level>,
+    pub asks: Vec<L3Level>,
+}
+
+impl L3Snapshot {
+    pub fn order_count(&self) -> usize {
+        self.bids.iter().chain(self.asks.iter()).map(|l| l.orders.len()).sum()
+    }
+
+    pub fn levels(&self, side: Side) -> &[L3Level] {
+        match side { Side::Buy => &self.bids, Side::Sell => &self.asks }
+    }
+
+    // Bring the view forward by one feed event; events by id alone (update, delete) search the book
+    pub fn apply(&mut self, ev: L3Event) {
+        match ev {
+            L3Event::Add { id, side, price, qty, ts, owner } => {
+                let levels = match side { Side::Buy => &mut self.bids, Side::Sell => &mut self.asks };
+                let at = levels.partition_point(|l| match side { Side::Buy => l.price > price, Side::Sell => l.price < price });
+                if levels.get(at).is_none_or(|l| l.price != price) { levels.insert(at, L3Level { price, orders: Vec::new() }); }
+                levels[at].orders.push(L3Order { id, qty, ts, owner });
+            }
+            L3Event::Execute { id, remaining, .. } if remaining > 0 => { if let Some(o) = self.find(id) { o.qty = remaining; } }
+            L3Event::Execute { id, .. } | L3Event::Delete { id } => self.remove(id),
+            L3Event::Update { id, qty } => { if let Some(o) = self.find(id) { o.qty = qty; } }
+        }
+    }
+
+    fn find(&mut self, id: OrderId) -> Option<&mut L3Order> {
+        self.bids.iter_mut().chain(self.asks.iter_mut()).flat_map(|l| l.orders.iter_mut()).find(|o| o.id == id)
+    }
+
+    fn remove(&mut self, id: OrderId) {
+        for levels in [&mut self.bids, &mut self.asks] {
+            let Some(li) = levels.iter().position(|l| l.orders.iter().any(|o| o.id == id)) else { continue };
+            levels[li].orders.retain(|o| o.id != id);
+            if levels[li].orders.is_empty() { levels.remove(li); }
+            return;
+        }
+    }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn l3_snapshot(&self, attribution: Attribution) -> L3Snapshot {
+        let levels = |side| {
+            let mut out = Vec::new();
+            self.storage.for_each_level(side, &mut |price, orders| {
+                let orders = orders.map(|o| L3Order { id: o.id, qty: o.qty, ts: o.ts, owner: market_data::owner_of(Some(attribution), o) }).collect();
+                out.push(L3Level { price, orders });
+                true
+            });
+            out
+        };
+        L3Snapshot { bids: levels(Side::Buy), asks: levels(Side::Sell) }
+    }
+}
@@ -1,92 +1,304 @@
-use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::collections::{HashMap, VecDeque};
+use stops::StopBook;
+
+pub mod amend;
+pub mod auction;
+pub mod budget;
+pub mod cancels;
+pub mod client_ids;
+pub mod clock;
+pub mod conditions;
+pub mod depth_feed;
+pub mod eod;
+pub mod events;
+pub mod faults;
+pub mod fill_report;
+pub mod fok;
+pub mod halt;
+pub mod hooks;
+pub mod iceberg;
+pub mod ids;
+pub mod l3_snapshot;
+pub mod market_data;
+pub mod min_resting;
+pub mod order_status;
+pub mod output;
+pub mod positions;
+pub mod post_only;
+pub mod pricing;
+pub mod reduce;
+pub mod reference;
+pub mod refdata;
+pub mod reprice;
+pub mod risk;
+pub mod session;
+pub mod short_sale;
+pub mod snapshot;
+pub mod snapshot_codec;
+pub mod stats;
+pub mod stops;
+pub mod storage;
+pub mod stp;
+pub mod surveillance;
+pub mod symbol_config;
+pub mod tape;
+pub mod throttle;
+pub mod verify;
+#[cfg(feature = "rkyv")]
+pub mod wire;
+pub mod workload;
+pub use halt::{HaltAction, HaltPolicy};
+pub use budget::Continuation;
+pub use cancels::{CancelReason, ImplicitCancel};
+pub use client_ids::{ClientIdEntry, ClientIdRetention};
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use conditions::{Liquidity, TradeConditions};
+pub use depth_feed::DepthDelta;
+pub use eod::{EodReport, Settlement, SettlementSource};
+pub use events::{EngineEvent, EventSink};
+pub use faults::{EngineFault, ErrorPolicy, FaultAction};
+pub use fill_report::FillReport;
+pub use hooks::{PostTradeHook, PreMatchHook};
+pub use ids::{EpochPrefixed, IdGenerator, NodePrefixed, Sequential};
+pub use risk::RiskGroups;
+pub use stp::{StpAction, StpGroups};
+pub use post_only::{AmendCrossPolicy, PostOnlyPolicy, Repriced};
+pub use pricing::SideAggregate;
+pub use market_data::{Attribution, L3Event, MarketDataSnapshot, Owner};
+pub use l3_snapshot::{L3Level, L3Order, L3Snapshot};
+pub use min_resting::{MinResting, MinRestingAction};
+pub use order_status::{OrderState, OrderStatus};
+pub use output::Emission;
+pub use reference::{run_differential, DiffOp, ReferenceBook};
+pub use refdata::{ReferenceValue, ReferenceValues};
+pub use session::{SessionChange, SessionState};
+pub use snapshot::{BookSnapshot, LevelSnapshot};
+pub use symbol_config::SymbolConfig;
+pub use tape::{TapeEntry, TradeTape};
+pub use throttle::{EntryThrottle, ThrottleHit};
+pub use stats::SessionStats;
+pub use stops::StopActivation;
+pub use storage::{BTreeStorage, BookStorage, LadderStorage, SkipListStorage, SlabStorage, SoaStorage, LADDER_MAX_SPAN};
+pub use workload::{Workload, WorkloadConfig, WorkloadMix, WorkloadOp, WorkloadReport};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug, PartialEq, Eq)))]
 pub enum Side {
     Buy,
     Sell,
 }
 
+impl Side {
+    pub fn opposite(self) -> Side {
+        match self { Side::Buy => Side::Sell, Side::Sell => Side::Buy }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug, PartialEq, Eq)))]
 pub enum Command {
-    Limit { seq: u64, side: Side, price: u64, qty: u64 },
-    Market { seq: u64, side: Side, qty: u64 },
-    Cancel { seq: u64, id: OrderId },
+    Limit { seq: u64, side: Side, price: u64, qty: u64, tif: TimeInForce, account: AccountId },
+    Market { seq: u64, side: Side, qty: u64, account: AccountId },
+    // With an account the order is removed only if it belongs to that account (see `OrderBook::cancel_owned`)
+    Cancel { seq: u64, id: OrderId, account: Option<AccountId> },
+    // General form carrying every order attribute (time in force, ...)
+    Submit { seq: u64, req: OrderRequest },
+    // Session transition (e.g. from a trading calendar); sequenced so replicas and replay see it in order
+    Session { seq: u64, state: SessionState },
+    // Move a resting order to a new price level (see `OrderBook::reprice`)
+    Reprice { seq: u64, id: OrderId, new_price: u64 },
+    // Cancel by the account's client order id (see `OrderBook::cancel_by_client_id`)
+    CancelClient { seq: u64, account: AccountId, client_id: ClientOrderId },
+    // Change a resting order's price and quantity (see `OrderBook::amend`)
+    Amend { seq: u64, id: OrderId, new_price: u64, new_qty: u64 },
+    // Take quantity off a resting order in place (see `OrderBook::reduce`)
+    Reduce { seq: u64, id: OrderId, qty_delta: u64 },
+    // External reference value for the instrument (see `refdata`)
+    Reference { seq: u64, value: ReferenceValue },
+    // Close, settle and reset the session (see `eod`)
+    EndOfDay { seq: u64 },
+    // Run a slice of a paused sweep on an otherwise quiet book (see `budget`)
+    Resume { seq: u64 },
 }
 
-impl OrderBook {
+impl<S: BookStorage> OrderBook<S> {
     pub fn process_commands_batch_checked_into(
         &mut self,
         cmds: &mut [Command],
         trades_out: &mut Vec<Trade>,
-    ) -> Result<Vec<(OrderId, u64)>, EngineError> {
-        // Ensure strict increasing seq; if not sorted, sort by seq stably.
+    ) -> Result<Vec<CommandResult>, EngineError> {
+        // Ensure strict increasing seq; if not sorted, sort by seq stably and check for duplicates.
+        // An already strictly increasing batch cannot hold duplicates, so it is scanned once.
         let is_sorted = cmds.windows(2).all(|w| seq_of(&w[0]) < seq_of(&w[1]));
         if !is_sorted {
-            cmds.sort_by_key(|c| seq_of(c));
-        }
-        // After sort, check for duplicates
-        if cmds.windows(2).any(|w| seq_of(&w[0]) >= seq_of(&w[1])) {
-            return Err(EngineError::InvalidSequence);
+            cmds.sort_by_key(seq_of);
+            if cmds.windows(2).any(|w| seq_of(&w[0]) >= seq_of(&w[1])) {
+                return Err(EngineError::InvalidSequence);
+            }
         }
+        self.process_commands_batch_presorted_unchecked(cmds, trades_out)
+    }
+
+    // Trusted internal callers only (the ingestor's sequencer stamps strictly increasing seqs itself):
+    // no sort and no duplicate scan. Out-of-order or repeated seqs are applied as given; debug builds
+    // assert the order instead.
+    // One result per command: a rejected command reports its error and the batch carries on, as the
+    // commands behind it are already sequenced; only an internal error can stop it (see `ErrorPolicy`)
+    pub fn process_commands_batch_presorted_unchecked(
+        &mut self,
+        cmds: &[Command],
+        trades_out: &mut Vec<Trade>,
+    ) -> Result<Vec<CommandResult>, EngineError> {
+        debug_assert!(cmds.windows(2).all(|w| seq_of(&w[0]) < seq_of(&w[1])), "presorted batch out of seq order");
         let mut results = Vec::with_capacity(cmds.len());
         for &cmd in cmds.iter() {
-            match cmd {
-                Command::Limit { side, price, qty, .. } => {
-                    let start_len = trades_out.len();
-                    let (id, remaining) = self.submit_limit_into(side, price, qty, trades_out);
-                    let _ = trades_out.len() - start_len;
-                    results.push((id, remaining));
-                }
-                Command::Market { side, qty, .. } => {
-                    let start_len = trades_out.len();
-                    let (id, remaining) = self.submit_market_into(side, qty, trades_out);
-                    let _ = trades_out.len() - start_len;
-                    results.push((id, remaining));
-                }
-                Command::Cancel { id, .. } => {
-                    match self.cancel(id) {
-                        Ok(_o) => results.push((id, 0)),
-                        Err(e) => return Err(e),
-                    }
-                }
+            match self.process_command(cmd, trades_out) {
+                Err(e) if e.is_internal() => { self.resolve_error(seq_of(&cmd), e.clone())?; results.push(Err(e)); }
+                r => results.push(r),
             }
         }
         Ok(results)
     }
 
+    // One sequenced command: (order id, qty left) for orders, (id, 0) for cancels, (0, 0) for session changes, end of day and reference values
+    pub fn process_command(&mut self, cmd: Command, trades_out: &mut Vec<Trade>) -> Result<(OrderId, u64), EngineError> {
+        self.release_deferred_cancels()?;
+        self.resume_continuation(trades_out);
+        let r = self.apply_command(cmd, trades_out);
+        if let Err(e) = &r { self.note_command_rejected(&cmd, e); }
+        r
+    }
+
+    fn apply_command(&mut self, cmd: Command, trades_out: &mut Vec<Trade>) -> Result<(OrderId, u64), EngineError> {
+        match cmd {
+            Command::Limit { side, price, qty, tif, account, .. } => self.submit_into(OrderRequest::limit(side, price, qty).with_tif(tif).with_account(account), trades_out),
+            Command::Market { side, qty, account, .. } => self.submit_into(OrderRequest::market(side, qty).with_account(account), trades_out),
+            // A deferred cancel is accepted; the order goes when its minimum resting time is up
+            Command::Cancel { id, account, .. } => match match account { Some(a) => self.cancel_owned(id, a), None => self.cancel(id) } {
+                Err(EngineError::Deferred { .. }) => Ok((id, 0)),
+                r => r.map(|_| (id, 0)),
+            },
+            Command::CancelClient { account, client_id, .. } => match self.order_id_by_client(account, client_id) {
+                None => Err(EngineError::UnknownOrder),
+                Some(id) => match self.cancel(id) { Err(EngineError::Deferred { .. }) => Ok((id, 0)), r => r.map(|_| (id, 0)) },
+            },
+            Command::Submit { req, .. } => self.submit_into(req, trades_out),
+            Command::Reprice { id, new_price, .. } => self.reprice(id, new_price, trades_out).map(|remaining| (id, remaining)),
+            Command::Amend { id, new_price, new_qty, .. } => self.amend(id, new_price, new_qty, trades_out).map(|remaining| (id, remaining)),
+            Command::Reduce { id, qty_delta, .. } => match self.reduce(id, qty_delta) { Err(EngineError::Deferred { .. }) => Ok((id, 0)), r => r.map(|remaining| (id, remaining)) },
+            Command::EndOfDay { .. } => {
+                let report = self.end_of_day(trades_out);
+                self.eod_reports.push(report);
+                Ok((OrderId(0), 0))
+            }
+            Command::Reference { value, .. } => { self.set_reference_value(value); Ok((OrderId(0), 0)) }
+            Command::Resume { .. } => Ok((OrderId(0), 0)),
+            Command::Session { state, .. } => {
+                // Auction trades go out with the batch; expired orders are reported as implicit cancels
+                trades_out.append(&mut self.set_session(state).trades);
+                Ok((OrderId(0), 0))
+            }
+        }
+    }
+
     pub fn process_commands_batch_into(
         &mut self,
         cmds: &[Command],
         trades_out: &mut Vec<Trade>,
-    ) -> Vec<Result<(OrderId, u64), EngineError>> {
+    ) -> Vec<CommandResult> {
         // Backward-friendly wrapper: copy slice to a Vec, then call checked variant.
         let mut owned: Vec<Command> = cmds.to_vec();
-        match self.process_commands_batch_checked_into(&mut owned, trades_out) {
-            Ok(res) => res.into_iter().map(Ok).collect(),
-            Err(e) => vec![Err(e)],
-        }
+        self.process_commands_batch_checked_into(&mut owned, trades_out).unwrap_or_else(|e| vec![Err(e)])
     }
 }
 
+impl Command {
+    pub fn seq(&self) -> u64 { seq_of(self) }
+}
+
 #[inline]
 fn seq_of(c: &Command) -> u64 {
     match *c {
         Command::Limit { seq, .. } => seq,
         Command::Market { seq, .. } => seq,
         Command::Cancel { seq, .. } => seq,
+        Command::Submit { seq, .. } => seq,
+        Command::Session { seq, .. } => seq,
+        Command::Reprice { seq, .. } => seq,
+        Command::Amend { seq, .. } => seq,
+        Command::Reduce { seq, .. } => seq,
+        Command::CancelClient { seq, .. } => seq,
+        Command::Reference { seq, .. } => seq,
+        Command::EndOfDay { seq } => seq,
+        Command::Resume { seq } => seq,
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug, PartialEq, Eq)))]
 pub enum OrderType {
     Limit,
     Market,
 }
 
+// How long a resting remainder stays on the book
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug, PartialEq, Eq)))]
+pub enum TimeInForce {
+    #[default]
+    Gtc,
+    // Expires when the session closes (see `OrderBook::set_session`)
+    Day,
+    // Auction-only: entered during PreOpen / PreClose, executes solely in that uncross (see `auction`)
+    AtOpen,
+    AtClose,
+    // Immediate or cancel: trades whatever crosses on entry, the remainder is discarded instead of resting
+    Ioc,
+    // Fill or kill: trades its whole quantity on entry or nothing at all (see `fok`)
+    Fok,
+}
+
+impl TimeInForce {
+    // Stable numbering for the journal, both snapshot formats and `state_hash`: 0 gtc, 1 day, 2 at-open,
+    // 3 at-close, 4 ioc, 5 fok
+    pub fn code(self) -> u8 {
+        match self { TimeInForce::Gtc => 0, TimeInForce::Day => 1, TimeInForce::AtOpen => 2, TimeInForce::AtClose => 3, TimeInForce::Ioc => 4, TimeInForce::Fok => 5 }
+    }
+
+    pub fn from_code(code: u64) -> Option<Self> {
+        match code { 0 => Some(TimeInForce::Gtc), 1 => Some(TimeInForce::Day), 2 => Some(TimeInForce::AtOpen), 3 => Some(TimeInForce::AtClose), 4 => Some(TimeInForce::Ioc), 5 => Some(TimeInForce::Fok), _ => None }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug, PartialEq, Eq)))]
 pub struct OrderId(pub u64);
 
-#[derive(Debug, Clone)]
+// Per-book execution id, from 1 in execution order; STP decrements are not executions and carry 0
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug, PartialEq, Eq)))]
+pub struct TradeId(pub u64);
+
+// Caller's own id for an order, unique per account for the session (see `client_ids`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug, PartialEq, Eq)))]
+pub struct ClientOrderId(pub u64);
+
+// Owner of an order; AccountId(0) when the caller does not attribute orders
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug, PartialEq, Eq)))]
+pub struct AccountId(pub u64);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Order {
     pub id: OrderId,
     pub side: Side,
@@ -94,17 +306,114 @@ pub struct Order {
     pub qty: u64,
     pub order_type: OrderType,
     pub ts: u64,
+    pub tif: TimeInForce,
+    pub account: AccountId,
+    pub reduce_only: bool,
+    pub correlation: u64,
+    // Iceberg slice size, 0 for a plain order; `qty` is then the displayed slice (see `iceberg`)
+    pub display_qty: u64,
+    // Hidden iceberg quantity behind the displayed slice
+    pub reserve_qty: u64,
+    // Open plus filled quantity: the accepted quantity, moved by amends, reduce-only trims and
+    // self-trade decrements (see `order_status`)
+    pub order_qty: u64,
+}
+
+// A new order as seen by pre-match hooks; `price` is ignored for market orders
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug, PartialEq, Eq)))]
+pub struct OrderRequest {
+    pub side: Side,
+    pub order_type: OrderType,
+    pub price: u64,
+    pub qty: u64,
+    pub tif: TimeInForce,
+    pub account: AccountId,
+    // Only ever shrink the account's position (see `positions`)
+    pub reduce_only: bool,
+    // Subject to the book's uptick rule when enabled (see `short_sale`)
+    pub short_sell: bool,
+    // Caller-chosen id copied onto the order and its trades (e.g. a basket); 0 = none
+    pub correlation: u64,
+    // Must not take liquidity on entry (see `post_only`)
+    pub post_only: bool,
+    // Trade only at the best opposite price level and cancel the rest, never rests; with a market
+    // order this probes the touch without sweeping the book
+    pub top_level_only: bool,
+    // How the order's trades and events are emitted (see `output`)
+    pub emission: Emission,
+    // Held until the last trade price reaches this stop price (see `stops`)
+    pub stop_price: Option<u64>,
+    // Rest as an iceberg showing this much at a time (see `iceberg`)
+    pub display_qty: Option<u64>,
+    // Caller's id for the order, for lookups and cancels besides the engine's id (see `client_ids`)
+    pub client_id: Option<ClientOrderId>,
 }
 
-#[derive(Debug, Clone)]
+impl OrderRequest {
+    pub fn limit(side: Side, price: u64, qty: u64) -> Self {
+        Self { side, order_type: OrderType::Limit, price, qty, tif: TimeInForce::Gtc, account: AccountId(0), reduce_only: false, short_sell: false, correlation: 0, post_only: false, top_level_only: false, emission: Emission::Live, stop_price: None, display_qty: None, client_id: None }
+    }
+
+    pub fn market(side: Side, qty: u64) -> Self {
+        Self { side, order_type: OrderType::Market, price: 0, qty, tif: TimeInForce::Gtc, account: AccountId(0), reduce_only: false, short_sell: false, correlation: 0, post_only: false, top_level_only: false, emission: Emission::Live, stop_price: None, display_qty: None, client_id: None }
+    }
+
+    pub fn with_tif(mut self, tif: TimeInForce) -> Self { self.tif = tif; self }
+
+    pub fn with_account(mut self, account: AccountId) -> Self { self.account = account; self }
+
+    pub fn reduce_only(mut self) -> Self { self.reduce_only = true; self }
+
+    pub fn short_sell(mut self) -> Self { self.short_sell = true; self }
+
+    pub fn with_correlation(mut self, correlation: u64) -> Self { self.correlation = correlation; self }
+
+    pub fn post_only(mut self) -> Self { self.post_only = true; self }
+
+    pub fn top_level_only(mut self) -> Self { self.top_level_only = true; self }
+
+    pub fn with_emission(mut self, emission: Emission) -> Self { self.emission = emission; self }
+
+    // Stop-market for a market request, stop-limit for a limit request
+    pub fn with_stop(mut self, stop_price: u64) -> Self { self.stop_price = Some(stop_price); self }
+
+    pub fn iceberg(mut self, display_qty: u64) -> Self { self.display_qty = Some(display_qty); self }
+
+    pub fn with_client_id(mut self, client_id: ClientOrderId) -> Self { self.client_id = Some(client_id); self }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug, PartialEq, Eq)))]
 pub struct Trade {
+    pub trade_id: TradeId,
     pub taker_id: OrderId,
     pub maker_id: OrderId,
     pub price: u64,
     pub qty: u64,
+    // Aggressor side; the taker bought when this is Buy
+    pub taker_side: Side,
+    pub taker_correlation: u64,
+    pub maker_correlation: u64,
+    // Owners of the two orders, for attributing fills downstream
+    pub taker_account: AccountId,
+    pub maker_account: AccountId,
+    // Maker's open qty after this trade (displayed plus iceberg reserve), 0 when it is done, so
+    // replicas and clearing need not track maker residuals
+    pub maker_remaining: u64,
+    // `maker_remaining == 0`: the maker left the book with this trade
+    pub maker_fully_filled: bool,
+    // The book's logical time of the trade (the `ts` of the command that caused it; an uncross uses
+    // the latest), not wall time, so replicas agree
+    pub ts: u64,
+    pub conditions: TradeConditions,
+    // Position in the book's output sequence (see `output`)
+    pub output_seq: u64,
 }
 
-#[derive(thiserror::Error, Debug)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
 pub enum EngineError {
     #[error("unknown order id")]
     UnknownOrder,
@@ -112,233 +421,351 @@ pub enum EngineError {
     InvalidSide,
     #[error("invalid sequence in batch")] 
     InvalidSequence,
+    #[error("order rejected: {0}")]
+    Rejected(String),
+    // Cancel queued until the order's minimum resting time is up (see `min_resting`)
+    #[error("cancel deferred until {until}")]
+    Deferred { until: u64 },
+    // The account already entered an order under this client id this session (see `client_ids`)
+    #[error("duplicate client order id {}", .0.0)]
+    DuplicateClientId(ClientOrderId),
+    // Too many new orders from the account within the throttle window (see `throttle`)
+    #[error("order entry throttled: {} orders in {}us, limit {}; retry after {}us", .0.rate, .0.window_micros, .0.limit, .0.retry_after_micros)]
+    Throttled(ThrottleHit),
+    // Price off the instrument's tick grid (see `symbol_config`)
+    #[error("price {price} is not a multiple of the tick size {tick}")]
+    InvalidTick { price: u64, tick: u64 },
+    // Quantity that is not a whole number of lots
+    #[error("quantity {qty} is not a multiple of the lot size {lot}")]
+    InvalidLot { qty: u64, lot: u64 },
+    // Quantity outside the instrument's order size bounds
+    #[error("quantity {qty} outside {min}..={max}")]
+    InvalidQty { qty: u64, min: u64, max: u64 },
+    // A book setting that cannot take effect, e.g. a `SymbolConfig` with a zero tick (see `symbol_config`)
+    #[error("invalid configuration: {0}")]
+    InvalidConfig(String),
+    // Book state found inconsistent (see `faults`)
+    #[error("internal invariant violated: {0}")]
+    Invariant(String),
 }
 
-#[derive(Default)]
-pub struct OrderBook {
-    bids: BTreeMap<u64, VecDeque<Order>>, // price -> fifo
-    asks: BTreeMap<u64, VecDeque<Order>>, // price -> fifo
+// Outcome of one sequenced command (see `process_command`)
+pub type CommandResult = Result<(OrderId, u64), EngineError>;
+
+// (price, total qty, resting orders) per level, best first
+pub type Depth = Vec<(u64, u64, usize)>;
+
+pub struct OrderBook<S: BookStorage = BTreeStorage> {
+    storage: S,
     index: HashMap<u64, (Side, u64)>,     // id -> (side, price)
-    next_id: u64,
+    next_id: u64, // orders numbered so far; ids come from `ids`
     ts: u64,
+    pre_match_hooks: Vec<Box<dyn PreMatchHook>>,
+    post_trade_hooks: Vec<Box<dyn PostTradeHook>>,
+    event_sink: Option<Box<dyn EventSink>>,
+    session: SessionState,
+    positions: Option<HashMap<AccountId, i64>>, // net filled qty per account, when tracked
+    reduce_only: HashMap<AccountId, Vec<OrderId>>, // resting reduce-only orders per account, oldest first
+    reduce_only_updates: Vec<(OrderId, u64)>,
+    touched: Vec<AccountId>,
+    uptick_rule: bool,
+    last_trade: Option<u64>,
+    last_different: Option<u64>, // most recent trade price other than `last_trade`
+    auction: Vec<Order>, // auction-only orders waiting for the uncross, in arrival order
+    halt_policy: HaltPolicy,
+    l3_mode: Option<Attribution>,
+    l3: Vec<L3Event>,
+    depth_feed: Option<depth_feed::DepthFeed>,
+    match_budget: Option<u32>,
+    continuations: VecDeque<budget::Continuation>,
+    implicit_cancels: Vec<ImplicitCancel>,
+    post_only: PostOnlyPolicy,
+    amend_cross: AmendCrossPolicy,
+    repriced: Vec<Repriced>,
+    stp: Option<StpAction>,
+    stp_groups: StpGroups,
+    tape: Option<TradeTape>,
+    stats: SessionStats,
+    error_policy: ErrorPolicy,
+    cancel_only: bool,
+    faults: Vec<EngineFault>,
+    risk: Option<(RiskGroups, String)>, // shared group limits and this book's instrument
+    refdata: ReferenceValues,
+    price_band: Option<u64>,
+    eod_reports: Vec<EodReport>,
+    // Emission of the command being processed (see `output`)
+    emission: Emission,
+    output_seq: u64, // last output sequence number assigned
+    last_trade_id: u64,
+    fill_reports_on: bool,
+    fill_reports: Vec<FillReport>,
+    clock: Box<dyn Clock>,
+    ids: Box<dyn IdGenerator>,
+    min_resting: Option<MinResting>,
+    rest_times: HashMap<u64, u64>, // id -> clock micros it started resting, while `min_resting` is set
+    deferred_cancels: Vec<(u64, OrderId)>,
+    stops: StopBook,
+    stop_activations: Vec<StopActivation>,
+    refills: Vec<Order>, // iceberg slices used up and waiting for `replenish`
+    client_ids: HashMap<(AccountId, ClientOrderId), OrderId>,
+    client_id_window: Option<client_ids::ClientIdWindow>, // retention and held ids, oldest term first
+    throttle: Option<(EntryThrottle, HashMap<AccountId, VecDeque<u64>>)>, // limit and recent entry times per account
+    throttle_hits: Vec<ThrottleHit>,
+    symbol_config: Option<SymbolConfig>,
+}
+
+impl<S: BookStorage> Default for OrderBook<S> {
+    fn default() -> Self { Self::with_storage(S::default()) }
 }
 
 impl OrderBook {
     pub fn new() -> Self { Self::default() }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    // Book on a specific backend, e.g. `OrderBook::with_storage(LadderStorage::with_range(9_000, 11_000))`
+    pub fn with_storage(storage: S) -> Self {
+        Self { storage, index: HashMap::new(), next_id: 0, ts: 0, pre_match_hooks: Vec::new(), post_trade_hooks: Vec::new(), event_sink: None, session: SessionState::Open,
+            positions: None, reduce_only: HashMap::new(), reduce_only_updates: Vec::new(), touched: Vec::new(),
+            uptick_rule: false, last_trade: None, last_different: None, auction: Vec::new(), halt_policy: HaltPolicy::default(), l3_mode: None, l3: Vec::new(), depth_feed: None, match_budget: None, continuations: VecDeque::new(), implicit_cancels: Vec::new(), post_only: PostOnlyPolicy::default(), amend_cross: AmendCrossPolicy::default(), repriced: Vec::new(), stp: None, stp_groups: StpGroups::default(), tape: None, stats: SessionStats::default(), error_policy: ErrorPolicy::default(), cancel_only: false, faults: Vec::new(), risk: None,
+            refdata: ReferenceValues::default(), price_band: None, eod_reports: Vec::new(), emission: Emission::Live, output_seq: 0, last_trade_id: 0, fill_reports_on: false, fill_reports: Vec::new(),
+            clock: Box::new(SystemClock), ids: Box::new(Sequential), min_resting: None, rest_times: HashMap::new(), deferred_cancels: Vec::new(),
+            stops: StopBook::default(), stop_activations: Vec::new(), refills: Vec::new(), client_ids: HashMap::new(), client_id_window: None, throttle: None, throttle_hits: Vec::new(), symbol_config: None }
+    }
 
     pub fn now(&mut self) -> u64 { self.ts += 1; self.ts }
 
-    pub fn next_order_id(&mut self) -> OrderId { self.next_id += 1; OrderId(self.next_id) }
+    pub fn next_order_id(&mut self) -> OrderId { self.next_id += 1; self.ids.order_id(self.next_id) }
 
-    pub fn submit_limit(&mut self, side: Side, price: u64, qty: u64) -> (OrderId, Vec<Trade>, u64) {
-        let id = self.next_order_id();
-        let ts = self.now();
+    pub(crate) fn next_trade_id(&mut self) -> TradeId { self.last_trade_id += 1; self.ids.trade_id(self.last_trade_id) }
+
+    // Id of the most recent trade, 0 before the first
+    pub fn last_trade_id(&self) -> TradeId { if self.last_trade_id == 0 { TradeId(0) } else { self.ids.trade_id(self.last_trade_id) } }
+
+    // Hooks run in registration order on every submit path (single, `_into` and batch)
+    pub fn add_pre_match_hook(&mut self, hook: Box<dyn PreMatchHook>) { self.pre_match_hooks.push(hook); }
+
+    pub fn add_post_trade_hook(&mut self, hook: Box<dyn PostTradeHook>) { self.post_trade_hooks.push(hook); }
+
+    pub fn submit_limit(&mut self, side: Side, price: u64, qty: u64) -> Result<(OrderId, Vec<Trade>, u64), EngineError> {
         let mut trades = Vec::new();
-        let mut remaining = qty;
-        match side {
-            Side::Buy => {
-                loop {
-                    if remaining == 0 { break; }
-                    let p_opt = self.asks.first_key_value().map(|(p, _)| *p);
-                    let p = match p_opt { Some(p) if p <= price => p, _ => break };
-                    if let Some(queue) = self.asks.get_mut(&p) {
-                        while remaining > 0 {
-                            if let Some(maker) = queue.front_mut() {
-                                let trade_qty = remaining.min(maker.qty);
-                                trades.push(Trade { taker_id: id, maker_id: maker.id, price: p, qty: trade_qty });
-                                maker.qty -= trade_qty;
-                                remaining -= trade_qty;
-                                if maker.qty == 0 { queue.pop_front(); } else { break; }
-                            } else { break; }
-                        }
-                        if queue.is_empty() { self.asks.remove(&p); }
-                    } else { break; }
-                }
-                if remaining > 0 {
-                    let o = Order { id, side, price, qty: remaining, order_type: OrderType::Limit, ts };
-                    self.bids.entry(price).or_default().push_back(o);
-                    self.index.insert(id.0, (Side::Buy, price));
-                }
-            }
-            Side::Sell => {
-                loop {
-                    if remaining == 0 { break; }
-                    let p_opt = self.bids.last_key_value().map(|(p, _)| *p);
-                    let p = match p_opt { Some(p) if p >= price => p, _ => break };
-                    if let Some(queue) = self.bids.get_mut(&p) {
-                        while remaining > 0 {
-                            if let Some(maker) = queue.front_mut() {
-                                let trade_qty = remaining.min(maker.qty);
-                                trades.push(Trade { taker_id: id, maker_id: maker.id, price: p, qty: trade_qty });
-                                maker.qty -= trade_qty;
-                                remaining -= trade_qty;
-                                if maker.qty == 0 { queue.pop_front(); } else { break; }
-                            } else { break; }
-                        }
-                        if queue.is_empty() { self.bids.remove(&p); }
-                    } else { break; }
-                }
-                if remaining > 0 {
-                    let o = Order { id, side, price, qty: remaining, order_type: OrderType::Limit, ts };
-                    self.asks.entry(price).or_default().push_back(o);
-                    self.index.insert(id.0, (Side::Sell, price));
-                }
-            }
-        }
-        (id, trades, remaining)
+        let (id, remaining) = self.submit_limit_into(side, price, qty, &mut trades)?;
+        Ok((id, trades, remaining))
     }
 
-    pub fn submit_market(&mut self, side: Side, qty: u64) -> (OrderId, Vec<Trade>, u64) {
-        let id = self.next_order_id();
-        let _ts = self.now();
+    // Limit order with IOC time in force: the returned qty left was discarded, nothing rests
+    pub fn submit_limit_ioc(&mut self, side: Side, price: u64, qty: u64) -> Result<(OrderId, Vec<Trade>, u64), EngineError> {
         let mut trades = Vec::new();
-        let mut remaining = qty;
-        match side {
-            Side::Buy => {
-                loop {
-                    if remaining == 0 { break; }
-                    let p_opt = self.asks.first_key_value().map(|(p, _)| *p);
-                    let p = match p_opt { Some(p) => p, None => break };
-                    if let Some(queue) = self.asks.get_mut(&p) {
-                        while remaining > 0 {
-                            if let Some(maker) = queue.front_mut() {
-                                let trade_qty = remaining.min(maker.qty);
-                                trades.push(Trade { taker_id: id, maker_id: maker.id, price: p, qty: trade_qty });
-                                maker.qty -= trade_qty;
-                                remaining -= trade_qty;
-                                if maker.qty == 0 { queue.pop_front(); } else { break; }
-                            } else { break; }
-                        }
-                        if queue.is_empty() { self.asks.remove(&p); }
-                    } else { break; }
-                }
-            }
-            Side::Sell => {
-                loop {
-                    if remaining == 0 { break; }
-                    let p_opt = self.bids.last_key_value().map(|(p, _)| *p);
-                    let p = match p_opt { Some(p) => p, None => break };
-                    if let Some(queue) = self.bids.get_mut(&p) {
-                        while remaining > 0 {
-                            if let Some(maker) = queue.front_mut() {
-                                let trade_qty = remaining.min(maker.qty);
-                                trades.push(Trade { taker_id: id, maker_id: maker.id, price: p, qty: trade_qty });
-                                maker.qty -= trade_qty;
-                                remaining -= trade_qty;
-                                if maker.qty == 0 { queue.pop_front(); } else { break; }
-                            } else { break; }
-                        }
-                        if queue.is_empty() { self.bids.remove(&p); }
-                    } else { break; }
-                }
-            }
-        }
-        (id, trades, remaining)
+        let (id, remaining) = self.submit_limit_ioc_into(side, price, qty, &mut trades)?;
+        Ok((id, trades, remaining))
+    }
+
+    pub fn submit_market(&mut self, side: Side, qty: u64) -> Result<(OrderId, Vec<Trade>, u64), EngineError> {
+        let mut trades = Vec::new();
+        let (id, remaining) = self.submit_market_into(side, qty, &mut trades)?;
+        Ok((id, trades, remaining))
+    }
+
+    pub fn submit(&mut self, req: OrderRequest) -> Result<(OrderId, Vec<Trade>, u64), EngineError> {
+        let mut trades = Vec::new();
+        let (id, remaining) = self.submit_into(req, &mut trades)?;
+        Ok((id, trades, remaining))
     }
 
     // Zero-allocation variants
-    pub fn submit_limit_into(&mut self, side: Side, price: u64, qty: u64, trades_out: &mut Vec<Trade>) -> (OrderId, u64) {
+    pub fn submit_limit_into(&mut self, side: Side, price: u64, qty: u64, trades_out: &mut Vec<Trade>) -> Result<(OrderId, u64), EngineError> {
+        self.submit_into(OrderRequest::limit(side, price, qty), trades_out)
+    }
+
+    pub fn submit_limit_ioc_into(&mut self, side: Side, price: u64, qty: u64, trades_out: &mut Vec<Trade>) -> Result<(OrderId, u64), EngineError> {
+        self.submit_into(OrderRequest::limit(side, price, qty).with_tif(TimeInForce::Ioc), trades_out)
+    }
+
+    pub fn submit_market_into(&mut self, side: Side, qty: u64, trades_out: &mut Vec<Trade>) -> Result<(OrderId, u64), EngineError> {
+        self.submit_into(OrderRequest::market(side, qty), trades_out)
+    }
+
+    // Single entry point for new orders: pre-match hooks, matching, resting, post-trade hooks.
+    // A hook rejection returns its error before an id is assigned or the book is touched.
+    pub fn submit_into(&mut self, req: OrderRequest, trades_out: &mut Vec<Trade>) -> Result<(OrderId, u64), EngineError> {
+        let r = self.emitting(req.emission, trades_out, |ob, out| ob.submit_emitting(req, out));
+        if let (Err(e), false) = (&r, req.emission == Emission::Suppressed) { self.note_rejected(None, req.account, req.correlation, e); }
+        r
+    }
+
+    fn submit_emitting(&mut self, req: OrderRequest, trades_out: &mut Vec<Trade>) -> Result<(OrderId, u64), EngineError> {
+        self.with_client_id(req, trades_out, Self::enter_order)
+    }
+
+    fn enter_order(&mut self, mut req: OrderRequest, trades_out: &mut Vec<Trade>) -> Result<(OrderId, u64), EngineError> {
+        if self.session == SessionState::Halted { return Err(EngineError::Rejected("instrument halted".into())); }
+        if self.cancel_only { return Err(EngineError::Rejected("book in cancel-only mode".into())); }
+        self.check_throttle(req.account)?;
+        for hook in self.pre_match_hooks.iter_mut() { hook.pre_match(&mut req)?; }
+        self.check_symbol_config(|c| c.check_order(&req))?;
+        self.check_iceberg(&req)?;
+        self.check_entry(&mut req)?;
+        if let Some(stop) = req.stop_price { return self.enter_stop(stop, req, trades_out); }
+        if matches!(req.tif, TimeInForce::AtOpen | TimeInForce::AtClose) { return self.enter_auction(&req).map(|id| (id, req.qty)); }
+        let original = req.price;
+        if req.post_only {
+            req.price = self.post_only_price(&req)?;
+            if req.price != original { self.check_symbol_config(|c| c.check_price(req.price))?; self.check_storage_price(req.price)?; }
+        }
         let id = self.next_order_id();
+        self.note_event(|| EngineEvent::Accepted { id, req });
+        if req.price != original { self.note_repriced(id, original, req.price); }
+        let remaining = self.execute(id, req, trades_out);
+        self.trigger_stops(trades_out);
+        Ok((id, remaining))
+    }
+
+    // Checks every new order passes after the pre-match hooks, again when a stop activates
+    fn check_entry(&self, req: &mut OrderRequest) -> Result<(), EngineError> {
+        if req.reduce_only {
+            let cap = self.reduce_only_cap(req.account, req.side)?;
+            if req.qty > cap {
+                // Trimmed to whole lots, so the order still enters on the lot grid and within min_qty
+                req.qty = cap - cap % self.symbol_config.map_or(1, |c| c.lot.max(1));
+                if req.qty == 0 { return Err(EngineError::Rejected("reduce-only order would not reduce the position by a whole lot".into())); }
+                self.check_symbol_config(|c| c.check_qty(req.qty))?;
+            }
+        }
+        if req.short_sell { self.check_short_sale(req)?; }
+        if let Some((risk, instrument)) = &self.risk { risk.check(instrument, req.account, req.side, req.qty)?; }
+        if req.order_type == OrderType::Limit { self.check_band(req.side, req.price)?; self.check_storage_price(req.price)?; }
+        Ok(())
+    }
+
+    // The backend can add a level at `price` (see `BookStorage::can_hold`)
+    pub(crate) fn check_storage_price(&self, price: u64) -> Result<(), EngineError> {
+        if self.storage.can_hold(price) { return Ok(()); }
+        Err(EngineError::Rejected(format!("price {price} outside the range the book's storage can hold")))
+    }
+
+    // Match, rest and report an accepted order under the id it was given; returns the qty left
+    fn execute(&mut self, id: OrderId, req: OrderRequest, trades_out: &mut Vec<Trade>) -> u64 {
+        if req.tif == TimeInForce::Fok && !self.fully_fillable(&req) {
+            self.kill(id, &req);
+            return req.qty;
+        }
+        self.execute_slice(id, req, 0, trades_out)
+    }
+
+    // `execute` for `req.qty` still open after earlier slices filled `filled` (see `budget`)
+    pub(crate) fn execute_slice(&mut self, id: OrderId, req: OrderRequest, filled: u64, trades_out: &mut Vec<Trade>) -> u64 {
         let ts = self.now();
-        let mut remaining = qty;
-        match side {
-            Side::Buy => {
-                loop {
-                    if remaining == 0 { break; }
-                    let p_opt = self.asks.first_key_value().map(|(p, _)| *p);
-                    let p = match p_opt { Some(p) if p <= price => p, _ => break };
-                    if let Some(queue) = self.asks.get_mut(&p) {
-                        while remaining > 0 {
-                            if let Some(maker) = queue.front_mut() {
-                                let trade_qty = remaining.min(maker.qty);
-                                trades_out.push(Trade { taker_id: id, maker_id: maker.id, price: p, qty: trade_qty });
-                                maker.qty -= trade_qty;
-                                remaining -= trade_qty;
-                                if maker.qty == 0 { queue.pop_front(); } else { break; }
-                            } else { break; }
-                        }
-                        if queue.is_empty() { self.asks.remove(&p); }
-                    } else { break; }
-                }
-                if remaining > 0 {
-                    let o = Order { id, side, price, qty: remaining, order_type: OrderType::Limit, ts };
-                    self.bids.entry(price).or_default().push_back(o);
-                    self.index.insert(id.0, (Side::Buy, price));
-                }
+        let start_len = trades_out.len();
+        let limit = match req.order_type { OrderType::Limit => Some(req.price), OrderType::Market => None };
+        let budget = match req.tif { TimeInForce::Fok => u32::MAX, _ => self.match_budget.unwrap_or(u32::MAX) };
+        let (remaining, stp_cancelled, paused) = self.match_incoming(id, &req, limit, budget, trades_out);
+        if paused {
+            let filled = filled + req.qty - remaining - order_status::decremented(&trades_out[start_len..]);
+            self.continuations.push_back(budget::Continuation { id, req: OrderRequest { qty: remaining, ..req }, filled });
+        }
+        // A short sale stopped by the uptick rule while still marketable must not rest and cross the book
+        let stopped = paused || stp_cancelled || req.top_level_only || matches!(req.tif, TimeInForce::Ioc | TimeInForce::Fok) || req.short_sell && remaining > 0 && self.storage.best_price(Side::Buy).is_some_and(|b| b >= req.price);
+        if remaining > 0 && req.order_type == OrderType::Limit && !stopped {
+            let display_qty = req.display_qty.unwrap_or(0);
+            let (qty, reserve_qty) = iceberg::split(display_qty, remaining);
+            let order = Order { id, side: req.side, price: req.price, qty, order_type: OrderType::Limit, ts, tif: req.tif, account: req.account, reduce_only: req.reduce_only, correlation: req.correlation, display_qty, reserve_qty, order_qty: filled + req.qty - order_status::decremented(&trades_out[start_len..]) };
+            self.note_l3(|mode| L3Event::Add { id, side: order.side, price: order.price, qty, ts, owner: market_data::owner_of(mode, &order) });
+            self.storage.push_back(order);
+            self.index.insert(id.0, (req.side, req.price));
+            if req.reduce_only { self.reduce_only.entry(req.account).or_default().push(id); }
+            if self.min_resting.is_some() { self.note_rested(id); }
+        }
+        if self.positions.is_some() && (trades_out.len() > start_len || req.reduce_only) { self.recheck_reduce_only(req.account); }
+        if trades_out.len() > start_len {
+            for hook in self.post_trade_hooks.iter_mut() { hook.post_trade(&req, id, &mut trades_out[start_len..]); }
+            self.note_trades(&trades_out[start_len..]);
+            self.note_fill_report(id, &req, remaining, &trades_out[start_len..]);
+            self.note_fills(id, req.qty, &trades_out[start_len..]);
+        }
+        if remaining > 0 && !paused && (stopped || req.order_type == OrderType::Market) {
+            self.note_event(|| EngineEvent::Canceled { id, qty: remaining, reason: stp_cancelled.then_some(CancelReason::SelfTrade) });
+        }
+        remaining
+    }
+
+    // Match `req.qty` against the opposite side while prices are within `limit` (None = market), or only
+    // at the first level traded for `top_level_only`, through at most `budget` levels; returns the
+    // unfilled qty, whether self-trade prevention cancelled it (it must not rest) and whether the budget
+    // ran out with liquidity still in reach
+    pub(crate) fn match_incoming(&mut self, id: OrderId, req: &OrderRequest, limit: Option<u64>, budget: u32, trades_out: &mut Vec<Trade>) -> (u64, bool, bool) {
+        // Plain orders have nothing to check between levels and take the lean sweep
+        if self.stp.is_none() && !req.top_level_only && !req.short_sell {
+            let (remaining, paused) = self.sweep(id, req, limit, budget, trades_out);
+            return (remaining, false, paused);
+        }
+        let maker_side = req.side.opposite();
+        let mut remaining = req.qty;
+        let (mut level, mut levels) = (None, 0);
+        while remaining > 0 {
+            let Some(p) = self.storage.best_price(maker_side).filter(|&p| storage::crosses(maker_side, p, limit)) else { break };
+            if req.top_level_only && level.is_some_and(|l| l != p) { break; }
+            if level != Some(p) {
+                if levels == budget { return (remaining, false, true); }
+                levels += 1;
             }
-            Side::Sell => {
-                loop {
-                    if remaining == 0 { break; }
-                    let p_opt = self.bids.last_key_value().map(|(p, _)| *p);
-                    let p = match p_opt { Some(p) if p >= price => p, _ => break };
-                    if let Some(queue) = self.bids.get_mut(&p) {
-                        while remaining > 0 {
-                            if let Some(maker) = queue.front_mut() {
-                                let trade_qty = remaining.min(maker.qty);
-                                trades_out.push(Trade { taker_id: id, maker_id: maker.id, price: p, qty: trade_qty });
-                                maker.qty -= trade_qty;
-                                remaining -= trade_qty;
-                                if maker.qty == 0 { queue.pop_front(); } else { break; }
-                            } else { break; }
-                        }
-                        if queue.is_empty() { self.bids.remove(&p); }
-                    } else { break; }
-                }
-                if remaining > 0 {
-                    let o = Order { id, side, price, qty: remaining, order_type: OrderType::Limit, ts };
-                    self.asks.entry(price).or_default().push_back(o);
-                    self.index.insert(id.0, (Side::Sell, price));
+            level = Some(p);
+            if req.short_sell && !self.short_sale_allowed(p) { break; }
+            // With STP on, trade one maker at a time so each is checked before it fills
+            let mut step = remaining;
+            if let Some((action, head)) = self.stp.and_then(|a| self.best_head(maker_side).map(|h| (a, h))) {
+                if self.stp_groups.same_group(req.account, head.account) {
+                    if self.prevent_self_trade(action, id, req, head, &mut remaining, trades_out) { return (remaining, true, false); }
+                    continue;
                 }
+                step = remaining.min(head.qty);
             }
+            remaining -= step;
+            let (storage, on_fill) = self.fill_parts(id, req, trades_out);
+            remaining += storage.match_level(maker_side, p, step, on_fill);
+            self.note_trade_price(p);
+            if !self.refills.is_empty() { self.replenish(); }
         }
-        (id, remaining)
+        (remaining, false, false)
     }
 
-    pub fn submit_market_into(&mut self, side: Side, qty: u64, trades_out: &mut Vec<Trade>) -> (OrderId, u64) {
-        let id = self.next_order_id();
-        let _ts = self.now();
-        let mut remaining = qty;
-        match side {
-            Side::Buy => {
-                loop {
-                    if remaining == 0 { break; }
-                    let p_opt = self.asks.first_key_value().map(|(p, _)| *p);
-                    let p = match p_opt { Some(p) => p, None => break };
-                    if let Some(queue) = self.asks.get_mut(&p) {
-                        while remaining > 0 {
-                            if let Some(maker) = queue.front_mut() {
-                                let trade_qty = remaining.min(maker.qty);
-                                trades_out.push(Trade { taker_id: id, maker_id: maker.id, price: p, qty: trade_qty });
-                                maker.qty -= trade_qty;
-                                remaining -= trade_qty;
-                                if maker.qty == 0 { queue.pop_front(); } else { break; }
-                            } else { break; }
-                        }
-                        if queue.is_empty() { self.asks.remove(&p); }
-                    } else { break; }
-                }
-            }
-            Side::Sell => {
-                loop {
-                    if remaining == 0 { break; }
-                    let p_opt = self.bids.last_key_value().map(|(p, _)| *p);
-                    let p = match p_opt { Some(p) => p, None => break };
-                    if let Some(queue) = self.bids.get_mut(&p) {
-                        while remaining > 0 {
-                            if let Some(maker) = queue.front_mut() {
-                                let trade_qty = remaining.min(maker.qty);
-                                trades_out.push(Trade { taker_id: id, maker_id: maker.id, price: p, qty: trade_qty });
-                                maker.qty -= trade_qty;
-                                remaining -= trade_qty;
-                                if maker.qty == 0 { queue.pop_front(); } else { break; }
-                            } else { break; }
-                        }
-                        if queue.is_empty() { self.bids.remove(&p); }
-                    } else { break; }
-                }
+    // Level after level through `match_best`: one storage lookup per level for the price check and the fills
+    fn sweep(&mut self, id: OrderId, req: &OrderRequest, limit: Option<u64>, budget: u32, trades_out: &mut Vec<Trade>) -> (u64, bool) {
+        let (mut remaining, mut levels) = (req.qty, 0);
+        while remaining > 0 {
+            if levels == budget {
+                let maker_side = req.side.opposite();
+                return (remaining, self.storage.best_price(maker_side).is_some_and(|p| storage::crosses(maker_side, p, limit)));
             }
+            levels += 1;
+            let (storage, on_fill) = self.fill_parts(id, req, trades_out);
+            let Some((p, left)) = storage.match_best(req.side.opposite(), limit, remaining, on_fill) else { break };
+            remaining = left;
+            self.note_trade_price(p);
+            if !self.refills.is_empty() { self.replenish(); }
         }
-        (id, remaining)
+        (remaining, false)
+    }
+
+    // The storage and the per-fill bookkeeping (trade, L3, index, positions, risk), borrowed apart
+    // so fills are recorded from inside the storage's fill loop
+    fn fill_parts<'a>(&'a mut self, id: OrderId, req: &'a OrderRequest, trades_out: &'a mut Vec<Trade>) -> (&'a mut S, impl FnMut(&Order, u64) + 'a) {
+        let (side, maker_side, l3_on) = (req.side, req.side.opposite(), self.l3_on());
+        let (index, positions, reduce_only, touched) = (&mut self.index, &mut self.positions, &self.reduce_only, &mut self.touched);
+        let (l3_mode, l3, risk, conditions, refills) = (self.l3_mode, &mut self.l3, &self.risk, self.emission.conditions(), &mut self.refills);
+        let (depth_feed, output_seq, trade_id, ids, ts) = (&mut self.depth_feed, &mut self.output_seq, &mut self.last_trade_id, &*self.ids, self.ts);
+        (&mut self.storage, move |maker: &Order, trade_qty| {
+            let (p, maker_remaining) = (maker.price, maker.qty + maker.reserve_qty);
+            (*output_seq, *trade_id) = (*output_seq + 1, *trade_id + 1);
+            trades_out.push(Trade { trade_id: ids.trade_id(*trade_id), taker_id: id, maker_id: maker.id, price: p, qty: trade_qty, taker_side: side, taker_correlation: req.correlation, maker_correlation: maker.correlation, taker_account: req.account, maker_account: maker.account, maker_remaining, maker_fully_filled: maker_remaining == 0, ts, conditions, output_seq: *output_seq });
+            if l3_on {
+                let ev = L3Event::Execute { id: maker.id, side: maker_side, price: p, qty: trade_qty, remaining: maker.qty, owner: market_data::owner_of(l3_mode, maker) };
+                if let Some(feed) = depth_feed { feed.record(&ev, output_seq); }
+                l3.push(ev);
+            }
+            if maker.qty == 0 { if maker.reserve_qty > 0 { refills.push(maker.clone()); } else { index.remove(&maker.id.0); } }
+            positions::record_fill(positions, reduce_only, touched, side, req.account, maker.account, trade_qty);
+            if let Some((risk, instrument)) = risk { risk.record_fill(instrument, side, req.account, maker.account, trade_qty); }
+        })
     }
 
     // Simple batch API to reduce call overhead
@@ -346,33 +773,84 @@ impl OrderBook {
         for &(side, price, qty) in orders { let _ = self.submit_limit_into(side, price, qty, trades_out); }
     }
 
+    // (orders ahead, qty ahead) of a resting order within its price level; 0 ahead = next to fill
+    pub fn queue_position(&self, id: OrderId) -> Option<(usize, u64)> {
+        let &(side, price) = self.index.get(&id.0)?;
+        self.storage.queue_position(side, price, id)
+    }
+
+    // Resting orders at one price level in FIFO (priority) order, each with its priority timestamp `ts`
+    pub fn level_orders(&self, side: Side, price: u64) -> Vec<Order> {
+        let mut out = Vec::new();
+        self.storage.for_each_level(side, &mut |p, orders| {
+            if p == price { out.extend(orders.cloned()); }
+            p != price && storage::crosses(side, p, Some(price))
+        });
+        out
+    }
+
     pub fn cancel(&mut self, id: OrderId) -> Result<Order, EngineError> {
-        if let Some((side, price)) = self.index.remove(&id.0) {
-            let book = match side { Side::Buy => &mut self.bids, Side::Sell => &mut self.asks };
-            if let Some(queue) = book.get_mut(&price) {
-                let mut idx = None;
-                for (i, o) in queue.iter().enumerate() { if o.id == id { idx = Some(i); break; } }
-                if let Some(i) = idx {
-                    let o = queue.remove(i).unwrap();
-                    if queue.is_empty() { book.remove(&price); }
-                    return Ok(o);
-                }
-            }
+        if self.session == SessionState::Halted && self.halt_policy.resting == HaltAction::Keep { return Err(EngineError::Rejected("instrument halted, book frozen".into())); }
+        self.check_min_resting(id)?;
+        let o = match self.index.contains_key(&id.0) {
+            true => self.remove_resting(id)?,
+            false => self.cancel_stop(id).or_else(|| self.cancel_continuation(id)).map_or_else(|| self.cancel_auction(id), Ok)?,
+        };
+        self.note_event(|| EngineEvent::Canceled { id, qty: o.qty + o.reserve_qty, reason: None });
+        Ok(o)
+    }
+
+    // `cancel` for an owner: an order of another account is left alone and the cancel rejected
+    pub fn cancel_owned(&mut self, id: OrderId, account: AccountId) -> Result<Order, EngineError> {
+        match self.account_of(id) {
+            Some(owner) if owner != account => Err(EngineError::Rejected(format!("order {} belongs to another account", id.0))),
+            _ => self.cancel(id),
         }
-        Err(EngineError::UnknownOrder)
     }
 
-    pub fn best_bid(&self) -> Option<(u64, u64)> {
-        self.bids.iter().rev().next().map(|(p, q)| (*p, q.iter().map(|o| o.qty).sum()))
+    // Owner of a resting, held stop, paused or auction order
+    fn account_of(&mut self, id: OrderId) -> Option<AccountId> {
+        if let Some(&(side, price)) = self.index.get(&id.0) { return self.storage.update(side, price, id, |o| o.account); }
+        if let Some(req) = self.stop_order(id) { return Some(req.account); }
+        if let Some(account) = self.continuation_account(id) { return Some(account); }
+        self.auction.iter().find(|o| o.id == id).map(|o| o.account)
+    }
+
+    fn remove_resting(&mut self, id: OrderId) -> Result<Order, EngineError> {
+        let (side, price) = self.index.remove(&id.0).ok_or(EngineError::UnknownOrder)?;
+        let o = self.storage.remove(side, price, id).ok_or_else(|| faults::desync(id))?;
+        self.rest_times.remove(&id.0);
+        self.note_l3(|_| L3Event::Delete { id });
+        Ok(o)
     }
-    pub fn best_ask(&self) -> Option<(u64, u64)> {
-        self.asks.iter().next().map(|(p, q)| (*p, q.iter().map(|o| o.qty).sum()))
+
+    pub fn best_bid(&self) -> Option<(u64, u64)> { self.best(Side::Buy) }
+
+    pub fn best_ask(&self) -> Option<(u64, u64)> { self.best(Side::Sell) }
+
+    fn best(&self, side: Side) -> Option<(u64, u64)> {
+        let mut best = None;
+        self.storage.for_each_level_totals(side, &mut |p, qty, _| { best = Some((p, qty)); false });
+        best
     }
-    pub fn top_n(&self, n: usize) -> (Vec<(u64, u64)>, Vec<(u64, u64)>) {
-        let bids = self.bids.iter().rev().take(n).map(|(p, q)| (*p, q.iter().map(|o| o.qty).sum())).collect();
-        let asks = self.asks.iter().take(n).map(|(p, q)| (*p, q.iter().map(|o| o.qty).sum())).collect();
+
+    pub fn top_n(&self, n: usize) -> (Depth, Depth) {
+        let (mut bids, mut asks) = (Vec::new(), Vec::new());
+        self.top_n_into(n, &mut bids, &mut asks);
         (bids, asks)
     }
+
+    // Zero-allocation variant for polling: the outputs are cleared and refilled, keeping their capacity
+    pub fn top_n_into(&self, n: usize, bids_out: &mut Depth, asks_out: &mut Depth) {
+        self.depth_into(Side::Buy, n, bids_out);
+        self.depth_into(Side::Sell, n, asks_out);
+    }
+
+    fn depth_into(&self, side: Side, n: usize, levels: &mut Depth) {
+        levels.clear();
+        if n == 0 { return; }
+        self.storage.for_each_level_totals(side, &mut |p, qty, orders| { levels.push((p, qty, orders)); levels.len() < n });
+    }
 }
 
 #[cfg(test)]
@@ -381,8 +859,8 @@ mod tests {
     #[test]
     fn limit_crosses() {
         let mut ob = OrderBook::new();
-        let (_a, _t, _r) = ob.submit_limit(Side::Sell, 101, 5);
-        let (_b, trades, r) = ob.submit_limit(Side::Buy, 105, 7);
+        let (_a, _t, _r) = ob.submit_limit(Side::Sell, 101, 5).unwrap();
+        let (_b, trades, r) = ob.submit_limit(Side::Buy, 105, 7).unwrap();
         assert_eq!(trades.iter().map(|t| t.qty).sum::<u64>(), 5);
         assert_eq!(r, 2);
         assert_eq!(ob.best_bid().unwrap().0, 105);
@@ -390,9 +868,9 @@ mod tests {
     #[test]
     fn market_consumes() {
         let mut ob = OrderBook::new();
-        let (_a, _t, _r) = ob.submit_limit(Side::Sell, 100, 3);
-        let (_b, _t2, _r2) = ob.submit_limit(Side::Sell, 101, 3);
-        let (_m, trades, r) = ob.submit_market(Side::Buy, 5);
+        let (_a, _t, _r) = ob.submit_limit(Side::Sell, 100, 3).unwrap();
+        let (_b, _t2, _r2) = ob.submit_limit(Side::Sell, 101, 3).unwrap();
+        let (_m, trades, r) = ob.submit_market(Side::Buy, 5).unwrap();
         assert_eq!(trades.iter().map(|t| t.qty).sum::<u64>(), 5);
         assert_eq!(r, 0);
         assert_eq!(ob.best_ask().unwrap().0, 101);
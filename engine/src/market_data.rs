@@ -0,0 +1,103 @@
+use crate::session::SessionState;
+use crate::{AccountId, BookStorage, Depth, Order, OrderBook, OrderId, ReferenceValues, SessionStats, Side, TapeEntry};
+
+// Order-by-order (L3) market data for the continuous book, off by default. `Attributed` events
+// carry the owner of each order (account and correlation tag) for internal consumers such as risk;
+// `Anonymous` ones never do. `L3Event::public` strips an attributed event, so one engine can feed
+// an internal and a public-style feed from the same stream.
+//   Add      an order came to rest; `ts` is its priority timestamp, FIFO within a level follows it
+//   Execute  a resting order traded (`remaining` 0 = filled and gone)
+//   Update   a resting order's qty shrank in place, keeping its priority (reduce-only trims)
+//   Delete   a resting order was cancelled or expired
+// Auction-only orders never rest and produce no events; resting orders filled in an uncross do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attribution {
+    Anonymous,
+    Attributed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Owner {
+    pub account: AccountId,
+    pub correlation: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum L3Event {
+    Add { id: OrderId, side: Side, price: u64, qty: u64, ts: u64, owner: Option<Owner> },
+    Execute { id: OrderId, side: Side, price: u64, qty: u64, remaining: u64, owner: Option<Owner> },
+    Update { id: OrderId, qty: u64 },
+    Delete { id: OrderId },
+}
+
+impl L3Event {
+    // The same event with owner attribution removed
+    pub fn public(self) -> Self {
+        match self {
+            Self::Add { id, side, price, qty, ts, .. } => Self::Add { id, side, price, qty, ts, owner: None },
+            Self::Execute { id, side, price, qty, remaining, .. } => Self::Execute { id, side, price, qty, remaining, owner: None },
+            other => other,
+        }
+    }
+}
+
+// Depth, recent trades, session stats and reference values captured together, e.g. as the starting point for a
+// consumer joining the feeds. `next_seq` is the caller's command sequence the book is at (seq of the
+// next command to apply, as in `SymbolSnapshot`); `trades` is empty while the trade tape is off.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketDataSnapshot {
+    pub next_seq: u64,
+    pub session: SessionState,
+    pub bids: Depth,
+    pub asks: Depth,
+    pub trades: Vec<TapeEntry>,
+    pub last_trade_id: u64,
+    pub stats: SessionStats,
+    pub reference: ReferenceValues,
+}
+
+pub(crate) fn owner_of(mode: Option<Attribution>, o: &Order) -> Option<Owner> {
+    (mode == Some(Attribution::Attributed)).then_some(Owner { account: o.account, correlation: o.correlation })
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    // None turns the feed off and drops undrained events
+    pub fn set_market_data(&mut self, mode: Option<Attribution>) {
+        self.sync_depth();
+        self.l3_mode = mode;
+        if mode.is_none() { self.l3.clear(); self.l3_taken(); }
+    }
+
+    pub fn market_data(&self) -> Option<Attribution> { self.l3_mode }
+
+    // Events since the previous drain, in the order they happened
+    pub fn drain_l3(&mut self) -> Vec<L3Event> {
+        let mut out = Vec::new();
+        self.drain_l3_into(&mut out);
+        out
+    }
+
+    pub fn drain_l3_into(&mut self, out: &mut Vec<L3Event>) {
+        self.sync_depth();
+        out.append(&mut self.l3);
+        self.l3_taken();
+    }
+
+    // Top `levels` per side and the last `trades` tape entries
+    pub fn market_data_snapshot(&self, next_seq: u64, levels: usize, trades: usize) -> MarketDataSnapshot {
+        let (bids, asks) = self.top_n(levels);
+        MarketDataSnapshot {
+            next_seq, session: self.session, bids, asks,
+            trades: self.last_n_trades(trades).cloned().collect(),
+            last_trade_id: self.trade_tape().map_or(0, |t| t.last_id()),
+            stats: self.stats,
+            reference: self.refdata,
+        }
+    }
+
+    pub(crate) fn note_l3(&mut self, ev: impl FnOnce(Option<Attribution>) -> L3Event) {
+        if !self.l3_on() { return; }
+        self.l3.push(ev(self.l3_mode));
+        self.sync_depth();
+    }
+}
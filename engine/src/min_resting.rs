@@ -0,0 +1,78 @@
+use crate::{BookStorage, CancelReason, EngineError, HaltAction, ImplicitCancel, OrderBook, OrderId, SessionState};
+
+// Minimum resting time (per instrument, off by default): an order must rest `micros` on the book,
+// measured on the book's clock (see `clock`), before it may be cancelled, so quotes cannot flicker.
+//   Reject  an early cancel fails with `EngineError::Rejected`
+//   Defer   an early cancel is queued and carried out once the order has rested long enough; `cancel`
+//           returns `EngineError::Deferred`, a sequenced Cancel command succeeds, and the removal is
+//           reported when it happens as an `ImplicitCancel` with `CancelReason::Deferred`
+// Deferred cancels are released at the start of every command the book processes, or by
+// `release_deferred_cancels` (e.g. from a timer on a quiet book); an order that traded away or was
+// removed otherwise in the meantime is skipped, and a frozen halt holds the queue. Only Cancel is
+// restricted: fills, reprices and removals by the engine are not. Rest times are recorded only while
+// the minimum is set (orders resting from before are free to cancel) and are not part of snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinRestingAction {
+    Reject,
+    Defer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinResting {
+    pub micros: u64,
+    pub action: MinRestingAction,
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn set_min_resting(&mut self, rule: Option<MinResting>) {
+        self.min_resting = rule;
+        if rule.is_none() { self.rest_times.clear(); }
+    }
+
+    pub fn min_resting(&self) -> Option<MinResting> { self.min_resting }
+
+    // Cancels waiting for their order's minimum resting time, (due at, id) in arrival order
+    pub fn deferred_cancels(&self) -> &[(u64, OrderId)] { &self.deferred_cancels }
+
+    pub fn next_deferred_due(&self) -> Option<u64> { self.deferred_cancels.iter().map(|&(due, _)| due).min() }
+
+    // Carry out the deferred cancels now due; returns how many orders were removed
+    pub fn release_deferred_cancels(&mut self) -> Result<usize, EngineError> {
+        if self.deferred_cancels.is_empty() || self.session == SessionState::Halted && self.halt_policy.resting == HaltAction::Keep { return Ok(0); }
+        let now = self.clock.now_micros();
+        let mut released = 0;
+        for (due, id) in std::mem::take(&mut self.deferred_cancels) {
+            if due > now { self.deferred_cancels.push((due, id)); continue; }
+            if !self.index.contains_key(&id.0) { continue; }
+            let o = self.remove_resting(id)?;
+            self.note_cancel(ImplicitCancel::of(&o, CancelReason::Deferred));
+            released += 1;
+        }
+        Ok(released)
+    }
+
+    pub(crate) fn note_rested(&mut self, id: OrderId) {
+        // Entries of orders gone by other paths are dropped in bulk once they pile up
+        if self.rest_times.len() > 2 * self.index.len() + 1024 {
+            let index = &self.index;
+            self.rest_times.retain(|id, _| index.contains_key(id));
+        }
+        self.rest_times.insert(id.0, self.clock.now_micros());
+    }
+
+    // Err when `id` rests and has not rested long enough to be cancelled
+    pub(crate) fn check_min_resting(&mut self, id: OrderId) -> Result<(), EngineError> {
+        let Some(rule) = self.min_resting else { return Ok(()) };
+        if !self.index.contains_key(&id.0) { return Ok(()); }
+        let Some(&rested) = self.rest_times.get(&id.0) else { return Ok(()) };
+        let (now, due) = (self.clock.now_micros(), rested.saturating_add(rule.micros));
+        if now >= due { return Ok(()); }
+        match rule.action {
+            MinRestingAction::Reject => Err(EngineError::Rejected(format!("order rested {}us of the minimum {}us", now.saturating_sub(rested), rule.micros))),
+            MinRestingAction::Defer => {
+                if !self.deferred_cancels.iter().any(|&(_, d)| d == id) { self.deferred_cancels.push((due, id)); }
+                Err(EngineError::Deferred { until: due })
+            }
+        }
+    }
+}
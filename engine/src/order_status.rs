@@ -0,0 +1,64 @@
+use crate::{stops, BookStorage, Order, OrderBook, OrderId, Side, Trade, TradeConditions};
+
+// Order status queries for live orders, without touching them. `get_order` returns a resting or
+// auction order as stored (an iceberg's `qty` is its displayed slice); `order_status` also covers
+// held stops and summarises the quantities:
+//   open      still working: displayed plus reserve
+//   filled    executed so far, across partial fills and iceberg slices
+//   original  open plus filled (`Order::order_qty`); an amend sets the open part, so it becomes the
+//             filled quantity plus the amended one, and reduce-only trims and self-trade decrements
+//             lower it without a fill
+// Orders that are gone (filled, cancelled, expired) are not kept; their last state is in the trade,
+// cancel and L3 streams. A resting order restored from a snapshot keeps its filled quantity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderState {
+    // Resting with nothing filled yet
+    New,
+    PartiallyFilled,
+    // Held stop, not yet triggered
+    PendingTrigger,
+    // Waiting for the opening or closing uncross
+    PendingAuction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderStatus {
+    pub id: OrderId,
+    pub side: Side,
+    pub price: u64,
+    pub state: OrderState,
+    pub original_qty: u64,
+    pub open_qty: u64,
+    pub filled_qty: u64,
+}
+
+impl OrderStatus {
+    fn of(o: &Order, state: OrderState) -> Self {
+        let open_qty = o.qty + o.reserve_qty;
+        Self { id: o.id, side: o.side, price: o.price, state, original_qty: o.order_qty, open_qty, filled_qty: o.order_qty - open_qty }
+    }
+}
+
+// Quantity an incoming order lost to self-trade decrements in `trades` (its own matching), not filled
+pub(crate) fn decremented(trades: &[Trade]) -> u64 {
+    trades.iter().filter(|t| t.conditions.contains(TradeConditions::STP_DECREMENT)).map(|t| t.qty).sum()
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    // A resting order, or an auction order waiting for its uncross
+    pub fn get_order(&self, id: OrderId) -> Option<&Order> {
+        match self.index.get(&id.0) {
+            Some(&(side, price)) => self.storage.get(side, price, id),
+            None => self.auction.iter().find(|o| o.id == id),
+        }
+    }
+
+    pub fn order_status(&self, id: OrderId) -> Option<OrderStatus> {
+        if let Some(req) = self.stop_order(id) {
+            return Some(OrderStatus::of(&stops::held(id, &req), OrderState::PendingTrigger));
+        }
+        let o = self.get_order(id)?;
+        let state = if !self.index.contains_key(&id.0) { OrderState::PendingAuction } else if o.qty + o.reserve_qty < o.order_qty { OrderState::PartiallyFilled } else { OrderState::New };
+        Some(OrderStatus::of(o, state))
+    }
+}
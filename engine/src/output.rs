@@ -0,0 +1,77 @@
+use crate::{BookStorage, Command, EngineError, OrderBook, OrderId, Trade, TradeConditions};
+
+// Per-command output control, for flow that has to change the book without passing for live
+// trading (bulk book seeding, replaying a journal after a restart). Carried by `OrderRequest::emission`
+// (sequenced with the order, so replicas and journal replay see it) or given for any command with
+// `process_command_as`.
+//   Live        default: trades, L3 events and implicit cancels are emitted as usual
+//   Suppressed  the command matches, rests and moves session stats, positions and risk as usual, but
+//               its trades are not handed back (nor put on the tape), and its L3 events, depth
+//               deltas, implicit cancels and fill reports are dropped; post-trade hooks still see the trades
+//   Replay      everything is emitted, trades carrying `TradeConditions::REPLAY` and implicit cancels
+//               and fill reports `replay: true`, so consumers can tell recovery artifacts from live
+//               fills (L3 events have no marker and are emitted unchanged)
+//
+// Output sequence: every trade, depth delta, implicit cancel, fill report and `Repriced` event gets
+// the next number of one per-book sequence (`output_seq`, from 1) as it is made, so consumers
+// reading several channels can merge them into the order things happened and spot a lost record
+// as a gap. `current_output_seq` is the last number assigned. Suppressed output takes no numbers
+// (its `Repriced` events are dropped along with the rest); L3 events are not numbered. Records
+// only exist for the feeds that are on, so two books number alike only with the same feeds on. The
+// sequence is kept in snapshots.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug, PartialEq, Eq)))]
+pub enum Emission {
+    #[default]
+    Live,
+    Suppressed,
+    Replay,
+}
+
+impl Emission {
+    // Conditions added to every trade produced under this emission
+    pub fn conditions(self) -> TradeConditions {
+        if self == Emission::Replay { TradeConditions::REPLAY } else { TradeConditions::NONE }
+    }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    // `process_command` with `emission` applied to everything the command produces; a Submit's own
+    // `emission` is overridden
+    pub fn process_command_as(&mut self, cmd: Command, emission: Emission, trades_out: &mut Vec<Trade>) -> Result<(OrderId, u64), EngineError> {
+        let cmd = match cmd { Command::Submit { seq, mut req } => { req.emission = emission; Command::Submit { seq, req } } _ => cmd };
+        self.emitting(emission, trades_out, |ob, out| ob.process_command(cmd, out))
+    }
+
+    pub fn current_output_seq(&self) -> u64 { self.output_seq }
+
+    pub(crate) fn next_output_seq(&mut self) -> u64 { self.output_seq += 1; self.output_seq }
+
+    // Run `f` with `emission` in force: trades are marked as they are made (so hooks and the tape see
+    // the mark), suppressed output is cut off afterwards
+    pub(crate) fn emitting<R>(&mut self, emission: Emission, trades_out: &mut Vec<Trade>, f: impl FnOnce(&mut Self, &mut Vec<Trade>) -> R) -> R {
+        if emission == Emission::Live || self.emission != Emission::Live { return f(self, trades_out); }
+        self.sync_depth();
+        let (trades, l3, deltas, cancels, reports) = (trades_out.len(), self.l3.len(), self.depth_deltas_len(), self.implicit_cancels.len(), self.fill_reports.len());
+        let (repriced, output_seq) = (self.repriced.len(), self.output_seq);
+        self.emission = emission;
+        let r = f(self, trades_out);
+        self.emission = Emission::Live;
+        match emission {
+            Emission::Suppressed => {
+                trades_out.truncate(trades);
+                self.sync_depth_quietly(l3, deltas);
+                self.implicit_cancels.truncate(cancels);
+                self.fill_reports.truncate(reports);
+                self.repriced.truncate(repriced);
+                self.output_seq = output_seq;
+            }
+            _ => {
+                for c in &mut self.implicit_cancels[cancels..] { c.replay = true; }
+                for r in &mut self.fill_reports[reports..] { r.replay = true; }
+            }
+        }
+        r
+    }
+}
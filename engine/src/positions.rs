@@ -0,0 +1,85 @@
+use crate::cancels::{CancelReason, ImplicitCancel};
+use crate::{AccountId, BookStorage, EngineError, L3Event, OrderBook, OrderId, Side};
+use std::collections::HashMap;
+
+// Net position per account (buys minus sells, in lots), updated from fills once tracking is enabled.
+// Reduce-only orders are capped by the position in the opposite direction: at entry (rejected when
+// flat or on the wrong side) and again whenever the position moves, oldest resting orders keeping
+// their quantity first. Trims and cancels are reported through `drain_reduce_only_updates`.
+impl<S: BookStorage> OrderBook<S> {
+    pub fn enable_positions(&mut self) {
+        if self.positions.is_none() { self.positions = Some(HashMap::new()); }
+    }
+
+    pub fn position(&self, account: AccountId) -> i64 {
+        self.positions.as_ref().and_then(|p| p.get(&account).copied()).unwrap_or(0)
+    }
+
+    // Seed or correct a position (e.g. after a restart); enables tracking
+    pub fn set_position(&mut self, account: AccountId, qty: i64) {
+        self.positions.get_or_insert_with(HashMap::new).insert(account, qty);
+        self.recheck_reduce_only(account);
+    }
+
+    // (order, new qty) for reduce-only orders trimmed since the last call; qty 0 means cancelled
+    pub fn drain_reduce_only_updates(&mut self) -> Vec<(OrderId, u64)> { std::mem::take(&mut self.reduce_only_updates) }
+
+    pub(crate) fn reduce_only_cap(&self, account: AccountId, side: Side) -> Result<u64, EngineError> {
+        if self.positions.is_none() { return Err(EngineError::Rejected("reduce-only needs position tracking".into())); }
+        let pos = self.position(account);
+        let cap = match side { Side::Buy => (-pos).max(0), Side::Sell => pos.max(0) } as u64;
+        if cap == 0 { return Err(EngineError::Rejected("reduce-only order would not reduce the position".into())); }
+        Ok(cap)
+    }
+
+    // Re-apply the caps for `extra` and every account whose position moved in the last match
+    pub(crate) fn recheck_reduce_only(&mut self, extra: AccountId) {
+        let mut accounts = std::mem::take(&mut self.touched);
+        if !accounts.contains(&extra) { accounts.push(extra); }
+        for &account in &accounts {
+            let Some(mut ids) = self.reduce_only.remove(&account) else { continue };
+            let pos = self.position(account);
+            let mut caps = [(-pos).max(0) as u64, pos.max(0) as u64]; // [buy, sell]
+            ids.retain(|id| {
+                let Some(&(side, price)) = self.index.get(&id.0) else { return false };
+                let cap = &mut caps[matches!(side, Side::Sell) as usize];
+                let trim = *cap;
+                let Some(qty) = self.storage.update(side, price, *id, |o| { let qty = o.qty; if qty > trim && trim > 0 { o.order_qty -= qty - trim; o.qty = trim; } qty }) else { return false };
+                if qty <= *cap { *cap -= qty; return true; }
+                if *cap > 0 {
+                    let qty = *cap;
+                    self.note_l3(|_| L3Event::Update { id: *id, qty });
+                    self.reduce_only_updates.push((*id, *cap));
+                    *cap = 0;
+                    return true;
+                }
+                self.index.remove(&id.0);
+                if let Some(o) = self.storage.remove(side, price, *id) { self.note_cancel(ImplicitCancel::of(&o, CancelReason::ReduceOnly)); }
+                self.note_l3(|_| L3Event::Delete { id: *id });
+                self.reduce_only_updates.push((*id, 0));
+                false
+            });
+            if !ids.is_empty() { self.reduce_only.insert(account, ids); }
+        }
+        accounts.clear();
+        self.touched = accounts;
+    }
+}
+
+// Apply one fill to the tracked positions and note makers whose reduce-only orders need a recheck
+#[inline]
+pub(crate) fn record_fill(
+    positions: &mut Option<HashMap<AccountId, i64>>,
+    reduce_only: &HashMap<AccountId, Vec<OrderId>>,
+    touched: &mut Vec<AccountId>,
+    taker_side: Side,
+    taker: AccountId,
+    maker: AccountId,
+    qty: u64,
+) {
+    let Some(pos) = positions.as_mut() else { return };
+    let signed = match taker_side { Side::Buy => qty as i64, Side::Sell => -(qty as i64) };
+    *pos.entry(taker).or_default() += signed;
+    *pos.entry(maker).or_default() -= signed;
+    if reduce_only.contains_key(&maker) && !touched.contains(&maker) { touched.push(maker); }
+}
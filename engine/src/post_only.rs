@@ -0,0 +1,90 @@
+use crate::{BookStorage, EngineError, OrderBook, OrderId, OrderRequest, OrderType, Side};
+
+// What a post-only limit does when its price would take liquidity (at or through the opposite
+// touch). `Reject` turns it away; `Reprice` moves it to `tick` behind the opposite touch so it rests
+// as the new best price on its side, and records a `Repriced` event with the original and adjusted
+// price (drain after each command or batch) so the client learns its effective price. A post-only
+// order that does not cross is entered unchanged. Per book, Reject by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PostOnlyPolicy {
+    #[default]
+    Reject,
+    Reprice { tick: u64 },
+}
+
+// Top-of-book protection for price modifications (`amend` to a new price and `reprice`): what a
+// resting order moved to a price at or through the opposite touch does. `Take` (the default) lets it
+// trade first as taker; `Reject` turns the modification away and leaves the order as it was;
+// `Reprice` moves it to `tick` behind the opposite touch, recorded as a `Repriced` event as for
+// post-only, so a modified order never takes liquidity. The adjusted price goes through the same tick,
+// band and storage checks as a requested one, all before anything about the order changes; a move
+// that does not cross is applied unchanged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AmendCrossPolicy {
+    #[default]
+    Take,
+    Reject,
+    Reprice { tick: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repriced {
+    pub id: OrderId,
+    pub original: u64,
+    pub adjusted: u64,
+    pub output_seq: u64,
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn set_post_only_policy(&mut self, policy: PostOnlyPolicy) { self.post_only = policy; }
+
+    pub fn post_only_policy(&self) -> PostOnlyPolicy { self.post_only }
+
+    pub fn set_amend_cross_policy(&mut self, policy: AmendCrossPolicy) { self.amend_cross = policy; }
+
+    pub fn amend_cross_policy(&self) -> AmendCrossPolicy { self.amend_cross }
+
+    pub fn drain_repriced(&mut self) -> Vec<Repriced> { std::mem::take(&mut self.repriced) }
+
+    pub fn drain_repriced_into(&mut self, out: &mut Vec<Repriced>) { out.append(&mut self.repriced); }
+
+    pub(crate) fn note_repriced(&mut self, id: OrderId, original: u64, adjusted: u64) {
+        let output_seq = self.next_output_seq();
+        self.repriced.push(Repriced { id, original, adjusted, output_seq });
+    }
+
+    // Price a post-only request may rest at, before it is assigned an id
+    pub(crate) fn post_only_price(&self, req: &OrderRequest) -> Result<u64, EngineError> {
+        if req.order_type != OrderType::Limit { return Err(EngineError::Rejected("post-only needs a limit price".into())); }
+        let Some(touch) = self.crossed_touch(req.side, req.price) else { return Ok(req.price) };
+        let adjusted = match self.post_only {
+            PostOnlyPolicy::Reject => None,
+            PostOnlyPolicy::Reprice { tick } => behind(req.side, touch, tick),
+        };
+        adjusted.ok_or_else(|| EngineError::Rejected("post-only order would take liquidity".into()))
+    }
+
+    // Price a resting order may be moved to under the amend cross policy; the caller records the
+    // `Repriced` event once the move has passed every check
+    pub(crate) fn amend_cross_price(&self, side: Side, new_price: u64) -> Result<u64, EngineError> {
+        let Some(touch) = self.crossed_touch(side, new_price) else { return Ok(new_price) };
+        let adjusted = match self.amend_cross {
+            AmendCrossPolicy::Take => return Ok(new_price),
+            AmendCrossPolicy::Reject => None,
+            AmendCrossPolicy::Reprice { tick } => behind(side, touch, tick),
+        };
+        adjusted.ok_or_else(|| EngineError::Rejected("amend would take liquidity".into()))
+    }
+
+    // The opposite touch when `price` on `side` is at or through it
+    fn crossed_touch(&self, side: Side, price: u64) -> Option<u64> {
+        self.storage.best_price(side.opposite()).filter(|&t| match side { Side::Buy => price >= t, Side::Sell => price <= t })
+    }
+}
+
+fn behind(side: Side, touch: u64, tick: u64) -> Option<u64> {
+    match side {
+        Side::Buy => touch.checked_sub(tick.max(1)).filter(|&p| p > 0),
+        Side::Sell => touch.checked_add(tick.max(1)),
+    }
+}
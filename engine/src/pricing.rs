@@ -0,0 +1,89 @@
+use crate::{BookStorage, OrderBook, Side};
+
+// Book analytics and indicative prices for quoting logic built directly on the book. Everything is
+// derived in one best-first pass over at most `levels` levels per side (or as many as `vwap` needs)
+// using the storage's level aggregates (`BookStorage::for_each_level_totals`; SlabStorage and
+// SoaStorage keep them cached per level), so no individual order is visited. All of them are None
+// while either side is empty, except `vwap`, which needs only the side it walks.
+//   mid_price     (best bid + best ask) / 2
+//   spread        best ask - best bid, in ticks of the price unit
+//   vwap          average price a taker on `side` would fill `qty` at by walking the opposite side,
+//                 None when that side does not hold `qty`
+//   weighted_mid  each side's qty-weighted average price over the window, weighted by the
+//                 opposite side's qty: (bid_avg * ask_qty + ask_avg * bid_qty) / (bid_qty + ask_qty)
+//   imbalance     (bid_qty - ask_qty) / (bid_qty + ask_qty), in -1..=1
+//   fair_price    mid shifted toward the heavier side by `imbalance * skew` half spreads
+// With `levels` 1 and `skew` 1 the fair price equals the weighted mid at the touch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SideAggregate {
+    pub levels: usize,
+    pub qty: u64,
+    // Sum of price * qty
+    pub notional: u128,
+    // Best and worst price inside the window
+    pub best: u64,
+    pub worst: u64,
+}
+
+impl SideAggregate {
+    pub fn avg_price(&self) -> Option<f64> { (self.qty > 0).then(|| self.notional as f64 / self.qty as f64) }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    // Totals over the best `levels` levels of `side`
+    pub fn side_aggregate(&self, side: Side, levels: usize) -> SideAggregate {
+        let mut agg = SideAggregate::default();
+        if levels == 0 { return agg; }
+        self.storage.for_each_level_totals(side, &mut |p, qty, _| {
+            if agg.levels == 0 { agg.best = p; }
+            agg.worst = p;
+            agg.levels += 1;
+            agg.qty += qty;
+            agg.notional += p as u128 * qty as u128;
+            agg.levels < levels
+        });
+        agg
+    }
+
+    fn both_sides(&self, levels: usize) -> Option<(SideAggregate, SideAggregate)> {
+        let (bids, asks) = (self.side_aggregate(Side::Buy, levels), self.side_aggregate(Side::Sell, levels));
+        (bids.qty > 0 && asks.qty > 0).then_some((bids, asks))
+    }
+
+    pub fn mid_price(&self) -> Option<f64> {
+        let (bid, ask) = (self.best_bid()?.0, self.best_ask()?.0);
+        Some((bid + ask) as f64 / 2.0)
+    }
+
+    pub fn spread(&self) -> Option<u64> { Some(self.best_ask()?.0 - self.best_bid()?.0) }
+
+    pub fn vwap(&self, side: Side, qty: u64) -> Option<f64> {
+        let (mut left, mut notional) = (qty, 0u128);
+        self.storage.for_each_level_totals(side.opposite(), &mut |p, level_qty, _| {
+            let take = left.min(level_qty);
+            notional += p as u128 * take as u128;
+            left -= take;
+            left > 0
+        });
+        (qty > 0 && left == 0).then(|| notional as f64 / qty as f64)
+    }
+
+    pub fn weighted_mid(&self, levels: usize) -> Option<f64> {
+        let (bids, asks) = self.both_sides(levels)?;
+        let (bq, aq) = (bids.qty as f64, asks.qty as f64);
+        Some((bids.avg_price()? * aq + asks.avg_price()? * bq) / (bq + aq))
+    }
+
+    pub fn imbalance(&self, levels: usize) -> Option<f64> {
+        let (bids, asks) = self.both_sides(levels)?;
+        Some(imbalance_of(bids.qty, asks.qty))
+    }
+
+    pub fn fair_price(&self, levels: usize, skew: f64) -> Option<f64> {
+        let (bids, asks) = self.both_sides(levels)?;
+        let (bid, ask) = (bids.best as f64, asks.best as f64);
+        Some((bid + ask) / 2.0 + imbalance_of(bids.qty, asks.qty) * skew * (ask - bid) / 2.0)
+    }
+}
+
+fn imbalance_of(bid_qty: u64, ask_qty: u64) -> f64 { (bid_qty as f64 - ask_qty as f64) / (bid_qty + ask_qty) as f64 }
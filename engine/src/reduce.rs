@@ -0,0 +1,30 @@
+use crate::{faults, BookStorage, EngineError, HaltAction, L3Event, OrderBook, OrderId, SessionState};
+
+// Partial cancel (`OrderBook::reduce`, `Command::Reduce`): take `qty_delta` off a resting order's open
+// quantity in place, keeping its price and queue position. It is the quantity-only decrease of `amend`
+// without the new total: an iceberg loses reserve first and its displayed slice only once the reserve
+// is gone (reported as an L3 Update). Being a cancel of part of the order, it follows the cancel rules
+// rather than the amend ones: allowed in cancel-only mode and while halted unless the book is frozen.
+// Reducing by the whole open quantity or more cancels the order (`cancel`, with its minimum resting
+// time); a smaller reduce is never held back. Held stops and auction orders cannot be reduced.
+impl<S: BookStorage> OrderBook<S> {
+    // Returns the qty left open (0 when the order was cancelled)
+    pub fn reduce(&mut self, id: OrderId, qty_delta: u64) -> Result<u64, EngineError> {
+        if qty_delta == 0 { return Err(EngineError::Rejected("reduce by zero quantity".into())); }
+        if self.session == SessionState::Halted && self.halt_policy.resting == HaltAction::Keep { return Err(EngineError::Rejected("instrument halted, book frozen".into())); }
+        let Some(&(side, price)) = self.index.get(&id.0) else {
+            if self.stop_order(id).is_some() || self.auction.iter().any(|o| o.id == id) { return Err(EngineError::Rejected("only resting orders can be reduced".into())); }
+            return Err(EngineError::UnknownOrder);
+        };
+        let total = self.storage.update(side, price, id, |o| o.qty + o.reserve_qty).ok_or_else(|| faults::desync(id))?;
+        if qty_delta < total { self.check_symbol_config(|c| c.check_lot(qty_delta))?; }
+        if qty_delta >= total { return self.cancel(id).map(|_| 0); }
+        let shown = self.storage.update(side, price, id, |o| {
+            (o.qty, o.reserve_qty) = (o.qty.min(total - qty_delta), o.reserve_qty.saturating_sub(qty_delta));
+            o.order_qty -= qty_delta;
+            o.qty
+        }).ok_or_else(|| faults::desync(id))?;
+        self.note_l3(|_| L3Event::Update { id, qty: shown });
+        Ok(total - qty_delta)
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{BookStorage, EngineError, OrderBook, Side};
+
+// External reference values injected per instrument (index price from the spot feed, settlement
+// price from the clearing house, funding rate from the funding job). The book only stores them:
+// they are set by `Command::Reference` (sequenced, so replicas and replay see them in order) or
+// `set_reference_value`, carried in snapshots, and read by
+//   pricing   `premium` (weighted mid against the index)
+//   band      `set_price_band`: limit prices further than `bps` basis points from the reference
+//             price (index, else settlement) are rejected; no reference, no check
+//   triggers  index conditions in the ingestor's cross-instrument triggers
+// Funding rates are signed parts per million per funding interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug, PartialEq, Eq)))]
+pub enum ReferenceValue {
+    IndexPrice(u64),
+    SettlementPrice(u64),
+    FundingRate(i64),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReferenceValues {
+    pub index_price: Option<u64>,
+    pub settlement_price: Option<u64>,
+    pub funding_rate: Option<i64>,
+}
+
+impl ReferenceValues {
+    pub fn apply(&mut self, value: ReferenceValue) {
+        match value {
+            ReferenceValue::IndexPrice(p) => self.index_price = Some(p),
+            ReferenceValue::SettlementPrice(p) => self.settlement_price = Some(p),
+            ReferenceValue::FundingRate(r) => self.funding_rate = Some(r),
+        }
+    }
+
+    // Price bands are anchored on: the index, or the settlement price before the first index
+    pub fn reference_price(&self) -> Option<u64> { self.index_price.or(self.settlement_price) }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn set_reference_value(&mut self, value: ReferenceValue) { self.refdata.apply(value); }
+
+    pub fn reference_values(&self) -> ReferenceValues { self.refdata }
+
+    // Band width in basis points around the reference price; None turns the check off
+    pub fn set_price_band(&mut self, bps: Option<u64>) { self.price_band = bps; }
+
+    pub fn price_band(&self) -> Option<u64> { self.price_band }
+
+    // Weighted mid minus the index price; None without an index or a two-sided book
+    pub fn premium(&self, levels: usize) -> Option<f64> {
+        let index = self.refdata.index_price?;
+        Some(self.weighted_mid(levels)? - index as f64)
+    }
+
+    pub(crate) fn check_band(&self, side: Side, price: u64) -> Result<(), EngineError> {
+        let (Some(bps), Some(reference)) = (self.price_band, self.refdata.reference_price()) else { return Ok(()) };
+        let width = (reference as u128 * bps as u128 / 10_000) as u64;
+        let (low, high) = (reference.saturating_sub(width), reference.saturating_add(width));
+        if (low..=high).contains(&price) { return Ok(()); }
+        Err(EngineError::Rejected(format!("{side:?} price {price} outside band {low}..={high}")))
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{AccountId, BookStorage, Command, Depth, OrderBook, OrderId, Side, Trade, TradeConditions, TradeId};
+
+// Deliberately naive price-time priority book, the oracle for differential tests and the fuzz
+// target (engine/fuzz): resting orders in one Vec in arrival order, the next maker found by a
+// linear scan for the best price and then the oldest order. No hooks, attributes or sessions;
+// ids and timestamps are assigned like OrderBook's (from 1, one per limit or market order), trade ids
+// from 1 and trades numbered like its output sequence (it makes no other records).
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceBook {
+    resting: Vec<(OrderId, Side, u64, u64)>, // (id, side, price, qty), oldest first
+    next_id: u64,
+    output_seq: u64,
+}
+
+impl ReferenceBook {
+    pub fn new() -> Self { Self::default() }
+
+    // `price` None = market; returns (id, unfilled qty), a limit remainder rests
+    pub fn submit(&mut self, side: Side, price: Option<u64>, qty: u64, trades_out: &mut Vec<Trade>) -> (OrderId, u64) {
+        self.next_id += 1;
+        let id = OrderId(self.next_id);
+        let mut remaining = qty;
+        while remaining > 0 {
+            let crosses = |p: u64| price.is_none_or(|l| match side { Side::Buy => p <= l, Side::Sell => p >= l });
+            let best = self.resting.iter().enumerate()
+                .filter(|(_, o)| o.1 != side && crosses(o.2))
+                .min_by_key(|(i, o)| (match side { Side::Buy => o.2, Side::Sell => u64::MAX - o.2 }, *i));
+            let Some((i, _)) = best else { break };
+            let maker = &mut self.resting[i];
+            let q = remaining.min(maker.3);
+            maker.3 -= q;
+            remaining -= q;
+            self.output_seq += 1;
+            trades_out.push(Trade { trade_id: TradeId(self.output_seq), taker_id: id, maker_id: maker.0, price: maker.2, qty: q, taker_side: side, taker_correlation: 0, maker_correlation: 0, taker_account: AccountId(0), maker_account: AccountId(0), maker_remaining: maker.3, maker_fully_filled: maker.3 == 0, ts: self.next_id, conditions: TradeConditions::NONE, output_seq: self.output_seq });
+            if maker.3 == 0 { self.resting.remove(i); }
+        }
+        if let (Some(p), true) = (price, remaining > 0) { self.resting.push((id, side, p, remaining)); }
+        (id, remaining)
+    }
+
+    // Remaining qty of the cancelled order
+    pub fn cancel(&mut self, id: OrderId) -> Option<u64> {
+        let i = self.resting.iter().position(|o| o.0 == id)?;
+        Some(self.resting.remove(i).3)
+    }
+
+    pub fn top_n(&self, n: usize) -> (Depth, Depth) {
+        let depth = |side: Side| {
+            let mut levels: Depth = Vec::new();
+            for o in self.resting.iter().filter(|o| o.1 == side) {
+                match levels.iter_mut().find(|l| l.0 == o.2) { Some(l) => { l.1 += o.3; l.2 += 1; } None => levels.push((o.2, o.3, 1)) }
+            }
+            levels.sort_by_key(|l| match side { Side::Buy => u64::MAX - l.0, Side::Sell => l.0 });
+            levels.truncate(n);
+            levels
+        };
+        (depth(Side::Buy), depth(Side::Sell))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffOp {
+    // Limit, Market or Cancel
+    Cmd(Command),
+    // Replace the book under test with one rebuilt from its own snapshot
+    SnapshotRestore,
+}
+
+// Drive `book` and a fresh ReferenceBook through the same ops, comparing per-op results, trades and
+// full depth after every step; returns the first divergence
+pub fn run_differential<S: BookStorage>(book: &mut OrderBook<S>, ops: &[DiffOp]) -> Result<(), String> {
+    let mut reference = ReferenceBook::new();
+    let (mut got, mut want) = (Vec::new(), Vec::new());
+    for (step, op) in ops.iter().enumerate() {
+        got.clear();
+        want.clear();
+        let (g, w) = match *op {
+            DiffOp::Cmd(Command::Limit { side, price, qty, .. }) => (book.submit_limit_into(side, price, qty, &mut got).ok(), Some(reference.submit(side, Some(price), qty, &mut want))),
+            DiffOp::Cmd(Command::Market { side, qty, .. }) => (book.submit_market_into(side, qty, &mut got).ok(), Some(reference.submit(side, None, qty, &mut want))),
+            DiffOp::Cmd(Command::Cancel { id, .. }) => (book.cancel(id).ok().map(|o| (id, o.qty)), reference.cancel(id).map(|q| (id, q))),
+            DiffOp::Cmd(other) => return Err(format!("step {}: {:?} is not modelled by the reference book", step, other)),
+            DiffOp::SnapshotRestore => {
+                let snap = book.snapshot();
+                let restored = OrderBook::from_snapshot_with_storage(S::default(), &snap).map_err(|e| format!("step {}: restore failed: {:?}", step, e))?;
+                if restored.snapshot() != snap || restored.state_hash() != book.state_hash() { return Err(format!("step {}: snapshot round-trip changed the book", step)); }
+                *book = restored;
+                (None, None)
+            }
+        };
+        if g != w { return Err(format!("step {} {:?}: result {:?}, reference {:?}", step, op, g, w)); }
+        if got != want { return Err(format!("step {} {:?}: trades {:?}, reference {:?}", step, op, got, want)); }
+        if book.top_n(usize::MAX) != reference.top_n(usize::MAX) { return Err(format!("step {} {:?}: depth {:?}, reference {:?}", step, op, book.top_n(usize::MAX), reference.top_n(usize::MAX))); }
+    }
+    Ok(())
+}
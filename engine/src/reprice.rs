@@ -0,0 +1,72 @@
+use crate::session::SessionState;
+use crate::{faults, iceberg, market_data, order_status, BookStorage, CancelReason, EngineError, EngineEvent, L3Event, OrderBook, OrderId, OrderRequest, Side, Trade};
+
+impl<S: BookStorage> OrderBook<S> {
+    // Move a resting limit order to `new_price` keeping its id, remaining qty and attributes. It
+    // joins the back of the new level (time priority is lost, as with cancel/replace) without being
+    // freed and re-inserted where the storage supports it (SlabStorage relinks the node in place).
+    // A price through the opposite touch trades first with the order as taker; any remainder rests
+    // at the new price, unless the amend cross policy (see `post_only`) rejects or re-prices the move.
+    // Returns the qty left resting. Pre-match hooks are not run again.
+    pub fn reprice(&mut self, id: OrderId, new_price: u64, trades_out: &mut Vec<Trade>) -> Result<u64, EngineError> {
+        if self.session == SessionState::Halted { return Err(EngineError::Rejected("instrument halted".into())); }
+        if self.cancel_only { return Err(EngineError::Rejected("book in cancel-only mode".into())); }
+        let &(side, price) = self.index.get(&id.0).ok_or(EngineError::UnknownOrder)?;
+        let target = self.move_target(side, new_price)?;
+        if target != new_price { self.note_repriced(id, new_price, target); }
+        self.move_order(id, side, price, target, trades_out)
+    }
+
+    // Where a resting order on `side` asked to move to `new_price` ends up, after every check a move
+    // makes; nothing changes here, so a rejected move leaves the order as it was
+    pub(crate) fn move_target(&self, side: Side, new_price: u64) -> Result<u64, EngineError> {
+        self.check_symbol_config(|c| c.check_price(new_price))?;
+        let target = self.amend_cross_price(side, new_price)?;
+        if target != new_price { self.check_symbol_config(|c| c.check_price(target))?; }
+        self.check_band(side, target)?;
+        self.check_storage_price(target)?;
+        Ok(target)
+    }
+
+    // Move an order to a price `move_target` accepted; fails only on a storage desync
+    pub(crate) fn move_order(&mut self, id: OrderId, side: Side, price: u64, new_price: u64, trades_out: &mut Vec<Trade>) -> Result<u64, EngineError> {
+        if new_price == price { return self.storage.update(side, price, id, |o| o.qty + o.reserve_qty).ok_or_else(|| faults::desync(id)); }
+        let ts = self.now();
+        let crosses = self.storage.best_price(side.opposite()).is_some_and(|p| match side { Side::Buy => p <= new_price, Side::Sell => p >= new_price });
+        if !crosses {
+            if !self.storage.relocate(side, price, id, new_price, ts) { return Err(faults::desync(id)); }
+            self.index.insert(id.0, (side, new_price));
+            let mode = self.l3_mode;
+            let (qty, reserve, owner) = self.storage.update(side, new_price, id, |o| (o.qty, o.reserve_qty, market_data::owner_of(mode, o))).ok_or_else(|| faults::desync(id))?;
+            self.note_l3(|_| L3Event::Delete { id });
+            self.note_l3(|_| L3Event::Add { id, side, price: new_price, qty, ts, owner });
+            return Ok(qty + reserve);
+        }
+
+        // Marketable: take it off the book and run it through matching like a new order
+        self.index.remove(&id.0);
+        let mut order = self.storage.remove(side, price, id).ok_or_else(|| faults::desync(id))?;
+        self.note_l3(|_| L3Event::Delete { id });
+        let display_qty = (order.display_qty > 0).then_some(order.display_qty);
+        let req = OrderRequest { tif: order.tif, account: order.account, reduce_only: order.reduce_only, correlation: order.correlation, display_qty, ..OrderRequest::limit(side, new_price, order.qty + order.reserve_qty) };
+        let start_len = trades_out.len();
+        let (remaining, stp_cancelled, _) = self.match_incoming(id, &req, Some(new_price), u32::MAX, trades_out);
+        if remaining > 0 && !stp_cancelled {
+            (order.price, order.ts) = (new_price, ts);
+            (order.qty, order.reserve_qty) = iceberg::split(order.display_qty, remaining);
+            order.order_qty -= order_status::decremented(&trades_out[start_len..]);
+            self.note_l3(|mode| L3Event::Add { id, side, price: new_price, qty: order.qty, ts, owner: market_data::owner_of(mode, &order) });
+            self.storage.push_back(order);
+            self.index.insert(id.0, (side, new_price));
+        }
+        if remaining > 0 && stp_cancelled { self.note_event(|| EngineEvent::Canceled { id, qty: remaining, reason: Some(CancelReason::SelfTrade) }); }
+        if self.positions.is_some() && trades_out.len() > start_len { self.recheck_reduce_only(req.account); }
+        if trades_out.len() > start_len {
+            for hook in self.post_trade_hooks.iter_mut() { hook.post_trade(&req, id, &mut trades_out[start_len..]); }
+            self.note_trades(&trades_out[start_len..]);
+            self.note_fills(id, req.qty, &trades_out[start_len..]);
+            self.trigger_stops(trades_out);
+        }
+        Ok(remaining)
+    }
+}
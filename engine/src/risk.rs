@@ -0,0 +1,81 @@
+use crate::{AccountId, BookStorage, EngineError, OrderBook, Side};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// Instrument groups with shared per-account exposure limits (e.g. every contract on one
+// underlying). One `RiskGroups` handle is attached to each book of the group with the book's
+// instrument name; fills on any of them move the account's group exposure, and every new order is
+// checked against it before matching.
+//   exposure  sum over the group's instruments of weight * net filled qty (buys minus sells)
+//   check     an order passes when a full fill would leave |exposure| within the account's limit,
+//             or would reduce it; resting orders are checked on entry but not reserved
+// Account 0 (unattributed flow) and instruments outside any group are not checked. Limits default
+// to the group's limit and can be set per account.
+#[derive(Debug, Clone, Default)]
+pub struct RiskGroups {
+    state: Arc<Mutex<RiskState>>,
+}
+
+#[derive(Debug, Default)]
+struct RiskState {
+    instruments: HashMap<String, (u64, i64)>, // instrument -> (group, weight)
+    group_limits: HashMap<u64, u64>,
+    account_limits: HashMap<(u64, AccountId), u64>,
+    exposure: HashMap<(u64, AccountId), i64>,
+}
+
+impl RiskState {
+    fn limit(&self, group: u64, account: AccountId) -> Option<u64> {
+        self.account_limits.get(&(group, account)).or_else(|| self.group_limits.get(&group)).copied()
+    }
+}
+
+impl RiskGroups {
+    pub fn new() -> Self { Self::default() }
+
+    // Put `instrument` in `group`; one lot of it counts `weight` toward the group exposure
+    pub fn add_instrument(&self, instrument: &str, group: u64, weight: i64) {
+        self.state.lock().unwrap().instruments.insert(instrument.to_string(), (group, weight));
+    }
+
+    pub fn set_group_limit(&self, group: u64, limit: u64) { self.state.lock().unwrap().group_limits.insert(group, limit); }
+
+    pub fn set_account_limit(&self, group: u64, account: AccountId, limit: u64) {
+        self.state.lock().unwrap().account_limits.insert((group, account), limit);
+    }
+
+    pub fn exposure(&self, group: u64, account: AccountId) -> i64 {
+        self.state.lock().unwrap().exposure.get(&(group, account)).copied().unwrap_or(0)
+    }
+
+    // Seed or correct an exposure (e.g. after a restart)
+    pub fn set_exposure(&self, group: u64, account: AccountId, exposure: i64) {
+        self.state.lock().unwrap().exposure.insert((group, account), exposure);
+    }
+
+    pub(crate) fn check(&self, instrument: &str, account: AccountId, side: Side, qty: u64) -> Result<(), EngineError> {
+        if account.0 == 0 { return Ok(()); }
+        let state = self.state.lock().unwrap();
+        let Some(&(group, weight)) = state.instruments.get(instrument) else { return Ok(()) };
+        let Some(limit) = state.limit(group, account) else { return Ok(()) };
+        let now = state.exposure.get(&(group, account)).copied().unwrap_or(0);
+        let after = now + signed(side, qty) * weight;
+        if after.unsigned_abs() <= limit || after.unsigned_abs() < now.unsigned_abs() { return Ok(()); }
+        Err(EngineError::Rejected(format!("group {group} exposure limit {limit} exceeded")))
+    }
+
+    pub(crate) fn record_fill(&self, instrument: &str, taker_side: Side, taker: AccountId, maker: AccountId, qty: u64) {
+        let mut state = self.state.lock().unwrap();
+        let Some(&(group, weight)) = state.instruments.get(instrument) else { return };
+        let delta = signed(taker_side, qty) * weight;
+        *state.exposure.entry((group, taker)).or_default() += delta;
+        *state.exposure.entry((group, maker)).or_default() -= delta;
+    }
+}
+
+fn signed(side: Side, qty: u64) -> i64 { match side { Side::Buy => qty as i64, Side::Sell => -(qty as i64) } }
+
+impl<S: BookStorage> OrderBook<S> {
+    // Attach the book to `risk` as `instrument`; None detaches it
+    pub fn set_risk_groups(&mut self, risk: Option<(RiskGroups, &str)>) { self.risk = risk.map(|(r, i)| (r, i.to_string())); }
+}
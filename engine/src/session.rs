@@ -0,0 +1,91 @@
+use crate::halt::HaltAction;
+use crate::cancels::{CancelReason, ImplicitCancel};
+use crate::{BookStorage, L3Event, Order, OrderBook, OrderId, SessionStats, Side, TimeInForce, Trade};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize), rkyv(derive(Debug, PartialEq, Eq)))]
+pub enum SessionState {
+    // Collecting AtOpen orders for the opening uncross
+    PreOpen,
+    #[default]
+    Open,
+    // Collecting AtClose orders for the closing uncross
+    PreClose,
+    Closed,
+    // Trading suspended; resting orders handled per the book's `HaltPolicy`
+    Halted,
+}
+
+impl SessionState {
+    // Stable numbering for logs and snapshot files: 0 pre-open, 1 open, 2 pre-close, 3 closed, 4 halted
+    pub fn code(self) -> u8 {
+        match self { SessionState::PreOpen => 0, SessionState::Open => 1, SessionState::PreClose => 2, SessionState::Closed => 3, SessionState::Halted => 4 }
+    }
+
+    pub fn from_code(code: u64) -> Option<Self> {
+        match code { 0 => Some(SessionState::PreOpen), 1 => Some(SessionState::Open), 2 => Some(SessionState::PreClose), 3 => Some(SessionState::Closed), 4 => Some(SessionState::Halted), _ => None }
+    }
+}
+
+// Outcome of a session transition: auction trades (PreOpen -> Open, PreClose -> Closed) and every
+// order that expired with it (unexecuted auction orders, then Day orders on the close)
+#[derive(Debug, Clone, Default)]
+pub struct SessionChange {
+    pub expired: Vec<Order>,
+    pub trades: Vec<Trade>,
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn session(&self) -> SessionState { self.session }
+
+    // Driven by the session scheduler. Leaving PreOpen for Open (PreClose for Closed) runs that
+    // auction's uncross; leaving it any other way expires its auction orders unexecuted. Moving to
+    // Closed expires every resting Day order (bids best first, then asks, FIFO within a level); GTC
+    // orders stay, so a snapshot taken after the close holds only what carries over to the next session.
+    // Halting and resuming follow the book's `HaltPolicy` (see `halt`).
+    pub fn set_session(&mut self, mut state: SessionState) -> SessionChange {
+        if self.session == SessionState::Halted && state == SessionState::Open && self.halt_policy.reopening_auction { state = SessionState::PreOpen; }
+        let mut change = SessionChange::default();
+        // Paused sweeps finish under the session they entered in
+        if state != self.session { self.finish_continuations(&mut change.trades); }
+        let was = std::mem::replace(&mut self.session, state);
+        if was == state { return change; }
+        if was == SessionState::Closed { self.stats = SessionStats::default(); }
+        let auction = match was { SessionState::PreOpen => Some((TimeInForce::AtOpen, SessionState::Open)), SessionState::PreClose => Some((TimeInForce::AtClose, SessionState::Closed)), _ => None };
+        if let Some((tif, uncross_into)) = auction {
+            if state == uncross_into { self.uncross(tif, &mut change.trades); }
+            let unexecuted = self.take_auction_orders(tif);
+            for o in &unexecuted { self.note_cancel(ImplicitCancel::of(o, CancelReason::Expired)); }
+            change.expired.extend(unexecuted);
+        }
+        if state == SessionState::Closed && was != SessionState::Closed {
+            change.expired.extend(self.expire_where(CancelReason::Expired, |o| o.tif == TimeInForce::Day));
+            change.expired.extend(self.expire_stops(CancelReason::Expired, |r| r.tif == TimeInForce::Day));
+        }
+        if state == SessionState::Halted && self.halt_policy.resting == HaltAction::Cancel {
+            change.expired.extend(self.expire_where(CancelReason::Halted, |_| true));
+            change.expired.extend(self.expire_stops(CancelReason::Halted, |_| true));
+        }
+        // Stops reached by an uncross fire once the book trades continuously
+        if state == SessionState::Open && !change.trades.is_empty() { self.trigger_stops(&mut change.trades); }
+        change
+    }
+
+    fn expire_where(&mut self, reason: CancelReason, mut f: impl FnMut(&Order) -> bool) -> Vec<Order> {
+        let mut doomed: Vec<(Side, u64, OrderId)> = Vec::new();
+        for side in [Side::Buy, Side::Sell] {
+            self.storage.for_each_level(side, &mut |price, orders| {
+                doomed.extend(orders.filter(|o| f(o)).map(|o| (side, price, o.id)));
+                true
+            });
+        }
+        doomed.into_iter().filter_map(|(side, price, id)| {
+            self.index.remove(&id.0);
+            self.note_l3(|_| L3Event::Delete { id });
+            let o = self.storage.remove(side, price, id)?;
+            self.note_cancel(ImplicitCancel::of(&o, reason));
+            Some(o)
+        }).collect()
+    }
+}
@@ -0,0 +1,40 @@
+use crate::{BookStorage, EngineError, OrderBook, OrderRequest, OrderType, Side};
+
+// Uptick rule for short sales (per book, i.e. per instrument; off by default): a short sell may only
+// execute at a price above the last trade price that differs from it, i.e. on a plus tick or a
+// zero-plus tick. Checked against the order's limit (market orders: the best bid) at entry and
+// again for every level it sweeps. A limit remainder stopped while still marketable is dropped rather
+// than left crossing the book; a resting short sale later executes at its limit as usual.
+// With no trade yet there is no reference price and short sales are unrestricted.
+impl<S: BookStorage> OrderBook<S> {
+    pub fn set_uptick_rule(&mut self, enabled: bool) { self.uptick_rule = enabled; }
+
+    pub fn uptick_rule(&self) -> bool { self.uptick_rule }
+
+    pub fn last_trade_price(&self) -> Option<u64> { self.last_trade }
+
+    pub(crate) fn short_sale_allowed(&self, price: u64) -> bool { !self.uptick_rule || uptick_allows(price, self.last_trade, self.last_different) }
+
+    pub(crate) fn check_short_sale(&self, req: &OrderRequest) -> Result<(), EngineError> {
+        if req.side != Side::Sell { return Err(EngineError::Rejected("short sell flag on a buy order".into())); }
+        let price = match req.order_type { OrderType::Limit => Some(req.price), OrderType::Market => self.storage.best_price(Side::Buy) };
+        match price {
+            Some(p) if !self.short_sale_allowed(p) => Err(EngineError::Rejected("short sale below the uptick reference price".into())),
+            _ => Ok(()),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn note_trade_price(&mut self, price: u64) {
+        if self.last_trade != Some(price) {
+            self.last_different = self.last_trade;
+            self.last_trade = Some(price);
+        }
+    }
+}
+
+// Plus tick or zero-plus tick against the last trade price and the last one that differed from it
+pub(crate) fn uptick_allows(price: u64, last: Option<u64>, last_different: Option<u64>) -> bool {
+    let reference = if Some(price) == last { last_different } else { last };
+    reference.is_none_or(|r| price > r)
+}
@@ -0,0 +1,177 @@
+use crate::{BookStorage, ClientIdEntry, ClientIdRetention, Continuation, EngineError, Order, OrderBook, OrderId, OrderRequest, ReferenceValues, SessionState, Side};
+
+// One price level with its resting orders in FIFO order
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LevelSnapshot {
+    pub price: u64,
+    pub orders: Vec<Order>,
+}
+
+// Point-in-time copy of a book, enough to rebuild it exactly (levels best first, FIFO preserved)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BookSnapshot {
+    // Orders numbered so far, before the book's id generator (see `ids`)
+    pub next_id: u64,
+    pub ts: u64,
+    pub bids: Vec<LevelSnapshot>,
+    pub asks: Vec<LevelSnapshot>,
+    // Injected reference values (not part of `state_hash`, which covers the orders)
+    pub reference: ReferenceValues,
+    // Last output sequence number assigned and trades numbered so far (see `output`, `ids`); not part
+    // of `state_hash` either
+    pub output_seq: u64,
+    pub last_trade_id: u64,
+    // Held stop orders (each with its stop price set) in trigger order, buys then sells, and auction-only
+    // orders in arrival order; both hashed after the resting orders when there are any
+    pub stops: Vec<(OrderId, OrderRequest)>,
+    pub auction: Vec<Order>,
+    // Last trade price stops trigger from, and the last different one the uptick rule compares with
+    pub last_trade: Option<u64>,
+    pub last_different: Option<u64>,
+    // Trading phase the book resumes in, so a book saved halted or collecting an auction does not come
+    // back trading continuously; hashed last when not Open
+    pub session: SessionState,
+    // Paused remainders of budget-sliced orders (see `budget`) in queue order; hashed after the held
+    // orders when there are any
+    pub continuations: Vec<Continuation>,
+    // Client ids held for duplicate detection (see `client_ids`) and the retention window bounding them;
+    // not part of `state_hash`
+    pub client_ids: Vec<ClientIdEntry>,
+    pub client_id_retention: Option<ClientIdRetention>,
+}
+
+impl BookSnapshot {
+    pub fn order_count(&self) -> usize {
+        self.bids.iter().chain(self.asks.iter()).map(|l| l.orders.len()).sum()
+    }
+
+    pub fn state_hash(&self) -> u64 {
+        let mut h = StateHasher::new(self.next_id, self.ts);
+        for (side, levels) in [(Side::Buy, &self.bids), (Side::Sell, &self.asks)] {
+            h.side(side);
+            for o in levels.iter().flat_map(|l| l.orders.iter()) { h.order(o); }
+        }
+        h.held(self.stops.iter().map(|(id, req)| (*id, req)), &self.auction);
+        h.continuations(&self.continuations);
+        h.session(self.session);
+        h.0
+    }
+}
+
+// FNV-1a over (next_id, ts, then every resting order per side in priority order); equal books hash equal
+// regardless of storage backend
+struct StateHasher(u64);
+
+impl StateHasher {
+    fn new(next_id: u64, ts: u64) -> Self {
+        let mut h = Self(0xcbf2_9ce4_8422_2325);
+        h.word(next_id);
+        h.word(ts);
+        h
+    }
+
+    fn word(&mut self, v: u64) {
+        for b in v.to_le_bytes() { self.0 ^= b as u64; self.0 = self.0.wrapping_mul(0x0100_0000_01b3); }
+    }
+
+    fn side(&mut self, side: Side) { self.word(match side { Side::Buy => 0, Side::Sell => 1 }); }
+
+    fn order(&mut self, o: &Order) {
+        for v in [o.id.0, o.price, o.qty, o.ts, o.tif.code() as u64, o.account.0, o.reduce_only as u64, o.correlation] { self.word(v); }
+        // Plain orders hash as before icebergs existed
+        if o.display_qty > 0 { self.word(o.display_qty); self.word(o.reserve_qty); }
+    }
+
+    // Books without held orders hash as before stops and auction orders were snapshotted
+    fn held<'a>(&mut self, stops: impl Iterator<Item = (OrderId, &'a OrderRequest)>, auction: &[Order]) {
+        let mut any = false;
+        for (id, req) in stops {
+            any = true;
+            for v in [id.0, req.side as u64, req.stop_price.unwrap_or(0), req.price, req.qty, req.account.0, req.correlation] { self.word(v); }
+        }
+        if !any && auction.is_empty() { return; }
+        self.side(Side::Sell);
+        for o in auction { self.order(o); }
+    }
+
+    fn continuations<'a>(&mut self, continuations: impl IntoIterator<Item = &'a Continuation>) {
+        for c in continuations {
+            for v in [c.id.0, c.req.side as u64, c.req.price, c.req.qty, c.filled, c.req.account.0, c.req.correlation] { self.word(v); }
+        }
+    }
+
+    // Open books hash as before the session was snapshotted
+    fn session(&mut self, session: SessionState) {
+        if session != SessionState::Open { self.word(session.code() as u64); }
+    }
+}
+
+impl OrderBook {
+    // The default storage holds any price, so restoring into it cannot fail
+    pub fn from_snapshot(snap: &BookSnapshot) -> Self { Self::restore(Default::default(), snap, false).expect("BTreeStorage holds any price") }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn snapshot(&self) -> BookSnapshot {
+        let levels = |side| {
+            let mut out = Vec::new();
+            self.storage.for_each_level(side, &mut |price, orders| { out.push(LevelSnapshot { price, orders: orders.cloned().collect() }); true });
+            out
+        };
+        BookSnapshot { next_id: self.next_id, ts: self.ts, bids: levels(Side::Buy), asks: levels(Side::Sell), reference: self.refdata, output_seq: self.output_seq, last_trade_id: self.last_trade_id,
+            stops: self.stops.held().map(|(id, req)| (id, *req)).collect(), auction: self.auction.clone(), last_trade: self.last_trade, last_different: self.last_different, session: self.session,
+            continuations: self.continuations.iter().copied().collect(), client_ids: self.client_id_entries(), client_id_retention: self.client_id_retention() }
+    }
+
+    // Cheap fingerprint for comparing replicas; same value as `snapshot().state_hash()` without the copy
+    pub fn state_hash(&self) -> u64 {
+        let mut h = StateHasher::new(self.next_id, self.ts);
+        for side in [Side::Buy, Side::Sell] {
+            h.side(side);
+            self.storage.for_each_level(side, &mut |_, orders| { for o in orders { h.order(o); } true });
+        }
+        h.held(self.stops.held(), &self.auction);
+        h.continuations(self.continuations.iter());
+        h.session(self.session);
+        h.0
+    }
+
+    // Restore into any backend; snapshots are backend independent, but a backend with a bounded price
+    // range (see `BookStorage::can_hold`) refuses a snapshot whose levels it cannot hold
+    pub fn from_snapshot_with_storage(storage: S, snap: &BookSnapshot) -> Result<Self, EngineError> { Self::restore(storage, snap, true) }
+
+    fn restore(storage: S, snap: &BookSnapshot, check_prices: bool) -> Result<Self, EngineError> {
+        let mut ob = Self::with_storage(storage);
+        ob.next_id = snap.next_id;
+        ob.ts = snap.ts;
+        ob.refdata = snap.reference;
+        (ob.output_seq, ob.last_trade_id) = (snap.output_seq, snap.last_trade_id);
+        (ob.last_trade, ob.last_different) = (snap.last_trade, snap.last_different);
+        ob.session = snap.session;
+        // Held orders come back as they were: nothing triggers or uncrosses on restore
+        for &(id, req) in &snap.stops { ob.stops.insert(id, req.stop_price.unwrap_or(0), req); }
+        ob.auction = snap.auction.clone();
+        ob.continuations = snap.continuations.iter().copied().collect();
+        ob.restore_client_ids(snap.client_id_retention, &snap.client_ids);
+        for o in &ob.auction { ob.ts = ob.ts.max(o.ts); }
+        // Each level is queued by priority timestamp, so FIFO survives a snapshot written in any order;
+        // the clock resumes after the latest timestamp so new orders queue behind restored ones
+        for (side, levels) in [(Side::Buy, &snap.bids), (Side::Sell, &snap.asks)] {
+            for level in levels {
+                if check_prices { ob.check_storage_price(level.price)?; }
+                let mut orders: Vec<&Order> = level.orders.iter().collect();
+                orders.sort_by_key(|o| o.ts);
+                for o in orders {
+                    ob.index.insert(o.id.0, (side, o.price));
+                    if o.reduce_only { ob.reduce_only.entry(o.account).or_default().push(o.id); }
+                    ob.ts = ob.ts.max(o.ts);
+                    ob.storage.push_back(o.clone());
+                }
+            }
+        }
+        for ids in ob.reduce_only.values_mut() { ids.sort_by_key(|id| id.0); }
+        Ok(ob)
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{AccountId, BookSnapshot, BookStorage, ClientIdRetention, ClientOrderId, Continuation, Emission, LevelSnapshot, Order, OrderBook, OrderId, OrderRequest, OrderType, ReferenceValues, SessionState, Side, TimeInForce};
+use std::io::{self, Read, Write};
+
+// Compact binary snapshot (`OrderBook::save_snapshot` / `load_snapshot`), everything but the magic
+// a LEB128 varint:
+//   header   magic "MEBOOK02", next_id, ts, output_seq, last_trade_id, session (`SessionState::code`),
+//            flags (bit 0 index, 1 settlement, 2 funding, 3 last trade, 4 last different set) and the
+//            values set, in that order (funding rate zigzag)
+//   orders   bid count, ask count, auction count, then per order: id, flags (bit 0 sell, 1 reduce-only,
+//            2 market, tif << 4), price (within a side the zigzag difference from the order before),
+//            qty, ts, account, correlation, display qty, reserve qty, order qty; bids then asks best
+//            first and FIFO within a level, then auction orders in arrival order
+//   stops    count, then per stop in trigger order: id, flags (as orders, plus bit 8 short-sell,
+//            9 top-level-only, 10 post-only, emission << 12), price, qty, stop price, account,
+//            correlation, display qty (0 = not an iceberg), client id (+1, 0 = none)
+//   paused   continuation count, then per continuation in queue order: the fields of a stop with the
+//            stop price +1 (0 = none), then the qty filled by earlier slices
+//   clients  retention flags (bit 0 max ids, 1 max age set, 2 window on) and the values set, client id count, then
+//            per held id in `BookSnapshot::client_ids` order: term start, account, client id, order id
+// Ids, timestamps and the id / time counters are kept exactly, so a loaded book queues and numbers new
+// orders as the saved one would have.
+const MAGIC: &[u8; 8] = b"MEBOOK02";
+
+impl BookSnapshot {
+    pub fn write_to(&self, mut w: impl Write) -> io::Result<()> {
+        let mut out = Vec::with_capacity(64 + self.order_count() * 16);
+        out.extend_from_slice(MAGIC);
+        let r = self.reference;
+        let flags = [r.index_price.is_some(), r.settlement_price.is_some(), r.funding_rate.is_some(), self.last_trade.is_some(), self.last_different.is_some()];
+        for v in [self.next_id, self.ts, self.output_seq, self.last_trade_id, self.session.code() as u64, flags.iter().enumerate().map(|(i, &f)| (f as u64) << i).sum()] { put(&mut out, v); }
+        for v in [r.index_price, r.settlement_price, r.funding_rate.map(zigzag), self.last_trade, self.last_different].into_iter().flatten() { put(&mut out, v); }
+        let count = |levels: &[LevelSnapshot]| levels.iter().map(|l| l.orders.len() as u64).sum::<u64>();
+        for v in [count(&self.bids), count(&self.asks), self.auction.len() as u64] { put(&mut out, v); }
+        for levels in [&self.bids, &self.asks] {
+            let mut prev = None;
+            for o in levels.iter().flat_map(|l| l.orders.iter()) {
+                put_order(&mut out, o, prev.map_or(o.price, |p: u64| zigzag(o.price.wrapping_sub(p) as i64)));
+                prev = Some(o.price);
+            }
+        }
+        for o in &self.auction { put_order(&mut out, o, o.price); }
+        put(&mut out, self.stops.len() as u64);
+        for (id, req) in &self.stops { put_request(&mut out, *id, req, req.stop_price.unwrap_or(0)); }
+        put(&mut out, self.continuations.len() as u64);
+        for c in &self.continuations {
+            put_request(&mut out, c.id, &c.req, c.req.stop_price.map_or(0, |p| p + 1));
+            put(&mut out, c.filled);
+        }
+        let retention = self.client_id_retention.unwrap_or_default();
+        put(&mut out, self.client_id_retention.map_or(0, |r| r.max_ids.is_some() as u64 | (r.max_age_micros.is_some() as u64) << 1 | 4));
+        for v in [retention.max_ids.map(|m| m as u64), retention.max_age_micros].into_iter().flatten() { put(&mut out, v); }
+        put(&mut out, self.client_ids.len() as u64);
+        for &(since, account, client_id, id) in &self.client_ids {
+            for v in [since, account.0, client_id.0, id.0] { put(&mut out, v); }
+        }
+        w.write_all(&out)
+    }
+
+    pub fn read_from(mut r: impl Read) -> io::Result<Self> {
+        let mut buf = Vec::new();
+        r.read_to_end(&mut buf)?;
+        if buf.len() < MAGIC.len() || &buf[..MAGIC.len()] != MAGIC { return Err(invalid("not a book snapshot")); }
+        let mut rd = Reader { buf: &buf, at: MAGIC.len() };
+        let mut snap = BookSnapshot { next_id: rd.varint()?, ts: rd.varint()?, output_seq: rd.varint()?, last_trade_id: rd.varint()?, ..BookSnapshot::default() };
+        snap.session = SessionState::from_code(rd.varint()?).ok_or_else(|| invalid("unknown session state"))?;
+        let flags = rd.varint()?;
+        let mut opt = |bit: u32| if flags >> bit & 1 != 0 { rd.varint().map(Some) } else { Ok(None) };
+        snap.reference = ReferenceValues { index_price: opt(0)?, settlement_price: opt(1)?, funding_rate: opt(2)?.map(unzigzag) };
+        (snap.last_trade, snap.last_different) = (opt(3)?, opt(4)?);
+        let (bid_count, ask_count, auction_count) = (rd.varint()?, rd.varint()?, rd.varint()?);
+        for (count, levels) in [(bid_count, &mut snap.bids), (ask_count, &mut snap.asks)] {
+            let mut prev: Option<u64> = None;
+            for _ in 0..count {
+                let mut o = rd.order()?;
+                if let Some(p) = prev { o.price = p.wrapping_add(unzigzag(o.price) as u64); }
+                prev = Some(o.price);
+                match levels.last_mut() {
+                    Some(l) if l.price == o.price => l.orders.push(o),
+                    _ => levels.push(LevelSnapshot { price: o.price, orders: vec![o] }),
+                }
+            }
+        }
+        for _ in 0..auction_count { snap.auction.push(rd.order()?); }
+        for _ in 0..rd.varint()? {
+            let (id, req, stop) = rd.request()?;
+            snap.stops.push((id, OrderRequest { stop_price: Some(stop), ..req }));
+        }
+        for _ in 0..rd.varint()? {
+            let (id, req, stop) = rd.request()?;
+            snap.continuations.push(Continuation { id, req: OrderRequest { stop_price: stop.checked_sub(1), ..req }, filled: rd.varint()? });
+        }
+        let flags = rd.varint()?;
+        let mut opt = |bit: u32| if flags >> bit & 1 != 0 { rd.varint().map(Some) } else { Ok(None) };
+        let retention = ClientIdRetention { max_ids: opt(0)?.map(|m| m as usize), max_age_micros: opt(1)? };
+        snap.client_id_retention = (flags & 4 != 0).then_some(retention);
+        for _ in 0..rd.varint()? {
+            snap.client_ids.push((rd.varint()?, AccountId(rd.varint()?), ClientOrderId(rd.varint()?), OrderId(rd.varint()?)));
+        }
+        if rd.at != buf.len() { return Err(invalid("trailing bytes after snapshot")); }
+        Ok(snap)
+    }
+}
+
+impl OrderBook {
+    pub fn load_snapshot(r: impl Read) -> io::Result<Self> { Self::load_snapshot_with_storage(Default::default(), r) }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn save_snapshot(&self, w: impl Write) -> io::Result<()> { self.snapshot().write_to(w) }
+
+    pub fn load_snapshot_with_storage(storage: S, r: impl Read) -> io::Result<Self> {
+        Self::from_snapshot_with_storage(storage, &BookSnapshot::read_from(r)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{e:?}")))
+    }
+}
+
+fn invalid(msg: &str) -> io::Error { io::Error::new(io::ErrorKind::InvalidData, msg.to_string()) }
+
+fn type_flags(side: Side, reduce_only: bool, order_type: OrderType, tif: TimeInForce) -> u64 {
+    (side == Side::Sell) as u64 | (reduce_only as u64) << 1 | ((order_type == OrderType::Market) as u64) << 2 | (tif.code() as u64) << 4
+}
+
+fn decode_type_flags(flags: u64) -> io::Result<(Side, bool, OrderType, TimeInForce)> {
+    let side = if flags & 1 == 0 { Side::Buy } else { Side::Sell };
+    let order_type = if flags & 4 == 0 { OrderType::Limit } else { OrderType::Market };
+    let tif = TimeInForce::from_code(flags >> 4 & 0xF).ok_or_else(|| invalid("unknown time in force"))?;
+    Ok((side, flags & 2 != 0, order_type, tif))
+}
+
+fn put_order(out: &mut Vec<u8>, o: &Order, price: u64) {
+    for v in [o.id.0, type_flags(o.side, o.reduce_only, o.order_type, o.tif), price, o.qty, o.ts, o.account.0, o.correlation, o.display_qty, o.reserve_qty, o.order_qty] { put(out, v); }
+}
+
+// A held or paused order; the caller picks how its stop price is written
+fn put_request(out: &mut Vec<u8>, id: OrderId, req: &OrderRequest, stop: u64) {
+    let emission = match req.emission { Emission::Live => 0, Emission::Suppressed => 1, Emission::Replay => 2 };
+    let flags = type_flags(req.side, req.reduce_only, req.order_type, req.tif) | (req.short_sell as u64) << 8 | (req.top_level_only as u64) << 9 | (req.post_only as u64) << 10 | emission << 12;
+    for v in [id.0, flags, req.price, req.qty, stop, req.account.0, req.correlation, req.display_qty.unwrap_or(0), req.client_id.map_or(0, |c| c.0 + 1)] { put(out, v); }
+}
+
+fn put(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 { out.push(v as u8 | 0x80); v >>= 7; }
+    out.push(v as u8);
+}
+
+fn zigzag(v: i64) -> u64 { ((v << 1) ^ (v >> 63)) as u64 }
+
+fn unzigzag(v: u64) -> i64 { (v >> 1) as i64 ^ -((v & 1) as i64) }
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    at: usize,
+}
+
+impl Reader<'_> {
+    fn varint(&mut self) -> io::Result<u64> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let &b = self.buf.get(self.at).ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+            self.at += 1;
+            v |= ((b & 0x7F) as u64) << shift;
+            if b & 0x80 == 0 { return Ok(v); }
+        }
+        Err(invalid("varint too long"))
+    }
+
+    // Price as written: absolute for the first order of a side, otherwise the distance from the one before
+    fn order(&mut self) -> io::Result<Order> {
+        let (id, flags) = (OrderId(self.varint()?), self.varint()?);
+        let (side, reduce_only, order_type, tif) = decode_type_flags(flags)?;
+        let (price, qty, ts, account, correlation, display_qty, reserve_qty, order_qty) = (self.varint()?, self.varint()?, self.varint()?, self.varint()?, self.varint()?, self.varint()?, self.varint()?, self.varint()?);
+        Ok(Order { id, side, price, qty, order_type, ts, tif, account: AccountId(account), reduce_only, correlation, display_qty, reserve_qty, order_qty })
+    }
+
+    // Written by `put_request`: (id, request without its stop price, stop price as written)
+    fn request(&mut self) -> io::Result<(OrderId, OrderRequest, u64)> {
+        let (id, flags) = (OrderId(self.varint()?), self.varint()?);
+        let (side, reduce_only, order_type, tif) = decode_type_flags(flags)?;
+        let emission = match flags >> 12 { 0 => Emission::Live, 1 => Emission::Suppressed, 2 => Emission::Replay, _ => return Err(invalid("unknown emission")) };
+        let (price, qty, stop, account, correlation, display, client) = (self.varint()?, self.varint()?, self.varint()?, self.varint()?, self.varint()?, self.varint()?, self.varint()?);
+        Ok((id, OrderRequest { side, order_type, price, qty, tif, account: AccountId(account), reduce_only, short_sell: flags >> 8 & 1 != 0, correlation, post_only: flags >> 10 & 1 != 0,
+            top_level_only: flags >> 9 & 1 != 0, emission, stop_price: None, display_qty: (display > 0).then_some(display), client_id: client.checked_sub(1).map(ClientOrderId) }, stop))
+    }
+}
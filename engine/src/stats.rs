@@ -0,0 +1,35 @@
+use crate::{BookStorage, OrderBook, Trade, TradeConditions};
+
+// Per-session trade statistics, kept from the book's executions (continuous and auction trades
+// alike; STP decrements are not trades). They start over when the book leaves Closed, i.e. with
+// the next session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionStats {
+    pub open: Option<u64>,
+    pub high: Option<u64>,
+    pub low: Option<u64>,
+    pub last: Option<u64>,
+    pub volume: u64,
+    // Sum of price * qty
+    pub notional: u128,
+    pub trades: u64,
+}
+
+impl SessionStats {
+    pub fn vwap(&self) -> Option<f64> { (self.volume > 0).then(|| self.notional as f64 / self.volume as f64) }
+
+    pub(crate) fn record(&mut self, t: &Trade) {
+        if t.conditions.contains(TradeConditions::STP_DECREMENT) { return; }
+        self.open.get_or_insert(t.price);
+        self.high = Some(self.high.map_or(t.price, |h| h.max(t.price)));
+        self.low = Some(self.low.map_or(t.price, |l| l.min(t.price)));
+        self.last = Some(t.price);
+        self.volume += t.qty;
+        self.notional += t.price as u128 * t.qty as u128;
+        self.trades += 1;
+    }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn session_stats(&self) -> SessionStats { self.stats }
+}
@@ -0,0 +1,122 @@
+use crate::{BookStorage, CancelReason, EngineError, EngineEvent, ImplicitCancel, Order, OrderBook, OrderId, OrderRequest, Side, TimeInForce, Trade};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+// Stop-market and stop-limit orders (`OrderRequest::with_stop`): held off the book in a trigger index
+// keyed by stop price until the last trade price reaches the stop, then entered as the market or limit
+// order they carry.
+//   trigger   buy stops at last >= stop, sell stops at last <= stop; a stop already reached on entry
+//             is activated at once, with no trade yet it waits
+//   order     buys fire lowest stop first, sells highest first, oldest first within a stop price;
+//             trades of an activated order move the last price and can fire further stops within the
+//             same command (trades from submits, reprices and auction uncrosses all trigger)
+//   checks    pre-match hooks and entry checks run when the stop is accepted; reduce-only, short
+//             sale, group risk and the price band are checked again on activation, and a stop failing
+//             them is dropped as an `ImplicitCancel` with `CancelReason::StopRejected`
+//   priority  the order keeps the id returned on entry and takes its time priority on activation
+// Each activation is recorded as a `StopActivation`, drained like implicit cancels, in the command that
+// printed the triggering trade. Held stops cancel by id like resting orders and expire with Day orders
+// at the close and with the book on a cancelling halt. Post-only and auction-only stops are rejected.
+// Stops are not in depth or L3 events; snapshots keep them with the last trade price they trigger from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StopActivation {
+    pub id: OrderId,
+    pub side: Side,
+    pub stop_price: u64,
+    // Last trade price that reached the stop
+    pub last_price: u64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct StopBook {
+    buys: BTreeMap<(u64, u64), OrderRequest>, // (stop, id)
+    sells: BTreeMap<(Reverse<u64>, u64), OrderRequest>,
+    index: HashMap<u64, (Side, u64)>, // id -> (side, stop)
+}
+
+impl StopBook {
+    pub(crate) fn insert(&mut self, id: OrderId, stop: u64, req: OrderRequest) {
+        match req.side { Side::Buy => self.buys.insert((stop, id.0), req), Side::Sell => self.sells.insert((Reverse(stop), id.0), req) };
+        self.index.insert(id.0, (req.side, stop));
+    }
+
+    fn remove(&mut self, id: OrderId) -> Option<OrderRequest> {
+        let (side, stop) = self.index.remove(&id.0)?;
+        match side { Side::Buy => self.buys.remove(&(stop, id.0)), Side::Sell => self.sells.remove(&(Reverse(stop), id.0)) }
+    }
+
+    // Next stop reached by a last trade at `last`
+    fn next_triggered(&mut self, last: u64) -> Option<(OrderId, OrderRequest)> {
+        let id = match (self.buys.first_key_value(), self.sells.first_key_value()) {
+            (Some((&(stop, id), _)), _) if stop <= last => id,
+            (_, Some((&(Reverse(stop), id), _))) if stop >= last => id,
+            _ => return None,
+        };
+        self.remove(OrderId(id)).map(|req| (OrderId(id), req))
+    }
+
+    // Held stops in trigger order, buys then sells
+    pub(crate) fn held(&self) -> impl Iterator<Item = (OrderId, &OrderRequest)> {
+        self.buys.iter().map(|(&(_, id), r)| (OrderId(id), r)).chain(self.sells.iter().map(|(&(_, id), r)| (OrderId(id), r)))
+    }
+
+    fn ids_where(&self, mut f: impl FnMut(&OrderRequest) -> bool) -> Vec<OrderId> {
+        let buys = self.buys.iter().map(|(&(_, id), r)| (id, r));
+        buys.chain(self.sells.iter().map(|(&(_, id), r)| (id, r))).filter(|(_, r)| f(r)).map(|(id, _)| OrderId(id)).collect()
+    }
+}
+
+// The stop as an order, for cancels and expiry
+pub(crate) fn held(id: OrderId, req: &OrderRequest) -> Order {
+    Order { id, side: req.side, price: req.price, qty: req.qty, order_type: req.order_type, ts: 0, tif: req.tif, account: req.account, reduce_only: req.reduce_only, correlation: req.correlation, display_qty: 0, reserve_qty: 0, order_qty: req.qty }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn stop_count(&self) -> usize { self.stops.index.len() }
+
+    // Held stop by id, with its stop price still set
+    pub fn stop_order(&self, id: OrderId) -> Option<OrderRequest> {
+        let &(side, stop) = self.stops.index.get(&id.0)?;
+        match side { Side::Buy => self.stops.buys.get(&(stop, id.0)), Side::Sell => self.stops.sells.get(&(Reverse(stop), id.0)) }.copied()
+    }
+
+    pub fn drain_stop_activations(&mut self) -> Vec<StopActivation> { std::mem::take(&mut self.stop_activations) }
+
+    pub(crate) fn enter_stop(&mut self, stop: u64, req: OrderRequest, trades_out: &mut Vec<Trade>) -> Result<(OrderId, u64), EngineError> {
+        if req.post_only { return Err(EngineError::Rejected("stop orders cannot be post-only".into())); }
+        if matches!(req.tif, TimeInForce::AtOpen | TimeInForce::AtClose) { return Err(EngineError::Rejected("stop orders cannot be auction-only".into())); }
+        let id = self.next_order_id();
+        self.note_event(|| EngineEvent::Accepted { id, req });
+        self.stops.insert(id, stop, req);
+        self.trigger_stops(trades_out);
+        Ok((id, req.qty))
+    }
+
+    pub(crate) fn cancel_stop(&mut self, id: OrderId) -> Option<Order> { self.stops.remove(id).map(|req| held(id, &req)) }
+
+    pub(crate) fn expire_stops(&mut self, reason: CancelReason, mut f: impl FnMut(&OrderRequest) -> bool) -> Vec<Order> {
+        let doomed = self.stops.ids_where(&mut f);
+        doomed.into_iter().filter_map(|id| {
+            let o = self.cancel_stop(id)?;
+            self.note_cancel(ImplicitCancel::of(&o, reason));
+            Some(o)
+        }).collect()
+    }
+
+    // Activate every stop the last trade price has reached, including those reached by the trades of
+    // the orders activated here
+    pub(crate) fn trigger_stops(&mut self, trades_out: &mut Vec<Trade>) {
+        if self.stops.index.is_empty() { return; }
+        while let Some(last) = self.last_trade {
+            let Some((id, mut req)) = self.stops.next_triggered(last) else { break };
+            let stop_price = req.stop_price.take().unwrap_or(last);
+            self.stop_activations.push(StopActivation { id, side: req.side, stop_price, last_price: last });
+            self.note_event(|| EngineEvent::Triggered { id, stop_price, last_price: last });
+            if self.check_entry(&mut req).is_err() {
+                self.note_cancel(ImplicitCancel::of(&held(id, &req), CancelReason::StopRejected));
+                continue;
+            }
+            self.emitting(req.emission, trades_out, |ob, out| ob.execute(id, req, out));
+        }
+    }
+}
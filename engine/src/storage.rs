@@ -0,0 +1,820 @@
+use crate::{Order, OrderId, Side};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+// Price-level storage behind OrderBook. Both sides live in one backend; levels hold orders in
+// FIFO order and must disappear as soon as they are empty.
+pub trait BookStorage: Default {
+    // Most aggressive resting price on `side` (highest bid / lowest ask)
+    fn best_price(&self, side: Side) -> Option<u64>;
+
+    // Fill up to `qty` against the FIFO at (`side`, `price`), oldest first. `on_fill` sees each maker
+    // after its qty was decremented (qty == 0 means it was fully filled and removed). Returns the unfilled qty.
+    fn match_level<F: FnMut(&Order, u64)>(&mut self, side: Side, price: u64, qty: u64, on_fill: F) -> u64;
+
+    // `match_level` on the best level of `side` when it crosses `limit` (None: any price), finding
+    // the level once for both the price check and the fill. Returns (price, unfilled qty); None when
+    // the side is empty or does not cross.
+    fn match_best<F: FnMut(&Order, u64)>(&mut self, side: Side, limit: Option<u64>, qty: u64, on_fill: F) -> Option<(u64, u64)> {
+        let price = self.best_price(side).filter(|&p| crosses(side, p, limit))?;
+        Some((price, self.match_level(side, price, qty, on_fill)))
+    }
+
+    // Append at the back of the order's (side, price) level
+    fn push_back(&mut self, order: Order);
+
+    fn remove(&mut self, side: Side, price: u64, id: OrderId) -> Option<Order>;
+
+    // Modify a resting order in place (keeps its queue position); `f` must not set qty to 0 or move it
+    fn update<R>(&mut self, side: Side, price: u64, id: OrderId, f: impl FnOnce(&mut Order) -> R) -> Option<R>;
+
+    // Move a resting order to the back of the `new_price` level on its side, stamping `ts`
+    fn relocate(&mut self, side: Side, price: u64, id: OrderId, new_price: u64, ts: u64) -> bool {
+        let Some(mut o) = self.remove(side, price, id) else { return false };
+        (o.price, o.ts) = (new_price, ts);
+        self.push_back(o);
+        true
+    }
+
+    // Whether a level at `price` can be added; backends with a bounded price range (`LadderStorage`)
+    // override it, and new orders and reprices to a price it refuses are rejected
+    fn can_hold(&self, _price: u64) -> bool { true }
+
+    // The resting order `id` at (`side`, `price`)
+    fn get(&self, side: Side, price: u64, id: OrderId) -> Option<&Order>;
+
+    // (orders ahead, qty ahead) of `id` within its level; cost is proportional to the orders ahead
+    fn queue_position(&self, side: Side, price: u64, id: OrderId) -> Option<(usize, u64)>;
+
+    // Visit levels best first with their orders in FIFO order; stop when `f` returns false
+    fn for_each_level(&self, side: Side, f: &mut dyn FnMut(u64, &mut dyn Iterator<Item = &Order>) -> bool);
+
+    // Levels best first with their total resting qty and order count (depth aggregation); stop when `f` returns false
+    fn for_each_level_totals(&self, side: Side, f: &mut dyn FnMut(u64, u64, usize) -> bool) {
+        self.for_each_level(side, &mut |p, orders| {
+            let (qty, n) = orders.fold((0, 0), |(q, n), o| (q + o.qty, n + 1));
+            f(p, qty, n)
+        });
+    }
+}
+
+// A resting price on `maker_side` is reachable by a taker limited to `limit`
+#[inline]
+pub(crate) fn crosses(maker_side: Side, price: u64, limit: Option<u64>) -> bool {
+    limit.is_none_or(|l| match maker_side { Side::Sell => price <= l, Side::Buy => price >= l })
+}
+
+// Two loops instead of one branchy one: makers that fit in what is left are filled whole and popped
+// (the common case when sweeping), then at most one maker takes the partial tail and stays.
+#[inline]
+fn fill_queue<F: FnMut(&Order, u64)>(queue: &mut VecDeque<Order>, mut remaining: u64, on_fill: &mut F) -> u64 {
+    while let Some(maker) = queue.front_mut().filter(|m| m.qty <= remaining) {
+        let trade_qty = std::mem::take(&mut maker.qty);
+        remaining -= trade_qty;
+        on_fill(maker, trade_qty);
+        queue.pop_front();
+    }
+    if remaining == 0 { return 0; }
+    let Some(maker) = queue.front_mut() else { return remaining };
+    maker.qty -= remaining;
+    on_fill(maker, remaining);
+    0
+}
+
+fn position_in(queue: &VecDeque<Order>, id: OrderId) -> Option<(usize, u64)> {
+    let mut ahead = 0;
+    for (i, o) in queue.iter().enumerate() {
+        if o.id == id { return Some((i, ahead)); }
+        ahead += o.qty;
+    }
+    None
+}
+
+// BTreeMap(price) + VecDeque(FIFO): general purpose, handles any price range
+#[derive(Default)]
+pub struct BTreeStorage {
+    bids: BTreeMap<u64, VecDeque<Order>>, // price -> fifo
+    asks: BTreeMap<u64, VecDeque<Order>>, // price -> fifo
+}
+
+impl BTreeStorage {
+    fn side_mut(&mut self, side: Side) -> &mut BTreeMap<u64, VecDeque<Order>> {
+        match side { Side::Buy => &mut self.bids, Side::Sell => &mut self.asks }
+    }
+}
+
+impl BookStorage for BTreeStorage {
+    fn best_price(&self, side: Side) -> Option<u64> {
+        match side {
+            Side::Buy => self.bids.last_key_value().map(|(p, _)| *p),
+            Side::Sell => self.asks.first_key_value().map(|(p, _)| *p),
+        }
+    }
+
+    fn match_level<F: FnMut(&Order, u64)>(&mut self, side: Side, price: u64, qty: u64, mut on_fill: F) -> u64 {
+        let book = self.side_mut(side);
+        let Some(queue) = book.get_mut(&price) else { return qty };
+        let remaining = fill_queue(queue, qty, &mut on_fill);
+        if queue.is_empty() { book.remove(&price); }
+        remaining
+    }
+
+    fn match_best<F: FnMut(&Order, u64)>(&mut self, side: Side, limit: Option<u64>, qty: u64, mut on_fill: F) -> Option<(u64, u64)> {
+        let mut level = match side { Side::Buy => self.bids.last_entry(), Side::Sell => self.asks.first_entry() }?;
+        let price = *level.key();
+        if !crosses(side, price, limit) { return None; }
+        let remaining = fill_queue(level.get_mut(), qty, &mut on_fill);
+        if level.get().is_empty() { level.remove(); }
+        Some((price, remaining))
+    }
+
+    fn push_back(&mut self, order: Order) {
+        self.side_mut(order.side).entry(order.price).or_default().push_back(order);
+    }
+
+    fn remove(&mut self, side: Side, price: u64, id: OrderId) -> Option<Order> {
+        let book = self.side_mut(side);
+        let queue = book.get_mut(&price)?;
+        let i = queue.iter().position(|o| o.id == id)?;
+        let o = queue.remove(i);
+        if queue.is_empty() { book.remove(&price); }
+        o
+    }
+
+    fn update<R>(&mut self, side: Side, price: u64, id: OrderId, f: impl FnOnce(&mut Order) -> R) -> Option<R> {
+        self.side_mut(side).get_mut(&price)?.iter_mut().find(|o| o.id == id).map(f)
+    }
+
+    fn get(&self, side: Side, price: u64, id: OrderId) -> Option<&Order> {
+        let book = match side { Side::Buy => &self.bids, Side::Sell => &self.asks };
+        book.get(&price)?.iter().find(|o| o.id == id)
+    }
+
+    fn queue_position(&self, side: Side, price: u64, id: OrderId) -> Option<(usize, u64)> {
+        let book = match side { Side::Buy => &self.bids, Side::Sell => &self.asks };
+        position_in(book.get(&price)?, id)
+    }
+
+    fn for_each_level(&self, side: Side, f: &mut dyn FnMut(u64, &mut dyn Iterator<Item = &Order>) -> bool) {
+        match side {
+            Side::Buy => { for (p, q) in self.bids.iter().rev() { if !f(*p, &mut q.iter()) { break; } } }
+            Side::Sell => { for (p, q) in self.asks.iter() { if !f(*p, &mut q.iter()) { break; } } }
+        }
+    }
+}
+
+// Dense array of levels indexed by price - base; O(1) level access, best for narrow price ranges.
+// Grows on demand in both directions; finding the next best level after one empties is a linear scan.
+// The span from the lowest to the highest price ever held is capped at `LADDER_MAX_SPAN` slots (the
+// ladder never shrinks): orders that would stretch it further are rejected (see `can_hold`) instead of
+// allocating a slot for every price in between; restoring a wider snapshot into a ladder is refused
+// the same way.
+pub const LADDER_MAX_SPAN: u64 = 1 << 20;
+
+#[derive(Default)]
+pub struct LadderStorage {
+    base: u64,
+    bids: Vec<VecDeque<Order>>,
+    asks: Vec<VecDeque<Order>>,
+    best_bid: Option<usize>,
+    best_ask: Option<usize>,
+}
+
+impl LadderStorage {
+    // Preallocate slots for [min_price, max_price]
+    pub fn with_range(min_price: u64, max_price: u64) -> Self {
+        assert!(max_price.saturating_sub(min_price) < LADDER_MAX_SPAN, "ladder range wider than LADDER_MAX_SPAN");
+        let n = (max_price.saturating_sub(min_price) + 1) as usize;
+        Self { base: min_price, bids: vec![VecDeque::new(); n], asks: vec![VecDeque::new(); n], best_bid: None, best_ask: None }
+    }
+
+    fn slot(&mut self, price: u64) -> usize {
+        assert!(self.can_hold(price), "price {price} would stretch the ladder past LADDER_MAX_SPAN");
+        if self.bids.is_empty() {
+            self.base = price;
+        } else if price < self.base {
+            let shift = (self.base - price) as usize;
+            self.bids.splice(0..0, std::iter::repeat_with(VecDeque::new).take(shift));
+            self.asks.splice(0..0, std::iter::repeat_with(VecDeque::new).take(shift));
+            self.best_bid = self.best_bid.map(|i| i + shift);
+            self.best_ask = self.best_ask.map(|i| i + shift);
+            self.base = price;
+        }
+        let i = (price - self.base) as usize;
+        if i >= self.bids.len() {
+            self.bids.resize_with(i + 1, VecDeque::new);
+            self.asks.resize_with(i + 1, VecDeque::new);
+        }
+        i
+    }
+
+    fn index_of(&self, price: u64) -> Option<usize> {
+        let i = price.checked_sub(self.base)? as usize;
+        (i < self.bids.len()).then_some(i)
+    }
+
+    // Recompute best after the level at `from` emptied
+    fn refresh_best(&mut self, side: Side, from: usize) {
+        match side {
+            Side::Buy => self.best_bid = (0..=from).rev().find(|&i| !self.bids[i].is_empty()),
+            Side::Sell => self.best_ask = (from..self.asks.len()).find(|&i| !self.asks[i].is_empty()),
+        }
+    }
+}
+
+impl BookStorage for LadderStorage {
+    fn best_price(&self, side: Side) -> Option<u64> {
+        match side { Side::Buy => self.best_bid, Side::Sell => self.best_ask }.map(|i| self.base + i as u64)
+    }
+
+    fn can_hold(&self, price: u64) -> bool {
+        if self.bids.is_empty() { return true; }
+        let (low, high) = (self.base.min(price), (self.base + self.bids.len() as u64 - 1).max(price));
+        high - low < LADDER_MAX_SPAN
+    }
+
+    fn match_level<F: FnMut(&Order, u64)>(&mut self, side: Side, price: u64, qty: u64, mut on_fill: F) -> u64 {
+        let Some(i) = self.index_of(price) else { return qty };
+        let queue = match side { Side::Buy => &mut self.bids[i], Side::Sell => &mut self.asks[i] };
+        let remaining = fill_queue(queue, qty, &mut on_fill);
+        let emptied_best = queue.is_empty() && match side { Side::Buy => self.best_bid, Side::Sell => self.best_ask } == Some(i);
+        if emptied_best { self.refresh_best(side, i); }
+        remaining
+    }
+
+    fn push_back(&mut self, order: Order) {
+        let i = self.slot(order.price);
+        match order.side {
+            Side::Buy => { self.bids[i].push_back(order); if self.best_bid.is_none_or(|b| i > b) { self.best_bid = Some(i); } }
+            Side::Sell => { self.asks[i].push_back(order); if self.best_ask.is_none_or(|b| i < b) { self.best_ask = Some(i); } }
+        }
+    }
+
+    fn remove(&mut self, side: Side, price: u64, id: OrderId) -> Option<Order> {
+        let i = self.index_of(price)?;
+        let queue = match side { Side::Buy => &mut self.bids[i], Side::Sell => &mut self.asks[i] };
+        let pos = queue.iter().position(|o| o.id == id)?;
+        let o = queue.remove(pos);
+        let emptied_best = queue.is_empty() && match side { Side::Buy => self.best_bid, Side::Sell => self.best_ask } == Some(i);
+        if emptied_best { self.refresh_best(side, i); }
+        o
+    }
+
+    fn update<R>(&mut self, side: Side, price: u64, id: OrderId, f: impl FnOnce(&mut Order) -> R) -> Option<R> {
+        let i = self.index_of(price)?;
+        let queue = match side { Side::Buy => &mut self.bids[i], Side::Sell => &mut self.asks[i] };
+        queue.iter_mut().find(|o| o.id == id).map(f)
+    }
+
+    fn get(&self, side: Side, price: u64, id: OrderId) -> Option<&Order> {
+        let i = self.index_of(price)?;
+        match side { Side::Buy => &self.bids[i], Side::Sell => &self.asks[i] }.iter().find(|o| o.id == id)
+    }
+
+    fn queue_position(&self, side: Side, price: u64, id: OrderId) -> Option<(usize, u64)> {
+        let i = self.index_of(price)?;
+        position_in(match side { Side::Buy => &self.bids[i], Side::Sell => &self.asks[i] }, id)
+    }
+
+    fn for_each_level(&self, side: Side, f: &mut dyn FnMut(u64, &mut dyn Iterator<Item = &Order>) -> bool) {
+        match side {
+            Side::Buy => {
+                let Some(best) = self.best_bid else { return };
+                for i in (0..=best).rev() {
+                    let q = &self.bids[i];
+                    if !q.is_empty() && !f(self.base + i as u64, &mut q.iter()) { break; }
+                }
+            }
+            Side::Sell => {
+                let Some(best) = self.best_ask else { return };
+                for (i, q) in self.asks.iter().enumerate().skip(best) {
+                    if !q.is_empty() && !f(self.base + i as u64, &mut q.iter()) { break; }
+                }
+            }
+        }
+    }
+}
+
+const NIL: usize = usize::MAX;
+
+struct Node {
+    order: Order,
+    prev: usize,
+    next: usize,
+}
+
+// `qty` and `orders` are running totals kept in step with the list, so depth aggregation never walks it
+#[derive(Clone, Copy)]
+struct Level {
+    head: usize,
+    tail: usize,
+    qty: u64,
+    orders: usize,
+}
+
+// Orders live in a slab and are chained per level through intrusive prev/next links, with an
+// id -> slot map: O(1) cancel anywhere in the queue and no per-level allocations.
+#[derive(Default)]
+pub struct SlabStorage {
+    nodes: Vec<Option<Node>>,
+    free: Vec<usize>,
+    slots: HashMap<u64, usize>, // id -> slot
+    bids: BTreeMap<u64, Level>,
+    asks: BTreeMap<u64, Level>,
+}
+
+impl SlabStorage {
+    fn levels_mut(&mut self, side: Side) -> &mut BTreeMap<u64, Level> {
+        match side { Side::Buy => &mut self.bids, Side::Sell => &mut self.asks }
+    }
+
+    fn node(&self, slot: usize) -> &Node { self.nodes[slot].as_ref().expect("live slab slot") }
+
+    fn node_mut(&mut self, slot: usize) -> &mut Node { self.nodes[slot].as_mut().expect("live slab slot") }
+
+    // Detach `slot` from its level list and free it
+    fn unlink(&mut self, side: Side, price: u64, slot: usize) -> Order {
+        self.detach(side, price, slot);
+        let node = self.nodes[slot].take().expect("live slab slot");
+        self.free.push(slot);
+        self.slots.remove(&node.order.id.0);
+        node.order
+    }
+
+    // Take `slot` out of its level list, leaving the node allocated
+    fn detach(&mut self, side: Side, price: u64, slot: usize) {
+        let (prev, next, qty) = { let n = self.node(slot); (n.prev, n.next, n.order.qty) };
+        if prev != NIL { self.node_mut(prev).next = next; }
+        if next != NIL { self.node_mut(next).prev = prev; }
+        let levels = self.levels_mut(side);
+        let mut level = levels[&price];
+        (level.qty, level.orders) = (level.qty - qty, level.orders - 1);
+        if level.head == slot { level.head = next; }
+        if level.tail == slot { level.tail = prev; }
+        if level.head == NIL { levels.remove(&price); } else { levels.insert(price, level); }
+    }
+
+    // `fill_queue` over a level's list: whole makers are freed walking from the head, the partial
+    // tail stays; the level's head and totals are updated in place and written back by the caller's
+    // single map entry (an emptied level is left with head == NIL for the caller to remove)
+    fn fill_level<F: FnMut(&Order, u64)>(nodes: &mut [Option<Node>], free: &mut Vec<usize>, slots: &mut HashMap<u64, usize>, level: &mut Level, mut remaining: u64, on_fill: &mut F) -> u64 {
+        while let Some(node) = nodes.get_mut(level.head).and_then(Option::as_mut).filter(|n| n.order.qty <= remaining) {
+            let trade_qty = std::mem::take(&mut node.order.qty);
+            remaining -= trade_qty;
+            on_fill(&node.order, trade_qty);
+            let node = nodes[level.head].take().expect("live slab slot");
+            free.push(level.head);
+            slots.remove(&node.order.id.0);
+            (level.head, level.qty, level.orders) = (node.next, level.qty - trade_qty, level.orders - 1);
+        }
+        if level.head == NIL { level.tail = NIL; return remaining; }
+        let maker = nodes[level.head].as_mut().expect("live slab slot");
+        maker.prev = NIL;
+        if remaining == 0 { return 0; }
+        maker.order.qty -= remaining;
+        level.qty -= remaining;
+        on_fill(&maker.order, remaining);
+        0
+    }
+
+    // Link a live node at the tail of its order's level
+    fn append(&mut self, slot: usize) {
+        let (side, price, qty) = { let o = &self.node(slot).order; (o.side, o.price, o.qty) };
+        let tail = self.levels_mut(side).get(&price).map(|l| l.tail);
+        let node = self.node_mut(slot);
+        (node.prev, node.next) = (tail.unwrap_or(NIL), NIL);
+        match tail {
+            Some(tail) => {
+                self.node_mut(tail).next = slot;
+                let level = self.levels_mut(side).get_mut(&price).unwrap();
+                (level.tail, level.qty, level.orders) = (slot, level.qty + qty, level.orders + 1);
+            }
+            None => { self.levels_mut(side).insert(price, Level { head: slot, tail: slot, qty, orders: 1 }); }
+        }
+    }
+}
+
+impl BookStorage for SlabStorage {
+    fn best_price(&self, side: Side) -> Option<u64> {
+        match side {
+            Side::Buy => self.bids.last_key_value().map(|(p, _)| *p),
+            Side::Sell => self.asks.first_key_value().map(|(p, _)| *p),
+        }
+    }
+
+    fn match_level<F: FnMut(&Order, u64)>(&mut self, side: Side, price: u64, qty: u64, mut on_fill: F) -> u64 {
+        let Self { nodes, free, slots, bids, asks } = self;
+        let levels = match side { Side::Buy => bids, Side::Sell => asks };
+        let Some(level) = levels.get_mut(&price) else { return qty };
+        let remaining = Self::fill_level(nodes, free, slots, level, qty, &mut on_fill);
+        if level.head == NIL { levels.remove(&price); }
+        remaining
+    }
+
+    fn match_best<F: FnMut(&Order, u64)>(&mut self, side: Side, limit: Option<u64>, qty: u64, mut on_fill: F) -> Option<(u64, u64)> {
+        let Self { nodes, free, slots, bids, asks } = self;
+        let mut level = match side { Side::Buy => bids.last_entry(), Side::Sell => asks.first_entry() }?;
+        let price = *level.key();
+        if !crosses(side, price, limit) { return None; }
+        let remaining = Self::fill_level(nodes, free, slots, level.get_mut(), qty, &mut on_fill);
+        if level.get().head == NIL { level.remove(); }
+        Some((price, remaining))
+    }
+
+    fn push_back(&mut self, order: Order) {
+        let id = order.id.0;
+        let node = Node { order, prev: NIL, next: NIL };
+        let slot = match self.free.pop() {
+            Some(s) => { self.nodes[s] = Some(node); s }
+            None => { self.nodes.push(Some(node)); self.nodes.len() - 1 }
+        };
+        self.slots.insert(id, slot);
+        self.append(slot);
+    }
+
+    fn remove(&mut self, side: Side, price: u64, id: OrderId) -> Option<Order> {
+        let slot = *self.slots.get(&id.0)?;
+        let o = &self.node(slot).order;
+        if o.side != side || o.price != price { return None; }
+        Some(self.unlink(side, price, slot))
+    }
+
+    fn update<R>(&mut self, side: Side, price: u64, id: OrderId, f: impl FnOnce(&mut Order) -> R) -> Option<R> {
+        let slot = *self.slots.get(&id.0)?;
+        let o = &mut self.node_mut(slot).order;
+        if o.side != side || o.price != price { return None; }
+        let before = o.qty;
+        let r = f(o);
+        let after = o.qty;
+        let level = self.levels_mut(side).get_mut(&price).unwrap();
+        level.qty = level.qty - before + after;
+        Some(r)
+    }
+
+    // Relinks the node in place: the order keeps its slot, no free-list traffic
+    fn relocate(&mut self, side: Side, price: u64, id: OrderId, new_price: u64, ts: u64) -> bool {
+        let Some(&slot) = self.slots.get(&id.0) else { return false };
+        let o = &self.node(slot).order;
+        if o.side != side || o.price != price { return false; }
+        self.detach(side, price, slot);
+        let o = &mut self.node_mut(slot).order;
+        (o.price, o.ts) = (new_price, ts);
+        self.append(slot);
+        true
+    }
+
+    fn get(&self, side: Side, price: u64, id: OrderId) -> Option<&Order> {
+        let o = &self.node(*self.slots.get(&id.0)?).order;
+        (o.side == side && o.price == price).then_some(o)
+    }
+
+    // Walks toward the head from the order's own slot, so only the orders ahead are visited
+    fn queue_position(&self, side: Side, price: u64, id: OrderId) -> Option<(usize, u64)> {
+        let slot = *self.slots.get(&id.0)?;
+        let node = self.node(slot);
+        if node.order.side != side || node.order.price != price { return None; }
+        let (mut n, mut ahead, mut cur) = (0, 0, node.prev);
+        while cur != NIL {
+            let prev = self.node(cur);
+            n += 1;
+            ahead += prev.order.qty;
+            cur = prev.prev;
+        }
+        Some((n, ahead))
+    }
+
+    fn for_each_level(&self, side: Side, f: &mut dyn FnMut(u64, &mut dyn Iterator<Item = &Order>) -> bool) {
+        let mut visit = |p: u64, l: &Level| {
+            let mut cur = l.head;
+            let mut it = std::iter::from_fn(|| {
+                if cur == NIL { return None; }
+                let n = self.node(cur);
+                cur = n.next;
+                Some(&n.order)
+            });
+            f(p, &mut it)
+        };
+        match side {
+            Side::Buy => { for (p, l) in self.bids.iter().rev() { if !visit(*p, l) { break; } } }
+            Side::Sell => { for (p, l) in self.asks.iter() { if !visit(*p, l) { break; } } }
+        }
+    }
+
+    fn for_each_level_totals(&self, side: Side, f: &mut dyn FnMut(u64, u64, usize) -> bool) {
+        match side {
+            Side::Buy => { for (p, l) in self.bids.iter().rev() { if !f(*p, l.qty, l.orders) { break; } } }
+            Side::Sell => { for (p, l) in self.asks.iter() { if !f(*p, l.qty, l.orders) { break; } } }
+        }
+    }
+}
+
+// One price level in struct-of-arrays form: the fields the hot loops read (id for lookups, qty for
+// fills, queue position and depth sums) sit in their own contiguous arrays, apart from the full
+// records. `orders[i]` is the same order as `ids[i]`/`qtys[i]`; its qty is written together with
+// `qtys[i]` so the records handed out through the trait stay exact.
+#[derive(Default)]
+struct SoaLevel {
+    ids: VecDeque<u64>,
+    qtys: VecDeque<u64>,
+    orders: VecDeque<Order>,
+}
+
+impl SoaLevel {
+    fn find(&self, id: OrderId) -> Option<usize> { self.ids.iter().position(|&x| x == id.0) }
+
+    fn remove(&mut self, i: usize) -> Option<Order> {
+        self.ids.remove(i);
+        self.qtys.remove(i);
+        self.orders.remove(i)
+    }
+
+    // `fill_queue` over the three queues: whole makers pop off all of them, the partial tail is
+    // written to both qty copies
+    fn fill<F: FnMut(&Order, u64)>(&mut self, mut remaining: u64, on_fill: &mut F) -> u64 {
+        while let Some(trade_qty) = self.qtys.front().copied().filter(|&q| q <= remaining) {
+            remaining -= trade_qty;
+            self.ids.pop_front();
+            self.qtys.pop_front();
+            let Some(mut maker) = self.orders.pop_front() else { break };
+            maker.qty = 0;
+            on_fill(&maker, trade_qty);
+        }
+        if remaining == 0 { return 0; }
+        let (Some(maker_qty), Some(maker)) = (self.qtys.front_mut(), self.orders.front_mut()) else { return remaining };
+        *maker_qty -= remaining;
+        maker.qty = *maker_qty;
+        on_fill(maker, remaining);
+        0
+    }
+
+    fn total_qty(&self) -> u64 {
+        let (a, b) = self.qtys.as_slices();
+        a.iter().sum::<u64>() + b.iter().sum::<u64>()
+    }
+}
+
+// BTreeMap(price) of struct-of-arrays levels: cache friendlier matching, cancels and depth
+// aggregation on deep queues, at the cost of writing qty twice per fill
+#[derive(Default)]
+pub struct SoaStorage {
+    bids: BTreeMap<u64, SoaLevel>,
+    asks: BTreeMap<u64, SoaLevel>,
+}
+
+impl SoaStorage {
+    fn side(&self, side: Side) -> &BTreeMap<u64, SoaLevel> {
+        match side { Side::Buy => &self.bids, Side::Sell => &self.asks }
+    }
+
+    fn side_mut(&mut self, side: Side) -> &mut BTreeMap<u64, SoaLevel> {
+        match side { Side::Buy => &mut self.bids, Side::Sell => &mut self.asks }
+    }
+
+    fn levels(&self, side: Side) -> Box<dyn Iterator<Item = (&u64, &SoaLevel)> + '_> {
+        match side { Side::Buy => Box::new(self.bids.iter().rev()), Side::Sell => Box::new(self.asks.iter()) }
+    }
+}
+
+impl BookStorage for SoaStorage {
+    fn best_price(&self, side: Side) -> Option<u64> {
+        match side {
+            Side::Buy => self.bids.last_key_value().map(|(p, _)| *p),
+            Side::Sell => self.asks.first_key_value().map(|(p, _)| *p),
+        }
+    }
+
+    fn match_level<F: FnMut(&Order, u64)>(&mut self, side: Side, price: u64, qty: u64, mut on_fill: F) -> u64 {
+        let book = self.side_mut(side);
+        let Some(level) = book.get_mut(&price) else { return qty };
+        let remaining = level.fill(qty, &mut on_fill);
+        if level.ids.is_empty() { book.remove(&price); }
+        remaining
+    }
+
+    fn match_best<F: FnMut(&Order, u64)>(&mut self, side: Side, limit: Option<u64>, qty: u64, mut on_fill: F) -> Option<(u64, u64)> {
+        let mut level = match side { Side::Buy => self.bids.last_entry(), Side::Sell => self.asks.first_entry() }?;
+        let price = *level.key();
+        if !crosses(side, price, limit) { return None; }
+        let remaining = level.get_mut().fill(qty, &mut on_fill);
+        if level.get().ids.is_empty() { level.remove(); }
+        Some((price, remaining))
+    }
+
+    fn push_back(&mut self, order: Order) {
+        let level = self.side_mut(order.side).entry(order.price).or_default();
+        level.ids.push_back(order.id.0);
+        level.qtys.push_back(order.qty);
+        level.orders.push_back(order);
+    }
+
+    fn remove(&mut self, side: Side, price: u64, id: OrderId) -> Option<Order> {
+        let book = self.side_mut(side);
+        let level = book.get_mut(&price)?;
+        let i = level.find(id)?;
+        let o = level.remove(i);
+        if level.ids.is_empty() { book.remove(&price); }
+        o
+    }
+
+    fn update<R>(&mut self, side: Side, price: u64, id: OrderId, f: impl FnOnce(&mut Order) -> R) -> Option<R> {
+        let level = self.side_mut(side).get_mut(&price)?;
+        let i = level.find(id)?;
+        let r = f(&mut level.orders[i]);
+        level.qtys[i] = level.orders[i].qty;
+        Some(r)
+    }
+
+    fn get(&self, side: Side, price: u64, id: OrderId) -> Option<&Order> {
+        let level = self.side(side).get(&price)?;
+        level.find(id).map(|i| &level.orders[i])
+    }
+
+    fn queue_position(&self, side: Side, price: u64, id: OrderId) -> Option<(usize, u64)> {
+        let level = self.side(side).get(&price)?;
+        let i = level.find(id)?;
+        Some((i, level.qtys.range(..i).sum()))
+    }
+
+    fn for_each_level(&self, side: Side, f: &mut dyn FnMut(u64, &mut dyn Iterator<Item = &Order>) -> bool) {
+        for (p, l) in self.levels(side) { if !f(*p, &mut l.orders.iter()) { break; } }
+    }
+
+    fn for_each_level_totals(&self, side: Side, f: &mut dyn FnMut(u64, u64, usize) -> bool) {
+        for (p, l) in self.levels(side) { if !f(*p, l.total_qty(), l.ids.len()) { break; } }
+    }
+}
+
+const SKIP_HEIGHT: usize = 12;
+
+struct SkipNode {
+    key: u64,
+    price: u64,
+    queue: VecDeque<Order>,
+    height: usize,
+    next: [usize; SKIP_HEIGHT],
+}
+
+// One side's levels as a skip list ordered best first, nodes in an arena with a free list
+struct SkipLevels {
+    nodes: Vec<SkipNode>,
+    free: Vec<usize>,
+    head: [usize; SKIP_HEIGHT],
+    height: usize,
+    rng: u64,
+}
+
+impl Default for SkipLevels {
+    fn default() -> Self { Self { nodes: Vec::new(), free: Vec::new(), head: [NIL; SKIP_HEIGHT], height: 1, rng: 0x9E37_79B9_7F4A_7C15 } }
+}
+
+impl SkipLevels {
+    // Successor of `at` on `lvl`; NIL stands for the head
+    fn next_of(&self, at: usize, lvl: usize) -> usize { if at == NIL { self.head[lvl] } else { self.nodes[at].next[lvl] } }
+
+    fn set_next(&mut self, at: usize, lvl: usize, to: usize) { if at == NIL { self.head[lvl] = to } else { self.nodes[at].next[lvl] = to } }
+
+    // Last node with a key below `key` on every level
+    fn predecessors(&self, key: u64) -> [usize; SKIP_HEIGHT] {
+        let mut preds = [NIL; SKIP_HEIGHT];
+        let mut at = NIL;
+        for lvl in (0..self.height).rev() {
+            loop {
+                let n = self.next_of(at, lvl);
+                if n == NIL || self.nodes[n].key >= key { break; }
+                at = n;
+            }
+            preds[lvl] = at;
+        }
+        preds
+    }
+
+    fn find(&self, key: u64) -> Option<usize> {
+        let n = self.next_of(self.predecessors(key)[0], 0);
+        (n != NIL && self.nodes[n].key == key).then_some(n)
+    }
+
+    // Geometric tower height (p = 1/4) from a fixed-seed xorshift, so layouts are reproducible
+    fn random_height(&mut self) -> usize {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        ((self.rng.trailing_zeros() / 2) as usize + 1).min(SKIP_HEIGHT)
+    }
+
+    fn get_or_insert(&mut self, key: u64, price: u64) -> usize {
+        let preds = self.predecessors(key);
+        let n = self.next_of(preds[0], 0);
+        if n != NIL && self.nodes[n].key == key { return n; }
+        // Levels above the current height have the head as predecessor (NIL in `preds`)
+        let height = self.random_height();
+        self.height = self.height.max(height);
+        let slot = match self.free.pop() {
+            Some(s) => { let node = &mut self.nodes[s]; (node.key, node.price, node.height, node.next) = (key, price, height, [NIL; SKIP_HEIGHT]); s }
+            None => { self.nodes.push(SkipNode { key, price, queue: VecDeque::new(), height, next: [NIL; SKIP_HEIGHT] }); self.nodes.len() - 1 }
+        };
+        for (lvl, &pred) in preds.iter().enumerate().take(height) {
+            self.nodes[slot].next[lvl] = self.next_of(pred, lvl);
+            self.set_next(pred, lvl, slot);
+        }
+        slot
+    }
+
+    // Drop an emptied level; its node (and queue allocation) goes back to the free list
+    fn unlink(&mut self, key: u64) {
+        let preds = self.predecessors(key);
+        let n = self.next_of(preds[0], 0);
+        if n == NIL || self.nodes[n].key != key { return; }
+        for (lvl, &pred) in preds.iter().enumerate().take(self.nodes[n].height) { self.set_next(pred, lvl, self.nodes[n].next[lvl]); }
+        while self.height > 1 && self.head[self.height - 1] == NIL { self.height -= 1; }
+        self.free.push(n);
+    }
+
+    fn first(&self) -> Option<&SkipNode> { (self.head[0] != NIL).then(|| &self.nodes[self.head[0]]) }
+
+    fn iter(&self) -> impl Iterator<Item = &SkipNode> {
+        let mut cur = self.head[0];
+        std::iter::from_fn(move || {
+            if cur == NIL { return None; }
+            let n = &self.nodes[cur];
+            cur = n.next[0];
+            Some(n)
+        })
+    }
+}
+
+// Skip list of price levels per side, best level first (bids keyed by !price, asks by price), each
+// level a VecDeque FIFO. Finding, adding and dropping a level cost O(log levels) regardless of how
+// far apart prices are, and the best level is the list head: meant for crypto-style books with a
+// huge, sparse price range, where a ladder would be mostly empty slots.
+#[derive(Default)]
+pub struct SkipListStorage {
+    bids: SkipLevels,
+    asks: SkipLevels,
+}
+
+impl SkipListStorage {
+    fn key(side: Side, price: u64) -> u64 { match side { Side::Buy => !price, Side::Sell => price } }
+
+    fn side(&self, side: Side) -> &SkipLevels { match side { Side::Buy => &self.bids, Side::Sell => &self.asks } }
+
+    fn side_mut(&mut self, side: Side) -> &mut SkipLevels { match side { Side::Buy => &mut self.bids, Side::Sell => &mut self.asks } }
+}
+
+impl BookStorage for SkipListStorage {
+    fn best_price(&self, side: Side) -> Option<u64> { self.side(side).first().map(|n| n.price) }
+
+    fn match_level<F: FnMut(&Order, u64)>(&mut self, side: Side, price: u64, qty: u64, mut on_fill: F) -> u64 {
+        let key = Self::key(side, price);
+        let levels = self.side_mut(side);
+        let Some(n) = levels.find(key) else { return qty };
+        let remaining = fill_queue(&mut levels.nodes[n].queue, qty, &mut on_fill);
+        if levels.nodes[n].queue.is_empty() { levels.unlink(key); }
+        remaining
+    }
+
+    fn match_best<F: FnMut(&Order, u64)>(&mut self, side: Side, limit: Option<u64>, qty: u64, mut on_fill: F) -> Option<(u64, u64)> {
+        let levels = self.side_mut(side);
+        let n = levels.head[0];
+        let price = levels.nodes.get(n)?.price;
+        if !crosses(side, price, limit) { return None; }
+        let remaining = fill_queue(&mut levels.nodes[n].queue, qty, &mut on_fill);
+        if levels.nodes[n].queue.is_empty() { levels.unlink(Self::key(side, price)); }
+        Some((price, remaining))
+    }
+
+    fn push_back(&mut self, order: Order) {
+        let key = Self::key(order.side, order.price);
+        let levels = self.side_mut(order.side);
+        let n = levels.get_or_insert(key, order.price);
+        levels.nodes[n].queue.push_back(order);
+    }
+
+    fn remove(&mut self, side: Side, price: u64, id: OrderId) -> Option<Order> {
+        let key = Self::key(side, price);
+        let levels = self.side_mut(side);
+        let n = levels.find(key)?;
+        let queue = &mut levels.nodes[n].queue;
+        let o = queue.remove(queue.iter().position(|o| o.id == id)?);
+        if queue.is_empty() { levels.unlink(key); }
+        o
+    }
+
+    fn update<R>(&mut self, side: Side, price: u64, id: OrderId, f: impl FnOnce(&mut Order) -> R) -> Option<R> {
+        let levels = self.side_mut(side);
+        let n = levels.find(Self::key(side, price))?;
+        levels.nodes[n].queue.iter_mut().find(|o| o.id == id).map(f)
+    }
+
+    fn get(&self, side: Side, price: u64, id: OrderId) -> Option<&Order> {
+        let levels = self.side(side);
+        levels.nodes[levels.find(Self::key(side, price))?].queue.iter().find(|o| o.id == id)
+    }
+
+    fn queue_position(&self, side: Side, price: u64, id: OrderId) -> Option<(usize, u64)> {
+        let levels = self.side(side);
+        position_in(&levels.nodes[levels.find(Self::key(side, price))?].queue, id)
+    }
+
+    fn for_each_level(&self, side: Side, f: &mut dyn FnMut(u64, &mut dyn Iterator<Item = &Order>) -> bool) {
+        for n in self.side(side).iter() { if !f(n.price, &mut n.queue.iter()) { break; } }
+    }
+}
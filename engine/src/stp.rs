@@ -0,0 +1,108 @@
+use crate::cancels::{CancelReason, ImplicitCancel};
+use crate::{AccountId, BookStorage, L3Event, OrderBook, OrderId, OrderRequest, Side, Trade, TradeConditions, TradeId};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+// Self-trade prevention (per book, off by default). Before an incoming order trades with the
+// resting order at the front of the opposite touch, their owners are compared by STP group; on a
+// match the configured action applies instead of a trade:
+//   CancelResting  the resting order is cancelled (implicit cancel, reason SelfTrade), matching continues
+//   CancelIncoming the incoming remainder is cancelled, nothing rests
+//   CancelBoth     both of the above
+//   Decrement      both are reduced by the smaller qty, reported as a Trade with STP_DECREMENT
+//                  (no position, last-price or market data effect besides the qty change)
+// Account 0 (unattributed flow) never self-matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StpAction {
+    CancelResting,
+    CancelIncoming,
+    CancelBoth,
+    Decrement,
+}
+
+// Account -> STP group mapping, shared by every book it is attached to and updatable while they
+// run (e.g. from the thread owning the mapping while workers own the books). Accounts without an
+// entry form a group of their own.
+#[derive(Debug, Clone, Default)]
+pub struct StpGroups {
+    groups: Arc<RwLock<HashMap<AccountId, u64>>>,
+}
+
+impl StpGroups {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn assign(&self, account: AccountId, group: u64) { self.groups.write().unwrap().insert(account, group); }
+
+    pub fn unassign(&self, account: AccountId) { self.groups.write().unwrap().remove(&account); }
+
+    pub fn group_of(&self, account: AccountId) -> Option<u64> { self.groups.read().unwrap().get(&account).copied() }
+
+    pub fn same_group(&self, a: AccountId, b: AccountId) -> bool {
+        if a.0 == 0 || b.0 == 0 { return false; }
+        if a == b { return true; }
+        let groups = self.groups.read().unwrap();
+        matches!((groups.get(&a), groups.get(&b)), (Some(x), Some(y)) if x == y)
+    }
+}
+
+// Resting order at the front of a level
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Head {
+    pub id: OrderId,
+    pub price: u64,
+    pub qty: u64,
+    pub reserve: u64,
+    pub account: AccountId,
+    pub correlation: u64,
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn set_self_trade_prevention(&mut self, action: Option<StpAction>) { self.stp = action; }
+
+    pub fn self_trade_prevention(&self) -> Option<StpAction> { self.stp }
+
+    // Replace the book's group mapping, e.g. with one handle shared across books
+    pub fn set_stp_groups(&mut self, groups: StpGroups) { self.stp_groups = groups; }
+
+    pub fn stp_groups(&self) -> &StpGroups { &self.stp_groups }
+
+    pub(crate) fn best_head(&self, side: Side) -> Option<Head> {
+        let mut head = None;
+        self.storage.for_each_level(side, &mut |price, orders| {
+            head = orders.next().map(|o| Head { id: o.id, price, qty: o.qty, reserve: o.reserve_qty, account: o.account, correlation: o.correlation });
+            false
+        });
+        head
+    }
+
+    // Apply `action` to a self-match between the incoming order and `maker`; returns true when the
+    // incoming remainder is cancelled
+    pub(crate) fn prevent_self_trade(&mut self, action: StpAction, taker: OrderId, req: &OrderRequest, maker: Head, remaining: &mut u64, trades_out: &mut Vec<Trade>) -> bool {
+        let (maker_side, price) = (req.side.opposite(), maker.price);
+        if matches!(action, StpAction::CancelResting | StpAction::CancelBoth) { self.cancel_self_match(maker_side, price, maker.id); }
+        if matches!(action, StpAction::CancelIncoming | StpAction::CancelBoth) { return true; }
+        if action == StpAction::Decrement {
+            let qty = (*remaining).min(maker.qty);
+            *remaining -= qty;
+            let output_seq = self.next_output_seq();
+            let maker_remaining = maker.qty - qty + maker.reserve;
+            trades_out.push(Trade { trade_id: TradeId(0), taker_id: taker, maker_id: maker.id, price, qty, taker_side: req.side, taker_correlation: req.correlation, maker_correlation: maker.correlation, taker_account: req.account, maker_account: maker.account, maker_remaining, maker_fully_filled: maker_remaining == 0, ts: self.ts, conditions: TradeConditions::STP_DECREMENT | self.emission.conditions(), output_seq });
+            if qty == maker.qty {
+                match self.storage.remove(maker_side, price, maker.id) { Some(o) => self.refill_or_unindex(&o), None => { self.index.remove(&maker.id.0); } }
+                self.note_l3(|_| L3Event::Delete { id: maker.id });
+                if !self.refills.is_empty() { self.replenish(); }
+            } else {
+                self.storage.update(maker_side, price, maker.id, |o| { o.qty -= qty; o.order_qty -= qty; });
+                self.note_l3(|_| L3Event::Update { id: maker.id, qty: maker.qty - qty });
+            }
+        }
+        false
+    }
+
+    fn cancel_self_match(&mut self, side: Side, price: u64, id: OrderId) {
+        self.index.remove(&id.0);
+        let Some(o) = self.storage.remove(side, price, id) else { return };
+        self.note_cancel(ImplicitCancel::of(&o, CancelReason::SelfTrade));
+        self.note_l3(|_| L3Event::Delete { id });
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{AccountId, OrderId, Side};
+use std::collections::{HashMap, VecDeque};
+
+// Market-abuse surveillance over order lifecycle events, per owner (account):
+// - cancel-to-trade ratio: cancels / fills, once an owner has at least `min_cancels` cancels
+// - quote stuffing: order and cancel messages within the sliding `window_micros`
+// - layering: a fill on one side while the owner rests orders on `layering_min_levels` or more
+//   distinct prices on the other side
+// Each alert fires when its metric crosses the threshold and re-arms once it drops back below,
+// so a persistent condition produces one alert rather than one per event. A zero threshold
+// disables that check. Timestamps are caller supplied (e.g. micros) and must not go backwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleEvent {
+    Accepted { account: AccountId, id: OrderId, side: Side, price: Option<u64>, qty: u64, ts: u64 },
+    // One per fill, for both the taker and the maker; `remaining` 0 means the order is done
+    Filled { account: AccountId, id: OrderId, side: Side, qty: u64, remaining: u64, ts: u64 },
+    Cancelled { account: AccountId, id: OrderId, ts: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurveillanceConfig {
+    pub window_micros: u64,
+    pub max_messages_per_window: usize,
+    pub max_cancel_to_trade: f64,
+    pub min_cancels: u64,
+    pub layering_min_levels: usize,
+}
+
+impl Default for SurveillanceConfig {
+    fn default() -> Self {
+        Self { window_micros: 1_000_000, max_messages_per_window: 1_000, max_cancel_to_trade: 20.0, min_cancels: 50, layering_min_levels: 3 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertKind {
+    CancelToTrade,
+    QuoteStuffing,
+    Layering,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Alert {
+    pub account: AccountId,
+    pub kind: AlertKind,
+    pub value: f64,
+    pub ts: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OwnerMetrics {
+    pub orders: u64,
+    pub cancels: u64,
+    pub fills: u64,
+    pub cancel_to_trade: f64,
+    pub messages_in_window: usize,
+    // Largest number of distinct resting price levels seen on one side
+    pub max_layers: usize,
+}
+
+#[derive(Default)]
+struct Owner {
+    metrics: OwnerMetrics,
+    window: VecDeque<u64>,
+    resting: HashMap<OrderId, (Side, u64)>,
+    levels: [HashMap<u64, usize>; 2], // resting orders per price, [buy, sell]
+    tripped: [bool; 3],               // per AlertKind
+}
+
+impl Owner {
+    fn distinct_levels(&self, side: Side) -> usize { self.levels[side as usize].len() }
+
+    fn forget(&mut self, id: OrderId) {
+        let Some((side, price)) = self.resting.remove(&id) else { return };
+        let levels = &mut self.levels[side as usize];
+        if let Some(n) = levels.get_mut(&price) {
+            *n -= 1;
+            if *n == 0 { levels.remove(&price); }
+        }
+    }
+}
+
+pub struct Surveillance {
+    config: SurveillanceConfig,
+    owners: HashMap<AccountId, Owner>,
+    alerts: Vec<Alert>,
+}
+
+impl Surveillance {
+    pub fn new(config: SurveillanceConfig) -> Self { Self { config, owners: HashMap::new(), alerts: Vec::new() } }
+
+    pub fn metrics(&self, account: AccountId) -> Option<OwnerMetrics> { self.owners.get(&account).map(|o| o.metrics) }
+
+    pub fn drain_alerts(&mut self) -> Vec<Alert> { std::mem::take(&mut self.alerts) }
+
+    pub fn on_event(&mut self, ev: &LifecycleEvent) {
+        let cfg = self.config;
+        let (account, ts) = match *ev {
+            LifecycleEvent::Accepted { account, ts, .. } | LifecycleEvent::Filled { account, ts, .. } | LifecycleEvent::Cancelled { account, ts, .. } => (account, ts),
+        };
+        let owner = self.owners.entry(account).or_default();
+        let mut layering = None;
+        match *ev {
+            LifecycleEvent::Accepted { id, side, price, .. } => {
+                owner.metrics.orders += 1;
+                owner.window.push_back(ts);
+                if let Some(p) = price {
+                    owner.resting.insert(id, (side, p));
+                    *owner.levels[side as usize].entry(p).or_default() += 1;
+                    owner.metrics.max_layers = owner.metrics.max_layers.max(owner.distinct_levels(side));
+                }
+            }
+            LifecycleEvent::Filled { id, side, remaining, .. } => {
+                owner.metrics.fills += 1;
+                layering = Some(owner.distinct_levels(side.opposite()));
+                if remaining == 0 { owner.forget(id); }
+            }
+            LifecycleEvent::Cancelled { id, .. } => {
+                owner.metrics.cancels += 1;
+                owner.window.push_back(ts);
+                owner.forget(id);
+            }
+        }
+        while owner.window.front().is_some_and(|&t| t + cfg.window_micros <= ts) { owner.window.pop_front(); }
+        let m = &mut owner.metrics;
+        m.messages_in_window = owner.window.len();
+        m.cancel_to_trade = m.cancels as f64 / m.fills.max(1) as f64;
+
+        let ratio = (cfg.max_cancel_to_trade > 0.0 && m.cancels >= cfg.min_cancels).then_some(m.cancel_to_trade);
+        let checks = [
+            (AlertKind::CancelToTrade, ratio.map(|r| (r, r > cfg.max_cancel_to_trade))),
+            (AlertKind::QuoteStuffing, (cfg.max_messages_per_window > 0).then_some((m.messages_in_window as f64, m.messages_in_window > cfg.max_messages_per_window))),
+            (AlertKind::Layering, layering.filter(|_| cfg.layering_min_levels > 0).map(|n| (n as f64, n >= cfg.layering_min_levels))),
+        ];
+        for (kind, check) in checks {
+            let Some((value, over)) = check else { continue };
+            let tripped = &mut owner.tripped[kind as usize];
+            if over && !*tripped { self.alerts.push(Alert { account, kind, value, ts }); }
+            *tripped = over;
+        }
+    }
+}
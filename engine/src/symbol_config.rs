@@ -0,0 +1,64 @@
+use crate::{BookStorage, EngineError, OrderBook, OrderRequest, OrderType};
+
+// Per-instrument trading rules (`set_symbol_config`, off by default), checked on every new order after
+// the pre-match hooks and before it gets an id, and on amends, reprices and reduces of resting orders:
+//   tick              limit and stop prices must be a multiple of it
+//   lot               quantities (order, iceberg display, amend and reduce sizes) must be a multiple of it
+//   min_qty, max_qty  bounds on the quantity of a new order or an amend
+// Market orders have no price to check. Every order that passes enters as whole lots, so fills, cuts
+// and reserve refills keep resting quantities on the lot grid. A config with a zero tick or lot, or
+// with min_qty above max_qty, is refused with `EngineError::InvalidConfig` and the previous one stays.
+// Like the price band, the config is a book setting: not in snapshots, set again after a restore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SymbolConfig {
+    pub tick: u64,
+    pub lot: u64,
+    pub min_qty: u64,
+    pub max_qty: u64,
+}
+
+impl Default for SymbolConfig {
+    fn default() -> Self { Self { tick: 1, lot: 1, min_qty: 1, max_qty: u64::MAX } }
+}
+
+impl SymbolConfig {
+    pub fn check_price(&self, price: u64) -> Result<(), EngineError> {
+        if price.is_multiple_of(self.tick.max(1)) { Ok(()) } else { Err(EngineError::InvalidTick { price, tick: self.tick }) }
+    }
+
+    pub fn check_lot(&self, qty: u64) -> Result<(), EngineError> {
+        if qty.is_multiple_of(self.lot.max(1)) { Ok(()) } else { Err(EngineError::InvalidLot { qty, lot: self.lot }) }
+    }
+
+    pub fn check_qty(&self, qty: u64) -> Result<(), EngineError> {
+        self.check_lot(qty)?;
+        if (self.min_qty..=self.max_qty).contains(&qty) { Ok(()) } else { Err(EngineError::InvalidQty { qty, min: self.min_qty, max: self.max_qty }) }
+    }
+
+    pub fn validate(&self) -> Result<(), EngineError> {
+        if self.tick == 0 || self.lot == 0 { return Err(EngineError::InvalidConfig(format!("tick {} and lot {} must be non-zero", self.tick, self.lot))); }
+        if self.min_qty > self.max_qty { return Err(EngineError::InvalidConfig(format!("min_qty {} above max_qty {}", self.min_qty, self.max_qty))); }
+        Ok(())
+    }
+
+    pub fn check_order(&self, req: &OrderRequest) -> Result<(), EngineError> {
+        if req.order_type == OrderType::Limit { self.check_price(req.price)?; }
+        if let Some(stop) = req.stop_price { self.check_price(stop)?; }
+        if let Some(display) = req.display_qty { self.check_lot(display)?; }
+        self.check_qty(req.qty)
+    }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn set_symbol_config(&mut self, config: Option<SymbolConfig>) -> Result<(), EngineError> {
+        if let Some(c) = &config { c.validate()?; }
+        self.symbol_config = config;
+        Ok(())
+    }
+
+    pub fn symbol_config(&self) -> Option<SymbolConfig> { self.symbol_config }
+
+    pub(crate) fn check_symbol_config(&self, check: impl FnOnce(&SymbolConfig) -> Result<(), EngineError>) -> Result<(), EngineError> {
+        self.symbol_config.as_ref().map_or(Ok(()), check)
+    }
+}
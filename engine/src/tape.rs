@@ -0,0 +1,69 @@
+use crate::{BookStorage, Emission, OrderBook, Trade, TradeConditions};
+use std::collections::VecDeque;
+
+// Bounded tape of a book's recent trades (off by default), for showing recent activity without
+// archiving the trade stream. Trades get ids from 1 in execution order as they are recorded,
+// after post-trade hooks ran, so the tape shows what callers were handed. Once `capacity` is
+// reached the oldest entry is dropped; `trades_since` then starts at the oldest one still retained
+// (a first id above `trade_id + 1` means some were missed). STP decrements are not trades and are
+// not recorded. Tape ids count only what callers were handed; `Trade::trade_id` also counts
+// suppressed trades.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapeEntry {
+    pub trade_id: u64,
+    pub trade: Trade,
+}
+
+#[derive(Debug, Clone)]
+pub struct TradeTape {
+    capacity: usize,
+    last_id: u64,
+    entries: VecDeque<TapeEntry>,
+}
+
+impl TradeTape {
+    pub fn new(capacity: usize) -> Self { Self { capacity, last_id: 0, entries: VecDeque::with_capacity(capacity) } }
+
+    // Returns the trade id, 0 when the trade is not recorded
+    pub fn record(&mut self, trade: &Trade) -> u64 {
+        if trade.conditions.contains(TradeConditions::STP_DECREMENT) || self.capacity == 0 { return 0; }
+        if self.entries.len() == self.capacity { self.entries.pop_front(); }
+        self.last_id += 1;
+        self.entries.push_back(TapeEntry { trade_id: self.last_id, trade: trade.clone() });
+        self.last_id
+    }
+
+    // Retained trades with an id above `trade_id`, oldest first
+    pub fn since(&self, trade_id: u64) -> impl Iterator<Item = &TapeEntry> {
+        let skip = self.entries.front().map_or(0, |e| trade_id.saturating_sub(e.trade_id - 1) as usize);
+        self.entries.iter().skip(skip)
+    }
+
+    // The most recent `n` trades, oldest first
+    pub fn last_n(&self, n: usize) -> impl Iterator<Item = &TapeEntry> { self.entries.iter().skip(self.entries.len().saturating_sub(n)) }
+
+    // Id of the most recent trade, 0 before the first
+    pub fn last_id(&self) -> u64 { self.last_id }
+
+    pub fn len(&self) -> usize { self.entries.len() }
+
+    pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    // Keep the last `capacity` trades; None turns the tape off and drops it
+    pub fn set_trade_tape(&mut self, capacity: Option<usize>) { self.tape = capacity.map(TradeTape::new); }
+
+    pub fn trade_tape(&self) -> Option<&TradeTape> { self.tape.as_ref() }
+
+    pub fn trades_since(&self, trade_id: u64) -> impl Iterator<Item = &TapeEntry> { self.tape.iter().flat_map(move |t| t.since(trade_id)) }
+
+    pub fn last_n_trades(&self, n: usize) -> impl Iterator<Item = &TapeEntry> { self.tape.iter().flat_map(move |t| t.last_n(n)) }
+
+    // Executions handed to the caller, for the tape and the session stats
+    pub(crate) fn note_trades(&mut self, trades: &[Trade]) {
+        for t in trades { self.stats.record(t); }
+        if self.emission == Emission::Suppressed { return; }
+        if let Some(tape) = self.tape.as_mut() { for t in trades { tape.record(t); } }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{AccountId, BookStorage, EngineError, OrderBook};
+use std::collections::{HashMap, VecDeque};
+
+// Order entry throttle (per instrument, off by default): each account may enter at most `max_orders`
+// new orders within any `window_micros` of the book's clock (see `clock`). The next one is rejected
+// with `EngineError::Throttled`, whose `ThrottleHit` carries the account, its rate in the window, the
+// limit and a retry-after hint (until the oldest entry leaves the window); hits are also kept for
+// `drain_throttle_hits` so a gateway can answer with a standard "slow down". The check comes after the
+// halt and cancel-only checks and before any other; an order rejected later still used its slot.
+// Cancels, reprices, amends and stop activations are never throttled. Entry times are not part of
+// snapshots. The ingestor applies the same window per producer (see `ingestor::throttle`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryThrottle {
+    pub max_orders: u32,
+    pub window_micros: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThrottleHit {
+    pub owner: AccountId,
+    // Entries in the window when the order arrived
+    pub rate: u32,
+    pub limit: u32,
+    pub window_micros: u64,
+    pub retry_after_micros: u64,
+}
+
+impl EntryThrottle {
+    // Record an entry at `now` in `recent` (entry times, oldest first), or return (rate, retry after)
+    pub fn admit(&self, recent: &mut VecDeque<u64>, now: u64) -> Result<(), (u32, u64)> {
+        while recent.front().is_some_and(|&t| now.saturating_sub(t) >= self.window_micros) { recent.pop_front(); }
+        if recent.len() >= self.max_orders as usize {
+            let oldest = recent.front().copied().unwrap_or(now);
+            return Err((recent.len() as u32, (oldest + self.window_micros).saturating_sub(now)));
+        }
+        recent.push_back(now);
+        Ok(())
+    }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    pub fn set_entry_throttle(&mut self, throttle: Option<EntryThrottle>) { self.throttle = throttle.map(|t| (t, HashMap::new())); }
+
+    pub fn entry_throttle(&self) -> Option<EntryThrottle> { self.throttle.as_ref().map(|(t, _)| *t) }
+
+    pub fn drain_throttle_hits(&mut self) -> Vec<ThrottleHit> { std::mem::take(&mut self.throttle_hits) }
+
+    pub(crate) fn check_throttle(&mut self, owner: AccountId) -> Result<(), EngineError> {
+        let Some((throttle, recent)) = &mut self.throttle else { return Ok(()) };
+        let now = self.clock.now_micros();
+        let Err((rate, retry_after_micros)) = throttle.admit(recent.entry(owner).or_default(), now) else { return Ok(()) };
+        let hit = ThrottleHit { owner, rate, limit: throttle.max_orders, window_micros: throttle.window_micros, retry_after_micros };
+        self.throttle_hits.push(hit);
+        Err(EngineError::Throttled(hit))
+    }
+}
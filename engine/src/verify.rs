@@ -0,0 +1,54 @@
+use crate::{BookStorage, EngineError, OrderBook, Side};
+
+// Full consistency check of the resting book, for tests and soak runs (it walks every order, so it
+// is not meant for the hot path). Checked between commands:
+//   levels    every order rests on its own side at its own price with quantity showing (an iceberg
+//             slice is refilled before the command returns), no more open than its order quantity,
+//             and levels keep strict time priority
+//   index     the id index holds exactly the resting orders, each at its side and price
+//   clock     ids and priority timestamps were handed out by this book (up to `last_order_id` / `ts`)
+//   touch     the book is not crossed
+//   client    every client order id maps to an id this book assigned
+// The first violation found is returned as `EngineError::Invariant`.
+impl<S: BookStorage> OrderBook<S> {
+    pub fn verify(&self) -> Result<(), EngineError> {
+        let mut resting = 0;
+        let mut error = None;
+        for side in [Side::Buy, Side::Sell] {
+            self.storage.for_each_level(side, &mut |price, orders| {
+                let mut last_ts = 0;
+                for o in orders {
+                    resting += 1;
+                    let problem = if o.side != side || o.price != price {
+                        "rests on the wrong side or level"
+                    } else if o.qty == 0 {
+                        "rests with nothing displayed"
+                    } else if o.qty + o.reserve_qty > o.order_qty {
+                        "has more open than its order quantity"
+                    } else if o.ts <= last_ts {
+                        "is out of time priority"
+                    } else if o.id.0 > self.last_order_id().0 || o.ts > self.ts {
+                        "has an id or timestamp the book never assigned"
+                    } else if self.index.get(&o.id.0) != Some(&(side, price)) {
+                        "is missing from the id index"
+                    } else {
+                        last_ts = o.ts;
+                        continue;
+                    };
+                    error = Some(EngineError::Invariant(format!("order {} {problem}", o.id.0)));
+                    return false;
+                }
+                true
+            });
+            if let Some(e) = error { return Err(e); }
+        }
+        if resting != self.index.len() { return Err(EngineError::Invariant(format!("id index holds {} orders, levels {resting}", self.index.len()))); }
+        if let (Some((bid, _)), Some((ask, _))) = (self.best_bid(), self.best_ask()) {
+            if bid >= ask { return Err(EngineError::Invariant(format!("book crossed: bid {bid} ask {ask}"))); }
+        }
+        if let Some(id) = self.client_ids.values().find(|id| id.0 > self.last_order_id().0) {
+            return Err(EngineError::Invariant(format!("client order id maps to unassigned order {}", id.0)));
+        }
+        Ok(())
+    }
+}
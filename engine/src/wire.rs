@@ -0,0 +1,94 @@
+// rkyv wire format: gateways and journal readers access frames in place (`access_*`) and walk
+// archived commands without decoding the whole frame or allocating per message.
+use crate::{AccountId, ArchivedAccountId, ArchivedClientOrderId, ArchivedCommand, ArchivedOrderId, ArchivedSide, BookStorage, ClientOrderId, Command, CommandResult, EngineError, ImplicitCancel, OrderBook, OrderId, Side, Trade};
+use rkyv::rancor::Error;
+use rkyv::util::AlignedVec;
+use rkyv::{Archive, Deserialize, Serialize};
+
+// A sequenced batch of commands as sent by a gateway
+#[derive(Archive, Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[rkyv(derive(Debug))]
+pub struct CommandBatch {
+    pub commands: Vec<Command>,
+}
+
+// Output of one applied batch: `seq` of its last command, the trades it produced and the orders it
+// removed as a side effect
+#[derive(Archive, Serialize, Deserialize, Debug, Clone, Default)]
+#[rkyv(derive(Debug))]
+pub struct EventFrame {
+    pub seq: u64,
+    pub trades: Vec<Trade>,
+    pub cancels: Vec<ImplicitCancel>,
+}
+
+pub fn encode_commands(batch: &CommandBatch) -> Result<AlignedVec, Error> { rkyv::to_bytes::<Error>(batch) }
+
+// Reuses `buf` (cleared first) to avoid a fresh allocation per frame
+pub fn encode_commands_in(batch: &CommandBatch, mut buf: AlignedVec) -> Result<AlignedVec, Error> {
+    buf.clear();
+    rkyv::api::high::to_bytes_in::<_, Error>(batch, buf)
+}
+
+pub fn encode_events(frame: &EventFrame) -> Result<AlignedVec, Error> { rkyv::to_bytes::<Error>(frame) }
+
+// Validates and views a frame in place; `bytes` must be 16-byte aligned (e.g. an `AlignedVec`)
+pub fn access_commands(bytes: &[u8]) -> Result<&ArchivedCommandBatch, Error> { rkyv::access::<ArchivedCommandBatch, Error>(bytes) }
+
+pub fn access_events(bytes: &[u8]) -> Result<&ArchivedEventFrame, Error> { rkyv::access::<ArchivedEventFrame, Error>(bytes) }
+
+impl From<&ArchivedSide> for Side {
+    fn from(s: &ArchivedSide) -> Self {
+        match s { ArchivedSide::Buy => Side::Buy, ArchivedSide::Sell => Side::Sell }
+    }
+}
+
+impl From<&ArchivedOrderId> for OrderId {
+    fn from(id: &ArchivedOrderId) -> Self { OrderId(id.0.to_native()) }
+}
+
+impl From<&ArchivedAccountId> for AccountId {
+    fn from(a: &ArchivedAccountId) -> Self { AccountId(a.0.to_native()) }
+}
+
+impl From<&ArchivedClientOrderId> for ClientOrderId {
+    fn from(c: &ArchivedClientOrderId) -> Self { ClientOrderId(c.0.to_native()) }
+}
+
+impl From<&ArchivedCommand> for Command {
+    fn from(c: &ArchivedCommand) -> Self {
+        match c {
+            ArchivedCommand::Limit { seq, side, price, qty, tif, account } => Command::Limit { seq: seq.to_native(), side: side.into(), price: price.to_native(), qty: qty.to_native(), tif: rkyv::deserialize::<_, Error>(tif).expect("plain time in force"), account: account.into() },
+            ArchivedCommand::Market { seq, side, qty, account } => Command::Market { seq: seq.to_native(), side: side.into(), qty: qty.to_native(), account: account.into() },
+            ArchivedCommand::Cancel { seq, id, account } => Command::Cancel { seq: seq.to_native(), id: id.into(), account: account.as_ref().map(AccountId::from) },
+            ArchivedCommand::CancelClient { seq, account, client_id } => Command::CancelClient { seq: seq.to_native(), account: account.into(), client_id: client_id.into() },
+            // Plain data all the way down, so deserializing cannot fail
+            ArchivedCommand::Submit { seq, req } => Command::Submit { seq: seq.to_native(), req: rkyv::deserialize::<_, Error>(req).expect("plain order request") },
+            ArchivedCommand::Reprice { seq, id, new_price } => Command::Reprice { seq: seq.to_native(), id: id.into(), new_price: new_price.to_native() },
+            ArchivedCommand::Amend { seq, id, new_price, new_qty } => Command::Amend { seq: seq.to_native(), id: id.into(), new_price: new_price.to_native(), new_qty: new_qty.to_native() },
+            ArchivedCommand::Reduce { seq, id, qty_delta } => Command::Reduce { seq: seq.to_native(), id: id.into(), qty_delta: qty_delta.to_native() },
+            ArchivedCommand::EndOfDay { seq } => Command::EndOfDay { seq: seq.to_native() },
+            ArchivedCommand::Resume { seq } => Command::Resume { seq: seq.to_native() },
+            ArchivedCommand::Reference { seq, value } => Command::Reference { seq: seq.to_native(), value: rkyv::deserialize::<_, Error>(value).expect("plain reference value") },
+            ArchivedCommand::Session { seq, state } => Command::Session { seq: seq.to_native(), state: rkyv::deserialize::<_, Error>(state).expect("plain session state") },
+        }
+    }
+}
+
+impl ArchivedCommandBatch {
+    pub fn len(&self) -> usize { self.commands.len() }
+
+    pub fn is_empty(&self) -> bool { self.commands.is_empty() }
+
+    pub fn iter_commands(&self) -> impl Iterator<Item = Command> + '_ { self.commands.iter().map(Command::from) }
+}
+
+impl<S: BookStorage> OrderBook<S> {
+    // Apply an archived batch directly from the receive buffer, with the same seq checks as the owned path;
+    // the commands are decoded into `scratch` (cleared first), so a reused buffer saves the allocation
+    pub fn process_archived_batch_into(&mut self, batch: &ArchivedCommandBatch, scratch: &mut Vec<Command>, trades_out: &mut Vec<Trade>) -> Result<Vec<CommandResult>, EngineError> {
+        scratch.clear();
+        scratch.extend(batch.iter_commands());
+        self.process_commands_batch_checked_into(scratch, trades_out)
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{AccountId, BookStorage, Command, OrderBook, OrderId, Side, TimeInForce, Trade};
+use std::time::{Duration, Instant};
+
+// Synthetic order flow for benchmarks and load tests. Each step draws an operation from the
+// weighted mix against the current book: adds rest behind the touch with a distance skewed toward
+// it (most flow sits within a few ticks, a thin tail goes deep), a share of adds is priced through
+// the touch, cancels and amends pick a random order placed earlier (which may have filled since,
+// as with real cancel races), and markets take a random size. Amends are cancel/replace: the order
+// moves one tick and takes a new size, losing its queue position. Deterministic for a given seed.
+// `apply_sequenced` drives the book through `process_command` instead, for runs that journal or
+// replay the flow; there amends are `Command::Amend` and keep the order's id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkloadMix {
+    // Relative weights
+    pub add: u32,
+    pub cancel: u32,
+    pub amend: u32,
+    pub market: u32,
+}
+
+impl WorkloadMix {
+    pub const BALANCED: Self = Self { add: 50, cancel: 30, amend: 10, market: 10 };
+    pub const MAKER_HEAVY: Self = Self { add: 45, cancel: 40, amend: 13, market: 2 };
+    pub const TAKER_HEAVY: Self = Self { add: 50, cancel: 10, amend: 5, market: 35 };
+
+    fn total(&self) -> u32 { self.add + self.cancel + self.amend + self.market }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkloadConfig {
+    pub mix: WorkloadMix,
+    pub seed: u64,
+    pub mid: u64,
+    pub tick: u64,
+    // Adds rest up to this many ticks behind the touch
+    pub max_depth_ticks: u64,
+    // Percentage of adds priced through the opposite touch (marketable limits)
+    pub cross_pct: u32,
+    pub max_qty: u64,
+    // Time in force of adds made through `apply_sequenced` (Day orders expire at each end of day)
+    pub tif: TimeInForce,
+}
+
+impl Default for WorkloadConfig {
+    fn default() -> Self {
+        Self { mix: WorkloadMix::BALANCED, seed: 0x9E37_79B9_7F4A_7C15, mid: 10_000, tick: 1, max_depth_ticks: 50, cross_pct: 10, max_qty: 10, tif: TimeInForce::Gtc }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkloadOp {
+    Add { side: Side, price: u64, qty: u64 },
+    Cancel { id: OrderId },
+    Amend { id: OrderId, price: u64, qty: u64 },
+    Market { side: Side, qty: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkloadReport {
+    pub ops: u64,
+    pub trades: u64,
+    pub elapsed: Duration,
+    // Per-operation latency percentiles in nanoseconds
+    pub p50_nanos: u64,
+    pub p90_nanos: u64,
+    pub p99_nanos: u64,
+    pub p999_nanos: u64,
+    pub max_nanos: u64,
+}
+
+impl WorkloadReport {
+    pub fn ops_per_sec(&self) -> f64 { self.ops as f64 / self.elapsed.as_secs_f64().max(f64::MIN_POSITIVE) }
+}
+
+pub struct Workload {
+    cfg: WorkloadConfig,
+    rng: u64,
+    live: Vec<(OrderId, u64)>, // placed adds (id, price), possibly filled since
+}
+
+impl Workload {
+    pub fn new(cfg: WorkloadConfig) -> Self { Self { cfg, rng: cfg.seed.max(1), live: Vec::new() } }
+
+    fn next(&mut self) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+
+    fn below(&mut self, n: u64) -> u64 { self.next() % n.max(1) }
+
+    fn qty(&mut self) -> u64 { 1 + self.below(self.cfg.max_qty) }
+
+    // `levels` price levels a side around `mid`, `qty` each
+    pub fn seed_book<S: BookStorage>(&mut self, ob: &mut OrderBook<S>, levels: u64, qty: u64) {
+        let (mid, tick) = (self.cfg.mid, self.cfg.tick);
+        for i in 1..=levels {
+            for (side, price) in [(Side::Buy, mid.saturating_sub(i * tick)), (Side::Sell, mid + i * tick)] {
+                if let Ok((id, _, remaining)) = ob.submit_limit(side, price, qty) {
+                    if remaining > 0 { self.live.push((id, price)); }
+                }
+            }
+        }
+    }
+
+    pub fn next_op<S: BookStorage>(&mut self, ob: &OrderBook<S>) -> WorkloadOp {
+        let mix = self.cfg.mix;
+        let mut pick = self.below(mix.total() as u64) as u32;
+        let side = if self.next() & 1 == 0 { Side::Buy } else { Side::Sell };
+        if pick < mix.market { return WorkloadOp::Market { side, qty: self.qty() }; }
+        pick -= mix.market;
+        if pick < mix.cancel + mix.amend && !self.live.is_empty() {
+            let i = self.below(self.live.len() as u64) as usize;
+            let (id, price) = self.live.swap_remove(i);
+            if pick < mix.cancel { return WorkloadOp::Cancel { id }; }
+            let price = if self.next() & 1 == 0 { price + self.cfg.tick } else { price.saturating_sub(self.cfg.tick).max(self.cfg.tick) };
+            return WorkloadOp::Amend { id, price, qty: self.qty() };
+        }
+        WorkloadOp::Add { side, price: self.add_price(ob, side), qty: self.qty() }
+    }
+
+    fn add_price<S: BookStorage>(&mut self, ob: &OrderBook<S>, side: Side) -> u64 {
+        let (tick, mid) = (self.cfg.tick, self.cfg.mid);
+        let touch = match side { Side::Buy => ob.best_bid().map_or(mid.saturating_sub(tick), |b| b.0), Side::Sell => ob.best_ask().map_or(mid + tick, |a| a.0) };
+        if self.below(100) < self.cfg.cross_pct as u64 {
+            let through = 1 + self.below(3);
+            return match side {
+                Side::Buy => ob.best_ask().map_or(touch, |a| a.0) + (through - 1) * tick,
+                Side::Sell => ob.best_bid().map_or(touch, |b| b.0).saturating_sub((through - 1) * tick).max(tick),
+            };
+        }
+        // u^2 skews the distance toward the touch
+        let u = self.below(1_000);
+        let ticks = u * u * self.cfg.max_depth_ticks / 1_000_000;
+        match side { Side::Buy => touch.saturating_sub(ticks * tick).max(tick), Side::Sell => touch + ticks * tick }
+    }
+
+    pub fn apply<S: BookStorage>(&mut self, ob: &mut OrderBook<S>, op: WorkloadOp, trades_out: &mut Vec<Trade>) {
+        match op {
+            WorkloadOp::Add { side, price, qty } => {
+                if let Ok((id, remaining)) = ob.submit_limit_into(side, price, qty, trades_out) {
+                    if remaining > 0 { self.live.push((id, price)); }
+                }
+            }
+            WorkloadOp::Cancel { id } => { let _ = ob.cancel(id); }
+            WorkloadOp::Amend { id, price, qty } => {
+                let Ok(old) = ob.cancel(id) else { return };
+                if let Ok((id, remaining)) = ob.submit_limit_into(old.side, price, qty, trades_out) {
+                    if remaining > 0 { self.live.push((id, price)); }
+                }
+            }
+            WorkloadOp::Market { side, qty } => { let _ = ob.submit_market_into(side, qty, trades_out); }
+        }
+    }
+
+    // Apply `op` as the sequenced command `seq`, returning the command for journaling or replay
+    pub fn apply_sequenced<S: BookStorage>(&mut self, ob: &mut OrderBook<S>, op: WorkloadOp, seq: u64, trades_out: &mut Vec<Trade>) -> Command {
+        let (cmd, price) = match op {
+            WorkloadOp::Add { side, price, qty } => (Command::Limit { seq, side, price, qty, tif: self.cfg.tif, account: AccountId(0) }, price),
+            WorkloadOp::Cancel { id } => (Command::Cancel { seq, id, account: None }, 0),
+            WorkloadOp::Amend { id, price, qty } => (Command::Amend { seq, id, new_price: price, new_qty: qty }, price),
+            WorkloadOp::Market { side, qty } => (Command::Market { seq, side, qty, account: AccountId(0) }, 0),
+        };
+        if let (Ok((id, remaining)), WorkloadOp::Add { .. } | WorkloadOp::Amend { .. }) = (ob.process_command(cmd, trades_out), op) {
+            if remaining > 0 { self.live.push((id, price)); }
+        }
+        cmd
+    }
+
+    // Forget placed orders that no longer rest, so long runs do not accumulate them
+    pub fn prune<S: BookStorage>(&mut self, ob: &OrderBook<S>) { self.live.retain(|(id, _)| ob.queue_position(*id).is_some()); }
+
+    // Run `n` operations, timing each one (generation excluded)
+    pub fn run<S: BookStorage>(&mut self, ob: &mut OrderBook<S>, n: u64) -> WorkloadReport {
+        let mut lat: Vec<u64> = Vec::with_capacity(n as usize);
+        let mut trades_buf = Vec::with_capacity(64);
+        let mut trades = 0u64;
+        let mut elapsed = Duration::ZERO;
+        for _ in 0..n {
+            let op = self.next_op(ob);
+            let start = Instant::now();
+            self.apply(ob, op, &mut trades_buf);
+            let took = start.elapsed();
+            elapsed += took;
+            lat.push(took.as_nanos() as u64);
+            trades += trades_buf.len() as u64;
+            trades_buf.clear();
+        }
+        lat.sort_unstable();
+        let pct = |q: f64| lat.get(((lat.len().saturating_sub(1)) as f64 * q) as usize).copied().unwrap_or(0);
+        WorkloadReport { ops: n, trades, elapsed, p50_nanos: pct(0.5), p90_nanos: pct(0.9), p99_nanos: pct(0.99), p999_nanos: pct(0.999), max_nanos: lat.last().copied().unwrap_or(0) }
+    }
+}
@@ -0,0 +1,38 @@
+use match_engine::{AccountId, Command, EngineError, OrderBook, OrderId, OrderRequest, Side, TimeInForce};
+
+#[test]
+fn trades_carry_both_accounts() {
+    let mut ob = OrderBook::new();
+    let mut trades = Vec::new();
+    let mut cmds = [
+        Command::Limit { seq: 1, side: Side::Sell, price: 100, qty: 3, tif: TimeInForce::Gtc, account: AccountId(7) },
+        Command::Market { seq: 2, side: Side::Buy, qty: 1, account: AccountId(9) },
+    ];
+    ob.process_commands_batch_checked_into(&mut cmds, &mut trades).unwrap();
+    ob.submit_into(OrderRequest::limit(Side::Buy, 100, 1).with_account(AccountId(11)), &mut trades).unwrap();
+    assert_eq!(trades.iter().map(|t| (t.taker_account, t.maker_account, t.qty)).collect::<Vec<_>>(), vec![(AccountId(9), AccountId(7), 1), (AccountId(11), AccountId(7), 1)]);
+    // Unattributed orders trade as account 0
+    let (_, trades, _) = ob.submit_market(Side::Buy, 1).unwrap();
+    assert_eq!((trades[0].taker_account, trades[0].maker_account), (AccountId(0), AccountId(7)));
+}
+
+#[test]
+fn owned_cancel_checks_the_owner() {
+    let mut ob = OrderBook::new();
+    let (resting, _) = ob.submit_into(OrderRequest::limit(Side::Buy, 99, 2).with_account(AccountId(3)), &mut Vec::new()).unwrap();
+    let (stop, _) = ob.submit_into(OrderRequest::market(Side::Sell, 1).with_stop(90).with_account(AccountId(3)), &mut Vec::new()).unwrap();
+    for id in [resting, stop] {
+        assert!(matches!(ob.cancel_owned(id, AccountId(4)), Err(EngineError::Rejected(_))));
+    }
+    assert_eq!((ob.best_bid(), ob.stop_count()), (Some((99, 2)), 1));
+    assert_eq!(ob.cancel_owned(stop, AccountId(3)).unwrap().id, stop);
+
+    // The same through sequenced cancels; an unowned cancel still removes any order
+    let mut cmds = [Command::Cancel { seq: 1, id: resting, account: Some(AccountId(4)) }];
+    assert!(matches!(ob.process_commands_batch_checked_into(&mut cmds, &mut Vec::new()).unwrap()[..], [Err(EngineError::Rejected(_))]));
+    let mut cmds = [Command::Cancel { seq: 2, id: resting, account: Some(AccountId(3)) }];
+    assert_eq!(ob.process_commands_batch_checked_into(&mut cmds, &mut Vec::new()).unwrap(), vec![Ok((resting, 0))]);
+    let (other, _, _) = ob.submit_limit(Side::Sell, 105, 1).unwrap();
+    assert!(ob.process_command(Command::Cancel { seq: 3, id: other, account: None }, &mut Vec::new()).is_ok());
+    assert!(matches!(ob.cancel_owned(OrderId(99), AccountId(3)), Err(EngineError::UnknownOrder)));
+}
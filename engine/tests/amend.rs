@@ -0,0 +1,46 @@
+use match_engine::{Attribution, BTreeStorage, BookStorage, Command, EngineError, L3Event, LadderStorage, OrderBook, OrderRequest, Side, SkipListStorage, SlabStorage, SoaStorage};
+
+fn decrease_keeps_priority_and_increase_loses_it<S: BookStorage>(mut ob: OrderBook<S>) {
+    let (a, _, _) = ob.submit_limit(Side::Buy, 99, 5).unwrap();
+    let (b, _, _) = ob.submit_limit(Side::Buy, 99, 3).unwrap();
+    assert_eq!(ob.amend(a, 99, 2, &mut Vec::new()).unwrap(), 2);
+    assert_eq!((ob.queue_position(a), ob.queue_position(b)), (Some((0, 0)), Some((1, 2))));
+    assert_eq!(ob.amend(a, 99, 4, &mut Vec::new()).unwrap(), 4);
+    assert_eq!((ob.queue_position(b), ob.queue_position(a)), (Some((0, 0)), Some((1, 3))));
+    assert_eq!(ob.top_n(5).0, vec![(99, 7, 2)]);
+    // A price change moves it to the back of the new level at the new quantity
+    ob.amend(b, 98, 1, &mut Vec::new()).unwrap();
+    assert_eq!(ob.top_n(5).0, vec![(99, 4, 1), (98, 1, 1)]);
+    assert!(matches!(ob.amend(a, 99, 0, &mut Vec::new()), Err(EngineError::Rejected(_))));
+    ob.cancel(a).unwrap();
+    assert!(matches!(ob.amend(a, 99, 1, &mut Vec::new()), Err(EngineError::UnknownOrder)));
+}
+
+#[test]
+fn amend_priority_on_every_backend() {
+    decrease_keeps_priority_and_increase_loses_it(OrderBook::<BTreeStorage>::default());
+    decrease_keeps_priority_and_increase_loses_it(OrderBook::<LadderStorage>::default());
+    decrease_keeps_priority_and_increase_loses_it(OrderBook::<SlabStorage>::default());
+    decrease_keeps_priority_and_increase_loses_it(OrderBook::<SoaStorage>::default());
+    decrease_keeps_priority_and_increase_loses_it(OrderBook::<SkipListStorage>::default());
+}
+
+#[test]
+fn marketable_amend_trades_in_batch_and_icebergs_cut_reserve_first() {
+    let mut ob = OrderBook::new();
+    let (ask, _, _) = ob.submit_limit(Side::Sell, 101, 3).unwrap();
+    let (bid, _, _) = ob.submit_limit(Side::Buy, 99, 5).unwrap();
+    let mut trades = Vec::new();
+    let results = ob.process_commands_batch_checked_into(&mut [Command::Amend { seq: 1, id: bid, new_price: 101, new_qty: 4 }], &mut trades).unwrap();
+    assert_eq!(results, vec![Ok((bid, 1))]);
+    assert_eq!(trades.iter().map(|t| (t.maker_id, t.price, t.qty)).collect::<Vec<_>>(), vec![(ask, 101, 3)]);
+    assert_eq!((ob.best_bid(), ob.best_ask()), (Some((101, 1)), None));
+
+    let (ice, _, _) = ob.submit(OrderRequest::limit(Side::Sell, 110, 10).iceberg(4)).unwrap();
+    ob.set_market_data(Some(Attribution::Anonymous));
+    assert_eq!(ob.amend(ice, 110, 5, &mut Vec::new()).unwrap(), 5);
+    assert_eq!(ob.best_ask(), Some((110, 4)));
+    assert_eq!(ob.amend(ice, 110, 3, &mut Vec::new()).unwrap(), 3);
+    assert_eq!(ob.best_ask(), Some((110, 3)));
+    assert_eq!(ob.drain_l3(), vec![L3Event::Update { id: ice, qty: 4 }, L3Event::Update { id: ice, qty: 3 }]);
+}
@@ -0,0 +1,36 @@
+use match_engine::{AmendCrossPolicy, EngineError, OrderBook, Repriced, Side};
+
+#[test]
+fn crossing_amend_takes_by_default_and_can_be_rejected() {
+    let mut ob = OrderBook::new();
+    ob.submit_limit(Side::Sell, 101, 3).unwrap();
+    let (bid, _, _) = ob.submit_limit(Side::Buy, 99, 5).unwrap();
+    let mut trades = Vec::new();
+    assert_eq!(ob.amend(bid, 101, 4, &mut trades).unwrap(), 1);
+    assert_eq!(trades.len(), 1);
+
+    ob.set_amend_cross_policy(AmendCrossPolicy::Reject);
+    ob.submit_limit(Side::Sell, 103, 2).unwrap();
+    assert!(matches!(ob.amend(bid, 103, 9, &mut trades), Err(EngineError::Rejected(_))));
+    assert!(matches!(ob.reprice(bid, 104, &mut trades), Err(EngineError::Rejected(_))));
+    // Left exactly as it was; a move that does not cross still goes through
+    assert_eq!(ob.best_bid(), Some((101, 1)));
+    assert_eq!(ob.amend(bid, 102, 6, &mut trades).unwrap(), 6);
+    assert_eq!((trades.len(), ob.best_bid()), (1, Some((102, 6))));
+    assert!(ob.drain_repriced().is_empty());
+}
+
+#[test]
+fn reprice_policy_moves_the_amend_behind_the_touch() {
+    let mut ob = OrderBook::new();
+    ob.set_amend_cross_policy(AmendCrossPolicy::Reprice { tick: 2 });
+    ob.submit_limit(Side::Buy, 95, 3).unwrap();
+    ob.submit_limit(Side::Sell, 110, 3).unwrap();
+    let (ask, _, _) = ob.submit_limit(Side::Sell, 120, 4).unwrap();
+    let mut trades = Vec::new();
+    assert_eq!(ob.amend(ask, 90, 2, &mut trades).unwrap(), 2);
+    assert_eq!(ob.reprice(ask, 95, &mut trades).unwrap(), 2);
+    assert!(trades.is_empty());
+    assert_eq!(ob.drain_repriced(), vec![Repriced { id: ask, original: 90, adjusted: 97, output_seq: 1 }, Repriced { id: ask, original: 95, adjusted: 97, output_seq: 2 }]);
+    assert_eq!((ob.best_bid(), ob.best_ask()), (Some((95, 3)), Some((97, 2))));
+}
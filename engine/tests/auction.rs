@@ -0,0 +1,62 @@
+use match_engine::{EngineError, OrderBook, OrderRequest, SessionState, Side, TimeInForce};
+
+fn moo(side: Side, qty: u64) -> OrderRequest { OrderRequest::market(side, qty).with_tif(TimeInForce::AtOpen) }
+
+fn loc(side: Side, price: u64, qty: u64) -> OrderRequest { OrderRequest::limit(side, price, qty).with_tif(TimeInForce::AtClose) }
+
+#[test]
+fn auction_orders_only_accepted_in_their_phase() {
+    let mut ob = OrderBook::new();
+    assert!(matches!(ob.submit(moo(Side::Buy, 1)), Err(EngineError::Rejected(_))));
+    ob.set_session(SessionState::PreOpen);
+    assert!(matches!(ob.submit(loc(Side::Buy, 100, 1)), Err(EngineError::Rejected(_))));
+    let (id, trades, remaining) = ob.submit(moo(Side::Buy, 3)).unwrap();
+    assert!(trades.is_empty() && remaining == 3);
+    // Held outside the continuous book
+    assert_eq!(ob.best_bid(), None);
+    assert_eq!(ob.auction_orders().len(), 1);
+    assert_eq!(ob.cancel(id).unwrap().qty, 3);
+    assert!(ob.auction_orders().is_empty());
+}
+
+#[test]
+fn opening_uncross_crosses_auction_and_book_orders() {
+    let mut ob = OrderBook::new();
+    let (ask_101, _, _) = ob.submit_limit(Side::Sell, 101, 4).unwrap();
+    let (ask_103, _, _) = ob.submit_limit(Side::Sell, 103, 5).unwrap();
+    ob.set_session(SessionState::PreOpen);
+    let (buy_mkt, _, _) = ob.submit(moo(Side::Buy, 6)).unwrap();
+    let (buy_lmt, _, _) = ob.submit(OrderRequest::limit(Side::Buy, 104, 2).with_tif(TimeInForce::AtOpen)).unwrap();
+    let (sell_lmt, _, _) = ob.submit(OrderRequest::limit(Side::Sell, 104, 1).with_tif(TimeInForce::AtOpen)).unwrap();
+
+    let change = ob.set_session(SessionState::Open);
+    // Demand 8 at <= 103, supply 9 at 103 vs 4 at 101: 103 maximises volume (8)
+    assert!(change.trades.iter().all(|t| t.price == 103));
+    assert_eq!(change.trades.iter().map(|t| t.qty).sum::<u64>(), 8);
+    let fills = |id| change.trades.iter().filter(|t| t.taker_id == id || t.maker_id == id).map(|t| t.qty).sum::<u64>();
+    assert_eq!((fills(buy_mkt), fills(buy_lmt), fills(ask_101), fills(ask_103)), (6, 2, 4, 4));
+    // The auction sell limit above the uncross price did not trade and expires
+    assert_eq!(change.expired.iter().map(|o| o.id).collect::<Vec<_>>(), vec![sell_lmt]);
+    assert_eq!(ob.best_ask(), Some((103, 1)));
+    assert_eq!(ob.last_trade_price(), Some(103));
+}
+
+#[test]
+fn auction_orders_expire_when_the_auction_does_not_happen() {
+    let mut ob = OrderBook::new();
+    ob.submit_limit(Side::Buy, 99, 1).unwrap();
+    ob.set_session(SessionState::PreClose);
+    let (a, _, _) = ob.submit(loc(Side::Sell, 100, 2)).unwrap();
+    let (b, _, _) = ob.submit(loc(Side::Buy, 98, 2)).unwrap();
+    // No crossing interest: nothing trades, both expire at the close
+    let change = ob.set_session(SessionState::Closed);
+    assert!(change.trades.is_empty());
+    assert_eq!(change.expired.iter().map(|o| o.id).collect::<Vec<_>>(), vec![a, b]);
+
+    // Phase abandoned (back to continuous trading): expired without an uncross
+    ob.set_session(SessionState::PreClose);
+    ob.submit(loc(Side::Sell, 90, 1)).unwrap();
+    let change = ob.set_session(SessionState::Open);
+    assert!(change.trades.is_empty() && change.expired.len() == 1);
+    assert_eq!(ob.best_bid(), Some((99, 1)));
+}
@@ -0,0 +1,84 @@
+use match_engine::{Command, OrderBook, OrderRequest, OrderState, SessionState, Side, TimeInForce};
+
+fn ladder(ob: &mut OrderBook, levels: u64) {
+    for i in 0..levels { ob.submit_limit(Side::Sell, 101 + i, 2).unwrap(); }
+}
+
+#[test]
+fn sweep_is_sliced_across_later_commands() {
+    let mut ob = OrderBook::new();
+    ladder(&mut ob, 6);
+    ob.set_match_budget(Some(2));
+    let mut trades = Vec::new();
+    let (sweep, left) = ob.process_command(Command::Limit { seq: 0, side: Side::Buy, price: 105, qty: 13, tif: TimeInForce::Gtc, account: Default::default() }, &mut trades).unwrap();
+    assert_eq!((left, trades.len(), ob.pending_continuations()), (9, 2, 1));
+    // Paused: not resting, so no bid yet
+    assert_eq!(ob.best_bid(), None);
+
+    // The next command first runs a slice, then itself
+    trades.clear();
+    let (bid, _) = ob.process_command(Command::Limit { seq: 1, side: Side::Buy, price: 90, qty: 1, tif: TimeInForce::Gtc, account: Default::default() }, &mut trades).unwrap();
+    assert_eq!(trades.iter().map(|t| (t.taker_id, t.price)).collect::<Vec<_>>(), vec![(sweep, 103), (sweep, 104)]);
+    assert_eq!(ob.best_bid(), Some((90, 1)));
+
+    // A quiet book is drained with Resume; the last level is within budget and the rest rests
+    trades.clear();
+    ob.process_command(Command::Resume { seq: 2 }, &mut trades).unwrap();
+    assert_eq!(trades.iter().map(|t| t.price).collect::<Vec<_>>(), vec![105]);
+    assert_eq!(ob.pending_continuations(), 0);
+    let status = ob.order_status(sweep).unwrap();
+    assert_eq!((status.state, status.original_qty, status.open_qty, status.filled_qty), (OrderState::PartiallyFilled, 13, 3, 10));
+    assert_eq!((ob.best_bid(), ob.queue_position(bid)), (Some((105, 3)), Some((0, 0))));
+    assert!(ob.verify().is_ok());
+}
+
+#[test]
+fn cancels_session_changes_and_fok() {
+    let mut ob = OrderBook::new();
+    ladder(&mut ob, 6);
+    ob.set_match_budget(Some(1));
+    let (a, _, left) = ob.submit(OrderRequest::market(Side::Buy, 5)).unwrap();
+    assert_eq!(left, 3);
+    let cancelled = ob.cancel(a).unwrap();
+    assert_eq!((cancelled.qty, cancelled.order_qty), (3, 5));
+    assert_eq!(ob.pending_continuations(), 0);
+
+    // A session change runs paused sweeps to the end first
+    ob.submit(OrderRequest::market(Side::Buy, 5)).unwrap();
+    let change = ob.set_session(SessionState::Halted);
+    assert_eq!(change.trades.iter().map(|t| t.price).collect::<Vec<_>>(), vec![103, 104]);
+    ob.set_session(SessionState::Open);
+
+    // Fill-or-kill is all or nothing, never sliced
+    let (_, trades, left) = ob.submit(OrderRequest::market(Side::Buy, 3).with_tif(TimeInForce::Fok)).unwrap();
+    assert_eq!((trades.len(), left, ob.pending_continuations()), (2, 0, 0));
+}
+
+#[test]
+fn paused_sweeps_survive_a_snapshot() {
+    let mut ob = OrderBook::new();
+    ladder(&mut ob, 8);
+    ob.set_match_budget(Some(2));
+    let (sweep, _, _) = ob.submit(OrderRequest::limit(Side::Buy, 105, 13).with_correlation(7)).unwrap();
+    ob.submit(OrderRequest::market(Side::Buy, 7)).unwrap();
+    assert_eq!(ob.pending_continuations(), 2);
+
+    let mut bytes = Vec::new();
+    ob.save_snapshot(&mut bytes).unwrap();
+    let mut restored = [OrderBook::from_snapshot(&ob.snapshot()), OrderBook::load_snapshot(bytes.as_slice()).unwrap()];
+    for book in &mut restored {
+        book.set_match_budget(Some(2));
+        assert_eq!((book.pending_continuations(), book.state_hash()), (2, ob.state_hash()));
+    }
+
+    // The restored queue resumes where the saved one would have, down to the resting remainder
+    let mut expected = Vec::new();
+    while ob.resume_continuation(&mut expected).is_some() {}
+    for book in &mut restored {
+        let mut trades = Vec::new();
+        while book.resume_continuation(&mut trades).is_some() {}
+        assert_eq!(trades, expected);
+        assert_eq!(book.order_status(sweep), ob.order_status(sweep));
+        assert_eq!(book.snapshot(), ob.snapshot());
+    }
+}
@@ -0,0 +1,107 @@
+use match_engine::{AccountId, ClientIdRetention, ClientOrderId, Command, EngineError, ManualClock, OrderBook, OrderRequest, Side};
+
+fn order(side: Side, price: u64, account: u64, client_id: u64) -> OrderRequest {
+    OrderRequest::limit(side, price, 1).with_account(AccountId(account)).with_client_id(ClientOrderId(client_id))
+}
+
+#[test]
+fn duplicate_client_ids_are_rejected_per_account() {
+    let mut ob = OrderBook::new();
+    let mut trades = Vec::new();
+    let (first, _) = ob.submit_into(order(Side::Buy, 99, 1, 10), &mut trades).unwrap();
+    assert_eq!(ob.order_id_by_client(AccountId(1), ClientOrderId(10)), Some(first));
+    // The same client id is free for another account, but not reusable by the same one
+    let (other, _) = ob.submit_into(order(Side::Buy, 98, 2, 10), &mut trades).unwrap();
+    assert_eq!(ob.order_id_by_client(AccountId(2), ClientOrderId(10)), Some(other));
+    assert!(matches!(ob.submit_into(order(Side::Sell, 105, 1, 10), &mut trades), Err(EngineError::DuplicateClientId(ClientOrderId(10)))));
+    assert_eq!(ob.best_ask(), None);
+    // Still taken once the order is gone; a rejected order does not claim its id
+    ob.cancel(first).unwrap();
+    assert!(matches!(ob.submit_into(order(Side::Buy, 99, 1, 10), &mut trades), Err(EngineError::DuplicateClientId(ClientOrderId(10)))));
+    ob.set_cancel_only(true);
+    assert!(ob.submit_into(order(Side::Buy, 99, 1, 11), &mut trades).is_err());
+    ob.set_cancel_only(false);
+    assert_eq!(ob.order_id_by_client(AccountId(1), ClientOrderId(11)), None);
+    // End of day starts a new session of ids
+    ob.process_command(Command::EndOfDay { seq: 1 }, &mut trades).unwrap();
+    assert_eq!(ob.order_id_by_client(AccountId(2), ClientOrderId(10)), None);
+    assert!(ob.submit_into(order(Side::Buy, 99, 1, 10), &mut trades).is_ok());
+}
+
+#[test]
+fn cancel_by_client_id() {
+    let mut ob = OrderBook::new();
+    let (id, _) = ob.submit_into(order(Side::Sell, 101, 5, 1), &mut Vec::new()).unwrap();
+    assert!(matches!(ob.cancel_by_client_id(AccountId(6), ClientOrderId(1)), Err(EngineError::UnknownOrder)));
+    assert_eq!(ob.cancel_by_client_id(AccountId(5), ClientOrderId(1)).unwrap().id, id);
+    assert_eq!(ob.best_ask(), None);
+
+    // Sequenced: the command reports the engine id it cancelled
+    let (id, _) = ob.submit_into(order(Side::Sell, 101, 5, 2), &mut Vec::new()).unwrap();
+    let mut cmds = [Command::CancelClient { seq: 1, account: AccountId(5), client_id: ClientOrderId(2) }];
+    assert_eq!(ob.process_commands_batch_checked_into(&mut cmds, &mut Vec::new()).unwrap(), vec![Ok((id, 0))]);
+    assert!(ob.process_command(Command::CancelClient { seq: 2, account: AccountId(5), client_id: ClientOrderId(2) }, &mut Vec::new()).is_err());
+}
+
+#[test]
+fn retention_window_forgets_old_ids_but_not_working_orders() {
+    let clock = ManualClock::new(0);
+    let mut ob = OrderBook::new();
+    ob.set_clock(clock.clone());
+    ob.set_client_id_retention(Some(ClientIdRetention { max_ids: Some(3), max_age_micros: Some(1_000) }));
+    let mut trades = Vec::new();
+    // A resting order and two that trade away at once
+    let (resting, _) = ob.submit_into(order(Side::Buy, 90, 1, 1), &mut trades).unwrap();
+    ob.submit_limit(Side::Sell, 100, 2).unwrap();
+    for c in [2, 3] { ob.submit_into(order(Side::Buy, 100, 1, c), &mut trades).unwrap(); }
+    assert_eq!(ob.client_id_count(), 3);
+    // Over the count: the resting order's id is kept and the oldest finished one goes
+    ob.submit_into(order(Side::Buy, 80, 1, 4), &mut trades).unwrap();
+    assert_eq!(ob.client_id_count(), 3);
+    assert_eq!(ob.order_id_by_client(AccountId(1), ClientOrderId(1)), Some(resting));
+    assert_eq!(ob.order_id_by_client(AccountId(1), ClientOrderId(2)), None);
+    assert!(ob.submit_into(order(Side::Buy, 80, 1, 2), &mut trades).is_ok());
+    assert!(matches!(ob.submit_into(order(Side::Buy, 80, 1, 4), &mut trades), Err(EngineError::DuplicateClientId(_))));
+
+    // Past the age limit, ids of finished orders are free again; cancel by client id still works for resting ones
+    clock.advance(1_000);
+    ob.cancel(ob.order_id_by_client(AccountId(1), ClientOrderId(4)).unwrap()).unwrap();
+    clock.advance(1_000);
+    ob.submit_into(order(Side::Buy, 70, 1, 5), &mut trades).unwrap();
+    ob.submit_into(order(Side::Buy, 70, 1, 6), &mut trades).unwrap();
+    assert_eq!(ob.order_id_by_client(AccountId(1), ClientOrderId(3)), None);
+    assert_eq!(ob.order_id_by_client(AccountId(1), ClientOrderId(4)), None);
+    assert_eq!(ob.cancel_by_client_id(AccountId(1), ClientOrderId(1)).unwrap().id, resting);
+}
+
+#[test]
+fn client_ids_and_their_window_survive_a_snapshot() {
+    let clock = ManualClock::new(0);
+    let mut ob = OrderBook::new();
+    ob.set_clock(clock.clone());
+    let retention = ClientIdRetention { max_ids: None, max_age_micros: Some(1_000) };
+    ob.set_client_id_retention(Some(retention));
+    let mut trades = Vec::new();
+    let (resting, _) = ob.submit_into(order(Side::Buy, 90, 1, 1), &mut trades).unwrap();
+    clock.advance(600);
+    let (cancelled, _) = ob.submit_into(order(Side::Buy, 80, 1, 2), &mut trades).unwrap();
+    ob.cancel(cancelled).unwrap();
+
+    let mut bytes = Vec::new();
+    ob.save_snapshot(&mut bytes).unwrap();
+    for mut restored in [OrderBook::from_snapshot(&ob.snapshot()), OrderBook::load_snapshot(bytes.as_slice()).unwrap()] {
+        restored.set_clock(clock.clone());
+        assert_eq!((restored.client_id_retention(), restored.client_id_count()), (Some(retention), 2));
+        assert!(matches!(restored.submit_into(order(Side::Buy, 80, 1, 2), &mut trades), Err(EngineError::DuplicateClientId(_))));
+        assert_eq!(restored.snapshot(), ob.snapshot());
+        // Terms carry over: id 2 was entered at 600, so it is free again at 1_600 and not before
+        clock.set(1_500);
+        restored.submit_into(order(Side::Buy, 70, 1, 3), &mut trades).unwrap();
+        assert!(restored.order_id_by_client(AccountId(1), ClientOrderId(2)).is_some());
+        clock.set(1_600);
+        restored.submit_into(order(Side::Buy, 70, 1, 4), &mut trades).unwrap();
+        assert_eq!(restored.order_id_by_client(AccountId(1), ClientOrderId(2)), None);
+        assert_eq!(restored.cancel_by_client_id(AccountId(1), ClientOrderId(1)).unwrap().id, resting);
+        clock.set(600);
+    }
+}
@@ -0,0 +1,70 @@
+use match_engine::workload::{Workload, WorkloadConfig};
+use match_engine::{Attribution, DepthDelta, Emission, OrderBook, OrderRequest, Side};
+use std::collections::BTreeMap;
+
+#[test]
+fn level_totals_on_add_fill_and_cancel() {
+    let mut ob = OrderBook::new();
+    ob.submit_limit(Side::Sell, 101, 4).unwrap();
+    assert!(ob.drain_depth().is_empty());
+    ob.set_depth_feed(true);
+    ob.submit_limit(Side::Sell, 101, 2).unwrap();
+    ob.submit(OrderRequest::limit(Side::Buy, 99, 9).iceberg(3)).unwrap();
+    ob.submit_market(Side::Buy, 5).unwrap();
+    let (bid, _, _) = ob.submit_limit(Side::Buy, 99, 1).unwrap();
+    ob.cancel(bid).unwrap();
+    let d = |side, price, new_qty, output_seq| DepthDelta { side, price, new_qty, output_seq };
+    assert_eq!(ob.drain_depth(), vec![
+        d(Side::Sell, 101, 6, 1),
+        d(Side::Buy, 99, 3, 2),
+        // The market order fills the resting 4 then 1 of the next, each trade numbered before its delta
+        d(Side::Sell, 101, 2, 4), d(Side::Sell, 101, 1, 6),
+        d(Side::Buy, 99, 4, 7), d(Side::Buy, 99, 3, 8),
+    ]);
+    // Deltas do not need the L3 feed, nor leave anything in it
+    assert!(ob.drain_l3().is_empty());
+    ob.set_depth_feed(false);
+    ob.submit_limit(Side::Buy, 98, 1).unwrap();
+    assert!(!ob.depth_feed() && ob.drain_depth().is_empty());
+}
+
+// Full depth from `top_n`, keyed by (side, price)
+fn levels(ob: &OrderBook) -> BTreeMap<(u8, u64), u64> {
+    let (bids, asks) = ob.top_n(usize::MAX);
+    bids.into_iter().map(|(p, q, _)| ((0, p), q)).chain(asks.into_iter().map(|(p, q, _)| ((1, p), q))).collect()
+}
+
+#[test]
+fn deltas_keep_a_depth_view_in_step() {
+    for l3 in [None, Some(Attribution::Anonymous)] {
+        let mut ob = OrderBook::new();
+        let mut workload = Workload::new(WorkloadConfig { seed: 7, ..WorkloadConfig::default() });
+        workload.seed_book(&mut ob, 10, 4);
+        ob.set_market_data(l3);
+        ob.set_depth_feed(true);
+        let mut view = levels(&ob);
+        let mut trades = Vec::new();
+        for i in 0..5_000 {
+            let op = workload.next_op(&ob);
+            workload.apply(&mut ob, op, &mut trades);
+            if i % 97 == 0 { ob.submit(OrderRequest::limit(Side::Buy, 990, 3).with_emission(Emission::Suppressed)).unwrap(); }
+            if i % 10 == 0 {
+                for DepthDelta { side, price, new_qty, .. } in ob.drain_depth() {
+                    let key = (side as u8, price);
+                    if new_qty == 0 { view.remove(&key); } else { view.insert(key, new_qty); }
+                }
+                ob.drain_l3();
+            }
+        }
+        for DepthDelta { side, price, new_qty, .. } in ob.drain_depth() {
+            let key = (side as u8, price);
+            if new_qty == 0 { view.remove(&key); } else { view.insert(key, new_qty); }
+        }
+        // Suppressed orders move their level silently; the rest must match exactly
+        let mut book = levels(&ob);
+        book.retain(|k, _| k.1 != 990);
+        view.retain(|k, _| k.1 != 990);
+        assert_eq!(view, book);
+        assert!(trades.len() > 100);
+    }
+}
@@ -0,0 +1,35 @@
+use match_engine::{AccountId, run_differential, BTreeStorage, BookStorage, Command, DiffOp, LadderStorage, OrderBook, OrderId, Side, SkipListStorage, SlabStorage, SoaStorage, TimeInForce};
+
+// Random command sequences in a narrow price band so orders cross, queue and get cancelled often;
+// cancels aim at recent ids (live, filled or unknown)
+fn ops(seed: u64, n: usize) -> Vec<DiffOp> {
+    let mut x = seed;
+    let mut next_id = 0u64;
+    (0..n).map(|_| {
+        x ^= x << 13; x ^= x >> 7; x ^= x << 17;
+        let side = if x & 1 == 0 { Side::Buy } else { Side::Sell };
+        let qty = (x >> 8) % 8; // includes 0
+        match (x >> 4) % 20 {
+            0 => DiffOp::SnapshotRestore,
+            1..=4 => DiffOp::Cmd(Command::Cancel { seq: 0, id: OrderId(next_id.saturating_sub((x >> 16) % 10)), account: None }),
+            5..=6 => { next_id += 1; DiffOp::Cmd(Command::Market { seq: 0, side, qty, account: AccountId(0) }) }
+            _ => { next_id += 1; DiffOp::Cmd(Command::Limit { seq: 0, side, price: 95 + (x >> 20) % 11, qty, tif: TimeInForce::Gtc, account: AccountId(0) }) }
+        }
+    }).collect()
+}
+
+fn check<S: BookStorage>(storage: fn() -> S) {
+    for seed in 1..=20u64 {
+        let ops = ops(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15), 3_000);
+        if let Err(e) = run_differential(&mut OrderBook::with_storage(storage()), &ops) { panic!("seed {}: {}", seed, e); }
+    }
+}
+
+#[test]
+fn every_backend_matches_the_reference_model() {
+    check(BTreeStorage::default);
+    check(LadderStorage::default);
+    check(SlabStorage::default);
+    check(SoaStorage::default);
+    check(SkipListStorage::default);
+}
@@ -0,0 +1,44 @@
+use match_engine::{AccountId, Attribution, Command, Emission, OrderBook, OrderRequest, SessionState, Side, TimeInForce, TradeConditions};
+
+#[test]
+fn suppressed_orders_change_the_book_without_output() {
+    let mut ob = OrderBook::new();
+    ob.set_market_data(Some(Attribution::Anonymous));
+    ob.set_trade_tape(Some(16));
+    let mut trades = Vec::new();
+    let seed = |req: OrderRequest| req.with_emission(Emission::Suppressed);
+    ob.submit_into(seed(OrderRequest::limit(Side::Sell, 101, 5)), &mut trades).unwrap();
+    ob.submit_into(seed(OrderRequest::limit(Side::Sell, 102, 5)), &mut trades).unwrap();
+    let (_, remaining) = ob.submit_into(seed(OrderRequest::limit(Side::Buy, 101, 2)), &mut trades).unwrap();
+    assert_eq!(remaining, 0);
+    assert!(trades.is_empty() && ob.drain_l3().is_empty());
+    assert_eq!(ob.trade_tape().unwrap().len(), 0);
+    // The fill still happened: the book and session stats moved
+    assert_eq!(ob.best_ask(), Some((101, 3)));
+    assert_eq!((ob.session_stats().last, ob.session_stats().volume), (Some(101), 2));
+    // Live flow afterwards is emitted as usual
+    ob.submit_limit_into(Side::Buy, 101, 1, &mut trades).unwrap();
+    assert_eq!((trades.len(), trades[0].conditions), (1, TradeConditions::NONE));
+    assert!(!ob.drain_l3().is_empty());
+}
+
+#[test]
+fn replayed_commands_mark_trades_and_cancels() {
+    let mut ob = OrderBook::new();
+    ob.set_trade_tape(Some(16));
+    let mut trades = Vec::new();
+    ob.submit_into(OrderRequest::limit(Side::Sell, 100, 5), &mut trades).unwrap();
+    ob.submit_into(OrderRequest::limit(Side::Sell, 105, 1).with_tif(TimeInForce::Day), &mut trades).unwrap();
+    ob.process_command_as(Command::Market { seq: 1, side: Side::Buy, qty: 2, account: AccountId(0) }, Emission::Replay, &mut trades).unwrap();
+    ob.submit_into(OrderRequest::market(Side::Buy, 1).with_emission(Emission::Replay), &mut trades).unwrap();
+    ob.submit_limit_into(Side::Buy, 100, 1, &mut trades).unwrap();
+    let replayed: Vec<bool> = trades.iter().map(|t| t.conditions.contains(TradeConditions::REPLAY)).collect();
+    assert_eq!(replayed, [true, true, false]);
+    let taped: Vec<bool> = ob.trades_since(0).map(|e| e.trade.conditions.contains(TradeConditions::REPLAY)).collect();
+    assert_eq!(taped, replayed);
+    // Side effects of a replayed command are marked too: the close expires the Day order
+    ob.process_command_as(Command::Session { seq: 2, state: SessionState::Closed }, Emission::Replay, &mut trades).unwrap();
+    let cancels = ob.drain_implicit_cancels();
+    assert_eq!(cancels.len(), 1);
+    assert!(cancels[0].replay);
+}
@@ -0,0 +1,47 @@
+use match_engine::{Command, OrderBook, OrderRequest, ReferenceValue, SessionState, SessionStats, Settlement, SettlementSource, Side, TimeInForce};
+
+#[test]
+fn end_of_day_runs_closing_auction_settles_and_resets() {
+    let mut ob = OrderBook::new();
+    let _ = ob.submit_limit(Side::Sell, 101, 2).unwrap();
+    let _ = ob.submit_market(Side::Buy, 1).unwrap();
+    let _ = ob.submit(OrderRequest::limit(Side::Buy, 90, 5).with_tif(TimeInForce::Day)).unwrap();
+    let (gtc, _, _) = ob.submit_limit(Side::Buy, 95, 1).unwrap();
+    ob.set_session(SessionState::PreClose);
+    let _ = ob.submit(OrderRequest::market(Side::Buy, 1).with_tif(TimeInForce::AtClose)).unwrap();
+
+    let mut trades = Vec::new();
+    let report = ob.end_of_day(&mut trades);
+    assert_eq!(ob.session(), SessionState::Closed);
+    assert_eq!(trades.iter().map(|t| (t.price, t.qty)).collect::<Vec<_>>(), vec![(101, 1)]);
+    assert_eq!(report.settlement, Some(Settlement { price: 101, source: SettlementSource::ClosingAuction }));
+    assert_eq!((report.closing_auction_trades, report.expired), (1, 1));
+    assert_eq!((report.stats.trades, report.stats.volume, report.stats.last), (2, 2, Some(101)));
+    // Counters start over, the settlement anchors the next session, only GTC orders carry over
+    assert_eq!(ob.session_stats(), SessionStats::default());
+    assert_eq!(ob.reference_values().settlement_price, Some(101));
+    assert_eq!(ob.snapshot().order_count(), 1);
+    assert_eq!(ob.best_bid(), Some((95, 1)));
+    assert!(ob.cancel(gtc).is_ok());
+}
+
+#[test]
+fn settlement_falls_back_to_last_trade_then_reference() {
+    let mut ob = OrderBook::new();
+    ob.set_reference_value(ReferenceValue::SettlementPrice(80));
+    let mut trades = Vec::new();
+    let mut cmds = [Command::EndOfDay { seq: 1 }];
+    ob.process_commands_batch_checked_into(&mut cmds, &mut trades).unwrap();
+    let quiet = ob.drain_eod_reports();
+    assert_eq!(quiet.len(), 1);
+    assert_eq!(quiet[0].settlement, Some(Settlement { price: 80, source: SettlementSource::Reference }));
+
+    ob.set_session(SessionState::Open);
+    let _ = ob.submit_limit(Side::Sell, 84, 1).unwrap();
+    let _ = ob.submit_market(Side::Buy, 1).unwrap();
+    let mut cmds = [Command::EndOfDay { seq: 2 }];
+    ob.process_commands_batch_checked_into(&mut cmds, &mut trades).unwrap();
+    assert_eq!(ob.drain_eod_reports()[0].settlement, Some(Settlement { price: 84, source: SettlementSource::LastTrade }));
+    assert!(ob.drain_eod_reports().is_empty());
+    assert!(OrderBook::new().end_of_day(&mut trades).settlement.is_none());
+}
@@ -0,0 +1,58 @@
+use match_engine::{AccountId, BTreeStorage, BookStorage, Command, EngineError, ErrorPolicy, FaultAction, Order, OrderBook, OrderId, Side, TimeInForce};
+
+// Backend that loses order 1 on removal, so the book's id index and its levels disagree
+#[derive(Default)]
+struct Lossy(BTreeStorage);
+
+impl BookStorage for Lossy {
+    fn best_price(&self, side: Side) -> Option<u64> { self.0.best_price(side) }
+    fn match_level<F: FnMut(&Order, u64)>(&mut self, side: Side, price: u64, qty: u64, on_fill: F) -> u64 { self.0.match_level(side, price, qty, on_fill) }
+    fn push_back(&mut self, order: Order) { self.0.push_back(order) }
+    fn remove(&mut self, side: Side, price: u64, id: OrderId) -> Option<Order> { if id == OrderId(1) { None } else { self.0.remove(side, price, id) } }
+    fn update<R>(&mut self, side: Side, price: u64, id: OrderId, f: impl FnOnce(&mut Order) -> R) -> Option<R> { self.0.update(side, price, id, f) }
+    fn get(&self, side: Side, price: u64, id: OrderId) -> Option<&Order> { self.0.get(side, price, id) }
+    fn queue_position(&self, side: Side, price: u64, id: OrderId) -> Option<(usize, u64)> { self.0.queue_position(side, price, id) }
+    fn for_each_level(&self, side: Side, f: &mut dyn FnMut(u64, &mut dyn Iterator<Item = &Order>) -> bool) { self.0.for_each_level(side, f) }
+}
+
+fn batch() -> Vec<Command> {
+    vec![
+        Command::Cancel { seq: 10, id: OrderId(1), account: None },
+        Command::Limit { seq: 11, side: Side::Buy, price: 98, qty: 1, tif: TimeInForce::Gtc, account: AccountId(0) },
+    ]
+}
+
+#[test]
+fn resilient_skips_the_failing_command_and_reports_it() {
+    let mut ob = OrderBook::with_storage(Lossy::default());
+    ob.submit_limit_into(Side::Buy, 99, 1, &mut Vec::new()).unwrap();
+    let results = ob.process_commands_batch_checked_into(&mut batch(), &mut Vec::new()).unwrap();
+    assert!(matches!(results[..], [Err(EngineError::Invariant(_)), Ok((OrderId(2), 1))]));
+    let faults = ob.drain_faults();
+    assert_eq!((faults.len(), faults[0].seq, faults[0].action), (1, 10, FaultAction::Skipped));
+    assert!(!ob.is_cancel_only());
+    // Ordinary rejections are the command's result and raise no fault
+    let results = ob.process_commands_batch_checked_into(&mut [Command::Cancel { seq: 12, id: OrderId(9), account: None }], &mut Vec::new()).unwrap();
+    assert_eq!(results, vec![Err(EngineError::UnknownOrder)]);
+    assert!(ob.drain_faults().is_empty());
+}
+
+#[test]
+fn fail_fast_stops_the_batch_and_leaves_the_book_cancel_only() {
+    let mut ob = OrderBook::with_storage(Lossy::default());
+    ob.set_error_policy(ErrorPolicy::FailFast);
+    ob.submit_limit_into(Side::Buy, 99, 1, &mut Vec::new()).unwrap();
+    ob.submit_limit_into(Side::Sell, 101, 1, &mut Vec::new()).unwrap();
+    assert!(matches!(ob.process_commands_batch_checked_into(&mut batch(), &mut Vec::new()), Err(EngineError::Invariant(_))));
+    assert_eq!(ob.drain_faults()[0].action, FaultAction::CancelOnly);
+    assert!(ob.is_cancel_only());
+    // An ordinary rejection is no fault, even fail-fast
+    assert!(ob.resolve_error(12, EngineError::UnknownOrder).is_ok());
+    assert!(ob.drain_faults().is_empty());
+    // The limit after the fault never ran; new orders are refused, cancels still work
+    assert_eq!(ob.best_bid(), Some((99, 1)));
+    assert!(matches!(ob.submit_limit_into(Side::Buy, 98, 1, &mut Vec::new()), Err(EngineError::Rejected(_))));
+    assert!(ob.cancel(OrderId(2)).is_ok());
+    ob.set_cancel_only(false);
+    assert!(ob.submit_limit_into(Side::Buy, 98, 1, &mut Vec::new()).is_ok());
+}
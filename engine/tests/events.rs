@@ -0,0 +1,69 @@
+use match_engine::{AccountId, Command, EngineEvent, OrderBook, OrderId, OrderRequest, SessionState, Side, TimeInForce, TradeId};
+use std::sync::{Arc, Mutex};
+
+fn recorded(ob: &mut OrderBook) -> Arc<Mutex<Vec<EngineEvent>>> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    ob.set_event_sink(Some(Box::new(move |ev: &EngineEvent| sink.lock().unwrap().push(ev.clone()))));
+    events
+}
+
+#[test]
+fn lifecycle_of_taker_and_makers() {
+    let mut ob = OrderBook::new();
+    let events = recorded(&mut ob);
+    let (a, _, _) = ob.submit_limit(Side::Sell, 101, 2).unwrap();
+    let (b, _, _) = ob.submit_limit(Side::Sell, 102, 4).unwrap();
+    let req = OrderRequest::limit(Side::Buy, 102, 9).with_tif(TimeInForce::Ioc);
+    let (c, _, _) = ob.submit(req).unwrap();
+    let (d, _, _) = ob.submit_limit(Side::Buy, 90, 5).unwrap();
+    ob.cancel(d).unwrap();
+    let got = std::mem::take(&mut *events.lock().unwrap());
+    assert_eq!(got[2..], [
+        EngineEvent::Accepted { id: c, req },
+        EngineEvent::PartiallyFilled { id: c, trade_id: TradeId(1), price: 101, qty: 2, leaves: 7 },
+        EngineEvent::Filled { id: a, trade_id: TradeId(1), price: 101, qty: 2 },
+        EngineEvent::PartiallyFilled { id: c, trade_id: TradeId(2), price: 102, qty: 4, leaves: 3 },
+        EngineEvent::Filled { id: b, trade_id: TradeId(2), price: 102, qty: 4 },
+        EngineEvent::Canceled { id: c, qty: 3, reason: None },
+        EngineEvent::Accepted { id: d, req: OrderRequest::limit(Side::Buy, 90, 5) },
+        EngineEvent::Canceled { id: d, qty: 5, reason: None },
+    ]);
+}
+
+#[test]
+fn batch_rejections_expiry_and_stop_triggers() {
+    let mut ob = OrderBook::new();
+    let events = recorded(&mut ob);
+    let (day, _, _) = ob.submit(OrderRequest::limit(Side::Buy, 99, 4).with_tif(TimeInForce::Day)).unwrap();
+    let (stop, _, _) = ob.submit(OrderRequest::market(Side::Buy, 1).with_stop(100)).unwrap();
+    ob.submit_limit(Side::Sell, 100, 3).unwrap();
+    events.lock().unwrap().clear();
+    let mut cmds = [
+        Command::Limit { seq: 1, side: Side::Buy, price: 100, qty: 1, tif: TimeInForce::Gtc, account: AccountId(0) },
+        Command::Session { seq: 2, state: SessionState::Closed },
+        Command::Cancel { seq: 3, id: OrderId(77), account: Some(AccountId(5)) },
+    ];
+    let _ = ob.process_commands_batch_checked_into(&mut cmds, &mut Vec::new());
+    let got = std::mem::take(&mut *events.lock().unwrap());
+    assert!(got.contains(&EngineEvent::Triggered { id: stop, stop_price: 100, last_price: 100 }));
+    assert!(got.iter().any(|e| matches!(e, EngineEvent::Filled { id, qty: 1, .. } if *id == stop)));
+    assert_eq!(got[got.len() - 2], EngineEvent::Expired { id: day, qty: 4 });
+    assert!(matches!(got.last(), Some(EngineEvent::Rejected { id: Some(OrderId(77)), account: AccountId(5), .. })));
+
+    ob.set_session(SessionState::Open);
+    assert!(ob.submit(OrderRequest::limit(Side::Buy, 100, 1).post_only().with_correlation(9)).is_err());
+    assert!(matches!(events.lock().unwrap()[..], [EngineEvent::Rejected { id: None, correlation: 9, .. }]));
+}
+
+#[test]
+fn unexecuted_auction_orders_expire() {
+    let mut ob = OrderBook::new();
+    ob.set_session(SessionState::PreOpen);
+    let (id, _, _) = ob.submit(OrderRequest::market(Side::Buy, 3).with_tif(TimeInForce::AtOpen)).unwrap();
+    let events = recorded(&mut ob);
+    let seq = ob.current_output_seq();
+    ob.set_session(SessionState::Open);
+    assert_eq!(events.lock().unwrap()[..], [EngineEvent::Expired { id, qty: 3 }]);
+    assert_eq!(ob.drain_implicit_cancels()[0].output_seq, seq + 1);
+}
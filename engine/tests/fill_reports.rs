@@ -0,0 +1,43 @@
+use match_engine::{AccountId, OrderBook, OrderRequest, Side, StpAction};
+
+#[test]
+fn one_report_per_taker_command() {
+    let mut ob = OrderBook::new();
+    ob.set_fill_reports(true);
+    let mut trades = Vec::new();
+    for (price, qty) in [(100, 2), (100, 3), (101, 4), (103, 5)] { ob.submit_limit_into(Side::Sell, price, qty, &mut trades).unwrap(); }
+    assert!(ob.drain_fill_reports().is_empty());
+    let req = OrderRequest::limit(Side::Buy, 101, 10).with_account(AccountId(7)).with_correlation(42);
+    let (id, remaining) = ob.submit_into(req, &mut trades).unwrap();
+    assert_eq!(trades.len(), 3);
+    let reports = ob.drain_fill_reports();
+    assert_eq!(reports.len(), 1);
+    let r = &reports[0];
+    assert_eq!((r.id, r.account, r.correlation, r.side), (id, AccountId(7), 42, Side::Buy));
+    assert_eq!((r.filled, r.remaining, remaining), (9, 1, 1));
+    assert_eq!(r.fills, vec![(100, 5), (101, 4)]);
+    assert_eq!(r.notional, 100 * 5 + 101 * 4);
+    assert!((r.avg_price().unwrap() - 904.0 / 9.0).abs() < 1e-9);
+    // Resting orders that did not trade get nothing
+    ob.submit_limit_into(Side::Buy, 90, 1, &mut trades).unwrap();
+    assert!(ob.drain_fill_reports().is_empty());
+}
+
+#[test]
+fn off_by_default_and_stp_decrements_are_not_fills() {
+    let mut ob = OrderBook::new();
+    let mut trades = Vec::new();
+    ob.submit_limit_into(Side::Sell, 100, 2, &mut trades).unwrap();
+    ob.submit_market_into(Side::Buy, 2, &mut trades).unwrap();
+    assert!(ob.drain_fill_reports().is_empty());
+    ob.set_fill_reports(true);
+    ob.set_self_trade_prevention(Some(StpAction::Decrement));
+    ob.submit_into(OrderRequest::limit(Side::Sell, 100, 3).with_account(AccountId(1)), &mut trades).unwrap();
+    ob.submit_into(OrderRequest::market(Side::Buy, 2).with_account(AccountId(1)), &mut trades).unwrap();
+    assert!(ob.drain_fill_reports().is_empty());
+    // The decrement left 1 of the resting 3
+    let (_, remaining) = ob.submit_into(OrderRequest::market(Side::Buy, 4).with_account(AccountId(2)), &mut trades).unwrap();
+    let reports = ob.drain_fill_reports();
+    assert_eq!(reports.len(), 1);
+    assert_eq!((reports[0].filled, reports[0].remaining, remaining), (1, 3, 3));
+}
@@ -0,0 +1,55 @@
+use match_engine::{AccountId, CancelReason, ImplicitCancel, OrderBook, OrderId, OrderRequest, Side, StpAction, TimeInForce};
+
+#[test]
+fn fills_in_full_or_leaves_the_book_untouched() {
+    let mut ob = OrderBook::new();
+    ob.submit_limit(Side::Sell, 100, 2).unwrap();
+    ob.submit_limit(Side::Sell, 101, 3).unwrap();
+    ob.submit_limit(Side::Sell, 103, 4).unwrap();
+    let before = ob.snapshot();
+
+    // 6 needed, only 5 within 101: killed, nothing traded
+    let (id, trades, remaining) = ob.submit_limit_fok(Side::Buy, 101, 6).unwrap();
+    assert!(trades.is_empty());
+    assert_eq!(remaining, 6);
+    assert_eq!(ob.drain_implicit_cancels(), vec![ImplicitCancel { id, side: Side::Buy, price: 101, qty: 6, reason: CancelReason::Killed, replay: false, output_seq: 1 }]);
+    assert_eq!((ob.snapshot().bids, ob.snapshot().asks), (before.bids, before.asks));
+
+    // Exactly what is available: fills across both levels
+    let (_, trades, remaining) = ob.submit_limit_fok(Side::Buy, 101, 5).unwrap();
+    assert_eq!(trades.iter().map(|t| (t.price, t.qty)).collect::<Vec<_>>(), vec![(100, 2), (101, 3)]);
+    assert_eq!(remaining, 0);
+    assert!(ob.drain_implicit_cancels().is_empty());
+    assert_eq!(ob.best_ask(), Some((103, 4)));
+
+    // Market FOK counts every level
+    let (_, trades, _) = ob.submit(OrderRequest::market(Side::Buy, 5).with_tif(TimeInForce::Fok)).unwrap();
+    assert!(trades.is_empty());
+    let (_, trades, _) = ob.submit(OrderRequest::market(Side::Buy, 4).with_tif(TimeInForce::Fok)).unwrap();
+    assert_eq!(trades.len(), 1);
+}
+
+#[test]
+fn prescan_follows_matching_rules() {
+    // Only the best level counts for top_level_only
+    let mut ob = OrderBook::new();
+    ob.submit_limit(Side::Buy, 99, 2).unwrap();
+    ob.submit_limit(Side::Buy, 98, 5).unwrap();
+    let (_, trades, _) = ob.submit(OrderRequest::limit(Side::Sell, 98, 3).with_tif(TimeInForce::Fok).top_level_only()).unwrap();
+    assert!(trades.is_empty());
+    assert_eq!(ob.best_bid(), Some((99, 2)));
+
+    // Own liquidity does not count when self-trade prevention would cancel the incoming order
+    let mut ob = OrderBook::new();
+    ob.set_self_trade_prevention(Some(StpAction::CancelIncoming));
+    ob.submit(OrderRequest::limit(Side::Sell, 100, 5).with_account(AccountId(7))).unwrap();
+    ob.submit(OrderRequest::limit(Side::Sell, 101, 5).with_account(AccountId(8))).unwrap();
+    let (id, trades, _) = ob.submit(OrderRequest::limit(Side::Buy, 101, 3).with_account(AccountId(7)).with_tif(TimeInForce::Fok)).unwrap();
+    assert!(trades.is_empty());
+    assert_eq!(ob.drain_implicit_cancels()[0].id, id);
+    // ... while resting own orders cancelled by STP are skipped and the rest fills
+    ob.set_self_trade_prevention(Some(StpAction::CancelResting));
+    let (_, trades, remaining) = ob.submit(OrderRequest::limit(Side::Buy, 101, 5).with_account(AccountId(7)).with_tif(TimeInForce::Fok)).unwrap();
+    assert_eq!((trades.len(), remaining), (1, 0));
+    assert_eq!(ob.drain_implicit_cancels().iter().map(|c| (c.id, c.reason)).collect::<Vec<_>>(), vec![(OrderId(1), CancelReason::SelfTrade)]);
+}
@@ -0,0 +1,51 @@
+use match_engine::{AccountId, EngineError, OrderBook, OrderRequest, RiskGroups, Side};
+
+fn attached(risk: &RiskGroups, instrument: &str) -> OrderBook {
+    let mut ob = OrderBook::new();
+    ob.set_risk_groups(Some((risk.clone(), instrument)));
+    ob.submit_limit(Side::Sell, 100, 50).unwrap();
+    ob.submit_limit(Side::Buy, 90, 50).unwrap();
+    ob
+}
+
+fn order(side: Side, price: u64, qty: u64, account: u64) -> OrderRequest { OrderRequest::limit(side, price, qty).with_account(AccountId(account)) }
+
+#[test]
+fn exposure_is_shared_across_the_group() {
+    let risk = RiskGroups::new();
+    risk.add_instrument("ES-DEC", 1, 1);
+    risk.add_instrument("ES-MAR", 1, 2);
+    risk.set_group_limit(1, 10);
+    let (mut dec, mut mar) = (attached(&risk, "ES-DEC"), attached(&risk, "ES-MAR"));
+
+    dec.submit(order(Side::Buy, 100, 6, 7)).unwrap();
+    assert_eq!(risk.exposure(1, AccountId(7)), 6);
+    // 6 + 2 * 3 = 12 > 10 on the other contract
+    assert!(matches!(mar.submit(order(Side::Buy, 100, 3, 7)), Err(EngineError::Rejected(_))));
+    mar.submit(order(Side::Buy, 100, 2, 7)).unwrap();
+    assert_eq!(risk.exposure(1, AccountId(7)), 10);
+    // Reducing is always allowed; the seller of those lots (account 0) is not limited
+    mar.submit(order(Side::Sell, 90, 1, 7)).unwrap();
+    assert_eq!(risk.exposure(1, AccountId(7)), 8);
+    assert_eq!(risk.exposure(1, AccountId(0)), -8);
+}
+
+#[test]
+fn maker_fills_count_and_account_limits_override() {
+    let risk = RiskGroups::new();
+    risk.add_instrument("CL-JAN", 3, 1);
+    risk.set_group_limit(3, 5);
+    risk.set_account_limit(3, AccountId(9), 20);
+    let mut ob = attached(&risk, "CL-JAN");
+    let mut other = OrderBook::new();
+    other.set_risk_groups(Some((risk.clone(), "BRENT"))); // not in any group: unchecked
+    other.submit(order(Side::Buy, 1, 100, 4)).unwrap();
+
+    // A resting bid is checked on entry and counts once it fills
+    ob.submit(order(Side::Buy, 95, 5, 4)).unwrap();
+    ob.submit(order(Side::Sell, 95, 5, 8)).unwrap();
+    assert_eq!((risk.exposure(3, AccountId(4)), risk.exposure(3, AccountId(8))), (5, -5));
+    assert!(ob.submit(order(Side::Buy, 95, 1, 4)).is_err());
+    assert!(ob.submit(order(Side::Buy, 100, 15, 9)).is_ok());
+    assert_eq!(risk.exposure(3, AccountId(9)), 15);
+}
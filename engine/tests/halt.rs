@@ -0,0 +1,41 @@
+use match_engine::{EngineError, HaltAction, HaltPolicy, OrderBook, OrderRequest, SessionState, Side, TimeInForce};
+
+#[test]
+fn halt_policy_controls_resting_orders() {
+    let mut ob = OrderBook::new();
+    let (bid, _, _) = ob.submit_limit(Side::Buy, 99, 5).unwrap();
+    ob.set_session(SessionState::Halted);
+    // Keep: new orders and cancels both rejected, the book is frozen
+    assert!(matches!(ob.submit_limit(Side::Sell, 99, 1), Err(EngineError::Rejected(_))));
+    assert!(matches!(ob.cancel(bid), Err(EngineError::Rejected(_))));
+    assert_eq!(ob.best_bid(), Some((99, 5)));
+
+    ob.set_session(SessionState::Open);
+    ob.set_halt_policy(HaltPolicy { resting: HaltAction::CancelOnly, reopening_auction: false });
+    let (ask, _, _) = ob.submit_limit(Side::Sell, 101, 2).unwrap();
+    ob.set_session(SessionState::Halted);
+    assert!(matches!(ob.submit_limit(Side::Sell, 100, 1), Err(EngineError::Rejected(_))));
+    assert_eq!(ob.cancel(ask).unwrap().qty, 2);
+
+    ob.set_session(SessionState::Open);
+    ob.set_halt_policy(HaltPolicy { resting: HaltAction::Cancel, reopening_auction: false });
+    let change = ob.set_session(SessionState::Halted);
+    assert_eq!(change.expired.iter().map(|o| o.id).collect::<Vec<_>>(), vec![bid]);
+    assert_eq!(ob.best_bid(), None);
+}
+
+#[test]
+fn resume_with_reopening_auction() {
+    let mut ob = OrderBook::new();
+    ob.set_halt_policy(HaltPolicy { resting: HaltAction::Keep, reopening_auction: true });
+    ob.submit_limit(Side::Sell, 101, 4).unwrap();
+    ob.set_session(SessionState::Halted);
+    // Resuming goes through PreOpen first
+    ob.set_session(SessionState::Open);
+    assert_eq!(ob.session(), SessionState::PreOpen);
+    ob.submit(OrderRequest::market(Side::Buy, 3).with_tif(TimeInForce::AtOpen)).unwrap();
+    let change = ob.set_session(SessionState::Open);
+    assert_eq!(ob.session(), SessionState::Open);
+    assert_eq!(change.trades.iter().map(|t| (t.price, t.qty)).collect::<Vec<_>>(), vec![(101, 3)]);
+    assert_eq!(ob.best_ask(), Some((101, 1)));
+}
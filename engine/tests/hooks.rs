@@ -0,0 +1,65 @@
+use match_engine::{AccountId, Command, EngineError, OrderBook, OrderId, OrderRequest, OrderType, Side, TimeInForce, Trade};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn pre_match_hook_rejects_without_touching_book() {
+    let mut ob = OrderBook::new();
+    ob.add_pre_match_hook(Box::new(|req: &mut OrderRequest| {
+        if req.qty > 10 { return Err(EngineError::Rejected("max qty".into())); }
+        Ok(())
+    }));
+    let _ = ob.submit_limit(Side::Sell, 100, 5).unwrap();
+    let err = ob.submit_market(Side::Buy, 11).unwrap_err();
+    assert!(matches!(err, EngineError::Rejected(_)));
+    assert_eq!(ob.best_ask(), Some((100, 5)));
+    // Rejected order did not consume an id
+    let (id, _, _) = ob.submit_limit(Side::Buy, 90, 1).unwrap();
+    assert_eq!(id.0, 2);
+}
+
+#[test]
+fn pre_match_hook_can_enrich_request() {
+    let mut ob = OrderBook::new();
+    ob.add_pre_match_hook(Box::new(|req: &mut OrderRequest| {
+        if req.order_type == OrderType::Limit { req.qty = req.qty.min(3); }
+        Ok(())
+    }));
+    let (_, _, remaining) = ob.submit_limit(Side::Buy, 100, 8).unwrap();
+    assert_eq!(remaining, 3);
+    assert_eq!(ob.best_bid(), Some((100, 3)));
+}
+
+#[test]
+fn post_trade_hook_sees_fills_from_batch_path() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = seen.clone();
+    let mut ob = OrderBook::new();
+    ob.add_post_trade_hook(Box::new(move |req: &OrderRequest, _taker, trades: &mut [Trade]| {
+        sink.lock().unwrap().push((req.side, trades.iter().map(|t| t.qty).sum::<u64>()));
+    }));
+    let mut cmds = vec![
+        Command::Limit { seq: 1, side: Side::Sell, price: 100, qty: 4, tif: TimeInForce::Gtc, account: AccountId(0) },
+        Command::Limit { seq: 2, side: Side::Buy, price: 90, qty: 4, tif: TimeInForce::Gtc, account: AccountId(0) },
+        Command::Market { seq: 3, side: Side::Buy, qty: 6, account: AccountId(0) },
+    ];
+    let mut trades = Vec::new();
+    ob.process_commands_batch_checked_into(&mut cmds, &mut trades).unwrap();
+    assert_eq!(*seen.lock().unwrap(), vec![(Side::Buy, 4)]);
+}
+
+#[test]
+fn hook_rejection_in_a_batch_leaves_the_commands_behind_it() {
+    let mut ob = OrderBook::new();
+    ob.add_pre_match_hook(Box::new(|req: &mut OrderRequest| {
+        if req.qty > 10 { return Err(EngineError::Rejected("max qty".into())); }
+        Ok(())
+    }));
+    let mut cmds = vec![
+        Command::Limit { seq: 1, side: Side::Sell, price: 100, qty: 4, tif: TimeInForce::Gtc, account: AccountId(0) },
+        Command::Market { seq: 2, side: Side::Buy, qty: 11, account: AccountId(0) },
+        Command::Limit { seq: 3, side: Side::Sell, price: 110, qty: 7, tif: TimeInForce::Gtc, account: AccountId(0) },
+    ];
+    let results = ob.process_commands_batch_checked_into(&mut cmds, &mut Vec::new()).unwrap();
+    assert_eq!(results, vec![Ok((OrderId(1), 4)), Err(EngineError::Rejected("max qty".into())), Ok((OrderId(2), 7))]);
+    assert_eq!(ob.top_n(2).1, vec![(100, 4, 1), (110, 7, 1)]);
+}
@@ -0,0 +1,55 @@
+use match_engine::{BTreeStorage, BookStorage, Depth, LadderStorage, OrderBook, Side, SkipListStorage, SlabStorage, SoaStorage, StpAction};
+
+type Tape = Vec<(u64, u64, u64)>;
+// Trades, best ask and the partly filled maker's queue position after the sweep, then the final depth
+type Swept = (Tape, Option<(u64, u64)>, Option<(usize, u64)>, Depth);
+
+// Asks 100: [2, 3], 101: [4]; a market buy of 6 takes both makers at 100 whole and 1 of the 101 maker
+fn sweep<S: BookStorage>(mut ob: OrderBook<S>) -> Swept {
+    let mut trades = Vec::new();
+    ob.submit_limit(Side::Sell, 100, 2).unwrap();
+    ob.submit_limit(Side::Sell, 100, 3).unwrap();
+    let (tail, _, _) = ob.submit_limit(Side::Sell, 101, 4).unwrap();
+    ob.submit_market_into(Side::Buy, 6, &mut trades).unwrap();
+    let tape = trades.iter().map(|t| (t.maker_id.0, t.price, t.qty)).collect();
+    let (asks, position) = (ob.best_ask(), ob.queue_position(tail));
+    // The partly filled maker keeps its place and the level its totals
+    trades.clear();
+    let (_, remaining) = ob.submit_limit_into(Side::Buy, 101, 10, &mut trades).unwrap();
+    assert_eq!((trades.len(), remaining), (1, 7));
+    (tape, asks, position, ob.top_n(5).0)
+}
+
+#[test]
+fn full_fills_then_one_partial_tail() {
+    let expected = (vec![(1, 100, 2), (2, 100, 3), (3, 101, 1)], Some((101, 3)), Some((0, 0)), vec![(101, 7, 1)]);
+    assert_eq!(sweep(OrderBook::with_storage(BTreeStorage::default())), expected);
+    assert_eq!(sweep(OrderBook::with_storage(LadderStorage::with_range(50, 150))), expected);
+    assert_eq!(sweep(OrderBook::with_storage(SlabStorage::default())), expected);
+    assert_eq!(sweep(OrderBook::with_storage(SoaStorage::default())), expected);
+    assert_eq!(sweep(OrderBook::with_storage(SkipListStorage::default())), expected);
+}
+
+// Plain orders take the sweep; with STP on (no shared accounts) the checked per-maker path runs instead
+fn flow<S: BookStorage>(mut ob: OrderBook<S>, stp: bool) -> Tape {
+    if stp { ob.set_self_trade_prevention(Some(StpAction::CancelResting)); }
+    let mut trades = Vec::new();
+    for i in 0..400u64 {
+        let side = if i % 3 == 0 { Side::Buy } else { Side::Sell };
+        let price = match side { Side::Buy => 95 + i % 9, Side::Sell => 97 + i % 9 };
+        let _ = ob.submit_limit_into(side, price, 1 + i % 7, &mut trades);
+        if i % 11 == 0 { let _ = ob.submit_market_into(side.opposite(), 12, &mut trades); }
+    }
+    trades.iter().map(|t| (t.maker_id.0, t.price, t.qty)).collect()
+}
+
+#[test]
+fn sweep_matches_checked_path() {
+    let reference = flow(OrderBook::with_storage(BTreeStorage::default()), true);
+    assert!(reference.len() > 100);
+    assert_eq!(flow(OrderBook::with_storage(BTreeStorage::default()), false), reference);
+    assert_eq!(flow(OrderBook::with_storage(SlabStorage::default()), false), reference);
+    assert_eq!(flow(OrderBook::with_storage(SoaStorage::default()), false), reference);
+    assert_eq!(flow(OrderBook::with_storage(SkipListStorage::default()), false), reference);
+    assert_eq!(flow(OrderBook::with_storage(LadderStorage::with_range(50, 150)), false), reference);
+}
@@ -0,0 +1,47 @@
+use match_engine::{AccountId, CancelReason, OrderBook, OrderRequest, Side, StpAction};
+
+#[test]
+fn only_the_displayed_slice_is_visible_and_refills_at_the_back() {
+    let mut ob = OrderBook::new();
+    let (ice, _, _) = ob.submit(OrderRequest::limit(Side::Sell, 100, 10).iceberg(3)).unwrap();
+    let (plain, _, _) = ob.submit_limit(Side::Sell, 100, 2).unwrap();
+    assert_eq!(ob.best_ask(), Some((100, 5)));
+    assert_eq!(ob.top_n(1).1, vec![(100, 5, 2)]);
+
+    // The slice fills first; its refill queues behind the plain order
+    let (_, trades, _) = ob.submit_market(Side::Buy, 3).unwrap();
+    assert_eq!(trades.iter().map(|t| (t.maker_id, t.qty)).collect::<Vec<_>>(), vec![(ice, 3)]);
+    assert_eq!(ob.queue_position(plain), Some((0, 0)));
+    assert_eq!(ob.queue_position(ice), Some((1, 2)));
+    assert_eq!(ob.best_ask(), Some((100, 5)));
+
+    // A large taker works through the reserve slice by slice
+    let (_, trades, remaining) = ob.submit_limit(Side::Buy, 100, 20).unwrap();
+    assert_eq!(trades.iter().map(|t| (t.maker_id, t.qty)).collect::<Vec<_>>(), vec![(plain, 2), (ice, 3), (ice, 3), (ice, 1)]);
+    assert_eq!(remaining, 11);
+    assert_eq!(ob.best_ask(), None);
+    assert_eq!(ob.best_bid(), Some((100, 11)));
+}
+
+#[test]
+fn cancel_and_reprice_carry_the_reserve() {
+    let mut ob = OrderBook::new();
+    let (ice, _, _) = ob.submit(OrderRequest::limit(Side::Buy, 99, 9).iceberg(4)).unwrap();
+    assert_eq!(ob.reprice(ice, 98, &mut Vec::new()).unwrap(), 9);
+    assert_eq!(ob.best_bid(), Some((98, 4)));
+    let snap = ob.snapshot();
+    assert_eq!((snap.bids[0].orders[0].display_qty, snap.bids[0].orders[0].reserve_qty), (4, 5));
+    assert_eq!(OrderBook::from_snapshot(&snap).state_hash(), ob.state_hash());
+
+    let o = ob.cancel(ice).unwrap();
+    assert_eq!(o.qty + o.reserve_qty, 9);
+    assert!(ob.submit(OrderRequest::market(Side::Buy, 5).iceberg(1)).is_err());
+    assert!(ob.submit(OrderRequest::limit(Side::Buy, 99, 5).iceberg(0)).is_err());
+
+    // Implicit cancels report the whole remaining quantity
+    ob.set_self_trade_prevention(Some(StpAction::CancelResting));
+    ob.submit(OrderRequest::limit(Side::Sell, 101, 6).iceberg(2).with_account(AccountId(7))).unwrap();
+    ob.submit(OrderRequest::limit(Side::Buy, 101, 1).with_account(AccountId(7))).unwrap();
+    let cancels = ob.drain_implicit_cancels();
+    assert_eq!(cancels.iter().map(|c| (c.qty, c.reason)).collect::<Vec<_>>(), vec![(6, CancelReason::SelfTrade)]);
+}
@@ -0,0 +1,34 @@
+use match_engine::{AccountId, CancelReason, Command, HaltAction, HaltPolicy, ImplicitCancel, OrderBook, OrderRequest, SessionState, Side, TimeInForce};
+
+#[test]
+fn session_commands_report_expired_orders() {
+    let mut ob = OrderBook::new();
+    let (day, _, _) = ob.submit(OrderRequest::limit(Side::Buy, 99, 4).with_tif(TimeInForce::Day)).unwrap();
+    ob.submit_limit(Side::Buy, 98, 1).unwrap();
+    let mut cmds = [Command::Session { seq: 1, state: SessionState::Closed }];
+    ob.process_commands_batch_checked_into(&mut cmds, &mut Vec::new()).unwrap();
+    assert_eq!(ob.drain_implicit_cancels(), vec![ImplicitCancel { id: day, side: Side::Buy, price: 99, qty: 4, reason: CancelReason::Expired, replay: false, output_seq: 1 }]);
+    assert!(ob.drain_implicit_cancels().is_empty());
+
+    ob.set_session(SessionState::Open);
+    ob.set_halt_policy(HaltPolicy { resting: HaltAction::Cancel, reopening_auction: false });
+    let mut out = Vec::new();
+    ob.set_session(SessionState::Halted);
+    ob.drain_implicit_cancels_into(&mut out);
+    assert_eq!(out.iter().map(|c| (c.price, c.reason)).collect::<Vec<_>>(), vec![(98, CancelReason::Halted)]);
+}
+
+#[test]
+fn reduce_only_removal_is_reported() {
+    let mut ob = OrderBook::new();
+    ob.enable_positions();
+    let acct = AccountId(1);
+    ob.submit_limit(Side::Sell, 100, 2).unwrap();
+    ob.submit(OrderRequest::limit(Side::Buy, 100, 2).with_account(acct)).unwrap();
+    let (ro, _, _) = ob.submit(OrderRequest::limit(Side::Sell, 105, 2).with_account(acct).reduce_only()).unwrap();
+    // Flattening the long elsewhere leaves the reduce-only order nothing to reduce
+    ob.submit_limit(Side::Buy, 100, 2).unwrap();
+    ob.submit(OrderRequest::limit(Side::Sell, 100, 2).with_account(acct)).unwrap();
+    assert_eq!(ob.drain_implicit_cancels(), vec![ImplicitCancel { id: ro, side: Side::Sell, price: 105, qty: 2, reason: CancelReason::ReduceOnly, replay: false, output_seq: 3 }]);
+    assert!(ob.cancel(ro).is_err());
+}
@@ -1,5 +1,4 @@
-use match_engine::{OrderBook, Side};
-use std::time::Instant;
+use match_engine::{OrderBook, Side, Workload, WorkloadConfig, WorkloadMix, WorkloadOp};
 
 fn seed_book(ob: &mut OrderBook, mid: u64, levels: u64, tick: u64, qty: u64) {
     for i in 1..=levels {
@@ -13,9 +12,9 @@ fn seed_book(ob: &mut OrderBook, mid: u64, levels: u64, tick: u64, qty: u64) {
 #[test]
 fn limit_crossing_fifo_and_rest() {
     let mut ob = OrderBook::new();
-    let (a1, _, _) = ob.submit_limit(Side::Sell, 100, 3);
-    let (a2, _, _) = ob.submit_limit(Side::Sell, 100, 4);
-    let (_b, trades, r) = ob.submit_limit(Side::Buy, 105, 5);
+    let (a1, _, _) = ob.submit_limit(Side::Sell, 100, 3).unwrap();
+    let (a2, _, _) = ob.submit_limit(Side::Sell, 100, 4).unwrap();
+    let (_b, trades, r) = ob.submit_limit(Side::Buy, 105, 5).unwrap();
     assert_eq!(r, 0);
     assert_eq!(trades.len(), 2);
     assert_eq!(trades[0].maker_id.0, a1.0);
@@ -30,7 +29,7 @@ fn limit_crossing_fifo_and_rest() {
 fn partial_fill_then_rest_bid() {
     let mut ob = OrderBook::new();
     let _ = ob.submit_limit(Side::Sell, 101, 2);
-    let (bid_id, trades, remaining) = ob.submit_limit(Side::Buy, 101, 5);
+    let (bid_id, trades, remaining) = ob.submit_limit(Side::Buy, 101, 5).unwrap();
     assert_eq!(trades.iter().map(|t| t.qty).sum::<u64>(), 2);
     assert_eq!(remaining, 3);
     let (px, qty) = ob.best_bid().unwrap();
@@ -48,7 +47,7 @@ fn market_consumes_across_levels() {
     let (px, qty) = ob.best_ask().unwrap();
     assert_eq!(px, 10_001);
     assert_eq!(qty, 5);
-    let (_id, trades, r) = ob.submit_market(Side::Buy, 9);
+    let (_id, trades, r) = ob.submit_market(Side::Buy, 9).unwrap();
     assert_eq!(r, 0);
     assert_eq!(trades.iter().map(|t| t.qty).sum::<u64>(), 9);
     let (px, qty) = ob.best_ask().unwrap();
@@ -65,8 +64,8 @@ fn top_n_depth_is_aggregated() {
     let _ = ob.submit_limit(Side::Sell, 101, 3);
     let _ = ob.submit_limit(Side::Sell, 102, 6);
     let (bids, asks) = ob.top_n(2);
-    assert_eq!(bids, vec![(100, 3), (99, 4)]);
-    assert_eq!(asks, vec![(101, 3), (102, 6)]);
+    assert_eq!(bids, vec![(100, 3, 2), (99, 4, 1)]);
+    assert_eq!(asks, vec![(101, 3, 1), (102, 6, 1)]);
 }
 
 #[test]
@@ -79,35 +78,45 @@ fn cancel_unknown_returns_error() {
 #[test]
 fn load_scenario_smoke_and_throughput_print() {
     let mut ob = OrderBook::new();
-    seed_book(&mut ob, 10_000, 200, 1, 1_000);
+    let mut w = Workload::new(WorkloadConfig::default());
+    w.seed_book(&mut ob, 200, 1_000);
+    let r = w.run(&mut ob, 50_000);
+    eprintln!("load_scenario: ops={} trades={} ops/sec≈{:.0} p50={}ns p99={}ns", r.ops, r.trades, r.ops_per_sec(), r.p50_nanos, r.p99_nanos);
+    assert!(r.trades > 0);
+    assert!(r.p50_nanos <= r.p99_nanos && r.p99_nanos <= r.max_nanos);
+    // Never left crossed
+    if let (Some((bid, _)), Some((ask, _))) = (ob.best_bid(), ob.best_ask()) { assert!(bid < ask); }
+}
 
-    let n: u64 = 50_000;
-    let start = Instant::now();
-    for i in 0..n {
-        if i % 10 == 0 {
-            let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
-            let _ = ob.submit_market(side, 1 + (i % 7));
-        } else {
-            let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
-            if side == Side::Buy {
-                if let Some((ask, _)) = ob.best_ask() { let _ = ob.submit_limit(side, ask, 1 + (i % 3)); }
-                else { let _ = ob.submit_limit(side, 9_999, 1); }
-            } else {
-                if let Some((bid, _)) = ob.best_bid() { let _ = ob.submit_limit(side, bid, 1 + (i % 3)); }
-                else { let _ = ob.submit_limit(side, 10_001, 1); }
-            }
-        }
-        if i % 777 == 0 {
-            if let Some((px, _)) = ob.best_bid() {
-                let (id, _, r) = ob.submit_limit(Side::Buy, px, 2);
-                if r > 0 { let _ = ob.cancel(id); }
-            }
-        }
-    }
-    let dur = start.elapsed().as_secs_f64();
-    let ops_per_sec = (n as f64) / dur;
-    eprintln!("load_scenario: ops={} duration={:.3}s ops/sec≈{:.0}", n, dur, ops_per_sec);
-    let bb = ob.best_bid();
-    let ba = ob.best_ask();
-    assert!(bb.is_some() || ba.is_some());
+#[test]
+fn workload_is_deterministic_per_seed() {
+    let run = |seed| {
+        let mut ob = OrderBook::new();
+        let mut w = Workload::new(WorkloadConfig { seed, mix: WorkloadMix::TAKER_HEAVY, ..WorkloadConfig::default() });
+        w.seed_book(&mut ob, 20, 10);
+        (0..2_000).map(|_| { let op = w.next_op(&ob); w.apply(&mut ob, op, &mut Vec::new()); op }).collect::<Vec<WorkloadOp>>()
+    };
+    assert_eq!(run(7), run(7));
+    assert_ne!(run(7), run(8));
+}
+
+#[test]
+fn snapshot_restore_preserves_fifo_and_ids() {
+    let mut ob = OrderBook::new();
+    seed_book(&mut ob, 1_000, 3, 1, 5);
+    let (a1, _, _) = ob.submit_limit(Side::Sell, 1_001, 2).unwrap();
+    let snap = ob.snapshot();
+    assert_eq!(snap.order_count(), 7);
+    assert_eq!(snap.bids[0].price, 999);
+    assert_eq!(snap.asks[0].orders.last().unwrap().id, a1);
+
+    let mut restored = OrderBook::from_snapshot(&snap);
+    assert_eq!(restored.snapshot(), snap);
+    assert_eq!(restored.top_n(3), ob.top_n(3));
+    // Same command on both books yields the same fills and the same next id
+    let (id_a, trades_a, _) = ob.submit_market(Side::Buy, 7).unwrap();
+    let (id_b, trades_b, _) = restored.submit_market(Side::Buy, 7).unwrap();
+    assert_eq!(id_a, id_b);
+    assert_eq!(trades_a.iter().map(|t| (t.maker_id, t.qty)).collect::<Vec<_>>(), trades_b.iter().map(|t| (t.maker_id, t.qty)).collect::<Vec<_>>());
+    assert!(restored.cancel(a1).is_err());
 }
@@ -0,0 +1,33 @@
+use match_engine::{AccountId, Command, OrderBook, Side, TimeInForce};
+
+#[test]
+fn ioc_limit_sweeps_within_price_and_discards_the_rest() {
+    let mut ob = OrderBook::new();
+    ob.submit_limit(Side::Sell, 100, 2).unwrap();
+    ob.submit_limit(Side::Sell, 101, 3).unwrap();
+    ob.submit_limit(Side::Sell, 103, 4).unwrap();
+    let (_, trades, remaining) = ob.submit_limit_ioc(Side::Buy, 101, 10).unwrap();
+    assert_eq!(trades.iter().map(|t| (t.price, t.qty)).collect::<Vec<_>>(), vec![(100, 2), (101, 3)]);
+    assert_eq!(remaining, 5);
+    // Nothing rests at 101, the level beyond the limit is untouched
+    assert_eq!(ob.best_bid(), None);
+    assert_eq!(ob.best_ask(), Some((103, 4)));
+    // Not marketable at all: discarded outright
+    let (_, trades, remaining) = ob.submit_limit_ioc(Side::Sell, 200, 1).unwrap();
+    assert!(trades.is_empty());
+    assert_eq!(remaining, 1);
+    assert_eq!(ob.best_ask(), Some((103, 4)));
+}
+
+#[test]
+fn command_limit_carries_time_in_force() {
+    let mut ob = OrderBook::new();
+    let mut trades = Vec::new();
+    ob.process_command(Command::Limit { seq: 1, side: Side::Sell, price: 100, qty: 1, tif: TimeInForce::Gtc, account: AccountId(0) }, &mut trades).unwrap();
+    let (_, remaining) = ob.process_command(Command::Limit { seq: 2, side: Side::Buy, price: 100, qty: 3, tif: TimeInForce::Ioc, account: AccountId(0) }, &mut trades).unwrap();
+    assert_eq!((trades.len(), remaining), (1, 2));
+    assert_eq!(ob.best_bid(), None);
+    // The same order as Gtc rests its remainder
+    ob.process_command(Command::Limit { seq: 3, side: Side::Buy, price: 100, qty: 3, tif: TimeInForce::Gtc, account: AccountId(0) }, &mut trades).unwrap();
+    assert_eq!(ob.best_bid(), Some((100, 3)));
+}
@@ -0,0 +1,40 @@
+use match_engine::{AccountId, Attribution, L3Level, L3Order, OrderBook, OrderRequest, Owner, Side};
+
+#[test]
+fn levels_in_fifo_order_with_displayed_qty() {
+    let mut ob = OrderBook::new();
+    let (a, _, _) = ob.submit_limit(Side::Buy, 100, 3).unwrap();
+    let (b, _, _) = ob.submit(OrderRequest::limit(Side::Buy, 100, 10).iceberg(2).with_account(AccountId(7)).with_correlation(5)).unwrap();
+    let (c, _, _) = ob.submit_limit(Side::Buy, 101, 1).unwrap();
+    let (d, _, _) = ob.submit_limit(Side::Sell, 105, 4).unwrap();
+    let snap = ob.l3_snapshot(Attribution::Anonymous);
+    assert_eq!(snap.bids, vec![
+        L3Level { price: 101, orders: vec![L3Order { id: c, qty: 1, ts: 3, owner: None }] },
+        L3Level { price: 100, orders: vec![L3Order { id: a, qty: 3, ts: 1, owner: None }, L3Order { id: b, qty: 2, ts: 2, owner: None }] },
+    ]);
+    assert_eq!(snap.levels(Side::Sell), [L3Level { price: 105, orders: vec![L3Order { id: d, qty: 4, ts: 4, owner: None }] }]);
+    assert_eq!(snap.order_count(), 4);
+    let attributed = ob.l3_snapshot(Attribution::Attributed);
+    assert_eq!(attributed.bids[1].orders[1].owner, Some(Owner { account: AccountId(7), correlation: 5 }));
+}
+
+#[test]
+fn snapshot_plus_feed_tracks_the_book() {
+    let mut ob = OrderBook::new();
+    ob.set_market_data(Some(Attribution::Attributed));
+    for (i, p) in [99, 98, 100, 97].into_iter().enumerate() { ob.submit_limit(Side::Buy, p, 2 + i as u64).unwrap(); }
+    ob.submit(OrderRequest::limit(Side::Sell, 103, 9).iceberg(3)).unwrap();
+    let (ask, _, _) = ob.submit_limit(Side::Sell, 104, 5).unwrap();
+    ob.drain_l3();
+    let mut view = ob.l3_snapshot(Attribution::Attributed);
+
+    let mut trades = Vec::new();
+    ob.submit_market(Side::Buy, 4).unwrap();
+    ob.submit_market(Side::Sell, 3).unwrap();
+    ob.reprice(ask, 102, &mut trades).unwrap();
+    ob.amend(ask, 102, 2, &mut trades).unwrap();
+    ob.submit_limit(Side::Sell, 101, 1).unwrap();
+    ob.submit_limit(Side::Buy, 102, 1).unwrap();
+    for ev in ob.drain_l3() { view.apply(ev); }
+    assert_eq!(view, ob.l3_snapshot(Attribution::Attributed));
+}
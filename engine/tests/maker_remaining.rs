@@ -0,0 +1,32 @@
+use match_engine::{AccountId, OrderBook, OrderRequest, SessionState, Side, StpAction, TimeInForce};
+
+#[test]
+fn trades_carry_the_makers_open_qty() {
+    let mut ob = OrderBook::new();
+    let (a, _, _) = ob.submit_limit(Side::Sell, 101, 3).unwrap();
+    let (ice, _, _) = ob.submit(OrderRequest::limit(Side::Sell, 101, 10).iceberg(4)).unwrap();
+    let (_, trades, _) = ob.submit_market(Side::Buy, 2).unwrap();
+    assert_eq!((trades[0].maker_id, trades[0].maker_remaining), (a, 1));
+    // The iceberg counts its reserve until the last slice goes
+    let (_, trades, _) = ob.submit_market(Side::Buy, 6).unwrap();
+    let residuals: Vec<_> = trades.iter().map(|t| (t.maker_id, t.qty, t.maker_remaining)).collect();
+    assert_eq!(residuals, vec![(a, 1, 0), (ice, 4, 6), (ice, 1, 5)]);
+    let (_, trades, _) = ob.submit_market(Side::Buy, 9).unwrap();
+    assert_eq!(trades.last().map(|t| t.maker_remaining), Some(0));
+    assert_eq!(ob.best_ask(), None);
+}
+
+#[test]
+fn decrements_and_auction_fills_carry_it_too() {
+    const DESK: AccountId = AccountId(4);
+    let mut ob = OrderBook::new();
+    let (a, _, _) = ob.submit(OrderRequest::limit(Side::Sell, 100, 5).with_account(DESK)).unwrap();
+    ob.set_self_trade_prevention(Some(StpAction::Decrement));
+    let (_, trades, _) = ob.submit(OrderRequest::limit(Side::Buy, 100, 2).with_account(DESK)).unwrap();
+    assert_eq!((trades[0].maker_id, trades[0].maker_remaining), (a, 3));
+
+    ob.set_session(SessionState::PreOpen);
+    ob.submit(OrderRequest::market(Side::Buy, 1).with_tif(TimeInForce::AtOpen)).unwrap();
+    let trades = ob.set_session(SessionState::Open).trades;
+    assert_eq!((trades[0].maker_id, trades[0].qty, trades[0].maker_remaining), (a, 1, 2));
+}
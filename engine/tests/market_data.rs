@@ -0,0 +1,44 @@
+use match_engine::{AccountId, Attribution, L3Event, OrderBook, OrderRequest, Owner, Side};
+
+const RISK_DESK: AccountId = AccountId(3);
+
+#[test]
+fn attributed_stream_and_public_view() {
+    let mut ob = OrderBook::new();
+    assert!(ob.drain_l3().is_empty());
+    ob.set_market_data(Some(Attribution::Attributed));
+    let (ask, _, _) = ob.submit(OrderRequest::limit(Side::Sell, 101, 5).with_account(RISK_DESK).with_correlation(77)).unwrap();
+    let (bid, _, _) = ob.submit_limit(Side::Buy, 99, 2).unwrap();
+    ob.submit_market(Side::Buy, 3).unwrap();
+    ob.cancel(bid).unwrap();
+    let owner = Some(Owner { account: RISK_DESK, correlation: 77 });
+    let events = ob.drain_l3();
+    assert_eq!(events, vec![
+        L3Event::Add { id: ask, side: Side::Sell, price: 101, qty: 5, ts: 1, owner },
+        L3Event::Add { id: bid, side: Side::Buy, price: 99, qty: 2, ts: 2, owner: Some(Owner { account: AccountId(0), correlation: 0 }) },
+        L3Event::Execute { id: ask, side: Side::Sell, price: 101, qty: 3, remaining: 2, owner },
+        L3Event::Delete { id: bid },
+    ]);
+    // The public view carries no attribution
+    assert!(events.into_iter().map(L3Event::public).all(|e| match e {
+        L3Event::Add { owner, .. } | L3Event::Execute { owner, .. } => owner.is_none(),
+        _ => true,
+    }));
+}
+
+#[test]
+fn anonymous_mode_never_attributes() {
+    let mut ob = OrderBook::new();
+    ob.set_market_data(Some(Attribution::Anonymous));
+    let (ask, _, _) = ob.submit(OrderRequest::limit(Side::Sell, 101, 5).with_account(RISK_DESK)).unwrap();
+    ob.submit_limit(Side::Buy, 101, 5).unwrap();
+    let mut out = Vec::new();
+    ob.drain_l3_into(&mut out);
+    assert_eq!(out, vec![
+        L3Event::Add { id: ask, side: Side::Sell, price: 101, qty: 5, ts: 1, owner: None },
+        L3Event::Execute { id: ask, side: Side::Sell, price: 101, qty: 5, remaining: 0, owner: None },
+    ]);
+    ob.set_market_data(None);
+    ob.submit_limit(Side::Buy, 100, 1).unwrap();
+    assert!(ob.drain_l3().is_empty());
+}
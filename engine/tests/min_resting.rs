@@ -0,0 +1,56 @@
+use match_engine::{AccountId, CancelReason, Command, EngineError, ManualClock, MinResting, MinRestingAction, OrderBook, OrderId, Side};
+
+fn book(action: MinRestingAction) -> (OrderBook, ManualClock) {
+    let clock = ManualClock::new(1_000);
+    let mut ob = OrderBook::new();
+    ob.set_clock(clock.clone());
+    ob.set_min_resting(Some(MinResting { micros: 500, action }));
+    (ob, clock)
+}
+
+#[test]
+fn early_cancel_is_rejected_until_the_order_has_rested() {
+    let (mut ob, clock) = book(MinRestingAction::Reject);
+    let (id, _, _) = ob.submit_limit(Side::Buy, 100, 5).unwrap();
+    clock.advance(499);
+    assert!(matches!(ob.cancel(id), Err(EngineError::Rejected(_))));
+    assert_eq!(ob.best_bid(), Some((100, 5)));
+    clock.advance(1);
+    assert_eq!(ob.cancel(id).unwrap().qty, 5);
+    // Unknown ids still fail as such, and the rule can be lifted
+    assert!(matches!(ob.cancel(OrderId(42)), Err(EngineError::UnknownOrder)));
+    let (id, _, _) = ob.submit_limit(Side::Sell, 105, 1).unwrap();
+    ob.set_min_resting(None);
+    assert!(ob.cancel(id).is_ok());
+}
+
+#[test]
+fn deferred_cancel_is_carried_out_once_due() {
+    let (mut ob, clock) = book(MinRestingAction::Defer);
+    let (id, _, _) = ob.submit_limit(Side::Sell, 101, 4).unwrap();
+    clock.advance(100);
+    // A sequenced cancel is accepted, the order stays for now
+    assert_eq!(ob.process_command(Command::Cancel { seq: 1, id, account: None }, &mut Vec::new()).unwrap(), (id, 0));
+    assert!(matches!(ob.cancel(id), Err(EngineError::Deferred { until: 1_500 })));
+    assert_eq!(ob.deferred_cancels(), &[(1_500, id)]);
+    assert_eq!(ob.next_deferred_due(), Some(1_500));
+    // Still fillable while it waits
+    let (_, trades, _) = ob.submit_market(Side::Buy, 1).unwrap();
+    assert_eq!(trades[0].qty, 1);
+
+    clock.set(1_500);
+    assert_eq!(ob.release_deferred_cancels().unwrap(), 1);
+    assert_eq!(ob.best_ask(), None);
+    let cancels = ob.drain_implicit_cancels();
+    assert_eq!(cancels.iter().map(|c| (c.id, c.qty, c.reason)).collect::<Vec<_>>(), vec![(id, 3, CancelReason::Deferred)]);
+    assert!(ob.deferred_cancels().is_empty());
+
+    // A deferred cancel on an order that traded away meanwhile is dropped when due
+    let (id, _, _) = ob.submit_limit(Side::Sell, 102, 2).unwrap();
+    ob.process_command(Command::Cancel { seq: 2, id, account: None }, &mut Vec::new()).unwrap();
+    ob.submit_market(Side::Buy, 2).unwrap();
+    clock.advance(500);
+    ob.process_command(Command::Market { seq: 3, side: Side::Buy, qty: 1, account: AccountId(0) }, &mut Vec::new()).unwrap();
+    assert!(ob.deferred_cancels().is_empty());
+    assert!(ob.drain_implicit_cancels().is_empty());
+}
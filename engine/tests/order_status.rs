@@ -0,0 +1,46 @@
+use match_engine::{OrderBook, OrderId, OrderRequest, OrderState, OrderStatus, SessionState, Side, TimeInForce};
+
+#[test]
+fn status_tracks_partial_fills_without_touching_the_order() {
+    let mut ob = OrderBook::new();
+    let mut trades = Vec::new();
+    ob.submit_limit(Side::Sell, 101, 2).unwrap();
+    // Takes the 2 offered, rests the other 8
+    let (id, _) = ob.submit_into(OrderRequest::limit(Side::Buy, 101, 10), &mut trades).unwrap();
+    let status = |ob: &OrderBook| ob.order_status(id).unwrap();
+    assert_eq!(status(&ob), OrderStatus { id, side: Side::Buy, price: 101, state: OrderState::PartiallyFilled, original_qty: 10, open_qty: 8, filled_qty: 2 });
+    ob.submit_market(Side::Sell, 3).unwrap();
+    assert_eq!((status(&ob).open_qty, status(&ob).filled_qty), (5, 5));
+    assert_eq!(ob.get_order(id).map(|o| (o.qty, o.order_qty)), Some((5, 10)));
+
+    // An amend sets the open part; what was filled stays filled
+    ob.amend(id, 101, 7, &mut trades).unwrap();
+    assert_eq!((status(&ob).original_qty, status(&ob).open_qty, status(&ob).filled_qty), (12, 7, 5));
+    // Restored from a snapshot it keeps its history; gone once fully filled
+    let mut ob = OrderBook::from_snapshot(&ob.snapshot());
+    assert_eq!(ob.order_status(id).unwrap().filled_qty, 5);
+    ob.submit_market(Side::Sell, 7).unwrap();
+    assert_eq!((ob.get_order(id), ob.order_status(id)), (None, None));
+}
+
+#[test]
+fn status_of_icebergs_stops_and_auction_orders() {
+    let mut ob = OrderBook::new();
+    let (iceberg, _) = ob.submit_into(OrderRequest::limit(Side::Sell, 105, 10).iceberg(3), &mut Vec::new()).unwrap();
+    assert_eq!(ob.order_status(iceberg).map(|s| (s.state, s.open_qty)), Some((OrderState::New, 10)));
+    // Filling through a slice refills it; the fill counts across slices
+    ob.submit_market(Side::Buy, 4).unwrap();
+    let o = ob.get_order(iceberg).unwrap();
+    assert_eq!((o.qty, o.reserve_qty), (2, 4));
+    assert_eq!(ob.order_status(iceberg).map(|s| (s.state, s.filled_qty)), Some((OrderState::PartiallyFilled, 4)));
+
+    let (stop, _) = ob.submit_into(OrderRequest::market(Side::Buy, 2).with_stop(110), &mut Vec::new()).unwrap();
+    assert_eq!(ob.get_order(stop), None);
+    assert_eq!(ob.order_status(stop).map(|s| (s.state, s.open_qty, s.filled_qty)), Some((OrderState::PendingTrigger, 2, 0)));
+
+    ob.set_session(SessionState::PreOpen);
+    let (auction, _) = ob.submit_into(OrderRequest::limit(Side::Buy, 100, 3).with_tif(TimeInForce::AtOpen), &mut Vec::new()).unwrap();
+    assert_eq!(ob.get_order(auction).map(|o| o.qty), Some(3));
+    assert_eq!(ob.order_status(auction).map(|s| s.state), Some(OrderState::PendingAuction));
+    assert_eq!(ob.order_status(OrderId(99)), None);
+}
@@ -0,0 +1,38 @@
+use match_engine::{Emission, OrderBook, OrderRequest, PostOnlyPolicy, Side, TimeInForce};
+
+#[test]
+fn every_channel_shares_one_gapless_sequence() {
+    let mut ob = OrderBook::new();
+    ob.set_depth_feed(true);
+    ob.set_fill_reports(true);
+    ob.set_post_only_policy(PostOnlyPolicy::Reprice { tick: 1 });
+    ob.submit_limit(Side::Sell, 101, 2).unwrap();
+    ob.submit_limit(Side::Sell, 102, 2).unwrap();
+    ob.submit(OrderRequest::limit(Side::Buy, 102, 1).post_only()).unwrap();
+    let (_, trades, _) = ob.submit_market(Side::Buy, 3).unwrap();
+    ob.submit(OrderRequest::limit(Side::Buy, 90, 4).with_tif(TimeInForce::Fok)).unwrap();
+
+    let mut seqs: Vec<u64> = trades.iter().map(|t| t.output_seq).collect();
+    seqs.extend(ob.drain_depth().iter().map(|d| d.output_seq));
+    seqs.extend(ob.drain_fill_reports().iter().map(|r| r.output_seq));
+    seqs.extend(ob.drain_implicit_cancels().iter().map(|c| c.output_seq));
+    seqs.extend(ob.drain_repriced().iter().map(|r| r.output_seq));
+    seqs.sort_unstable();
+    assert_eq!(seqs, (1..=ob.current_output_seq()).collect::<Vec<_>>());
+    // Two asks, the Repriced event and its bid, then the sweep's two trades each before its delta
+    assert_eq!(trades.iter().map(|t| t.output_seq).collect::<Vec<_>>(), vec![5, 7]);
+}
+
+#[test]
+fn suppressed_output_takes_no_numbers_and_snapshots_keep_the_sequence() {
+    let mut ob = OrderBook::new();
+    ob.set_depth_feed(true);
+    ob.submit_limit(Side::Sell, 101, 2).unwrap();
+    let before = ob.current_output_seq();
+    ob.submit(OrderRequest::limit(Side::Buy, 101, 1).with_emission(Emission::Suppressed)).unwrap();
+    assert_eq!(ob.current_output_seq(), before);
+
+    let mut restored = OrderBook::from_snapshot(&ob.snapshot());
+    let (_, trades, _) = restored.submit_market(Side::Buy, 1).unwrap();
+    assert_eq!(trades[0].output_seq, before + 1);
+}
@@ -0,0 +1,31 @@
+use match_engine::{EngineError, OrderBook, OrderRequest, PostOnlyPolicy, Repriced, Side};
+
+#[test]
+fn crossing_post_only_is_rejected_by_default() {
+    let mut ob = OrderBook::new();
+    ob.submit_limit(Side::Sell, 101, 3).unwrap();
+    assert!(matches!(ob.submit(OrderRequest::limit(Side::Buy, 101, 1).post_only()), Err(EngineError::Rejected(_))));
+    assert!(matches!(ob.submit(OrderRequest::market(Side::Buy, 1).post_only()), Err(EngineError::Rejected(_))));
+    // Not crossing: entered unchanged, no event
+    let (_, trades, remaining) = ob.submit(OrderRequest::limit(Side::Buy, 100, 1).post_only()).unwrap();
+    assert!(trades.is_empty());
+    assert_eq!(remaining, 1);
+    assert!(ob.drain_repriced().is_empty());
+    assert_eq!(ob.best_ask(), Some((101, 3)));
+}
+
+#[test]
+fn reprice_policy_moves_inside_the_touch() {
+    let mut ob = OrderBook::new();
+    ob.set_post_only_policy(PostOnlyPolicy::Reprice { tick: 5 });
+    ob.submit_limit(Side::Sell, 110, 3).unwrap();
+    ob.submit_limit(Side::Buy, 90, 3).unwrap();
+    let (buy, trades, _) = ob.submit(OrderRequest::limit(Side::Buy, 120, 2).post_only()).unwrap();
+    let (sell, _, _) = ob.submit(OrderRequest::limit(Side::Sell, 80, 2).post_only()).unwrap();
+    assert!(trades.is_empty());
+    let mut events = Vec::new();
+    ob.drain_repriced_into(&mut events);
+    assert_eq!(events, vec![Repriced { id: buy, original: 120, adjusted: 105, output_seq: 1 }, Repriced { id: sell, original: 80, adjusted: 110, output_seq: 2 }]);
+    assert_eq!(ob.best_bid(), Some((105, 2)));
+    assert_eq!(ob.top_n(1).1, vec![(110, 5, 2)]);
+}
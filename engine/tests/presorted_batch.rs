@@ -0,0 +1,34 @@
+use match_engine::{AccountId, Command, EngineError, OrderBook, Side, TimeInForce};
+
+fn cmds() -> Vec<Command> {
+    (0..200u64).map(|seq| {
+        let side = if seq % 2 == 0 { Side::Buy } else { Side::Sell };
+        match seq % 7 {
+            0 => Command::Market { seq, side, qty: 3, account: AccountId(0) },
+            _ => Command::Limit { seq, side, price: if side == Side::Buy { 100 - seq % 4 } else { 99 + seq % 4 }, qty: 1 + seq % 5, tif: TimeInForce::Gtc, account: AccountId(0) },
+        }
+    }).collect()
+}
+
+#[test]
+fn presorted_matches_checked_path() {
+    let (mut checked, mut presorted) = (OrderBook::new(), OrderBook::new());
+    let (mut a, mut b) = (Vec::new(), Vec::new());
+    let results = checked.process_commands_batch_checked_into(&mut cmds(), &mut a).unwrap();
+    assert_eq!(presorted.process_commands_batch_presorted_unchecked(&cmds(), &mut b).unwrap(), results);
+    assert!(!a.is_empty());
+    assert_eq!(a, b);
+    assert_eq!(checked.state_hash(), presorted.state_hash());
+}
+
+#[test]
+fn checked_path_still_sorts_and_rejects_duplicates() {
+    let mut ob = OrderBook::new();
+    let mut trades = Vec::new();
+    let mut shuffled = vec![Command::Limit { seq: 2, side: Side::Sell, price: 100, qty: 1, tif: TimeInForce::Gtc, account: AccountId(0) }, Command::Limit { seq: 1, side: Side::Sell, price: 101, qty: 1, tif: TimeInForce::Gtc, account: AccountId(0) }];
+    let (first, _) = ob.process_commands_batch_checked_into(&mut shuffled, &mut trades).unwrap()[0].clone().unwrap();
+    assert_eq!(ob.best_ask(), Some((100, 1)));
+    assert_eq!(ob.queue_position(first), Some((0, 0)));
+    let mut dup = vec![Command::Market { seq: 5, side: Side::Buy, qty: 1, account: AccountId(0) }, Command::Market { seq: 5, side: Side::Buy, qty: 1, account: AccountId(0) }];
+    assert!(matches!(ob.process_commands_batch_checked_into(&mut dup, &mut trades), Err(EngineError::InvalidSequence)));
+}
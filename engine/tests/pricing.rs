@@ -0,0 +1,54 @@
+use match_engine::{OrderBook, OrderRequest, Side, SideAggregate, SlabStorage};
+
+fn book() -> OrderBook<SlabStorage> {
+    let mut ob = OrderBook::with_storage(SlabStorage::default());
+    ob.submit_limit(Side::Buy, 99, 3).unwrap();
+    ob.submit_limit(Side::Buy, 98, 5).unwrap();
+    ob.submit_limit(Side::Sell, 101, 1).unwrap();
+    ob.submit_limit(Side::Sell, 103, 3).unwrap();
+    ob
+}
+
+#[test]
+fn weighted_mid_and_fair_price_lean_toward_the_heavier_side() {
+    let ob = book();
+    // Touch: bid 99 x3, ask 101 x1 -> (99 * 1 + 101 * 3) / 4
+    assert_eq!(ob.weighted_mid(1), Some(100.5));
+    assert_eq!(ob.imbalance(1), Some(0.5));
+    assert_eq!(ob.fair_price(1, 1.0), ob.weighted_mid(1));
+    assert_eq!(ob.fair_price(1, 0.0), Some(100.0));
+    // Two levels: bid avg 98.375 x8, ask avg 102.5 x4
+    assert_eq!(ob.side_aggregate(Side::Buy, 2), SideAggregate { levels: 2, qty: 8, notional: 787, best: 99, worst: 98 });
+    assert_eq!(ob.weighted_mid(2), Some((98.375 * 4.0 + 102.5 * 8.0) / 12.0));
+    assert_eq!(ob.imbalance(5), Some(4.0 / 12.0));
+}
+
+#[test]
+fn cached_aggregates_follow_fills_and_cancels() {
+    let mut ob = book();
+    ob.submit_limit(Side::Sell, 99, 2).unwrap();
+    let (id, _, _) = ob.submit(OrderRequest::limit(Side::Sell, 101, 4)).unwrap();
+    assert_eq!(ob.side_aggregate(Side::Sell, 1), SideAggregate { levels: 1, qty: 5, notional: 505, best: 101, worst: 101 });
+    ob.cancel(id).unwrap();
+    assert_eq!(ob.side_aggregate(Side::Buy, 1).qty, 1);
+    assert_eq!(ob.side_aggregate(Side::Sell, 1).qty, 1);
+    ob.submit_limit(Side::Buy, 101, 1).unwrap();
+    assert_eq!(ob.top_n(2).1, vec![(103, 3, 1)]);
+    // One side empty
+    ob.submit_limit(Side::Buy, 103, 3).unwrap();
+    assert_eq!(ob.weighted_mid(3), None);
+    assert_eq!(ob.fair_price(3, 1.0), None);
+}
+
+#[test]
+fn mid_spread_and_vwap_to_fill() {
+    let mut ob = book();
+    assert_eq!((ob.mid_price(), ob.spread()), (Some(100.0), Some(2)));
+    // A buyer of 3 takes 1 at 101 and 2 at 103; a seller of 8 sweeps both bid levels
+    assert_eq!(ob.vwap(Side::Buy, 3), Some(307.0 / 3.0));
+    assert_eq!(ob.vwap(Side::Sell, 8), Some(787.0 / 8.0));
+    assert_eq!((ob.vwap(Side::Sell, 9), ob.vwap(Side::Buy, 0)), (None, None));
+    ob.submit_market(Side::Buy, 4).unwrap();
+    assert_eq!((ob.mid_price(), ob.spread(), ob.imbalance(1)), (None, None, None));
+    assert_eq!(ob.vwap(Side::Sell, 1), Some(99.0));
+}
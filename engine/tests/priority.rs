@@ -0,0 +1,54 @@
+use match_engine::{Attribution, BookStorage, L3Event, LadderStorage, OrderBook, OrderId, Side, SkipListStorage, SlabStorage, SoaStorage};
+
+// Three orders at 100 with the first moved away and back, so FIFO differs from id order
+fn build<S: BookStorage>(ob: &mut OrderBook<S>) {
+    for qty in [1, 2, 3] { ob.submit_limit_into(Side::Buy, 100, qty, &mut Vec::new()).unwrap(); }
+    ob.reprice(OrderId(1), 99, &mut Vec::new()).unwrap();
+    ob.reprice(OrderId(1), 100, &mut Vec::new()).unwrap();
+    ob.submit_limit_into(Side::Sell, 105, 4, &mut Vec::new()).unwrap();
+}
+
+fn fifo<S: BookStorage>(ob: &OrderBook<S>) -> Vec<(u64, u64)> { ob.level_orders(Side::Buy, 100).iter().map(|o| (o.id.0, o.ts)).collect() }
+
+fn round_trip<S: BookStorage>(mut ob: OrderBook<S>, fresh: impl Fn() -> S) {
+    build(&mut ob);
+    let before = fifo(&ob);
+    assert_eq!(before.iter().map(|&(id, _)| id).collect::<Vec<_>>(), vec![2, 3, 1]);
+    let mut restored = OrderBook::from_snapshot_with_storage(fresh(), &ob.snapshot()).unwrap();
+    assert_eq!(fifo(&restored), before);
+    assert_eq!(restored.state_hash(), ob.state_hash());
+    // New orders queue behind restored ones, and fills follow the restored priority
+    let (late, _) = restored.submit_limit_into(Side::Buy, 100, 1, &mut Vec::new()).unwrap();
+    assert_eq!(restored.queue_position(late), Some((3, 6)));
+    let mut trades = Vec::new();
+    restored.submit_market_into(Side::Sell, 6, &mut trades).unwrap();
+    assert_eq!(trades.iter().map(|t| t.maker_id.0).collect::<Vec<_>>(), vec![2, 3, 1]);
+}
+
+#[test]
+fn snapshot_restore_preserves_fifo_on_every_backend() {
+    round_trip(OrderBook::new(), Default::default);
+    round_trip(OrderBook::with_storage(LadderStorage::with_range(50, 150)), || LadderStorage::with_range(50, 150));
+    round_trip(OrderBook::with_storage(SlabStorage::default()), SlabStorage::default);
+    round_trip(OrderBook::with_storage(SkipListStorage::default()), SkipListStorage::default);
+    round_trip(OrderBook::with_storage(SoaStorage::default()), SoaStorage::default);
+}
+
+#[test]
+fn priority_timestamps_order_restores_and_l3_adds() {
+    let mut ob = OrderBook::new();
+    ob.set_market_data(Some(Attribution::Anonymous));
+    build(&mut ob);
+    // The L3 stream alone reproduces the level's FIFO: the latest Add per id, ordered by ts
+    let mut adds: Vec<(u64, u64)> = Vec::new();
+    for e in ob.drain_l3() {
+        if let L3Event::Add { id, price: 100, ts, .. } = e { adds.retain(|&(i, _)| i != id.0); adds.push((id.0, ts)); }
+    }
+    adds.sort_by_key(|&(_, ts)| ts);
+    assert_eq!(adds, fifo(&ob));
+
+    // A snapshot listing a level out of order is restored by priority timestamp
+    let mut snap = ob.snapshot();
+    snap.bids[0].orders.reverse();
+    assert_eq!(fifo(&OrderBook::from_snapshot(&snap)), fifo(&ob));
+}
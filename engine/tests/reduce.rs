@@ -0,0 +1,50 @@
+use match_engine::{Attribution, BTreeStorage, BookStorage, Command, EngineError, HaltAction, HaltPolicy, L3Event, LadderStorage, OrderBook, OrderRequest, SessionState, Side, SkipListStorage, SlabStorage, SoaStorage};
+
+fn reduce_keeps_priority<S: BookStorage>(mut ob: OrderBook<S>) {
+    let (a, _, _) = ob.submit_limit(Side::Sell, 101, 5).unwrap();
+    let (b, _, _) = ob.submit_limit(Side::Sell, 101, 3).unwrap();
+    assert_eq!(ob.reduce(a, 2).unwrap(), 3);
+    assert_eq!((ob.queue_position(a), ob.queue_position(b)), (Some((0, 0)), Some((1, 3))));
+    assert_eq!(ob.top_n(5).1, vec![(101, 6, 2)]);
+    let status = ob.order_status(a).unwrap();
+    assert_eq!((status.original_qty, status.open_qty, status.filled_qty), (3, 3, 0));
+    assert!(matches!(ob.reduce(a, 0), Err(EngineError::Rejected(_))));
+    // Reducing by everything left (or more) cancels
+    assert_eq!(ob.reduce(a, 7).unwrap(), 0);
+    assert!(matches!(ob.reduce(a, 1), Err(EngineError::UnknownOrder)));
+    assert_eq!(ob.top_n(5).1, vec![(101, 3, 1)]);
+    assert!(ob.verify().is_ok());
+}
+
+#[test]
+fn reduce_on_every_backend() {
+    reduce_keeps_priority(OrderBook::<BTreeStorage>::default());
+    reduce_keeps_priority(OrderBook::<LadderStorage>::default());
+    reduce_keeps_priority(OrderBook::<SlabStorage>::default());
+    reduce_keeps_priority(OrderBook::<SoaStorage>::default());
+    reduce_keeps_priority(OrderBook::<SkipListStorage>::default());
+}
+
+#[test]
+fn icebergs_lose_reserve_first_and_cancel_rules_apply() {
+    let mut ob = OrderBook::new();
+    ob.set_market_data(Some(Attribution::Anonymous));
+    let (ice, _, _) = ob.submit(OrderRequest::limit(Side::Buy, 99, 10).iceberg(4)).unwrap();
+    ob.drain_l3();
+    let mut trades = Vec::new();
+    assert_eq!(ob.process_command(Command::Reduce { seq: 1, id: ice, qty_delta: 5 }, &mut trades).unwrap(), (ice, 5));
+    assert_eq!(ob.process_command(Command::Reduce { seq: 2, id: ice, qty_delta: 3 }, &mut trades).unwrap(), (ice, 2));
+    assert_eq!(ob.drain_l3(), vec![L3Event::Update { id: ice, qty: 4 }, L3Event::Update { id: ice, qty: 2 }]);
+
+    // Allowed in cancel-only mode, not on a frozen book
+    ob.set_cancel_only(true);
+    assert_eq!(ob.reduce(ice, 1).unwrap(), 1);
+    ob.set_cancel_only(false);
+    ob.set_halt_policy(HaltPolicy { resting: HaltAction::Keep, reopening_auction: false });
+    ob.set_session(SessionState::Halted);
+    assert!(matches!(ob.reduce(ice, 1), Err(EngineError::Rejected(_))));
+    ob.set_session(SessionState::Open);
+    let stop = ob.submit(OrderRequest::market(Side::Buy, 3).with_stop(120)).unwrap().0;
+    assert!(matches!(ob.reduce(stop, 1), Err(EngineError::Rejected(_))));
+    assert!(trades.is_empty());
+}
@@ -0,0 +1,62 @@
+use match_engine::{AccountId, EngineError, OrderBook, OrderRequest, Side};
+
+const A: AccountId = AccountId(1);
+const B: AccountId = AccountId(2);
+
+#[test]
+fn reduce_only_needs_an_opposite_position() {
+    let mut ob = OrderBook::new();
+    let req = OrderRequest::limit(Side::Sell, 100, 5).with_account(A).reduce_only();
+    assert!(matches!(ob.submit(req), Err(EngineError::Rejected(_))));
+    ob.enable_positions();
+    assert!(matches!(ob.submit(req), Err(EngineError::Rejected(_))));
+    ob.set_position(A, -3);
+    assert!(matches!(ob.submit(req), Err(EngineError::Rejected(_))));
+}
+
+#[test]
+fn reduce_only_orders_follow_the_position() {
+    let mut ob = OrderBook::new();
+    ob.enable_positions();
+    // A buys 5 from B
+    ob.submit(OrderRequest::limit(Side::Sell, 100, 5).with_account(B)).unwrap();
+    ob.submit(OrderRequest::limit(Side::Buy, 100, 5).with_account(A)).unwrap();
+    assert_eq!((ob.position(A), ob.position(B)), (5, -5));
+
+    // Entry qty is trimmed to the position; the younger order gets what is left
+    let (first, _, _) = ob.submit(OrderRequest::limit(Side::Sell, 110, 8).with_account(A).reduce_only()).unwrap();
+    let (second, _, _) = ob.submit(OrderRequest::limit(Side::Sell, 105, 2).with_account(A).reduce_only()).unwrap();
+    assert_eq!(ob.drain_reduce_only_updates(), vec![(second, 0)]);
+    assert_eq!(ob.top_n(5).1, vec![(110, 5, 1)]);
+
+    // A sells 3 elsewhere: the resting order shrinks to the remaining 2
+    ob.submit(OrderRequest::limit(Side::Buy, 90, 3).with_account(B)).unwrap();
+    ob.submit(OrderRequest::market(Side::Sell, 3).with_account(A)).unwrap();
+    assert_eq!(ob.position(A), 2);
+    assert_eq!(ob.drain_reduce_only_updates(), vec![(first, 2)]);
+    assert_eq!(ob.top_n(5).1, vec![(110, 2, 1)]);
+
+    // Position flattened externally: the order is cancelled
+    ob.set_position(A, 0);
+    assert_eq!(ob.drain_reduce_only_updates(), vec![(first, 0)]);
+    assert!(ob.best_ask().is_none());
+    assert!(ob.cancel(first).is_err());
+}
+
+#[test]
+fn maker_fill_reduces_resting_reduce_only_orders() {
+    let mut ob = OrderBook::new();
+    ob.set_position(A, 4);
+    let (bid, _, _) = ob.submit(OrderRequest::limit(Side::Buy, 95, 2).with_account(A)).unwrap();
+    let (ask, _, _) = ob.submit(OrderRequest::limit(Side::Sell, 101, 4).with_account(A).reduce_only()).unwrap();
+    // Filling A's bid as maker grows the long position; the reduce-only ask is left alone
+    ob.submit(OrderRequest::market(Side::Sell, 2).with_account(B)).unwrap();
+    assert_eq!(ob.position(A), 6);
+    assert!(ob.drain_reduce_only_updates().is_empty());
+    // Partial fill of the reduce-only ask itself keeps it consistent with the position
+    ob.submit(OrderRequest::market(Side::Buy, 1).with_account(B)).unwrap();
+    assert_eq!(ob.position(A), 5);
+    assert!(ob.drain_reduce_only_updates().is_empty());
+    assert!(ob.cancel(bid).is_err());
+    assert_eq!(ob.cancel(ask).unwrap().qty, 3);
+}
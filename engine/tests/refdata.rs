@@ -0,0 +1,44 @@
+use match_engine::{BookSnapshot, Command, EngineError, OrderBook, ReferenceValue, ReferenceValues, Side};
+
+#[test]
+fn reference_values_are_sequenced_and_carried_in_snapshots() {
+    let mut ob = OrderBook::new();
+    let mut trades = Vec::new();
+    let mut cmds = [
+        Command::Reference { seq: 1, value: ReferenceValue::IndexPrice(100) },
+        Command::Reference { seq: 2, value: ReferenceValue::FundingRate(-25) },
+        Command::Reference { seq: 3, value: ReferenceValue::SettlementPrice(98) },
+        Command::Reference { seq: 4, value: ReferenceValue::IndexPrice(101) },
+    ];
+    ob.process_commands_batch_checked_into(&mut cmds, &mut trades).unwrap();
+    let expected = ReferenceValues { index_price: Some(101), settlement_price: Some(98), funding_rate: Some(-25) };
+    assert_eq!(ob.reference_values(), expected);
+    assert_eq!(ob.market_data_snapshot(5, 1, 0).reference, expected);
+
+    let _ = ob.submit_limit(Side::Buy, 100, 3);
+    let _ = ob.submit_limit(Side::Sell, 104, 1);
+    // Weighted mid (100 * 1 + 104 * 3) / 4 = 103 against the index
+    assert_eq!(ob.premium(1), Some(2.0));
+    let snap: BookSnapshot = ob.snapshot();
+    assert_eq!(snap.reference, expected);
+    assert_eq!(OrderBook::from_snapshot(&snap).reference_values(), expected);
+}
+
+#[test]
+fn price_band_follows_the_reference_price() {
+    let mut ob = OrderBook::new();
+    ob.set_price_band(Some(500));
+    // No reference yet: no check
+    assert!(ob.submit_limit(Side::Buy, 50, 1).is_ok());
+
+    ob.set_reference_value(ReferenceValue::SettlementPrice(1_000));
+    assert!(ob.submit_limit(Side::Sell, 1_050, 1).is_ok());
+    assert!(matches!(ob.submit_limit(Side::Sell, 1_051, 1), Err(EngineError::Rejected(_))));
+    // The index takes over from the settlement price
+    ob.set_reference_value(ReferenceValue::IndexPrice(2_000));
+    assert!(matches!(ob.submit_limit(Side::Buy, 1_050, 1), Err(EngineError::Rejected(_))));
+    let (id, _, _) = ob.submit_limit(Side::Buy, 1_900, 2).unwrap();
+    let mut trades = Vec::new();
+    assert!(matches!(ob.reprice(id, 1_899, &mut trades), Err(EngineError::Rejected(_))));
+    assert!(ob.submit_market(Side::Sell, 1).is_ok());
+}
@@ -0,0 +1,37 @@
+use match_engine::{BTreeStorage, BookStorage, Command, EngineError, LadderStorage, OrderBook, Side, SkipListStorage, SlabStorage, SoaStorage};
+
+fn passive_move_goes_to_back_of_level<S: BookStorage>(mut ob: OrderBook<S>) {
+    let (a, _, _) = ob.submit_limit(Side::Buy, 99, 2).unwrap();
+    let (b, _, _) = ob.submit_limit(Side::Buy, 98, 3).unwrap();
+    ob.submit_limit(Side::Sell, 105, 4).unwrap();
+    assert_eq!(ob.reprice(a, 98, &mut Vec::new()).unwrap(), 2);
+    assert_eq!(ob.queue_position(b), Some((0, 0)));
+    assert_eq!(ob.queue_position(a), Some((1, 3)));
+    assert_eq!(ob.top_n(5).0, vec![(98, 5, 2)]);
+    // Back up again; the id keeps working for cancel
+    ob.reprice(a, 100, &mut Vec::new()).unwrap();
+    assert_eq!(ob.best_bid(), Some((100, 2)));
+    assert_eq!(ob.cancel(a).unwrap().price, 100);
+    assert!(matches!(ob.reprice(a, 97, &mut Vec::new()), Err(EngineError::UnknownOrder)));
+}
+
+#[test]
+fn passive_reprice_on_every_backend() {
+    passive_move_goes_to_back_of_level(OrderBook::<BTreeStorage>::default());
+    passive_move_goes_to_back_of_level(OrderBook::<LadderStorage>::default());
+    passive_move_goes_to_back_of_level(OrderBook::<SlabStorage>::default());
+    passive_move_goes_to_back_of_level(OrderBook::<SoaStorage>::default());
+    passive_move_goes_to_back_of_level(OrderBook::<SkipListStorage>::default());
+}
+
+#[test]
+fn marketable_reprice_trades_as_taker_in_batch() {
+    let mut ob = OrderBook::new();
+    let (ask, _, _) = ob.submit_limit(Side::Sell, 101, 3).unwrap();
+    let (bid, _, _) = ob.submit_limit(Side::Buy, 99, 5).unwrap();
+    let mut trades = Vec::new();
+    let results = ob.process_commands_batch_checked_into(&mut [Command::Reprice { seq: 1, id: bid, new_price: 101 }], &mut trades).unwrap();
+    assert_eq!(results, vec![Ok((bid, 2))]);
+    assert_eq!(trades.iter().map(|t| (t.taker_id, t.maker_id, t.price, t.qty)).collect::<Vec<_>>(), vec![(bid, ask, 101, 3)]);
+    assert_eq!((ob.best_bid(), ob.best_ask()), (Some((101, 2)), None));
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "serde")]
+use match_engine::{AccountId, BookSnapshot, Command, OrderBook, OrderId, OrderRequest, ReferenceValue, SessionState, Side, TimeInForce, Trade};
+
+fn commands() -> Vec<Command> {
+    vec![
+        Command::Limit { seq: 1, side: Side::Sell, price: 101, qty: 5, tif: TimeInForce::Gtc, account: AccountId(3) },
+        Command::Submit { seq: 2, req: OrderRequest::limit(Side::Sell, 103, 9).iceberg(3).with_correlation(4) },
+        Command::Submit { seq: 3, req: OrderRequest::market(Side::Buy, 2).with_stop(104) },
+        Command::Market { seq: 4, side: Side::Buy, qty: 2, account: AccountId(0) },
+        Command::Reference { seq: 5, value: ReferenceValue::FundingRate(-7) },
+        Command::Cancel { seq: 6, id: OrderId(9), account: None },
+        Command::Session { seq: 7, state: SessionState::Open },
+    ]
+}
+
+#[test]
+fn commands_and_trades_round_trip_through_json_and_bincode() {
+    let cmds = commands();
+    let json = serde_json::to_string(&cmds).unwrap();
+    assert_eq!(serde_json::from_str::<Vec<Command>>(&json).unwrap(), cmds);
+    assert_eq!(bincode::deserialize::<Vec<Command>>(&bincode::serialize(&cmds).unwrap()).unwrap(), cmds);
+
+    let mut ob = OrderBook::new();
+    let mut trades = Vec::new();
+    let _ = ob.process_commands_batch_checked_into(&mut cmds.clone(), &mut trades);
+    assert_eq!(trades.len(), 1);
+    let json = serde_json::to_string(&trades).unwrap();
+    assert_eq!(serde_json::from_str::<Vec<Trade>>(&json).unwrap(), trades);
+    assert_eq!(bincode::deserialize::<Vec<Trade>>(&bincode::serialize(&trades).unwrap()).unwrap(), trades);
+}
+
+#[test]
+fn snapshots_round_trip_and_restore() {
+    let mut ob = OrderBook::new();
+    let _ = ob.process_commands_batch_checked_into(&mut commands(), &mut Vec::new());
+    ob.submit_limit(Side::Buy, 99, 4).unwrap();
+    let snap = ob.snapshot();
+    assert_eq!(snap.stops.len(), 1);
+    let json = serde_json::to_vec(&snap).unwrap();
+    let back: BookSnapshot = serde_json::from_slice(&json).unwrap();
+    assert_eq!(back, snap);
+    let bin: BookSnapshot = bincode::deserialize(&bincode::serialize(&snap).unwrap()).unwrap();
+    assert_eq!(OrderBook::from_snapshot(&bin).state_hash(), ob.state_hash());
+}
@@ -0,0 +1,23 @@
+use match_engine::{OrderBook, OrderRequest, SessionState, Side, TimeInForce};
+
+#[test]
+fn day_orders_expire_at_session_close() {
+    let mut ob = OrderBook::new();
+    let (gtc_bid, _, _) = ob.submit_limit(Side::Buy, 99, 5).unwrap();
+    let (day_bid, _, _) = ob.submit(OrderRequest::limit(Side::Buy, 100, 3).with_tif(TimeInForce::Day)).unwrap();
+    let (day_ask, _, _) = ob.submit(OrderRequest::limit(Side::Sell, 105, 4).with_tif(TimeInForce::Day)).unwrap();
+    // Partially filled Day order keeps its TIF
+    let _ = ob.submit_market(Side::Sell, 1).unwrap();
+
+    assert!(ob.set_session(SessionState::Open).expired.is_empty());
+    let expired = ob.set_session(SessionState::Closed).expired;
+    assert_eq!(expired.iter().map(|o| (o.id, o.qty)).collect::<Vec<_>>(), vec![(day_bid, 2), (day_ask, 4)]);
+    assert_eq!(ob.session(), SessionState::Closed);
+    assert!(ob.cancel(day_bid).is_err());
+
+    // End-of-day snapshot carries only GTC orders
+    let snap = ob.snapshot();
+    assert_eq!(snap.order_count(), 1);
+    assert_eq!(snap.bids[0].orders[0].id, gtc_bid);
+    assert!(ob.set_session(SessionState::Closed).expired.is_empty());
+}
@@ -0,0 +1,61 @@
+use match_engine::{EngineError, OrderBook, OrderRequest, Side};
+
+fn short(price: u64, qty: u64) -> OrderRequest { OrderRequest::limit(Side::Sell, price, qty).short_sell() }
+
+#[test]
+fn uptick_rule_is_off_by_default() {
+    let mut ob = OrderBook::new();
+    ob.submit_limit(Side::Buy, 100, 1).unwrap();
+    ob.submit_market(Side::Sell, 1).unwrap();
+    ob.submit_limit(Side::Buy, 99, 1).unwrap();
+    let (_, trades, _) = ob.submit(short(99, 1)).unwrap();
+    assert_eq!(trades.len(), 1);
+}
+
+#[test]
+fn short_sales_need_a_plus_or_zero_plus_tick() {
+    let mut ob = OrderBook::new();
+    ob.set_uptick_rule(true);
+    // No reference price yet
+    ob.submit(short(105, 1)).unwrap();
+    assert!(matches!(ob.submit(OrderRequest::market(Side::Buy, 1).short_sell()), Err(EngineError::Rejected(_))));
+
+    // Trades at 100 then 101: 101 is a plus tick, so 101 (zero-plus) and above are allowed
+    ob.submit_limit(Side::Buy, 100, 1).unwrap();
+    ob.submit_market(Side::Sell, 1).unwrap();
+    ob.submit_limit(Side::Buy, 101, 1).unwrap();
+    ob.submit_market(Side::Sell, 1).unwrap();
+    assert_eq!(ob.last_trade_price(), Some(101));
+    assert!(matches!(ob.submit(short(100, 1)), Err(EngineError::Rejected(_))));
+    let (resting, trades, _) = ob.submit(short(101, 2)).unwrap();
+    assert!(trades.is_empty());
+
+    // Downtick to 99: shorts at 99 or 100 are now refused, market shorts too
+    ob.submit_limit(Side::Buy, 99, 5).unwrap();
+    ob.submit_market(Side::Sell, 1).unwrap();
+    assert!(matches!(ob.submit(short(99, 1)), Err(EngineError::Rejected(_))));
+    assert!(matches!(ob.submit(OrderRequest::market(Side::Sell, 1).short_sell()), Err(EngineError::Rejected(_))));
+    // A regular sell is unaffected
+    assert_eq!(ob.submit(OrderRequest::market(Side::Sell, 1)).unwrap().1.len(), 1);
+    assert!(ob.cancel(resting).is_ok());
+}
+
+#[test]
+fn short_sale_sweep_stops_at_a_downtick() {
+    let mut ob = OrderBook::new();
+    ob.set_uptick_rule(true);
+    ob.submit_limit(Side::Buy, 99, 1).unwrap();
+    ob.submit_market(Side::Sell, 1).unwrap();
+    ob.submit_limit(Side::Buy, 102, 2).unwrap();
+    ob.submit_limit(Side::Buy, 101, 2).unwrap();
+    // Executes at 102 (plus tick); 101 would be a downtick from 102 and the remainder would cross, so it is dropped
+    let (id, trades, _) = ob.submit(short(100, 5)).unwrap();
+    assert_eq!(trades.iter().map(|t| (t.price, t.qty)).collect::<Vec<_>>(), vec![(102, 2)]);
+    assert_eq!(ob.best_bid(), Some((101, 2)));
+    assert!(ob.cancel(id).is_err());
+    // Not marketable after the sweep: the remainder rests
+    ob.submit_limit(Side::Buy, 103, 1).unwrap();
+    let (id, trades, _) = ob.submit(short(102, 3)).unwrap();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(ob.cancel(id).unwrap().qty, 2);
+}
@@ -0,0 +1,68 @@
+use match_engine::{AccountId, BookSnapshot, LadderStorage, OrderBook, OrderRequest, ReferenceValue, SessionState, Side, TimeInForce};
+
+fn busy_book() -> OrderBook {
+    let mut ob = OrderBook::new();
+    for i in 0..6 {
+        ob.submit(OrderRequest::limit(Side::Buy, 100 - i % 3, 2 + i).with_account(AccountId(i)).with_correlation(i * 10)).unwrap();
+        ob.submit(OrderRequest::limit(Side::Sell, 105 + i % 3, 1 + i).with_tif(TimeInForce::Day)).unwrap();
+    }
+    ob.submit(OrderRequest::limit(Side::Sell, 104, 9).iceberg(2)).unwrap();
+    ob.submit_market(Side::Buy, 3).unwrap();
+    ob.submit(OrderRequest::market(Side::Sell, 1).with_stop(90)).unwrap();
+    ob.set_reference_value(ReferenceValue::FundingRate(-25));
+    ob
+}
+
+#[test]
+fn binary_snapshot_restores_ids_timestamps_and_fifo() {
+    let mut ob = busy_book();
+    let mut bytes = Vec::new();
+    ob.save_snapshot(&mut bytes).unwrap();
+    assert!(bytes.len() < 8 * 11 * ob.snapshot().order_count());
+    let mut loaded = OrderBook::load_snapshot(bytes.as_slice()).unwrap();
+    assert_eq!(loaded.snapshot(), ob.snapshot());
+    assert_eq!(loaded.state_hash(), ob.state_hash());
+    loaded.verify().unwrap();
+
+    // Counters carry on: the next order gets the same id and queue place, and trades come out the same
+    for book in [&mut ob, &mut loaded] { book.submit_limit(Side::Buy, 98, 1).unwrap(); }
+    let (a, b) = (ob.submit_market(Side::Sell, 30).unwrap(), loaded.submit_market(Side::Sell, 30).unwrap());
+    assert_eq!(a, b);
+    assert_eq!(loaded.snapshot(), ob.snapshot());
+
+    // Any backend can load it
+    let ladder = OrderBook::load_snapshot_with_storage(LadderStorage::with_range(50, 150), bytes.as_slice()).unwrap();
+    assert_eq!(ladder.state_hash(), BookSnapshot::read_from(bytes.as_slice()).unwrap().state_hash());
+}
+
+#[test]
+fn rejects_foreign_and_truncated_input() {
+    let mut bytes = Vec::new();
+    busy_book().save_snapshot(&mut bytes).unwrap();
+    assert!(OrderBook::load_snapshot(&b"MESNAP09"[..]).is_err());
+    assert!(OrderBook::load_snapshot(&bytes[..bytes.len() - 1]).is_err());
+    bytes.push(0);
+    assert!(OrderBook::load_snapshot(bytes.as_slice()).is_err());
+    let mut empty = Vec::new();
+    OrderBook::new().save_snapshot(&mut empty).unwrap();
+    assert_eq!(OrderBook::load_snapshot(empty.as_slice()).unwrap().snapshot(), BookSnapshot::default());
+}
+
+#[test]
+fn session_state_survives_both_restore_paths() {
+    for state in [SessionState::Halted, SessionState::PreOpen, SessionState::Closed] {
+        let mut ob = busy_book();
+        ob.set_session(state);
+        let mut bytes = Vec::new();
+        ob.save_snapshot(&mut bytes).unwrap();
+        let (loaded, cloned) = (OrderBook::load_snapshot(bytes.as_slice()).unwrap(), OrderBook::from_snapshot(&ob.snapshot()));
+        assert_eq!((loaded.session(), cloned.session()), (state, state));
+        assert_eq!(loaded.state_hash(), ob.state_hash());
+    }
+
+    // A halted book keeps refusing orders after the restart
+    let mut ob = busy_book();
+    ob.set_session(SessionState::Halted);
+    assert_ne!(ob.state_hash(), busy_book().state_hash());
+    assert!(OrderBook::from_snapshot(&ob.snapshot()).submit_limit(Side::Buy, 99, 1).is_err());
+}
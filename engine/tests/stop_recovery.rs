@@ -0,0 +1,60 @@
+use match_engine::{AccountId, OrderBook, OrderRequest, SessionState, Side, TimeInForce};
+
+// Stops on both sides, an iceberg and a trade price to trigger from
+fn book_with_stops() -> OrderBook {
+    let mut ob = OrderBook::new();
+    for (price, qty) in [(100, 1), (101, 2), (102, 2), (103, 5)] { ob.submit_limit(Side::Sell, price, qty).unwrap(); }
+    ob.submit(OrderRequest::limit(Side::Buy, 95, 9).iceberg(3)).unwrap();
+    ob.submit_limit(Side::Buy, 94, 4).unwrap();
+    ob.submit(OrderRequest::market(Side::Buy, 2).with_stop(101).with_account(AccountId(7))).unwrap();
+    ob.submit(OrderRequest::limit(Side::Buy, 102, 4).with_stop(102).with_correlation(9)).unwrap();
+    ob.submit(OrderRequest::market(Side::Sell, 4).with_stop(96)).unwrap();
+    ob.submit(OrderRequest::limit(Side::Sell, 94, 2).with_stop(97).iceberg(1)).unwrap();
+    ob.submit_limit(Side::Buy, 100, 1).unwrap();
+    ob
+}
+
+#[test]
+fn restored_stops_trigger_like_the_original() {
+    let mut ob = book_with_stops();
+    let snap = ob.snapshot();
+    assert_eq!(snap.stops.len(), 4);
+    assert_eq!(snap.stops.iter().map(|(_, r)| r.stop_price.unwrap()).collect::<Vec<_>>(), vec![101, 102, 97, 96]);
+    assert_eq!(snap.last_trade, Some(100));
+    let mut restored = OrderBook::from_snapshot(&snap);
+    assert_eq!(restored.snapshot(), snap);
+    assert_eq!(restored.state_hash(), ob.state_hash());
+    assert_eq!(snap.state_hash(), ob.state_hash());
+
+    // The same flow fires the same stops, in the same order, with the same trades
+    let run = |book: &mut OrderBook| {
+        let (_, mut trades, _) = book.submit_limit(Side::Buy, 101, 1).unwrap();
+        trades.extend(book.submit_market(Side::Sell, 12).unwrap().1);
+        (trades, book.drain_stop_activations())
+    };
+    let (trades, activations) = run(&mut ob);
+    assert_eq!(activations.len(), 4);
+    assert_eq!(run(&mut restored), (trades, activations));
+    assert_eq!(restored.snapshot(), ob.snapshot());
+}
+
+#[test]
+fn auction_orders_survive_a_snapshot() {
+    let mut ob = OrderBook::new();
+    ob.submit_limit(Side::Sell, 101, 4).unwrap();
+    ob.submit_limit(Side::Buy, 101, 1).unwrap();
+    ob.set_session(SessionState::PreOpen);
+    ob.submit(OrderRequest::market(Side::Buy, 2).with_tif(TimeInForce::AtOpen)).unwrap();
+    ob.submit(OrderRequest::limit(Side::Buy, 102, 1).with_tif(TimeInForce::AtOpen)).unwrap();
+    let snap = ob.snapshot();
+    assert_eq!(snap.auction.len(), 2);
+    let mut restored = OrderBook::from_snapshot(&snap);
+    assert_eq!(restored.auction_orders(), ob.auction_orders());
+    assert_eq!(restored.state_hash(), ob.state_hash());
+    // A restored book comes back in continuous trading; re-entering the phase uncrosses identically
+    restored.set_session(SessionState::PreOpen);
+    let (a, b) = (ob.set_session(SessionState::Open).trades, restored.set_session(SessionState::Open).trades);
+    assert_eq!(a, b);
+    assert_eq!(a.iter().map(|t| (t.price, t.qty)).collect::<Vec<_>>(), vec![(101, 2), (101, 1)]);
+    assert_eq!(restored.snapshot(), ob.snapshot());
+}
@@ -0,0 +1,54 @@
+use match_engine::{CancelReason, OrderBook, OrderRequest, ReferenceValue, SessionState, Side, StopActivation, TimeInForce};
+
+#[test]
+fn stops_fire_when_the_last_trade_reaches_them_and_cascade() {
+    let mut ob = OrderBook::new();
+    for (price, qty) in [(100, 1), (101, 2), (102, 2), (103, 5)] { ob.submit_limit(Side::Sell, price, qty).unwrap(); }
+    let (stop_market, _, _) = ob.submit(OrderRequest::market(Side::Buy, 2).with_stop(101)).unwrap();
+    let (stop_limit, _, _) = ob.submit(OrderRequest::limit(Side::Buy, 102, 4).with_stop(102)).unwrap();
+    let (sell_stop, _, _) = ob.submit(OrderRequest::market(Side::Sell, 1).with_stop(90)).unwrap();
+    assert_eq!((ob.stop_count(), ob.best_bid()), (3, None));
+    assert_eq!(ob.stop_order(stop_limit).unwrap().stop_price, Some(102));
+
+    // Trading at 100 reaches neither buy stop; 101 fires the stop-market, whose fill at 102 fires the stop-limit
+    let (_, trades, _) = ob.submit_limit(Side::Buy, 100, 1).unwrap();
+    assert_eq!(trades.len(), 1);
+    assert!(ob.drain_stop_activations().is_empty());
+    let (taker, trades, _) = ob.submit_limit(Side::Buy, 101, 1).unwrap();
+    assert_eq!(trades.iter().map(|t| (t.taker_id, t.price, t.qty)).collect::<Vec<_>>(), vec![
+        (taker, 101, 1), (stop_market, 101, 1), (stop_market, 102, 1), (stop_limit, 102, 1),
+    ]);
+    assert_eq!(ob.drain_stop_activations(), vec![
+        StopActivation { id: stop_market, side: Side::Buy, stop_price: 101, last_price: 101 },
+        StopActivation { id: stop_limit, side: Side::Buy, stop_price: 102, last_price: 102 },
+    ]);
+    // The stop-limit rests its remainder at its limit under its own id
+    assert_eq!(ob.best_bid(), Some((102, 3)));
+    assert_eq!(ob.queue_position(stop_limit), Some((0, 0)));
+    assert_eq!(ob.stop_count(), 1);
+    assert_eq!(ob.cancel(sell_stop).unwrap().qty, 1);
+    assert_eq!(ob.stop_count(), 0);
+}
+
+#[test]
+fn held_stops_expire_and_are_rechecked_on_activation() {
+    let mut ob = OrderBook::new();
+    ob.submit_limit(Side::Buy, 100, 5).unwrap();
+    ob.submit_limit(Side::Sell, 105, 5).unwrap();
+    ob.submit(OrderRequest::market(Side::Sell, 1).with_stop(100).with_tif(TimeInForce::Day)).unwrap();
+    assert!(ob.submit(OrderRequest::limit(Side::Sell, 99, 1).with_stop(100).post_only()).is_err());
+    let change = ob.set_session(SessionState::Closed);
+    assert_eq!(change.expired.len(), 1);
+    assert_eq!(ob.drain_implicit_cancels()[0].reason, CancelReason::Expired);
+    ob.set_session(SessionState::Open);
+
+    // A stop-limit whose limit is outside the band by the time it fires is dropped
+    let (id, _, _) = ob.submit(OrderRequest::limit(Side::Sell, 95, 1).with_stop(100)).unwrap();
+    ob.set_reference_value(ReferenceValue::IndexPrice(100));
+    ob.set_price_band(Some(200));
+    ob.submit_market(Side::Sell, 1).unwrap();
+    assert_eq!(ob.drain_stop_activations().len(), 1);
+    let cancels = ob.drain_implicit_cancels();
+    assert_eq!(cancels.iter().map(|c| (c.id, c.reason)).collect::<Vec<_>>(), vec![(id, CancelReason::StopRejected)]);
+    assert_eq!(ob.best_bid(), Some((100, 4)));
+}
@@ -0,0 +1,143 @@
+use match_engine::{BTreeStorage, BookStorage, Depth, EngineError, LadderStorage, OrderBook, OrderId, Side, SkipListStorage, SlabStorage, SoaStorage, LADDER_MAX_SPAN};
+
+type Tape = Vec<(u64, u64, u64, u64)>;
+
+// Deterministic mixed flow: resting limits around a drifting mid, crossing limits, markets and cancels.
+// Returns every trade and the final depth so backends can be compared exactly.
+fn run_flow<S: BookStorage>(mut ob: OrderBook<S>) -> (Tape, (Depth, Depth)) {
+    let mut trades = Vec::new();
+    let mut live: Vec<OrderId> = Vec::new();
+    let mut x: u64 = 0x9E37_79B9_7F4A_7C15;
+    for i in 0..5_000u64 {
+        x ^= x << 13; x ^= x >> 7; x ^= x << 17;
+        let side = if x & 1 == 0 { Side::Buy } else { Side::Sell };
+        let qty = 1 + (x >> 8) % 9;
+        match (x >> 4) % 10 {
+            0 => { let _ = ob.submit_market_into(side, qty, &mut trades); }
+            1 | 2 if !live.is_empty() => {
+                let id = live.swap_remove(((x >> 16) as usize) % live.len());
+                let _ = ob.cancel(id);
+            }
+            _ => {
+                let offset = (x >> 20) % 12;
+                let mid = 1_000 + (i / 500);
+                let price = match side { Side::Buy => mid + 2 - offset, Side::Sell => mid - 2 + offset };
+                let (id, remaining) = ob.submit_limit_into(side, price, qty, &mut trades).unwrap();
+                if remaining > 0 { live.push(id); }
+            }
+        }
+    }
+    let tape = trades.iter().map(|t| (t.taker_id.0, t.maker_id.0, t.price, t.qty)).collect();
+    (tape, ob.top_n(usize::MAX))
+}
+
+#[test]
+fn backends_produce_identical_results() {
+    let reference = run_flow(OrderBook::with_storage(BTreeStorage::default()));
+    assert!(!reference.0.is_empty());
+    assert_eq!(run_flow(OrderBook::with_storage(LadderStorage::default())), reference);
+    assert_eq!(run_flow(OrderBook::with_storage(LadderStorage::with_range(900, 1_100))), reference);
+    assert_eq!(run_flow(OrderBook::with_storage(SlabStorage::default())), reference);
+    assert_eq!(run_flow(OrderBook::with_storage(SoaStorage::default())), reference);
+    assert_eq!(run_flow(OrderBook::with_storage(SkipListStorage::default())), reference);
+}
+
+#[test]
+fn ladder_grows_below_base_and_tracks_best() {
+    let mut ob = OrderBook::with_storage(LadderStorage::default());
+    let _ = ob.submit_limit(Side::Buy, 100, 1).unwrap();
+    let _ = ob.submit_limit(Side::Buy, 95, 2).unwrap();
+    let _ = ob.submit_limit(Side::Sell, 110, 3).unwrap();
+    let _ = ob.submit_limit(Side::Sell, 90, 1).unwrap(); // crosses the 100 bid
+    assert_eq!(ob.best_bid(), Some((95, 2)));
+    assert_eq!(ob.best_ask(), Some((110, 3)));
+}
+
+#[test]
+fn ladder_span_is_capped() {
+    let mut ob = OrderBook::with_storage(LadderStorage::default());
+    let (bid, _) = ob.submit_limit_into(Side::Buy, 1_000, 1, &mut Vec::new()).unwrap();
+    // A stray price far from the book is rejected instead of allocating every slot in between
+    assert!(matches!(ob.submit_limit_into(Side::Sell, 1_000 + LADDER_MAX_SPAN, 1, &mut Vec::new()), Err(EngineError::Rejected(_))));
+    assert!(ob.reprice(bid, u64::MAX, &mut Vec::new()).is_err());
+    // A refused amend leaves the order's quantity alone
+    assert!(ob.amend(bid, 1_000 + LADDER_MAX_SPAN, 3, &mut Vec::new()).is_err());
+    assert_eq!(ob.best_bid(), Some((1_000, 1)));
+    ob.submit_limit_into(Side::Sell, 1_000 + LADDER_MAX_SPAN - 1, 1, &mut Vec::new()).unwrap();
+    // Market orders and ordinary backends are unaffected
+    ob.submit_market_into(Side::Buy, 1, &mut Vec::new()).unwrap();
+    let mut tree = OrderBook::new();
+    tree.submit_limit_into(Side::Buy, 1_000, 1, &mut Vec::new()).unwrap();
+    tree.submit_limit_into(Side::Sell, u64::MAX / 2, 1, &mut Vec::new()).unwrap();
+    assert!(ob.verify().is_ok());
+    // A snapshot wider than a ladder can hold is refused on restore, in memory and from bytes
+    assert!(matches!(OrderBook::from_snapshot_with_storage(LadderStorage::default(), &tree.snapshot()), Err(EngineError::Rejected(_))));
+    let mut bytes = Vec::new();
+    tree.save_snapshot(&mut bytes).unwrap();
+    assert_eq!(OrderBook::load_snapshot_with_storage(LadderStorage::default(), bytes.as_slice()).err().map(|e| e.kind()), Some(std::io::ErrorKind::InvalidData));
+}
+
+#[test]
+fn slab_cancel_middle_of_queue_keeps_fifo() {
+    let mut ob = OrderBook::with_storage(SlabStorage::default());
+    let (a, _, _) = ob.submit_limit(Side::Sell, 100, 1).unwrap();
+    let (b, _, _) = ob.submit_limit(Side::Sell, 100, 2).unwrap();
+    let (c, _, _) = ob.submit_limit(Side::Sell, 100, 3).unwrap();
+    assert_eq!(ob.cancel(b).unwrap().qty, 2);
+    let (_, trades, _) = ob.submit_market(Side::Buy, 4).unwrap();
+    assert_eq!(trades.iter().map(|t| (t.maker_id, t.qty)).collect::<Vec<_>>(), vec![(a, 1), (c, 3)]);
+    assert!(ob.cancel(c).is_err());
+}
+
+fn queue_positions<S: BookStorage>(mut ob: OrderBook<S>) {
+    let (a, _, _) = ob.submit_limit(Side::Buy, 100, 4).unwrap();
+    let (b, _, _) = ob.submit_limit(Side::Buy, 100, 2).unwrap();
+    let (c, _, _) = ob.submit_limit(Side::Buy, 100, 3).unwrap();
+    let (d, _, _) = ob.submit_limit(Side::Buy, 99, 1).unwrap();
+    assert_eq!(ob.queue_position(a), Some((0, 0)));
+    assert_eq!(ob.queue_position(c), Some((2, 6)));
+    assert_eq!(ob.queue_position(d), Some((0, 0)));
+    // Cancels and partial fills ahead move the order up
+    ob.cancel(b).unwrap();
+    ob.submit_market(Side::Sell, 3).unwrap();
+    assert_eq!(ob.queue_position(c), Some((1, 1)));
+    ob.submit_market(Side::Sell, 2).unwrap();
+    assert_eq!(ob.queue_position(a), None);
+    assert_eq!(ob.queue_position(c), Some((0, 0)));
+}
+
+#[test]
+fn queue_position_matches_across_backends() {
+    queue_positions(OrderBook::with_storage(BTreeStorage::default()));
+    queue_positions(OrderBook::with_storage(LadderStorage::default()));
+    queue_positions(OrderBook::with_storage(SlabStorage::default()));
+    queue_positions(OrderBook::with_storage(SoaStorage::default()));
+    queue_positions(OrderBook::with_storage(SkipListStorage::default()));
+}
+
+#[test]
+fn skip_list_handles_sparse_prices_across_the_range() {
+    let (mut skip, mut btree) = (OrderBook::with_storage(SkipListStorage::default()), OrderBook::with_storage(BTreeStorage::default()));
+    let mut x: u64 = 0x2545_F491_4F6C_DD1D;
+    let mut live = Vec::new();
+    for _ in 0..4_000 {
+        x ^= x << 13; x ^= x >> 7; x ^= x << 17;
+        let side = if x & 1 == 0 { Side::Buy } else { Side::Sell };
+        // Prices anywhere in 1..2^40, bids below asks so levels build up and empty out by cancels
+        let price = 1 + (x >> 24) % (1 << 39) + if side == Side::Sell { 1 << 39 } else { 0 };
+        if (x >> 4).is_multiple_of(4) && !live.is_empty() {
+            let id = live.swap_remove(((x >> 8) as usize) % live.len());
+            assert_eq!(skip.cancel(id).map(|o| o.qty).ok(), btree.cancel(id).map(|o| o.qty).ok());
+        } else {
+            let (id, _, _) = skip.submit_limit(side, price, 1 + x % 7).unwrap();
+            btree.submit_limit(side, price, 1 + x % 7).unwrap();
+            live.push(id);
+        }
+    }
+    assert_eq!(skip.top_n(usize::MAX), btree.top_n(usize::MAX));
+    // Sweep through the asks level by level
+    let (_, a, _) = skip.submit_market(Side::Buy, 5_000).unwrap();
+    let (_, b, _) = btree.submit_market(Side::Buy, 5_000).unwrap();
+    assert_eq!(a, b);
+    assert_eq!(skip.top_n(usize::MAX), btree.top_n(usize::MAX));
+}
@@ -0,0 +1,56 @@
+use match_engine::{AccountId, CancelReason, OrderBook, OrderId, OrderRequest, Side, StpAction, StpGroups, TradeConditions};
+
+const DESK_A: AccountId = AccountId(1);
+const DESK_B: AccountId = AccountId(2);
+const OTHER: AccountId = AccountId(3);
+
+fn sell(ob: &mut OrderBook, account: AccountId, price: u64, qty: u64) -> OrderId {
+    ob.submit(OrderRequest::limit(Side::Sell, price, qty).with_account(account)).unwrap().0
+}
+
+#[test]
+fn groups_map_desks_of_one_firm_together() {
+    let groups = StpGroups::new();
+    let mut ob = OrderBook::new();
+    ob.set_self_trade_prevention(Some(StpAction::CancelResting));
+    ob.set_stp_groups(groups.clone());
+    let a = sell(&mut ob, DESK_A, 100, 2);
+    // Different accounts, no mapping yet: they trade
+    let (_, trades, _) = ob.submit(OrderRequest::limit(Side::Buy, 100, 1).with_account(DESK_B)).unwrap();
+    assert_eq!(trades.len(), 1);
+
+    // Mapping updated through the shared handle while the book keeps running
+    groups.assign(DESK_A, 7);
+    groups.assign(DESK_B, 7);
+    let o = sell(&mut ob, OTHER, 100, 1);
+    let (_, trades, remaining) = ob.submit(OrderRequest::limit(Side::Buy, 100, 2).with_account(DESK_B)).unwrap();
+    // DESK_A's order at the front is cancelled, then OTHER's trades
+    assert_eq!(trades.iter().map(|t| (t.maker_id, t.qty)).collect::<Vec<_>>(), vec![(o, 1)]);
+    assert_eq!(remaining, 1);
+    let cancels = ob.drain_implicit_cancels();
+    assert_eq!(cancels.iter().map(|c| (c.id, c.qty, c.reason)).collect::<Vec<_>>(), vec![(a, 1, CancelReason::SelfTrade)]);
+
+    groups.unassign(DESK_B);
+    assert!(!groups.same_group(DESK_A, DESK_B));
+    assert!(!groups.same_group(AccountId(0), AccountId(0)));
+}
+
+#[test]
+fn incoming_cancel_and_decrement() {
+    let mut ob = OrderBook::new();
+    ob.set_self_trade_prevention(Some(StpAction::CancelIncoming));
+    let a = sell(&mut ob, DESK_A, 100, 3);
+    let (id, trades, remaining) = ob.submit(OrderRequest::limit(Side::Buy, 101, 2).with_account(DESK_A)).unwrap();
+    assert!(trades.is_empty());
+    assert_eq!(remaining, 2);
+    assert_eq!(ob.queue_position(id), None); // not rested
+    assert_eq!(ob.best_ask(), Some((100, 3)));
+
+    ob.set_self_trade_prevention(Some(StpAction::Decrement));
+    let (_, trades, remaining) = ob.submit(OrderRequest::limit(Side::Buy, 100, 2).with_account(DESK_A)).unwrap();
+    assert_eq!(remaining, 0);
+    assert_eq!(trades.len(), 1);
+    assert_eq!((trades[0].maker_id, trades[0].qty, trades[0].conditions), (a, 2, TradeConditions::STP_DECREMENT));
+    assert_eq!(ob.best_ask(), Some((100, 1)));
+    assert_eq!(ob.last_trade_price(), None);
+}
@@ -0,0 +1,54 @@
+use match_engine::surveillance::{AlertKind, LifecycleEvent, Surveillance, SurveillanceConfig};
+use match_engine::{AccountId, OrderId, Side};
+
+const SPOOFER: AccountId = AccountId(7);
+
+fn accepted(id: u64, side: Side, price: u64, ts: u64) -> LifecycleEvent {
+    LifecycleEvent::Accepted { account: SPOOFER, id: OrderId(id), side, price: Some(price), qty: 10, ts }
+}
+
+fn cancelled(id: u64, ts: u64) -> LifecycleEvent { LifecycleEvent::Cancelled { account: SPOOFER, id: OrderId(id), ts } }
+
+#[test]
+fn layering_and_cancel_ratio_alert_once() {
+    let cfg = SurveillanceConfig { max_cancel_to_trade: 2.0, min_cancels: 3, max_messages_per_window: 0, ..Default::default() };
+    let mut s = Surveillance::new(cfg);
+    // Three bid layers, then a sell fills on the other side
+    for (i, p) in [99, 98, 97].into_iter().enumerate() { s.on_event(&accepted(i as u64 + 1, Side::Buy, p, i as u64)); }
+    s.on_event(&accepted(4, Side::Sell, 100, 3));
+    s.on_event(&LifecycleEvent::Filled { account: SPOOFER, id: OrderId(4), side: Side::Sell, qty: 10, remaining: 0, ts: 4 });
+    let alerts = s.drain_alerts();
+    assert_eq!(alerts.len(), 1);
+    assert_eq!((alerts[0].kind, alerts[0].value), (AlertKind::Layering, 3.0));
+
+    // Layers pulled right after the fill: 3 cancels / 1 fill crosses 2.0 once
+    for (i, id) in [1, 2, 3].into_iter().enumerate() { s.on_event(&cancelled(id, 5 + i as u64)); }
+    let alerts = s.drain_alerts();
+    assert_eq!(alerts.iter().map(|a| a.kind).collect::<Vec<_>>(), vec![AlertKind::CancelToTrade]);
+    let m = s.metrics(SPOOFER).unwrap();
+    assert_eq!((m.orders, m.cancels, m.fills, m.max_layers), (4, 3, 1, 3));
+    assert_eq!(m.cancel_to_trade, 3.0);
+
+    s.on_event(&accepted(5, Side::Buy, 99, 10));
+    s.on_event(&cancelled(5, 11));
+    assert!(s.drain_alerts().is_empty());
+}
+
+#[test]
+fn quote_stuffing_uses_a_sliding_window() {
+    let cfg = SurveillanceConfig { window_micros: 100, max_messages_per_window: 4, max_cancel_to_trade: 0.0, layering_min_levels: 0, ..Default::default() };
+    let mut s = Surveillance::new(cfg);
+    for i in 0..3 {
+        s.on_event(&accepted(i, Side::Buy, 100, i * 10));
+        s.on_event(&cancelled(i, i * 10 + 1));
+    }
+    let alerts = s.drain_alerts();
+    assert_eq!(alerts.len(), 1);
+    assert_eq!((alerts[0].kind, alerts[0].ts), (AlertKind::QuoteStuffing, 20));
+
+    // Quiet period drains the window and re-arms the alert
+    s.on_event(&accepted(10, Side::Buy, 100, 500));
+    assert_eq!(s.metrics(SPOOFER).unwrap().messages_in_window, 1);
+    for i in 0..4 { s.on_event(&cancelled(10 + i, 501 + i)); }
+    assert_eq!(s.drain_alerts().len(), 1);
+}
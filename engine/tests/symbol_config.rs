@@ -0,0 +1,87 @@
+use match_engine::{AccountId, AmendCrossPolicy, EngineError, OrderBook, OrderRequest, Side, SymbolConfig};
+
+fn config() -> SymbolConfig { SymbolConfig { tick: 5, lot: 10, min_qty: 20, max_qty: 1_000 } }
+
+#[test]
+fn new_orders_must_be_on_the_grid_and_within_size_bounds() {
+    let mut ob = OrderBook::new();
+    ob.set_symbol_config(Some(config())).unwrap();
+    assert!(matches!(ob.submit_limit(Side::Buy, 101, 20), Err(EngineError::InvalidTick { price: 101, tick: 5 })));
+    assert!(matches!(ob.submit_limit(Side::Buy, 100, 25), Err(EngineError::InvalidLot { qty: 25, lot: 10 })));
+    assert!(matches!(ob.submit_limit(Side::Buy, 100, 10), Err(EngineError::InvalidQty { qty: 10, min: 20, max: 1_000 })));
+    assert!(matches!(ob.submit_market(Side::Buy, 1_010), Err(EngineError::InvalidQty { .. })));
+    assert!(matches!(ob.submit(OrderRequest::limit(Side::Buy, 100, 40).iceberg(15)), Err(EngineError::InvalidLot { qty: 15, .. })));
+    assert!(matches!(ob.submit(OrderRequest::market(Side::Sell, 20).with_stop(93)), Err(EngineError::InvalidTick { price: 93, .. })));
+    assert_eq!(ob.best_bid(), None);
+
+    // Rejections take no id; market orders have no price to check
+    let (id, _, _) = ob.submit_limit(Side::Buy, 100, 30).unwrap();
+    assert_eq!(id.0, 1);
+    let (_, trades, _) = ob.submit_market(Side::Sell, 20).unwrap();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(ob.best_bid(), Some((100, 10)));
+
+    ob.set_symbol_config(None).unwrap();
+    assert!(ob.submit_limit(Side::Buy, 101, 1).is_ok());
+}
+
+#[test]
+fn amends_reprices_and_reduces_of_resting_orders_are_checked() {
+    let mut ob = OrderBook::new();
+    ob.set_symbol_config(Some(config())).unwrap();
+    let (id, _, _) = ob.submit_limit(Side::Sell, 110, 100).unwrap();
+    assert!(matches!(ob.amend(id, 112, 100, &mut Vec::new()), Err(EngineError::InvalidTick { price: 112, .. })));
+    assert!(matches!(ob.amend(id, 110, 2_000, &mut Vec::new()), Err(EngineError::InvalidQty { .. })));
+    assert!(matches!(ob.reprice(id, 108, &mut Vec::new()), Err(EngineError::InvalidTick { .. })));
+    assert!(matches!(ob.reduce(id, 5), Err(EngineError::InvalidLot { qty: 5, lot: 10 })));
+    assert_eq!(ob.best_ask(), Some((110, 100)));
+
+    ob.amend(id, 115, 60, &mut Vec::new()).unwrap();
+    assert_eq!(ob.reduce(id, 10).unwrap(), 50);
+    // Taking out the whole order is a cancel, whatever the remainder
+    assert_eq!(ob.reduce(id, 55).unwrap(), 0);
+    assert_eq!(ob.best_ask(), None);
+}
+
+#[test]
+fn a_rejected_amend_leaves_the_order_as_it_was() {
+    let mut ob = OrderBook::new();
+    ob.set_symbol_config(Some(config())).unwrap();
+    ob.set_amend_cross_policy(AmendCrossPolicy::Reprice { tick: 3 });
+    ob.submit_limit(Side::Buy, 100, 20).unwrap();
+    let (ask, _, _) = ob.submit_limit(Side::Sell, 120, 100).unwrap();
+    // 95 is on the grid, but the cross policy would rest the order at 103
+    assert!(matches!(ob.amend(ask, 95, 40, &mut Vec::new()), Err(EngineError::InvalidTick { price: 103, tick: 5 })));
+    assert!(matches!(ob.reprice(ask, 95, &mut Vec::new()), Err(EngineError::InvalidTick { price: 103, .. })));
+    assert_eq!(ob.best_ask(), Some((120, 100)));
+    assert!(ob.drain_repriced().is_empty());
+}
+
+#[test]
+fn reduce_only_trims_keep_whole_lots() {
+    let (a, b) = (AccountId(1), AccountId(2));
+    let mut ob = OrderBook::new();
+    ob.set_symbol_config(Some(config())).unwrap();
+    ob.enable_positions();
+    ob.set_position(a, 45);
+    ob.submit(OrderRequest::limit(Side::Sell, 110, 100).with_account(a).reduce_only()).unwrap();
+    assert_eq!(ob.best_ask(), Some((110, 40)));
+    // Trimmed below min_qty, or below one lot
+    ob.set_position(b, 15);
+    let req = OrderRequest::limit(Side::Sell, 115, 100).with_account(b).reduce_only();
+    assert!(matches!(ob.submit(req), Err(EngineError::InvalidQty { qty: 10, .. })));
+    ob.set_position(b, 5);
+    assert!(matches!(ob.submit(req), Err(EngineError::Rejected(_))));
+    assert_eq!(ob.top_n(5).1, vec![(110, 40, 1)]);
+}
+
+#[test]
+fn configs_that_cannot_take_effect_are_refused() {
+    let mut ob = OrderBook::new();
+    ob.set_symbol_config(Some(config())).unwrap();
+    for bad in [SymbolConfig { tick: 0, ..config() }, SymbolConfig { lot: 0, ..config() }, SymbolConfig { min_qty: 1_001, ..config() }] {
+        assert!(matches!(ob.set_symbol_config(Some(bad)), Err(EngineError::InvalidConfig(_))));
+        assert_eq!(ob.symbol_config(), Some(config()));
+    }
+    assert!(ob.set_symbol_config(Some(SymbolConfig { min_qty: 1_000, ..config() })).is_ok());
+}
@@ -0,0 +1,34 @@
+use match_engine::{AccountId, OrderBook, OrderRequest, Side, StpAction};
+
+#[test]
+fn tape_keeps_the_last_trades_with_ids() {
+    let mut ob = OrderBook::new();
+    ob.set_trade_tape(Some(3));
+    for px in [100, 101, 102, 103] { ob.submit_limit(Side::Sell, px, 1).unwrap(); }
+    ob.submit_market(Side::Buy, 4).unwrap();
+    let tape = ob.trade_tape().unwrap();
+    assert_eq!((tape.last_id(), tape.len()), (4, 3));
+    // Trade 1 aged out: the tape starts at the oldest retained
+    assert_eq!(ob.trades_since(0).map(|e| (e.trade_id, e.trade.price)).collect::<Vec<_>>(), vec![(2, 101), (3, 102), (4, 103)]);
+    assert_eq!(ob.trades_since(3).map(|e| e.trade_id).collect::<Vec<_>>(), vec![4]);
+    assert_eq!(ob.trades_since(4).count(), 0);
+    assert_eq!(ob.last_n_trades(2).map(|e| e.trade_id).collect::<Vec<_>>(), vec![3, 4]);
+    assert_eq!(ob.last_n_trades(10).count(), 3);
+}
+
+#[test]
+fn tape_is_off_by_default_and_skips_stp_decrements() {
+    let mut ob = OrderBook::new();
+    ob.submit_limit(Side::Sell, 100, 1).unwrap();
+    ob.submit_market(Side::Buy, 1).unwrap();
+    assert!(ob.trade_tape().is_none());
+    assert_eq!(ob.last_n_trades(5).count(), 0);
+
+    ob.set_trade_tape(Some(10));
+    ob.set_self_trade_prevention(Some(StpAction::Decrement));
+    ob.submit(OrderRequest::limit(Side::Sell, 100, 2).with_account(AccountId(7))).unwrap();
+    let (_, trades, _) = ob.submit(OrderRequest::limit(Side::Buy, 100, 1).with_account(AccountId(7))).unwrap();
+    assert_eq!(trades.len(), 1);
+    ob.submit_limit(Side::Buy, 100, 1).unwrap();
+    assert_eq!(ob.last_n_trades(5).map(|e| (e.trade_id, e.trade.qty)).collect::<Vec<_>>(), vec![(1, 1)]);
+}
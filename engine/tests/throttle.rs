@@ -0,0 +1,39 @@
+use match_engine::{AccountId, EngineError, EntryThrottle, ManualClock, OrderBook, OrderRequest, Side, ThrottleHit};
+
+fn order(account: u64) -> OrderRequest { OrderRequest::limit(Side::Buy, 99, 1).with_account(AccountId(account)) }
+
+#[test]
+fn entry_throttle_limits_each_account_in_a_sliding_window() {
+    let clock = ManualClock::new(1_000);
+    let mut ob = OrderBook::new();
+    ob.set_clock(clock.clone());
+    ob.set_entry_throttle(Some(EntryThrottle { max_orders: 2, window_micros: 100 }));
+    let mut trades = Vec::new();
+    ob.submit_into(order(1), &mut trades).unwrap();
+    clock.advance(30);
+    ob.submit_into(order(1), &mut trades).unwrap();
+    // Another account has its own window
+    ob.submit_into(order(2), &mut trades).unwrap();
+    clock.advance(10);
+    let hit = ThrottleHit { owner: AccountId(1), rate: 2, limit: 2, window_micros: 100, retry_after_micros: 60 };
+    assert!(matches!(ob.submit_into(order(1), &mut trades), Err(EngineError::Throttled(h)) if h == hit));
+    assert_eq!(ob.drain_throttle_hits(), vec![hit]);
+    // Cancels are never throttled; once the first entry leaves the window there is room again
+    let (id, _) = ob.submit_into(order(2), &mut trades).unwrap();
+    ob.cancel(id).unwrap();
+    clock.advance(60);
+    assert!(ob.submit_into(order(1), &mut trades).is_ok());
+    assert!(ob.drain_throttle_hits().is_empty());
+}
+
+#[test]
+fn throttle_error_reads_as_slow_down() {
+    let mut ob = OrderBook::new();
+    ob.set_clock(ManualClock::new(5));
+    ob.set_entry_throttle(Some(EntryThrottle { max_orders: 1, window_micros: 1_000 }));
+    ob.submit_limit(Side::Sell, 101, 1).unwrap();
+    let err = ob.submit_limit(Side::Sell, 102, 1).unwrap_err();
+    assert_eq!(err.to_string(), "order entry throttled: 1 orders in 1000us, limit 1; retry after 1000us");
+    ob.set_entry_throttle(None);
+    assert!(ob.submit_limit(Side::Sell, 102, 1).is_ok());
+}
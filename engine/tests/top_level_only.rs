@@ -0,0 +1,33 @@
+use match_engine::{OrderBook, OrderRequest, Side};
+
+#[test]
+fn market_order_stops_at_the_best_level() {
+    let mut ob = OrderBook::new();
+    ob.submit_limit(Side::Sell, 100, 2).unwrap();
+    ob.submit_limit(Side::Sell, 100, 1).unwrap();
+    ob.submit_limit(Side::Sell, 101, 5).unwrap();
+    let (_, trades, remaining) = ob.submit(OrderRequest::market(Side::Buy, 10).top_level_only()).unwrap();
+    assert_eq!(trades.iter().map(|t| (t.price, t.qty)).collect::<Vec<_>>(), vec![(100, 2), (100, 1)]);
+    // The rest is cancelled, the next level is untouched
+    assert_eq!(remaining, 7);
+    assert_eq!(ob.best_ask(), Some((101, 5)));
+    assert_eq!(ob.best_bid(), None);
+}
+
+#[test]
+fn limit_order_trades_one_level_and_never_rests() {
+    let mut ob = OrderBook::new();
+    ob.submit_limit(Side::Buy, 99, 1).unwrap();
+    ob.submit_limit(Side::Buy, 98, 4).unwrap();
+    let (_, trades, remaining) = ob.submit(OrderRequest::limit(Side::Sell, 98, 3).top_level_only()).unwrap();
+    assert_eq!(trades.len(), 1);
+    assert_eq!((trades[0].price, trades[0].qty), (99, 1));
+    assert_eq!(remaining, 2);
+    assert_eq!(ob.best_bid(), Some((98, 4)));
+    assert_eq!(ob.best_ask(), None);
+    // Nothing to trade against: cancelled outright
+    let (_, trades, remaining) = ob.submit(OrderRequest::limit(Side::Buy, 50, 3).top_level_only()).unwrap();
+    assert!(trades.is_empty());
+    assert_eq!(remaining, 3);
+    assert_eq!(ob.best_bid(), Some((98, 4)));
+}
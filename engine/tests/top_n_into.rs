@@ -0,0 +1,22 @@
+use match_engine::{OrderBook, Side};
+
+#[test]
+fn matches_top_n_and_reuses_buffers() {
+    let mut ob = OrderBook::new();
+    for i in 0..8 { ob.submit_limit(Side::Buy, 100 - i, 1 + i).unwrap(); ob.submit_limit(Side::Sell, 101 + i, 2).unwrap(); }
+    ob.submit_limit(Side::Buy, 100, 4).unwrap();
+    let (mut bids, mut asks) = (Vec::with_capacity(16), vec![(1, 1, 1); 3]);
+    ob.top_n_into(5, &mut bids, &mut asks);
+    assert_eq!((bids.clone(), asks.clone()), ob.top_n(5));
+    assert_eq!(bids[0], (100, 5, 2));
+    let capacity = bids.capacity();
+    ob.submit_market(Side::Sell, 5).unwrap();
+    ob.top_n_into(5, &mut bids, &mut asks);
+    assert_eq!((bids.clone(), asks.clone()), ob.top_n(5));
+    assert_eq!(bids.capacity(), capacity);
+    // Fewer levels than asked for, and none at all
+    ob.top_n_into(100, &mut bids, &mut asks);
+    assert_eq!((bids.len(), asks.len()), (7, 8));
+    ob.top_n_into(0, &mut bids, &mut asks);
+    assert!(bids.is_empty() && asks.is_empty());
+}
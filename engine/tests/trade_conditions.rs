@@ -0,0 +1,35 @@
+use match_engine::{Liquidity, OrderBook, OrderId, OrderRequest, SessionState, Side, TimeInForce, TradeConditions};
+
+#[test]
+fn continuous_trades_carry_liquidity_indicators() {
+    let mut ob = OrderBook::new();
+    let (maker, _, _) = ob.submit_limit(Side::Sell, 100, 5).unwrap();
+    let (taker, trades, _) = ob.submit_limit(Side::Buy, 100, 2).unwrap();
+    let t = &trades[0];
+    assert!(t.conditions.is_empty());
+    assert_eq!(t.liquidity(maker), Some(Liquidity::Added));
+    assert_eq!(t.liquidity(taker), Some(Liquidity::Removed));
+    assert_eq!(t.liquidity(OrderId(99)), None);
+    let busted = t.bust();
+    assert!(busted.conditions.contains(TradeConditions::BUST));
+    assert_eq!((busted.price, busted.qty, busted.maker_id), (t.price, t.qty, t.maker_id));
+}
+
+#[test]
+fn uncross_trades_are_flagged_by_auction() {
+    let mut ob = OrderBook::new();
+    ob.set_session(SessionState::PreOpen);
+    let (buy, _, _) = ob.submit(OrderRequest::limit(Side::Buy, 101, 3).with_tif(TimeInForce::AtOpen)).unwrap();
+    ob.submit(OrderRequest::limit(Side::Sell, 100, 3).with_tif(TimeInForce::AtOpen)).unwrap();
+    let open = ob.set_session(SessionState::Open);
+    assert_eq!(open.trades.len(), 1);
+    assert_eq!(open.trades[0].conditions, TradeConditions::AUCTION | TradeConditions::OPENING);
+    assert_eq!(open.trades[0].liquidity(buy), Some(Liquidity::Auction));
+
+    ob.set_session(SessionState::PreClose);
+    ob.submit(OrderRequest::market(Side::Buy, 1).with_tif(TimeInForce::AtClose)).unwrap();
+    ob.submit(OrderRequest::limit(Side::Sell, 100, 1).with_tif(TimeInForce::AtClose)).unwrap();
+    let close = ob.set_session(SessionState::Closed);
+    assert!(close.trades.iter().all(|t| t.conditions.contains(TradeConditions::AUCTION | TradeConditions::CLOSING)));
+    assert!(!close.trades.is_empty());
+}
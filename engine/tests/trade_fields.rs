@@ -0,0 +1,28 @@
+use match_engine::{AccountId, OrderBook, OrderRequest, Side, StpAction, TradeId};
+
+#[test]
+fn trades_carry_ids_time_and_maker_completion() {
+    let mut ob = OrderBook::new();
+    let (a, _, _) = ob.submit_limit(Side::Sell, 101, 2).unwrap();
+    ob.submit_limit(Side::Sell, 102, 5).unwrap();
+    let (taker, trades, _) = ob.submit_limit(Side::Buy, 102, 3).unwrap();
+    let fields: Vec<_> = trades.iter().map(|t| (t.trade_id, t.taker_side, t.ts, t.maker_fully_filled)).collect();
+    assert_eq!(fields, vec![(TradeId(1), Side::Buy, 3, true), (TradeId(2), Side::Buy, 3, false)]);
+    assert_eq!((trades[0].maker_id, trades[0].taker_id), (a, taker));
+
+    // Ids continue after a restore
+    let mut restored = OrderBook::from_snapshot(&ob.snapshot());
+    let (_, trades, _) = restored.submit_market(Side::Buy, 1).unwrap();
+    assert_eq!((trades[0].trade_id, restored.last_trade_id()), (TradeId(3), TradeId(3)));
+}
+
+#[test]
+fn stp_decrements_are_not_numbered() {
+    let mut ob = OrderBook::new();
+    ob.set_self_trade_prevention(Some(StpAction::Decrement));
+    ob.submit(OrderRequest::limit(Side::Sell, 100, 2).with_account(AccountId(1))).unwrap();
+    ob.submit_limit(Side::Sell, 100, 2).unwrap();
+    let (_, trades, _) = ob.submit(OrderRequest::limit(Side::Buy, 100, 3).with_account(AccountId(1))).unwrap();
+    assert_eq!(trades.iter().map(|t| (t.trade_id, t.qty)).collect::<Vec<_>>(), vec![(TradeId(0), 2), (TradeId(1), 1)]);
+    assert_eq!(ob.last_trade_id(), TradeId(1));
+}
@@ -0,0 +1,41 @@
+use match_engine::workload::{Workload, WorkloadConfig, WorkloadMix};
+use match_engine::{OrderBook, Side, TimeInForce};
+
+#[test]
+fn sequenced_workload_keeps_the_book_consistent_and_replayable() {
+    let mut w = Workload::new(WorkloadConfig { mix: WorkloadMix::TAKER_HEAVY, tif: TimeInForce::Day, ..WorkloadConfig::default() });
+    let (mut ob, mut trades) = (OrderBook::new(), Vec::new());
+    w.seed_book(&mut ob, 10, 3);
+    let mut shadow = OrderBook::from_snapshot(&ob.snapshot());
+    let mut cmds = Vec::new();
+    for seq in 0..20_000 {
+        let op = w.next_op(&ob);
+        cmds.push(w.apply_sequenced(&mut ob, op, seq, &mut trades));
+        if seq % 1_000 == 0 { ob.verify().unwrap(); }
+    }
+    let mut replayed = Vec::new();
+    // Cancels of orders that have since filled are rejected on both sides alike
+    for cmd in cmds { let _ = shadow.process_command(cmd, &mut replayed); }
+    shadow.verify().unwrap();
+    assert_eq!((shadow.state_hash(), replayed.len()), (ob.state_hash(), trades.len()));
+    assert!(!trades.is_empty());
+}
+
+#[test]
+fn verify_reports_a_crossed_or_misordered_book() {
+    let mut ob = OrderBook::new();
+    ob.submit_limit(Side::Buy, 99, 1).unwrap();
+    ob.submit_limit(Side::Sell, 101, 1).unwrap();
+    ob.submit_limit(Side::Sell, 101, 2).unwrap();
+    ob.verify().unwrap();
+
+    let mut crossed = ob.snapshot();
+    crossed.bids[0].price = 102;
+    crossed.bids[0].orders[0].price = 102;
+    let err = OrderBook::from_snapshot(&crossed).verify().unwrap_err();
+    assert!(err.is_internal() && err.to_string().contains("crossed"), "{err}");
+
+    let mut tied = ob.snapshot();
+    tied.asks[0].orders[1].ts = tied.asks[0].orders[0].ts;
+    assert!(OrderBook::from_snapshot(&tied).verify().unwrap_err().to_string().contains("time priority"));
+}
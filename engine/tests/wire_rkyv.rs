@@ -0,0 +1,50 @@
+#![cfg(feature = "rkyv")]
+use match_engine::wire::{access_commands, access_events, encode_commands, encode_commands_in, encode_events, CommandBatch, EventFrame};
+use match_engine::{AccountId, Command, OrderBook, OrderId, Side, TimeInForce};
+
+fn sample() -> CommandBatch {
+    CommandBatch { commands: vec![
+        Command::Limit { seq: 1, side: Side::Sell, price: 100, qty: 5, tif: TimeInForce::Gtc, account: AccountId(0) },
+        Command::Market { seq: 2, side: Side::Buy, qty: 2, account: AccountId(0) },
+        Command::Cancel { seq: 3, id: OrderId(1), account: None },
+    ] }
+}
+
+#[test]
+fn command_batch_round_trips_in_place() {
+    let batch = sample();
+    let bytes = encode_commands(&batch).unwrap();
+    let archived = access_commands(&bytes).unwrap();
+    assert_eq!(archived.len(), 3);
+    assert_eq!(archived.iter_commands().collect::<Vec<_>>(), batch.commands);
+    // Buffer reuse yields the same bytes
+    let again = encode_commands_in(&batch, bytes.clone()).unwrap();
+    assert_eq!(again.as_slice(), bytes.as_slice());
+}
+
+#[test]
+fn archived_batch_applies_like_owned_batch() {
+    let bytes = encode_commands(&sample()).unwrap();
+    let mut ob = OrderBook::new();
+    let (mut scratch, mut trades) = (Vec::new(), Vec::new());
+    let results = ob.process_archived_batch_into(access_commands(&bytes).unwrap(), &mut scratch, &mut trades).unwrap();
+    assert_eq!(results, vec![Ok((OrderId(1), 5)), Ok((OrderId(2), 0)), Ok((OrderId(1), 0))]);
+    assert_eq!(ob.best_ask(), None);
+    assert_eq!(scratch, sample().commands);
+
+    let frame = EventFrame { seq: 3, trades, cancels: ob.drain_implicit_cancels() };
+    let bytes = encode_events(&frame).unwrap();
+    let archived = access_events(&bytes).unwrap();
+    assert_eq!(archived.seq, 3);
+    assert_eq!(archived.trades.len(), 1);
+    assert_eq!(archived.trades[0].qty, 2);
+    assert!(archived.cancels.is_empty());
+}
+
+#[test]
+fn corrupt_frame_is_rejected() {
+    let bytes = encode_commands(&sample()).unwrap();
+    let mut corrupt = rkyv::util::AlignedVec::<16>::new();
+    corrupt.extend_from_slice(&bytes[..bytes.len() - 4]);
+    assert!(access_commands(&corrupt).is_err());
+}
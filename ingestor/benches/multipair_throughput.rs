@@ -26,7 +26,7 @@ fn spawn_symbol_producer(tx: cb::Sender<RawCommand>, idx: usize, total_orders: u
         let mut sent = 0u64;
         let mut i = idx as u64;
         while sent < total_orders {
-            let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
+            let side = if i.is_multiple_of(2) { Side::Buy } else { Side::Sell };
             let cmd = if i % 10 < 3 {
                 RawCommand::Limit { side, price: 10_000, qty: 1 + (i % 5) }
             } else {
@@ -62,7 +62,7 @@ fn bench_multipair(c: &mut Criterion) {
                     // Wait until all orders processed
                     let mut done = 0u64;
                     while done < n {
-                        if let Ok(batch_done) = ig.rx_done.recv() { done += batch_done as u64; }
+                        if let Ok(p) = ig.rx_progress.recv() { done += p.commands as u64; }
                     }
                     for j in joins { let _ = j.join(); }
                     // No trade drain needed (we disabled emission)
@@ -0,0 +1,104 @@
+// Persistence overhead at several book sizes, to track it as the formats evolve:
+//   snapshot  capture (`OrderBook::snapshot`) and restore (`OrderBook::from_snapshot`) of the in-memory
+//             form; with `--features mmap` also writing and restoring the snapshot file, with its size
+//             printed and used as the byte throughput
+//   journal   replay of a journal file (read, decode, apply) on top of a restored book, in commands/sec;
+//             the file's bytes per command are printed once per size
+// cargo bench -p ingestor --bench persistence [--features mmap]
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::temp_path;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use ingestor::journal::{read_journal, FileJournal, FsyncPolicy, JournalWriter};
+use match_engine::{AccountId, Command, OrderBook, OrderId, Side, TimeInForce};
+
+const MID: u64 = 100_000;
+
+// `orders` resting orders, alternating sides, spread over 100 levels a side
+fn seeded(orders: u64) -> OrderBook {
+    let mut ob = OrderBook::new();
+    for i in 0..orders {
+        let (side, price) = if i % 2 == 0 { (Side::Buy, MID - 1 - i / 2 % 100) } else { (Side::Sell, MID + 1 + i / 2 % 100) };
+        let _ = ob.submit_limit(side, price, 1 + i % 7);
+    }
+    ob
+}
+
+// Passive adds, small takers and cancels of recent ids, continuing after `orders` seeded ones
+fn replay_cmds(orders: u64, n: u64) -> Vec<Command> {
+    (0..n).map(|seq| {
+        let side = if seq % 2 == 0 { Side::Buy } else { Side::Sell };
+        match seq % 10 {
+            0..=5 => Command::Limit { seq, side, price: if side == Side::Buy { MID - 1 - seq % 100 } else { MID + 1 + seq % 100 }, qty: 1 + seq % 5, tif: TimeInForce::Gtc, account: AccountId(0) },
+            6 | 7 => Command::Market { seq, side, qty: 1 + seq % 3, account: AccountId(0) },
+            _ => Command::Cancel { seq, id: OrderId(1 + (orders + seq) / 2), account: None },
+        }
+    }).collect()
+}
+
+fn bench_snapshot(c: &mut Criterion) {
+    let mut group = c.benchmark_group("snapshot");
+    for orders in [1_000u64, 10_000, 100_000] {
+        let ob = seeded(orders);
+        let snap = ob.snapshot();
+        group.throughput(Throughput::Elements(orders));
+        group.bench_with_input(BenchmarkId::new("capture", orders), &ob, |b, ob| b.iter(|| ob.snapshot()));
+        group.bench_with_input(BenchmarkId::new("restore", orders), &snap, |b, snap| b.iter(|| OrderBook::from_snapshot(snap)));
+        #[cfg(feature = "mmap")]
+        {
+            use ingestor::mmap_snapshot::{write_snapshot_file, MappedSnapshots};
+            use ingestor::SymbolSnapshot;
+            let path = temp_path(&format!("bench-snap-{orders}"));
+            let snaps = [SymbolSnapshot { symbol: "BENCH".into(), next_seq: orders, book: snap }];
+            write_snapshot_file(&path, &snaps).unwrap();
+            let bytes = std::fs::metadata(&path).unwrap().len();
+            eprintln!("snapshot file for {orders} orders: {bytes} bytes ({:.1} bytes/order)", bytes as f64 / orders as f64);
+            group.throughput(Throughput::Bytes(bytes));
+            group.bench_with_input(BenchmarkId::new("write_file", orders), &snaps, |b, snaps| b.iter(|| write_snapshot_file(&path, snaps).unwrap()));
+            group.bench_with_input(BenchmarkId::new("open_restore", orders), &path, |b, path| {
+                b.iter(|| MappedSnapshots::open(path).unwrap().get("BENCH").unwrap().restore().unwrap())
+            });
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+    group.finish();
+}
+
+fn bench_journal_replay(c: &mut Criterion) {
+    let mut group = c.benchmark_group("journal_replay");
+    group.sample_size(20);
+    for orders in [1_000u64, 10_000, 100_000] {
+        let n = 50_000;
+        let snap = seeded(orders).snapshot();
+        let path = temp_path(&format!("bench-journal-{orders}"));
+        {
+            let mut j = FileJournal::open(&path, FsyncPolicy::Never).unwrap();
+            for batch in replay_cmds(orders, n).chunks(256) { j.append(batch).unwrap(); }
+            j.sync().unwrap();
+        }
+        let bytes = std::fs::metadata(&path).unwrap().len();
+        eprintln!("journal of {n} commands: {bytes} bytes ({:.1} bytes/command)", bytes as f64 / n as f64);
+        group.throughput(Throughput::Elements(n));
+        group.bench_with_input(BenchmarkId::new("read_apply", orders), &path, |b, path| {
+            b.iter_batched(
+                || OrderBook::from_snapshot(&snap),
+                |mut ob| {
+                    let cmds = read_journal(path).unwrap();
+                    let mut trades = Vec::new();
+                    for batch in cmds.chunks(256) {
+                        let _ = ob.process_commands_batch_presorted_unchecked(batch, &mut trades);
+                        trades.clear();
+                    }
+                    ob
+                },
+                BatchSize::LargeInput,
+            )
+        });
+        let _ = std::fs::remove_file(&path);
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_snapshot, bench_journal_replay);
+criterion_main!(benches);
@@ -0,0 +1,106 @@
+// Command provenance: with an `AuditSink` attached (`Attachments::audit`), the worker records every
+// command it sequences together with where it came from, when it was received, the seq it got and
+// what the book did with it, so "who sent this and what happened to it" is one lookup by order id.
+//   producer     the sequencer producer that sent it (`RawCommand::Sourced`), None when sent directly
+//   received_at  the producer's stamp for sourced commands, otherwise the worker's clock (micros
+//                since the Unix epoch) when it took the command's batch
+//   outcome      Applied with the order id it created or targeted, Rejected with the engine error,
+//                or Skipped when an earlier command of the same batch failed and the rest was dropped
+use crate::RawCommand;
+use match_engine::{Command, OrderBook, OrderId, Trade};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Applied { order_id: OrderId, remaining: u64 },
+    Rejected { reason: String },
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    pub symbol: String,
+    pub seq: u64,
+    pub producer: Option<u32>,
+    pub received_at: u64,
+    pub command: Command,
+    pub outcome: Outcome,
+}
+
+impl AuditRecord {
+    // Order the record is about: the one the command targets, or the one it created
+    pub fn order_id(&self) -> Option<OrderId> {
+        match (self.command, &self.outcome) {
+            (Command::Cancel { id, .. } | Command::Reprice { id, .. } | Command::Amend { id, .. } | Command::Reduce { id, .. }, _) => Some(id),
+            (Command::Session { .. } | Command::Reference { .. } | Command::EndOfDay { .. } | Command::Resume { .. }, _) => None,
+            (_, Outcome::Applied { order_id, .. }) => Some(*order_id),
+            _ => None,
+        }
+    }
+}
+
+pub trait AuditSink: Send + Sync {
+    fn record(&self, rec: &AuditRecord);
+    // Every record about `id` on `symbol`, oldest first
+    fn by_order(&self, symbol: &str, id: OrderId) -> Vec<AuditRecord>;
+}
+
+// Keeps every record in memory, indexed by (symbol, order id) (tests, embedding)
+#[derive(Default)]
+pub struct MemoryAuditLog {
+    inner: Mutex<AuditInner>,
+}
+
+#[derive(Default)]
+struct AuditInner {
+    records: Vec<AuditRecord>,
+    by_order: HashMap<(String, u64), Vec<usize>>,
+}
+
+impl MemoryAuditLog {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn len(&self) -> usize { self.inner.lock().unwrap().records.len() }
+
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    // Records of `symbol` in seq order
+    pub fn records(&self, symbol: &str) -> Vec<AuditRecord> {
+        self.inner.lock().unwrap().records.iter().filter(|r| r.symbol == symbol).cloned().collect()
+    }
+}
+
+impl AuditSink for MemoryAuditLog {
+    fn record(&self, rec: &AuditRecord) {
+        let mut inner = self.inner.lock().unwrap();
+        let at = inner.records.len();
+        if let Some(id) = rec.order_id() { inner.by_order.entry((rec.symbol.clone(), id.0)).or_default().push(at); }
+        inner.records.push(rec.clone());
+    }
+
+    fn by_order(&self, symbol: &str, id: OrderId) -> Vec<AuditRecord> {
+        let inner = self.inner.lock().unwrap();
+        inner.by_order.get(&(symbol.to_string(), id.0)).map_or_else(Vec::new, |at| at.iter().map(|&i| inner.records[i].clone()).collect())
+    }
+}
+
+// Apply a sequenced batch like `process_commands_batch_checked_into` (stopping at the first error
+// the book's `ErrorPolicy` does not skip) and record each command; `raw[i]` is the command `batch[i]` was sequenced from
+pub(crate) fn apply_audited(book: &mut OrderBook, symbol: &str, raw: &[RawCommand], batch: &[Command], taken_at: u64, trades_out: &mut Vec<Trade>, sink: &dyn AuditSink) {
+    let mut failed = false;
+    for (rc, &command) in raw.iter().zip(batch) {
+        let outcome = if failed { Outcome::Skipped } else {
+            match book.process_command(command, trades_out) {
+                Ok((order_id, remaining)) => Outcome::Applied { order_id, remaining },
+                Err(e) => {
+                    let reason = e.to_string();
+                    failed = book.resolve_error(command.seq(), e).is_err();
+                    Outcome::Rejected { reason }
+                }
+            }
+        };
+        let (producer, received_at) = match *rc { RawCommand::Sourced { producer, received_at, .. } => (Some(producer), received_at), _ => (None, taken_at) };
+        sink.record(&AuditRecord { symbol: symbol.to_string(), seq: command.seq(), producer, received_at, command, outcome });
+    }
+}
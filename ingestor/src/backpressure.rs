@@ -0,0 +1,35 @@
+// Queue watermarks: a worker reports `High` once its input backlog reaches the high mark and `Low`
+// once it has drained back to the low mark, so gateways can throttle before sends start to block.
+// The backlog is sampled at batch boundaries, so it reacts within one batch.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatermarkLevel {
+    High,
+    Low,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatermarkEvent {
+    pub symbol: String,
+    pub level: WatermarkLevel,
+    pub depth: usize,
+}
+
+// Hysteresis between the two marks: one High, then nothing until the matching Low
+pub(crate) struct WatermarkState {
+    high: usize,
+    low: usize,
+    above: bool,
+}
+
+impl WatermarkState {
+    // `high == 0` disables notifications; `low` is clamped below `high`
+    pub(crate) fn new(high: usize, low: usize) -> Self { Self { high, low: low.min(high.saturating_sub(1)), above: false } }
+
+    pub(crate) fn observe(&mut self, depth: usize) -> Option<WatermarkLevel> {
+        if self.high == 0 { return None; }
+        if !self.above && depth >= self.high { self.above = true; return Some(WatermarkLevel::High); }
+        if self.above && depth <= self.low { self.above = false; return Some(WatermarkLevel::Low); }
+        None
+    }
+}
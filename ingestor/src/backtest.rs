@@ -0,0 +1,244 @@
+// Backtesting a strategy against the real matching engine. A `Backtest` feeds recorded market
+// commands (a `Vec<Command>`, timestamped pairs or a journal, see `replay`) into a book and calls the
+// `Strategy` back after each one; the strategy trades through the `Context` it is handed, with its
+// orders matched by the same engine code as production.
+//   callbacks  after every market command: `on_trade` for each trade, `on_fill` for each side of a
+//              trade that is the strategy's, then `on_depth` if the top `depth_levels` changed;
+//              orders the strategy sends from a callback are matched at once and their trades
+//              dispatched the same way before the next market command
+//   timer      with `timer_micros` set, `advance_to(ts)` (and `run_timed`) fire `on_timer` every
+//              interval from the first time given up to `ts`; the book runs on a `ManualClock` at the
+//              replayed time
+//   orders     placed under `BacktestConfig::account`; `Context` keeps the strategy's position and
+//              cash from its fills and its open orders
+//   ids        the strategy's orders take ids the recorded run did not have, so cancels, amends and
+//              reprices in the log are renumbered onto the ids the replayed orders actually got
+//              (sequential ids; strategy stops that trigger later are not accounted for)
+use crate::journal::{JournalEntry, JournalReader};
+use crate::mirror::MirroredDepth;
+use crate::replay::ReplayError;
+use match_engine::{AccountId, Command, Depth, EngineError, ManualClock, OrderBook, OrderId, OrderRequest, Side, Trade, TradeId};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+// One side of a trade that belongs to the strategy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fill {
+    pub id: OrderId,
+    pub trade_id: TradeId,
+    pub side: Side,
+    pub price: u64,
+    pub qty: u64,
+    // The strategy's order was resting
+    pub maker: bool,
+}
+
+#[allow(unused_variables)]
+pub trait Strategy {
+    fn on_depth(&mut self, depth: &MirroredDepth, ctx: &mut Context) {}
+    fn on_trade(&mut self, trade: &Trade, ctx: &mut Context) {}
+    fn on_fill(&mut self, fill: &Fill, ctx: &mut Context) {}
+    fn on_timer(&mut self, now_micros: u64, ctx: &mut Context) {}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BacktestConfig {
+    // Owner of the strategy's orders; must not appear in the recorded flow
+    pub account: AccountId,
+    pub depth_levels: usize,
+    // 0 = no timer
+    pub timer_micros: u64,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self { Self { account: AccountId(u64::MAX), depth_levels: 5, timer_micros: 0 } }
+}
+
+// The strategy's view of the simulated venue during a callback
+pub struct Context {
+    book: OrderBook,
+    clock: ManualClock,
+    account: AccountId,
+    pending: VecDeque<Trade>,
+    orders: Vec<OrderId>,
+    // Ids the strategy's orders took, ascending (see `Backtest::recorded_id`)
+    taken: Vec<u64>,
+    position: i64,
+    cash: i128,
+}
+
+impl Context {
+    pub fn book(&self) -> &OrderBook { &self.book }
+
+    pub fn now_micros(&self) -> u64 { match_engine::Clock::now_micros(&self.clock) }
+
+    // Signed quantity bought minus sold
+    pub fn position(&self) -> i64 { self.position }
+
+    // Proceeds of sells minus the cost of buys, in price * qty units
+    pub fn cash(&self) -> i128 { self.cash }
+
+    // The strategy's orders still in the book, oldest first
+    pub fn open_orders(&mut self) -> &[OrderId] {
+        let book = &self.book;
+        self.orders.retain(|&id| book.get_order(id).is_some());
+        &self.orders
+    }
+
+    // Any order request, placed under the strategy's account
+    pub fn submit(&mut self, req: OrderRequest) -> Result<OrderId, EngineError> {
+        let before = self.book.last_order_id().0;
+        let mut trades = Vec::new();
+        let r = self.book.submit_into(req.with_account(self.account), &mut trades);
+        self.taken.extend(before + 1..=self.book.last_order_id().0);
+        self.pending.extend(trades);
+        let (id, _) = r?;
+        self.orders.push(id);
+        Ok(id)
+    }
+
+    pub fn buy(&mut self, price: u64, qty: u64) -> Result<OrderId, EngineError> { self.submit(OrderRequest::limit(Side::Buy, price, qty)) }
+
+    pub fn sell(&mut self, price: u64, qty: u64) -> Result<OrderId, EngineError> { self.submit(OrderRequest::limit(Side::Sell, price, qty)) }
+
+    pub fn buy_market(&mut self, qty: u64) -> Result<OrderId, EngineError> { self.submit(OrderRequest::market(Side::Buy, qty)) }
+
+    pub fn sell_market(&mut self, qty: u64) -> Result<OrderId, EngineError> { self.submit(OrderRequest::market(Side::Sell, qty)) }
+
+    pub fn cancel(&mut self, id: OrderId) -> Result<(), EngineError> { self.book.cancel_owned(id, self.account).map(|_| ()) }
+
+    // Cancels every open order; returns how many went
+    pub fn cancel_all(&mut self) -> usize {
+        let ids = self.open_orders().to_vec();
+        ids.into_iter().filter(|&id| self.cancel(id).is_ok()).count()
+    }
+
+    fn fills(&self, t: &Trade) -> impl Iterator<Item = Fill> {
+        let taker = (t.taker_account == self.account).then_some(Fill { id: t.taker_id, trade_id: t.trade_id, side: t.taker_side, price: t.price, qty: t.qty, maker: false });
+        let maker = (t.maker_account == self.account).then_some(Fill { id: t.maker_id, trade_id: t.trade_id, side: t.taker_side.opposite(), price: t.price, qty: t.qty, maker: true });
+        taker.into_iter().chain(maker)
+    }
+}
+
+pub struct Backtest<S: Strategy> {
+    strategy: S,
+    ctx: Context,
+    config: BacktestConfig,
+    depth: MirroredDepth,
+    scratch: (Depth, Depth),
+    next_timer: Option<u64>, // None until the first `advance_to`
+}
+
+impl<S: Strategy> Backtest<S> {
+    // `book` as the recording started, with the settings production used
+    pub fn new(mut book: OrderBook, strategy: S, config: BacktestConfig) -> Self {
+        let clock = ManualClock::new(0);
+        book.set_clock(clock.clone());
+        let ctx = Context { book, clock, account: config.account, pending: VecDeque::new(), orders: Vec::new(), taken: Vec::new(), position: 0, cash: 0 };
+        Self { strategy, ctx, config, depth: MirroredDepth::default(), scratch: (Depth::new(), Depth::new()), next_timer: None }
+    }
+
+    pub fn strategy(&self) -> &S { &self.strategy }
+
+    pub fn context(&self) -> &Context { &self.ctx }
+
+    pub fn into_parts(self) -> (S, OrderBook) { (self.strategy, self.ctx.book) }
+
+    // Move the clock to `ts_micros`, firing the timer at each boundary passed
+    pub fn advance_to(&mut self, ts_micros: u64) {
+        if self.config.timer_micros > 0 && self.next_timer.is_none() { self.next_timer = Some(ts_micros); }
+        while let Some(at) = self.next_timer.filter(|&at| at <= ts_micros) {
+            self.ctx.clock.set(at.max(self.ctx.now_micros()));
+            self.strategy.on_timer(at, &mut self.ctx);
+            self.dispatch();
+            self.next_timer = Some(at + self.config.timer_micros);
+        }
+        if ts_micros > self.ctx.now_micros() { self.ctx.clock.set(ts_micros); }
+    }
+
+    // One recorded market command; rejections are the recorded flow's and are ignored
+    pub fn apply(&mut self, cmd: Command) {
+        let cmd = self.renumber(cmd);
+        let mut trades = Vec::new();
+        if let Err(e) = self.ctx.book.process_command(cmd, &mut trades) { let _ = self.ctx.book.resolve_error(cmd.seq(), e); }
+        self.depth.next_seq = cmd.seq().wrapping_add(1);
+        self.ctx.pending.extend(trades);
+        self.dispatch();
+    }
+
+    pub fn run(&mut self, commands: impl IntoIterator<Item = Command>) {
+        for cmd in commands { self.apply(cmd); }
+    }
+
+    // (timestamp, command) pairs in time order
+    pub fn run_timed(&mut self, commands: impl IntoIterator<Item = (u64, Command)>) {
+        for (ts, cmd) in commands {
+            self.advance_to(ts);
+            self.apply(cmd);
+        }
+    }
+
+    // A journal from its first command on, which must be gapless; returns how many were applied
+    pub fn run_journal(&mut self, path: &Path) -> Result<usize, ReplayError> {
+        let mut next: Option<u64> = None;
+        let mut applied = 0;
+        for entry in JournalReader::new(BufReader::new(File::open(path)?)) {
+            let JournalEntry::Command(cmd) = entry? else { continue };
+            let expected = *next.get_or_insert(cmd.seq());
+            if cmd.seq() != expected { return Err(ReplayError::Gap { expected, found: cmd.seq() }); }
+            next = Some(expected + 1);
+            self.apply(cmd);
+            applied += 1;
+        }
+        Ok(applied)
+    }
+
+    // The id a recorded order got in this run: its recorded id moved past every id the strategy took before it
+    fn recorded_id(&self, id: OrderId) -> OrderId {
+        let (mut actual, mut passed) = (id.0, 0);
+        loop {
+            let n = self.ctx.taken.partition_point(|&s| s <= actual);
+            if n == passed { return OrderId(actual); }
+            actual += (n - passed) as u64;
+            passed = n;
+        }
+    }
+
+    fn renumber(&self, cmd: Command) -> Command {
+        match cmd {
+            Command::Cancel { seq, id, account } => Command::Cancel { seq, id: self.recorded_id(id), account },
+            Command::Reprice { seq, id, new_price } => Command::Reprice { seq, id: self.recorded_id(id), new_price },
+            Command::Amend { seq, id, new_price, new_qty } => Command::Amend { seq, id: self.recorded_id(id), new_price, new_qty },
+            Command::Reduce { seq, id, qty_delta } => Command::Reduce { seq, id: self.recorded_id(id), qty_delta },
+            other => other,
+        }
+    }
+
+    // Deliver trades (and the fills among them), then the depth, until the strategy's reactions settle
+    fn dispatch(&mut self) {
+        loop {
+            while let Some(t) = self.ctx.pending.pop_front() {
+                self.strategy.on_trade(&t, &mut self.ctx);
+                for fill in self.ctx.fills(&t).collect::<Vec<_>>() {
+                    let signed = if fill.side == Side::Buy { fill.qty as i64 } else { -(fill.qty as i64) };
+                    self.ctx.position += signed;
+                    self.ctx.cash -= signed as i128 * fill.price as i128;
+                    self.strategy.on_fill(&fill, &mut self.ctx);
+                }
+            }
+            self.ctx.book.drain_implicit_cancels();
+            self.ctx.book.drain_faults();
+            self.ctx.book.drain_eod_reports();
+            self.ctx.book.drain_fill_reports();
+            self.ctx.book.drain_stop_activations();
+            self.ctx.book.drain_repriced();
+            self.ctx.book.top_n_into(self.config.depth_levels, &mut self.scratch.0, &mut self.scratch.1);
+            if self.scratch.0 == self.depth.bids && self.scratch.1 == self.depth.asks { return; }
+            std::mem::swap(&mut self.scratch.0, &mut self.depth.bids);
+            std::mem::swap(&mut self.scratch.1, &mut self.depth.asks);
+            self.strategy.on_depth(&self.depth, &mut self.ctx);
+        }
+    }
+}
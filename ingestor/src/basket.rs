@@ -0,0 +1,46 @@
+// Baskets: orders on several symbols submitted as one unit. Every leg is validated before any is
+// routed, so a basket is either rejected as a whole or all legs reach their workers (atomic at
+// validation level only; legs then match independently). All legs share a fresh correlation id,
+// which the engine copies onto resting orders and every trade they take part in.
+use crate::{MultiIngestor, RawCommand};
+use match_engine::{OrderRequest, OrderType, Side};
+use std::sync::atomic::Ordering;
+
+#[derive(Debug, Clone, Copy)]
+pub struct BasketLeg<'a> {
+    pub symbol: &'a str,
+    pub req: OrderRequest,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BasketError {
+    Empty,
+    UnknownSymbol { leg: usize, symbol: String },
+    InvalidLeg { leg: usize, reason: &'static str },
+    // A worker went away while legs were being routed; earlier legs may have been accepted
+    Disconnected { leg: usize },
+}
+
+fn check_leg(req: &OrderRequest) -> Result<(), &'static str> {
+    if req.qty == 0 { return Err("zero quantity"); }
+    if req.order_type == OrderType::Limit && req.price == 0 { return Err("limit order without a price"); }
+    if req.short_sell && req.side != Side::Sell { return Err("short sell flag on a buy order"); }
+    Ok(())
+}
+
+impl MultiIngestor {
+    // Returns the correlation id stamped on every leg (any correlation set by the caller is replaced)
+    pub fn submit_basket(&self, legs: &[BasketLeg]) -> Result<u64, BasketError> {
+        if legs.is_empty() { return Err(BasketError::Empty); }
+        for (i, leg) in legs.iter().enumerate() {
+            if !self.routes.contains_key(leg.symbol) { return Err(BasketError::UnknownSymbol { leg: i, symbol: leg.symbol.to_string() }); }
+            check_leg(&leg.req).map_err(|reason| BasketError::InvalidLeg { leg: i, reason })?;
+        }
+        let correlation = self.next_correlation.fetch_add(1, Ordering::Relaxed);
+        for (i, leg) in legs.iter().enumerate() {
+            let cmd = RawCommand::Submit(leg.req.with_correlation(correlation));
+            self.routes[leg.symbol].send(cmd).map_err(|_| BasketError::Disconnected { leg: i })?;
+        }
+        Ok(correlation)
+    }
+}
@@ -1,21 +1,100 @@
-use ingestor::{Ingestor, RawCommand};
-use match_engine::{OrderBook, Side, OrderId};
+use ingestor::schedule::now_micros;
+use ingestor::{Attachments, AuditRecord, AuditSink, MultiIngestor, Options, Outcome, RawCommand, SymbolSnapshot};
+use match_engine::{OrderBook, Side, OrderId, ReferenceValue, TradeTape, OrderRequest, TimeInForce};
+use std::collections::HashMap;
 use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-fn main() {
-    let book = OrderBook::new();
-    let ig = Ingestor::start_with_book(book, 4096);
+const SYMBOL: &str = "CLI";
 
-    println!("Commands: limit buy|sell <px> <qty> | market buy|sell <qty> | cancel <id> | quit");
-    let stdin = io::stdin();
+// `--measure`: each command goes out stamped with its send time (`RawCommand::sourced`) and comes back
+// through the audit path once the worker has applied it, where the round trip is printed and kept for
+// the percentile summary on exit
+#[derive(Default)]
+struct Latencies {
+    sent: AtomicUsize,
+    acked: Mutex<Vec<u64>>,
+}
+
+impl AuditSink for Latencies {
+    fn record(&self, rec: &AuditRecord) {
+        let micros = now_micros().saturating_sub(rec.received_at);
+        self.acked.lock().unwrap().push(micros);
+        match &rec.outcome {
+            Outcome::Applied { order_id, remaining } => println!("ack seq={} id={} remaining={} {}us", rec.seq, order_id.0, remaining, micros),
+            Outcome::Rejected { reason } => println!("ack seq={} rejected: {} {}us", rec.seq, reason, micros),
+            Outcome::Skipped => println!("ack seq={} skipped {}us", rec.seq, micros),
+        }
+    }
+
+    fn by_order(&self, _: &str, _: OrderId) -> Vec<AuditRecord> { Vec::new() }
+}
 
-    // spawn printer of trades
+impl Latencies {
+    // Waits briefly for the acks of commands still in flight
+    fn summary(&self) -> String {
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while self.acked.lock().unwrap().len() < self.sent.load(Ordering::Acquire) && Instant::now() < deadline { std::thread::sleep(Duration::from_millis(1)); }
+        let mut lat = self.acked.lock().unwrap().clone();
+        if lat.is_empty() { return "no commands measured".into(); }
+        lat.sort_unstable();
+        let pct = |q: f64| lat[((lat.len() - 1) as f64 * q) as usize];
+        format!("{} commands: p50={}us p90={}us p99={}us max={}us", lat.len(), pct(0.5), pct(0.9), pct(0.99), lat[lat.len() - 1])
+    }
+}
+
+// Worker for `book` continuing at `next_seq`, and a printer of its trades keeping the recent ones for `tape`
+fn start(book: OrderBook, next_seq: u64, tape: &Arc<Mutex<TradeTape>>, measure: &Option<Arc<Latencies>>) -> MultiIngestor {
+    let audit = measure.clone().map(|m| m as Arc<dyn AuditSink>);
+    let attach = Attachments { start_seq: HashMap::from([(SYMBOL.to_string(), next_seq)]), audit, ..Attachments::default() };
+    let ig = MultiIngestor::start_with_attachments(vec![(SYMBOL.to_string(), book)], Options::default(), attach).expect("no symbol configs to refuse");
     let rx = ig.rx_trade.clone();
+    let recorder = tape.clone();
     std::thread::spawn(move || {
-        while let Ok(t) = rx.recv() {
-            println!("trade taker={} maker={} px={} qty={}", t.taker_id.0, t.maker_id.0, t.price, t.qty);
+        while let Ok((_, t)) = rx.recv() {
+            let trade_id = recorder.lock().unwrap().record(&t);
+            println!("trade #{} taker={} maker={} px={} qty={}", trade_id, t.taker_id.0, t.maker_id.0, t.price, t.qty);
         }
     });
+    ig
+}
+
+fn send(ig: &MultiIngestor, measure: Option<&Latencies>, cmd: RawCommand) {
+    let cmd = match measure {
+        Some(m) => { m.sent.fetch_add(1, Ordering::AcqRel); cmd.sourced(0, now_micros()) }
+        None => cmd,
+    };
+    let _ = ig.routes[SYMBOL].send(cmd);
+}
+
+// Saved books use the memory-mapped snapshot file format
+#[cfg(feature = "mmap")]
+fn save(path: &Path, snap: &SymbolSnapshot) -> io::Result<()> { ingestor::mmap_snapshot::write_snapshot_file(path, std::slice::from_ref(snap)) }
+
+#[cfg(feature = "mmap")]
+fn load(path: &Path) -> io::Result<SymbolSnapshot> {
+    let file = ingestor::mmap_snapshot::MappedSnapshots::open(path)?;
+    let book = file.get(SYMBOL).or_else(|| file.symbols().next()).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no book in file"))?;
+    book.to_symbol_snapshot()
+}
+
+#[cfg(not(feature = "mmap"))]
+fn save(_: &Path, _: &SymbolSnapshot) -> io::Result<()> { Err(io::Error::new(io::ErrorKind::Unsupported, "built without the `mmap` feature")) }
+
+#[cfg(not(feature = "mmap"))]
+fn load(_: &Path) -> io::Result<SymbolSnapshot> { Err(io::Error::new(io::ErrorKind::Unsupported, "built without the `mmap` feature")) }
+
+fn main() {
+    let tape = Arc::new(Mutex::new(TradeTape::new(1000)));
+    let latencies = std::env::args().any(|a| a == "--measure").then(|| Arc::new(Latencies::default()));
+    let measure = latencies.as_deref();
+    let mut ig = start(OrderBook::new(), 0, &tape, &latencies);
+
+    println!("Commands: limit buy|sell <px> <qty> [ioc|fok] | market buy|sell <qty> | stop buy|sell <stop> <qty> [limit px] | cancel <id> | reprice <id> <px> | amend <id> <px> <qty> | reduce <id> <qty> | ref index|settle|funding <v> | eod | tape [n] | seed <levels> <mid> <tick> <qty> | save <file> | load <file> | quit");
+    let stdin = io::stdin();
 
     loop {
         print!("> ");
@@ -26,22 +105,92 @@ fn main() {
         if parts.is_empty() { continue; }
         match parts[0] {
             "quit" | "exit" => break,
-            "limit" if parts.len() == 4 => {
+            "limit" if parts.len() == 4 || parts.len() == 5 && matches!(parts[4], "ioc" | "fok") => {
                 let side = match parts[1] { "buy" => Side::Buy, "sell" => Side::Sell, _ => { println!("side must be buy|sell"); continue; } };
                 let price: u64 = match parts[2].parse() { Ok(v) => v, Err(_) => { println!("invalid price"); continue; } };
                 let qty: u64 = match parts[3].parse() { Ok(v) => v, Err(_) => { println!("invalid qty"); continue; } };
-                let _ = ig.tx_cmd.send(RawCommand::Limit { side, price, qty });
+                let cmd = match parts.get(4) {
+                    Some(&"ioc") => RawCommand::Submit(OrderRequest::limit(side, price, qty).with_tif(TimeInForce::Ioc)),
+                    Some(_) => RawCommand::Submit(OrderRequest::limit(side, price, qty).with_tif(TimeInForce::Fok)),
+                    None => RawCommand::Limit { side, price, qty },
+                };
+                send(&ig, measure, cmd);
             }
             "market" if parts.len() == 3 => {
                 let side = match parts[1] { "buy" => Side::Buy, "sell" => Side::Sell, _ => { println!("side must be buy|sell"); continue; } };
                 let qty: u64 = match parts[2].parse() { Ok(v) => v, Err(_) => { println!("invalid qty"); continue; } };
-                let _ = ig.tx_cmd.send(RawCommand::Market { side, qty });
+                send(&ig, measure, RawCommand::Market { side, qty });
+            }
+            "stop" if parts.len() == 4 || parts.len() == 6 && parts[4] == "limit" => {
+                let side = match parts[1] { "buy" => Side::Buy, "sell" => Side::Sell, _ => { println!("side must be buy|sell"); continue; } };
+                let stop: u64 = match parts[2].parse() { Ok(v) => v, Err(_) => { println!("invalid stop price"); continue; } };
+                let qty: u64 = match parts[3].parse() { Ok(v) => v, Err(_) => { println!("invalid qty"); continue; } };
+                let req = match parts.get(5).map(|p| p.parse::<u64>()) {
+                    Some(Ok(price)) => OrderRequest::limit(side, price, qty),
+                    Some(Err(_)) => { println!("invalid limit price"); continue; }
+                    None => OrderRequest::market(side, qty),
+                };
+                send(&ig, measure, RawCommand::Submit(req.with_stop(stop)));
             }
             "cancel" if parts.len() == 2 => {
                 let id = match parts[1].parse::<u64>() { Ok(v) => OrderId(v), Err(_) => { println!("invalid id"); continue; } };
-                let _ = ig.tx_cmd.send(RawCommand::Cancel { id });
+                send(&ig, measure, RawCommand::Cancel { id, account: None });
+            }
+            "reprice" if parts.len() == 3 => {
+                let id = match parts[1].parse::<u64>() { Ok(v) => OrderId(v), Err(_) => { println!("invalid id"); continue; } };
+                let new_price: u64 = match parts[2].parse() { Ok(v) => v, Err(_) => { println!("invalid price"); continue; } };
+                send(&ig, measure, RawCommand::Reprice { id, new_price });
+            }
+            "amend" if parts.len() == 4 => {
+                let id = match parts[1].parse::<u64>() { Ok(v) => OrderId(v), Err(_) => { println!("invalid id"); continue; } };
+                let (new_price, new_qty) = match (parts[2].parse::<u64>(), parts[3].parse::<u64>()) { (Ok(p), Ok(q)) => (p, q), _ => { println!("invalid price or qty"); continue; } };
+                send(&ig, measure, RawCommand::Amend { id, new_price, new_qty });
+            }
+            "reduce" if parts.len() == 3 => {
+                let id = match parts[1].parse::<u64>() { Ok(v) => OrderId(v), Err(_) => { println!("invalid id"); continue; } };
+                let qty_delta: u64 = match parts[2].parse() { Ok(v) => v, Err(_) => { println!("invalid qty"); continue; } };
+                send(&ig, measure, RawCommand::Reduce { id, qty_delta });
+            }
+            "ref" if parts.len() == 3 => {
+                let value = match (parts[1], parts[2].parse::<i64>()) {
+                    ("index", Ok(v)) if v >= 0 => ReferenceValue::IndexPrice(v as u64),
+                    ("settle", Ok(v)) if v >= 0 => ReferenceValue::SettlementPrice(v as u64),
+                    ("funding", Ok(v)) => ReferenceValue::FundingRate(v),
+                    _ => { println!("usage: ref index|settle|funding <v>"); continue; }
+                };
+                send(&ig, measure, RawCommand::Reference(value));
+            }
+            "eod" if parts.len() == 1 => send(&ig, measure, RawCommand::EndOfDay),
+            "seed" if parts.len() == 5 => {
+                let Ok(nums) = parts[1..].iter().map(|p| p.parse::<u64>()).collect::<Result<Vec<_>, _>>() else { println!("usage: seed <levels> <mid> <tick> <qty>"); continue; };
+                let (levels, mid, tick, qty) = (nums[0], nums[1], nums[2], nums[3]);
+                // Levels a side around `mid`, bids below it and asks above; bids that would reach 0 are left out
+                for i in 1..=levels {
+                    if let Some(price) = mid.checked_sub(i * tick).filter(|&p| p > 0) { send(&ig, measure, RawCommand::Limit { side: Side::Buy, price, qty }); }
+                    send(&ig, measure, RawCommand::Limit { side: Side::Sell, price: mid + i * tick, qty });
+                }
+            }
+            "save" if parts.len() == 2 => {
+                let Some(snap) = ig.book_snapshot(SYMBOL) else { println!("book unavailable"); continue; };
+                match save(Path::new(parts[1]), &snap) {
+                    Ok(()) => println!("saved {} orders at seq {}", snap.book.order_count(), snap.next_seq),
+                    Err(e) => println!("save failed: {e}"),
+                }
+            }
+            "load" if parts.len() == 2 => {
+                let snap = match load(Path::new(parts[1])) { Ok(s) => s, Err(e) => { println!("load failed: {e}"); continue; } };
+                // The running worker stops once its senders are dropped with it
+                ig = start(OrderBook::from_snapshot(&snap.book), snap.next_seq, &tape, &latencies);
+                println!("loaded {} orders at seq {}", snap.book.order_count(), snap.next_seq);
+            }
+            "tape" if parts.len() <= 2 => {
+                let n: usize = match parts.get(1).map_or(Ok(10), |s| s.parse()) { Ok(v) => v, Err(_) => { println!("invalid count"); continue; } };
+                for e in tape.lock().unwrap().last_n(n) {
+                    println!("#{} {:?} px={} qty={} taker={} maker={}", e.trade_id, e.trade.taker_side, e.trade.price, e.trade.qty, e.trade.taker_id.0, e.trade.maker_id.0);
+                }
             }
             _ => println!("unknown command"),
         }
     }
+    if let Some(l) = measure { println!("{}", l.summary()); }
 }
@@ -0,0 +1,29 @@
+use ingestor::{TimeTravel, TravelError};
+use std::path::Path;
+
+// timetravel <journal> [seq] [steps]: rebuild the book as of `seq` (default: the journal's start),
+// print its depth, then step through `steps` commands (default: to the end) printing each diff
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 || args.len() > 4 {
+        eprintln!("usage: timetravel <journal> [seq] [steps]");
+        std::process::exit(2);
+    }
+    let num = |i: usize| args.get(i).map(|a| a.parse::<u64>().unwrap_or_else(|_| { eprintln!("invalid number {a}"); std::process::exit(2) }));
+    let mut tt = match TimeTravel::open(Path::new(&args[1]), None) {
+        Ok(tt) => tt,
+        Err(TravelError::MissingBase { journal, .. }) => { eprintln!("journal was compacted at seq {journal}; rebuild it from that snapshot with the library API"); std::process::exit(1) }
+        Err(e) => { eprintln!("cannot open journal: {e:?}"); std::process::exit(1) }
+    };
+    let seq = num(2).unwrap_or(*tt.range().start());
+    let book = match tt.seek(seq) {
+        Ok(book) => book,
+        Err(e) => { eprintln!("{e:?}"); std::process::exit(1) }
+    };
+    let (bids, asks) = book.top_n(10);
+    println!("book before seq {seq}: bids {bids:?} asks {asks:?}");
+    for _ in 0..num(3).unwrap_or(u64::MAX) {
+        let Some(step) = tt.step() else { break };
+        println!("{step}");
+    }
+}
@@ -0,0 +1,145 @@
+// Trading calendar: per instrument group, the regular session times, trading weekdays, holidays
+// and early closes. Gateways query it for "market closed" answers; `MultiIngestor::schedule_calendar`
+// turns it into sequenced session transitions for every symbol in a group, with the close run as
+// the end-of-day routine (`RawCommand::EndOfDay`). All times are UTC;
+// session times are micros after midnight, dates are days since 1970-01-01.
+use crate::{MultiIngestor, RawCommand, ScheduledCommand};
+use match_engine::SessionState;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+pub const MICROS_PER_DAY: u64 = 86_400_000_000;
+
+// Days since 1970-01-01 of a proleptic Gregorian date
+pub fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (m as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// (year, month, day) of a day count since 1970-01-01
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (yoe + era * 400 + (m <= 2) as i64, m, d)
+}
+
+// Phase start times within a trading day; equal neighbours skip a phase (e.g. no opening auction)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionTimes {
+    pub pre_open: u64,
+    pub open: u64,
+    pub pre_close: u64,
+    pub close: u64,
+}
+
+impl SessionTimes {
+    fn phases(self) -> [(u64, SessionState); 4] {
+        [(self.pre_open, SessionState::PreOpen), (self.open, SessionState::Open), (self.pre_close, SessionState::PreClose), (self.close, SessionState::Closed)]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupCalendar {
+    regular: SessionTimes,
+    weekdays: [bool; 7], // Monday first
+    holidays: BTreeSet<i64>,
+    early_closes: BTreeMap<i64, SessionTimes>,
+}
+
+impl GroupCalendar {
+    // Trades Monday to Friday at `regular` times
+    pub fn new(regular: SessionTimes) -> Self {
+        Self { regular, weekdays: [true, true, true, true, true, false, false], holidays: BTreeSet::new(), early_closes: BTreeMap::new() }
+    }
+
+    pub fn with_weekdays(mut self, weekdays: [bool; 7]) -> Self { self.weekdays = weekdays; self }
+
+    pub fn add_holiday(&mut self, y: i64, m: u32, d: u32) { self.holidays.insert(days_from_civil(y, m, d)); }
+
+    pub fn add_early_close(&mut self, y: i64, m: u32, d: u32, pre_close: u64, close: u64) {
+        self.early_closes.insert(days_from_civil(y, m, d), SessionTimes { pre_close, close, ..self.regular });
+    }
+
+    pub fn is_trading_day(&self, day: i64) -> bool {
+        self.weekdays[(day + 3).rem_euclid(7) as usize] && !self.holidays.contains(&day)
+    }
+
+    // None on weekends and holidays
+    pub fn session_times(&self, day: i64) -> Option<SessionTimes> {
+        self.is_trading_day(day).then(|| self.early_closes.get(&day).copied().unwrap_or(self.regular))
+    }
+
+    pub fn state_at(&self, ts_micros: u64) -> SessionState {
+        let Some(times) = self.session_times((ts_micros / MICROS_PER_DAY) as i64) else { return SessionState::Closed };
+        let tod = ts_micros % MICROS_PER_DAY;
+        times.phases().iter().rev().find(|(at, _)| tod >= *at).map_or(SessionState::Closed, |&(_, s)| s)
+    }
+
+    // Phase changes with from <= ts < to, in time order
+    pub fn transitions(&self, from: u64, to: u64) -> Vec<(u64, SessionState)> {
+        let mut out = Vec::new();
+        for day in (from / MICROS_PER_DAY) as i64..=(to / MICROS_PER_DAY) as i64 {
+            let Some(times) = self.session_times(day) else { continue };
+            let phases = times.phases();
+            for (i, &(at, state)) in phases.iter().enumerate() {
+                if phases.get(i + 1).is_some_and(|&(next, _)| next == at) { continue; }
+                let ts = day as u64 * MICROS_PER_DAY + at;
+                if (from..to).contains(&ts) { out.push((ts, state)); }
+            }
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TradingCalendar {
+    groups: HashMap<String, GroupCalendar>,
+    members: HashMap<String, String>, // symbol -> group
+}
+
+impl TradingCalendar {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn add_group(&mut self, name: &str, cal: GroupCalendar) { self.groups.insert(name.to_string(), cal); }
+
+    pub fn assign(&mut self, symbol: &str, group: &str) { self.members.insert(symbol.to_string(), group.to_string()); }
+
+    pub fn group_of(&self, symbol: &str) -> Option<&GroupCalendar> { self.groups.get(self.members.get(symbol)?) }
+
+    // None for symbols without a calendar
+    pub fn state_at(&self, symbol: &str, ts_micros: u64) -> Option<SessionState> { self.group_of(symbol).map(|g| g.state_at(ts_micros)) }
+
+    pub fn is_open(&self, symbol: &str, ts_micros: u64) -> bool { self.state_at(symbol, ts_micros) == Some(SessionState::Open) }
+
+    pub fn next_transition(&self, symbol: &str, ts_micros: u64) -> Option<(u64, SessionState)> {
+        let g = self.group_of(symbol)?;
+        // Look ahead far enough to cross long holiday stretches
+        (0..4).find_map(|w| g.transitions(ts_micros + 1 + w * 14 * MICROS_PER_DAY, ts_micros + 1 + (w + 1) * 14 * MICROS_PER_DAY).first().copied())
+    }
+}
+
+impl MultiIngestor {
+    // Queue every calendar transition in [from, to) as a timed session command on the symbols it
+    // covers (transitions already past apply immediately); returns the number of commands queued
+    pub fn schedule_calendar(&self, cal: &TradingCalendar, from: u64, to: u64) -> usize {
+        let mut n = 0;
+        for (symbol, tx) in &self.routes {
+            let Some(g) = cal.group_of(symbol) else { continue };
+            for (at, state) in g.transitions(from, to) {
+                let cmd = if state == SessionState::Closed { ScheduledCommand::EndOfDay } else { ScheduledCommand::Session(state) };
+                if tx.send(RawCommand::At { activate_at: at, cmd }).is_ok() { n += 1; }
+            }
+        }
+        n
+    }
+}
@@ -0,0 +1,295 @@
+// Session capture: every sequenced batch a worker applies, with the events it produced, in one
+// indexed binary file, so a production session can be replayed into a fresh book (tests, the
+// workload simulator, debugging) and checked byte for byte against what production emitted.
+//
+// file:    magic "MECAPT04" | records | index | footer
+// record:  len u32 | fnv1a32(body) u32 | body
+//   body:  symbol_len u8 | symbol | first_seq u64 | command_count u32 | trade_count u32 | cancel_count u32
+//          | commands (journal frames, see `journal`) | trades | cancels
+//   trade:  trade_id, taker_id, maker_id, price, qty, taker_correlation, maker_correlation, taker_account, maker_account, maker_remaining, ts u64
+//           | taker_side u8 | conditions u16 (maker_fully_filled is derived from maker_remaining)
+//   cancel: id, price, qty u64 | side u8 | reason u8 (0 expired, 1 halted, 2 reduce-only, 3 self-trade, 4 killed;
+//           bit 7 set for replay cancels)
+// index:   per record: offset u64 | first_seq u64 | command_count u32 | symbol_len u8 | symbol
+// footer:  index_off u64 | record_count u64 | magic "MECAPIDX"
+// Output sequence numbers are not captured (they depend on which feeds the book has on) and decode
+// as 0. All integers little-endian; sides 0 buy / 1 sell. The index is written by `finish`; a capture
+// cut short (crash, no `finish`) is still readable: the reader rebuilds the index by scanning the
+// records and ignores a torn record at the tail. A checksum mismatch is an error.
+use crate::journal::{decode_command, encode_command, fnv1a32};
+use match_engine::{AccountId, CancelReason, Command, ImplicitCancel, OrderBook, OrderId, Side, Trade, TradeConditions, TradeId};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"MECAPT04";
+const FOOTER_MAGIC: &[u8; 8] = b"MECAPIDX";
+const FOOTER_LEN: usize = 24;
+const RECORD_HEADER_LEN: usize = 8;
+
+fn invalid(msg: &str) -> io::Error { io::Error::new(io::ErrorKind::InvalidData, msg.to_string()) }
+
+fn side_byte(side: Side) -> u8 { match side { Side::Buy => 0, Side::Sell => 1 } }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureIndexEntry {
+    pub offset: u64,
+    pub symbol: String,
+    pub first_seq: u64,
+    pub commands: u32,
+}
+
+impl CaptureIndexEntry {
+    pub fn contains(&self, seq: u64) -> bool { seq >= self.first_seq && seq - self.first_seq < self.commands as u64 }
+}
+
+// One captured batch: the sequenced commands and what applying them produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedBatch {
+    pub symbol: String,
+    pub first_seq: u64,
+    pub commands: Vec<Command>,
+    pub trades: Vec<Trade>,
+    pub cancels: Vec<ImplicitCancel>,
+}
+
+// First batch whose replay did not reproduce the captured events
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayMismatch {
+    pub symbol: String,
+    pub first_seq: u64,
+    pub captured: Vec<u8>,
+    pub replayed: Vec<u8>,
+}
+
+impl CapturedBatch {
+    // Apply the batch to `book` like the worker did and compare the encoded events
+    pub fn replay(&self, book: &mut OrderBook) -> Result<(), ReplayMismatch> {
+        let mut cmds = self.commands.clone();
+        let mut trades = Vec::new();
+        let _ = book.process_commands_batch_checked_into(&mut cmds, &mut trades);
+        let cancels = book.drain_implicit_cancels();
+        let (mut captured, mut replayed) = (Vec::new(), Vec::new());
+        encode_events(&self.trades, &self.cancels, &mut captured);
+        encode_events(&trades, &cancels, &mut replayed);
+        if captured == replayed { return Ok(()); }
+        Err(ReplayMismatch { symbol: self.symbol.clone(), first_seq: self.first_seq, captured, replayed })
+    }
+}
+
+// Trades, then cancels, laid out as in a record (shared with the `delivery` spill file)
+pub(crate) fn encode_events(trades: &[Trade], cancels: &[ImplicitCancel], out: &mut Vec<u8>) {
+    for t in trades {
+        for v in [t.trade_id.0, t.taker_id.0, t.maker_id.0, t.price, t.qty, t.taker_correlation, t.maker_correlation, t.taker_account.0, t.maker_account.0, t.maker_remaining, t.ts] { out.extend_from_slice(&v.to_le_bytes()); }
+        out.push(side_byte(t.taker_side));
+        out.extend_from_slice(&t.conditions.0.to_le_bytes());
+    }
+    for c in cancels {
+        for v in [c.id.0, c.price, c.qty] { out.extend_from_slice(&v.to_le_bytes()); }
+        let reason = match c.reason { CancelReason::Expired => 0, CancelReason::Halted => 1, CancelReason::ReduceOnly => 2, CancelReason::SelfTrade => 3, CancelReason::Killed => 4, CancelReason::Deferred => 5, CancelReason::StopRejected => 6 };
+        out.extend_from_slice(&[side_byte(c.side), reason | (c.replay as u8) << 7]);
+    }
+}
+
+// Appends records as batches are applied; share it between workers through `Attachments::capture`
+pub struct CaptureWriter {
+    out: BufWriter<File>,
+    pos: u64,
+    index: Vec<CaptureIndexEntry>,
+    finished: bool,
+    buf: Vec<u8>,
+}
+
+impl CaptureWriter {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(MAGIC)?;
+        Ok(Self { out, pos: MAGIC.len() as u64, index: Vec::new(), finished: false, buf: Vec::new() })
+    }
+
+    pub fn record(&mut self, symbol: &str, commands: &[Command], trades: &[Trade], cancels: &[ImplicitCancel]) -> io::Result<()> {
+        if self.finished { return Err(io::Error::other("capture already finished")); }
+        let Some(first) = commands.first() else { return Ok(()) };
+        if symbol.len() > u8::MAX as usize { return Err(io::Error::new(io::ErrorKind::InvalidInput, "symbol too long for capture")); }
+        let b = &mut self.buf;
+        b.clear();
+        b.extend_from_slice(&[0u8; RECORD_HEADER_LEN]);
+        b.push(symbol.len() as u8);
+        b.extend_from_slice(symbol.as_bytes());
+        b.extend_from_slice(&first.seq().to_le_bytes());
+        for n in [commands.len(), trades.len(), cancels.len()] { b.extend_from_slice(&(n as u32).to_le_bytes()); }
+        for c in commands { encode_command(c, b); }
+        encode_events(trades, cancels, b);
+        let body_len = (b.len() - RECORD_HEADER_LEN) as u32;
+        let checksum = fnv1a32(&b[RECORD_HEADER_LEN..]);
+        b[..4].copy_from_slice(&body_len.to_le_bytes());
+        b[4..8].copy_from_slice(&checksum.to_le_bytes());
+        self.out.write_all(b)?;
+        self.index.push(CaptureIndexEntry { offset: self.pos, symbol: symbol.to_string(), first_seq: first.seq(), commands: commands.len() as u32 });
+        self.pos += b.len() as u64;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> { self.out.flush() }
+
+    // Write the index and footer and sync; later records are refused
+    pub fn finish(&mut self) -> io::Result<()> {
+        if self.finished { return Ok(()); }
+        self.finished = true;
+        for e in &self.index {
+            self.out.write_all(&e.offset.to_le_bytes())?;
+            self.out.write_all(&e.first_seq.to_le_bytes())?;
+            self.out.write_all(&e.commands.to_le_bytes())?;
+            self.out.write_all(&[e.symbol.len() as u8])?;
+            self.out.write_all(e.symbol.as_bytes())?;
+        }
+        self.out.write_all(&self.pos.to_le_bytes())?;
+        self.out.write_all(&(self.index.len() as u64).to_le_bytes())?;
+        self.out.write_all(FOOTER_MAGIC)?;
+        self.out.flush()?;
+        self.out.get_ref().sync_all()
+    }
+}
+
+pub struct CaptureReader {
+    bytes: Vec<u8>,
+    index: Vec<CaptureIndexEntry>,
+    indexed: bool,
+}
+
+struct Cursor<'a> {
+    b: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        let s = self.b.get(self.at..self.at + n).ok_or_else(|| invalid("short capture record"))?;
+        self.at += n;
+        Ok(s)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> { Ok(self.take(1)?[0]) }
+    fn u16(&mut self) -> io::Result<u16> { Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap())) }
+    fn u32(&mut self) -> io::Result<u32> { Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap())) }
+    fn u64(&mut self) -> io::Result<u64> { Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap())) }
+    fn side(&mut self) -> io::Result<Side> { match self.u8()? { 0 => Ok(Side::Buy), 1 => Ok(Side::Sell), _ => Err(invalid("bad side")) } }
+
+    fn symbol(&mut self) -> io::Result<String> {
+        let len = self.u8()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| invalid("bad symbol"))
+    }
+}
+
+pub(crate) fn decode_events(b: &[u8], n_trades: usize, n_cancels: usize) -> io::Result<(Vec<Trade>, Vec<ImplicitCancel>)> {
+    let mut c = Cursor { b, at: 0 };
+    let mut trades = Vec::with_capacity(n_trades.min(4096));
+    for _ in 0..n_trades {
+        let trade_id = TradeId(c.u64()?);
+        let (taker_id, maker_id, price, qty, taker_correlation, maker_correlation) = (OrderId(c.u64()?), OrderId(c.u64()?), c.u64()?, c.u64()?, c.u64()?, c.u64()?);
+        let (taker_account, maker_account, maker_remaining, ts) = (AccountId(c.u64()?), AccountId(c.u64()?), c.u64()?, c.u64()?);
+        trades.push(Trade { trade_id, taker_id, maker_id, price, qty, taker_side: c.side()?, taker_correlation, maker_correlation, taker_account, maker_account, maker_remaining, maker_fully_filled: maker_remaining == 0, ts, conditions: TradeConditions(c.u16()?), output_seq: 0 });
+    }
+    let mut cancels = Vec::with_capacity(n_cancels.min(4096));
+    for _ in 0..n_cancels {
+        let (id, price, qty, side) = (OrderId(c.u64()?), c.u64()?, c.u64()?, c.side()?);
+        let byte = c.u8()?;
+        let reason = match byte & 0x7F { 0 => CancelReason::Expired, 1 => CancelReason::Halted, 2 => CancelReason::ReduceOnly, 3 => CancelReason::SelfTrade, 4 => CancelReason::Killed, 5 => CancelReason::Deferred, 6 => CancelReason::StopRejected, _ => return Err(invalid("bad cancel reason")) };
+        cancels.push(ImplicitCancel { id, side, price, qty, reason, replay: byte & 0x80 != 0, output_seq: 0 });
+    }
+    if c.at != b.len() { return Err(invalid("trailing capture bytes")); }
+    Ok((trades, cancels))
+}
+
+impl CaptureReader {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC { return Err(invalid("not a capture file")); }
+        let mut r = Self { bytes, index: Vec::new(), indexed: false };
+        match r.read_index()? {
+            Some(index) => { r.index = index; r.indexed = true; }
+            None => r.index = r.scan()?,
+        }
+        Ok(r)
+    }
+
+    fn read_index(&self) -> io::Result<Option<Vec<CaptureIndexEntry>>> {
+        let b = &self.bytes;
+        if b.len() < MAGIC.len() + FOOTER_LEN || &b[b.len() - 8..] != FOOTER_MAGIC { return Ok(None); }
+        let mut c = Cursor { b, at: b.len() - FOOTER_LEN };
+        let (index_off, count) = (c.u64()? as usize, c.u64()? as usize);
+        let mut c = Cursor { b: &b[..b.len() - FOOTER_LEN], at: index_off };
+        let mut index = Vec::with_capacity(count);
+        for _ in 0..count {
+            let (offset, first_seq, commands) = (c.u64()?, c.u64()?, c.u32()?);
+            index.push(CaptureIndexEntry { offset, first_seq, commands, symbol: c.symbol()? });
+        }
+        Ok(Some(index))
+    }
+
+    // Index of an unfinished capture, from the records themselves
+    fn scan(&self) -> io::Result<Vec<CaptureIndexEntry>> {
+        let mut index = Vec::new();
+        let mut at = MAGIC.len();
+        while let Some(body) = self.body_at(at)? {
+            let mut c = Cursor { b: body, at: 0 };
+            let symbol = c.symbol()?;
+            index.push(CaptureIndexEntry { offset: at as u64, symbol, first_seq: c.u64()?, commands: c.u32()? });
+            at += RECORD_HEADER_LEN + body.len();
+        }
+        Ok(index)
+    }
+
+    // Checked body of the record at `at`, None past the end or for a torn tail
+    fn body_at(&self, at: usize) -> io::Result<Option<&[u8]>> {
+        let Some(header) = self.bytes.get(at..at + RECORD_HEADER_LEN) else { return Ok(None) };
+        let len = u32::from_le_bytes(header[..4].try_into().unwrap()) as usize;
+        let Some(body) = self.bytes.get(at + RECORD_HEADER_LEN..at + RECORD_HEADER_LEN + len) else { return Ok(None) };
+        if fnv1a32(body) != u32::from_le_bytes(header[4..].try_into().unwrap()) { return Err(invalid("capture checksum mismatch")); }
+        Ok(Some(body))
+    }
+
+    // Whether the capture was finished (index read from the file rather than rebuilt)
+    pub fn is_indexed(&self) -> bool { self.indexed }
+
+    pub fn len(&self) -> usize { self.index.len() }
+
+    pub fn is_empty(&self) -> bool { self.index.is_empty() }
+
+    pub fn index(&self) -> &[CaptureIndexEntry] { &self.index }
+
+    // Position of the batch of `symbol` that contains `seq`
+    pub fn find(&self, symbol: &str, seq: u64) -> Option<usize> { self.index.iter().position(|e| e.symbol == symbol && e.contains(seq)) }
+
+    pub fn batch(&self, i: usize) -> io::Result<CapturedBatch> {
+        let entry = self.index.get(i).ok_or_else(|| invalid("capture batch out of range"))?;
+        let body = self.body_at(entry.offset as usize)?.ok_or_else(|| invalid("capture record out of bounds"))?;
+        let mut c = Cursor { b: body, at: 0 };
+        let (symbol, first_seq) = (c.symbol()?, c.u64()?);
+        let (n_cmds, n_trades, n_cancels) = (c.u32()?, c.u32()?, c.u32()?);
+        let mut commands = Vec::with_capacity(n_cmds as usize);
+        for _ in 0..n_cmds {
+            let len = c.u32()? as usize;
+            c.take(4)?;
+            commands.push(decode_command(c.take(len)?)?);
+        }
+        let (trades, cancels) = decode_events(&body[c.at..], n_trades as usize, n_cancels as usize)?;
+        Ok(CapturedBatch { symbol, first_seq, commands, trades, cancels })
+    }
+
+    // Batches of `symbol` in capture order
+    pub fn batches<'a>(&'a self, symbol: &'a str) -> impl Iterator<Item = io::Result<CapturedBatch>> + 'a {
+        (0..self.index.len()).filter(move |&i| self.index[i].symbol == symbol).map(move |i| self.batch(i))
+    }
+
+    // Replay every batch of `symbol` into `book`, stopping at the first one that does not reproduce
+    // its events; returns the number of batches replayed
+    pub fn replay(&self, symbol: &str, book: &mut OrderBook) -> io::Result<Result<usize, ReplayMismatch>> {
+        let mut n = 0;
+        for b in self.batches(symbol) {
+            if let Err(m) = b?.replay(book) { return Ok(Err(m)); }
+            n += 1;
+        }
+        Ok(Ok(n))
+    }
+}
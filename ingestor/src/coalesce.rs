@@ -0,0 +1,80 @@
+// Adaptive coalescing (`Options::adaptive_coalescing`): instead of a fixed `coalesce_micros` and
+// `batch_size`, each worker retunes both after every batch from what it observed, within the bounds:
+//   window      how long the worker waits for the next command before closing a batch: two smoothed
+//               gaps between arrivals, so a burst keeps coalescing until it pauses; when the next
+//               command is not expected within `max_micros`, waiting would only add latency and the
+//               window drops to `min_micros`. Only time in which commands were arriving counts: a
+//               batch whose first command was waited for spans that receipt to its last one; one
+//               taken from a non-empty queue spans the previous batch's last receipt to its own.
+//               A batch of a single waited-for command means the window passed without another
+//               arrival, so the burst is over and the estimate is dropped
+//   batch size  doubles while full batches leave a backlog behind (the worker is falling behind, so
+//               larger batches buy throughput), halves once batches run at a quarter of it or less
+//               with nothing queued
+// Starts from `min_micros` and `batch_size` clamped to the bounds. The settings in effect for the next
+// batch are reported on its predecessor's `Progress::coalescing`. Multi-symbol workers only:
+// `ShardedIngestor::start` refuses it, `Ingestor` always batches with its fixed `batch_size`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveCoalescing {
+    pub min_micros: u32,
+    pub max_micros: u32,
+    pub min_batch: usize,
+    pub max_batch: usize,
+}
+
+impl Default for AdaptiveCoalescing {
+    fn default() -> Self { Self { min_micros: 0, max_micros: 500, min_batch: 64, max_batch: 4096 } }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoalesceSettings {
+    pub window_micros: u32,
+    pub batch_size: usize,
+}
+
+// One worker's tuning state; public so the policy can be driven with synthetic timings
+#[derive(Debug, Clone)]
+pub struct Coalescer {
+    cfg: AdaptiveCoalescing,
+    settings: CoalesceSettings,
+    gap_micros: Option<f64>, // smoothed time between arrivals
+    last_at: Option<u64>, // last receipt of the previous batch
+}
+
+impl Coalescer {
+    pub fn new(cfg: AdaptiveCoalescing, batch_size: usize) -> Self {
+        let batch_size = batch_size.clamp(cfg.min_batch.max(1), cfg.max_batch.max(cfg.min_batch).max(1));
+        Self { cfg, settings: CoalesceSettings { window_micros: cfg.min_micros, batch_size }, gap_micros: None, last_at: None }
+    }
+
+    pub fn settings(&self) -> CoalesceSettings { self.settings }
+
+    // A batch of `taken` commands received from `first_at` to `last_at`, with `backlog` still queued
+    // behind it; `waited` when the worker was idle until the first one arrived
+    pub fn observe(&mut self, taken: usize, backlog: usize, waited: bool, first_at: u64, last_at: u64) {
+        if taken == 0 { return; }
+        let prev = self.last_at.replace(last_at);
+        let (since, gaps) = match prev {
+            Some(prev) if !waited => (prev, taken),
+            _ => (first_at, taken - 1),
+        };
+        self.gap_micros = match gaps {
+            0 if waited => None,
+            0 => self.gap_micros,
+            _ => {
+                let gap = last_at.saturating_sub(since) as f64 / gaps as f64;
+                Some(self.gap_micros.map_or(gap, |g| (g + gap) / 2.0))
+            }
+        };
+        let (cfg, s) = (self.cfg, &mut self.settings);
+        if backlog > 0 && taken >= s.batch_size {
+            s.batch_size = (s.batch_size * 2).min(cfg.max_batch.max(1));
+        } else if backlog == 0 && taken * 4 <= s.batch_size {
+            s.batch_size = (s.batch_size / 2).max(cfg.min_batch.max(1));
+        }
+        s.window_micros = match self.gap_micros {
+            Some(g) if 2.0 * g <= cfg.max_micros as f64 => ((2.0 * g).ceil() as u32).clamp(cfg.min_micros, cfg.max_micros.max(cfg.min_micros)),
+            _ => cfg.min_micros,
+        };
+    }
+}
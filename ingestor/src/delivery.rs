@@ -0,0 +1,234 @@
+// Acknowledged (at-least-once) delivery of trades and implicit cancels. With a `DeliveryLog` attached
+// (`Attachments::delivery`), every applied batch that produced events is appended as one `Delivery`
+// at the next log offset (from 0, across symbols; per symbol in seq order). Consumers register by
+// name, poll from an offset and `commit` the offset after the last delivery they processed; the log
+// retains every delivery a registered consumer has not committed yet, so a consumer that crashed
+// registers again, gets its committed offset back and resumes from there without losing fills. It
+// may see deliveries it processed but did not commit: `(symbol, last_seq)` identifies a delivery.
+//   memory  the newest `memory_batches` retained deliveries are kept in memory ...
+//   spill   ... older ones are written to the spill file and read back on poll; the file is
+//           truncated once every consumer committed past it. Without a spill file the oldest
+//           deliveries are dropped instead and a consumer still behind them gets `Trimmed`.
+// Until a consumer registers, nothing is released (within the bounds above), so a late consumer
+// starts at the oldest delivery still retained.
+// spill record: len u32 | fnv1a32(body) u32 | body: offset u64 | last_seq u64 | trade_count u32
+//               | cancel_count u32 | symbol_len u8 | symbol | trades | cancels (laid out as in `capture`)
+use crate::capture::{decode_events, encode_events};
+use crate::journal::fnv1a32;
+use match_engine::{ImplicitCancel, Trade};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+const RECORD_HEADER_LEN: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivery {
+    pub offset: u64,
+    pub symbol: String,
+    pub last_seq: u64, // seq of the last command of the batch
+    pub trades: Vec<Trade>,
+    pub cancels: Vec<ImplicitCancel>,
+}
+
+#[derive(Debug)]
+pub enum DeliveryError {
+    // The offset was dropped (no spill file); `oldest` is the first one still retained
+    Trimmed { oldest: u64 },
+    Io(io::Error),
+}
+
+impl From<io::Error> for DeliveryError {
+    fn from(e: io::Error) -> Self { DeliveryError::Io(e) }
+}
+
+pub struct DeliveryLog {
+    inner: Mutex<LogInner>,
+    appended: Condvar,
+}
+
+struct LogInner {
+    memory_batches: usize,
+    oldest: u64, // retained: oldest..next, the spilled ones first
+    next: u64,
+    memory: VecDeque<Delivery>,
+    spill: Option<Spill>,
+    consumers: HashMap<String, u64>, // next offset each consumer needs
+}
+
+struct Spill {
+    path: PathBuf,
+    file: File,
+    positions: VecDeque<u64>, // file position of each spilled offset, from `oldest`
+    len: u64,
+    buf: Vec<u8>,
+}
+
+impl Spill {
+    fn write(&mut self, d: &Delivery) -> io::Result<()> {
+        if d.symbol.len() > u8::MAX as usize { return Err(io::Error::new(io::ErrorKind::InvalidInput, "symbol too long for spill")); }
+        let b = &mut self.buf;
+        b.clear();
+        b.extend_from_slice(&[0u8; RECORD_HEADER_LEN]);
+        for v in [d.offset, d.last_seq] { b.extend_from_slice(&v.to_le_bytes()); }
+        for n in [d.trades.len(), d.cancels.len()] { b.extend_from_slice(&(n as u32).to_le_bytes()); }
+        b.push(d.symbol.len() as u8);
+        b.extend_from_slice(d.symbol.as_bytes());
+        encode_events(&d.trades, &d.cancels, b);
+        let body_len = (b.len() - RECORD_HEADER_LEN) as u32;
+        let checksum = fnv1a32(&b[RECORD_HEADER_LEN..]);
+        b[..4].copy_from_slice(&body_len.to_le_bytes());
+        b[4..8].copy_from_slice(&checksum.to_le_bytes());
+        self.file.seek(SeekFrom::Start(self.len))?;
+        self.file.write_all(b)?;
+        self.positions.push_back(self.len);
+        self.len += b.len() as u64;
+        Ok(())
+    }
+
+    fn read(&mut self, i: usize) -> io::Result<Delivery> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let mut header = [0u8; RECORD_HEADER_LEN];
+        self.file.seek(SeekFrom::Start(self.positions[i]))?;
+        self.file.read_exact(&mut header)?;
+        let mut body = vec![0u8; u32::from_le_bytes(header[..4].try_into().unwrap()) as usize];
+        self.file.read_exact(&mut body)?;
+        if fnv1a32(&body) != u32::from_le_bytes(header[4..].try_into().unwrap()) { return Err(invalid("spill checksum mismatch")); }
+        let fixed = body.get(..25).ok_or_else(|| invalid("short spill record"))?;
+        let u64_at = |at: usize| u64::from_le_bytes(fixed[at..at + 8].try_into().unwrap());
+        let u32_at = |at: usize| u32::from_le_bytes(fixed[at..at + 4].try_into().unwrap()) as usize;
+        let symbol_end = 25 + fixed[24] as usize;
+        let symbol = body.get(25..symbol_end).and_then(|s| String::from_utf8(s.to_vec()).ok()).ok_or_else(|| invalid("bad spill symbol"))?;
+        let (trades, cancels) = decode_events(&body[symbol_end..], u32_at(16), u32_at(20))?;
+        Ok(Delivery { offset: u64_at(0), symbol, last_seq: u64_at(8), trades, cancels })
+    }
+
+    fn reset(&mut self) -> io::Result<()> {
+        self.file.set_len(0)?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+impl LogInner {
+    // Drop everything every registered consumer committed
+    fn release(&mut self) -> io::Result<()> {
+        let Some(&min) = self.consumers.values().min() else { return Ok(()) };
+        while self.oldest < min.min(self.next) {
+            match self.spill.as_mut().and_then(|s| s.positions.pop_front()) {
+                Some(_) => {}
+                None => { self.memory.pop_front(); }
+            }
+            self.oldest += 1;
+        }
+        match &mut self.spill {
+            Some(s) if s.positions.is_empty() && s.len > 0 => s.reset(),
+            _ => Ok(()),
+        }
+    }
+
+    fn spilled(&self) -> usize { self.spill.as_ref().map_or(0, |s| s.positions.len()) }
+}
+
+impl DeliveryLog {
+    // Retain at most `memory_batches` deliveries; older unacknowledged ones are dropped
+    pub fn in_memory(memory_batches: usize) -> Self { Self::with(memory_batches, None) }
+
+    // Retain every unacknowledged delivery, the ones beyond `memory_batches` in a spill file at `path`
+    pub fn with_spill(memory_batches: usize, path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(path)?;
+        Ok(Self::with(memory_batches, Some(Spill { path: path.to_path_buf(), file, positions: VecDeque::new(), len: 0, buf: Vec::new() })))
+    }
+
+    fn with(memory_batches: usize, spill: Option<Spill>) -> Self {
+        let inner = LogInner { memory_batches: memory_batches.max(1), oldest: 0, next: 0, memory: VecDeque::new(), spill, consumers: HashMap::new() };
+        Self { inner: Mutex::new(inner), appended: Condvar::new() }
+    }
+
+    // Append one batch's events (the workers do this; public for embedding); returns its offset
+    pub fn append(&self, symbol: &str, last_seq: u64, trades: &[Trade], cancels: &[ImplicitCancel]) -> io::Result<u64> {
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        let offset = inner.next;
+        inner.memory.push_back(Delivery { offset, symbol: symbol.to_string(), last_seq, trades: trades.to_vec(), cancels: cancels.to_vec() });
+        inner.next += 1;
+        let mut result = Ok(offset);
+        while inner.memory.len() > inner.memory_batches {
+            let d = inner.memory.pop_front().unwrap();
+            match &mut inner.spill {
+                Some(s) => if let Err(e) = s.write(&d) {
+                    // Keep it in memory rather than lose it
+                    inner.memory.push_front(d);
+                    result = Err(e);
+                    break;
+                },
+                None => inner.oldest = d.offset + 1,
+            }
+        }
+        drop(guard);
+        self.appended.notify_all();
+        result
+    }
+
+    // Register `consumer` (or look it up again after a restart); returns the offset to resume from
+    pub fn register(&self, consumer: &str) -> u64 {
+        let mut inner = self.inner.lock().unwrap();
+        let oldest = inner.oldest;
+        *inner.consumers.entry(consumer.to_string()).or_insert(oldest)
+    }
+
+    // Stop retaining deliveries for `consumer`
+    pub fn unregister(&self, consumer: &str) -> io::Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consumers.remove(consumer);
+        inner.release()
+    }
+
+    // `consumer` processed everything before `next`; commits never move back
+    pub fn commit(&self, consumer: &str, next: u64) -> io::Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        let next = next.min(inner.next);
+        let at = inner.consumers.entry(consumer.to_string()).or_insert(next);
+        *at = (*at).max(next);
+        inner.release()
+    }
+
+    pub fn committed(&self, consumer: &str) -> Option<u64> { self.inner.lock().unwrap().consumers.get(consumer).copied() }
+
+    // Up to `max` deliveries from offset `from` on, waiting up to `timeout` for the first one
+    pub fn poll(&self, from: u64, max: usize, timeout: Duration) -> Result<Vec<Delivery>, DeliveryError> {
+        let deadline = Instant::now() + timeout;
+        let mut guard = self.inner.lock().unwrap();
+        while guard.next <= from {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() { return Ok(Vec::new()); }
+            guard = self.appended.wait_timeout(guard, left).unwrap().0;
+        }
+        let inner = &mut *guard;
+        if from < inner.oldest { return Err(DeliveryError::Trimmed { oldest: inner.oldest }); }
+        let (spilled_end, memory_start) = (inner.oldest + inner.spilled() as u64, inner.next - inner.memory.len() as u64);
+        let mut out = Vec::new();
+        for at in (from..inner.next).take(max) {
+            out.push(match &mut inner.spill {
+                Some(s) if at < spilled_end => s.read((at - inner.oldest) as usize)?,
+                _ => inner.memory[(at - memory_start) as usize].clone(),
+            });
+        }
+        Ok(out)
+    }
+
+    // Offsets still retained
+    pub fn retained(&self) -> Range<u64> {
+        let inner = self.inner.lock().unwrap();
+        inner.oldest..inner.next
+    }
+
+    // Retained deliveries currently in the spill file
+    pub fn spilled(&self) -> usize { self.inner.lock().unwrap().spilled() }
+
+    pub fn spill_path(&self) -> Option<PathBuf> { self.inner.lock().unwrap().spill.as_ref().map(|s| s.path.clone()) }
+}
@@ -0,0 +1,200 @@
+// Analytics export to Parquet: one file per table in the output directory, rows buffered and
+// written as row groups of `row_group_rows`. Prices and quantities are the engine's integer ticks/lots.
+//
+// trades.parquet   symbol utf8, ts_micros u64, taker_id u64, maker_id u64, price u64, qty u64
+// orders.parquet   symbol utf8, ts_micros u64, order_id u64, event utf8 (accepted|filled|cancelled|rejected),
+//                  side utf8 (buy|sell), price u64 (null for market), qty u64
+//                  (accepted: original qty, filled: fill qty, cancelled: remaining qty)
+// depth.parquet    symbol utf8, ts_micros u64, side utf8, level u32 (0 = best), price u64, qty u64, orders u32
+use arrow_array::builder::{StringBuilder, UInt32Builder, UInt64Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use match_engine::{OrderBook, OrderId, Side, Trade};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderEventKind {
+    Accepted,
+    Filled,
+    Cancelled,
+    Rejected,
+}
+
+impl OrderEventKind {
+    fn as_str(self) -> &'static str {
+        match self { Self::Accepted => "accepted", Self::Filled => "filled", Self::Cancelled => "cancelled", Self::Rejected => "rejected" }
+    }
+}
+
+fn side_str(side: Side) -> &'static str { match side { Side::Buy => "buy", Side::Sell => "sell" } }
+
+fn to_io(e: parquet::errors::ParquetError) -> io::Error { io::Error::other(e) }
+
+pub fn trades_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("symbol", DataType::Utf8, false),
+        Field::new("ts_micros", DataType::UInt64, false),
+        Field::new("taker_id", DataType::UInt64, false),
+        Field::new("maker_id", DataType::UInt64, false),
+        Field::new("price", DataType::UInt64, false),
+        Field::new("qty", DataType::UInt64, false),
+    ]))
+}
+
+pub fn orders_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("symbol", DataType::Utf8, false),
+        Field::new("ts_micros", DataType::UInt64, false),
+        Field::new("order_id", DataType::UInt64, false),
+        Field::new("event", DataType::Utf8, false),
+        Field::new("side", DataType::Utf8, false),
+        Field::new("price", DataType::UInt64, true),
+        Field::new("qty", DataType::UInt64, false),
+    ]))
+}
+
+pub fn depth_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("symbol", DataType::Utf8, false),
+        Field::new("ts_micros", DataType::UInt64, false),
+        Field::new("side", DataType::Utf8, false),
+        Field::new("level", DataType::UInt32, false),
+        Field::new("price", DataType::UInt64, false),
+        Field::new("qty", DataType::UInt64, false),
+        Field::new("orders", DataType::UInt32, false),
+    ]))
+}
+
+struct Table {
+    writer: ArrowWriter<File>,
+    schema: SchemaRef,
+    symbol: StringBuilder,
+    ts: UInt64Builder,
+    strs: Vec<StringBuilder>,
+    u32s: Vec<UInt32Builder>,
+    u64s: Vec<UInt64Builder>,
+    rows: usize,
+}
+
+impl Table {
+    fn create(path: &Path, schema: SchemaRef) -> io::Result<Self> {
+        let props = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+        let writer = ArrowWriter::try_new(File::create(path)?, schema.clone(), Some(props)).map_err(to_io)?;
+        Ok(Self { writer, schema, symbol: StringBuilder::new(), ts: UInt64Builder::new(), strs: Vec::new(), u32s: Vec::new(), u64s: Vec::new(), rows: 0 })
+    }
+
+    // Columns after (symbol, ts) in schema order; `order` says which builder list each comes from
+    fn flush(&mut self, order: &[Col]) -> io::Result<()> {
+        if self.rows == 0 { return Ok(()); }
+        let mut cols: Vec<ArrayRef> = vec![Arc::new(self.symbol.finish()), Arc::new(self.ts.finish())];
+        let (mut s, mut a, mut b) = (0, 0, 0);
+        for c in order {
+            match c {
+                Col::Str => { cols.push(Arc::new(self.strs[s].finish())); s += 1; }
+                Col::U32 => { cols.push(Arc::new(self.u32s[a].finish())); a += 1; }
+                Col::U64 => { cols.push(Arc::new(self.u64s[b].finish())); b += 1; }
+            }
+        }
+        let batch = RecordBatch::try_new(self.schema.clone(), cols).map_err(io::Error::other)?;
+        self.writer.write(&batch).map_err(to_io)?;
+        self.writer.flush().map_err(to_io)?;
+        self.rows = 0;
+        Ok(())
+    }
+}
+
+enum Col { Str, U32, U64 }
+
+const TRADE_COLS: &[Col] = &[Col::U64, Col::U64, Col::U64, Col::U64];
+const ORDER_COLS: &[Col] = &[Col::U64, Col::Str, Col::Str, Col::U64, Col::U64];
+const DEPTH_COLS: &[Col] = &[Col::Str, Col::U32, Col::U64, Col::U64, Col::U32];
+
+fn with_builders(mut t: Table, cols: &[Col]) -> Table {
+    for c in cols {
+        match c {
+            Col::Str => t.strs.push(StringBuilder::new()),
+            Col::U32 => t.u32s.push(UInt32Builder::new()),
+            Col::U64 => t.u64s.push(UInt64Builder::new()),
+        }
+    }
+    t
+}
+
+pub struct ParquetExporter {
+    trades: Table,
+    orders: Table,
+    depth: Table,
+    row_group_rows: usize,
+}
+
+impl ParquetExporter {
+    pub fn create(dir: &Path, row_group_rows: usize) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(Self {
+            trades: with_builders(Table::create(&dir.join("trades.parquet"), trades_schema())?, TRADE_COLS),
+            orders: with_builders(Table::create(&dir.join("orders.parquet"), orders_schema())?, ORDER_COLS),
+            depth: with_builders(Table::create(&dir.join("depth.parquet"), depth_schema())?, DEPTH_COLS),
+            row_group_rows: row_group_rows.max(1),
+        })
+    }
+
+    pub fn record_trade(&mut self, symbol: &str, ts_micros: u64, t: &Trade) -> io::Result<()> {
+        let tb = &mut self.trades;
+        tb.symbol.append_value(symbol);
+        tb.ts.append_value(ts_micros);
+        for (b, v) in tb.u64s.iter_mut().zip([t.taker_id.0, t.maker_id.0, t.price, t.qty]) { b.append_value(v); }
+        tb.rows += 1;
+        if tb.rows >= self.row_group_rows { tb.flush(TRADE_COLS)?; }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn record_order_event(&mut self, symbol: &str, ts_micros: u64, id: OrderId, kind: OrderEventKind, side: Side, price: Option<u64>, qty: u64) -> io::Result<()> {
+        let tb = &mut self.orders;
+        tb.symbol.append_value(symbol);
+        tb.ts.append_value(ts_micros);
+        tb.u64s[0].append_value(id.0);
+        tb.strs[0].append_value(kind.as_str());
+        tb.strs[1].append_value(side_str(side));
+        tb.u64s[1].append_option(price);
+        tb.u64s[2].append_value(qty);
+        tb.rows += 1;
+        if tb.rows >= self.row_group_rows { tb.flush(ORDER_COLS)?; }
+        Ok(())
+    }
+
+    // One row per level of the top `levels` on each side
+    pub fn record_depth(&mut self, symbol: &str, ts_micros: u64, book: &OrderBook, levels: usize) -> io::Result<()> {
+        let (bids, asks) = book.top_n(levels);
+        let tb = &mut self.depth;
+        for (side, lv) in [(Side::Buy, &bids), (Side::Sell, &asks)] {
+            for (i, &(price, qty, orders)) in lv.iter().enumerate() {
+                tb.symbol.append_value(symbol);
+                tb.ts.append_value(ts_micros);
+                tb.strs[0].append_value(side_str(side));
+                tb.u32s[0].append_value(i as u32);
+                tb.u64s[0].append_value(price);
+                tb.u64s[1].append_value(qty);
+                tb.u32s[1].append_value(orders as u32);
+                tb.rows += 1;
+            }
+        }
+        if tb.rows >= self.row_group_rows { tb.flush(DEPTH_COLS)?; }
+        Ok(())
+    }
+
+    // Flush buffered rows and write the Parquet footers; files are unreadable until this runs
+    pub fn finish(mut self) -> io::Result<()> {
+        self.trades.flush(TRADE_COLS)?;
+        self.orders.flush(ORDER_COLS)?;
+        self.depth.flush(DEPTH_COLS)?;
+        for t in [self.trades, self.orders, self.depth] { t.writer.close().map_err(to_io)?; }
+        Ok(())
+    }
+}
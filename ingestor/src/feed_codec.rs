@@ -0,0 +1,223 @@
+// Binary encoding of the depth feed (`publisher::FeedMsg`) for datagram / WebSocket transports, one
+// message per frame, all integers little-endian:
+//   header      kind u8 (1 snapshot, 2 delta) | flags u8 | symbol_len u8 | symbol
+//   plain       seq u64, then
+//                 snapshot  bid_count u16 | ask_count u16 | levels: price u64 | qty u64 | orders u32
+//                 delta     side u8 | price u64 | qty u64 | orders u32
+//   compressed  (flags bit 0) seq varint, then
+//                 snapshot  bid_count, ask_count varint | per side best first: price varint (the first
+//                           level absolute, the others as the distance from the level before) | qty,
+//                           orders varint
+//                 delta     side u8 | price zigzag varint offset from the best price on its side as of
+//                           the previous seq (flags bit 1: absolute instead) | qty, orders varint
+// Varints are LEB128. Both ends track the published book per symbol (a `FeedBook`) to agree on the
+// best price, so a relative delta only decodes right after the message before it: after a gap the
+// decoder returns `OutOfSync` for relative deltas until the next snapshot (self-contained) catches
+// it up. The encoder falls back to absolute prices whenever it has no book in step, e.g. for deltas
+// published before the first snapshot, so resent messages can always be encoded absolute.
+use crate::publisher::{FeedBook, FeedMsg};
+use match_engine::Side;
+use std::collections::HashMap;
+
+const KIND_SNAPSHOT: u8 = 1;
+const KIND_DELTA: u8 = 2;
+const FLAG_COMPRESSED: u8 = 1;
+const FLAG_ABSOLUTE: u8 = 1 << 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodecError {
+    Truncated,
+    Invalid(&'static str),
+    // A relative delta arrived without the message before it; wait for a snapshot
+    OutOfSync { symbol: String, seq: u64 },
+}
+
+#[derive(Default)]
+pub struct FeedEncoder {
+    compress: bool,
+    books: HashMap<String, FeedBook>,
+}
+
+impl FeedEncoder {
+    pub fn new(compress: bool) -> Self { Self { compress, books: HashMap::new() } }
+
+    pub fn is_compressed(&self) -> bool { self.compress }
+
+    // Append one encoded message to `out`; messages of a symbol must be encoded in feed order
+    pub fn encode(&mut self, msg: &FeedMsg, out: &mut Vec<u8>) {
+        let book = self.books.entry(msg.symbol().to_string()).or_default();
+        match msg {
+            FeedMsg::Snapshot { symbol, seq, bids, asks } => {
+                header(out, KIND_SNAPSHOT, if self.compress { FLAG_COMPRESSED } else { 0 }, symbol);
+                if self.compress {
+                    put_varint(out, *seq);
+                    put_varint(out, bids.len() as u64);
+                    put_varint(out, asks.len() as u64);
+                    for levels in [bids, asks] {
+                        let mut prev = None;
+                        for &(price, qty, orders) in levels {
+                            put_varint(out, prev.map_or(price, |p: u64| p.abs_diff(price)));
+                            put_varint(out, qty);
+                            put_varint(out, orders as u64);
+                            prev = Some(price);
+                        }
+                    }
+                } else {
+                    out.extend_from_slice(&seq.to_le_bytes());
+                    out.extend_from_slice(&(bids.len() as u16).to_le_bytes());
+                    out.extend_from_slice(&(asks.len() as u16).to_le_bytes());
+                    for &(price, qty, orders) in bids.iter().chain(asks) { put_level(out, price, qty, orders); }
+                }
+            }
+            FeedMsg::Delta { symbol, seq, side, price, qty, orders } => {
+                let best = if book.seq() == Some(seq.wrapping_sub(1)) { best_of(book, *side) } else { None };
+                match (self.compress, best) {
+                    (false, _) => {
+                        header(out, KIND_DELTA, 0, symbol);
+                        out.extend_from_slice(&seq.to_le_bytes());
+                        out.push(side_byte(*side));
+                        put_level(out, *price, *qty, *orders);
+                    }
+                    (true, best) => {
+                        header(out, KIND_DELTA, FLAG_COMPRESSED | if best.is_none() { FLAG_ABSOLUTE } else { 0 }, symbol);
+                        put_varint(out, *seq);
+                        out.push(side_byte(*side));
+                        match best {
+                            Some(b) => put_varint(out, zigzag(price.wrapping_sub(b) as i64)),
+                            None => put_varint(out, *price),
+                        }
+                        put_varint(out, *qty);
+                        put_varint(out, *orders as u64);
+                    }
+                }
+            }
+        }
+        let _ = book.apply(msg);
+    }
+}
+
+#[derive(Default)]
+pub struct FeedDecoder {
+    books: HashMap<String, FeedBook>,
+}
+
+impl FeedDecoder {
+    pub fn new() -> Self { Self::default() }
+
+    // Decode one frame (plain or compressed)
+    pub fn decode(&mut self, frame: &[u8]) -> Result<FeedMsg, CodecError> {
+        let mut r = Reader { buf: frame, at: 0 };
+        let (kind, flags) = (r.u8()?, r.u8()?);
+        let len = r.u8()? as usize;
+        let symbol = std::str::from_utf8(r.take(len)?).map_err(|_| CodecError::Invalid("symbol not utf-8"))?.to_string();
+        let compressed = flags & FLAG_COMPRESSED != 0;
+        let seq = if compressed { r.varint()? } else { r.u64()? };
+        let book = self.books.entry(symbol.clone()).or_default();
+        let msg = match kind {
+            KIND_SNAPSHOT if compressed => {
+                let (nb, na) = (r.varint()? as usize, r.varint()? as usize);
+                let mut sides = [Vec::with_capacity(nb.min(1024)), Vec::with_capacity(na.min(1024))];
+                for (levels, (n, side)) in sides.iter_mut().zip([(nb, Side::Buy), (na, Side::Sell)]) {
+                    let mut prev: Option<u64> = None;
+                    for _ in 0..n {
+                        let v = r.varint()?;
+                        // Levels run best first: bids down, asks up
+                        let price = match (prev, side) {
+                            (None, _) => v,
+                            (Some(p), Side::Buy) => p.checked_sub(v).ok_or(CodecError::Invalid("bid below zero"))?,
+                            (Some(p), Side::Sell) => p.checked_add(v).ok_or(CodecError::Invalid("ask overflow"))?,
+                        };
+                        levels.push((price, r.varint()?, r.varint()? as usize));
+                        prev = Some(price);
+                    }
+                }
+                let [bids, asks] = sides;
+                FeedMsg::Snapshot { symbol, seq, bids, asks }
+            }
+            KIND_SNAPSHOT => {
+                let (nb, na) = (r.u16()? as usize, r.u16()? as usize);
+                let mut levels = Vec::with_capacity(nb + na);
+                for _ in 0..nb + na { levels.push((r.u64()?, r.u64()?, r.u32()? as usize)); }
+                let asks = levels.split_off(nb);
+                FeedMsg::Snapshot { symbol, seq, bids: levels, asks }
+            }
+            KIND_DELTA => {
+                let side = match r.u8()? { 0 => Side::Buy, 1 => Side::Sell, _ => return Err(CodecError::Invalid("bad side")) };
+                let (price, qty, orders) = if !compressed {
+                    (r.u64()?, r.u64()?, r.u32()? as usize)
+                } else {
+                    let v = r.varint()?;
+                    let price = if flags & FLAG_ABSOLUTE != 0 { v } else {
+                        let best = if book.seq() == Some(seq.wrapping_sub(1)) { best_of(book, side) } else { None };
+                        let Some(best) = best else { return Err(CodecError::OutOfSync { symbol, seq }) };
+                        best.wrapping_add(unzigzag(v) as u64)
+                    };
+                    (price, r.varint()?, r.varint()? as usize)
+                };
+                FeedMsg::Delta { symbol, seq, side, price, qty, orders }
+            }
+            _ => return Err(CodecError::Invalid("bad kind")),
+        };
+        if r.at != frame.len() { return Err(CodecError::Invalid("trailing bytes")); }
+        let _ = book.apply(&msg);
+        Ok(msg)
+    }
+}
+
+fn best_of(book: &FeedBook, side: Side) -> Option<u64> {
+    match side { Side::Buy => book.bids(), Side::Sell => book.asks() }.first().map(|l| l.0)
+}
+
+fn side_byte(side: Side) -> u8 { match side { Side::Buy => 0, Side::Sell => 1 } }
+
+fn header(out: &mut Vec<u8>, kind: u8, flags: u8, symbol: &str) {
+    let symbol = &symbol.as_bytes()[..symbol.len().min(u8::MAX as usize)];
+    out.extend_from_slice(&[kind, flags, symbol.len() as u8]);
+    out.extend_from_slice(symbol);
+}
+
+fn put_level(out: &mut Vec<u8>, price: u64, qty: u64, orders: usize) {
+    out.extend_from_slice(&price.to_le_bytes());
+    out.extend_from_slice(&qty.to_le_bytes());
+    out.extend_from_slice(&(orders as u32).to_le_bytes());
+}
+
+fn put_varint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 { out.push(v as u8 | 0x80); v >>= 7; }
+    out.push(v as u8);
+}
+
+fn zigzag(v: i64) -> u64 { ((v << 1) ^ (v >> 63)) as u64 }
+
+fn unzigzag(v: u64) -> i64 { (v >> 1) as i64 ^ -((v & 1) as i64) }
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], CodecError> {
+        let s = self.buf.get(self.at..self.at + n).ok_or(CodecError::Truncated)?;
+        self.at += n;
+        Ok(s)
+    }
+
+    fn u8(&mut self) -> Result<u8, CodecError> { Ok(self.take(1)?[0]) }
+
+    fn u16(&mut self) -> Result<u16, CodecError> { Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap())) }
+
+    fn u32(&mut self) -> Result<u32, CodecError> { Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap())) }
+
+    fn u64(&mut self) -> Result<u64, CodecError> { Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap())) }
+
+    fn varint(&mut self) -> Result<u64, CodecError> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.u8()?;
+            v |= ((b & 0x7F) as u64) << shift;
+            if b & 0x80 == 0 { return Ok(v); }
+        }
+        Err(CodecError::Invalid("varint too long"))
+    }
+}
@@ -0,0 +1,170 @@
+// Deterministic multi-producer test harness for `MultiIngestor` and `ShardedIngestor`. A `Script` gives
+// each producer thread its own list of sends, split into phases by barriers every producer takes part
+// in. At a barrier the harness waits until all producers have sent their phase and the ingestor has
+// applied every command sent so far, collects the trades, runs the caller's hook (e.g. to move a symbol
+// to another worker) and only then releases the producers into the next phase.
+// Within a phase a symbol may be written by one producer only (checked before the run), so each
+// symbol's command order, and with it its trade tape, is fixed however the threads interleave; the
+// expected tapes can be computed without threads by `Script::expected_tapes`. Scheduled commands (`At`)
+// and cross-instrument triggers are outside what the harness can predict; books must emit trades.
+use crate::{MultiIngestor, MultiRawCommand, Progress, RawCommand, ShardedIngestor};
+use crossbeam_channel::{Receiver, Sender};
+use match_engine::{OrderBook, Trade};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Barrier;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy)]
+pub enum ScriptStep<'a> {
+    Send { symbol: &'a str, cmd: RawCommand },
+    Barrier,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HarnessError {
+    // Two producers write the same symbol within one phase, so its order would depend on scheduling
+    SharedSymbol { phase: usize, symbol: String, producers: (usize, usize) },
+    // The ingestor had not applied a phase's commands in time
+    Timeout { phase: usize, symbol: String, applied: usize, sent: usize },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Script {
+    producers: Vec<Vec<(String, RawCommand)>>,
+    // Per phase, how many commands each producer sends before the barrier ending it
+    phase_ends: Vec<Vec<usize>>,
+}
+
+impl Script {
+    pub fn new(producers: usize) -> Self { Self { producers: vec![Vec::new(); producers], phase_ends: Vec::new() } }
+
+    pub fn send(&mut self, producer: usize, symbol: &str, cmd: RawCommand) -> &mut Self {
+        self.producers[producer].push((symbol.to_string(), cmd));
+        self
+    }
+
+    // End the current phase for every producer
+    pub fn barrier(&mut self) -> &mut Self {
+        self.phase_ends.push(self.producers.iter().map(Vec::len).collect());
+        self
+    }
+
+    // Phases the run goes through, the last one ending with the script
+    pub fn phases(&self) -> usize { self.phase_ends.len() + 1 }
+
+    // Producer `p`'s steps in order, with its barriers
+    pub fn steps(&self, p: usize) -> Vec<ScriptStep<'_>> {
+        let mut steps = Vec::new();
+        let mut at = 0;
+        for ends in self.phase_ends.iter().map(|e| e[p]).chain([self.producers[p].len()]) {
+            steps.extend(self.producers[p][at..ends].iter().map(|(symbol, cmd)| ScriptStep::Send { symbol, cmd: *cmd }));
+            steps.push(ScriptStep::Barrier);
+            at = ends;
+        }
+        steps.pop();
+        steps
+    }
+
+    // Commands of `phase` by symbol in the order they are applied, checking each has a single writer
+    fn phase_cmds(&self, phase: usize) -> Result<BTreeMap<&str, Vec<RawCommand>>, HarnessError> {
+        let mut by_symbol: BTreeMap<&str, (usize, Vec<RawCommand>)> = BTreeMap::new();
+        for (p, cmds) in self.producers.iter().enumerate() {
+            let from = if phase == 0 { 0 } else { self.phase_ends[phase - 1][p] };
+            let to = self.phase_ends.get(phase).map_or(cmds.len(), |e| e[p]);
+            for (symbol, cmd) in &cmds[from..to] {
+                let (writer, list) = by_symbol.entry(symbol).or_insert((p, Vec::new()));
+                if *writer != p { return Err(HarnessError::SharedSymbol { phase, symbol: symbol.clone(), producers: (*writer, p) }); }
+                list.push(*cmd);
+            }
+        }
+        Ok(by_symbol.into_iter().map(|(s, (_, cmds))| (s, cmds)).collect())
+    }
+
+    // Trade tapes a correct ingestor produces from `books`, applying each symbol's commands in order
+    pub fn expected_tapes(&self, books: Vec<(String, OrderBook)>) -> Result<BTreeMap<String, Vec<Trade>>, HarnessError> {
+        let mut books: HashMap<String, (OrderBook, u64)> = books.into_iter().map(|(s, b)| (s, (b, 0))).collect();
+        let mut tapes: BTreeMap<String, Vec<Trade>> = BTreeMap::new();
+        for phase in 0..self.phases() {
+            for (symbol, cmds) in self.phase_cmds(phase)? {
+                let Some((book, seq)) = books.get_mut(symbol) else { continue };
+                let tape = tapes.entry(symbol.to_string()).or_default();
+                for cmd in cmds {
+                    let _ = book.process_commands_batch_presorted_unchecked(&[crate::sequence(cmd, *seq)], tape);
+                    *seq += 1;
+                }
+            }
+        }
+        tapes.retain(|_, t| !t.is_empty());
+        Ok(tapes)
+    }
+}
+
+// The ingestor's input and outputs; the harness must be the only reader of both receivers
+pub struct Harness {
+    tx_cmd: Sender<MultiRawCommand>,
+    rx_trade: Receiver<(String, Trade)>,
+    rx_progress: Receiver<Progress>,
+    pub timeout: Duration,
+}
+
+impl From<&MultiIngestor> for Harness {
+    fn from(ig: &MultiIngestor) -> Self { Self::new(ig.tx_cmd.clone(), ig.rx_trade.clone(), ig.rx_progress.clone()) }
+}
+
+impl From<&ShardedIngestor> for Harness {
+    fn from(ig: &ShardedIngestor) -> Self { Self::new(ig.tx_cmd.clone(), ig.rx_trade.clone(), ig.rx_progress.clone()) }
+}
+
+impl Harness {
+    pub fn new(tx_cmd: Sender<MultiRawCommand>, rx_trade: Receiver<(String, Trade)>, rx_progress: Receiver<Progress>) -> Self {
+        Self { tx_cmd, rx_trade, rx_progress, timeout: Duration::from_secs(5) }
+    }
+
+    // Play `script`, one thread per producer, calling `at_barrier(phase)` once each phase is applied;
+    // returns the trade tape of every symbol that traded
+    pub fn run(&self, script: &Script, mut at_barrier: impl FnMut(usize)) -> Result<BTreeMap<String, Vec<Trade>>, HarnessError> {
+        let phases = (0..script.phases()).map(|p| script.phase_cmds(p)).collect::<Result<Vec<_>, _>>()?;
+        let barrier = Barrier::new(script.producers.len() + 1);
+        let mut tapes: BTreeMap<String, Vec<Trade>> = BTreeMap::new();
+        let (mut sent, mut applied) = (HashMap::<String, usize>::new(), HashMap::<String, usize>::new());
+        let mut error = None;
+        std::thread::scope(|scope| {
+            for p in 0..script.producers.len() {
+                let (tx, barrier, steps) = (self.tx_cmd.clone(), &barrier, script.steps(p));
+                scope.spawn(move || {
+                    // A barrier is two waits: arrive with the phase sent, leave once it is applied
+                    for step in steps.into_iter().chain([ScriptStep::Barrier]) {
+                        match step {
+                            ScriptStep::Send { symbol, cmd } => { let _ = tx.send(MultiRawCommand { symbol: symbol.to_string(), cmd }); }
+                            ScriptStep::Barrier => { barrier.wait(); barrier.wait(); }
+                        }
+                    }
+                });
+            }
+            for (phase, cmds) in phases.iter().enumerate() {
+                barrier.wait();
+                for (symbol, list) in cmds { *sent.entry(symbol.to_string()).or_default() += list.len(); }
+                // After an error the protocol still runs to the end so no producer is left waiting
+                if error.is_none() {
+                    if let Err(e) = self.await_applied(phase, &sent, &mut applied) { error = Some(e); }
+                    for (symbol, t) in self.rx_trade.try_iter() { tapes.entry(symbol).or_default().push(t); }
+                    at_barrier(phase);
+                }
+                barrier.wait();
+            }
+        });
+        match error { Some(e) => Err(e), None => Ok(tapes) }
+    }
+
+    fn await_applied(&self, phase: usize, sent: &HashMap<String, usize>, applied: &mut HashMap<String, usize>) -> Result<(), HarnessError> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let behind = sent.iter().find(|(s, &n)| applied.get(*s).copied().unwrap_or(0) < n);
+            let Some((symbol, &n)) = behind else { return Ok(()) };
+            match self.rx_progress.recv_deadline(deadline) {
+                Ok(p) => *applied.entry(p.symbol).or_default() += p.commands,
+                Err(_) => return Err(HarnessError::Timeout { phase, symbol: symbol.clone(), applied: applied.get(symbol).copied().unwrap_or(0), sent: n }),
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+// Structured instrument keys for futures and options chains: (underlying, expiry, strike, right)
+// instead of a hand-built symbol string. An `InstrumentRegistry` interns each key to a dense
+// `InstrumentId` once; after that, routing (`InstrumentRoutes`) and lookups are an index into a
+// vector, with no string hashing or parsing on the command path.
+//   spot     underlying only                    "BTC/USDT"
+//   future   underlying, expiry (yyyymmdd)      "BTC/USDT-20261225"
+//   option   underlying, expiry, strike, right  "BTC/USDT-20261225-60000-C"
+// Workers, snapshots, journals and the output channels still name a book by its canonical symbol
+// (the forms above, `InstrumentKey::to_string`), which `parse` turns back into the key; an
+// underlying containing '-' is fine as long as it does not end in what looks like an expiry.
+use crate::{MultiIngestor, RawCommand};
+use crossbeam_channel::Sender;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum OptionRight {
+    Call,
+    Put,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InstrumentKey {
+    pub underlying: String,
+    pub expiry: Option<u32>,
+    // Both set for an option, neither otherwise
+    pub strike: Option<u64>,
+    pub right: Option<OptionRight>,
+}
+
+impl InstrumentKey {
+    pub fn spot(underlying: &str) -> Self { Self { underlying: underlying.to_string(), expiry: None, strike: None, right: None } }
+
+    pub fn future(underlying: &str, expiry: u32) -> Self { Self { expiry: Some(expiry), ..Self::spot(underlying) } }
+
+    pub fn option(underlying: &str, expiry: u32, strike: u64, right: OptionRight) -> Self {
+        Self { strike: Some(strike), right: Some(right), ..Self::future(underlying, expiry) }
+    }
+
+    // Inverse of `to_string`; None for an empty underlying
+    pub fn parse(symbol: &str) -> Option<Self> {
+        let parts: Vec<&str> = symbol.rsplitn(4, '-').collect();
+        if let [right, strike, expiry, underlying] = parts[..] {
+            let right = match right { "C" => Some(OptionRight::Call), "P" => Some(OptionRight::Put), _ => None };
+            if let (Some(right), Ok(strike), Some(expiry)) = (right, strike.parse(), parse_expiry(expiry)) {
+                if !underlying.is_empty() { return Some(Self::option(underlying, expiry, strike, right)); }
+            }
+        }
+        if let Some((underlying, expiry)) = symbol.rsplit_once('-') {
+            if let (false, Some(expiry)) = (underlying.is_empty(), parse_expiry(expiry)) { return Some(Self::future(underlying, expiry)); }
+        }
+        (!symbol.is_empty()).then(|| Self::spot(symbol))
+    }
+}
+
+fn parse_expiry(s: &str) -> Option<u32> { (s.len() == 8 && s.bytes().all(|b| b.is_ascii_digit())).then(|| s.parse().ok()).flatten() }
+
+impl fmt::Display for InstrumentKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.underlying)?;
+        if let Some(expiry) = self.expiry { write!(f, "-{expiry:08}")?; }
+        if let (Some(strike), Some(right)) = (self.strike, self.right) { write!(f, "-{strike}-{}", match right { OptionRight::Call => "C", OptionRight::Put => "P" })?; }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InstrumentId(pub u32);
+
+// Ids are dense, from 0, in registration order, and never reused
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentRegistry {
+    ids: HashMap<InstrumentKey, InstrumentId>,
+    keys: Vec<InstrumentKey>,
+    symbols: Vec<String>,
+}
+
+impl InstrumentRegistry {
+    pub fn intern(&mut self, key: InstrumentKey) -> InstrumentId {
+        if let Some(&id) = self.ids.get(&key) { return id; }
+        let id = InstrumentId(self.keys.len() as u32);
+        self.symbols.push(key.to_string());
+        self.ids.insert(key.clone(), id);
+        self.keys.push(key);
+        id
+    }
+
+    pub fn id(&self, key: &InstrumentKey) -> Option<InstrumentId> { self.ids.get(key).copied() }
+
+    pub fn by_symbol(&self, symbol: &str) -> Option<InstrumentId> { self.id(&InstrumentKey::parse(symbol)?) }
+
+    pub fn key(&self, id: InstrumentId) -> Option<&InstrumentKey> { self.keys.get(id.0 as usize) }
+
+    pub fn symbol(&self, id: InstrumentId) -> Option<&str> { self.symbols.get(id.0 as usize).map(String::as_str) }
+
+    pub fn len(&self) -> usize { self.keys.len() }
+
+    pub fn is_empty(&self) -> bool { self.keys.is_empty() }
+
+    pub fn iter(&self) -> impl Iterator<Item = (InstrumentId, &InstrumentKey)> { self.keys.iter().enumerate().map(|(i, k)| (InstrumentId(i as u32), k)) }
+
+    // Expiries listed for `underlying` (futures or options), earliest first
+    pub fn expiries(&self, underlying: &str) -> Vec<u32> {
+        let mut out: Vec<u32> = self.keys.iter().filter(|k| k.underlying == underlying).filter_map(|k| k.expiry).collect();
+        out.sort_unstable();
+        out.dedup();
+        out
+    }
+
+    // Options on `underlying` expiring on `expiry`, by strike, calls before puts
+    pub fn chain(&self, underlying: &str, expiry: u32) -> Vec<InstrumentId> {
+        let mut out: Vec<InstrumentId> = self.iter().filter(|(_, k)| k.underlying == underlying && k.expiry == Some(expiry) && k.strike.is_some()).map(|(id, _)| id).collect();
+        out.sort_by_key(|&id| { let k = &self.keys[id.0 as usize]; (k.strike, k.right) });
+        out
+    }
+}
+
+// Per-instrument senders indexed by id (see `MultiIngestor::instrument_routes`)
+#[derive(Clone)]
+pub struct InstrumentRoutes {
+    routes: Vec<Option<Sender<RawCommand>>>,
+}
+
+impl InstrumentRoutes {
+    // False for an instrument without a worker or a stopped worker
+    pub fn send(&self, id: InstrumentId, cmd: RawCommand) -> bool {
+        self.routes.get(id.0 as usize).and_then(Option::as_ref).is_some_and(|tx| tx.send(cmd).is_ok())
+    }
+
+    pub fn contains(&self, id: InstrumentId) -> bool { self.routes.get(id.0 as usize).is_some_and(Option::is_some) }
+}
+
+impl MultiIngestor {
+    // Senders for every registered instrument whose canonical symbol has a worker; instruments
+    // registered later need a fresh call
+    pub fn instrument_routes(&self, registry: &InstrumentRegistry) -> InstrumentRoutes {
+        InstrumentRoutes { routes: registry.symbols.iter().map(|s| self.routes.get(s).cloned()).collect() }
+    }
+}
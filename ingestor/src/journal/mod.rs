@@ -0,0 +1,424 @@
+// Append-only command journal. Each sequenced command is one frame:
+//   len u32 | fnv1a32(payload) u32 | payload
+//   payload: seq u64 | tag u8 | fields (little-endian)
+// A torn frame at the tail (crash mid-write) ends the log; a checksum mismatch, or a length above
+// `MAX_FRAME_LEN` (no command comes close, so only corruption produces one), is an error.
+// A compacted journal starts with a snapshot reference frame (next_seq | TAG_SNAPSHOT_REF): the book
+// state before next_seq lives in that snapshot, and only the commands after it are kept.
+use match_engine::{AccountId, ClientOrderId, Command, Emission, OrderId, OrderRequest, OrderType, ReferenceValue, SessionState, Side, TimeInForce};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+pub mod uring;
+
+const TAG_LIMIT: u8 = 1;
+const TAG_MARKET: u8 = 2;
+const TAG_CANCEL: u8 = 3;
+const TAG_SUBMIT: u8 = 4;
+const TAG_SESSION: u8 = 5;
+const TAG_REPRICE: u8 = 6;
+const TAG_REFERENCE: u8 = 7;
+const TAG_END_OF_DAY: u8 = 8;
+const TAG_AMEND: u8 = 9;
+const TAG_CANCEL_CLIENT: u8 = 10;
+const TAG_REDUCE: u8 = 11;
+const TAG_RESUME: u8 = 12;
+const TAG_SNAPSHOT_REF: u8 = 0x7F;
+const FRAME_HEADER_LEN: usize = 8;
+pub const MAX_FRAME_LEN: usize = 4096;
+
+fn invalid(msg: &str) -> io::Error { io::Error::new(io::ErrorKind::InvalidData, msg.to_string()) }
+
+pub(crate) fn fnv1a32(bytes: &[u8]) -> u32 {
+    let mut h: u32 = 0x811c_9dc5;
+    for &b in bytes { h ^= b as u32; h = h.wrapping_mul(0x0100_0193); }
+    h
+}
+
+fn side_byte(side: Side) -> u8 { match side { Side::Buy => 0, Side::Sell => 1 } }
+
+fn tif_of(b: u8) -> io::Result<TimeInForce> { TimeInForce::from_code(b as u64).ok_or_else(|| invalid("bad time in force")) }
+
+fn emission_bits(e: Emission) -> u8 { match e { Emission::Live => 0, Emission::Suppressed => 1, Emission::Replay => 2 } }
+
+fn emission_of(bits: u8) -> io::Result<Emission> {
+    match bits { 0 => Ok(Emission::Live), 1 => Ok(Emission::Suppressed), 2 => Ok(Emission::Replay), _ => Err(invalid("bad emission")) }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalEntry {
+    Command(Command),
+    // State for every seq < next_seq is in the snapshot taken at next_seq
+    SnapshotRef { next_seq: u64 },
+}
+
+fn finish_frame(out: &mut [u8], start: usize) {
+    let payload_len = (out.len() - start - FRAME_HEADER_LEN) as u32;
+    let checksum = fnv1a32(&out[start + FRAME_HEADER_LEN..]);
+    out[start..start + 4].copy_from_slice(&payload_len.to_le_bytes());
+    out[start + 4..start + 8].copy_from_slice(&checksum.to_le_bytes());
+}
+
+pub fn encode_snapshot_ref(next_seq: u64, out: &mut Vec<u8>) {
+    let start = out.len();
+    out.extend_from_slice(&[0u8; FRAME_HEADER_LEN]);
+    out.extend_from_slice(&next_seq.to_le_bytes());
+    out.push(TAG_SNAPSHOT_REF);
+    finish_frame(out, start);
+}
+
+// Append one framed command to `out`
+pub fn encode_command(cmd: &Command, out: &mut Vec<u8>) {
+    let start = out.len();
+    out.extend_from_slice(&[0u8; FRAME_HEADER_LEN]);
+    match *cmd {
+        // seq | tag | side | price | qty [| tif [| account]], trailing fields absent for Gtc and account 0 so older journals read unchanged
+        Command::Limit { seq, side, price, qty, tif, account } => {
+            out.extend_from_slice(&seq.to_le_bytes());
+            out.extend_from_slice(&[TAG_LIMIT, side_byte(side)]);
+            out.extend_from_slice(&price.to_le_bytes());
+            out.extend_from_slice(&qty.to_le_bytes());
+            if tif != TimeInForce::Gtc || account.0 != 0 { out.push(tif.code()); }
+            if account.0 != 0 { out.extend_from_slice(&account.0.to_le_bytes()); }
+        }
+        // seq | tag | side | qty [| account, absent for account 0]
+        Command::Market { seq, side, qty, account } => {
+            out.extend_from_slice(&seq.to_le_bytes());
+            out.extend_from_slice(&[TAG_MARKET, side_byte(side)]);
+            out.extend_from_slice(&qty.to_le_bytes());
+            if account.0 != 0 { out.extend_from_slice(&account.0.to_le_bytes()); }
+        }
+        // seq | tag | id [| account, present when ownership is checked]
+        Command::Cancel { seq, id, account } => {
+            out.extend_from_slice(&seq.to_le_bytes());
+            out.push(TAG_CANCEL);
+            out.extend_from_slice(&id.0.to_le_bytes());
+            if let Some(a) = account { out.extend_from_slice(&a.0.to_le_bytes()); }
+        }
+        // seq | tag | account | client id
+        Command::CancelClient { seq, account, client_id } => {
+            out.extend_from_slice(&seq.to_le_bytes());
+            out.push(TAG_CANCEL_CLIENT);
+            out.extend_from_slice(&account.0.to_le_bytes());
+            out.extend_from_slice(&client_id.0.to_le_bytes());
+        }
+        // seq | tag | side | order_type (bit 7 client id follows) | tif | flags (bit 0 reduce-only, bit 1 short sell, bit 2 post-only, bit 3 top level only, bits 4-5 emission 0 live / 1 suppressed / 2 replay, bit 6 stop price follows, bit 7 display qty follows) | price | qty | account | correlation [| stop price] [| display qty] [| client id]
+        Command::Submit { seq, req } => {
+            out.extend_from_slice(&seq.to_le_bytes());
+            let order_type = match req.order_type { OrderType::Limit => 0, OrderType::Market => 1 } | (req.client_id.is_some() as u8) << 7;
+            out.extend_from_slice(&[TAG_SUBMIT, side_byte(req.side), order_type, req.tif.code(), req.reduce_only as u8 | (req.short_sell as u8) << 1 | (req.post_only as u8) << 2 | (req.top_level_only as u8) << 3 | emission_bits(req.emission) << 4 | (req.stop_price.is_some() as u8) << 6 | (req.display_qty.is_some() as u8) << 7]);
+            out.extend_from_slice(&req.price.to_le_bytes());
+            out.extend_from_slice(&req.qty.to_le_bytes());
+            out.extend_from_slice(&req.account.0.to_le_bytes());
+            out.extend_from_slice(&req.correlation.to_le_bytes());
+            for v in [req.stop_price, req.display_qty, req.client_id.map(|c| c.0)].into_iter().flatten() { out.extend_from_slice(&v.to_le_bytes()); }
+        }
+        // seq | tag | id | new_price
+        Command::Reprice { seq, id, new_price } => {
+            out.extend_from_slice(&seq.to_le_bytes());
+            out.push(TAG_REPRICE);
+            out.extend_from_slice(&id.0.to_le_bytes());
+            out.extend_from_slice(&new_price.to_le_bytes());
+        }
+        // seq | tag | id | new_price | new_qty
+        Command::Amend { seq, id, new_price, new_qty } => {
+            out.extend_from_slice(&seq.to_le_bytes());
+            out.push(TAG_AMEND);
+            out.extend_from_slice(&id.0.to_le_bytes());
+            out.extend_from_slice(&new_price.to_le_bytes());
+            out.extend_from_slice(&new_qty.to_le_bytes());
+        }
+        // seq | tag | id | qty_delta
+        Command::Reduce { seq, id, qty_delta } => {
+            out.extend_from_slice(&seq.to_le_bytes());
+            out.push(TAG_REDUCE);
+            out.extend_from_slice(&id.0.to_le_bytes());
+            out.extend_from_slice(&qty_delta.to_le_bytes());
+        }
+        // seq | tag
+        Command::EndOfDay { seq } => {
+            out.extend_from_slice(&seq.to_le_bytes());
+            out.push(TAG_END_OF_DAY);
+        }
+        // seq | tag
+        Command::Resume { seq } => {
+            out.extend_from_slice(&seq.to_le_bytes());
+            out.push(TAG_RESUME);
+        }
+        // seq | tag | kind (0 index price, 1 settlement price, 2 funding rate) | value (funding rate as i64)
+        Command::Reference { seq, value } => {
+            out.extend_from_slice(&seq.to_le_bytes());
+            let (kind, v) = match value { ReferenceValue::IndexPrice(p) => (0, p), ReferenceValue::SettlementPrice(p) => (1, p), ReferenceValue::FundingRate(r) => (2, r as u64) };
+            out.extend_from_slice(&[TAG_REFERENCE, kind]);
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        // seq | tag | state (0 pre-open, 1 open, 2 pre-close, 3 closed, 4 halted)
+        Command::Session { seq, state } => {
+            out.extend_from_slice(&seq.to_le_bytes());
+            out.extend_from_slice(&[TAG_SESSION, state.code()]);
+        }
+    }
+    finish_frame(out, start);
+}
+
+// Decode one frame payload (without the frame header)
+pub fn decode_entry(payload: &[u8]) -> io::Result<JournalEntry> {
+    if payload.len() == 9 && payload[8] == TAG_SNAPSHOT_REF {
+        return Ok(JournalEntry::SnapshotRef { next_seq: u64::from_le_bytes(payload[..8].try_into().unwrap()) });
+    }
+    decode_command(payload).map(JournalEntry::Command)
+}
+
+pub fn decode_command(payload: &[u8]) -> io::Result<Command> {
+    let u64_at = |off: usize| payload.get(off..off + 8).map(|b| u64::from_le_bytes(b.try_into().unwrap())).ok_or_else(|| invalid("short journal payload"));
+    let side_at = |off: usize| match payload.get(off) { Some(0) => Ok(Side::Buy), Some(1) => Ok(Side::Sell), _ => Err(invalid("bad side")) };
+    let seq = u64_at(0)?;
+    match payload.get(8) {
+        Some(&TAG_LIMIT) => {
+            let tif = match payload.get(26) { None => TimeInForce::Gtc, Some(&b) => tif_of(b)? };
+            Ok(Command::Limit { seq, side: side_at(9)?, price: u64_at(10)?, qty: u64_at(18)?, tif, account: AccountId(u64_at(27).unwrap_or(0)) })
+        }
+        Some(&TAG_MARKET) => Ok(Command::Market { seq, side: side_at(9)?, qty: u64_at(10)?, account: AccountId(u64_at(18).unwrap_or(0)) }),
+        Some(&TAG_CANCEL) => Ok(Command::Cancel { seq, id: OrderId(u64_at(9)?), account: u64_at(17).ok().map(AccountId) }),
+        Some(&TAG_CANCEL_CLIENT) => Ok(Command::CancelClient { seq, account: AccountId(u64_at(9)?), client_id: ClientOrderId(u64_at(17)?) }),
+        Some(&TAG_REPRICE) => Ok(Command::Reprice { seq, id: OrderId(u64_at(9)?), new_price: u64_at(17)? }),
+        Some(&TAG_AMEND) => Ok(Command::Amend { seq, id: OrderId(u64_at(9)?), new_price: u64_at(17)?, new_qty: u64_at(25)? }),
+        Some(&TAG_REDUCE) => Ok(Command::Reduce { seq, id: OrderId(u64_at(9)?), qty_delta: u64_at(17)? }),
+        Some(&TAG_END_OF_DAY) => Ok(Command::EndOfDay { seq }),
+        Some(&TAG_RESUME) => Ok(Command::Resume { seq }),
+        Some(&TAG_REFERENCE) => {
+            let value = match (payload.get(9), u64_at(10)?) {
+                (Some(0), p) => ReferenceValue::IndexPrice(p),
+                (Some(1), p) => ReferenceValue::SettlementPrice(p),
+                (Some(2), r) => ReferenceValue::FundingRate(r as i64),
+                _ => return Err(invalid("bad reference kind")),
+            };
+            Ok(Command::Reference { seq, value })
+        }
+        Some(&TAG_SUBMIT) => {
+            let order_type = match payload.get(10).map(|b| b & 0x7F) { Some(0) => OrderType::Limit, Some(1) => OrderType::Market, _ => return Err(invalid("bad order type")) };
+            let tif = tif_of(*payload.get(11).ok_or_else(|| invalid("short journal payload"))?)?;
+            let flags = *payload.get(12).ok_or_else(|| invalid("short journal payload"))?;
+            let account = AccountId(u64_at(29)?);
+            let stop_price = if flags & 0x40 != 0 { Some(u64_at(45)?) } else { None };
+            let display_qty = if flags & 0x80 != 0 { Some(u64_at(if stop_price.is_some() { 53 } else { 45 })?) } else { None };
+            let client_at = 45 + 8 * (stop_price.is_some() as usize + display_qty.is_some() as usize);
+            let client_id = if payload[10] & 0x80 != 0 { Some(ClientOrderId(u64_at(client_at)?)) } else { None };
+            Ok(Command::Submit { seq, req: OrderRequest { side: side_at(9)?, order_type, price: u64_at(13)?, qty: u64_at(21)?, tif, account, reduce_only: flags & 1 != 0, short_sell: flags & 2 != 0, correlation: u64_at(37)?, post_only: flags & 4 != 0, top_level_only: flags & 8 != 0, emission: emission_of((flags >> 4) & 3)?, stop_price, display_qty, client_id } })
+        }
+        Some(&TAG_SESSION) => {
+            let state = payload.get(9).and_then(|&b| SessionState::from_code(b as u64)).ok_or_else(|| invalid("bad session state"))?;
+            Ok(Command::Session { seq, state })
+        }
+        _ => Err(invalid("unknown journal record tag")),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsyncPolicy {
+    // Leave durability to the OS page cache
+    Never,
+    // fsync after every appended batch (strongest, slowest)
+    EveryBatch,
+    // fsync once every N appended batches
+    EveryNBatches(u32),
+}
+
+impl FsyncPolicy {
+    // Whether to fsync after `batches` appends since the last sync
+    pub fn due(self, batches: u32) -> bool {
+        match self {
+            FsyncPolicy::Never => false,
+            FsyncPolicy::EveryBatch => true,
+            FsyncPolicy::EveryNBatches(n) => batches >= n.max(1),
+        }
+    }
+}
+
+// Destination for sequenced commands. `append` is called once per batch before it is applied;
+// `sync` forces everything appended so far to stable storage; `compact` drops everything before
+// `next_seq` once a snapshot taken at `next_seq` is durable. Call all three from the owning thread.
+pub trait JournalWriter: Send {
+    fn append(&mut self, cmds: &[Command]) -> io::Result<()>;
+    fn sync(&mut self) -> io::Result<()>;
+    fn compact(&mut self, next_seq: u64) -> io::Result<CompactStats>;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompactStats {
+    pub kept: usize,
+    pub dropped: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+// Rewrite `path` as [SnapshotRef(next_seq)] + commands with seq >= next_seq, atomically (temp file + rename).
+// Resting orders survive through the referenced snapshot, so the log stays bounded by the snapshot interval.
+pub fn compact_journal(path: &Path, next_seq: u64) -> io::Result<CompactStats> {
+    let bytes_before = fs::metadata(path)?.len();
+    let mut stats = CompactStats { bytes_before, ..Default::default() };
+    let mut out = Vec::new();
+    encode_snapshot_ref(next_seq, &mut out);
+    for entry in JournalReader::new(BufReader::new(File::open(path)?)) {
+        match entry? {
+            JournalEntry::Command(c) if c.seq() >= next_seq => { encode_command(&c, &mut out); stats.kept += 1; }
+            JournalEntry::Command(_) => stats.dropped += 1,
+            JournalEntry::SnapshotRef { .. } => {}
+        }
+    }
+    let tmp = path.with_extension("compact");
+    {
+        let mut f = File::create(&tmp)?;
+        f.write_all(&out)?;
+        f.sync_all()?;
+    }
+    fs::rename(&tmp, path)?;
+    stats.bytes_after = out.len() as u64;
+    Ok(stats)
+}
+
+// Blocking writer: buffered file appends, fsync according to the policy
+pub struct FileJournal {
+    path: PathBuf,
+    out: BufWriter<File>,
+    policy: FsyncPolicy,
+    batches_since_sync: u32,
+    scratch: Vec<u8>,
+}
+
+impl FileJournal {
+    pub fn open(path: &Path, policy: FsyncPolicy) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { path: path.to_path_buf(), out: BufWriter::new(file), policy, batches_since_sync: 0, scratch: Vec::new() })
+    }
+}
+
+impl JournalWriter for FileJournal {
+    fn append(&mut self, cmds: &[Command]) -> io::Result<()> {
+        self.scratch.clear();
+        for c in cmds { encode_command(c, &mut self.scratch); }
+        self.out.write_all(&self.scratch)?;
+        self.batches_since_sync += 1;
+        if self.policy.due(self.batches_since_sync) { self.sync() } else { Ok(()) }
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.out.flush()?;
+        self.out.get_ref().sync_data()?;
+        self.batches_since_sync = 0;
+        Ok(())
+    }
+
+    fn compact(&mut self, next_seq: u64) -> io::Result<CompactStats> {
+        self.sync()?;
+        let stats = compact_journal(&self.path, next_seq)?;
+        self.out = BufWriter::new(OpenOptions::new().append(true).open(&self.path)?);
+        Ok(stats)
+    }
+}
+
+// Write-ahead journaling for `MultiIngestor` workers (`Attachments::journal`): one `FileJournal`
+// per symbol under `dir`, appended with each sequenced batch before the batch is applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalConfig {
+    pub dir: PathBuf,
+    pub fsync: FsyncPolicy,
+}
+
+impl JournalConfig {
+    pub fn new(dir: impl Into<PathBuf>, fsync: FsyncPolicy) -> Self { Self { dir: dir.into(), fsync } }
+
+    // `<dir>/<symbol>.journal`, with the symbol escaped like `MmapSnapshotStore::path_for`
+    pub fn path_for(&self, symbol: &str) -> PathBuf { self.dir.join(crate::snapshot::symbol_file_name(symbol, "journal")) }
+
+    // Symbols with a journal in `dir`
+    pub fn symbols(&self) -> io::Result<Vec<String>> { crate::snapshot::symbols_in(&self.dir, "journal") }
+
+    pub fn open(&self, symbol: &str) -> io::Result<FileJournal> {
+        fs::create_dir_all(&self.dir)?;
+        FileJournal::open(&self.path_for(symbol), self.fsync)
+    }
+}
+
+// Iterates entries from a journal stream, stopping cleanly at a torn tail frame
+pub struct JournalReader<R: Read> {
+    inner: R,
+    payload: Vec<u8>,
+    read: u64,
+}
+
+impl<R: Read> JournalReader<R> {
+    pub fn new(inner: R) -> Self { Self { inner, payload: Vec::new(), read: 0 } }
+
+    // Bytes of the complete frames returned so far
+    pub fn bytes_read(&self) -> u64 { self.read }
+}
+
+// Fill `buf` completely; Ok(false) on EOF before or in the middle of it
+fn read_full<R: Read>(r: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match r.read(&mut buf[filled..]) {
+            Ok(0) => return Ok(false),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+impl<R: Read> Iterator for JournalReader<R> {
+    type Item = io::Result<JournalEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut hdr = [0u8; FRAME_HEADER_LEN];
+        match read_full(&mut self.inner, &mut hdr) {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(e) => return Some(Err(e)),
+        }
+        let len = u32::from_le_bytes(hdr[0..4].try_into().unwrap()) as usize;
+        let checksum = u32::from_le_bytes(hdr[4..8].try_into().unwrap());
+        if len > MAX_FRAME_LEN { return Some(Err(invalid("journal frame too long"))); }
+        self.payload.resize(len, 0);
+        match read_full(&mut self.inner, &mut self.payload) {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(e) => return Some(Err(e)),
+        }
+        if fnv1a32(&self.payload) != checksum { return Some(Err(invalid("journal checksum mismatch"))); }
+        self.read += (FRAME_HEADER_LEN + len) as u64;
+        Some(decode_entry(&self.payload))
+    }
+}
+
+// Commands only; snapshot references are skipped
+pub fn read_journal(path: &Path) -> io::Result<Vec<Command>> {
+    let mut cmds = Vec::new();
+    for entry in JournalReader::new(BufReader::new(File::open(path)?)) {
+        if let JournalEntry::Command(c) = entry? { cmds.push(c); }
+    }
+    Ok(cmds)
+}
+
+// Cut a torn frame (crash mid-write) off the end of the journal, so that appends after a restart are
+// not hidden behind it; returns the bytes dropped
+pub fn truncate_torn_tail(path: &Path) -> io::Result<u64> {
+    let mut reader = JournalReader::new(BufReader::new(File::open(path)?));
+    for entry in reader.by_ref() { entry?; }
+    let len = fs::metadata(path)?.len();
+    if reader.bytes_read() < len { OpenOptions::new().write(true).open(path)?.set_len(reader.bytes_read())?; }
+    Ok(len - reader.bytes_read())
+}
+
+// The snapshot a compacted journal builds on (None if never compacted)
+pub fn journal_snapshot_ref(path: &Path) -> io::Result<Option<u64>> {
+    match JournalReader::new(BufReader::new(File::open(path)?)).next() {
+        Some(Ok(JournalEntry::SnapshotRef { next_seq })) => Ok(Some(next_seq)),
+        Some(Err(e)) => Err(e),
+        _ => Ok(None),
+    }
+}
@@ -0,0 +1,163 @@
+// io_uring journal writer: `append` encodes the batch into a pooled buffer, queues a positional
+// write and returns without waiting, so the matching thread only blocks when `queue_depth`
+// operations are already in flight. Fsyncs are queued behind earlier writes (IO_DRAIN) per the
+// FsyncPolicy; `sync` waits for everything queued so far.
+use super::{compact_journal, encode_command, CompactStats, FsyncPolicy, JournalWriter};
+use io_uring::{opcode, squeue, types, IoUring};
+use match_engine::Command;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+const FSYNC_TOKEN: u64 = u64::MAX;
+
+struct InFlight {
+    buf: Vec<u8>,
+    off: u64,
+    written: usize,
+}
+
+pub struct UringJournal {
+    path: PathBuf,
+    file: File,
+    ring: IoUring,
+    queue_depth: usize,
+    offset: u64,
+    slots: Vec<Option<InFlight>>, // user_data -> in-flight write
+    free_slots: Vec<usize>,
+    pool: Vec<Vec<u8>>,
+    pending: usize,
+    policy: FsyncPolicy,
+    batches_since_sync: u32,
+    error: Option<io::Error>,
+    completions: Vec<(u64, i32)>,
+}
+
+impl UringJournal {
+    // `queue_depth` bounds in-flight operations (writes plus fsyncs); at least 2
+    pub fn open(path: &Path, queue_depth: u32, policy: FsyncPolicy) -> io::Result<Self> {
+        let queue_depth = queue_depth.max(2);
+        let file = OpenOptions::new().create(true).write(true).truncate(false).open(path)?;
+        let offset = file.metadata()?.len();
+        let ring = IoUring::new(queue_depth)?;
+        Ok(Self {
+            path: path.to_path_buf(), file, ring, queue_depth: queue_depth as usize, offset,
+            slots: Vec::new(), free_slots: Vec::new(), pool: Vec::new(), pending: 0,
+            policy, batches_since_sync: 0, error: None, completions: Vec::new(),
+        })
+    }
+
+    pub fn in_flight(&self) -> usize { self.pending }
+
+    fn push(&mut self, entry: &squeue::Entry) -> io::Result<()> {
+        // SAFETY: buffers referenced by queued writes stay owned by `slots` until their completion is reaped
+        while unsafe { self.ring.submission().push(entry) }.is_err() {
+            self.ring.submit()?;
+        }
+        Ok(())
+    }
+
+    fn push_write(&mut self, slot: usize) -> io::Result<()> {
+        let w = self.slots[slot].as_ref().expect("in-flight slot");
+        let rest = &w.buf[w.written..];
+        let entry = opcode::Write::new(types::Fd(self.file.as_raw_fd()), rest.as_ptr(), rest.len() as u32)
+            .offset(w.off + w.written as u64)
+            .build()
+            .user_data(slot as u64);
+        self.push(&entry)
+    }
+
+    fn push_fsync(&mut self) -> io::Result<()> {
+        let entry = opcode::Fsync::new(types::Fd(self.file.as_raw_fd()))
+            .flags(types::FsyncFlags::DATASYNC)
+            .build()
+            .flags(squeue::Flags::IO_DRAIN)
+            .user_data(FSYNC_TOKEN);
+        self.push(&entry)?;
+        self.pending += 1;
+        self.batches_since_sync = 0;
+        Ok(())
+    }
+
+    // Process available completions: recycle finished buffers, resubmit short writes, record errors
+    fn reap(&mut self) -> io::Result<()> {
+        self.completions.clear();
+        self.completions.extend(self.ring.completion().map(|c| (c.user_data(), c.result())));
+        for i in 0..self.completions.len() {
+            let (token, res) = self.completions[i];
+            if res < 0 && self.error.is_none() { self.error = Some(io::Error::from_raw_os_error(-res)); }
+            if token == FSYNC_TOKEN { self.pending -= 1; continue; }
+            let slot = token as usize;
+            let w = self.slots[slot].as_mut().expect("in-flight slot");
+            if res > 0 { w.written += res as usize; }
+            if res == 0 && self.error.is_none() { self.error = Some(io::ErrorKind::WriteZero.into()); }
+            if res > 0 && w.written < w.buf.len() {
+                self.push_write(slot)?;
+                self.ring.submit()?;
+                continue;
+            }
+            let mut done = self.slots[slot].take().unwrap();
+            done.buf.clear();
+            self.pool.push(done.buf);
+            self.free_slots.push(slot);
+            self.pending -= 1;
+        }
+        Ok(())
+    }
+
+    fn wait_one(&mut self) -> io::Result<()> {
+        self.ring.submit_and_wait(1)?;
+        self.reap()
+    }
+
+    fn take_error(&mut self) -> io::Result<()> {
+        match self.error.take() { Some(e) => Err(e), None => Ok(()) }
+    }
+}
+
+impl JournalWriter for UringJournal {
+    fn append(&mut self, cmds: &[Command]) -> io::Result<()> {
+        self.take_error()?;
+        if cmds.is_empty() { return Ok(()); }
+        let mut buf = self.pool.pop().unwrap_or_default();
+        for c in cmds { encode_command(c, &mut buf); }
+        // Keep room for this write plus a possible fsync
+        while self.pending + 2 > self.queue_depth { self.wait_one()?; }
+        let off = self.offset;
+        self.offset += buf.len() as u64;
+        let slot = self.free_slots.pop().unwrap_or_else(|| { self.slots.push(None); self.slots.len() - 1 });
+        self.slots[slot] = Some(InFlight { buf, off, written: 0 });
+        self.push_write(slot)?;
+        self.pending += 1;
+        self.batches_since_sync += 1;
+        if self.policy.due(self.batches_since_sync) { self.push_fsync()?; }
+        self.ring.submit()?;
+        self.reap()?;
+        self.take_error()
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.take_error()?;
+        self.push_fsync()?;
+        while self.pending > 0 { self.wait_one()?; }
+        self.take_error()
+    }
+
+    fn compact(&mut self, next_seq: u64) -> io::Result<CompactStats> {
+        self.sync()?;
+        let stats = compact_journal(&self.path, next_seq)?;
+        self.file = OpenOptions::new().write(true).open(&self.path)?;
+        self.offset = stats.bytes_after;
+        Ok(stats)
+    }
+}
+
+impl Drop for UringJournal {
+    fn drop(&mut self) {
+        // The kernel may still read from our buffers; wait for every queued operation
+        while self.pending > 0 {
+            if self.wait_one().is_err() { break; }
+        }
+    }
+}
@@ -0,0 +1,36 @@
+// Per-batch pipeline stage timestamps (`Options::stage_timestamps`), carried on the batch's `Progress`
+// so end-to-end latency can be split into queueing, batching and matching when tuning `Options`
+// (`batch_size`, `coalesce_micros`). All stamps are micros since the Unix epoch.
+//   received     per command in seq order: the producer's stamp for commands that came through the
+//                sequencer (`RawCommand::Sourced`), otherwise the dequeue time (as in `audit`)
+//   dequeued     when the worker woke up with the batch's first command; commands coalesced after it
+//                and commands held back earlier (scheduled, speed bump) share it
+//   match_start  after coalescing, throttles and the journal append, just before matching
+//   match_end    after matching, before trades and progress are published
+// Multi-symbol workers only; `ShardedIngestor` progress carries no stages.
+use crate::RawCommand;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchStages {
+    pub received: Vec<u64>,
+    pub dequeued: u64,
+    pub match_start: u64,
+    pub match_end: u64,
+}
+
+impl BatchStages {
+    pub(crate) fn received_at(cmd: &RawCommand, dequeued: u64) -> u64 {
+        match *cmd { RawCommand::Sourced { received_at, .. } => received_at, _ => dequeued }
+    }
+
+    // Time the `i`th command of the batch spent queued before the worker took the batch
+    pub fn queueing_micros(&self, i: usize) -> Option<u64> { self.received.get(i).map(|&r| self.dequeued.saturating_sub(r)) }
+
+    // Coalescing and pre-match work (throttles, journal) for the whole batch
+    pub fn batching_micros(&self) -> u64 { self.match_start.saturating_sub(self.dequeued) }
+
+    pub fn matching_micros(&self) -> u64 { self.match_end.saturating_sub(self.match_start) }
+
+    // Receipt of the `i`th command to the end of matching
+    pub fn total_micros(&self, i: usize) -> Option<u64> { self.received.get(i).map(|&r| self.match_end.saturating_sub(r)) }
+}
@@ -1,15 +1,183 @@
 use crossbeam_channel as cb;
 use crossbeam_channel::{Receiver, Sender};
-use match_engine::{Command, OrderBook, Trade};
+use match_engine::{AccountId, ClientOrderId, Command, Depth, EngineError, EngineFault, EntryThrottle, FaultAction, EodReport, ErrorPolicy, FillReport, ImplicitCancel, MarketDataSnapshot, OrderBook, Repriced, StopActivation, SymbolConfig, TimeInForce, Trade};
 use std::collections::HashMap;
-use std::time::Duration;
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
+use schedule::{now_micros, Scheduler};
+use backpressure::WatermarkState;
+use telemetry::WorkerCounters;
+use triggers::TriggerRegistry;
+use std::time::{Duration, Instant};
+
+pub mod audit;
+pub mod backpressure;
+pub mod backtest;
+pub mod basket;
+pub mod calendar;
+pub mod capture;
+pub mod coalesce;
+pub mod delivery;
+#[cfg(feature = "parquet")]
+pub mod export;
+pub mod feed_codec;
+pub mod harness;
+pub mod instruments;
+pub mod journal;
+pub mod latency;
+pub mod metrics;
+pub mod mirror;
+#[cfg(feature = "mmap")]
+pub mod mmap_snapshot;
+pub mod publisher;
+#[cfg(feature = "mmap")]
+pub mod recovery;
+pub mod replay;
+pub mod replication;
+pub mod retransmit;
+pub mod reporting;
+pub mod schedule;
+pub mod sequencer;
+pub mod sharded;
+#[cfg(feature = "shm")]
+pub mod shm;
+pub mod snapshot;
+pub mod speed_bump;
+pub mod telemetry;
+pub mod throttle;
+pub mod timetravel;
+pub mod triggers;
+pub use replay::{replay, replay_journal, ReplayError, Replayer};
+pub use replication::{Divergence, ReplicaStatus, ReplicationMsg, Standby};
+pub use retransmit::{FeedFanout, ResendError, Retransmitter};
+pub use sequencer::{spawn_sequencer, MergeQueue, ProducerHandle, SequenceError};
+pub use sharded::{HashRing, Migration, ShardedIngestor};
+pub use audit::{AuditRecord, AuditSink, MemoryAuditLog, Outcome};
+pub use backpressure::{WatermarkEvent, WatermarkLevel};
+pub use backtest::{Backtest, BacktestConfig, Context, Fill, Strategy};
+pub use basket::{BasketError, BasketLeg};
+pub use capture::{CaptureReader, CaptureWriter, CapturedBatch};
+pub use coalesce::{AdaptiveCoalescing, CoalesceSettings, Coalescer};
+pub use delivery::{Delivery, DeliveryError, DeliveryLog};
+pub use feed_codec::{CodecError, FeedDecoder, FeedEncoder};
+pub use harness::{Harness, HarnessError, Script, ScriptStep};
+pub use instruments::{InstrumentId, InstrumentKey, InstrumentRegistry, InstrumentRoutes, OptionRight};
+pub use journal::{FsyncPolicy, JournalConfig, JournalWriter};
+pub use latency::BatchStages;
+pub use metrics::{MetricsRegistry, MetricsSink};
+pub use mirror::{DepthMirror, MirroredDepth};
+pub use publisher::{DepthPublisher, FeedBook, FeedError, FeedMsg, PublisherConfig};
+pub use snapshot::{MemorySnapshotStore, SnapshotStore, SymbolSnapshot};
+pub use speed_bump::SpeedBump;
+pub use telemetry::{Telemetry, WorkerStats};
+pub use throttle::{ThrottleEvent, ThrottleOwner};
+pub use timetravel::{OrderChange, Step, TimeTravel, TravelError};
+pub use triggers::{PriceCondition, TriggerError, TriggerFired};
 
 // External producers send unsequenced commands; ingestor assigns seq to guarantee global order
 #[derive(Debug, Clone, Copy)]
 pub enum RawCommand {
     Limit { side: match_engine::Side, price: u64, qty: u64 },
     Market { side: match_engine::Side, qty: u64 },
-    Cancel { id: match_engine::OrderId },
+    // With an account, only an order of that account is cancelled
+    Cancel { id: match_engine::OrderId, account: Option<AccountId> },
+    // Cancel the order the account entered under its own client order id
+    CancelClient { account: AccountId, client_id: ClientOrderId },
+    Reprice { id: match_engine::OrderId, new_price: u64 },
+    Amend { id: match_engine::OrderId, new_price: u64, new_qty: u64 },
+    // Take `qty_delta` off a resting order, keeping its queue position
+    Reduce { id: match_engine::OrderId, qty_delta: u64 },
+    // Any order attributes (time in force, ...)
+    Submit(match_engine::OrderRequest),
+    Session(match_engine::SessionState),
+    // External reference value (index, settlement price, funding rate) for the symbol
+    Reference(match_engine::ReferenceValue),
+    // Close, settle and reset the session, then snapshot (see `match_engine::eod`)
+    EndOfDay,
+    // One slice of a sweep paused by the matching budget (see `match_engine::budget`); workers send
+    // it themselves while a book has continuations and no other flow
+    Resume,
+    // Held by the worker until `activate_at` (micros since the Unix epoch), then sequenced as `cmd`
+    At { activate_at: u64, cmd: ScheduledCommand },
+    // `cmd` with its provenance for the audit log (see `audit`): the sequencer producer that sent it
+    // and its receive stamp; sequenced like `cmd` itself
+    Sourced { producer: u32, received_at: u64, cmd: ScheduledCommand },
+}
+
+impl RawCommand {
+    // Tag with provenance; scheduled and already sourced commands are returned unchanged
+    pub fn sourced(self, producer: u32, received_at: u64) -> Self {
+        match self.scheduled() { Some(cmd) => RawCommand::Sourced { producer, received_at, cmd }, None => self }
+    }
+
+    fn scheduled(self) -> Option<ScheduledCommand> {
+        Some(match self {
+            RawCommand::Limit { side, price, qty } => ScheduledCommand::Limit { side, price, qty },
+            RawCommand::Market { side, qty } => ScheduledCommand::Market { side, qty },
+            RawCommand::Cancel { id, account } => ScheduledCommand::Cancel { id, account },
+            RawCommand::CancelClient { account, client_id } => ScheduledCommand::CancelClient { account, client_id },
+            RawCommand::Reprice { id, new_price } => ScheduledCommand::Reprice { id, new_price },
+            RawCommand::Amend { id, new_price, new_qty } => ScheduledCommand::Amend { id, new_price, new_qty },
+            RawCommand::Reduce { id, qty_delta } => ScheduledCommand::Reduce { id, qty_delta },
+            RawCommand::Submit(req) => ScheduledCommand::Submit(req),
+            RawCommand::Session(state) => ScheduledCommand::Session(state),
+            RawCommand::Reference(value) => ScheduledCommand::Reference(value),
+            RawCommand::EndOfDay => ScheduledCommand::EndOfDay,
+            RawCommand::At { .. } | RawCommand::Sourced { .. } | RawCommand::Resume => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ScheduledCommand {
+    Limit { side: match_engine::Side, price: u64, qty: u64 },
+    Market { side: match_engine::Side, qty: u64 },
+    Cancel { id: match_engine::OrderId, account: Option<AccountId> },
+    CancelClient { account: AccountId, client_id: ClientOrderId },
+    Reprice { id: match_engine::OrderId, new_price: u64 },
+    Amend { id: match_engine::OrderId, new_price: u64, new_qty: u64 },
+    Reduce { id: match_engine::OrderId, qty_delta: u64 },
+    Submit(match_engine::OrderRequest),
+    Session(match_engine::SessionState),
+    Reference(match_engine::ReferenceValue),
+    EndOfDay,
+}
+
+impl From<ScheduledCommand> for RawCommand {
+    fn from(c: ScheduledCommand) -> Self {
+        match c {
+            ScheduledCommand::Limit { side, price, qty } => RawCommand::Limit { side, price, qty },
+            ScheduledCommand::Market { side, qty } => RawCommand::Market { side, qty },
+            ScheduledCommand::Cancel { id, account } => RawCommand::Cancel { id, account },
+            ScheduledCommand::CancelClient { account, client_id } => RawCommand::CancelClient { account, client_id },
+            ScheduledCommand::Reprice { id, new_price } => RawCommand::Reprice { id, new_price },
+            ScheduledCommand::Amend { id, new_price, new_qty } => RawCommand::Amend { id, new_price, new_qty },
+            ScheduledCommand::Reduce { id, qty_delta } => RawCommand::Reduce { id, qty_delta },
+            ScheduledCommand::Submit(req) => RawCommand::Submit(req),
+            ScheduledCommand::Session(state) => RawCommand::Session(state),
+            ScheduledCommand::Reference(value) => RawCommand::Reference(value),
+            ScheduledCommand::EndOfDay => RawCommand::EndOfDay,
+        }
+    }
+}
+
+// Worker-side conversion once a command is sequenced; `At` never reaches here (see Scheduler::admit)
+fn sequence(rc: RawCommand, seq: u64) -> Command {
+    match rc {
+        RawCommand::Limit { side, price, qty } => Command::Limit { seq, side, price, qty, tif: TimeInForce::Gtc, account: AccountId(0) },
+        RawCommand::Market { side, qty } => Command::Market { seq, side, qty, account: AccountId(0) },
+        RawCommand::Cancel { id, account } => Command::Cancel { seq, id, account },
+        RawCommand::CancelClient { account, client_id } => Command::CancelClient { seq, account, client_id },
+        RawCommand::Reprice { id, new_price } => Command::Reprice { seq, id, new_price },
+        RawCommand::Amend { id, new_price, new_qty } => Command::Amend { seq, id, new_price, new_qty },
+        RawCommand::Reduce { id, qty_delta } => Command::Reduce { seq, id, qty_delta },
+        RawCommand::Submit(req) => Command::Submit { seq, req },
+        RawCommand::Session(state) => Command::Session { seq, state },
+        RawCommand::Reference(value) => Command::Reference { seq, value },
+        RawCommand::EndOfDay => Command::EndOfDay { seq },
+        RawCommand::Resume => Command::Resume { seq },
+        RawCommand::At { cmd, .. } | RawCommand::Sourced { cmd, .. } => sequence(cmd.into(), seq),
+    }
 }
 
 // Multi-symbol API
@@ -19,75 +187,275 @@ pub struct MultiRawCommand {
     pub cmd: RawCommand,
 }
 
+// One per applied batch, sent after the batch's trades (when emitted), so a consumer that has seen
+// the progress event for `last_seq` has seen every trade up to and including it. Downstream stages
+// can persist `last_seq` per symbol as a watermark and skip anything at or below it on restart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Progress {
+    pub symbol: String,
+    pub commands: usize, // commands in the batch
+    pub last_seq: u64,   // seq of the last command applied
+    pub trades: usize,   // trades the batch generated (also counted when trades are not emitted)
+    pub cancels: Vec<ImplicitCancel>, // orders the batch removed as a side effect (expiry, halt, reduce-only)
+    pub fill_reports: Vec<FillReport>, // one per taker command that traded, when the book has fill reports on
+    pub faults: Vec<EngineFault>, // internal errors the batch hit (see `ErrorPolicy`); `MultiIngestor` also sends them on `rx_fault`
+    pub stop_activations: Vec<StopActivation>, // held stops the batch's trades triggered
+    pub repriced: Vec<Repriced>, // post-only entries and amends moved behind the touch (see `match_engine::post_only`)
+    pub stages: Option<BatchStages>, // with opts.stage_timestamps (see `latency`)
+    pub coalescing: Option<CoalesceSettings>, // for the next batch, with opts.adaptive_coalescing (see `coalesce`)
+}
+
 pub struct MultiIngestor {
     pub tx_cmd: Sender<MultiRawCommand>,
     pub rx_trade: Receiver<(String, Trade)>,
-    pub rx_done: Receiver<usize>, // number of commands processed in a batch across workers
+    pub rx_progress: Receiver<Progress>, // one per applied batch, across workers
     pub routes: HashMap<String, Sender<RawCommand>>, // direct per-symbol senders
+    pub rx_watermark: Receiver<WatermarkEvent>, // input queue crossing opts.queue_*_watermark
+    pub rx_triggered: Receiver<TriggerFired>, // cross-instrument triggers that fired (see `triggers`)
+    pub rx_fault: Receiver<(String, EngineFault)>, // internal errors hit while applying commands (see `ErrorPolicy`)
+    pub rx_eod: Receiver<(String, EodReport)>, // settlement and final statistics of each end of day
+    pub rx_throttle: Receiver<ThrottleEvent>, // orders refused by an account or producer throttle (see `throttle`)
+    telemetry: Telemetry,
+    next_correlation: AtomicU64, // basket correlation ids, from 1
+    triggers: Arc<TriggerRegistry>,
+    queries: HashMap<String, Sender<WorkerQuery>>,
+}
+
+// Answered by the symbol's worker between batches (see `MultiIngestor::market_data_snapshot`, `book_snapshot`)
+enum WorkerQuery {
+    MarketData { levels: usize, trades: usize, reply: Sender<MarketDataSnapshot> },
+    Book { reply: Sender<SymbolSnapshot> },
 }
 
 impl MultiIngestor {
-    pub fn start_with_books(books: Vec<(String, OrderBook)>, batch_size: usize) -> Self {
-        Self::start_with_books_with_opts(books, batch_size, true)
+    // Orders off a symbol's tick or lot grid, or outside its size bounds, are rejected by its book;
+    // symbols without an entry in `configs` are unchecked. A config the book refuses fails the start
+    // with `EngineError::InvalidConfig` (see `start_with_attachments`)
+    pub fn start_with_books(books: Vec<(String, OrderBook)>, batch_size: usize, configs: HashMap<String, SymbolConfig>) -> Result<Self, EngineError> {
+        Self::start_with_attachments(books, Options { batch_size, ..Options::default() }, Attachments { symbol_configs: configs, ..Attachments::default() })
     }
 
     pub fn start_with_books_with_opts(books: Vec<(String, OrderBook)>, batch_size: usize, emit_trades: bool) -> Self {
-        let opts = Options { batch_size, emit_trades, coalesce_micros: 0 };
+        let opts = Options { batch_size, emit_trades, ..Options::default() };
         Self::start_with_books_with_config(books, opts)
     }
 
     pub fn start_with_books_with_config(books: Vec<(String, OrderBook)>, opts: Options) -> Self {
+        Self::spawn(books, opts, Attachments::default())
+    }
+
+    // Workers snapshot their book into `store` according to `opts.snapshot_every_*`
+    pub fn start_with_books_with_snapshots(books: Vec<(String, OrderBook)>, opts: Options, store: Arc<dyn SnapshotStore>) -> Self {
+        Self::spawn(books, opts, Attachments { snapshot_store: Some(store), ..Attachments::default() })
+    }
+
+    // Primary side of replication: every applied batch is streamed to `replica` (see `Standby`)
+    pub fn start_primary(books: Vec<(String, OrderBook)>, opts: Options, replica: Sender<ReplicationMsg>) -> Self {
+        Self::spawn(books, opts, Attachments { replica: Some(replica), ..Attachments::default() })
+    }
+
+    // Every symbol config is set on its book before any worker starts; the first one a book refuses is
+    // returned as `EngineError::InvalidConfig` and nothing is started
+    pub fn start_with_attachments(mut books: Vec<(String, OrderBook)>, opts: Options, attach: Attachments) -> Result<Self, EngineError> {
+        for (symbol, book) in &mut books {
+            let Some(&config) = attach.symbol_configs.get(symbol) else { continue };
+            book.set_symbol_config(Some(config)).map_err(|e| EngineError::InvalidConfig(format!("symbol config for {symbol}: {e}")))?;
+        }
+        Ok(Self::spawn(books, opts, attach))
+    }
+
+    // Start the workers and the router; symbol configs are already on the books
+    pub(crate) fn spawn(books: Vec<(String, OrderBook)>, opts: Options, attach: Attachments) -> Self {
+        let Attachments { snapshot_store: store, replica, start_seq, feed, audit, capture, delivery, speed_bumps, journal, mirrors, symbol_configs: _ } = attach;
         let (tx_cmd, rx_cmd) = cb::unbounded::<MultiRawCommand>();
         let (tx_trade_all, rx_trade) = cb::unbounded::<(String, Trade)>();
-        let (tx_done_all, rx_done) = cb::unbounded::<usize>();
+        let (tx_progress, rx_progress) = cb::unbounded::<Progress>();
+        let (tx_watermark, rx_watermark) = cb::unbounded::<WatermarkEvent>();
+        let (tx_triggered, rx_triggered) = cb::unbounded::<TriggerFired>();
+        let (tx_fault, rx_fault) = cb::unbounded::<(String, EngineFault)>();
+        let (tx_eod, rx_eod) = cb::unbounded::<(String, EodReport)>();
+        let (tx_throttle, rx_throttle) = cb::unbounded::<ThrottleEvent>();
+        let triggers = Arc::new(TriggerRegistry::default());
+
+        // Snapshots are captured on the worker thread and written by a background writer
+        let tx_snap = store.map(|store| {
+            let (tx_snap, rx_snap) = cb::unbounded::<SymbolSnapshot>();
+            std::thread::spawn(move || {
+                while let Ok(snap) = rx_snap.recv() { let _ = store.save(&snap); }
+            });
+            tx_snap
+        });
+        let snap_every_cmds = opts.snapshot_every_commands;
+        let snap_interval = (opts.snapshot_interval_millis > 0).then(|| Duration::from_millis(opts.snapshot_interval_millis));
 
         // Create per-symbol workers and a router
         let mut routes: HashMap<String, Sender<RawCommand>> = HashMap::new();
+        let mut observed = Vec::new();
+        let mut queries = HashMap::new();
         for (symbol, book) in books {
             let (tx_raw, rx_raw) = cb::unbounded::<RawCommand>();
             routes.insert(symbol.clone(), tx_raw.clone());
+            let (tx_query, mut rx_query) = cb::unbounded::<WorkerQuery>();
+            queries.insert(symbol.clone(), tx_query);
+            let counters = Arc::new(WorkerCounters::default());
+            observed.push((symbol.clone(), rx_raw.clone(), counters.clone()));
             let tx_trade_all = tx_trade_all.clone();
-            let tx_done_all = tx_done_all.clone();
+            let tx_progress = tx_progress.clone();
+            let tx_snap = tx_snap.clone();
+            let tx_repl = replica.clone();
+            let tx_watermark = tx_watermark.clone();
+            let tx_triggered = tx_triggered.clone();
+            let triggers = triggers.clone();
+            let audit = audit.clone();
+            let capture = capture.clone();
+            let journal = journal.clone();
+            let delivery = delivery.clone();
+            let tx_fault = tx_fault.clone();
+            let tx_eod = tx_eod.clone();
+            let tx_throttle = tx_throttle.clone();
+            let mut throttle = opts.producer_throttle.map(throttle::ProducerThrottle::new);
+            let mut feed = feed.as_ref().map(|(cfg, tx)| (cfg.publisher_for(&symbol), tx.clone()));
+            let mut seq: u64 = start_seq.get(&symbol).copied().unwrap_or(0);
+            let mut bump = speed_bumps.get(&symbol).copied().map(speed_bump::BumpState::new);
+            let mirror = mirrors.get(&symbol).cloned();
             std::thread::spawn(move || {
                 let mut book = book; // move in
+                if let Some(policy) = opts.error_policy { book.set_error_policy(policy); }
+                let stopped = |seq: u64, e: std::io::Error| { let _ = tx_fault.send((symbol.clone(), EngineFault { seq, error: format!("journal: {e}"), action: FaultAction::Stopped })); };
+                let mut journal = match journal.as_ref().map(|cfg| cfg.open(&symbol)).transpose() {
+                    Ok(journal) => journal,
+                    Err(e) => return stopped(seq, e),
+                };
                 let mut trades_buf: Vec<Trade> = Vec::with_capacity(opts.batch_size * 2);
                 let mut batch_raw: Vec<RawCommand> = Vec::with_capacity(opts.batch_size);
                 let mut batch: Vec<Command> = Vec::with_capacity(opts.batch_size);
-                let mut seq: u64 = 0;
-                loop {
+                let mut since_snap: u64 = 0;
+                let mut since_hash: u64 = 0;
+                let mut last_snap = Instant::now();
+                let mut sched = Scheduler::new();
+                let mut marks = WatermarkState::new(opts.queue_high_watermark, opts.queue_low_watermark);
+                let mut coalescer = opts.adaptive_coalescing.map(|cfg| Coalescer::new(cfg, opts.batch_size));
+                let mut check_marks = |depth: usize| {
+                    if let Some(level) = marks.observe(depth) {
+                        let _ = tx_watermark.send(WatermarkEvent { symbol: symbol.clone(), level, depth });
+                    }
+                };
+                let mut feed_out: Vec<FeedMsg> = Vec::new();
+                let mut publish = |feed: &mut Option<(DepthPublisher, Sender<FeedMsg>)>, book: Option<&OrderBook>| {
+                    let Some((publisher, tx)) = feed else { return };
+                    match book { Some(book) => publisher.update(book, now_micros(), &mut feed_out), None => publisher.tick(now_micros(), &mut feed_out) }
+                    for m in feed_out.drain(..) { let _ = tx.send(m); }
+                };
+                publish(&mut feed, Some(&book));
+                let mut mirror_scratch = (Depth::new(), Depth::new());
+                if let Some(m) = &mirror { m.publish_book(&book, seq, &mut mirror_scratch); }
+                let take_snapshot = |book: &OrderBook, seq: u64| {
+                    if let Some(tx) = &tx_snap {
+                        let _ = tx.send(SymbolSnapshot { symbol: symbol.clone(), next_seq: seq, book: book.snapshot() });
+                    }
+                };
+                'work: loop {
                     batch_raw.clear();
-                    match rx_raw.recv() { Ok(cmd) => batch_raw.push(cmd), Err(_) => break }
+                    // With a pending time-based snapshot or scheduled command, wake up at its deadline even if idle
+                    let snap_wait = match (&tx_snap, snap_interval) {
+                        (Some(_), Some(iv)) if since_snap > 0 => Some(iv.saturating_sub(last_snap.elapsed())),
+                        _ => None,
+                    };
+                    let sched_wait = sched.next_wait(now_micros());
+                    let feed_wait = feed.as_ref().map(|(p, _)| Duration::from_micros(p.next_snapshot_in(now_micros())));
+                    let resume_wait = (book.pending_continuations() > 0).then_some(Duration::ZERO);
+                    let wait = [snap_wait, sched_wait, feed_wait, resume_wait].into_iter().flatten().min();
+                    // Market data requests are served while waiting, so they see the book between batches
+                    let deadline = wait.map(cb::after).unwrap_or_else(cb::never);
+                    let waited = rx_raw.is_empty();
+                    let first = loop {
+                        cb::select! {
+                            recv(rx_raw) -> msg => match msg { Ok(cmd) => break Some(cmd), Err(_) => break 'work },
+                            recv(rx_query) -> query => match query {
+                                Ok(WorkerQuery::MarketData { levels, trades, reply }) => { let _ = reply.send(book.market_data_snapshot(seq, levels, trades)); }
+                                Ok(WorkerQuery::Book { reply }) => { let _ = reply.send(SymbolSnapshot { symbol: symbol.clone(), next_seq: seq, book: book.snapshot() }); }
+                                Err(_) => rx_query = cb::never(),
+                            },
+                            recv(deadline) -> _ => break None,
+                        }
+                    };
+                    let dequeued = if opts.stage_timestamps { now_micros() } else { 0 };
+                    // Receipt times of the batch's first and latest command, for the adaptive window
+                    let first_at = if coalescer.is_some() { now_micros() } else { 0 };
+                    let mut last_at = first_at;
+                    let snap_due = snap_wait.is_some_and(|_| snap_interval.is_some_and(|iv| last_snap.elapsed() >= iv));
+                    publish(&mut feed, None);
+                    match first {
+                        Some(cmd) => batch_raw.push(cmd),
+                        None if snap_due => {
+                            take_snapshot(&book, seq);
+                            since_snap = 0;
+                            last_snap = Instant::now();
+                            continue;
+                        }
+                        None => {}
+                    }
                     // Coalesce additional messages to fill batch or until timeout
-                    if opts.coalesce_micros > 0 {
-                        let timeout = Duration::from_micros(opts.coalesce_micros as u64);
-                        while batch_raw.len() < opts.batch_size {
+                    let CoalesceSettings { window_micros, batch_size } = coalescer.as_ref().map_or(CoalesceSettings { window_micros: opts.coalesce_micros, batch_size: opts.batch_size }, Coalescer::settings);
+                    if window_micros > 0 {
+                        let timeout = Duration::from_micros(window_micros as u64);
+                        while batch_raw.len() < batch_size {
                             match rx_raw.recv_timeout(timeout) {
-                                Ok(cmd) => batch_raw.push(cmd),
+                                Ok(cmd) => {
+                                    batch_raw.push(cmd);
+                                    if coalescer.is_some() { last_at = now_micros(); }
+                                }
                                 Err(cb::RecvTimeoutError::Timeout) => break,
                                 Err(cb::RecvTimeoutError::Disconnected) => break,
                             }
                         }
                     } else {
-                        while batch_raw.len() < opts.batch_size {
+                        while batch_raw.len() < batch_size {
                             match rx_raw.try_recv() {
                                 Ok(cmd) => batch_raw.push(cmd),
                                 Err(cb::TryRecvError::Empty) => break,
                                 Err(cb::TryRecvError::Disconnected) => break,
                             }
                         }
+                        if coalescer.is_some() && batch_raw.len() > 1 { last_at = now_micros(); }
                     }
+                    if let Some(c) = &mut coalescer { c.observe(batch_raw.len(), rx_raw.len(), waited, first_at, last_at); }
+                    check_marks(rx_raw.len());
+                    if let Some(throttle) = &mut throttle { throttle.filter(&symbol, &mut batch_raw, now_micros(), &tx_throttle); }
+                    if let Some(bump) = &mut bump { bump.hold(&book, &mut batch_raw, now_micros(), &mut sched); }
+                    sched.admit(&mut batch_raw, now_micros());
+                    if batch_raw.is_empty() && book.pending_continuations() > 0 { batch_raw.push(RawCommand::Resume); }
+                    if batch_raw.is_empty() { continue; }
                     batch.clear();
                     for rc in batch_raw.iter().copied() {
-                        let s = seq; seq = seq.wrapping_add(1);
-                        batch.push(match rc {
-                            RawCommand::Limit { side, price, qty } => Command::Limit { seq: s, side, price, qty },
-                            RawCommand::Market { side, qty } => Command::Market { seq: s, side, qty },
-                            RawCommand::Cancel { id } => Command::Cancel { seq: s, id },
-                        });
+                        batch.push(sequence(rc, seq));
+                        seq = seq.wrapping_add(1);
+                    }
+                    let mut stages = opts.stage_timestamps.then(|| BatchStages { received: batch_raw.iter().map(|rc| BatchStages::received_at(rc, dequeued)).collect(), dequeued, ..BatchStages::default() });
+                    // Write-ahead: a batch is applied only once it is in the journal; if it cannot be
+                    // written the worker stops with the book as of the batch before
+                    if let Some(Err(e)) = journal.as_mut().map(|j| j.append(&batch)) {
+                        seq = batch[0].seq();
+                        stopped(seq, e);
+                        break 'work;
                     }
                     let start_len = trades_buf.len();
-                    let _ = book.process_commands_batch_checked_into(&mut batch, &mut trades_buf);
+                    if let Some(s) = &mut stages { s.match_start = now_micros(); }
+                    match &audit {
+                        Some(sink) => audit::apply_audited(&mut book, &symbol, &batch_raw, &batch, now_micros(), &mut trades_buf, sink.as_ref()),
+                        None => { let _ = book.process_commands_batch_presorted_unchecked(&batch, &mut trades_buf); }
+                    }
+                    if let Some(s) = &mut stages { s.match_end = now_micros(); }
                     let produced = trades_buf.len() - start_len;
+                    let faults = book.drain_faults();
+                    for fault in &faults { let _ = tx_fault.send((symbol.clone(), fault.clone())); }
+                    for hit in book.drain_throttle_hits() { let _ = tx_throttle.send(ThrottleEvent::from_hit(&symbol, hit)); }
+                    let cancels = book.drain_implicit_cancels();
+                    let eod = book.drain_eod_reports();
+                    if let Some(capture) = &capture { let _ = capture.lock().unwrap().record(&symbol, &batch, &trades_buf[start_len..], &cancels); }
+                    if let Some(log) = delivery.as_ref().filter(|_| produced > 0 || !cancels.is_empty()) {
+                        let _ = log.append(&symbol, seq.wrapping_sub(1), &trades_buf[start_len..], &cancels);
+                    }
                     if opts.emit_trades {
                         if produced > 0 {
                             // send tagged trades
@@ -99,9 +467,37 @@ impl MultiIngestor {
                         // just drop drained trades to avoid per-trade send overhead
                         trades_buf.truncate(start_len);
                     }
-                    // notify done by number of commands processed
-                    let _ = tx_done_all.send(batch.len());
+                    if let Some(tx) = &tx_repl {
+                        // The standby applies the same sequenced batch and gets the same result
+                        let _ = tx.send(ReplicationMsg::Batch { symbol: symbol.clone(), commands: batch.clone() });
+                        since_hash += batch.len() as u64;
+                        if opts.replication_hash_every_commands > 0 && since_hash >= opts.replication_hash_every_commands {
+                            let _ = tx.send(ReplicationMsg::Checkpoint { symbol: symbol.clone(), next_seq: seq, hash: book.state_hash() });
+                            since_hash = 0;
+                        }
+                    }
+                    if tx_snap.is_some() {
+                        since_snap += batch.len() as u64;
+                        let due_cmds = snap_every_cmds > 0 && since_snap >= snap_every_cmds;
+                        let due_time = snap_interval.is_some_and(|iv| last_snap.elapsed() >= iv);
+                        // End of day always snapshots the closed book
+                        if due_cmds || due_time || !eod.is_empty() {
+                            take_snapshot(&book, seq);
+                            since_snap = 0;
+                            last_snap = Instant::now();
+                        }
+                    }
+                    for report in eod { let _ = tx_eod.send((symbol.clone(), report)); }
+                    triggers.evaluate(&symbol, &book, &tx_triggered);
+                    publish(&mut feed, Some(&book));
+                    if let Some(m) = &mirror { m.publish_book(&book, seq, &mut mirror_scratch); }
+                    counters.record_batch(batch.len());
+                    check_marks(rx_raw.len());
+                    let _ = tx_progress.send(Progress { symbol: symbol.clone(), commands: batch.len(), last_seq: seq.wrapping_sub(1), trades: produced, cancels, fill_reports: book.drain_fill_reports(), faults, stop_activations: book.drain_stop_activations(), repriced: book.drain_repriced(), stages, coalescing: coalescer.as_ref().map(Coalescer::settings) });
                 }
+                if let Some(j) = &mut journal { let _ = j.sync(); }
+                // Final snapshot on shutdown so recovery doesn't replay the whole tail
+                if since_snap > 0 { take_snapshot(&book, seq); }
             });
         }
 
@@ -115,8 +511,33 @@ impl MultiIngestor {
             }
         });
 
-        Self { tx_cmd, rx_trade, rx_done, routes }
+        Self { tx_cmd, rx_trade, rx_progress, routes, rx_watermark, rx_triggered, rx_fault, rx_eod, rx_throttle, telemetry: Telemetry::new(observed), next_correlation: AtomicU64::new(1), triggers, queries }
     }
+
+    // Depth (top `levels`), the last `trades` tape entries, session stats and the symbol's next seq,
+    // captured by its worker between two batches so they agree with each other and with the trade,
+    // progress and feed streams up to `next_seq`. None for an unknown symbol or a stopped worker.
+    pub fn market_data_snapshot(&self, symbol: &str, levels: usize, trades: usize) -> Option<MarketDataSnapshot> {
+        let (reply, rx) = cb::bounded(1);
+        self.queries.get(symbol)?.send(WorkerQuery::MarketData { levels, trades, reply }).ok()?;
+        rx.recv().ok()
+    }
+
+    // Full book of `symbol` between two batches, with the seq of the next command it will apply
+    // (for saving and restoring state, e.g. with `mmap_snapshot::write_snapshot_file`)
+    pub fn book_snapshot(&self, symbol: &str) -> Option<SymbolSnapshot> {
+        let (reply, rx) = cb::bounded(1);
+        self.queries.get(symbol)?.send(WorkerQuery::Book { reply }).ok()?;
+        rx.recv().ok()
+    }
+
+    // Admin path for an unscheduled end of day; false for an unknown symbol or a stopped worker
+    pub fn end_of_day(&self, symbol: &str) -> bool { self.routes.get(symbol).is_some_and(|tx| tx.send(RawCommand::EndOfDay).is_ok()) }
+
+    // Per-worker queue depth, batch sizes, idle time and throughput since the previous poll
+    pub fn stats(&self) -> Vec<WorkerStats> { self.telemetry.stats() }
+
+    pub fn telemetry(&self) -> Telemetry { self.telemetry.clone() }
 }
 
 #[derive(Clone, Copy)]
@@ -124,6 +545,57 @@ pub struct Options {
     pub batch_size: usize,
     pub emit_trades: bool,
     pub coalesce_micros: u32,
+    // Snapshot after this many commands since the last one (0 = off); needs a SnapshotStore
+    pub snapshot_every_commands: u64,
+    // Snapshot when this much time passed since the last one and the book changed (0 = off)
+    pub snapshot_interval_millis: u64,
+    // With a replica attached, send a state-hash checkpoint after this many commands (0 = off)
+    pub replication_hash_every_commands: u64,
+    // Emit a High WatermarkEvent once a symbol's input backlog reaches this (0 = off) ...
+    pub queue_high_watermark: usize,
+    // ... and a Low one when it drains back to this
+    pub queue_low_watermark: usize,
+    // Applied to every book at start (None = keep each book's own)
+    pub error_policy: Option<ErrorPolicy>,
+    // New orders per sequencer producer and symbol, checked as received (None = off; see `throttle`)
+    pub producer_throttle: Option<EntryThrottle>,
+    // Stamp each batch's stages on its Progress (see `latency`)
+    pub stage_timestamps: bool,
+    // Retune coalesce_micros and batch_size per worker from arrivals and backlog (None = fixed; see `coalesce`)
+    pub adaptive_coalescing: Option<AdaptiveCoalescing>,
+}
+
+// Optional services attached to the workers
+#[derive(Clone, Default)]
+pub struct Attachments {
+    pub snapshot_store: Option<Arc<dyn SnapshotStore>>,
+    pub replica: Option<Sender<ReplicationMsg>>,
+    // First seq per symbol (default 0), e.g. to continue a promoted standby's sequence
+    pub start_seq: HashMap<String, u64>,
+    // Public depth feed (see `publisher`), all symbols on one channel
+    pub feed: Option<(PublisherConfig, Sender<FeedMsg>)>,
+    // Provenance and outcome of every sequenced command (see `audit`)
+    pub audit: Option<Arc<dyn AuditSink>>,
+    // Every applied batch and the events it produced, for byte-for-byte replay (see `capture`)
+    pub capture: Option<Arc<Mutex<CaptureWriter>>>,
+    // Trades and implicit cancels retained until consumers acknowledge them (see `delivery`)
+    pub delivery: Option<Arc<DeliveryLog>>,
+    // Per-symbol delay for taker commands before they are sequenced (see `speed_bump`)
+    pub speed_bumps: HashMap<String, SpeedBump>,
+    // Write-ahead command log per symbol (see `journal::JournalConfig`)
+    pub journal: Option<JournalConfig>,
+    // Per-symbol depth readable from any thread without a query (see `mirror`)
+    pub mirrors: HashMap<String, Arc<DepthMirror>>,
+    // Tick, lot and order size rules per symbol, set on its book before the worker starts (see
+    // `match_engine::symbol_config`). If a book refuses its config, `start_with_attachments` returns
+    // `EngineError::InvalidConfig` and recovery fails with `ReplayError::Io`
+    pub symbol_configs: HashMap<String, SymbolConfig>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self { batch_size: 4096, emit_trades: true, coalesce_micros: 0, snapshot_every_commands: 0, snapshot_interval_millis: 0, replication_hash_every_commands: 0, queue_high_watermark: 0, queue_low_watermark: 0, error_policy: None, producer_throttle: None, stage_timestamps: false, adaptive_coalescing: None }
+    }
 }
 
 pub struct Ingestor {
@@ -131,8 +603,16 @@ pub struct Ingestor {
     pub rx_trade: Receiver<Trade>,
 }
 
+// Single-book worker with fixed batching: takes whatever is queued up to `batch_size`, no coalescing
+// window or adaptive tuning (see `MultiIngestor` for `Options`)
 impl Ingestor {
-    pub fn start_with_book(mut book: OrderBook, batch_size: usize) -> Self {
+    pub fn start_with_book(book: OrderBook, batch_size: usize) -> Self { Self::start(book, batch_size, None) }
+
+    // Every sequenced batch is appended to `journal` before it is applied; if an append fails the
+    // worker stops, so `tx_cmd` sends fail from then on
+    pub fn start_with_book_journaled(book: OrderBook, batch_size: usize, journal: Box<dyn JournalWriter>) -> Self { Self::start(book, batch_size, Some(journal)) }
+
+    fn start(mut book: OrderBook, batch_size: usize, mut journal: Option<Box<dyn JournalWriter>>) -> Self {
         let (tx_cmd, rx_cmd) = cb::unbounded::<RawCommand>();
         let (tx_trade, rx_trade) = cb::unbounded::<Trade>();
 
@@ -141,12 +621,21 @@ impl Ingestor {
             let mut batch_raw: Vec<RawCommand> = Vec::with_capacity(batch_size);
             let mut batch: Vec<Command> = Vec::with_capacity(batch_size);
             let mut seq: u64 = 0;
+            let mut sched = Scheduler::new();
             loop {
                 batch_raw.clear();
-                // blocking take one to avoid busy loop
-                match rx_cmd.recv() {
-                    Ok(cmd) => batch_raw.push(cmd),
-                    Err(_) => break,
+                // blocking take one to avoid busy loop; wake up for the next scheduled command
+                let wait = if book.pending_continuations() > 0 { Some(Duration::ZERO) } else { sched.next_wait(now_micros()) };
+                match wait {
+                    Some(wait) => match rx_cmd.recv_timeout(wait) {
+                        Ok(cmd) => batch_raw.push(cmd),
+                        Err(cb::RecvTimeoutError::Timeout) => {}
+                        Err(cb::RecvTimeoutError::Disconnected) => break,
+                    },
+                    None => match rx_cmd.recv() {
+                        Ok(cmd) => batch_raw.push(cmd),
+                        Err(_) => break,
+                    },
                 }
                 // try to fill batch without blocking
                 while batch_raw.len() < batch_size {
@@ -156,22 +645,28 @@ impl Ingestor {
                         Err(cb::TryRecvError::Disconnected) => break,
                     }
                 }
+                sched.admit(&mut batch_raw, now_micros());
+                if batch_raw.is_empty() && book.pending_continuations() > 0 { batch_raw.push(RawCommand::Resume); }
+                if batch_raw.is_empty() { continue; }
                 // assign seq and convert to engine Command
                 batch.clear();
                 for rc in batch_raw.iter().copied() {
-                    let s = seq; seq = seq.wrapping_add(1);
-                    batch.push(match rc {
-                        RawCommand::Limit { side, price, qty } => Command::Limit { seq: s, side, price, qty },
-                        RawCommand::Market { side, qty } => Command::Market { seq: s, side, qty },
-                        RawCommand::Cancel { id } => Command::Cancel { seq: s, id },
-                    });
+                    batch.push(sequence(rc, seq));
+                    seq = seq.wrapping_add(1);
                 }
+                if let Some(Err(_)) = journal.as_mut().map(|j| j.append(&batch)) { break; }
                 let start_len = trades_buf.len();
-                let _ = book.process_commands_batch_checked_into(&mut batch, &mut trades_buf);
+                let _ = book.process_commands_batch_presorted_unchecked(&batch, &mut trades_buf);
                 for t in trades_buf.drain(start_len..) {
                     let _ = tx_trade.send(t);
                 }
+                // Trades only; discard side-effect cancels, reports and events so they don't pile up
+                book.drain_implicit_cancels();
+                book.drain_fill_reports();
+                book.drain_stop_activations();
+                book.drain_repriced();
             }
+            if let Some(j) = &mut journal { let _ = j.sync(); }
         });
 
         Self { tx_cmd, rx_trade }
@@ -0,0 +1,39 @@
+// Minimal metrics subsystem: components report labelled gauges into a `MetricsSink`; the bundled
+// `MetricsRegistry` keeps the latest value per (name, symbol) and renders Prometheus text format.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+pub trait MetricsSink: Send + Sync {
+    fn gauge(&self, name: &str, symbol: &str, value: f64);
+}
+
+#[derive(Default)]
+pub struct MetricsRegistry {
+    values: Mutex<BTreeMap<(String, String), f64>>,
+}
+
+impl MetricsRegistry {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn get(&self, name: &str, symbol: &str) -> Option<f64> {
+        self.values.lock().unwrap().get(&(name.to_string(), symbol.to_string())).copied()
+    }
+
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+        let mut last_name = "";
+        let values = self.values.lock().unwrap();
+        for ((name, symbol), v) in values.iter() {
+            if name != last_name { let _ = writeln!(out, "# TYPE {} gauge", name); last_name = name; }
+            let _ = writeln!(out, "{}{{symbol=\"{}\"}} {}", name, symbol.replace('\\', "\\\\").replace('"', "\\\""), v);
+        }
+        out
+    }
+}
+
+impl MetricsSink for MetricsRegistry {
+    fn gauge(&self, name: &str, symbol: &str, value: f64) {
+        self.values.lock().unwrap().insert((name.to_string(), symbol.to_string()), value);
+    }
+}
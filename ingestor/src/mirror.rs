@@ -0,0 +1,107 @@
+// Read-optimized mirror of one book's aggregated depth (`Attachments::mirrors`): the worker publishes
+// the top `levels` per side after every batch, and any thread in the process reads them at any time
+// without sending a query to the worker or taking a lock.
+//   layout   two buffers of (price, qty, orders) per level and side, plus the seq of the next
+//            command, all atomics; the worker fills the buffer readers are not pointed at, then
+//            flips `current` to it
+//   readers  copy the current buffer and check its version did not move while copying (a seqlock);
+//            a reader only retries when the worker lapped it, i.e. published twice during one copy
+// The worker never waits for readers. `read_into` reuses the caller's vectors, so steady-state reads
+// do not allocate. Only the owning worker calls `publish`.
+use match_engine::{Depth, OrderBook};
+use std::sync::atomic::{fence, AtomicU64, AtomicUsize, Ordering};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MirroredDepth {
+    // Seq of the next command: the depth reflects every command before it
+    pub next_seq: u64,
+    pub bids: Depth,
+    pub asks: Depth,
+}
+
+struct Buffer {
+    version: AtomicU64, // odd while being written
+    next_seq: AtomicU64,
+    lens: [AtomicUsize; 2],
+    cells: Box<[AtomicU64]>, // bids then asks, 3 per level
+}
+
+impl Buffer {
+    fn new(levels: usize) -> Self {
+        Self { version: AtomicU64::new(0), next_seq: AtomicU64::new(0), lens: Default::default(), cells: (0..levels * 6).map(|_| AtomicU64::new(0)).collect() }
+    }
+}
+
+pub struct DepthMirror {
+    levels: usize,
+    current: AtomicUsize,
+    buffers: [Buffer; 2],
+}
+
+impl DepthMirror {
+    pub fn new(levels: usize) -> Self { Self { levels, current: AtomicUsize::new(0), buffers: [Buffer::new(levels), Buffer::new(levels)] } }
+
+    pub fn levels(&self) -> usize { self.levels }
+
+    // Replace the mirrored depth; levels beyond `levels()` are dropped
+    pub fn publish(&self, next_seq: u64, bids: &[(u64, u64, usize)], asks: &[(u64, u64, usize)]) {
+        let idx = 1 - self.current.load(Ordering::Relaxed);
+        let buf = &self.buffers[idx];
+        let v = buf.version.load(Ordering::Relaxed);
+        buf.version.store(v + 1, Ordering::Relaxed);
+        fence(Ordering::Release);
+        buf.next_seq.store(next_seq, Ordering::Relaxed);
+        for (side, levels) in [bids, asks].into_iter().enumerate() {
+            let n = levels.len().min(self.levels);
+            let base = side * self.levels * 3;
+            for (i, &(price, qty, orders)) in levels[..n].iter().enumerate() {
+                for (j, x) in [price, qty, orders as u64].into_iter().enumerate() { buf.cells[base + i * 3 + j].store(x, Ordering::Relaxed); }
+            }
+            buf.lens[side].store(n, Ordering::Relaxed);
+        }
+        buf.version.store(v + 2, Ordering::Release);
+        self.current.store(idx, Ordering::Release);
+    }
+
+    // The worker's side: top levels of `book`, through `scratch` so publishing does not allocate
+    pub(crate) fn publish_book(&self, book: &OrderBook, next_seq: u64, scratch: &mut (Depth, Depth)) {
+        book.top_n_into(self.levels, &mut scratch.0, &mut scratch.1);
+        self.publish(next_seq, &scratch.0, &scratch.1);
+    }
+
+    pub fn read(&self) -> MirroredDepth {
+        let mut out = MirroredDepth::default();
+        self.read_into(&mut out);
+        out
+    }
+
+    pub fn read_into(&self, out: &mut MirroredDepth) {
+        loop {
+            let buf = &self.buffers[self.current.load(Ordering::Acquire)];
+            let v = buf.version.load(Ordering::Acquire);
+            if v & 1 == 1 { std::hint::spin_loop(); continue; }
+            out.next_seq = buf.next_seq.load(Ordering::Relaxed);
+            for (side, levels) in [&mut out.bids, &mut out.asks].into_iter().enumerate() {
+                levels.clear();
+                let base = side * self.levels * 3;
+                for i in 0..buf.lens[side].load(Ordering::Relaxed).min(self.levels) {
+                    let cell = |j: usize| buf.cells[base + i * 3 + j].load(Ordering::Relaxed);
+                    levels.push((cell(0), cell(1), cell(2) as usize));
+                }
+            }
+            fence(Ordering::Acquire);
+            if buf.version.load(Ordering::Relaxed) == v { return; }
+        }
+    }
+
+    // Seq of the next command as of the latest publish; cheap enough to poll for changes
+    pub fn next_seq(&self) -> u64 {
+        loop {
+            let buf = &self.buffers[self.current.load(Ordering::Acquire)];
+            let v = buf.version.load(Ordering::Acquire);
+            let next_seq = buf.next_seq.load(Ordering::Relaxed);
+            fence(Ordering::Acquire);
+            if v & 1 == 0 && buf.version.load(Ordering::Relaxed) == v { return next_seq; }
+        }
+    }
+}
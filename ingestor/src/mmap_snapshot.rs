@@ -0,0 +1,301 @@
+// Snapshot files laid out for memory mapping: a fixed header, one directory entry per symbol and
+// fixed-size order records, all little-endian u64. Opening a file only maps it and reads the
+// directory; a symbol's orders are decoded when its book is actually restored.
+//
+// header:    magic "MESNAP12", symbol_count
+// directory: name_off, name_len, next_seq, next_id, ts, orders_off, bid_count, ask_count,
+//            reference flags (bit 0 index, 1 settlement, 2 funding set), index price, settlement price, funding rate (i64),
+//            output_seq, last_trade_id, auction_count, stops_off, stop_count,
+//            last trade flags (bit 0 last trade, 1 last different set), last trade, last different,
+//            session (0 pre-open / 1 open / 2 pre-close / 3 closed / 4 halted), continuations_off, continuation_count,
+//            client id retention flags (bit 0 max ids, 1 max age set, 2 window on), max ids, max age, client_ids_off, client_id_count
+// orders:    id, side (0 buy / 1 sell) | reduce_only << 8, price, qty, order_type (0 limit / 1 market) | tif << 8 (`TimeInForce::code`:
+//            0 gtc / 1 day / 2 at-open / 3 at-close / 4 ioc / 5 fok),
+//            ts, account, correlation, display qty (0 = not an iceberg), reserve qty, order qty (open plus filled)
+//            bids best first then asks best first, FIFO within a level, then auction orders in arrival order
+// stops:     id, side | reduce_only << 8 | short_sell << 16 | top_level_only << 24 | post_only << 32, price, qty, order_type | tif << 8,
+//            stop price, account, correlation, display qty, client id (+1, 0 = none), emission (0 live / 1 suppressed / 2 replay)
+//            in trigger order, after all order records
+// paused:    continuations of budget-sliced orders in queue order, after all stops: a stop record with the stop price +1
+//            (0 = none), then the qty filled by earlier slices
+// clients:   term start, account, client id, order id per held client id in `BookSnapshot::client_ids` order, after all continuations
+use crate::snapshot::{SnapshotStore, SymbolSnapshot};
+use match_engine::{AccountId, BookSnapshot, ClientIdEntry, ClientIdRetention, ClientOrderId, Continuation, Emission, LevelSnapshot, Order, OrderBook, OrderId, OrderRequest, OrderType, ReferenceValues, SessionState, Side, TimeInForce};
+use memmap2::Mmap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 8] = b"MESNAP12";
+const HEADER_LEN: usize = 16;
+const DIR_ENTRY_LEN: usize = 224;
+const ORDER_LEN: usize = 88;
+const CONTINUATION_LEN: usize = 96;
+const CLIENT_ID_LEN: usize = 32;
+
+fn invalid(msg: &str) -> io::Error { io::Error::new(io::ErrorKind::InvalidData, msg.to_string()) }
+
+// Write all snapshots into one file (via a temp file + rename, so readers never see a torn file)
+pub fn write_snapshot_file(path: &Path, snaps: &[SymbolSnapshot]) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    let mut w = BufWriter::new(File::create(&tmp)?);
+    let orders_start = HEADER_LEN + snaps.len() * DIR_ENTRY_LEN;
+    let total_orders: usize = snaps.iter().map(|s| s.book.order_count() + s.book.auction.len()).sum();
+    let stops_start = orders_start + total_orders * ORDER_LEN;
+    let continuations_start = stops_start + snaps.iter().map(|s| s.book.stops.len()).sum::<usize>() * ORDER_LEN;
+    let client_ids_start = continuations_start + snaps.iter().map(|s| s.book.continuations.len()).sum::<usize>() * CONTINUATION_LEN;
+    let mut name_off = client_ids_start + snaps.iter().map(|s| s.book.client_ids.len()).sum::<usize>() * CLIENT_ID_LEN;
+    let (mut orders_off, mut stops_off, mut continuations_off, mut client_ids_off) = (orders_start, stops_start, continuations_start, client_ids_start);
+    w.write_all(MAGIC)?;
+    w.write_all(&(snaps.len() as u64).to_le_bytes())?;
+    for s in snaps {
+        let count = |levels: &[LevelSnapshot]| levels.iter().map(|l| l.orders.len() as u64).sum::<u64>();
+        let (bid_count, ask_count) = (count(&s.book.bids), count(&s.book.asks));
+        let r = s.book.reference;
+        let flags = r.index_price.is_some() as u64 | (r.settlement_price.is_some() as u64) << 1 | (r.funding_rate.is_some() as u64) << 2;
+        let refs = [flags, r.index_price.unwrap_or(0), r.settlement_price.unwrap_or(0), r.funding_rate.unwrap_or(0) as u64];
+        let (b, auction_count, stop_count) = (&s.book, s.book.auction.len() as u64, s.book.stops.len() as u64);
+        let last = [b.last_trade.is_some() as u64 | (b.last_different.is_some() as u64) << 1, b.last_trade.unwrap_or(0), b.last_different.unwrap_or(0)];
+        let retention = b.client_id_retention.unwrap_or_default();
+        let retention = [b.client_id_retention.map_or(0, |r| r.max_ids.is_some() as u64 | (r.max_age_micros.is_some() as u64) << 1 | 4), retention.max_ids.unwrap_or(0) as u64, retention.max_age_micros.unwrap_or(0)];
+        for v in [name_off as u64, s.symbol.len() as u64, s.next_seq, b.next_id, b.ts, orders_off as u64, bid_count, ask_count].into_iter().chain(refs).chain([b.output_seq, b.last_trade_id, auction_count, stops_off as u64, stop_count]).chain(last).chain([b.session.code() as u64, continuations_off as u64, b.continuations.len() as u64]).chain(retention).chain([client_ids_off as u64, b.client_ids.len() as u64]) {
+            w.write_all(&v.to_le_bytes())?;
+        }
+        name_off += s.symbol.len();
+        orders_off += (bid_count + ask_count + auction_count) as usize * ORDER_LEN;
+        stops_off += stop_count as usize * ORDER_LEN;
+        continuations_off += b.continuations.len() * CONTINUATION_LEN;
+        client_ids_off += b.client_ids.len() * CLIENT_ID_LEN;
+    }
+    for s in snaps {
+        for o in s.book.bids.iter().chain(s.book.asks.iter()).flat_map(|l| l.orders.iter()).chain(s.book.auction.iter()) {
+            for v in [o.id.0, side_code(o.side) | (o.reduce_only as u64) << 8, o.price, o.qty, type_code(o.order_type, o.tif), o.ts, o.account.0, o.correlation, o.display_qty, o.reserve_qty, o.order_qty] { w.write_all(&v.to_le_bytes())?; }
+        }
+    }
+    for s in snaps {
+        for (id, r) in &s.book.stops { write_request(&mut w, *id, r, r.stop_price.unwrap_or(0))?; }
+    }
+    for s in snaps {
+        for c in &s.book.continuations {
+            write_request(&mut w, c.id, &c.req, c.req.stop_price.map_or(0, |p| p + 1))?;
+            w.write_all(&c.filled.to_le_bytes())?;
+        }
+    }
+    for s in snaps {
+        for &(since, account, client_id, id) in &s.book.client_ids {
+            for v in [since, account.0, client_id.0, id.0] { w.write_all(&v.to_le_bytes())?; }
+        }
+    }
+    for s in snaps { w.write_all(s.symbol.as_bytes())?; }
+    w.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    fs::rename(&tmp, path)
+}
+
+// The stop record fields of a held or paused order; the caller picks how its stop price is written
+fn write_request(w: &mut impl Write, id: OrderId, r: &OrderRequest, stop: u64) -> io::Result<()> {
+    let flags = side_code(r.side) | (r.reduce_only as u64) << 8 | (r.short_sell as u64) << 16 | (r.top_level_only as u64) << 24 | (r.post_only as u64) << 32;
+    let emission = match r.emission { Emission::Live => 0u64, Emission::Suppressed => 1, Emission::Replay => 2 };
+    for v in [id.0, flags, r.price, r.qty, type_code(r.order_type, r.tif), stop, r.account.0, r.correlation, r.display_qty.unwrap_or(0), r.client_id.map_or(0, |c| c.0 + 1), emission] { w.write_all(&v.to_le_bytes())?; }
+    Ok(())
+}
+
+fn side_code(side: Side) -> u64 { match side { Side::Buy => 0, Side::Sell => 1 } }
+
+fn type_code(order_type: OrderType, tif: TimeInForce) -> u64 {
+    let order_type = match order_type { OrderType::Limit => 0u64, OrderType::Market => 1 };
+    order_type | (tif.code() as u64) << 8
+}
+
+fn decode_side(v: u64) -> Side { if v & 0xFF == 0 { Side::Buy } else { Side::Sell } }
+
+fn decode_type(v: u64) -> io::Result<(OrderType, TimeInForce)> {
+    let order_type = if v & 0xFF == 0 { OrderType::Limit } else { OrderType::Market };
+    Ok((order_type, TimeInForce::from_code(v >> 8).ok_or_else(|| invalid("unknown time in force"))?))
+}
+
+pub struct MappedSnapshots {
+    map: Mmap,
+    count: usize,
+}
+
+impl MappedSnapshots {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: snapshot files are written once via rename and never modified in place
+        let map = unsafe { Mmap::map(&file)? };
+        if map.len() < HEADER_LEN || &map[..8] != MAGIC { return Err(invalid("not a snapshot file")); }
+        let count = u64::from_le_bytes(map[8..16].try_into().unwrap()) as usize;
+        let s = Self { map, count };
+        if s.map.len() < HEADER_LEN + count * DIR_ENTRY_LEN { return Err(invalid("truncated directory")); }
+        for i in 0..count {
+            let e = s.entry(i);
+            let orders_end = e.orders_off + (e.bid_count + e.ask_count + e.auction_count) * ORDER_LEN;
+            if orders_end > s.map.len() || e.stops_off + e.stop_count * ORDER_LEN > s.map.len() || e.continuations_off + e.continuation_count * CONTINUATION_LEN > s.map.len()
+                || e.client_ids_off + e.client_id_count * CLIENT_ID_LEN > s.map.len() || e.name_off + e.name_len > s.map.len() || std::str::from_utf8(&s.map[e.name_off..e.name_off + e.name_len]).is_err() {
+                return Err(invalid("snapshot entry out of bounds"));
+            }
+            if e.session.is_none() { return Err(invalid("unknown session state")); }
+        }
+        Ok(s)
+    }
+
+    pub fn len(&self) -> usize { self.count }
+
+    pub fn is_empty(&self) -> bool { self.count == 0 }
+
+    fn u64_at(&self, off: usize) -> u64 { u64::from_le_bytes(self.map[off..off + 8].try_into().unwrap()) }
+
+    fn entry(&self, i: usize) -> DirEntry {
+        let base = HEADER_LEN + i * DIR_ENTRY_LEN;
+        let f = |k: usize| self.u64_at(base + k * 8);
+        DirEntry { name_off: f(0) as usize, name_len: f(1) as usize, next_seq: f(2), next_id: f(3), ts: f(4), orders_off: f(5) as usize, bid_count: f(6) as usize, ask_count: f(7) as usize,
+            reference: ReferenceValues { index_price: (f(8) & 1 != 0).then(|| f(9)), settlement_price: (f(8) & 2 != 0).then(|| f(10)), funding_rate: (f(8) & 4 != 0).then(|| f(11) as i64) }, output_seq: f(12), last_trade_id: f(13),
+            auction_count: f(14) as usize, stops_off: f(15) as usize, stop_count: f(16) as usize, last_trade: (f(17) & 1 != 0).then(|| f(18)), last_different: (f(17) & 2 != 0).then(|| f(19)), session: SessionState::from_code(f(20)),
+            continuations_off: f(21) as usize, continuation_count: f(22) as usize,
+            client_id_retention: (f(23) & 4 != 0).then(|| ClientIdRetention { max_ids: (f(23) & 1 != 0).then(|| f(24) as usize), max_age_micros: (f(23) & 2 != 0).then(|| f(25)) }),
+            client_ids_off: f(26) as usize, client_id_count: f(27) as usize }
+    }
+
+    pub fn symbols(&self) -> impl Iterator<Item = MappedBook<'_>> + '_ { (0..self.count).map(move |i| MappedBook { file: self, entry: self.entry(i) }) }
+
+    pub fn get(&self, symbol: &str) -> Option<MappedBook<'_>> { self.symbols().find(|b| b.symbol() == symbol) }
+}
+
+#[derive(Clone, Copy)]
+struct DirEntry {
+    name_off: usize,
+    name_len: usize,
+    next_seq: u64,
+    next_id: u64,
+    ts: u64,
+    orders_off: usize,
+    bid_count: usize,
+    ask_count: usize,
+    reference: ReferenceValues,
+    output_seq: u64,
+    last_trade_id: u64,
+    auction_count: usize,
+    stops_off: usize,
+    stop_count: usize,
+    last_trade: Option<u64>,
+    last_different: Option<u64>,
+    session: Option<SessionState>, // None for an unknown code, which `open` rejects
+    continuations_off: usize,
+    continuation_count: usize,
+    client_id_retention: Option<ClientIdRetention>,
+    client_ids_off: usize,
+    client_id_count: usize,
+}
+
+// One symbol's state viewed in place; nothing is decoded until orders are read
+#[derive(Clone, Copy)]
+pub struct MappedBook<'a> {
+    file: &'a MappedSnapshots,
+    entry: DirEntry,
+}
+
+impl<'a> MappedBook<'a> {
+    pub fn symbol(&self) -> &'a str {
+        let e = &self.entry;
+        std::str::from_utf8(&self.file.map[e.name_off..e.name_off + e.name_len]).unwrap()
+    }
+
+    pub fn next_seq(&self) -> u64 { self.entry.next_seq }
+
+    pub fn order_count(&self) -> usize { self.entry.bid_count + self.entry.ask_count }
+
+    pub fn stop_count(&self) -> usize { self.entry.stop_count }
+
+    pub fn continuation_count(&self) -> usize { self.entry.continuation_count }
+
+    pub fn client_id_count(&self) -> usize { self.entry.client_id_count }
+
+    // i-th record: bids best first, then asks best first, then auction orders. Records are only checked
+    // as they are decoded, so an unknown time in force or emission code fails here, not in `open`
+    pub fn order(&self, i: usize) -> io::Result<Order> {
+        let base = self.entry.orders_off + i * ORDER_LEN;
+        let f = |k: usize| self.file.u64_at(base + k * 8);
+        let (order_type, tif) = decode_type(f(4))?;
+        Ok(Order { id: OrderId(f(0)), side: decode_side(f(1)), price: f(2), qty: f(3), order_type, ts: f(5), tif, account: AccountId(f(6)), reduce_only: f(1) >> 8 != 0, correlation: f(7), display_qty: f(8), reserve_qty: f(9), order_qty: f(10) })
+    }
+
+    // i-th held stop in trigger order
+    pub fn stop(&self, i: usize) -> io::Result<(OrderId, OrderRequest)> {
+        let (id, req, stop) = self.request(self.entry.stops_off + i * ORDER_LEN)?;
+        Ok((id, OrderRequest { stop_price: Some(stop), ..req }))
+    }
+
+    // i-th paused continuation in queue order
+    pub fn continuation(&self, i: usize) -> io::Result<Continuation> {
+        let base = self.entry.continuations_off + i * CONTINUATION_LEN;
+        let (id, req, stop) = self.request(base)?;
+        Ok(Continuation { id, req: OrderRequest { stop_price: stop.checked_sub(1), ..req }, filled: self.file.u64_at(base + ORDER_LEN) })
+    }
+
+    // i-th held client id: (term start, account, client id, order id)
+    pub fn client_id(&self, i: usize) -> ClientIdEntry {
+        let f = |k: usize| self.file.u64_at(self.entry.client_ids_off + i * CLIENT_ID_LEN + k * 8);
+        (f(0), AccountId(f(1)), ClientOrderId(f(2)), OrderId(f(3)))
+    }
+
+    // Record written by `write_request`: (id, request without its stop price, stop price as written)
+    fn request(&self, base: usize) -> io::Result<(OrderId, OrderRequest, u64)> {
+        let f = |k: usize| self.file.u64_at(base + k * 8);
+        let (order_type, tif) = decode_type(f(4))?;
+        let flag = |bit: u32| f(1) >> bit & 0xFF != 0;
+        let emission = match f(10) { 0 => Emission::Live, 1 => Emission::Suppressed, 2 => Emission::Replay, _ => return Err(invalid("unknown emission")) };
+        let req = OrderRequest { side: decode_side(f(1)), order_type, price: f(2), qty: f(3), tif, account: AccountId(f(6)), reduce_only: flag(8), short_sell: flag(16), correlation: f(7), post_only: flag(32),
+            top_level_only: flag(24), emission, stop_price: None, display_qty: (f(8) > 0).then(|| f(8)), client_id: f(9).checked_sub(1).map(ClientOrderId) };
+        Ok((OrderId(f(0)), req, f(5)))
+    }
+
+    pub fn to_symbol_snapshot(&self) -> io::Result<SymbolSnapshot> {
+        let e = &self.entry;
+        let mut book = BookSnapshot { next_id: e.next_id, ts: e.ts, bids: Vec::new(), asks: Vec::new(), reference: e.reference, output_seq: e.output_seq, last_trade_id: e.last_trade_id,
+            stops: (0..e.stop_count).map(|i| self.stop(i)).collect::<io::Result<_>>()?, auction: (0..e.auction_count).map(|i| self.order(self.order_count() + i)).collect::<io::Result<_>>()?,
+            last_trade: e.last_trade, last_different: e.last_different, session: e.session.unwrap_or_default(), continuations: (0..e.continuation_count).map(|i| self.continuation(i)).collect::<io::Result<_>>()?,
+            client_ids: (0..e.client_id_count).map(|i| self.client_id(i)).collect(), client_id_retention: e.client_id_retention };
+        for i in 0..self.order_count() {
+            let o = self.order(i)?;
+            let levels = if i < self.entry.bid_count { &mut book.bids } else { &mut book.asks };
+            match levels.last_mut() {
+                Some(l) if l.price == o.price => l.orders.push(o),
+                _ => levels.push(LevelSnapshot { price: o.price, orders: vec![o] }),
+            }
+        }
+        Ok(SymbolSnapshot { symbol: self.symbol().to_string(), next_seq: self.entry.next_seq, book })
+    }
+
+    pub fn restore(&self) -> io::Result<OrderBook> { Ok(OrderBook::from_snapshot(&self.to_symbol_snapshot()?.book)) }
+}
+
+// One mmap-able file per symbol in `dir`; `load_latest` maps the file and decodes only that symbol
+pub struct MmapSnapshotStore {
+    dir: PathBuf,
+}
+
+impl MmapSnapshotStore {
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    // Symbols with a snapshot in the directory
+    pub fn symbols(&self) -> io::Result<Vec<String>> { crate::snapshot::symbols_in(&self.dir, "snap") }
+
+    pub fn path_for(&self, symbol: &str) -> PathBuf { self.dir.join(crate::snapshot::symbol_file_name(symbol, "snap")) }
+}
+
+impl SnapshotStore for MmapSnapshotStore {
+    fn save(&self, snap: &SymbolSnapshot) -> io::Result<()> {
+        write_snapshot_file(&self.path_for(&snap.symbol), std::slice::from_ref(snap))
+    }
+
+    fn load_latest(&self, symbol: &str) -> io::Result<Option<SymbolSnapshot>> {
+        let path = self.path_for(symbol);
+        if !path.exists() { return Ok(None); }
+        let file = MappedSnapshots::open(&path)?;
+        file.get(symbol).map(|b| b.to_symbol_snapshot()).transpose()
+    }
+}
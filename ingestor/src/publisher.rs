@@ -0,0 +1,159 @@
+// Public depth feed: incremental level updates plus a full snapshot at a fixed interval per symbol,
+// so a consumer can join mid-stream. Every delta carries the symbol's next feed seq (from 1); a
+// snapshot carries the seq of the last delta it already reflects and is built from the publisher's
+// own published state, so it lines up with the delta stream exactly. A late joiner buffers deltas,
+// waits for a snapshot, drops buffered deltas at or below its seq and applies the rest (`FeedBook`).
+// Deltas cover the top `levels` per side; a level leaving the window is published as qty 0.
+use match_engine::{BookStorage, Depth, OrderBook, Side};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeedMsg {
+    Snapshot { symbol: String, seq: u64, bids: Depth, asks: Depth },
+    // qty 0 = level removed
+    Delta { symbol: String, seq: u64, side: Side, price: u64, qty: u64, orders: usize },
+}
+
+impl FeedMsg {
+    pub fn symbol(&self) -> &str { match self { Self::Snapshot { symbol, .. } | Self::Delta { symbol, .. } => symbol } }
+
+    pub fn seq(&self) -> u64 { match self { Self::Snapshot { seq, .. } | Self::Delta { seq, .. } => *seq } }
+}
+
+#[derive(Debug, Clone)]
+pub struct PublisherConfig {
+    pub levels: usize,
+    pub snapshot_interval_micros: u64,
+    // Per-symbol overrides of the snapshot interval
+    pub intervals: HashMap<String, u64>,
+}
+
+impl Default for PublisherConfig {
+    fn default() -> Self { Self { levels: 10, snapshot_interval_micros: 1_000_000, intervals: HashMap::new() } }
+}
+
+impl PublisherConfig {
+    pub fn publisher_for(&self, symbol: &str) -> DepthPublisher {
+        DepthPublisher::new(symbol, self.levels, self.intervals.get(symbol).copied().unwrap_or(self.snapshot_interval_micros))
+    }
+}
+
+pub struct DepthPublisher {
+    symbol: String,
+    levels: usize,
+    interval_micros: u64,
+    seq: u64,
+    bids: Depth,
+    asks: Depth,
+    // Scratch for the next depth read, swapped with the published state
+    next: (Depth, Depth),
+    last_snapshot: Option<u64>,
+}
+
+impl DepthPublisher {
+    pub fn new(symbol: &str, levels: usize, interval_micros: u64) -> Self {
+        Self { symbol: symbol.to_string(), levels, interval_micros, seq: 0, bids: Vec::new(), asks: Vec::new(), next: (Vec::new(), Vec::new()), last_snapshot: None }
+    }
+
+    // Publish the changes since the previous update, then a snapshot if one is due
+    pub fn update<S: BookStorage>(&mut self, book: &OrderBook<S>, ts_micros: u64, out: &mut Vec<FeedMsg>) {
+        let (bids, asks) = &mut self.next;
+        book.top_n_into(self.levels, bids, asks);
+        for (side, old, new) in [(Side::Buy, &self.bids, &*bids), (Side::Sell, &self.asks, &*asks)] {
+            diff_levels(side, old, new, |price, qty, orders| {
+                self.seq += 1;
+                out.push(FeedMsg::Delta { symbol: self.symbol.clone(), seq: self.seq, side, price, qty, orders });
+            });
+        }
+        std::mem::swap(&mut self.bids, bids);
+        std::mem::swap(&mut self.asks, asks);
+        self.tick(ts_micros, out);
+    }
+
+    // Snapshot if the interval elapsed since the last one (the first call always publishes one)
+    pub fn tick(&mut self, ts_micros: u64, out: &mut Vec<FeedMsg>) {
+        if self.next_snapshot_in(ts_micros) > 0 { return; }
+        self.last_snapshot = Some(ts_micros);
+        out.push(self.snapshot());
+    }
+
+    // Micros until the next snapshot is due, 0 when due now
+    pub fn next_snapshot_in(&self, ts_micros: u64) -> u64 {
+        self.last_snapshot.map_or(0, |t| (t + self.interval_micros).saturating_sub(ts_micros))
+    }
+
+    // Current published state, e.g. for a consumer joining out of band
+    pub fn snapshot(&self) -> FeedMsg {
+        FeedMsg::Snapshot { symbol: self.symbol.clone(), seq: self.seq, bids: self.bids.clone(), asks: self.asks.clone() }
+    }
+}
+
+// Both lists are ordered best first for `side`
+fn diff_levels(side: Side, old: &Depth, new: &Depth, mut emit: impl FnMut(u64, u64, usize)) {
+    let better = |a: u64, b: u64| match side { Side::Buy => a > b, Side::Sell => a < b };
+    let (mut i, mut j) = (0, 0);
+    loop {
+        match (old.get(i), new.get(j)) {
+            (Some(o), Some(n)) if o.0 == n.0 => { if o != n { emit(n.0, n.1, n.2); } i += 1; j += 1; }
+            (Some(o), Some(n)) if better(o.0, n.0) => { emit(o.0, 0, 0); i += 1; }
+            (Some(o), None) => { emit(o.0, 0, 0); i += 1; }
+            (_, Some(n)) => { emit(n.0, n.1, n.2); j += 1; }
+            (None, None) => break,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedError {
+    // A delta skipped ahead; the book stays at `expected - 1` until the gap is filled or a newer snapshot arrives
+    Gap { expected: u64, got: u64 },
+}
+
+// Consumer side of one symbol's feed
+#[derive(Debug, Clone, Default)]
+pub struct FeedBook {
+    seq: Option<u64>,
+    pending: Vec<FeedMsg>,
+    bids: Depth,
+    asks: Depth,
+}
+
+impl FeedBook {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn apply(&mut self, msg: &FeedMsg) -> Result<(), FeedError> {
+        match (msg, self.seq) {
+            (FeedMsg::Delta { .. }, None) => { self.pending.push(msg.clone()); Ok(()) }
+            (FeedMsg::Snapshot { seq, bids, asks, .. }, current) => {
+                if current.is_some_and(|c| c >= *seq) { return Ok(()); }
+                (self.seq, self.bids, self.asks) = (Some(*seq), bids.clone(), asks.clone());
+                let pending = std::mem::take(&mut self.pending);
+                pending.iter().filter(|m| m.seq() > *seq).try_for_each(|m| self.apply(m))
+            }
+            (FeedMsg::Delta { seq, side, price, qty, orders, .. }, Some(current)) => {
+                if *seq <= current { return Ok(()); }
+                if *seq != current + 1 { return Err(FeedError::Gap { expected: current + 1, got: *seq }); }
+                let levels = match side { Side::Buy => &mut self.bids, Side::Sell => &mut self.asks };
+                let at = levels.iter().position(|l| match side { Side::Buy => l.0 <= *price, Side::Sell => l.0 >= *price }).unwrap_or(levels.len());
+                let exists = levels.get(at).is_some_and(|l| l.0 == *price);
+                match (exists, *qty) {
+                    (true, 0) => { levels.remove(at); }
+                    (true, _) => levels[at] = (*price, *qty, *orders),
+                    (false, 0) => {}
+                    (false, _) => levels.insert(at, (*price, *qty, *orders)),
+                }
+                self.seq = Some(*seq);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn is_synced(&self) -> bool { self.seq.is_some() }
+
+    // Seq of the last applied message
+    pub fn seq(&self) -> Option<u64> { self.seq }
+
+    pub fn bids(&self) -> &Depth { &self.bids }
+
+    pub fn asks(&self) -> &Depth { &self.asks }
+}
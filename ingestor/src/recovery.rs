@@ -0,0 +1,56 @@
+// Crash recovery for `MultiIngestor` from the files it leaves behind: the latest snapshot per symbol
+// (`MmapSnapshotStore`) plus the tail of its write-ahead journal (`JournalConfig`).
+//   symbols   every symbol with a snapshot or a journal
+//   book      the snapshot's book (or an empty one) with the journal commands from its `next_seq` on
+//             replayed (see `replay`); a torn frame at the end of a journal is cut off first, and a
+//             gap between snapshot and journal is an error rather than a silently different book
+//   settings  the symbol's config in the attachments and the error policy in `Options` are set on the
+//             book before the journal is replayed, so commands rejected live are rejected again
+//   restart   each worker continues at the seq after the last command it had applied, journaling to
+//             the same files and snapshotting to the same directory, so a second crash recovers the
+//             same way
+// Other settings outside snapshots and journals (book hooks, price bands, ...) are not restored.
+use crate::journal::{truncate_torn_tail, FsyncPolicy, JournalConfig};
+use crate::mmap_snapshot::MmapSnapshotStore;
+use crate::replay::{ReplayError, Replayer};
+use crate::snapshot::SnapshotStore;
+use crate::{Attachments, MultiIngestor, Options};
+use match_engine::{ErrorPolicy, OrderBook, SymbolConfig};
+use std::collections::BTreeSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+impl MultiIngestor {
+    // Journals fsynced every batch; see `recover_with_attachments` for the rest
+    pub fn recover(snapshot_dir: impl Into<PathBuf>, journal_dir: impl Into<PathBuf>, opts: Options) -> Result<Self, ReplayError> {
+        Self::recover_with_attachments(snapshot_dir, JournalConfig::new(journal_dir, FsyncPolicy::EveryBatch), opts, Attachments::default())
+    }
+
+    // `attach` minus the snapshot store, journal and start seqs, which recovery sets
+    pub fn recover_with_attachments(snapshot_dir: impl Into<PathBuf>, journal: JournalConfig, opts: Options, attach: Attachments) -> Result<Self, ReplayError> {
+        let store = MmapSnapshotStore::new(snapshot_dir)?;
+        let symbols: BTreeSet<String> = store.symbols()?.into_iter().chain(journal.symbols()?).collect();
+        let mut books = Vec::new();
+        let mut start_seq = attach.start_seq.clone();
+        for symbol in symbols {
+            let (book, next_seq) = recover_book(&store, &journal.path_for(&symbol), &symbol, attach.symbol_configs.get(&symbol).copied(), opts.error_policy)?;
+            start_seq.insert(symbol.clone(), next_seq);
+            books.push((symbol, book));
+        }
+        Ok(Self::spawn(books, opts, Attachments { snapshot_store: Some(Arc::new(store)), journal: Some(journal), start_seq, ..attach }))
+    }
+}
+
+fn recover_book(store: &MmapSnapshotStore, journal: &Path, symbol: &str, config: Option<SymbolConfig>, policy: Option<ErrorPolicy>) -> Result<(OrderBook, u64), ReplayError> {
+    let base = store.load_latest(symbol)?;
+    let mut replayer = base.as_ref().map_or_else(Replayer::empty, Replayer::from_snapshot);
+    replayer.book_mut().set_symbol_config(config).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("symbol config for {symbol}: {e}")))?;
+    if let Some(policy) = policy { replayer.book_mut().set_error_policy(policy); }
+    if journal.exists() {
+        truncate_torn_tail(journal)?;
+        replayer.apply_journal(journal)?;
+    }
+    let next_seq = replayer.next_seq().unwrap_or(0);
+    Ok((replayer.into_book(), next_seq))
+}
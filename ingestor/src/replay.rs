@@ -0,0 +1,147 @@
+// Deterministic replay of a command log (a journal file or a `Vec<Command>`) into a book: forward
+// only and streaming, for recovery and for checking that a log reproduces a book (`TimeTravel` is the
+// seekable, step-by-step variant for debugging).
+//   base        an empty book that starts at the log's first seq, or a snapshot (`SymbolSnapshot`) or
+//               configured book at its `next_seq`; commands before the base are skipped
+//   continuity  every command applied must carry exactly the next seq; a gap, or a compacted journal
+//               whose snapshot reference is newer than the base, is an error and nothing past it is
+//               applied
+//   settings    anything not in snapshots or the log (error policy, uptick rule, price band, ...) is
+//               set by the caller on the book given to `Replayer::new`
+//   end         the `_until` variants stop before seq `end`: point-in-time recovery
+// Commands are applied one at a time, as the live workers apply a batch: a rejected command is that
+// command's outcome and the ones after it still apply, so the book matches the live one whatever the
+// batch boundaries were (they are not in the journal). Internal errors are resolved through the book's
+// `ErrorPolicy`; a fail-fast stop drops the rest of the live batch, which replay cannot tell apart, so
+// it goes on with the book cancel-only. Outputs are drained as they are produced; trades and
+// rejections are kept only with `recording_trades` / `recording_rejections`.
+use crate::journal::{journal_snapshot_ref, JournalEntry, JournalReader};
+use crate::snapshot::SymbolSnapshot;
+use match_engine::{Command, EngineError, OrderBook, Trade};
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    // The journal was compacted at `journal` and the replay starts earlier (or has no base)
+    MissingBase { journal: u64, next_seq: Option<u64> },
+    // The log skips from `expected` to `found`
+    Gap { expected: u64, found: u64 },
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(e: io::Error) -> Self { ReplayError::Io(e) }
+}
+
+pub struct Replayer {
+    book: OrderBook,
+    next_seq: Option<u64>, // None: an empty book that takes the first seq it is given
+    trades: Option<Vec<Trade>>,
+    rejections: Option<Vec<(u64, EngineError)>>,
+    scratch: Vec<Trade>,
+}
+
+impl Replayer {
+    // `book` as of every command with seq < `next_seq`
+    pub fn new(book: OrderBook, next_seq: u64) -> Self { Self { book, next_seq: Some(next_seq), trades: None, rejections: None, scratch: Vec::new() } }
+
+    pub fn from_snapshot(snap: &SymbolSnapshot) -> Self { Self::new(OrderBook::from_snapshot(&snap.book), snap.next_seq) }
+
+    pub fn empty() -> Self { Self { book: OrderBook::new(), next_seq: None, trades: None, rejections: None, scratch: Vec::new() } }
+
+    // Keep every trade the replay produces (`take_trades`)
+    pub fn recording_trades(mut self) -> Self {
+        self.trades = Some(Vec::new());
+        self
+    }
+
+    // Keep the seq and error of every command the book rejected (`take_rejections`)
+    pub fn recording_rejections(mut self) -> Self {
+        self.rejections = Some(Vec::new());
+        self
+    }
+
+    pub fn apply(&mut self, commands: &[Command]) -> Result<usize, ReplayError> { self.apply_until(commands, u64::MAX) }
+
+    // Commands with seq < `end`, in order; returns how many were applied
+    pub fn apply_until(&mut self, commands: &[Command], end: u64) -> Result<usize, ReplayError> {
+        let mut applied = 0;
+        for &c in commands {
+            match self.step(c, end)? { Next::Applied => applied += 1, Next::Skipped => {} Next::End => break }
+        }
+        Ok(applied)
+    }
+
+    pub fn apply_journal(&mut self, path: &Path) -> Result<usize, ReplayError> { self.apply_journal_until(path, u64::MAX) }
+
+    // The journal at `path`, read frame by frame
+    pub fn apply_journal_until(&mut self, path: &Path, end: u64) -> Result<usize, ReplayError> {
+        if let Some(journal) = journal_snapshot_ref(path)? {
+            if self.next_seq.is_none_or(|s| s < journal) { return Err(ReplayError::MissingBase { journal, next_seq: self.next_seq }); }
+        }
+        let mut applied = 0;
+        for entry in JournalReader::new(BufReader::new(File::open(path)?)) {
+            let JournalEntry::Command(c) = entry? else { continue };
+            match self.step(c, end)? { Next::Applied => applied += 1, Next::Skipped => {} Next::End => break }
+        }
+        Ok(applied)
+    }
+
+    // Seq of the next command to apply; None for an empty replayer that has not seen one
+    pub fn next_seq(&self) -> Option<u64> { self.next_seq }
+
+    pub fn book(&self) -> &OrderBook { &self.book }
+
+    pub fn book_mut(&mut self) -> &mut OrderBook { &mut self.book }
+
+    pub fn take_trades(&mut self) -> Vec<Trade> { self.trades.as_mut().map(std::mem::take).unwrap_or_default() }
+
+    pub fn take_rejections(&mut self) -> Vec<(u64, EngineError)> { self.rejections.as_mut().map(std::mem::take).unwrap_or_default() }
+
+    pub fn into_book(self) -> OrderBook { self.book }
+
+    fn step(&mut self, c: Command, end: u64) -> Result<Next, ReplayError> {
+        let seq = c.seq();
+        if seq >= end { return Ok(Next::End); }
+        let expected = *self.next_seq.get_or_insert(seq);
+        if seq < expected { return Ok(Next::Skipped); }
+        if seq > expected { return Err(ReplayError::Gap { expected, found: seq }); }
+        if let Err(e) = self.book.process_command(c, &mut self.scratch) {
+            if let Some(rejections) = &mut self.rejections { rejections.push((seq, e.clone())); }
+            let _ = self.book.resolve_error(seq, e);
+        }
+        self.book.drain_implicit_cancels();
+        self.book.drain_faults();
+        self.book.drain_eod_reports();
+        self.book.drain_fill_reports();
+        self.book.drain_stop_activations();
+        self.book.drain_repriced();
+        match &mut self.trades { Some(trades) => trades.append(&mut self.scratch), None => self.scratch.clear() }
+        self.next_seq = Some(seq + 1);
+        Ok(Next::Applied)
+    }
+}
+
+enum Next {
+    Applied,
+    // Before the base
+    Skipped,
+    // At or past `end`
+    End,
+}
+
+// A book rebuilt from `commands` alone, starting at the first one's seq
+pub fn replay(commands: &[Command]) -> Result<OrderBook, ReplayError> {
+    let mut r = Replayer::empty();
+    r.apply(commands)?;
+    Ok(r.into_book())
+}
+
+// The book the journal at `path` leads to on top of `base` (None: from an empty book)
+pub fn replay_journal(path: &Path, base: Option<&SymbolSnapshot>) -> Result<OrderBook, ReplayError> {
+    let mut r = base.map_or_else(Replayer::empty, Replayer::from_snapshot);
+    r.apply_journal(path)?;
+    Ok(r.into_book())
+}
@@ -0,0 +1,124 @@
+// Primary/backup replication. A primary (`MultiIngestor::start_primary`) streams every sequenced
+// batch after applying it; the standby applies the same batches to identical books, so matching is
+// deterministic and the books stay equal. Periodic checkpoints carry the primary's state hash so
+// the standby can detect divergence. `Standby::promote` turns the standby into a live ingestor that
+// continues each symbol's sequence.
+use crate::{Attachments, MultiIngestor, Options};
+use crossbeam_channel as cb;
+use crossbeam_channel::{Receiver, Sender};
+use match_engine::{Command, OrderBook};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+#[derive(Debug, Clone)]
+pub enum ReplicationMsg {
+    Batch { symbol: String, commands: Vec<Command> },
+    // Primary state after applying everything before `next_seq`
+    Checkpoint { symbol: String, next_seq: u64, hash: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Divergence {
+    UnknownSymbol(String),
+    SeqGap { symbol: String, expected: u64, got: u64 },
+    HashMismatch { symbol: String, next_seq: u64, primary: u64, standby: u64 },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReplicaStatus {
+    pub next_seq: HashMap<String, u64>,
+    pub checkpoints_ok: u64,
+    pub divergences: Vec<Divergence>,
+    pub primary_disconnected: bool,
+}
+
+struct Replica {
+    symbol: String,
+    book: OrderBook,
+    next_seq: u64,
+}
+
+pub struct Standby {
+    ctl: Sender<()>,
+    status: Arc<Mutex<ReplicaStatus>>,
+    handle: JoinHandle<Vec<Replica>>,
+}
+
+impl Standby {
+    // `books` must match the primary's starting books (e.g. restored from the same snapshot)
+    pub fn start(books: Vec<(String, OrderBook)>, rx: Receiver<ReplicationMsg>) -> Self {
+        let (ctl, rx_ctl) = cb::bounded::<()>(1);
+        let status = Arc::new(Mutex::new(ReplicaStatus::default()));
+        let thread_status = status.clone();
+        let handle = std::thread::spawn(move || {
+            let mut replicas: Vec<Replica> = books.into_iter().map(|(symbol, book)| Replica { symbol, book, next_seq: 0 }).collect();
+            let by_symbol: HashMap<String, usize> = replicas.iter().enumerate().map(|(i, r)| (r.symbol.clone(), i)).collect();
+            let mut trades = Vec::new();
+            loop {
+                let msg = cb::select! {
+                    recv(rx) -> msg => msg,
+                    recv(rx_ctl) -> _ => break,
+                };
+                let Ok(msg) = msg else {
+                    thread_status.lock().unwrap().primary_disconnected = true;
+                    // Keep the books until promoted
+                    let _ = rx_ctl.recv();
+                    break;
+                };
+                apply(&mut replicas, &by_symbol, msg, &mut trades, &thread_status);
+            }
+            // Apply whatever the primary managed to send before the stop
+            while let Ok(msg) = rx.try_recv() { apply(&mut replicas, &by_symbol, msg, &mut trades, &thread_status); }
+            replicas
+        });
+        Self { ctl, status, handle }
+    }
+
+    pub fn status(&self) -> ReplicaStatus { self.status.lock().unwrap().clone() }
+
+    // Failover: stop replicating and start serving from the standby books. Sequences continue where
+    // the primary's stream ended; scheduled commands still pending on the primary are not carried over.
+    pub fn promote(self, opts: Options) -> MultiIngestor {
+        let _ = self.ctl.send(());
+        let replicas = self.handle.join().expect("standby thread panicked");
+        let start_seq = replicas.iter().map(|r| (r.symbol.clone(), r.next_seq)).collect();
+        let books = replicas.into_iter().map(|r| (r.symbol, r.book)).collect();
+        MultiIngestor::spawn(books, opts, Attachments { start_seq, ..Attachments::default() })
+    }
+}
+
+fn apply(replicas: &mut [Replica], by_symbol: &HashMap<String, usize>, msg: ReplicationMsg, trades: &mut Vec<match_engine::Trade>, status: &Mutex<ReplicaStatus>) {
+    let symbol = match &msg { ReplicationMsg::Batch { symbol, .. } | ReplicationMsg::Checkpoint { symbol, .. } => symbol };
+    let Some(&i) = by_symbol.get(symbol) else {
+        status.lock().unwrap().divergences.push(Divergence::UnknownSymbol(symbol.clone()));
+        return;
+    };
+    let r = &mut replicas[i];
+    match msg {
+        ReplicationMsg::Batch { mut commands, .. } => {
+            let Some(first) = commands.first().map(Command::seq) else { return };
+            if first != r.next_seq {
+                status.lock().unwrap().divergences.push(Divergence::SeqGap { symbol: r.symbol.clone(), expected: r.next_seq, got: first });
+            }
+            r.next_seq = commands.last().map(Command::seq).unwrap_or(first).wrapping_add(1);
+            let _ = r.book.process_commands_batch_checked_into(&mut commands, trades);
+            trades.clear();
+            // The primary reports these; the standby only has to stay in step
+            r.book.drain_implicit_cancels();
+            r.book.drain_fill_reports();
+            r.book.drain_stop_activations();
+            r.book.drain_repriced();
+            status.lock().unwrap().next_seq.insert(r.symbol.clone(), r.next_seq);
+        }
+        ReplicationMsg::Checkpoint { next_seq, hash, .. } => {
+            let standby = r.book.state_hash();
+            let mut st = status.lock().unwrap();
+            if next_seq == r.next_seq && standby == hash {
+                st.checkpoints_ok += 1;
+            } else {
+                st.divergences.push(Divergence::HashMismatch { symbol: r.symbol.clone(), next_seq, primary: hash, standby });
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+// Regulatory-style trade reporting: one pipe-delimited text file per UTC trading day in the output
+// directory, `trades-YYYYMMDD.psv`, written as `.partial` and renamed once the day is closed (a
+// trade dated on a later day, `end_of_day` or `finish`), so downstream pipelines only ever pick up
+// complete files.
+//
+// HDR|<venue>|<YYYYMMDD>
+// exec_time|venue|symbol|trade_id|buy_order_id|sell_order_id|aggressor|capacity|price|qty
+//   exec_time  YYYY-MM-DDTHH:MM:SS.ffffffZ       aggressor  B|S (taker side)
+//   trade_id   <YYYYMMDD>-<n>, n from 1 per day  capacity   P (principal) | A (agency) | R (riskless principal)
+// TRL|<trade count>|<total qty>
+use crate::calendar::{civil_from_days, MICROS_PER_DAY};
+use match_engine::{Side, Trade};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capacity {
+    Principal,
+    Agency,
+    RisklessPrincipal,
+}
+
+impl Capacity {
+    fn code(self) -> char { match self { Self::Principal => 'P', Self::Agency => 'A', Self::RisklessPrincipal => 'R' } }
+}
+
+fn date_code(day: u64) -> String {
+    let (y, m, d) = civil_from_days(day as i64);
+    format!("{:04}{:02}{:02}", y, m, d)
+}
+
+fn exec_time(ts_micros: u64) -> String {
+    let (y, m, d) = civil_from_days((ts_micros / MICROS_PER_DAY) as i64);
+    let in_day = ts_micros % MICROS_PER_DAY;
+    let secs = in_day / 1_000_000;
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z", y, m, d, secs / 3600, secs / 60 % 60, secs % 60, in_day % 1_000_000)
+}
+
+struct DayFile {
+    day: u64,
+    path: PathBuf,
+    out: BufWriter<File>,
+    count: u64,
+    qty: u64,
+}
+
+pub struct TradeReporter {
+    dir: PathBuf,
+    venue: String,
+    capacity: Capacity,
+    current: Option<DayFile>,
+    closed: Vec<PathBuf>,
+}
+
+impl TradeReporter {
+    // `capacity` is reported on every trade (the engine does not carry it per order)
+    pub fn create(dir: &Path, venue: &str, capacity: Capacity) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(Self { dir: dir.to_path_buf(), venue: venue.to_string(), capacity, current: None, closed: Vec::new() })
+    }
+
+    pub fn record(&mut self, symbol: &str, ts_micros: u64, t: &Trade) -> io::Result<()> {
+        let day = ts_micros / MICROS_PER_DAY;
+        if self.current.as_ref().is_some_and(|f| f.day != day) { self.end_of_day()?; }
+        if self.current.is_none() { self.current = Some(self.open_day(day)?); }
+        let f = self.current.as_mut().unwrap();
+        f.count += 1;
+        f.qty += t.qty;
+        let (buy, sell) = match t.taker_side { Side::Buy => (t.taker_id, t.maker_id), Side::Sell => (t.maker_id, t.taker_id) };
+        let aggressor = match t.taker_side { Side::Buy => 'B', Side::Sell => 'S' };
+        writeln!(f.out, "{}|{}|{}|{}-{}|{}|{}|{}|{}|{}|{}", exec_time(ts_micros), self.venue, symbol, date_code(day), f.count, buy.0, sell.0, aggressor, self.capacity.code(), t.price, t.qty)
+    }
+
+    // Close the current day's file (trailer, fsync, rename); a no-op when no trade was recorded
+    pub fn end_of_day(&mut self) -> io::Result<()> {
+        let Some(mut f) = self.current.take() else { return Ok(()) };
+        writeln!(f.out, "TRL|{}|{}", f.count, f.qty)?;
+        f.out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        let done = f.path.with_extension("");
+        fs::rename(&f.path, &done)?;
+        self.closed.push(done);
+        Ok(())
+    }
+
+    // Completed day files so far, oldest first
+    pub fn closed_files(&self) -> &[PathBuf] { &self.closed }
+
+    pub fn finish(mut self) -> io::Result<Vec<PathBuf>> {
+        self.end_of_day()?;
+        Ok(self.closed)
+    }
+
+    fn open_day(&self, day: u64) -> io::Result<DayFile> {
+        let date = date_code(day);
+        let path = self.dir.join(format!("trades-{}.psv.partial", date));
+        let mut out = BufWriter::new(File::create(&path)?);
+        writeln!(out, "HDR|{}|{}", self.venue, date)?;
+        writeln!(out, "exec_time|venue|symbol|trade_id|buy_order_id|sell_order_id|aggressor|capacity|price|qty")?;
+        Ok(DayFile { day, path, out, count: 0, qty: 0 })
+    }
+}
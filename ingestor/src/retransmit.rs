@@ -0,0 +1,83 @@
+// Pub-sub fan-out of the depth feed (`publisher`) with gap fill. The fan-out thread keeps the last
+// `retain` deltas per symbol before forwarding each message to every subscriber, so anything a
+// subscriber has seen can be resent. A consumer whose `FeedBook` reports a gap asks for
+// `resend(symbol, from_seq, to_seq)`; once the range has aged out it has to wait for the next
+// snapshot instead. Snapshots are not retained (they share the seq of the delta before them).
+use crate::publisher::FeedMsg;
+use crossbeam_channel::{self as cb, Receiver, Sender};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResendError {
+    UnknownSymbol,
+    // Part of the range is older than the oldest retained seq
+    NotRetained { oldest: u64 },
+    // The range goes past the last published seq
+    NotPublished { last: u64 },
+}
+
+// Last N deltas per symbol
+pub struct Retransmitter {
+    retain: usize,
+    symbols: HashMap<String, VecDeque<FeedMsg>>,
+}
+
+impl Retransmitter {
+    pub fn new(retain: usize) -> Self { Self { retain, symbols: HashMap::new() } }
+
+    pub fn record(&mut self, msg: &FeedMsg) {
+        if !matches!(msg, FeedMsg::Delta { .. }) || self.retain == 0 { return; }
+        let kept = self.symbols.entry(msg.symbol().to_string()).or_default();
+        if kept.len() == self.retain { kept.pop_front(); }
+        kept.push_back(msg.clone());
+    }
+
+    // Deltas `from_seq..=to_seq`, in order
+    pub fn resend(&self, symbol: &str, from_seq: u64, to_seq: u64) -> Result<Vec<FeedMsg>, ResendError> {
+        let kept = self.symbols.get(symbol).ok_or(ResendError::UnknownSymbol)?;
+        let (oldest, last) = match (kept.front(), kept.back()) { (Some(a), Some(b)) => (a.seq(), b.seq()), _ => return Err(ResendError::UnknownSymbol) };
+        if from_seq < oldest { return Err(ResendError::NotRetained { oldest }); }
+        if to_seq > last { return Err(ResendError::NotPublished { last }); }
+        Ok(kept.iter().filter(|m| (from_seq..=to_seq).contains(&m.seq())).cloned().collect())
+    }
+}
+
+pub struct FeedFanout {
+    store: Arc<Mutex<Retransmitter>>,
+    subscribers: Arc<Mutex<Vec<Sender<FeedMsg>>>>,
+    handle: JoinHandle<()>,
+}
+
+impl FeedFanout {
+    // Runs until `rx_feed` disconnects (the ingestor stopped)
+    pub fn start(rx_feed: Receiver<FeedMsg>, retain: usize) -> Self {
+        let store = Arc::new(Mutex::new(Retransmitter::new(retain)));
+        let subscribers: Arc<Mutex<Vec<Sender<FeedMsg>>>> = Arc::default();
+        let (s, subs) = (store.clone(), subscribers.clone());
+        let handle = std::thread::spawn(move || {
+            while let Ok(msg) = rx_feed.recv() {
+                s.lock().unwrap().record(&msg);
+                subs.lock().unwrap().retain(|tx| tx.send(msg.clone()).is_ok());
+            }
+            // Disconnect subscribers so their receivers end too
+            subs.lock().unwrap().clear();
+        });
+        Self { store, subscribers, handle }
+    }
+
+    // Messages published from now on, until the feed ends; join with the next snapshot (`FeedBook`)
+    pub fn subscribe(&self) -> Receiver<FeedMsg> {
+        let (tx, rx) = cb::unbounded();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    pub fn resend(&self, symbol: &str, from_seq: u64, to_seq: u64) -> Result<Vec<FeedMsg>, ResendError> {
+        self.store.lock().unwrap().resend(symbol, from_seq, to_seq)
+    }
+
+    // Wait for the feed to end
+    pub fn join(self) { let _ = self.handle.join(); }
+}
@@ -0,0 +1,53 @@
+// Scheduled commands: `RawCommand::At` is held by the worker until the wall clock reaches
+// `activate_at` (microseconds since the Unix epoch) and then sequenced like any other command.
+// Commands due at the same time keep their arrival order.
+use crate::RawCommand;
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub fn now_micros() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_micros() as u64).unwrap_or(0)
+}
+
+#[derive(Default)]
+pub struct Scheduler {
+    pending: BTreeMap<(u64, u64), RawCommand>, // (activate_at, arrival) -> command
+    arrivals: u64,
+}
+
+impl Scheduler {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn len(&self) -> usize { self.pending.len() }
+
+    pub fn is_empty(&self) -> bool { self.pending.is_empty() }
+
+    pub fn push(&mut self, activate_at: u64, cmd: RawCommand) {
+        self.pending.insert((activate_at, self.arrivals), cmd);
+        self.arrivals += 1;
+    }
+
+    // Time left until the earliest pending activation
+    pub fn next_wait(&self, now: u64) -> Option<Duration> {
+        self.pending.keys().next().map(|&(at, _)| Duration::from_micros(at.saturating_sub(now)))
+    }
+
+    // Rewrite a freshly received batch for execution at `now`: park future `At` commands, unwrap due
+    // ones, and put every scheduled command that became due ahead of the new arrivals
+    pub fn admit(&mut self, batch: &mut Vec<RawCommand>, now: u64) {
+        let mut due = Vec::new();
+        while let Some(entry) = self.pending.first_entry() {
+            if entry.key().0 > now { break; }
+            due.push(entry.remove());
+        }
+        let mut i = 0;
+        while i < batch.len() {
+            match batch[i] {
+                RawCommand::At { activate_at, cmd } if activate_at > now => { self.push(activate_at, cmd.into()); batch.remove(i); }
+                RawCommand::At { cmd, .. } => { batch[i] = cmd.into(); i += 1; }
+                _ => i += 1,
+            }
+        }
+        if !due.is_empty() { batch.splice(0..0, due); }
+    }
+}
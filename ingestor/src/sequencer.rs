@@ -0,0 +1,152 @@
+// Deterministic merge of several producers (e.g. one per gateway) into one global order.
+// Every producer stamps its commands with a non-decreasing timestamp and gets its own contiguous
+// sub-sequence. Commands are released in (ts, producer, sub_seq) order: equal timestamps go to the
+// lower producer id, and a producer's own commands keep their submission order. A command is only
+// released once no open producer can still send something that sorts before it, so idle producers
+// must send heartbeats (or be dropped) for the others to make progress. The same per-producer
+// streams therefore always produce the same global order, regardless of thread timing.
+use crate::MultiRawCommand;
+use crossbeam_channel as cb;
+use crossbeam_channel::Sender;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceError {
+    UnknownProducer,
+    TimestampRegressed { last: u64, got: u64 },
+    ProducerClosed,
+}
+
+#[derive(Debug, Clone)]
+pub struct Sequenced {
+    pub ts: u64,
+    pub producer: u32,
+    pub sub_seq: u64,
+    pub cmd: MultiRawCommand,
+}
+
+#[derive(Default)]
+struct ProducerState {
+    queue: VecDeque<Sequenced>,
+    watermark: u64, // no future command from this producer has ts < watermark
+    next_sub_seq: u64,
+    closed: bool,
+}
+
+// Single-threaded merge core; `spawn_sequencer` drives it from producer channels
+pub struct MergeQueue {
+    producers: Vec<ProducerState>,
+}
+
+impl MergeQueue {
+    pub fn new(producers: u32) -> Self {
+        Self { producers: (0..producers).map(|_| ProducerState::default()).collect() }
+    }
+
+    fn state(&mut self, producer: u32) -> Result<&mut ProducerState, SequenceError> {
+        let p = self.producers.get_mut(producer as usize).ok_or(SequenceError::UnknownProducer)?;
+        if p.closed { return Err(SequenceError::ProducerClosed); }
+        Ok(p)
+    }
+
+    pub fn push(&mut self, producer: u32, ts: u64, cmd: MultiRawCommand) -> Result<u64, SequenceError> {
+        let p = self.state(producer)?;
+        if ts < p.watermark { return Err(SequenceError::TimestampRegressed { last: p.watermark, got: ts }); }
+        let sub_seq = p.next_sub_seq;
+        p.next_sub_seq += 1;
+        p.watermark = ts;
+        p.queue.push_back(Sequenced { ts, producer, sub_seq, cmd });
+        Ok(sub_seq)
+    }
+
+    // Promise that this producer sends nothing with a timestamp below `ts`
+    pub fn heartbeat(&mut self, producer: u32, ts: u64) -> Result<(), SequenceError> {
+        let p = self.state(producer)?;
+        if ts < p.watermark { return Err(SequenceError::TimestampRegressed { last: p.watermark, got: ts }); }
+        p.watermark = ts;
+        Ok(())
+    }
+
+    // A closed producer no longer holds back the merge; its queued commands are still released
+    pub fn close(&mut self, producer: u32) {
+        if let Some(p) = self.producers.get_mut(producer as usize) { p.closed = true; }
+    }
+
+    pub fn all_closed(&self) -> bool { self.producers.iter().all(|p| p.closed) }
+
+    pub fn pending(&self) -> usize { self.producers.iter().map(|p| p.queue.len()).sum() }
+
+    // Next command in global order, or None if an open producer might still send an earlier one
+    pub fn pop_ready(&mut self) -> Option<Sequenced> {
+        let (idx, head) = self.producers.iter().enumerate()
+            .filter_map(|(i, p)| p.queue.front().map(|h| (i, (h.ts, h.producer))))
+            .min_by_key(|&(_, key)| key)?;
+        let blocked = self.producers.iter().enumerate().any(|(i, p)| {
+            // An idle open producer's next command would sort as (watermark, i)
+            i != idx && !p.closed && p.queue.is_empty() && (p.watermark, i as u32) < head
+        });
+        if blocked { return None; }
+        self.producers[idx].queue.pop_front()
+    }
+}
+
+enum ProducerMsg {
+    Command { ts: u64, cmd: MultiRawCommand },
+    Heartbeat { ts: u64 },
+    Close,
+}
+
+// Sending side of one producer; dropping it closes the producer
+pub struct ProducerHandle {
+    id: u32,
+    last_ts: u64,
+    tx: Sender<(u32, ProducerMsg)>,
+}
+
+impl ProducerHandle {
+    pub fn id(&self) -> u32 { self.id }
+
+    pub fn send(&mut self, ts: u64, cmd: MultiRawCommand) -> Result<(), SequenceError> {
+        self.advance(ts)?;
+        self.tx.send((self.id, ProducerMsg::Command { ts, cmd })).map_err(|_| SequenceError::ProducerClosed)
+    }
+
+    pub fn heartbeat(&mut self, ts: u64) -> Result<(), SequenceError> {
+        self.advance(ts)?;
+        self.tx.send((self.id, ProducerMsg::Heartbeat { ts })).map_err(|_| SequenceError::ProducerClosed)
+    }
+
+    fn advance(&mut self, ts: u64) -> Result<(), SequenceError> {
+        if ts < self.last_ts { return Err(SequenceError::TimestampRegressed { last: self.last_ts, got: ts }); }
+        self.last_ts = ts;
+        Ok(())
+    }
+}
+
+impl Drop for ProducerHandle {
+    fn drop(&mut self) { let _ = self.tx.send((self.id, ProducerMsg::Close)); }
+}
+
+// Spawn a merge thread forwarding the global order to `out` (typically `MultiIngestor::tx_cmd`),
+// each command tagged with its producer and timestamp (`RawCommand::Sourced`) for the audit log.
+// Do not also send to `out` directly, or the order is no longer reproducible.
+pub fn spawn_sequencer(producers: u32, out: Sender<MultiRawCommand>) -> Vec<ProducerHandle> {
+    let (tx, rx) = cb::unbounded::<(u32, ProducerMsg)>();
+    std::thread::spawn(move || {
+        let mut merge = MergeQueue::new(producers);
+        while !merge.all_closed() {
+            let Ok((id, msg)) = rx.recv() else { break };
+            // Handles already validated timestamps, so the merge core cannot reject these
+            let _ = match msg {
+                ProducerMsg::Command { ts, cmd } => merge.push(id, ts, cmd).map(|_| ()),
+                ProducerMsg::Heartbeat { ts } => merge.heartbeat(id, ts),
+                ProducerMsg::Close => { merge.close(id); Ok(()) }
+            };
+            while let Some(s) = merge.pop_ready() {
+                let cmd = MultiRawCommand { symbol: s.cmd.symbol, cmd: s.cmd.cmd.sourced(s.producer, s.ts) };
+                if out.send(cmd).is_err() { return; }
+            }
+        }
+    });
+    (0..producers).map(|id| ProducerHandle { id, last_ts: 0, tx: tx.clone() }).collect()
+}
@@ -0,0 +1,349 @@
+// Sharded workers: many symbols per worker thread instead of one thread per symbol. Symbols are
+// placed on a consistent-hash ring (`VNODES` points per worker), so adding or removing a worker
+// only moves the symbols whose ring owner changed; `move_symbol` pins a symbol to a chosen worker
+// to relieve an overloaded one (see `worker_depths`).
+//
+// A symbol moves by snapshot hand-off without stopping the ingestor: the router tells the target
+// to expect it and starts routing its commands there at once (the target buffers them), while the
+// source applies everything queued before the hand-off, exports a `SymbolSnapshot` and sends it
+// straight to the target, which rebuilds the book, continues its sequence and replays the buffer.
+// Only that symbol pauses, for the time the source takes to reach the hand-off. State outside the
+// snapshot (hooks, tracked positions, auction orders) does not move with the book. Only
+// `Options::batch_size` and `emit_trades` apply; scheduled commands (`RawCommand::At`) are ignored.
+// Internal errors are reported on the batch's `Progress::faults`; when one stops a batch (fail-fast),
+// that progress covers only the commands up to the fault.
+use crate::snapshot::SymbolSnapshot;
+use crate::{sequence, MultiRawCommand, Options, Progress, RawCommand};
+use crossbeam_channel as cb;
+use crossbeam_channel::{Receiver, Sender};
+use match_engine::{Command, OrderBook, Trade};
+use std::collections::{HashMap, HashSet};
+
+pub const VNODES: usize = 64;
+
+fn hash(bytes: &[u8]) -> u64 {
+    // FNV-1a with a final avalanche so short, similar keys spread over the ring
+    let mut h = bytes.iter().fold(0xcbf2_9ce4_8422_2325u64, |h, &b| (h ^ b as u64).wrapping_mul(0x100_0000_01b3));
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    h ^ (h >> 33)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HashRing {
+    points: Vec<(u64, usize)>, // sorted by hash
+}
+
+impl HashRing {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn add(&mut self, worker: usize) {
+        self.points.extend((0..VNODES).map(|v| (hash(format!("worker-{}-{}", worker, v).as_bytes()), worker)));
+        self.points.sort_unstable();
+    }
+
+    pub fn remove(&mut self, worker: usize) { self.points.retain(|&(_, w)| w != worker); }
+
+    pub fn workers(&self) -> usize { self.points.len() / VNODES }
+
+    // First point clockwise from the key's hash
+    pub fn owner(&self, key: &str) -> Option<usize> {
+        let h = hash(key.as_bytes());
+        let i = self.points.partition_point(|&(p, _)| p < h);
+        self.points.get(i).or(self.points.first()).map(|&(_, w)| w)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    pub symbol: String,
+    pub from: usize,
+    pub to: usize,
+}
+
+enum WorkerMsg {
+    Cmd(String, RawCommand),
+    Adopt(String, Box<OrderBook>),
+    Expect(String),
+    Import(SymbolSnapshot),
+    // Hand the symbol to `to` and/or return its final snapshot through `reply`
+    Export { symbol: String, to: Option<Sender<WorkerMsg>>, reply: Option<Sender<Option<SymbolSnapshot>>> },
+    Stop,
+}
+
+struct Shard {
+    book: OrderBook,
+    seq: u64,
+}
+
+struct Worker {
+    shards: HashMap<String, Shard>,
+    pending: HashMap<String, Vec<RawCommand>>, // commands for symbols still being handed over
+    deferred: HashMap<String, WorkerMsg>,      // exports of symbols not imported yet
+    stopping: bool,
+    run_symbol: String,
+    run: Vec<RawCommand>,
+    batch: Vec<Command>,
+    trades_buf: Vec<Trade>,
+    opts: Options,
+    tx_trade: Sender<(String, Trade)>,
+    tx_progress: Sender<Progress>,
+}
+
+impl Worker {
+    fn push(&mut self, symbol: String, cmd: RawCommand) {
+        if symbol != self.run_symbol { self.flush(); self.run_symbol = symbol; }
+        self.run.push(cmd);
+        if self.run.len() >= self.opts.batch_size { self.flush(); }
+    }
+
+    // Apply the current run of consecutive commands for one symbol as a batch
+    fn flush(&mut self) {
+        if self.run.is_empty() { return; }
+        self.run.retain(|c| !matches!(c, RawCommand::At { .. }));
+        if let Some(buf) = self.pending.get_mut(&self.run_symbol) { buf.append(&mut self.run); return; }
+        let Some(shard) = self.shards.get_mut(&self.run_symbol) else { self.run.clear(); return };
+        self.batch.clear();
+        for rc in self.run.drain(..) {
+            self.batch.push(sequence(rc, shard.seq));
+            shard.seq = shard.seq.wrapping_add(1);
+        }
+        if self.batch.is_empty() { return; }
+        let result = shard.book.process_commands_batch_presorted_unchecked(&self.batch, &mut self.trades_buf);
+        let faults = shard.book.drain_faults();
+        // Fail-fast: the book went cancel-only at the fault and the commands behind it were not applied
+        let applied = match (result, faults.last()) {
+            (Err(_), Some(fault)) => self.batch.iter().position(|c| c.seq() == fault.seq).map_or(self.batch.len(), |i| i + 1),
+            _ => self.batch.len(),
+        };
+        let trades = self.trades_buf.len();
+        if self.opts.emit_trades {
+            for t in self.trades_buf.drain(..) { let _ = self.tx_trade.send((self.run_symbol.clone(), t)); }
+        } else {
+            self.trades_buf.clear();
+        }
+        let _ = self.tx_progress.send(Progress { symbol: self.run_symbol.clone(), commands: applied, last_seq: self.batch[applied - 1].seq(), trades, faults, cancels: shard.book.drain_implicit_cancels(), fill_reports: shard.book.drain_fill_reports(), stop_activations: shard.book.drain_stop_activations(), repriced: shard.book.drain_repriced(), stages: None, coalescing: None });
+    }
+
+    fn control(&mut self, msg: WorkerMsg) {
+        self.flush();
+        match msg {
+            WorkerMsg::Cmd(symbol, cmd) => self.push(symbol, cmd),
+            WorkerMsg::Adopt(symbol, book) => { self.shards.insert(symbol, Shard { book: *book, seq: 0 }); }
+            WorkerMsg::Expect(symbol) => { self.pending.insert(symbol, Vec::new()); }
+            WorkerMsg::Import(snap) => {
+                let buffered = self.pending.remove(&snap.symbol).unwrap_or_default();
+                self.shards.insert(snap.symbol.clone(), Shard { book: OrderBook::from_snapshot(&snap.book), seq: snap.next_seq });
+                let symbol = snap.symbol.clone();
+                for cmd in buffered { self.push(symbol.clone(), cmd); }
+                self.flush();
+                if let Some(export) = self.deferred.remove(&symbol) { self.control(export); }
+            }
+            WorkerMsg::Export { ref symbol, .. } if self.pending.contains_key(symbol) => { self.deferred.insert(symbol.clone(), msg); }
+            WorkerMsg::Export { symbol, to, reply } => {
+                let snap = self.shards.remove(&symbol).map(|s| SymbolSnapshot { symbol, next_seq: s.seq, book: s.book.snapshot() });
+                if let (Some(to), Some(snap)) = (to, &snap) { let _ = to.send(WorkerMsg::Import(snap.clone())); }
+                if let Some(reply) = reply { let _ = reply.send(snap); }
+            }
+            WorkerMsg::Stop => self.stopping = true,
+        }
+    }
+
+    fn run(mut self, rx: Receiver<WorkerMsg>) {
+        while let Ok(first) = rx.recv() {
+            for msg in std::iter::once(first).chain(rx.try_iter().take(self.opts.batch_size.saturating_sub(1))) {
+                match msg {
+                    WorkerMsg::Cmd(symbol, cmd) => self.push(symbol, cmd),
+                    other => self.control(other),
+                }
+                // A removed worker still completes hand-offs that were passing through it
+                if self.stopping && self.pending.is_empty() { return; }
+            }
+            self.flush();
+        }
+    }
+}
+
+enum Control {
+    AddSymbol { symbol: String, book: Box<OrderBook>, reply: Sender<usize> },
+    RemoveSymbol { symbol: String, reply: Sender<Option<SymbolSnapshot>> },
+    AddWorker { reply: Sender<(usize, Vec<Migration>)> },
+    RemoveWorker { worker: usize, reply: Sender<Option<Vec<Migration>>> },
+    MoveSymbol { symbol: String, worker: usize, reply: Sender<Option<Migration>> },
+    Assignments { reply: Sender<Vec<(String, usize)>> },
+    Depths { reply: Sender<Vec<(usize, usize)>> },
+}
+
+struct Router {
+    workers: Vec<Option<Sender<WorkerMsg>>>, // by worker id; None once removed
+    ring: HashRing,
+    assign: HashMap<String, usize>,
+    pinned: HashSet<String>, // placed by `move_symbol` rather than the ring
+    opts: Options,
+    tx_trade: Sender<(String, Trade)>,
+    tx_progress: Sender<Progress>,
+}
+
+impl Router {
+    fn spawn_worker(&mut self) -> usize {
+        let (tx, rx) = cb::unbounded::<WorkerMsg>();
+        let worker = Worker {
+            shards: HashMap::new(), pending: HashMap::new(), deferred: HashMap::new(), stopping: false, run_symbol: String::new(), run: Vec::with_capacity(self.opts.batch_size),
+            batch: Vec::with_capacity(self.opts.batch_size), trades_buf: Vec::with_capacity(self.opts.batch_size * 2),
+            opts: self.opts, tx_trade: self.tx_trade.clone(), tx_progress: self.tx_progress.clone(),
+        };
+        std::thread::spawn(move || worker.run(rx));
+        self.workers.push(Some(tx));
+        let id = self.workers.len() - 1;
+        self.ring.add(id);
+        id
+    }
+
+    fn send(&self, worker: usize, msg: WorkerMsg) {
+        if let Some(Some(tx)) = self.workers.get(worker) { let _ = tx.send(msg); }
+    }
+
+    fn add_symbol(&mut self, symbol: String, book: Box<OrderBook>) -> usize {
+        let worker = self.ring.owner(&symbol).expect("at least one worker");
+        self.send(worker, WorkerMsg::Adopt(symbol.clone(), book));
+        self.assign.insert(symbol, worker);
+        worker
+    }
+
+    fn migrate(&mut self, symbol: &str, to: usize) -> Option<Migration> {
+        let from = *self.assign.get(symbol)?;
+        let target = self.workers.get(to)?.clone()?;
+        if from == to { return None; }
+        let _ = target.send(WorkerMsg::Expect(symbol.to_string()));
+        self.send(from, WorkerMsg::Export { symbol: symbol.to_string(), to: Some(target), reply: None });
+        self.assign.insert(symbol.to_string(), to);
+        Some(Migration { symbol: symbol.to_string(), from, to })
+    }
+
+    // Move every symbol not on its ring owner (pinned symbols stay)
+    fn rebalance(&mut self) -> Vec<Migration> {
+        let mut symbols: Vec<String> = self.assign.keys().filter(|s| !self.pinned.contains(*s)).cloned().collect();
+        symbols.sort();
+        symbols.into_iter().filter_map(|s| {
+            let owner = self.ring.owner(&s)?;
+            self.migrate(&s, owner)
+        }).collect()
+    }
+
+    fn remove_worker(&mut self, worker: usize) -> Option<Vec<Migration>> {
+        if self.ring.workers() < 2 || !self.workers.get(worker).is_some_and(|w| w.is_some()) { return None; }
+        self.ring.remove(worker);
+        let mut moved = Vec::new();
+        let mut symbols: Vec<String> = self.assign.iter().filter(|&(_, &w)| w == worker).map(|(s, _)| s.clone()).collect();
+        symbols.sort();
+        for s in symbols {
+            self.pinned.remove(&s);
+            let owner = self.ring.owner(&s)?;
+            moved.extend(self.migrate(&s, owner));
+        }
+        // Queued after the exports, so the worker hands everything over before it exits
+        self.send(worker, WorkerMsg::Stop);
+        self.workers[worker] = None;
+        Some(moved)
+    }
+
+    fn control(&mut self, ctl: Control) {
+        match ctl {
+            Control::AddSymbol { symbol, book, reply } => { let _ = reply.send(self.add_symbol(symbol, book)); }
+            Control::RemoveSymbol { symbol, reply } => {
+                self.pinned.remove(&symbol);
+                match self.assign.remove(&symbol) {
+                    Some(worker) => self.send(worker, WorkerMsg::Export { symbol, to: None, reply: Some(reply) }),
+                    None => { let _ = reply.send(None); }
+                }
+            }
+            Control::AddWorker { reply } => {
+                let id = self.spawn_worker();
+                let _ = reply.send((id, self.rebalance()));
+            }
+            Control::RemoveWorker { worker, reply } => { let _ = reply.send(self.remove_worker(worker)); }
+            Control::MoveSymbol { symbol, worker, reply } => {
+                let moved = self.migrate(&symbol, worker);
+                if self.assign.get(&symbol) == Some(&worker) { self.pinned.insert(symbol); }
+                let _ = reply.send(moved);
+            }
+            Control::Assignments { reply } => {
+                let mut all: Vec<(String, usize)> = self.assign.iter().map(|(s, &w)| (s.clone(), w)).collect();
+                all.sort();
+                let _ = reply.send(all);
+            }
+            Control::Depths { reply } => {
+                let _ = reply.send(self.workers.iter().enumerate().filter_map(|(i, w)| w.as_ref().map(|tx| (i, tx.len()))).collect());
+            }
+        }
+    }
+}
+
+pub struct ShardedIngestor {
+    pub tx_cmd: Sender<MultiRawCommand>,
+    pub rx_trade: Receiver<(String, Trade)>,
+    pub rx_progress: Receiver<Progress>,
+    ctl: Sender<Control>,
+}
+
+impl ShardedIngestor {
+    // Panics on `opts.adaptive_coalescing`: sharded workers batch with the fixed settings
+    pub fn start(books: Vec<(String, OrderBook)>, workers: usize, opts: Options) -> Self {
+        assert!(opts.adaptive_coalescing.is_none(), "adaptive coalescing is not supported by ShardedIngestor");
+        let (tx_cmd, rx_cmd) = cb::unbounded::<MultiRawCommand>();
+        let (tx_trade, rx_trade) = cb::unbounded::<(String, Trade)>();
+        let (tx_progress, rx_progress) = cb::unbounded::<Progress>();
+        let (ctl, rx_ctl) = cb::unbounded::<Control>();
+        let mut router = Router { workers: Vec::new(), ring: HashRing::new(), assign: HashMap::new(), pinned: HashSet::new(), opts, tx_trade, tx_progress };
+        for _ in 0..workers.max(1) { router.spawn_worker(); }
+        for (symbol, book) in books { router.add_symbol(symbol, Box::new(book)); }
+        std::thread::spawn(move || loop {
+            // Commands and control share one thread, so a hand-off is ordered after every command
+            // routed before it
+            cb::select! {
+                recv(rx_cmd) -> msg => match msg {
+                    Ok(m) => if let Some(&w) = router.assign.get(&m.symbol) { router.send(w, WorkerMsg::Cmd(m.symbol, m.cmd)); },
+                    Err(_) => break,
+                },
+                recv(rx_ctl) -> msg => match msg {
+                    Ok(c) => router.control(c),
+                    Err(_) => break,
+                },
+            }
+        });
+        Self { tx_cmd, rx_trade, rx_progress, ctl }
+    }
+
+    fn ask<T>(&self, make: impl FnOnce(Sender<T>) -> Control) -> Option<T> {
+        let (reply, rx) = cb::bounded(1);
+        self.ctl.send(make(reply)).ok()?;
+        rx.recv().ok()
+    }
+
+    // Returns the worker the symbol was placed on
+    pub fn add_symbol(&self, symbol: &str, book: OrderBook) -> usize {
+        self.ask(|reply| Control::AddSymbol { symbol: symbol.to_string(), book: Box::new(book), reply }).expect("router running")
+    }
+
+    // Stops routing the symbol and returns its book after every command routed before the call
+    pub fn remove_symbol(&self, symbol: &str) -> Option<SymbolSnapshot> {
+        self.ask(|reply| Control::RemoveSymbol { symbol: symbol.to_string(), reply }).flatten()
+    }
+
+    // Spawns a worker and moves the symbols the ring now places on it
+    pub fn add_worker(&self) -> (usize, Vec<Migration>) { self.ask(|reply| Control::AddWorker { reply }).expect("router running") }
+
+    // Moves the worker's symbols away and stops it; None for an unknown worker or the last one
+    pub fn remove_worker(&self, worker: usize) -> Option<Vec<Migration>> { self.ask(|reply| Control::RemoveWorker { worker, reply }).flatten() }
+
+    // Pins the symbol to `worker` until it is removed or the worker goes away
+    pub fn move_symbol(&self, symbol: &str, worker: usize) -> Option<Migration> {
+        self.ask(|reply| Control::MoveSymbol { symbol: symbol.to_string(), worker, reply }).flatten()
+    }
+
+    // (symbol, worker), sorted by symbol
+    pub fn assignments(&self) -> Vec<(String, usize)> { self.ask(|reply| Control::Assignments { reply }).unwrap_or_default() }
+
+    // (worker, queued messages) for live workers
+    pub fn worker_depths(&self) -> Vec<(usize, usize)> { self.ask(|reply| Control::Depths { reply }).unwrap_or_default() }
+}
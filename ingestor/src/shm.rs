@@ -0,0 +1,323 @@
+// Shared-memory transport for an out-of-process client (e.g. a C++ feed handler): one file-backed
+// region (typically under /dev/shm) holding two single-producer/single-consumer rings, commands from
+// the client and events to it. No sockets and no syscalls on the data path.
+//
+// Layout (little-endian, every counter an 8-byte atomic on its own 64-byte line):
+//   0    magic "MESHM001", version u32, slot size u32 (64), capacity u64 (slots per ring, power of two)
+//   64   client_pid   registration: 0 = free, CAS to the client's pid to attach, store 0 to detach
+//   128  session      incremented by every successful attach
+//   192  acked_seq    highest client_seq the server has consumed (+1; 0 = none)
+//   256  gaps         commands whose client_seq was not acked_seq (server counts, then resyncs)
+//   320  cmd head (client writes)   384 cmd tail (server writes)
+//   448  evt head (server writes)   512 evt tail (client writes)
+//   576  command slots, then event slots, `capacity` * 64 bytes each
+// A ring is full when head - tail == capacity; the writer fills slot `head % capacity`, then
+// publishes with a release store of head + 1. Readers acquire-load head.
+//
+// Command slot: client_seq u64 | tag u8 (1 limit, 2 market, 3 cancel, 4 reprice, 5 cancel by client id, 6 reduce) | side u8 | symbol_len u8 | pad
+//               | price u64 | qty_or_id u64 | symbol [u8; 32]   (reprice: new price, order id;
+//               cancel: side 1 when the account in price is checked as the owner;
+//               cancel by client id: account, client order id; reduce: qty delta, order id)
+// Event slot:   kind u8 (1 trade) | symbol_len u8 | taker side u8 | pad | conditions u16 | pad[2] | taker u64 | maker u64 | price u64 | qty u64
+//               | symbol [u8; 24]   (correlation ids, accounts and maker residuals are not carried; they decode as 0)
+use crate::{MultiIngestor, MultiRawCommand, RawCommand};
+use match_engine::{AccountId, ClientOrderId, OrderId, Side, Trade, TradeConditions, TradeId};
+use memmap2::MmapMut;
+use std::fs::OpenOptions;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const MAGIC: &[u8; 8] = b"MESHM001";
+const VERSION: u32 = 1;
+const SLOT: usize = 64;
+const OFF_CLIENT_PID: usize = 64;
+const OFF_SESSION: usize = 128;
+const OFF_ACKED: usize = 192;
+const OFF_GAPS: usize = 256;
+const OFF_CMD_HEAD: usize = 320;
+const OFF_CMD_TAIL: usize = 384;
+const OFF_EVT_HEAD: usize = 448;
+const OFF_EVT_TAIL: usize = 512;
+const OFF_SLOTS: usize = 576;
+const CMD_SYMBOL_MAX: usize = 32;
+const EVT_SYMBOL_MAX: usize = 24;
+
+#[derive(Debug, Clone)]
+pub struct ShmCommand {
+    pub client_seq: u64,
+    pub cmd: MultiRawCommand,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShmEvent {
+    pub symbol: String,
+    pub trade: Trade,
+}
+
+struct Region {
+    map: MmapMut,
+    capacity: u64,
+}
+
+// SAFETY: all cross-thread access to the mapping goes through atomics or slots handed over by them
+unsafe impl Send for Region {}
+unsafe impl Sync for Region {}
+
+impl Region {
+    fn open(path: &Path, create_capacity: Option<u64>) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).create(create_capacity.is_some()).truncate(false).open(path)?;
+        if let Some(capacity) = create_capacity {
+            let len = region_len(capacity).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "capacity must be a non-zero power of two that fits in memory"))?;
+            file.set_len(len as u64)?;
+        }
+        // SAFETY: the region is shared on purpose; every concurrently written field is accessed atomically
+        let mut map = unsafe { MmapMut::map_mut(&file)? };
+        if let Some(capacity) = create_capacity {
+            map.fill(0);
+            map[8..12].copy_from_slice(&VERSION.to_le_bytes());
+            map[12..16].copy_from_slice(&(SLOT as u32).to_le_bytes());
+            map[16..24].copy_from_slice(&capacity.to_le_bytes());
+            map[..8].copy_from_slice(MAGIC);
+            map.flush()?;
+        }
+        if map.len() < OFF_SLOTS || &map[..8] != MAGIC { return Err(io::Error::new(io::ErrorKind::InvalidData, "not a shm transport region")); }
+        if u32::from_le_bytes(map[8..12].try_into().unwrap()) != VERSION { return Err(io::Error::new(io::ErrorKind::InvalidData, "unsupported shm version")); }
+        // The capacity comes from the file: a corrupt one must not wrap the size check or the slot mask
+        let capacity = u64::from_le_bytes(map[16..24].try_into().unwrap());
+        let len = region_len(capacity).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "bad shm capacity"))?;
+        if map.len() < len { return Err(io::Error::new(io::ErrorKind::InvalidData, "truncated shm region")); }
+        Ok(Self { map, capacity })
+    }
+
+    fn counter(&self, off: usize) -> &AtomicU64 {
+        // SAFETY: `off` is 8-byte aligned inside the page-aligned mapping
+        unsafe { &*(self.map.as_ptr().add(off) as *const AtomicU64) }
+    }
+
+    fn slot_ptr(&self, ring: usize, index: u64) -> *mut u8 {
+        let off = OFF_SLOTS + (ring * self.capacity as usize + (index & (self.capacity - 1)) as usize) * SLOT;
+        // SAFETY: in bounds (checked at open); only the side that owns the slot per head/tail writes it
+        unsafe { (self.map.as_ptr() as *mut u8).add(off) }
+    }
+
+    // Single-producer push: false when the ring is full
+    fn push(&self, ring: usize, head_off: usize, tail_off: usize, slot: &[u8; SLOT]) -> bool {
+        let head = self.counter(head_off).load(Ordering::Relaxed);
+        if head - self.counter(tail_off).load(Ordering::Acquire) >= self.capacity { return false; }
+        // SAFETY: slot `head` is not visible to the consumer until head is published below
+        unsafe { std::ptr::copy_nonoverlapping(slot.as_ptr(), self.slot_ptr(ring, head), SLOT) };
+        self.counter(head_off).store(head + 1, Ordering::Release);
+        true
+    }
+
+    fn pop(&self, ring: usize, head_off: usize, tail_off: usize) -> Option<[u8; SLOT]> {
+        let tail = self.counter(tail_off).load(Ordering::Relaxed);
+        if tail == self.counter(head_off).load(Ordering::Acquire) { return None; }
+        let mut slot = [0u8; SLOT];
+        // SAFETY: the producer published this slot and will not reuse it until tail moves past it
+        unsafe { std::ptr::copy_nonoverlapping(self.slot_ptr(ring, tail), slot.as_mut_ptr(), SLOT) };
+        self.counter(tail_off).store(tail + 1, Ordering::Release);
+        Some(slot)
+    }
+}
+
+// Bytes a region of two rings of `capacity` slots needs; None unless capacity is a non-zero power of two
+// and the size fits in usize
+fn region_len(capacity: u64) -> Option<usize> {
+    if !capacity.is_power_of_two() { return None; }
+    usize::try_from(capacity).ok()?.checked_mul(2 * SLOT)?.checked_add(OFF_SLOTS)
+}
+
+fn u64_at(b: &[u8], off: usize) -> u64 { u64::from_le_bytes(b[off..off + 8].try_into().unwrap()) }
+
+fn side_byte(side: Side) -> u8 { match side { Side::Buy => 0, Side::Sell => 1 } }
+
+fn encode_command(client_seq: u64, cmd: &MultiRawCommand) -> io::Result<[u8; SLOT]> {
+    let symbol = cmd.symbol.as_bytes();
+    if symbol.len() > CMD_SYMBOL_MAX { return Err(io::Error::new(io::ErrorKind::InvalidInput, "symbol too long for shm slot")); }
+    let (tag, side, price, qty_or_id) = match cmd.cmd {
+        RawCommand::Limit { side, price, qty } => (1u8, side_byte(side), price, qty),
+        RawCommand::Market { side, qty } => (2, side_byte(side), 0, qty),
+        RawCommand::Cancel { id, account } => (3, account.is_some() as u8, account.map_or(0, |a| a.0), id.0),
+        RawCommand::Reprice { id, new_price } => (4, 0, new_price, id.0),
+        RawCommand::CancelClient { account, client_id } => (5, 0, account.0, client_id.0),
+        RawCommand::Reduce { id, qty_delta } => (6, 0, qty_delta, id.0),
+        RawCommand::At { .. } | RawCommand::Sourced { .. } | RawCommand::Amend { .. } | RawCommand::Submit(_) | RawCommand::Session(_) | RawCommand::Reference(_) | RawCommand::EndOfDay | RawCommand::Resume => return Err(io::Error::new(io::ErrorKind::InvalidInput, "only limit, market, cancels, reprice and reduce are supported over shm")),
+    };
+    let mut s = [0u8; SLOT];
+    s[0..8].copy_from_slice(&client_seq.to_le_bytes());
+    s[8] = tag;
+    s[9] = side;
+    s[10] = symbol.len() as u8;
+    s[16..24].copy_from_slice(&price.to_le_bytes());
+    s[24..32].copy_from_slice(&qty_or_id.to_le_bytes());
+    s[32..32 + symbol.len()].copy_from_slice(symbol);
+    Ok(s)
+}
+
+fn decode_command(s: &[u8; SLOT]) -> Option<ShmCommand> {
+    let side = if s[9] == 0 { Side::Buy } else { Side::Sell };
+    let (price, qty_or_id) = (u64_at(s, 16), u64_at(s, 24));
+    let cmd = match s[8] {
+        1 => RawCommand::Limit { side, price, qty: qty_or_id },
+        2 => RawCommand::Market { side, qty: qty_or_id },
+        3 => RawCommand::Cancel { id: OrderId(qty_or_id), account: (s[9] == 1).then_some(AccountId(price)) },
+        4 => RawCommand::Reprice { id: OrderId(qty_or_id), new_price: price },
+        5 => RawCommand::CancelClient { account: AccountId(price), client_id: ClientOrderId(qty_or_id) },
+        6 => RawCommand::Reduce { id: OrderId(qty_or_id), qty_delta: price },
+        _ => return None,
+    };
+    let len = (s[10] as usize).min(CMD_SYMBOL_MAX);
+    let symbol = std::str::from_utf8(&s[32..32 + len]).ok()?.to_string();
+    Some(ShmCommand { client_seq: u64_at(s, 0), cmd: MultiRawCommand { symbol, cmd } })
+}
+
+fn encode_event(symbol: &str, t: &Trade) -> [u8; SLOT] {
+    // Longer symbols are truncated; clients route by the prefix
+    let sym = &symbol.as_bytes()[..symbol.len().min(EVT_SYMBOL_MAX)];
+    let mut s = [0u8; SLOT];
+    s[0] = 1;
+    s[1] = sym.len() as u8;
+    s[2] = matches!(t.taker_side, Side::Sell) as u8;
+    s[4..6].copy_from_slice(&t.conditions.0.to_le_bytes());
+    for (i, v) in [t.taker_id.0, t.maker_id.0, t.price, t.qty].into_iter().enumerate() {
+        s[8 + i * 8..16 + i * 8].copy_from_slice(&v.to_le_bytes());
+    }
+    s[40..40 + sym.len()].copy_from_slice(sym);
+    s
+}
+
+fn decode_event(s: &[u8; SLOT]) -> Option<ShmEvent> {
+    if s[0] != 1 { return None; }
+    let len = (s[1] as usize).min(EVT_SYMBOL_MAX);
+    let symbol = String::from_utf8_lossy(&s[40..40 + len]).into_owned();
+    let trade = Trade { trade_id: TradeId(0), taker_id: OrderId(u64_at(s, 8)), maker_id: OrderId(u64_at(s, 16)), price: u64_at(s, 24), qty: u64_at(s, 32), taker_side: if s[2] == 0 { Side::Buy } else { Side::Sell }, taker_correlation: 0, maker_correlation: 0, taker_account: AccountId(0), maker_account: AccountId(0), maker_remaining: 0, maker_fully_filled: false, ts: 0, conditions: TradeConditions(u16::from_le_bytes([s[4], s[5]])), output_seq: 0 };
+    Some(ShmEvent { symbol, trade })
+}
+
+// Engine side: owns the region, consumes commands and publishes events
+pub struct ShmServer {
+    region: Arc<Region>,
+}
+
+impl ShmServer {
+    // Create (or reset) the region; `capacity` slots per ring, a power of two
+    pub fn create(path: &Path, capacity: u64) -> io::Result<Self> {
+        Ok(Self { region: Arc::new(Region::open(path, Some(capacity))?) })
+    }
+
+    pub fn client_pid(&self) -> Option<u64> {
+        Some(self.region.counter(OFF_CLIENT_PID).load(Ordering::Acquire)).filter(|&p| p != 0)
+    }
+
+    pub fn gaps(&self) -> u64 { self.region.counter(OFF_GAPS).load(Ordering::Relaxed) }
+
+    pub fn poll_command(&self) -> Option<ShmCommand> { poll_command(&self.region) }
+
+    // false when the client is not draining events fast enough
+    pub fn publish_event(&self, symbol: &str, trade: &Trade) -> bool {
+        self.region.push(1, OFF_EVT_HEAD, OFF_EVT_TAIL, &encode_event(symbol, trade))
+    }
+
+    // Forward client commands into `ig` and its trades back to the client until the bridge is dropped.
+    // Takes over `ig.rx_trade`; don't consume trades elsewhere meanwhile.
+    pub fn bridge(self, ig: &MultiIngestor) -> ShmBridge {
+        let stop = Arc::new(AtomicBool::new(false));
+        let (region, tx_cmd, stop_in) = (self.region.clone(), ig.tx_cmd.clone(), stop.clone());
+        let inbound = std::thread::spawn(move || {
+            while !stop_in.load(Ordering::Relaxed) {
+                match poll_command(&region) {
+                    Some(c) => { if tx_cmd.send(c.cmd).is_err() { break; } }
+                    None => std::thread::sleep(Duration::from_micros(50)),
+                }
+            }
+        });
+        let (region, rx_trade, stop_out) = (self.region, ig.rx_trade.clone(), stop.clone());
+        let outbound = std::thread::spawn(move || {
+            while !stop_out.load(Ordering::Relaxed) {
+                let Ok((symbol, trade)) = rx_trade.recv_timeout(Duration::from_millis(10)) else { continue };
+                let slot = encode_event(&symbol, &trade);
+                // Backpressure: wait for the client rather than dropping events
+                while !region.push(1, OFF_EVT_HEAD, OFF_EVT_TAIL, &slot) {
+                    if stop_out.load(Ordering::Relaxed) { return; }
+                    std::thread::sleep(Duration::from_micros(50));
+                }
+            }
+        });
+        ShmBridge { stop, threads: vec![inbound, outbound] }
+    }
+}
+
+fn poll_command(region: &Region) -> Option<ShmCommand> {
+    loop {
+        let slot = region.pop(0, OFF_CMD_HEAD, OFF_CMD_TAIL)?;
+        let Some(c) = decode_command(&slot) else { region.counter(OFF_GAPS).fetch_add(1, Ordering::Relaxed); continue };
+        let acked = region.counter(OFF_ACKED);
+        if c.client_seq != acked.load(Ordering::Relaxed) { region.counter(OFF_GAPS).fetch_add(1, Ordering::Relaxed); }
+        acked.store(c.client_seq + 1, Ordering::Release);
+        return Some(c);
+    }
+}
+
+pub struct ShmBridge {
+    stop: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl Drop for ShmBridge {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        for t in self.threads.drain(..) { let _ = t.join(); }
+    }
+}
+
+// Client side; the reference implementation of the protocol for non-Rust clients
+pub struct ShmClient {
+    region: Region,
+    pid: u64,
+    next_seq: u64,
+}
+
+impl ShmClient {
+    // Register as the region's single client; fails with AddrInUse if another client is attached.
+    // Numbering resumes after the last command the server acknowledged.
+    pub fn attach(path: &Path) -> io::Result<Self> {
+        let region = Region::open(path, None)?;
+        let pid = std::process::id() as u64;
+        if region.counter(OFF_CLIENT_PID).compare_exchange(0, pid, Ordering::AcqRel, Ordering::Acquire).is_err() {
+            return Err(io::Error::new(io::ErrorKind::AddrInUse, "shm region already has a client"));
+        }
+        region.counter(OFF_SESSION).fetch_add(1, Ordering::AcqRel);
+        // Commands still queued from a previous session are numbered after the acked ones
+        let queued = region.counter(OFF_CMD_HEAD).load(Ordering::Acquire) - region.counter(OFF_CMD_TAIL).load(Ordering::Acquire);
+        let next_seq = region.counter(OFF_ACKED).load(Ordering::Acquire) + queued;
+        Ok(Self { region, pid, next_seq })
+    }
+
+    pub fn session(&self) -> u64 { self.region.counter(OFF_SESSION).load(Ordering::Acquire) }
+
+    // Commands below this client_seq have been taken by the server
+    pub fn acked_seq(&self) -> u64 { self.region.counter(OFF_ACKED).load(Ordering::Acquire) }
+
+    // Returns the command's client_seq; WouldBlock when the command ring is full
+    pub fn publish(&mut self, cmd: &MultiRawCommand) -> io::Result<u64> {
+        let slot = encode_command(self.next_seq, cmd)?;
+        if !self.region.push(0, OFF_CMD_HEAD, OFF_CMD_TAIL, &slot) { return Err(io::ErrorKind::WouldBlock.into()); }
+        self.next_seq += 1;
+        Ok(self.next_seq - 1)
+    }
+
+    pub fn poll_event(&self) -> Option<ShmEvent> {
+        self.region.pop(1, OFF_EVT_HEAD, OFF_EVT_TAIL).and_then(|s| decode_event(&s))
+    }
+}
+
+impl Drop for ShmClient {
+    fn drop(&mut self) {
+        let _ = self.region.counter(OFF_CLIENT_PID).compare_exchange(self.pid, 0, Ordering::AcqRel, Ordering::Acquire);
+    }
+}
@@ -0,0 +1,79 @@
+use match_engine::BookSnapshot;
+use std::collections::HashMap;
+use std::io;
+use std::sync::Mutex;
+
+// Book state of one symbol after all commands with seq < next_seq were applied.
+// Recovery loads the snapshot and replays only journal entries with seq >= next_seq.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolSnapshot {
+    pub symbol: String,
+    pub next_seq: u64,
+    pub book: BookSnapshot,
+}
+
+// Portable per-symbol file name: anything outside [A-Za-z0-9_.-] is escaped as %XX
+pub(crate) fn symbol_file_name(symbol: &str, ext: &str) -> String {
+    let mut name = String::with_capacity(symbol.len() + ext.len() + 1);
+    for b in symbol.bytes() {
+        if b.is_ascii_alphanumeric() || b == b'_' || b == b'-' || b == b'.' { name.push(b as char); } else { name.push_str(&format!("%{:02X}", b)); }
+    }
+    name.push('.');
+    name.push_str(ext);
+    name
+}
+
+// Inverse of `symbol_file_name`; None for other files
+pub(crate) fn symbol_from_file_name(name: &str, ext: &str) -> Option<String> {
+    let escaped = name.strip_suffix(ext)?.strip_suffix('.')?;
+    let mut bytes = Vec::with_capacity(escaped.len());
+    let mut it = escaped.bytes();
+    while let Some(b) = it.next() {
+        if b != b'%' { bytes.push(b); continue; }
+        let hex = [it.next()?, it.next()?];
+        bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+    }
+    String::from_utf8(bytes).ok()
+}
+
+// Symbols of the `*.ext` files in `dir`, sorted; empty when `dir` does not exist
+pub(crate) fn symbols_in(dir: &std::path::Path, ext: &str) -> io::Result<Vec<String>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut out = Vec::new();
+    for entry in entries {
+        if let Some(symbol) = entry?.file_name().to_str().and_then(|n| symbol_from_file_name(n, ext)) { out.push(symbol); }
+    }
+    out.sort();
+    Ok(out)
+}
+
+// Pluggable destination for periodic snapshots; called from a background writer thread
+pub trait SnapshotStore: Send + Sync {
+    fn save(&self, snap: &SymbolSnapshot) -> io::Result<()>;
+    fn load_latest(&self, symbol: &str) -> io::Result<Option<SymbolSnapshot>>;
+}
+
+// Keeps the latest snapshot per symbol in memory (tests, embedding)
+#[derive(Default)]
+pub struct MemorySnapshotStore {
+    latest: Mutex<HashMap<String, SymbolSnapshot>>,
+}
+
+impl MemorySnapshotStore {
+    pub fn new() -> Self { Self::default() }
+}
+
+impl SnapshotStore for MemorySnapshotStore {
+    fn save(&self, snap: &SymbolSnapshot) -> io::Result<()> {
+        self.latest.lock().unwrap().insert(snap.symbol.clone(), snap.clone());
+        Ok(())
+    }
+
+    fn load_latest(&self, symbol: &str) -> io::Result<Option<SymbolSnapshot>> {
+        Ok(self.latest.lock().unwrap().get(symbol).cloned())
+    }
+}
@@ -0,0 +1,76 @@
+// Speed bump (per symbol, off by default): taker commands are held for a fixed or random delay
+// before they are sequenced, as some venues do to take the edge off latency races; everything else
+// (resting adds, cancels, reprices, session and reference changes) passes straight through, so a
+// maker can still pull a quote a bumped taker is on its way to hit.
+//   taker  market orders, IOC / FOK orders, and limit orders marketable against the book as it is
+//          when the worker receives them
+//   delay  uniform in `min_micros..=max_micros` from a per-symbol xorshift seeded with `seed`, so
+//          a run is repeatable for a given arrival order; `min == max` is a fixed delay
+// Stop orders are held by the book until triggered and are not bumped.
+// Held commands wait in the worker's scheduler (see `schedule`), keeping arrival order among those
+// due at the same time; `At` commands are scheduled already and are not bumped again.
+use crate::schedule::Scheduler;
+use crate::RawCommand;
+use match_engine::{OrderBook, OrderRequest, OrderType, Side, TimeInForce};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeedBump {
+    pub min_micros: u64,
+    pub max_micros: u64,
+    pub seed: u64,
+}
+
+impl SpeedBump {
+    pub fn fixed(micros: u64) -> Self { Self { min_micros: micros, max_micros: micros, seed: 1 } }
+
+    pub fn random(min_micros: u64, max_micros: u64, seed: u64) -> Self { Self { min_micros, max_micros: max_micros.max(min_micros), seed } }
+}
+
+pub(crate) struct BumpState {
+    cfg: SpeedBump,
+    rng: u64,
+}
+
+impl BumpState {
+    pub(crate) fn new(cfg: SpeedBump) -> Self { Self { cfg, rng: cfg.seed.max(1) } }
+
+    fn delay(&mut self) -> u64 {
+        let span = self.cfg.max_micros - self.cfg.min_micros;
+        if span == 0 { return self.cfg.min_micros; }
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.cfg.min_micros + self.rng % (span + 1)
+    }
+
+    // Move the taker commands of a freshly received batch into `sched`, due after their delay
+    pub(crate) fn hold(&mut self, book: &OrderBook, batch: &mut Vec<RawCommand>, now: u64, sched: &mut Scheduler) {
+        let mut i = 0;
+        while i < batch.len() {
+            if !is_taker(book, &batch[i]) { i += 1; continue; }
+            let delay = self.delay();
+            if delay == 0 { i += 1; continue; }
+            sched.push(now + delay, batch.remove(i));
+        }
+    }
+}
+
+// Would take liquidity if sequenced against `book` now
+pub fn is_taker(book: &OrderBook, cmd: &RawCommand) -> bool {
+    let marketable = |side: Side, price: u64| match side {
+        Side::Buy => book.best_ask().is_some_and(|(a, _)| price >= a),
+        Side::Sell => book.best_bid().is_some_and(|(b, _)| price <= b),
+    };
+    match *cmd {
+        RawCommand::Market { .. } => true,
+        RawCommand::Limit { side, price, .. } => marketable(side, price),
+        RawCommand::Submit(req) => request_takes(req, marketable(req.side, req.price)),
+        RawCommand::Sourced { cmd, .. } => is_taker(book, &cmd.into()),
+        _ => false,
+    }
+}
+
+fn request_takes(req: OrderRequest, marketable: bool) -> bool {
+    if matches!(req.tif, TimeInForce::AtOpen | TimeInForce::AtClose) || req.post_only || req.stop_price.is_some() { return false; }
+    req.order_type == OrderType::Market || matches!(req.tif, TimeInForce::Ioc | TimeInForce::Fok) || marketable
+}
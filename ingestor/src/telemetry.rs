@@ -0,0 +1,116 @@
+// Per-worker telemetry. Workers bump relaxed atomics once per batch; readers poll `Telemetry::stats`
+// (commands/sec is measured between two polls) or run an exporter that feeds a `MetricsSink`.
+use crate::metrics::MetricsSink;
+use crate::schedule::now_micros;
+use crate::RawCommand;
+use crossbeam_channel::Receiver;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+pub(crate) struct WorkerCounters {
+    commands: AtomicU64,
+    batches: AtomicU64,
+    last_batch: AtomicU64,
+    max_batch: AtomicU64,
+    last_batch_at: AtomicU64, // micros since the Unix epoch, 0 = never
+}
+
+impl WorkerCounters {
+    pub(crate) fn record_batch(&self, n: usize) {
+        let n = n as u64;
+        self.commands.fetch_add(n, Ordering::Relaxed);
+        self.batches.fetch_add(1, Ordering::Relaxed);
+        self.last_batch.store(n, Ordering::Relaxed);
+        self.max_batch.fetch_max(n, Ordering::Relaxed);
+        self.last_batch_at.store(now_micros(), Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkerStats {
+    pub symbol: String,
+    pub queue_depth: usize,
+    pub commands_total: u64,
+    pub batches_total: u64,
+    pub last_batch_size: u64,
+    pub max_batch_size: u64,
+    pub avg_batch_size: f64,
+    pub since_last_batch: Option<Duration>,
+    pub commands_per_sec: f64,
+}
+
+struct WorkerEntry {
+    symbol: String,
+    queue: Receiver<RawCommand>, // observer only, never received from
+    counters: Arc<WorkerCounters>,
+}
+
+// Cheap to clone; safe to move into a monitoring thread
+#[derive(Clone)]
+pub struct Telemetry {
+    workers: Arc<Vec<WorkerEntry>>,
+    last_poll: Arc<Mutex<(Instant, HashMap<String, u64>)>>,
+}
+
+impl Telemetry {
+    pub(crate) fn new(workers: Vec<(String, Receiver<RawCommand>, Arc<WorkerCounters>)>) -> Self {
+        let workers = workers.into_iter().map(|(symbol, queue, counters)| WorkerEntry { symbol, queue, counters }).collect();
+        Self { workers: Arc::new(workers), last_poll: Arc::new(Mutex::new((Instant::now(), HashMap::new()))) }
+    }
+
+    // One entry per worker, in symbol order
+    pub fn stats(&self) -> Vec<WorkerStats> {
+        let now = now_micros();
+        let mut last = self.last_poll.lock().unwrap();
+        let elapsed = last.0.elapsed().as_secs_f64();
+        last.0 = Instant::now();
+        let mut out: Vec<WorkerStats> = self.workers.iter().map(|w| {
+            let c = &w.counters;
+            let commands_total = c.commands.load(Ordering::Relaxed);
+            let batches_total = c.batches.load(Ordering::Relaxed);
+            let at = c.last_batch_at.load(Ordering::Relaxed);
+            let prev = last.1.insert(w.symbol.clone(), commands_total).unwrap_or(0);
+            WorkerStats {
+                symbol: w.symbol.clone(),
+                queue_depth: w.queue.len(),
+                commands_total,
+                batches_total,
+                last_batch_size: c.last_batch.load(Ordering::Relaxed),
+                max_batch_size: c.max_batch.load(Ordering::Relaxed),
+                avg_batch_size: if batches_total == 0 { 0.0 } else { commands_total as f64 / batches_total as f64 },
+                since_last_batch: (at != 0).then(|| Duration::from_micros(now.saturating_sub(at))),
+                commands_per_sec: if elapsed > 0.0 { (commands_total - prev) as f64 / elapsed } else { 0.0 },
+            }
+        }).collect();
+        out.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        out
+    }
+
+    pub fn export(&self, sink: &dyn MetricsSink) {
+        for s in self.stats() {
+            let sym = s.symbol.as_str();
+            sink.gauge("me_worker_queue_depth", sym, s.queue_depth as f64);
+            sink.gauge("me_worker_commands_total", sym, s.commands_total as f64);
+            sink.gauge("me_worker_batches_total", sym, s.batches_total as f64);
+            sink.gauge("me_worker_last_batch_size", sym, s.last_batch_size as f64);
+            sink.gauge("me_worker_max_batch_size", sym, s.max_batch_size as f64);
+            sink.gauge("me_worker_avg_batch_size", sym, s.avg_batch_size);
+            sink.gauge("me_worker_commands_per_sec", sym, s.commands_per_sec);
+            if let Some(d) = s.since_last_batch { sink.gauge("me_worker_seconds_since_last_batch", sym, d.as_secs_f64()); }
+        }
+    }
+
+    // Export every `interval` until all workers have exited
+    pub fn spawn_exporter(&self, sink: Arc<dyn MetricsSink>, interval: Duration) -> JoinHandle<()> {
+        let t = self.clone();
+        std::thread::spawn(move || loop {
+            t.export(sink.as_ref());
+            if t.workers.iter().all(|w| Arc::strong_count(&w.counters) == 1) { break; }
+            std::thread::sleep(interval);
+        })
+    }
+}
@@ -0,0 +1,55 @@
+// Throttle feedback: every throttled order becomes a `ThrottleEvent` on `MultiIngestor::rx_throttle`,
+// with who was throttled, on which symbol, their rate in the window, the limit and a retry-after
+// hint, for gateways to turn into a standard "slow down" response. Two throttles feed it:
+//   account   the book's own entry throttle (`OrderBook::set_entry_throttle`); the order is sequenced
+//             and rejected, so the audit log also records it with the engine error
+//   producer  `Options::producer_throttle`: new orders (limit, market, submit) from one sequencer
+//             producer (`RawCommand::Sourced`) on one symbol, on the worker's clock as they are
+//             received; a throttled order is dropped before it gets a seq
+// Cancels, reprices and amends are never throttled, nor are commands sent without a producer.
+use crate::{RawCommand, ScheduledCommand};
+use crossbeam_channel::Sender;
+use match_engine::{AccountId, EntryThrottle, ThrottleHit};
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ThrottleOwner {
+    Account(AccountId),
+    Producer(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThrottleEvent {
+    pub symbol: String,
+    pub owner: ThrottleOwner,
+    pub rate: u32,
+    pub limit: u32,
+    pub window_micros: u64,
+    pub retry_after_micros: u64,
+}
+
+impl ThrottleEvent {
+    pub fn from_hit(symbol: &str, hit: ThrottleHit) -> Self {
+        Self { symbol: symbol.to_string(), owner: ThrottleOwner::Account(hit.owner), rate: hit.rate, limit: hit.limit, window_micros: hit.window_micros, retry_after_micros: hit.retry_after_micros }
+    }
+}
+
+// One worker's producer throttle: recent entry times per producer
+pub(crate) struct ProducerThrottle {
+    cfg: EntryThrottle,
+    recent: HashMap<u32, VecDeque<u64>>,
+}
+
+impl ProducerThrottle {
+    pub(crate) fn new(cfg: EntryThrottle) -> Self { Self { cfg, recent: HashMap::new() } }
+
+    // Drop the new orders of a freshly received batch that exceed their producer's rate
+    pub(crate) fn filter(&mut self, symbol: &str, batch: &mut Vec<RawCommand>, now: u64, tx: &Sender<ThrottleEvent>) {
+        batch.retain(|cmd| {
+            let RawCommand::Sourced { producer, cmd: ScheduledCommand::Limit { .. } | ScheduledCommand::Market { .. } | ScheduledCommand::Submit(_), .. } = *cmd else { return true };
+            let Err((rate, retry_after_micros)) = self.cfg.admit(self.recent.entry(producer).or_default(), now) else { return true };
+            let _ = tx.send(ThrottleEvent { symbol: symbol.to_string(), owner: ThrottleOwner::Producer(producer), rate, limit: self.cfg.max_orders, window_micros: self.cfg.window_micros, retry_after_micros });
+            false
+        });
+    }
+}
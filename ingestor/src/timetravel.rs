@@ -0,0 +1,189 @@
+// Time-travel debugging over a journal (see `journal`): rebuild the exact book of one symbol as of any
+// seq, then step forward command by command to see what each one did and how the book changed.
+//   base      the book the journal builds on: empty, or a snapshot (`SymbolSnapshot`); a compacted
+//             journal needs the snapshot it references or a later one, commands before the
+//             snapshot's next_seq are skipped
+//   setup     book settings that are not in snapshots or the journal (uptick rule, self-trade
+//             prevention, price band, ...) are applied by `with_setup` whenever the book is rebuilt
+//   seek      `seek(seq)` leaves the book after every command with seq < `seq` (the snapshot
+//             `next_seq` convention); seeking back rebuilds the book from the base and replays, as
+//             a mid-journal snapshot would lose what the commands did to the session and statistics
+//   step      applies the next command and returns its outcome, trades, implicit cancels and the
+//             order-level diff of the book; `Display` prints it (the `timetravel` binary)
+// Commands are applied one at a time and failures resolved through the book's `ErrorPolicy`. The
+// journal has no batch boundaries, so a rejected command never drops the rest of its batch here as
+// it does in the worker; use a capture (`capture`) to reproduce that.
+use crate::journal::{journal_snapshot_ref, read_journal};
+use crate::snapshot::SymbolSnapshot;
+use match_engine::{BookSnapshot, Command, ImplicitCancel, Order, OrderBook, OrderId, Trade};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum TravelError {
+    Io(io::Error),
+    // The journal was compacted at `journal` and the base snapshot (if any) is older
+    MissingBase { journal: u64, snapshot: Option<u64> },
+    // The journal skips from `expected` to `found`
+    Gap { expected: u64, found: u64 },
+    // Seqs the book can be rebuilt at
+    OutOfRange { seq: u64, range: RangeInclusive<u64> },
+}
+
+impl From<io::Error> for TravelError {
+    fn from(e: io::Error) -> Self { TravelError::Io(e) }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderChange {
+    Added(Order),
+    Removed(Order),
+    Changed { before: Order, after: Order },
+}
+
+// One applied command and its effect on the book
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub command: Command,
+    pub outcome: Result<(OrderId, u64), String>,
+    pub trades: Vec<Trade>,
+    pub cancels: Vec<ImplicitCancel>,
+    // Resting orders removed, added or changed, in the book's priority order
+    pub changes: Vec<OrderChange>,
+    pub state_hash: u64,
+}
+
+type BookSetup = Box<dyn Fn(&mut OrderBook)>;
+
+pub struct TimeTravel {
+    base: BookSnapshot,
+    base_seq: u64,
+    commands: Vec<Command>,
+    book: OrderBook,
+    next: usize, // commands[..next] are applied
+    setup: Option<BookSetup>,
+}
+
+impl TimeTravel {
+    // `commands` in seq order; those before `base.next_seq` are skipped
+    pub fn new(base: Option<&SymbolSnapshot>, commands: Vec<Command>) -> Result<Self, TravelError> {
+        let base_seq = base.map_or_else(|| commands.first().map_or(0, Command::seq), |b| b.next_seq);
+        let commands: Vec<Command> = commands.into_iter().filter(|c| c.seq() >= base_seq).collect();
+        for (i, c) in commands.iter().enumerate() {
+            let expected = base_seq + i as u64;
+            if c.seq() != expected { return Err(TravelError::Gap { expected, found: c.seq() }); }
+        }
+        let base = base.map(|b| b.book.clone()).unwrap_or_default();
+        let book = OrderBook::from_snapshot(&base);
+        Ok(Self { base, base_seq, commands, book, next: 0, setup: None })
+    }
+
+    // The journal at `path` on top of `base`
+    pub fn open(path: &Path, base: Option<&SymbolSnapshot>) -> Result<Self, TravelError> {
+        if let Some(journal) = journal_snapshot_ref(path)? {
+            let snapshot = base.map(|b| b.next_seq);
+            if snapshot.is_none_or(|s| s < journal) { return Err(TravelError::MissingBase { journal, snapshot }); }
+        }
+        Self::new(base, read_journal(path)?)
+    }
+
+    // Configure the book the way production did; applied now and on every rebuild
+    pub fn with_setup(mut self, setup: impl Fn(&mut OrderBook) + 'static) -> Self {
+        setup(&mut self.book);
+        self.setup = Some(Box::new(setup));
+        self
+    }
+
+    pub fn book(&self) -> &OrderBook { &self.book }
+
+    // Seq of the next command `step` applies
+    pub fn next_seq(&self) -> u64 { self.base_seq + self.next as u64 }
+
+    // Every seq `seek` accepts: the base up to after the last command
+    pub fn range(&self) -> RangeInclusive<u64> { self.base_seq..=self.base_seq + self.commands.len() as u64 }
+
+    // The book after every command with seq < `seq`
+    pub fn seek(&mut self, seq: u64) -> Result<&OrderBook, TravelError> {
+        if !self.range().contains(&seq) { return Err(TravelError::OutOfRange { seq, range: self.range() }); }
+        let target = (seq - self.base_seq) as usize;
+        if target < self.next {
+            self.book = OrderBook::from_snapshot(&self.base);
+            if let Some(setup) = &self.setup { setup(&mut self.book); }
+            self.next = 0;
+        }
+        while self.next < target { self.apply(); }
+        Ok(&self.book)
+    }
+
+    // Apply the next command; None past the end of the journal
+    pub fn step(&mut self) -> Option<Step> {
+        if self.next == self.commands.len() { return None; }
+        let before = self.book.snapshot();
+        let mut step = self.apply();
+        let after = self.book.snapshot();
+        step.changes = diff(&before, &after);
+        step.state_hash = after.state_hash();
+        Some(step)
+    }
+
+    // The step without the diff (left to `step`, seeking does not need it)
+    fn apply(&mut self) -> Step {
+        let command = self.commands[self.next];
+        let mut trades = Vec::new();
+        let outcome = self.book.process_command(command, &mut trades).map_err(|e| {
+            let reason = e.to_string();
+            let _ = self.book.resolve_error(command.seq(), e);
+            reason
+        });
+        let cancels = self.book.drain_implicit_cancels();
+        self.book.drain_faults();
+        self.book.drain_eod_reports();
+        self.book.drain_fill_reports();
+        self.book.drain_stop_activations();
+        self.book.drain_repriced();
+        self.next += 1;
+        Step { command, outcome, trades, cancels, changes: Vec::new(), state_hash: 0 }
+    }
+}
+
+fn orders(snap: &BookSnapshot) -> impl Iterator<Item = &Order> { snap.bids.iter().chain(&snap.asks).flat_map(|l| l.orders.iter()) }
+
+// Order-level difference between two books: removed and changed orders in `before`'s priority
+// order, then added ones in `after`'s
+pub fn diff(before: &BookSnapshot, after: &BookSnapshot) -> Vec<OrderChange> {
+    let was: HashMap<OrderId, &Order> = orders(before).map(|o| (o.id, o)).collect();
+    let now: HashMap<OrderId, &Order> = orders(after).map(|o| (o.id, o)).collect();
+    let mut changes = Vec::new();
+    for o in orders(before) {
+        match now.get(&o.id) {
+            None => changes.push(OrderChange::Removed(o.clone())),
+            Some(&a) if a != o => changes.push(OrderChange::Changed { before: o.clone(), after: a.clone() }),
+            Some(_) => {}
+        }
+    }
+    changes.extend(orders(after).filter(|o| !was.contains_key(&o.id)).map(|o| OrderChange::Added(o.clone())));
+    changes
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "seq {} {:?}", self.command.seq(), self.command)?;
+        match &self.outcome {
+            Ok((id, remaining)) => writeln!(f, " -> order {} left {}", id.0, remaining)?,
+            Err(e) => writeln!(f, " -> rejected: {e}")?,
+        }
+        for t in &self.trades { writeln!(f, "  trade {} @ {} taker {} maker {}", t.qty, t.price, t.taker_id.0, t.maker_id.0)?; }
+        for c in &self.cancels { writeln!(f, "  cancel {} {:?} {} @ {} ({:?})", c.id.0, c.side, c.qty, c.price, c.reason)?; }
+        for c in &self.changes {
+            match c {
+                OrderChange::Added(o) => writeln!(f, "  + {} {:?} {} @ {}", o.id.0, o.side, o.qty, o.price)?,
+                OrderChange::Removed(o) => writeln!(f, "  - {} {:?} {} @ {}", o.id.0, o.side, o.qty, o.price)?,
+                OrderChange::Changed { before, after } => writeln!(f, "  ~ {} {:?} {} @ {} -> {} @ {}", before.id.0, before.side, before.qty, before.price, after.qty, after.price)?,
+            }
+        }
+        write!(f, "  hash {:016x}", self.state_hash)
+    }
+}
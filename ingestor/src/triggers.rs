@@ -0,0 +1,108 @@
+// Cross-instrument conditional orders: a command for a target symbol held until a price condition
+// holds on a reference symbol (e.g. hedge legs). Conditions are evaluated by the reference symbol's
+// worker after every batch it processes, so a condition already true at registration fires on the
+// next update. Triggers are one-shot: the command is routed to the target worker and the trigger
+// is removed. Workers skip the registry lock entirely while no trigger is armed.
+use crate::{MultiIngestor, RawCommand};
+use crossbeam_channel::Sender;
+use match_engine::OrderBook;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceCondition {
+    LastAtOrAbove(u64),
+    LastAtOrBelow(u64),
+    BidAtOrAbove(u64),
+    AskAtOrBelow(u64),
+    // Injected index price (`RawCommand::Reference`)
+    IndexAtOrAbove(u64),
+    IndexAtOrBelow(u64),
+}
+
+impl PriceCondition {
+    // Observed price when the condition holds
+    fn check(self, book: &OrderBook) -> Option<u64> {
+        match self {
+            Self::LastAtOrAbove(p) => book.last_trade_price().filter(|&l| l >= p),
+            Self::LastAtOrBelow(p) => book.last_trade_price().filter(|&l| l <= p),
+            Self::BidAtOrAbove(p) => book.best_bid().map(|(b, _)| b).filter(|&b| b >= p),
+            Self::AskAtOrBelow(p) => book.best_ask().map(|(a, _)| a).filter(|&a| a <= p),
+            Self::IndexAtOrAbove(p) => book.reference_values().index_price.filter(|&i| i >= p),
+            Self::IndexAtOrBelow(p) => book.reference_values().index_price.filter(|&i| i <= p),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriggerFired {
+    pub id: u64,
+    pub reference: String,
+    pub target: String,
+    pub observed: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TriggerError {
+    UnknownSymbol(String),
+}
+
+struct Entry {
+    id: u64,
+    condition: PriceCondition,
+    target: String,
+    tx: Sender<RawCommand>,
+    cmd: RawCommand,
+}
+
+#[derive(Default)]
+pub(crate) struct TriggerRegistry {
+    armed: AtomicUsize,
+    next_id: AtomicU64,
+    entries: Mutex<HashMap<String, Vec<Entry>>>,
+}
+
+impl TriggerRegistry {
+    // Called by the reference symbol's worker after each batch
+    pub(crate) fn evaluate(&self, symbol: &str, book: &OrderBook, fired: &Sender<TriggerFired>) {
+        if self.armed.load(Ordering::Acquire) == 0 { return; }
+        let mut entries = self.entries.lock().unwrap();
+        let Some(list) = entries.get_mut(symbol) else { return };
+        list.retain(|e| {
+            let Some(observed) = e.condition.check(book) else { return true };
+            let _ = e.tx.send(e.cmd);
+            let _ = fired.send(TriggerFired { id: e.id, reference: symbol.to_string(), target: e.target.clone(), observed });
+            self.armed.fetch_sub(1, Ordering::Release);
+            false
+        });
+    }
+}
+
+impl MultiIngestor {
+    pub fn add_trigger(&self, reference: &str, condition: PriceCondition, target: &str, cmd: RawCommand) -> Result<u64, TriggerError> {
+        if !self.routes.contains_key(reference) { return Err(TriggerError::UnknownSymbol(reference.to_string())); }
+        let tx = self.routes.get(target).ok_or_else(|| TriggerError::UnknownSymbol(target.to_string()))?.clone();
+        let reg = &self.triggers;
+        let id = reg.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        reg.entries.lock().unwrap().entry(reference.to_string()).or_default().push(Entry { id, condition, target: target.to_string(), tx, cmd });
+        reg.armed.fetch_add(1, Ordering::Release);
+        Ok(id)
+    }
+
+    // False when the trigger already fired or never existed
+    pub fn cancel_trigger(&self, id: u64) -> bool {
+        let reg = &self.triggers;
+        let mut entries = reg.entries.lock().unwrap();
+        for list in entries.values_mut() {
+            if let Some(i) = list.iter().position(|e| e.id == id) {
+                list.remove(i);
+                reg.armed.fetch_sub(1, Ordering::Release);
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn armed_triggers(&self) -> usize { self.triggers.armed.load(Ordering::Acquire) }
+}
@@ -0,0 +1,52 @@
+use ingestor::{spawn_sequencer, Attachments, AuditSink, MemoryAuditLog, MultiIngestor, MultiRawCommand, Options, Outcome, RawCommand};
+use match_engine::{OrderBook, OrderId, SessionState, Side};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn start(log: &Arc<MemoryAuditLog>) -> MultiIngestor {
+    let attach = Attachments { audit: Some(log.clone()), ..Attachments::default() };
+    MultiIngestor::start_with_attachments(vec![("BTC".into(), OrderBook::new())], Options::default(), attach).unwrap()
+}
+
+fn wait_for(ig: &MultiIngestor, last_seq: u64) {
+    while ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().last_seq < last_seq {}
+}
+
+#[test]
+fn audit_answers_who_sent_an_order_and_what_happened() {
+    let log = Arc::new(MemoryAuditLog::new());
+    let ig = start(&log);
+    let mut producers = spawn_sequencer(2, ig.tx_cmd.clone());
+    let cmd = |cmd| MultiRawCommand { symbol: "BTC".into(), cmd };
+    producers[1].send(10, cmd(RawCommand::Limit { side: Side::Sell, price: 100, qty: 5 })).unwrap();
+    producers[0].send(20, cmd(RawCommand::Market { side: Side::Buy, qty: 2 })).unwrap();
+    producers[0].send(30, cmd(RawCommand::Cancel { id: OrderId(1), account: None })).unwrap();
+    drop(producers);
+    wait_for(&ig, 2);
+
+    let history = log.by_order("BTC", OrderId(1));
+    assert_eq!(history.iter().map(|r| (r.seq, r.producer, r.received_at)).collect::<Vec<_>>(), vec![(0, Some(1), 10), (2, Some(0), 30)]);
+    assert_eq!(history[0].outcome, Outcome::Applied { order_id: OrderId(1), remaining: 5 });
+    assert_eq!(history[1].outcome, Outcome::Applied { order_id: OrderId(1), remaining: 0 });
+    assert_eq!(log.by_order("BTC", OrderId(2))[0].outcome, Outcome::Applied { order_id: OrderId(2), remaining: 0 });
+    assert!(log.by_order("ETH", OrderId(1)).is_empty());
+}
+
+#[test]
+fn rejected_command_and_the_rest_of_its_batch_are_recorded() {
+    let log = Arc::new(MemoryAuditLog::new());
+    let ig = start(&log);
+    // Sent directly: no producer, stamped by the worker
+    let tx = &ig.routes["BTC"];
+    tx.send(RawCommand::Session(SessionState::Halted)).unwrap();
+    wait_for(&ig, 0);
+    tx.send(RawCommand::Limit { side: Side::Buy, price: 99, qty: 1 }).unwrap();
+    tx.send(RawCommand::Cancel { id: OrderId(7), account: None }).unwrap();
+    while log.len() < 3 { std::thread::yield_now(); }
+    let records = log.records("BTC");
+    assert!(records.iter().all(|r| r.producer.is_none() && r.received_at > 0));
+    assert!(matches!(records[1].outcome, Outcome::Rejected { .. }));
+    // Applied in the same batch after the rejection or on its own, never silently lost
+    assert!(matches!(records[2].outcome, Outcome::Skipped | Outcome::Rejected { .. }));
+    assert_eq!(records.iter().map(|r| r.seq).collect::<Vec<_>>(), vec![0, 1, 2]);
+}
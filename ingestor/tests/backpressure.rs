@@ -0,0 +1,22 @@
+use ingestor::{MultiIngestor, Options, RawCommand, WatermarkLevel};
+use match_engine::{OrderBook, OrderRequest, Side};
+use std::time::Duration;
+
+#[test]
+fn watermarks_fire_once_per_crossing() {
+    // A slow book so the backlog builds up behind the worker
+    let mut book = OrderBook::new();
+    book.add_pre_match_hook(Box::new(|_: &mut OrderRequest| { std::thread::sleep(Duration::from_millis(1)); Ok(()) }));
+    let opts = Options { batch_size: 4, queue_high_watermark: 20, queue_low_watermark: 2, ..Options::default() };
+    let ig = MultiIngestor::start_with_books_with_config(vec![("BTC".to_string(), book)], opts);
+    let tx = ig.routes.get("BTC").unwrap();
+    for i in 0..60u64 { tx.send(RawCommand::Limit { side: Side::Buy, price: 100 + i, qty: 1 }).unwrap(); }
+
+    let high = ig.rx_watermark.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!((high.symbol.as_str(), high.level), ("BTC", WatermarkLevel::High));
+    assert!(high.depth >= 20);
+    let low = ig.rx_watermark.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(low.level, WatermarkLevel::Low);
+    assert!(low.depth <= 2);
+    assert!(ig.rx_watermark.recv_timeout(Duration::from_millis(100)).is_err());
+}
@@ -0,0 +1,71 @@
+use ingestor::journal::{FileJournal, JournalWriter};
+use ingestor::{replay_journal, Backtest, BacktestConfig, Context, Fill, FsyncPolicy, MirroredDepth, Strategy};
+use match_engine::{AccountId, Command, OrderBook, OrderId, Side, TimeInForce, Trade};
+
+fn limit(seq: u64, side: Side, price: u64, qty: u64) -> Command { Command::Limit { seq, side, price, qty, tif: TimeInForce::Gtc, account: AccountId(1) } }
+
+#[derive(Default)]
+struct Quoter {
+    timers: Vec<u64>,
+    fills: Vec<Fill>,
+    trades: usize,
+    depths: Vec<MirroredDepth>,
+}
+
+impl Strategy for Quoter {
+    fn on_timer(&mut self, now_micros: u64, ctx: &mut Context) {
+        self.timers.push(now_micros);
+        // Keep one bid at 99 for 2
+        if ctx.open_orders().is_empty() && ctx.position() == 0 { ctx.buy(99, 2).unwrap(); }
+    }
+
+    fn on_trade(&mut self, _: &Trade, _: &mut Context) { self.trades += 1; }
+
+    // Try to flatten straight away, within the same market command
+    fn on_fill(&mut self, fill: &Fill, ctx: &mut Context) {
+        self.fills.push(*fill);
+        if fill.maker && ctx.position() > 0 { let _ = ctx.sell_market(fill.qty); }
+    }
+
+    fn on_depth(&mut self, depth: &MirroredDepth, _: &mut Context) { self.depths.push(depth.clone()); }
+}
+
+#[test]
+fn strategy_trades_against_the_recorded_flow() {
+    let cfg = BacktestConfig { account: AccountId(7), depth_levels: 1, timer_micros: 1_000 };
+    let mut bt = Backtest::new(OrderBook::new(), Quoter::default(), cfg);
+    // Recorded ids: 1 the ask, 2 the 98 bid, 3 the market sell; the strategy's bid takes id 1 here
+    bt.run_timed([(500, limit(0, Side::Sell, 101, 5)), (800, limit(1, Side::Buy, 98, 4)), (1_200, Command::Cancel { seq: 2, id: OrderId(2), account: None }), (2_500, Command::Market { seq: 3, side: Side::Sell, qty: 3, account: AccountId(1) })]);
+
+    let q = bt.strategy();
+    assert_eq!(q.timers, vec![500, 1_500, 2_500]);
+    // The recorded cancel of id 2 took off the 98 bid (id 3 in this run), so the market sell filled 2
+    // against the strategy's bid only, and the strategy's flattening sell found no bid to hit
+    assert_eq!(q.fills, vec![Fill { id: OrderId(1), trade_id: q.fills[0].trade_id, side: Side::Buy, price: 99, qty: 2, maker: true }]);
+    assert_eq!((bt.context().position(), bt.context().cash(), q.trades), (2, -198, 1));
+    assert_eq!(q.depths.last().unwrap(), &MirroredDepth { next_seq: 4, bids: vec![], asks: vec![(101, 5, 1)] });
+    assert!(q.depths.iter().any(|d| d.bids == vec![(99, 2, 1)]));
+    let (_, book) = bt.into_parts();
+    assert_eq!((book.best_bid(), book.best_ask()), (None, Some((101, 5))));
+}
+
+#[test]
+fn passive_strategy_reproduces_the_journal() {
+    let path = std::env::temp_dir().join(format!("me-backtest-{}.journal", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut j = FileJournal::open(&path, FsyncPolicy::EveryBatch).unwrap();
+    j.append(&[limit(4, Side::Sell, 101, 5), limit(5, Side::Buy, 101, 2), limit(6, Side::Buy, 100, 1)]).unwrap();
+    drop(j);
+
+    struct Idle;
+    impl Strategy for Idle {}
+    let mut bt = Backtest::new(OrderBook::new(), Idle, BacktestConfig::default());
+    assert_eq!(bt.run_journal(&path).unwrap(), 3);
+    assert_eq!(bt.into_parts().1.snapshot(), replay_journal(&path, None).unwrap().snapshot());
+
+    let mut j = FileJournal::open(&path, FsyncPolicy::EveryBatch).unwrap();
+    j.append(&[limit(9, Side::Sell, 102, 1)]).unwrap();
+    drop(j);
+    let mut bt = Backtest::new(OrderBook::new(), Idle, BacktestConfig::default());
+    assert!(matches!(bt.run_journal(&path), Err(ingestor::ReplayError::Gap { expected: 7, found: 9 })));
+}
@@ -0,0 +1,46 @@
+use ingestor::{BasketError, BasketLeg, MultiIngestor, RawCommand};
+use match_engine::{OrderBook, OrderRequest, Side};
+use std::collections::HashMap;
+use std::time::Duration;
+
+fn wait_done(ig: &MultiIngestor, n: usize) {
+    let mut done = 0;
+    while done < n { done += ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().commands; }
+}
+
+#[test]
+fn basket_is_validated_as_a_whole_and_correlated() {
+    let books = vec![("BTC".to_string(), OrderBook::new()), ("ETH".to_string(), OrderBook::new())];
+    let ig = MultiIngestor::start_with_books(books, 16, HashMap::new()).unwrap();
+    ig.routes["BTC"].send(RawCommand::Limit { side: Side::Sell, price: 100, qty: 5 }).unwrap();
+    wait_done(&ig, 1);
+
+    // One bad leg rejects the basket; nothing is routed
+    let bad = [
+        BasketLeg { symbol: "BTC", req: OrderRequest::limit(Side::Buy, 100, 2) },
+        BasketLeg { symbol: "ETH", req: OrderRequest::limit(Side::Buy, 0, 2) },
+    ];
+    assert_eq!(ig.submit_basket(&bad), Err(BasketError::InvalidLeg { leg: 1, reason: "limit order without a price" }));
+    let unknown = [BasketLeg { symbol: "SOL", req: OrderRequest::market(Side::Buy, 1) }];
+    assert!(matches!(ig.submit_basket(&unknown), Err(BasketError::UnknownSymbol { leg: 0, .. })));
+    assert_eq!(ig.submit_basket(&[]), Err(BasketError::Empty));
+
+    let legs = [
+        BasketLeg { symbol: "BTC", req: OrderRequest::limit(Side::Buy, 100, 2) },
+        BasketLeg { symbol: "ETH", req: OrderRequest::limit(Side::Sell, 50, 3) },
+    ];
+    let corr = ig.submit_basket(&legs).unwrap();
+    assert_eq!(corr, 1);
+    wait_done(&ig, 2);
+    // The resting ETH leg carries the correlation onto its later fill as maker
+    ig.routes["ETH"].send(RawCommand::Market { side: Side::Buy, qty: 3 }).unwrap();
+    wait_done(&ig, 1);
+
+    let trades: Vec<_> = ig.rx_trade.try_iter().collect();
+    assert_eq!(trades.len(), 2);
+    let btc = trades.iter().find(|(s, _)| s == "BTC").unwrap();
+    assert_eq!((btc.1.taker_correlation, btc.1.maker_correlation, btc.1.qty), (corr, 0, 2));
+    let eth = trades.iter().find(|(s, _)| s == "ETH").unwrap();
+    assert_eq!((eth.1.taker_correlation, eth.1.maker_correlation), (0, corr));
+    assert_eq!(ig.submit_basket(&legs[..1]).unwrap(), 2);
+}
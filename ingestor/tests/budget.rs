@@ -0,0 +1,17 @@
+use ingestor::{MultiIngestor, Options, RawCommand};
+use match_engine::{OrderBook, Side};
+use std::time::Duration;
+
+#[test]
+fn idle_worker_resumes_a_paused_sweep() {
+    let mut book = OrderBook::new();
+    for i in 0..10 { book.submit_limit(Side::Sell, 101 + i, 1).unwrap(); }
+    book.set_match_budget(Some(3));
+    let ig = MultiIngestor::start_with_books_with_config(vec![("BTC".to_string(), book)], Options::default());
+    ig.routes["BTC"].send(RawCommand::Market { side: Side::Buy, qty: 8 }).unwrap();
+    // No further flow: the worker sequences Resume commands itself until the sweep is done
+    let prices: Vec<u64> = (0..8).map(|_| ig.rx_trade.recv_timeout(Duration::from_secs(5)).unwrap().1.price).collect();
+    assert_eq!(prices, (101..109).collect::<Vec<_>>());
+    let snap = ig.book_snapshot("BTC").unwrap();
+    assert_eq!((snap.book.order_count(), snap.next_seq), (2, 3));
+}
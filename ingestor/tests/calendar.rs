@@ -0,0 +1,59 @@
+use ingestor::calendar::{days_from_civil, GroupCalendar, SessionTimes, TradingCalendar, MICROS_PER_DAY};
+use ingestor::{MultiIngestor, RawCommand};
+use match_engine::{OrderBook, OrderRequest, SessionState, Side, TimeInForce};
+use std::collections::HashMap;
+use std::time::Duration;
+
+const HOUR: u64 = 3_600_000_000;
+
+fn equities() -> GroupCalendar {
+    let mut g = GroupCalendar::new(SessionTimes { pre_open: 13 * HOUR, open: 14 * HOUR, pre_close: 20 * HOUR, close: 21 * HOUR });
+    g.add_holiday(2024, 7, 4);
+    g.add_early_close(2024, 7, 3, 17 * HOUR, 18 * HOUR);
+    g
+}
+
+fn at(y: i64, m: u32, d: u32, hour: u64) -> u64 { days_from_civil(y, m, d) as u64 * MICROS_PER_DAY + hour * HOUR }
+
+#[test]
+fn calendar_answers_market_closed_queries() {
+    let mut cal = TradingCalendar::new();
+    cal.add_group("us-eq", equities());
+    cal.assign("AAPL", "us-eq");
+    assert_eq!(days_from_civil(1970, 1, 1), 0);
+    assert_eq!(cal.state_at("AAPL", at(2024, 7, 2, 15)), Some(SessionState::Open));
+    assert_eq!(cal.state_at("AAPL", at(2024, 7, 2, 13)), Some(SessionState::PreOpen));
+    // Early close on the 3rd, holiday on the 4th, weekend on the 6th
+    assert_eq!(cal.state_at("AAPL", at(2024, 7, 3, 17)), Some(SessionState::PreClose));
+    assert!(!cal.is_open("AAPL", at(2024, 7, 3, 19)));
+    assert!(!cal.is_open("AAPL", at(2024, 7, 4, 15)));
+    assert!(cal.is_open("AAPL", at(2024, 7, 5, 15)));
+    assert!(!cal.is_open("AAPL", at(2024, 7, 6, 15)));
+    assert_eq!(cal.state_at("BTC", 0), None);
+    // After the early close the next phase change is Friday's pre-open
+    assert_eq!(cal.next_transition("AAPL", at(2024, 7, 3, 18)), Some((at(2024, 7, 5, 13), SessionState::PreOpen)));
+    let t = cal.group_of("AAPL").unwrap().transitions(at(2024, 7, 3, 0), at(2024, 7, 6, 0));
+    assert_eq!(t.len(), 8);
+}
+
+#[test]
+fn scheduled_transitions_drive_the_books() {
+    let mut cal = TradingCalendar::new();
+    cal.add_group("us-eq", equities());
+    cal.assign("AAPL", "us-eq");
+    let books = vec![("AAPL".to_string(), OrderBook::new()), ("BTC".to_string(), OrderBook::new())];
+    let ig = MultiIngestor::start_with_books(books, 16, HashMap::new()).unwrap();
+    let wait = |n: usize| { let mut d = 0; while d < n { d += ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().commands; } };
+    // A past trading day up to the closing auction: transitions are due at once, only for calendar symbols
+    assert_eq!(ig.schedule_calendar(&cal, at(2024, 7, 2, 0), at(2024, 7, 2, 20) + 1), 3);
+    wait(3);
+    for side in [Side::Buy, Side::Sell] {
+        ig.routes["AAPL"].send(RawCommand::Submit(OrderRequest::limit(side, 100, 2).with_tif(TimeInForce::AtClose))).unwrap();
+    }
+    wait(2);
+    assert!(ig.rx_trade.try_recv().is_err());
+    // The close runs the closing uncross
+    assert_eq!(ig.schedule_calendar(&cal, at(2024, 7, 2, 20) + 1, at(2024, 7, 3, 0)), 1);
+    let (symbol, trade) = ig.rx_trade.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!((symbol.as_str(), trade.price, trade.qty), ("AAPL", 100, 2));
+}
@@ -0,0 +1,67 @@
+use ingestor::{AdaptiveCoalescing, CoalesceSettings, Coalescer, MultiIngestor, MultiRawCommand, Options, RawCommand, ShardedIngestor};
+use match_engine::{OrderBook, Side};
+use std::collections::HashMap;
+use std::time::Duration;
+
+const CFG: AdaptiveCoalescing = AdaptiveCoalescing { min_micros: 10, max_micros: 400, min_batch: 8, max_batch: 64 };
+
+fn settings(window_micros: u32, batch_size: usize) -> CoalesceSettings { CoalesceSettings { window_micros, batch_size } }
+
+#[test]
+fn window_and_batch_follow_the_load_within_bounds() {
+    let mut c = Coalescer::new(CFG, 1_000);
+    assert_eq!(c.settings(), settings(10, 64));
+    // Sparse: one command every 5ms, nothing to wait for
+    for t in 0..2 { c.observe(1, 0, true, t * 5_000, t * 5_000); }
+    assert_eq!(c.settings(), settings(10, 16));
+
+    // Burst: full batches with a backlog, commands 20us apart; the batch grows back to the cap and the
+    // window follows the arrival gap
+    let mut now = 15_000;
+    c.observe(16, 500, true, now, now + 20 * 15);
+    now += 20 * 15;
+    for _ in 0..12 {
+        let taken = c.settings().batch_size;
+        now += 20 * taken as u64;
+        c.observe(taken, 500, false, now - 20 * (taken as u64 - 1), now);
+    }
+    assert_eq!(c.settings().batch_size, 64);
+    assert_eq!(c.settings().window_micros, 40);
+
+    // A pause between two bursts is idle time, not a gap between arrivals
+    now += 50_000;
+    c.observe(64, 0, true, now, now + 20 * 63);
+    now += 20 * 63;
+    assert_eq!(c.settings().window_micros, 40);
+    // A lone command after the window ran out ends the burst
+    now += 5_000;
+    c.observe(1, 0, true, now, now);
+    assert_eq!(c.settings().window_micros, 10);
+
+    // Quiet again: small batches 5ms apart inside, empty queue
+    for _ in 0..8 { now += 10_000; c.observe(2, 0, true, now, now + 5_000); now += 5_000; }
+    assert_eq!(c.settings(), settings(10, 8));
+}
+
+#[test]
+fn workers_report_the_settings_for_the_next_batch() {
+    let opts = Options { batch_size: 32, adaptive_coalescing: Some(CFG), ..Options::default() };
+    let ig = MultiIngestor::start_with_books_with_config(vec![("X".to_string(), OrderBook::new())], opts);
+    for i in 0..3 {
+        ig.tx_cmd.send(MultiRawCommand { symbol: "X".to_string(), cmd: RawCommand::Limit { side: Side::Buy, price: 90 + i, qty: 1 } }).unwrap();
+        let p = ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(p.commands, 1);
+        // Single commands with an empty queue: the batch shrinks towards the floor, the window stays short
+        assert_eq!(p.coalescing.unwrap(), settings(10, [16, 8, 8][i as usize]));
+        std::thread::sleep(Duration::from_millis(2));
+    }
+    let fixed = MultiIngestor::start_with_books(vec![("X".to_string(), OrderBook::new())], 8, HashMap::new()).unwrap();
+    fixed.tx_cmd.send(MultiRawCommand { symbol: "X".to_string(), cmd: RawCommand::Market { side: Side::Buy, qty: 1 } }).unwrap();
+    assert_eq!(fixed.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().coalescing, None);
+}
+
+#[test]
+#[should_panic(expected = "adaptive coalescing is not supported by ShardedIngestor")]
+fn sharded_ingestor_refuses_adaptive_coalescing() {
+    ShardedIngestor::start(vec![("X".to_string(), OrderBook::new())], 2, Options { adaptive_coalescing: Some(CFG), ..Options::default() });
+}
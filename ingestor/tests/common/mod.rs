@@ -0,0 +1,20 @@
+// Helpers shared by the integration tests and the persistence bench; not every user needs all of them
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+// A path under the temp dir unique to `name` and this process, with whatever an earlier run left there
+// removed
+pub fn temp_path(name: &str) -> PathBuf {
+    let p = std::env::temp_dir().join(format!("me-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&p);
+    let _ = std::fs::remove_file(&p);
+    p
+}
+
+// `temp_path`, created as an empty directory
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = temp_path(name);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::temp_path;
+use ingestor::{Attachments, DeliveryError, DeliveryLog, MultiIngestor, Options, RawCommand};
+use match_engine::{AccountId, CancelReason, ImplicitCancel, OrderBook, OrderId, Side, Trade, TradeConditions, TradeId};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn trade(i: u64) -> Trade {
+    Trade { trade_id: TradeId(i + 1), taker_id: OrderId(i + 1), maker_id: OrderId(i), price: 100 + i, qty: 1 + i % 3, taker_side: Side::Buy, taker_correlation: i, maker_correlation: 0, taker_account: AccountId(i % 4), maker_account: AccountId(0), maker_remaining: i % 2, maker_fully_filled: i.is_multiple_of(2), ts: i, conditions: TradeConditions::default(), output_seq: 0 }
+}
+
+#[test]
+fn crashed_consumer_resumes_from_its_commit() {
+    let log = Arc::new(DeliveryLog::in_memory(1024));
+    let attach = Attachments { delivery: Some(log.clone()), ..Attachments::default() };
+    let books = vec![("BTC".to_string(), OrderBook::new()), ("ETH".to_string(), OrderBook::new())];
+    let ig = MultiIngestor::start_with_attachments(books, Options::default(), attach).unwrap();
+    assert_eq!(log.register("risk"), 0);
+    let mut sent = 0;
+    for i in 0..6u64 {
+        let route = &ig.routes[if i % 2 == 0 { "BTC" } else { "ETH" }];
+        route.send(RawCommand::Limit { side: Side::Sell, price: 100 + i, qty: 2 }).unwrap();
+        route.send(RawCommand::Market { side: Side::Buy, qty: 2 }).unwrap();
+        sent += 2;
+    }
+    let mut done = 0;
+    while done < sent { done += ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().commands; }
+    let published: Vec<_> = ig.rx_trade.try_iter().map(|(_, t)| t).collect();
+    assert_eq!(published.len(), 6);
+
+    // First run: processes a couple of deliveries, commits the first only, then crashes
+    let first = log.poll(log.register("risk"), 2, Duration::from_secs(1)).unwrap();
+    log.commit("risk", first[0].offset + 1).unwrap();
+
+    // Restart: resume from the commit; the uncommitted delivery is seen again, nothing is lost
+    let from = log.register("risk");
+    assert_eq!(from, 1);
+    let rest = log.poll(from, usize::MAX, Duration::from_secs(1)).unwrap();
+    assert_eq!(rest[0], first[1]);
+    let mut delivered: Vec<_> = first[..1].iter().chain(&rest).flat_map(|d| d.trades.clone()).collect();
+    let mut published = published;
+    delivered.sort_by_key(|t| (t.price, t.taker_id.0));
+    published.sort_by_key(|t| (t.price, t.taker_id.0));
+    assert_eq!(delivered, published);
+    log.commit("risk", rest.last().unwrap().offset + 1).unwrap();
+    assert!(log.retained().is_empty());
+}
+
+#[test]
+fn unacknowledged_deliveries_spill_to_disk() {
+    let path = temp_path("spill");
+    let log = DeliveryLog::with_spill(2, &path).unwrap();
+    log.register("a");
+    log.register("b");
+    let cancel = ImplicitCancel { id: OrderId(7), side: Side::Sell, price: 101, qty: 4, reason: CancelReason::Expired, replay: false, output_seq: 0 };
+    for i in 0..10u64 {
+        let cancels = if i == 3 { std::slice::from_ref(&cancel) } else { &[] };
+        log.append(if i % 2 == 0 { "BTC" } else { "ETH" }, i, &[trade(i)], cancels).unwrap();
+    }
+    assert_eq!(log.spilled(), 8);
+    let all = log.poll(0, 100, Duration::ZERO).unwrap();
+    assert_eq!(all.len(), 10);
+    for (i, d) in all.iter().enumerate() {
+        assert_eq!((d.offset, d.last_seq, d.trades.clone()), (i as u64, i as u64, vec![trade(i as u64)]));
+    }
+    assert_eq!(all[3].cancels, vec![cancel]);
+    assert_eq!(all[4].symbol, "BTC");
+
+    // Released only once every consumer committed; the file is emptied when nothing spilled is left
+    log.commit("a", 10).unwrap();
+    assert_eq!(log.retained(), 0..10);
+    log.commit("b", 5).unwrap();
+    assert_eq!((log.retained(), log.spilled()), (5..10, 3));
+    log.commit("b", 9).unwrap();
+    assert_eq!((log.retained(), log.spilled()), (9..10, 0));
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+
+    // Without a spill file the bound drops the oldest
+    let bounded = DeliveryLog::in_memory(3);
+    bounded.register("a");
+    for i in 0..5u64 { bounded.append("BTC", i, &[trade(i)], &[]).unwrap(); }
+    assert!(matches!(bounded.poll(0, 10, Duration::ZERO), Err(DeliveryError::Trimmed { oldest: 2 })));
+    assert_eq!(bounded.poll(2, 10, Duration::ZERO).unwrap().len(), 3);
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,58 @@
+use ingestor::calendar::{days_from_civil, GroupCalendar, SessionTimes, TradingCalendar, MICROS_PER_DAY};
+use ingestor::{MemorySnapshotStore, MultiIngestor, Options, RawCommand, SnapshotStore};
+use match_engine::{OrderBook, OrderRequest, SessionState, SettlementSource, Side, TimeInForce};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const HOUR: u64 = 3_600_000_000;
+
+fn at(y: i64, m: u32, d: u32, hour: u64) -> u64 { days_from_civil(y, m, d) as u64 * MICROS_PER_DAY + hour * HOUR }
+
+fn latest(store: &MemorySnapshotStore, symbol: &str, next_seq: u64) -> match_engine::BookSnapshot {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        if let Some(s) = store.load_latest(symbol).unwrap().filter(|s| s.next_seq == next_seq) { return s.book; }
+        assert!(Instant::now() < deadline, "no end-of-day snapshot");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn calendar_close_runs_end_of_day_and_snapshots() {
+    let mut cal = TradingCalendar::new();
+    cal.add_group("us-eq", GroupCalendar::new(SessionTimes { pre_open: 13 * HOUR, open: 14 * HOUR, pre_close: 20 * HOUR, close: 21 * HOUR }));
+    cal.assign("AAPL", "us-eq");
+    let store = Arc::new(MemorySnapshotStore::new());
+    let ig = MultiIngestor::start_with_books_with_snapshots(vec![("AAPL".to_string(), OrderBook::new())], Options::default(), store.clone());
+    let wait = |n: usize| { let mut d = 0; while d < n { d += ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().commands; } };
+    assert_eq!(ig.schedule_calendar(&cal, at(2024, 7, 2, 0), at(2024, 7, 2, 20) + 1), 3);
+    wait(3);
+    ig.routes["AAPL"].send(RawCommand::Submit(OrderRequest::limit(Side::Buy, 99, 4).with_tif(TimeInForce::Day))).unwrap();
+    ig.routes["AAPL"].send(RawCommand::Submit(OrderRequest::limit(Side::Sell, 100, 4))).unwrap();
+    ig.routes["AAPL"].send(RawCommand::Submit(OrderRequest::market(Side::Buy, 3).with_tif(TimeInForce::AtClose))).unwrap();
+    wait(3);
+
+    assert_eq!(ig.schedule_calendar(&cal, at(2024, 7, 2, 20) + 1, at(2024, 7, 3, 0)), 1);
+    let (symbol, report) = ig.rx_eod.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(symbol, "AAPL");
+    assert_eq!((report.settlement.map(|s| (s.price, s.source)), report.stats.volume, report.expired), (Some((100, SettlementSource::ClosingAuction)), 3, 1));
+    // Snapshot of the closed book: the Day bid expired, the GTC ask carries over with its remainder
+    let book = latest(&store, "AAPL", 7);
+    assert!(book.bids.is_empty());
+    assert_eq!(book.asks.iter().map(|l| (l.price, l.orders[0].qty)).collect::<Vec<_>>(), vec![(100, 1)]);
+    assert_eq!(book.reference.settlement_price, Some(100));
+    assert_eq!(ig.market_data_snapshot("AAPL", 1, 0).unwrap().session, SessionState::Closed);
+}
+
+#[test]
+fn admin_end_of_day_for_unscheduled_symbol() {
+    let ig = MultiIngestor::start_with_books(vec![("BTC".to_string(), OrderBook::new())], 16, HashMap::new()).unwrap();
+    ig.routes["BTC"].send(RawCommand::Limit { side: Side::Sell, price: 50, qty: 1 }).unwrap();
+    ig.routes["BTC"].send(RawCommand::Market { side: Side::Buy, qty: 1 }).unwrap();
+    assert!(ig.end_of_day("BTC"));
+    assert!(!ig.end_of_day("ETH"));
+    let (_, report) = ig.rx_eod.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(report.settlement.map(|s| (s.price, s.source)), Some((50, SettlementSource::LastTrade)));
+    assert_eq!(report.stats.trades, 1);
+}
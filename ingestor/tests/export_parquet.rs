@@ -0,0 +1,46 @@
+#![cfg(feature = "parquet")]
+use arrow_array::{Array, StringArray, UInt32Array, UInt64Array};
+use ingestor::export::{OrderEventKind, ParquetExporter};
+use match_engine::{OrderBook, Side};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::fs::File;
+use std::path::Path;
+
+fn read_all(path: &Path) -> Vec<arrow_array::RecordBatch> {
+    ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap()).unwrap().build().unwrap().map(|b| b.unwrap()).collect()
+}
+
+#[test]
+fn exports_trades_orders_and_depth() {
+    let dir = std::env::temp_dir().join(format!("me-export-{}", std::process::id()));
+    let mut ob = OrderBook::new();
+    let mut ex = ParquetExporter::create(&dir, 2).unwrap();
+    for (i, price) in [101u64, 102, 103].into_iter().enumerate() {
+        let (id, _, _) = ob.submit_limit(Side::Sell, price, 5).unwrap();
+        ex.record_order_event("BTC", i as u64, id, OrderEventKind::Accepted, Side::Sell, Some(price), 5).unwrap();
+    }
+    let (taker, trades, _) = ob.submit_market(Side::Buy, 7).unwrap();
+    ex.record_order_event("BTC", 10, taker, OrderEventKind::Accepted, Side::Buy, None, 7).unwrap();
+    for t in &trades { ex.record_trade("BTC", 10, t).unwrap(); }
+    ex.record_depth("BTC", 11, &ob, 5).unwrap();
+    ex.finish().unwrap();
+
+    let trades_out = read_all(&dir.join("trades.parquet"));
+    assert_eq!(trades_out.iter().map(|b| b.num_rows()).sum::<usize>(), 2);
+    let qty = trades_out[0].column_by_name("qty").unwrap().as_any().downcast_ref::<UInt64Array>().unwrap();
+    assert_eq!((qty.value(0), qty.value(1)), (5, 2));
+
+    let orders = read_all(&dir.join("orders.parquet"));
+    assert_eq!(orders.iter().map(|b| b.num_rows()).sum::<usize>(), 4);
+    let last = orders.last().unwrap();
+    let price = last.column_by_name("price").unwrap().as_any().downcast_ref::<UInt64Array>().unwrap();
+    assert!(price.is_null(last.num_rows() - 1));
+
+    let depth = read_all(&dir.join("depth.parquet"));
+    let sides = depth[0].column_by_name("side").unwrap().as_any().downcast_ref::<StringArray>().unwrap();
+    let prices = depth[0].column_by_name("price").unwrap().as_any().downcast_ref::<UInt64Array>().unwrap();
+    assert_eq!((sides.value(0), prices.value(0), prices.value(1)), ("sell", 102, 103));
+    let orders = depth[0].column_by_name("orders").unwrap().as_any().downcast_ref::<UInt32Array>().unwrap();
+    assert_eq!((orders.value(0), orders.value(1)), (1, 1));
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,69 @@
+use ingestor::{CodecError, DepthPublisher, FeedDecoder, FeedEncoder, FeedMsg};
+use match_engine::{OrderBook, Side};
+use std::path::Path;
+
+// Deterministic feed: a snapshot, then deltas from resting, trading and cancelling around 10_000
+fn feed() -> Vec<FeedMsg> {
+    let mut ob = OrderBook::new();
+    let mut publisher = DepthPublisher::new("BTC-USD", 5, 1_000);
+    let mut out = Vec::new();
+    publisher.update(&ob, 0, &mut out);
+    let mut ids = Vec::new();
+    for i in 0..6u64 {
+        ids.push(ob.submit_limit(Side::Buy, 9_990 - i * 5, 10 + i).unwrap().0);
+        ob.submit_limit(Side::Sell, 10_010 + i * 5, 20 + i).unwrap();
+        publisher.update(&ob, 100 * i, &mut out);
+    }
+    ob.submit_market(Side::Buy, 45).unwrap();
+    publisher.update(&ob, 700, &mut out);
+    ob.cancel(ids[0]).unwrap();
+    publisher.update(&ob, 1_500, &mut out);
+    ob.submit_limit(Side::Buy, 9_987, 7).unwrap();
+    ob.submit_limit(Side::Sell, 10_019, 3).unwrap();
+    publisher.update(&ob, 1_600, &mut out);
+    out
+}
+
+fn encode(compress: bool, msgs: &[FeedMsg]) -> Vec<Vec<u8>> {
+    let mut enc = FeedEncoder::new(compress);
+    msgs.iter().map(|m| { let mut b = Vec::new(); enc.encode(m, &mut b); b }).collect()
+}
+
+// Frames as len u16 | frame; `UPDATE_GOLDEN=1 cargo test` rewrites the files after a format change
+fn check_golden(name: &str, frames: &[Vec<u8>]) {
+    let bytes: Vec<u8> = frames.iter().flat_map(|f| (f.len() as u16).to_le_bytes().into_iter().chain(f.iter().copied())).collect();
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() { std::fs::write(&path, &bytes).unwrap(); }
+    assert_eq!(bytes, std::fs::read(&path).unwrap(), "{name} differs from the golden file");
+}
+
+#[test]
+fn plain_and_compressed_match_golden_files_and_round_trip() {
+    let msgs = feed();
+    assert!(msgs.len() > 15);
+    for (compress, name) in [(false, "feed_plain.bin"), (true, "feed_compressed.bin")] {
+        let frames = encode(compress, &msgs);
+        check_golden(name, &frames);
+        let mut dec = FeedDecoder::new();
+        let decoded: Vec<FeedMsg> = frames.iter().map(|f| dec.decode(f).unwrap()).collect();
+        assert_eq!(decoded, msgs);
+    }
+    let size = |frames: Vec<Vec<u8>>| frames.iter().map(Vec::len).sum::<usize>();
+    let (plain, compressed) = (size(encode(false, &msgs)), size(encode(true, &msgs)));
+    assert!(compressed * 2 < plain, "compressed {compressed} vs plain {plain}");
+}
+
+#[test]
+fn relative_delta_after_a_gap_waits_for_snapshot() {
+    let msgs = feed();
+    let frames = encode(true, &msgs);
+    let mut dec = FeedDecoder::new();
+    // In step up to seq 5, then seq 6 is lost: seq 7 is relative to a best price the decoder may not have
+    for f in &frames[..=5] { dec.decode(f).unwrap(); }
+    assert!(matches!(dec.decode(&frames[7]), Err(CodecError::OutOfSync { seq: 7, .. })));
+    assert_eq!(dec.decode(&frames[1][..frames[1].len() - 1]), Err(CodecError::Truncated));
+    // The next snapshot is self-contained and puts the decoder back in step
+    let snap = 1 + msgs[1..].iter().position(|m| matches!(m, FeedMsg::Snapshot { .. })).unwrap();
+    assert!(snap + 2 < msgs.len());
+    for i in snap..msgs.len() { assert_eq!(dec.decode(&frames[i]).unwrap(), msgs[i]); }
+}
@@ -0,0 +1,59 @@
+use ingestor::{Harness, HarnessError, MultiIngestor, Options, RawCommand, Script, ShardedIngestor};
+use match_engine::{OrderBook, OrderId, Side};
+
+const SYMBOLS: [&str; 4] = ["BTC", "ETH", "SOL", "XRP"];
+
+fn books() -> Vec<(String, OrderBook)> { SYMBOLS.iter().map(|s| (s.to_string(), OrderBook::new())).collect() }
+
+// Three producers; each phase they swap which symbols they write, makers first and takers after
+fn script() -> Script {
+    let mut s = Script::new(3);
+    for phase in 0..4u64 {
+        for (i, sym) in SYMBOLS.iter().enumerate() {
+            let p = (i + phase as usize) % 3;
+            for k in 0..5 {
+                let side = if (k + phase) % 2 == 0 { Side::Sell } else { Side::Buy };
+                let price = if side == Side::Sell { 101 + k } else { 99 - k };
+                s.send(p, sym, RawCommand::Limit { side, price, qty: 1 + k });
+                s.send(p, sym, RawCommand::Market { side: side.opposite(), qty: 1 + (k + i as u64) % 3 });
+            }
+            s.send(p, sym, RawCommand::Cancel { id: OrderId(2 + phase * 10), account: None });
+        }
+        s.barrier();
+    }
+    s
+}
+
+#[test]
+fn multi_ingestor_tapes_are_exact_across_producers() {
+    let script = script();
+    let expected = script.expected_tapes(books()).unwrap();
+    assert_eq!(expected.len(), SYMBOLS.len());
+    // The first taker of BTC lifts the first ask in full
+    assert_eq!((expected["BTC"][0].taker_id, expected["BTC"][0].maker_id, expected["BTC"][0].price, expected["BTC"][0].qty), (OrderId(2), OrderId(1), 101, 1));
+    for batch_size in [1, 7, 256] {
+        let ig = MultiIngestor::start_with_books_with_config(books(), Options { batch_size, ..Options::default() });
+        let mut barriers = Vec::new();
+        let tapes = Harness::from(&ig).run(&script, |phase| barriers.push(phase)).unwrap();
+        assert_eq!(tapes, expected, "batch size {batch_size}");
+        assert_eq!(barriers, vec![0, 1, 2, 3, 4]);
+    }
+}
+
+#[test]
+fn sharded_tapes_survive_migration_at_a_barrier() {
+    let script = script();
+    let ig = ShardedIngestor::start(books(), 2, Options { batch_size: 4, ..Options::default() });
+    let harness = Harness::from(&ig);
+    let tapes = harness.run(&script, |phase| {
+        // Move one symbol to the other worker between phases, with nothing in flight
+        let (sym, worker) = ig.assignments()[phase % SYMBOLS.len()].clone();
+        ig.move_symbol(&sym, 1 - worker).unwrap();
+    }).unwrap();
+    assert_eq!(tapes, script.expected_tapes(books()).unwrap());
+
+    // A symbol written by two producers in one phase has no fixed order and is refused
+    let mut shared = Script::new(2);
+    shared.send(0, "BTC", RawCommand::Market { side: Side::Buy, qty: 1 }).send(1, "BTC", RawCommand::Market { side: Side::Sell, qty: 1 });
+    assert_eq!(harness.run(&shared, |_| {}), Err(HarnessError::SharedSymbol { phase: 0, symbol: "BTC".into(), producers: (0, 1) }));
+}
@@ -0,0 +1,48 @@
+use ingestor::{InstrumentKey, InstrumentRegistry, MultiIngestor, OptionRight, RawCommand};
+use match_engine::{OrderBook, Side};
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[test]
+fn keys_intern_once_and_list_as_chains() {
+    let mut reg = InstrumentRegistry::default();
+    let spot = reg.intern(InstrumentKey::spot("BTC/USDT"));
+    let fut = reg.intern(InstrumentKey::future("BTC/USDT", 20261225));
+    let put = reg.intern(InstrumentKey::option("BTC/USDT", 20261225, 60_000, OptionRight::Put));
+    let call_low = reg.intern(InstrumentKey::option("BTC/USDT", 20261225, 55_000, OptionRight::Call));
+    let call = reg.intern(InstrumentKey::option("BTC/USDT", 20261225, 60_000, OptionRight::Call));
+    reg.intern(InstrumentKey::option("BTC/USDT", 20260327, 60_000, OptionRight::Call));
+    reg.intern(InstrumentKey::option("ETH/USDT", 20261225, 3_000, OptionRight::Call));
+    assert_eq!(reg.intern(InstrumentKey::future("BTC/USDT", 20261225)), fut);
+    assert_eq!(reg.len(), 7);
+
+    assert_eq!(reg.chain("BTC/USDT", 20261225), vec![call_low, call, put]);
+    assert_eq!(reg.expiries("BTC/USDT"), vec![20260327, 20261225]);
+    assert_eq!(reg.symbol(put), Some("BTC/USDT-20261225-60000-P"));
+    assert_eq!(reg.symbol(spot), Some("BTC/USDT"));
+    // Canonical symbols parse back to their keys, including underlyings with dashes
+    for (id, key) in reg.iter() { assert_eq!(InstrumentKey::parse(reg.symbol(id).unwrap()).as_ref(), Some(key)); }
+    assert_eq!(reg.by_symbol("BTC/USDT-20261225"), Some(fut));
+    assert_eq!(InstrumentKey::parse("BTC-PERP"), Some(InstrumentKey::spot("BTC-PERP")));
+    assert_eq!(InstrumentKey::parse("X-1-20261225-5-C"), Some(InstrumentKey::option("X-1", 20261225, 5, OptionRight::Call)));
+    assert_eq!(InstrumentKey::parse(""), None);
+}
+
+#[test]
+fn commands_route_by_interned_id() {
+    let mut reg = InstrumentRegistry::default();
+    let call = reg.intern(InstrumentKey::option("BTC/USDT", 20261225, 60_000, OptionRight::Call));
+    let put = reg.intern(InstrumentKey::option("BTC/USDT", 20261225, 60_000, OptionRight::Put));
+    let unlisted = reg.intern(InstrumentKey::future("BTC/USDT", 20261225));
+    let books = [call, put].iter().map(|&id| (reg.symbol(id).unwrap().to_string(), OrderBook::new())).collect();
+    let ig = MultiIngestor::start_with_books(books, 16, HashMap::new()).unwrap();
+    let routes = ig.instrument_routes(&reg);
+    assert!(!routes.contains(unlisted) && !routes.send(unlisted, RawCommand::Market { side: Side::Buy, qty: 1 }));
+
+    assert!(routes.send(put, RawCommand::Limit { side: Side::Sell, price: 120, qty: 2 }));
+    assert!(routes.send(put, RawCommand::Limit { side: Side::Buy, price: 120, qty: 1 }));
+    let mut seen = 0;
+    while seen < 2 { seen += ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().commands; }
+    let (symbol, trade) = ig.rx_trade.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!((reg.by_symbol(&symbol), trade.qty), (Some(put), 1));
+}
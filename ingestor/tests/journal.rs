@@ -0,0 +1,117 @@
+mod common;
+
+use common::temp_path;
+use ingestor::journal::{encode_command, journal_snapshot_ref, read_journal, FileJournal, FsyncPolicy, JournalEntry, JournalReader, JournalWriter};
+use match_engine::{AccountId, ClientOrderId, Command, Emission, OrderBook, OrderId, OrderRequest, ReferenceValue, SessionState, Side, TimeInForce};
+
+fn cmds(from: u64, n: u64) -> Vec<Command> {
+    (from..from + n).map(|seq| match seq % 4 {
+        0 if seq % 8 == 4 => Command::Limit { seq, side: Side::Buy, price: 100 + seq, qty: seq + 1, tif: TimeInForce::Ioc, account: AccountId(0) },
+        0 => Command::Limit { seq, side: Side::Buy, price: 100 + seq, qty: seq + 1, tif: TimeInForce::Gtc, account: AccountId(seq % 3) },
+        1 if seq % 8 == 5 => Command::Reference { seq, value: ReferenceValue::FundingRate(-(seq as i64)) },
+        1 => Command::Market { seq, side: Side::Sell, qty: 2, account: AccountId(seq % 2) },
+        2 if seq % 8 == 6 => Command::Session { seq, state: SessionState::PreClose },
+        2 if seq % 16 == 10 => Command::Submit { seq, req: OrderRequest::limit(Side::Sell, 200 + seq, 3).with_tif(TimeInForce::Fok).with_account(AccountId(seq)).with_emission(Emission::Replay) },
+        2 if seq % 32 == 2 => Command::Submit { seq, req: OrderRequest::market(Side::Buy, 3).with_stop(90 + seq).with_account(AccountId(seq)) },
+        2 if seq % 32 == 18 => Command::Submit { seq, req: OrderRequest::limit(Side::Sell, 200 + seq, 3).iceberg(1).with_emission(Emission::Suppressed) },
+        2 if seq % 64 == 34 => Command::Submit { seq, req: OrderRequest::limit(Side::Buy, 80, 3).with_stop(90 + seq).iceberg(2).with_client_id(ClientOrderId(seq)) },
+        2 => Command::Submit { seq, req: OrderRequest::limit(Side::Sell, 200 + seq, 3).with_tif(TimeInForce::Day).with_account(AccountId(seq)).post_only().with_client_id(ClientOrderId(seq * 2)) },
+        3 if seq % 8 == 7 => Command::Reprice { seq, id: OrderId(seq / 2), new_price: 150 + seq },
+        3 if seq % 16 == 11 => Command::Amend { seq, id: OrderId(seq / 2), new_price: 140 + seq, new_qty: seq % 5 + 1 },
+        3 if seq % 32 == 19 => Command::Reduce { seq, id: OrderId(seq / 2), qty_delta: seq % 3 + 1 },
+        3 if seq % 16 == 3 => Command::CancelClient { seq, account: AccountId(seq - 1), client_id: ClientOrderId(seq * 2 - 2) },
+        _ => Command::Cancel { seq, id: OrderId(seq / 2), account: (seq % 3 == 0).then_some(AccountId(seq % 2)) },
+    }).collect()
+}
+
+#[test]
+fn file_journal_round_trips_across_reopen() {
+    let path = temp_path("reopen");
+    {
+        let mut j = FileJournal::open(&path, FsyncPolicy::EveryNBatches(2)).unwrap();
+        j.append(&cmds(0, 5)).unwrap();
+        j.append(&cmds(5, 5)).unwrap();
+    }
+    {
+        let mut j = FileJournal::open(&path, FsyncPolicy::EveryBatch).unwrap();
+        j.append(&cmds(10, 3)).unwrap();
+    }
+    assert_eq!(read_journal(&path).unwrap(), cmds(0, 13));
+}
+
+#[test]
+fn torn_tail_is_ignored_and_corruption_is_reported() {
+    let mut bytes = Vec::new();
+    for c in cmds(0, 4) { encode_command(&c, &mut bytes); }
+    let torn = &bytes[..bytes.len() - 3];
+    let read: Vec<JournalEntry> = JournalReader::new(torn).collect::<Result<_, _>>().unwrap();
+    assert_eq!(read, cmds(0, 3).into_iter().map(JournalEntry::Command).collect::<Vec<_>>());
+
+    let mut corrupt = bytes.clone();
+    corrupt[12] ^= 0xFF;
+    assert!(JournalReader::new(&corrupt[..]).any(|r| r.is_err()));
+
+    // A garbage length is reported before anything is allocated for it
+    let mut huge = bytes.clone();
+    huge[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    let err = JournalReader::new(&huge[..]).next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(JournalReader::new(&bytes[..]).all(|r| r.is_ok()));
+}
+
+// One command at a time so a rejected cancel does not stop the rest
+fn apply(ob: &mut OrderBook, cmds: &[Command]) {
+    let mut trades = Vec::new();
+    for c in cmds { let _ = ob.process_commands_batch_checked_into(&mut [*c], &mut trades); }
+}
+
+#[test]
+fn compaction_keeps_resting_orders_via_snapshot() {
+    let path = temp_path("compact");
+    let flow: Vec<Command> = (0..60u64).map(|seq| match seq % 4 {
+        0 | 1 => Command::Limit { seq, side: Side::Buy, price: 90 + seq % 7, qty: 3, tif: TimeInForce::Gtc, account: AccountId(0) },
+        2 => Command::Limit { seq, side: Side::Sell, price: 94 + seq % 5, qty: 2, tif: TimeInForce::Gtc, account: AccountId(0) },
+        _ => Command::Cancel { seq, id: OrderId(seq / 3), account: None },
+    }).collect();
+    let mut j = FileJournal::open(&path, FsyncPolicy::Never).unwrap();
+    let mut live = OrderBook::new();
+    for chunk in flow[..40].chunks(8) {
+        j.append(chunk).unwrap();
+        apply(&mut live, chunk);
+    }
+    let snap = live.snapshot();
+    j.append(&flow[40..]).unwrap();
+    apply(&mut live, &flow[40..]);
+
+    let stats = j.compact(40).unwrap();
+    assert_eq!((stats.kept, stats.dropped), (20, 40));
+    assert!(stats.bytes_after < stats.bytes_before);
+    // Appends continue after the rewrite
+    let extra = [Command::Limit { seq: 60, side: Side::Sell, price: 120, qty: 1, tif: TimeInForce::Gtc, account: AccountId(0) }];
+    j.append(&extra).unwrap();
+    apply(&mut live, &extra);
+    drop(j);
+
+    assert_eq!(journal_snapshot_ref(&path).unwrap(), Some(40));
+    let tail = read_journal(&path).unwrap();
+    assert_eq!(tail.len(), 21);
+    let mut restored = OrderBook::from_snapshot(&snap);
+    apply(&mut restored, &tail);
+    assert_eq!(restored.snapshot(), live.snapshot());
+}
+
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+#[test]
+fn uring_journal_matches_file_journal() {
+    use ingestor::journal::uring::UringJournal;
+    let path = temp_path("uring");
+    let mut j = match UringJournal::open(&path, 4, FsyncPolicy::EveryNBatches(3)) {
+        Ok(j) => j,
+        Err(e) => { eprintln!("io_uring unavailable, skipping: {}", e); return; }
+    };
+    for b in 0..20 { j.append(&cmds(b * 7, 7)).unwrap(); }
+    j.sync().unwrap();
+    assert_eq!(j.in_flight(), 0);
+    drop(j);
+    assert_eq!(read_journal(&path).unwrap(), cmds(0, 140));
+}
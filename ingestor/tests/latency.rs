@@ -0,0 +1,37 @@
+use ingestor::{MultiIngestor, MultiRawCommand, Options, RawCommand};
+use match_engine::{OrderBook, Side};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn now_micros() -> u64 { SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_micros() as u64 }
+
+#[test]
+fn progress_carries_each_stage_of_the_batch() {
+    let opts = Options { coalesce_micros: 2_000, stage_timestamps: true, ..Options::default() };
+    let ig = MultiIngestor::start_with_books_with_config(vec![("X".to_string(), OrderBook::new())], opts);
+    let sent = now_micros();
+    // Stamped by a producer 5ms before it reached the ingestor, then a plain command
+    let sourced = RawCommand::Limit { side: Side::Sell, price: 100, qty: 2 }.sourced(3, sent - 5_000);
+    for cmd in [sourced, RawCommand::Limit { side: Side::Buy, price: 100, qty: 1 }] { ig.tx_cmd.send(MultiRawCommand { symbol: "X".to_string(), cmd }).unwrap(); }
+
+    let mut received = Vec::new();
+    while received.len() < 2 {
+        let p = ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap();
+        let s = p.stages.unwrap();
+        assert_eq!(s.received.len(), p.commands);
+        assert!(sent <= s.dequeued && s.dequeued <= s.match_start && s.match_start <= s.match_end && s.match_end <= now_micros());
+        assert_eq!(s.batching_micros() + s.matching_micros(), s.match_end - s.dequeued);
+        received.extend(s.received.iter().map(|&r| (r, s.dequeued)));
+        if received.len() == 1 { assert!(s.queueing_micros(0).unwrap() >= 5_000 && s.total_micros(1).is_none()); }
+    }
+    // The producer's stamp for the sourced command; the dequeue time for the plain one
+    assert_eq!(received[0].0, sent - 5_000);
+    assert_eq!(received[1].0, received[1].1);
+}
+
+#[test]
+fn stages_are_off_by_default() {
+    let ig = MultiIngestor::start_with_books(vec![("X".to_string(), OrderBook::new())], 8, HashMap::new()).unwrap();
+    ig.tx_cmd.send(MultiRawCommand { symbol: "X".to_string(), cmd: RawCommand::Market { side: Side::Buy, qty: 1 } }).unwrap();
+    assert_eq!(ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().stages, None);
+}
@@ -0,0 +1,46 @@
+use ingestor::{MultiIngestor, MultiRawCommand, RawCommand};
+use match_engine::{OrderBook, SessionState, Side};
+use std::collections::HashMap;
+use std::time::Duration;
+
+fn send(ig: &MultiIngestor, cmd: RawCommand) { ig.tx_cmd.send(MultiRawCommand { symbol: "BTC".into(), cmd }).unwrap(); }
+
+#[test]
+fn worker_captures_depth_tape_stats_and_seq_together() {
+    let mut book = OrderBook::new();
+    book.set_trade_tape(Some(16));
+    let ig = MultiIngestor::start_with_books(vec![("BTC".into(), book)], 64, HashMap::new()).unwrap();
+    for (side, price, qty) in [(Side::Sell, 101, 2), (Side::Sell, 102, 5), (Side::Buy, 99, 4)] { send(&ig, RawCommand::Limit { side, price, qty }); }
+    send(&ig, RawCommand::Market { side: Side::Buy, qty: 3 });
+    let mut last_seq = 0;
+    while last_seq < 3 { last_seq = ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().last_seq; }
+
+    let snap = ig.market_data_snapshot("BTC", 5, 10).unwrap();
+    assert_eq!(snap.next_seq, 4);
+    assert_eq!(snap.session, SessionState::Open);
+    assert_eq!((snap.bids, snap.asks), (vec![(99, 4, 1)], vec![(102, 4, 1)]));
+    assert_eq!(snap.trades.iter().map(|e| (e.trade_id, e.trade.price, e.trade.qty)).collect::<Vec<_>>(), vec![(1, 101, 2), (2, 102, 1)]);
+    assert_eq!(snap.last_trade_id, 2);
+    assert_eq!((snap.stats.open, snap.stats.high, snap.stats.low, snap.stats.volume, snap.stats.trades), (Some(101), Some(102), Some(101), 3, 2));
+    assert!(ig.market_data_snapshot("ETH", 5, 10).is_none());
+
+    // The full book at the same point restores to an identical book
+    let book = ig.book_snapshot("BTC").unwrap();
+    assert_eq!((book.symbol.as_str(), book.next_seq, book.book.order_count()), ("BTC", 4, 2));
+    assert_eq!(OrderBook::from_snapshot(&book.book).top_n(5), (vec![(99, 4, 1)], vec![(102, 4, 1)]));
+    assert!(ig.book_snapshot("ETH").is_none());
+}
+
+#[test]
+fn session_stats_start_over_after_the_close() {
+    let mut book = OrderBook::new();
+    book.submit_limit(Side::Sell, 100, 1).unwrap();
+    book.submit_market(Side::Buy, 1).unwrap();
+    assert_eq!(book.session_stats().vwap(), Some(100.0));
+    book.set_session(SessionState::Closed);
+    assert_eq!(book.session_stats().last, Some(100));
+    book.set_session(SessionState::Open);
+    assert_eq!(book.session_stats(), Default::default());
+    // Tape off: no trades in the bundle
+    assert!(book.market_data_snapshot(7, 1, 5).trades.is_empty());
+}
@@ -0,0 +1,56 @@
+use ingestor::{Attachments, DepthMirror, MirroredDepth, MultiIngestor, MultiRawCommand, Options, RawCommand};
+use match_engine::{OrderBook, Side};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn worker_keeps_the_mirror_at_the_book_after_each_batch() {
+    let mirror = Arc::new(DepthMirror::new(2));
+    let mut book = OrderBook::new();
+    book.submit_limit(Side::Buy, 90, 4).unwrap();
+    let attach = Attachments { mirrors: HashMap::from([("BTC".to_string(), mirror.clone())]), start_seq: HashMap::from([("BTC".to_string(), 10)]), ..Attachments::default() };
+    let ig = MultiIngestor::start_with_attachments(vec![("BTC".to_string(), book)], Options::default(), attach).unwrap();
+    // Published before the first command
+    while mirror.read().bids.is_empty() { std::thread::yield_now(); }
+    assert_eq!(mirror.read(), MirroredDepth { next_seq: 10, bids: vec![(90, 4, 1)], asks: vec![] });
+
+    for (side, price, qty) in [(Side::Sell, 101, 2), (Side::Sell, 102, 1), (Side::Sell, 103, 5), (Side::Buy, 91, 3), (Side::Sell, 101, 1)] {
+        ig.tx_cmd.send(MultiRawCommand { symbol: "BTC".to_string(), cmd: RawCommand::Limit { side, price, qty } }).unwrap();
+    }
+    let mut last_seq = 0;
+    while last_seq < 14 { last_seq = ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().last_seq; }
+    let depth = mirror.read();
+    assert_eq!(depth, MirroredDepth { next_seq: 15, bids: vec![(91, 3, 1), (90, 4, 1)], asks: vec![(101, 3, 2), (102, 1, 1)] });
+    assert_eq!(mirror.next_seq(), 15);
+}
+
+#[test]
+fn readers_never_see_a_torn_publish() {
+    // Every publish is internally consistent: all quantities equal the seq. A torn copy would mix two.
+    let mirror = Arc::new(DepthMirror::new(8));
+    let done = Arc::new(AtomicBool::new(false));
+    let readers: Vec<_> = (0..2).map(|_| {
+        let (mirror, done) = (mirror.clone(), done.clone());
+        std::thread::spawn(move || {
+            let mut depth = MirroredDepth::default();
+            let mut reads = 0u64;
+            while !done.load(Ordering::Relaxed) || reads == 0 {
+                mirror.read_into(&mut depth);
+                let n = (depth.next_seq % 10) as usize;
+                assert_eq!((depth.bids.len(), depth.asks.len()), (n.min(8), n.min(8)));
+                assert!(depth.bids.iter().chain(&depth.asks).all(|&(_, qty, orders)| qty == depth.next_seq && orders == n));
+                reads += 1;
+            }
+        })
+    }).collect();
+    for seq in 1..20_000u64 {
+        let n = (seq % 10) as usize;
+        let levels: Vec<(u64, u64, usize)> = (0..n as u64).map(|i| (100 + i, seq, n)).collect();
+        mirror.publish(seq, &levels, &levels);
+    }
+    done.store(true, Ordering::Relaxed);
+    for r in readers { r.join().unwrap(); }
+    assert_eq!(mirror.read().next_seq, 19_999);
+}
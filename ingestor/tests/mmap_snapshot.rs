@@ -0,0 +1,142 @@
+#![cfg(feature = "mmap")]
+mod common;
+
+use common::temp_dir;
+use ingestor::mmap_snapshot::{write_snapshot_file, MappedSnapshots, MmapSnapshotStore};
+use ingestor::{SnapshotStore, SymbolSnapshot};
+use match_engine::{AccountId, ClientIdRetention, ClientOrderId, EngineError, OrderBook, OrderRequest, ReferenceValue, SessionState, Side, TimeInForce};
+
+fn book(base: u64) -> OrderBook {
+    let mut ob = OrderBook::new();
+    for i in 1..=3 {
+        let _ = ob.submit_limit(Side::Buy, base - i, i);
+        let _ = ob.submit_limit(Side::Sell, base + i, i);
+    }
+    let _ = ob.submit_limit(Side::Buy, base - 1, 7);
+    ob.set_reference_value(ReferenceValue::IndexPrice(base));
+    ob.set_reference_value(ReferenceValue::FundingRate(-(base as i64)));
+    ob
+}
+
+#[test]
+fn multi_symbol_file_maps_and_restores_lazily() {
+    let dir = temp_dir("multi");
+    let path = dir.join("all.snap");
+    let snaps: Vec<SymbolSnapshot> = [("BTC/USDT", 100u64), ("ETH/USDT", 50)].iter().enumerate()
+        .map(|(i, (sym, base))| SymbolSnapshot { symbol: sym.to_string(), next_seq: 10 + i as u64, book: book(*base).snapshot() })
+        .collect();
+    write_snapshot_file(&path, &snaps).unwrap();
+
+    let file = MappedSnapshots::open(&path).unwrap();
+    assert_eq!(file.len(), 2);
+    let eth = file.get("ETH/USDT").unwrap();
+    assert_eq!(eth.next_seq(), 11);
+    assert_eq!(eth.order_count(), 7);
+    assert_eq!(eth.order(0).unwrap().price, 49);
+    assert_eq!(eth.to_symbol_snapshot().unwrap(), snaps[1]);
+    let restored = eth.restore().unwrap();
+    assert_eq!(restored.top_n(5), book(50).top_n(5));
+    assert_eq!(restored.reference_values(), book(50).reference_values());
+    assert!(file.get("SOL/USDT").is_none());
+}
+
+#[test]
+fn store_round_trips_and_rejects_garbage() {
+    let dir = temp_dir("store");
+    let store = MmapSnapshotStore::new(&dir).unwrap();
+    assert!(store.load_latest("BTC/USDT").unwrap().is_none());
+    let snap = SymbolSnapshot { symbol: "BTC/USDT".into(), next_seq: 3, book: book(100).snapshot() };
+    store.save(&snap).unwrap();
+    assert_eq!(store.load_latest("BTC/USDT").unwrap(), Some(snap));
+
+    std::fs::write(dir.join("bad.snap"), b"not a snapshot").unwrap();
+    assert!(MappedSnapshots::open(&dir.join("bad.snap")).is_err());
+
+    // An unknown time in force is an error once the order is decoded, never some other tif
+    let path = store.path_for("BTC/USDT");
+    let mut bytes = std::fs::read(&path).unwrap();
+    let orders_off = u64::from_le_bytes(bytes[16 + 40..16 + 48].try_into().unwrap()) as usize;
+    bytes[orders_off + 4 * 8 + 1] = 9;
+    std::fs::write(&path, &bytes).unwrap();
+    let file = MappedSnapshots::open(&path).unwrap();
+    let btc = file.get("BTC/USDT").unwrap();
+    assert_eq!(btc.order(0).unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    assert!(btc.restore().is_err());
+    assert_eq!(store.load_latest("BTC/USDT").unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn held_stops_and_auction_orders_round_trip() {
+    let dir = temp_dir("held");
+    let path = dir.join("held.snap");
+    let mut ob = book(100);
+    ob.set_client_id_retention(Some(ClientIdRetention { max_ids: Some(8), max_age_micros: None }));
+    let _ = ob.submit_limit(Side::Buy, 101, 1);
+    let _ = ob.submit(OrderRequest::limit(Side::Buy, 105, 4).with_stop(103).iceberg(2).with_account(AccountId(3)).with_client_id(ClientOrderId(0)));
+    let _ = ob.submit(OrderRequest::market(Side::Sell, 2).with_stop(97).with_tif(TimeInForce::Day));
+    ob.set_session(SessionState::PreClose);
+    let _ = ob.submit(OrderRequest::limit(Side::Sell, 99, 5).with_tif(TimeInForce::AtClose).with_correlation(8));
+    let snaps = [SymbolSnapshot { symbol: "BTC/USDT".into(), next_seq: 4, book: ob.snapshot() }, SymbolSnapshot { symbol: "ETH/USDT".into(), next_seq: 1, book: book(50).snapshot() }];
+    write_snapshot_file(&path, &snaps).unwrap();
+
+    let file = MappedSnapshots::open(&path).unwrap();
+    let btc = file.get("BTC/USDT").unwrap();
+    assert_eq!(btc.stop_count(), 2);
+    assert_eq!(btc.stop(0).unwrap().1.client_id, Some(ClientOrderId(0)));
+    assert_eq!(btc.to_symbol_snapshot().unwrap(), snaps[0]);
+    assert_eq!(file.get("ETH/USDT").unwrap().to_symbol_snapshot().unwrap(), snaps[1]);
+    let mut restored = btc.restore().unwrap();
+    assert_eq!(restored.state_hash(), ob.state_hash());
+    assert_eq!(restored.auction_orders(), ob.auction_orders());
+    assert_eq!(restored.session(), SessionState::PreClose);
+
+    // The client id map and its window come back with the book
+    assert_eq!(btc.client_id_count(), 1);
+    assert_eq!(btc.client_id(0).3, btc.stop(0).unwrap().0);
+    assert_eq!(restored.client_id_retention(), ob.client_id_retention());
+    assert!(matches!(restored.submit(OrderRequest::limit(Side::Buy, 90, 1).with_account(AccountId(3)).with_client_id(ClientOrderId(0))), Err(EngineError::DuplicateClientId(_))));
+}
+
+#[test]
+fn client_ids_round_trip() {
+    let dir = temp_dir("clients");
+    let path = dir.join("clients.snap");
+    let mut ob = book(100);
+    let (id, _, _) = ob.submit(OrderRequest::limit(Side::Buy, 90, 1).with_account(AccountId(3)).with_client_id(ClientOrderId(5))).unwrap();
+    let snaps = [SymbolSnapshot { symbol: "BTC/USDT".into(), next_seq: 2, book: ob.snapshot() }];
+    write_snapshot_file(&path, &snaps).unwrap();
+
+    let file = MappedSnapshots::open(&path).unwrap();
+    let btc = file.get("BTC/USDT").unwrap();
+    assert_eq!(btc.client_id_count(), 1);
+    assert_eq!(btc.client_id(0), (0, AccountId(3), ClientOrderId(5), id));
+    assert_eq!(btc.to_symbol_snapshot().unwrap(), snaps[0]);
+    let mut restored = btc.restore().unwrap();
+    assert!(matches!(restored.submit(OrderRequest::limit(Side::Buy, 80, 1).with_account(AccountId(3)).with_client_id(ClientOrderId(5))), Err(EngineError::DuplicateClientId(_))));
+}
+
+#[test]
+fn paused_continuations_round_trip() {
+    let dir = temp_dir("paused");
+    let path = dir.join("paused.snap");
+    let mut ob = book(100);
+    ob.set_match_budget(Some(1));
+    let _ = ob.submit(OrderRequest::limit(Side::Buy, 110, 9).with_client_id(ClientOrderId(4)).with_account(AccountId(2)));
+    let _ = ob.submit(OrderRequest::market(Side::Sell, 30).with_tif(TimeInForce::Ioc));
+    assert_eq!(ob.pending_continuations(), 2);
+    let snaps = [SymbolSnapshot { symbol: "BTC/USDT".into(), next_seq: 3, book: ob.snapshot() }];
+    write_snapshot_file(&path, &snaps).unwrap();
+
+    let file = MappedSnapshots::open(&path).unwrap();
+    let btc = file.get("BTC/USDT").unwrap();
+    assert_eq!(btc.continuation_count(), 2);
+    assert_eq!(btc.continuation(0).unwrap(), ob.snapshot().continuations[0]);
+    assert_eq!(btc.to_symbol_snapshot().unwrap(), snaps[0]);
+    let mut restored = btc.restore().unwrap();
+    restored.set_match_budget(Some(1));
+    assert_eq!(restored.state_hash(), ob.state_hash());
+    let (mut a, mut b) = (Vec::new(), Vec::new());
+    while ob.resume_continuation(&mut a).is_some() {}
+    while restored.resume_continuation(&mut b).is_some() {}
+    assert_eq!(a, b);
+}
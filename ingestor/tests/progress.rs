@@ -0,0 +1,78 @@
+use ingestor::{MultiIngestor, Progress, RawCommand};
+use match_engine::{OrderBook, OrderId, OrderRequest, PostOnlyPolicy, Repriced, Side, StopActivation};
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[test]
+fn progress_reports_seq_watermark_after_trades() {
+    let ig = MultiIngestor::start_with_books(vec![("BTC".to_string(), OrderBook::new())], 16, HashMap::new()).unwrap();
+    let tx = &ig.routes["BTC"];
+    let (mut applied, mut last) = (0, None);
+    for (i, cmd) in [RawCommand::Limit { side: Side::Sell, price: 100, qty: 2 }, RawCommand::Market { side: Side::Buy, qty: 1 }, RawCommand::Market { side: Side::Buy, qty: 1 }].into_iter().enumerate() {
+        tx.send(cmd).unwrap();
+        while applied <= i {
+            let p: Progress = ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert_eq!(p.symbol, "BTC");
+            applied += p.commands;
+            // Trades of the batch are already on rx_trade
+            assert_eq!(ig.rx_trade.try_iter().count(), p.trades);
+            last = Some(p.last_seq);
+        }
+    }
+    assert_eq!((applied, last), (3, Some(2)));
+}
+
+#[test]
+fn progress_carries_fill_reports_when_enabled() {
+    let mut book = OrderBook::new();
+    book.set_fill_reports(true);
+    let ig = MultiIngestor::start_with_books(vec![("BTC".to_string(), book)], 16, HashMap::new()).unwrap();
+    let tx = &ig.routes["BTC"];
+    tx.send(RawCommand::Limit { side: Side::Sell, price: 100, qty: 2 }).unwrap();
+    tx.send(RawCommand::Limit { side: Side::Sell, price: 101, qty: 2 }).unwrap();
+    tx.send(RawCommand::Market { side: Side::Buy, qty: 3 }).unwrap();
+    let mut reports = Vec::new();
+    let mut applied = 0;
+    while applied < 3 {
+        let p = ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap();
+        applied += p.commands;
+        reports.extend(p.fill_reports);
+    }
+    assert_eq!(reports.len(), 1);
+    assert_eq!((reports[0].filled, reports[0].fills.clone()), (3, vec![(100, 2), (101, 1)]));
+}
+
+#[test]
+fn progress_carries_repriced_post_only_orders() {
+    let mut book = OrderBook::new();
+    book.set_post_only_policy(PostOnlyPolicy::Reprice { tick: 1 });
+    let ig = MultiIngestor::start_with_books(vec![("BTC".to_string(), book)], 16, HashMap::new()).unwrap();
+    let tx = &ig.routes["BTC"];
+    tx.send(RawCommand::Limit { side: Side::Sell, price: 100, qty: 2 }).unwrap();
+    tx.send(RawCommand::Submit(OrderRequest::limit(Side::Buy, 101, 1).post_only())).unwrap();
+    let mut repriced = Vec::new();
+    let mut applied = 0;
+    while applied < 2 {
+        let p = ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap();
+        applied += p.commands;
+        repriced.extend(p.repriced);
+    }
+    assert_eq!(repriced, vec![Repriced { id: OrderId(2), original: 101, adjusted: 99, output_seq: 1 }]);
+}
+
+#[test]
+fn progress_carries_stop_activations() {
+    let ig = MultiIngestor::start_with_books(vec![("BTC".to_string(), OrderBook::new())], 16, HashMap::new()).unwrap();
+    let tx = &ig.routes["BTC"];
+    tx.send(RawCommand::Submit(OrderRequest::market(Side::Sell, 1).with_stop(99))).unwrap();
+    tx.send(RawCommand::Limit { side: Side::Buy, price: 99, qty: 3 }).unwrap();
+    tx.send(RawCommand::Limit { side: Side::Sell, price: 99, qty: 1 }).unwrap();
+    let mut activations = Vec::new();
+    let mut applied = 0;
+    while applied < 3 {
+        let p = ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap();
+        applied += p.commands;
+        activations.extend(p.stop_activations);
+    }
+    assert_eq!(activations, vec![StopActivation { id: OrderId(1), side: Side::Sell, stop_price: 99, last_price: 99 }]);
+}
@@ -0,0 +1,53 @@
+use crossbeam_channel as cb;
+use ingestor::{Attachments, DepthPublisher, FeedBook, FeedError, FeedMsg, MultiIngestor, Options, PublisherConfig, RawCommand};
+use match_engine::{OrderBook, Side};
+use std::time::Duration;
+
+#[test]
+fn late_joiner_aligns_snapshot_with_deltas() {
+    let mut book = OrderBook::new();
+    let mut publisher = DepthPublisher::new("BTC", 5, 1_000);
+    let mut stream = Vec::new();
+    publisher.update(&book, 0, &mut stream); // initial (empty) snapshot
+    book.submit_limit(Side::Buy, 99, 2).unwrap();
+    book.submit_limit(Side::Sell, 101, 3).unwrap();
+    publisher.update(&book, 10, &mut stream);
+    book.submit_limit(Side::Buy, 100, 1).unwrap();
+    publisher.update(&book, 1_000, &mut stream); // delta, then the periodic snapshot
+    book.submit_market(Side::Buy, 3).unwrap();
+    publisher.update(&book, 1_500, &mut stream);
+    assert!(matches!(stream[4], FeedMsg::Snapshot { seq: 3, .. }));
+
+    // Joining after the first deltas: buffered deltas at or below the snapshot seq are dropped
+    let mut late = FeedBook::new();
+    for m in &stream[2..] { late.apply(m).unwrap(); }
+    assert_eq!(late.seq(), Some(4));
+    assert_eq!((late.bids(), late.asks()), (&book.top_n(5).0, &book.top_n(5).1));
+    assert!(late.asks().is_empty());
+
+    // A missing delta is reported and the book holds until it arrives
+    let mut gapped = FeedBook::new();
+    gapped.apply(&stream[0]).unwrap();
+    assert_eq!(gapped.apply(&stream[2]), Err(FeedError::Gap { expected: 1, got: 2 }));
+    for m in &stream[1..] { gapped.apply(m).unwrap(); }
+    assert_eq!(gapped.bids(), late.bids());
+}
+
+#[test]
+fn ingestor_publishes_feed_per_symbol() {
+    let (tx_feed, rx_feed) = cb::unbounded();
+    let cfg = PublisherConfig { levels: 3, snapshot_interval_micros: 10_000, ..PublisherConfig::default() };
+    let attach = Attachments { feed: Some((cfg, tx_feed)), ..Attachments::default() };
+    let ig = MultiIngestor::start_with_attachments(vec![("ETH".to_string(), OrderBook::new())], Options::default(), attach).unwrap();
+    ig.routes["ETH"].send(RawCommand::Limit { side: Side::Sell, price: 200, qty: 4 }).unwrap();
+    let mut book = FeedBook::new();
+    let (mut snapshots, mut deltas) = (0, 0);
+    while snapshots < 3 || deltas < 1 {
+        let m = rx_feed.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(m.symbol(), "ETH");
+        match m { FeedMsg::Snapshot { .. } => snapshots += 1, FeedMsg::Delta { .. } => deltas += 1 }
+        book.apply(&m).unwrap();
+    }
+    // Snapshots keep coming while idle
+    assert_eq!(book.asks(), &vec![(200, 4, 1)]);
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "mmap")]
+mod common;
+
+use common::temp_path;
+use ingestor::journal::{read_journal, FileJournal};
+use ingestor::mmap_snapshot::MmapSnapshotStore;
+use ingestor::{Attachments, FsyncPolicy, JournalConfig, JournalWriter, MultiIngestor, MultiRawCommand, Options, RawCommand, ReplayError, SnapshotStore, SymbolSnapshot};
+use match_engine::{AccountId, Command, OrderBook, Side, SymbolConfig, TimeInForce};
+use std::io::Write;
+use std::time::Duration;
+
+fn send(ig: &MultiIngestor, symbol: &str, n: u64, from: u64) {
+    for i in from..from + n {
+        let cmd = match i % 4 { 3 => RawCommand::Market { side: Side::Sell, qty: 2 }, n => RawCommand::Limit { side: if n == 1 { Side::Sell } else { Side::Buy }, price: 100 + i % 3, qty: 3 } };
+        ig.tx_cmd.send(MultiRawCommand { symbol: symbol.to_string(), cmd }).unwrap();
+    }
+}
+
+fn wait_for(ig: &MultiIngestor, symbol: &str, last_seq: u64) {
+    loop {
+        let p = ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap();
+        if p.symbol == symbol && p.last_seq >= last_seq { return; }
+    }
+}
+
+#[test]
+fn recovers_from_snapshot_and_journal_tail_then_carries_on() {
+    let (snaps, journals) = (temp_path("snaps"), temp_path("journals"));
+    let journal = JournalConfig::new(&journals, FsyncPolicy::EveryBatch);
+    let opts = Options { batch_size: 4, snapshot_every_commands: 10, ..Options::default() };
+    let attach = Attachments { snapshot_store: Some(std::sync::Arc::new(MmapSnapshotStore::new(&snaps).unwrap())), journal: Some(journal.clone()), ..Attachments::default() };
+    let books = vec![("BTC/USDT".to_string(), OrderBook::new()), ("ETH".to_string(), OrderBook::new())];
+    let ig = MultiIngestor::start_with_attachments(books, opts, attach).unwrap();
+    send(&ig, "BTC/USDT", 25, 0);
+    send(&ig, "ETH", 7, 0);
+    wait_for(&ig, "BTC/USDT", 24);
+    let store = MmapSnapshotStore::new(&snaps).unwrap();
+    while store.load_latest("BTC/USDT").unwrap().is_none_or(|s| s.next_seq < 20) { std::thread::sleep(Duration::from_millis(5)); }
+    let live: Vec<SymbolSnapshot> = ["BTC/USDT", "ETH"].iter().map(|s| ig.book_snapshot(s).unwrap()).collect();
+    // Crash: half a frame at the end of one journal
+    std::fs::OpenOptions::new().append(true).open(journal.path_for("ETH")).unwrap().write_all(&[9, 0, 0, 0, 1]).unwrap();
+
+    let recovered = MultiIngestor::recover(&snaps, &journals, opts).unwrap();
+    for snap in &live { assert_eq!(&recovered.book_snapshot(&snap.symbol).unwrap(), snap); }
+    assert_eq!((live[0].next_seq, live[1].next_seq), (25, 7));
+
+    // New commands continue the sequence, and the journal stays readable past the cut
+    send(&recovered, "ETH", 3, 7);
+    wait_for(&recovered, "ETH", 9);
+    let seqs: Vec<u64> = read_journal(&journal.path_for("ETH")).unwrap().iter().map(Command::seq).collect();
+    assert_eq!(seqs, (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn a_gap_between_snapshot_and_journal_fails_recovery() {
+    let (snaps, journals) = (temp_path("gap-snaps"), temp_path("gap-journals"));
+    let mut book = OrderBook::new();
+    book.submit_limit(Side::Buy, 99, 1).unwrap();
+    MmapSnapshotStore::new(&snaps).unwrap().save(&SymbolSnapshot { symbol: "X".to_string(), next_seq: 50, book: book.snapshot() }).unwrap();
+    let journal = JournalConfig::new(&journals, FsyncPolicy::Never);
+    let mut j: FileJournal = journal.open("X").unwrap();
+    j.append(&[Command::Limit { seq: 60, side: Side::Sell, price: 101, qty: 1, tif: TimeInForce::Gtc, account: AccountId(0) }]).unwrap();
+    j.sync().unwrap();
+    assert!(matches!(MultiIngestor::recover(&snaps, &journals, Options::default()), Err(ReplayError::Gap { expected: 50, found: 60 })));
+
+    // A snapshot alone is enough
+    std::fs::remove_file(journal.path_for("X")).unwrap();
+    let ig = MultiIngestor::recover(&snaps, &journals, Options::default()).unwrap();
+    assert_eq!(ig.book_snapshot("X").unwrap().next_seq, 50);
+}
+
+#[test]
+fn commands_rejected_live_are_rejected_during_recovery() {
+    let (snaps, journals) = (temp_path("cfg-snaps"), temp_path("cfg-journals"));
+    let journal = JournalConfig::new(&journals, FsyncPolicy::EveryBatch);
+    let configs = std::collections::HashMap::from([("X".to_string(), SymbolConfig { tick: 5, ..SymbolConfig::default() })]);
+    let attach = Attachments { journal: Some(journal.clone()), symbol_configs: configs.clone(), ..Attachments::default() };
+    let ig = MultiIngestor::start_with_attachments(vec![("X".to_string(), OrderBook::new())], Options::default(), attach).unwrap();
+    for price in [100, 103, 95] { ig.tx_cmd.send(MultiRawCommand { symbol: "X".to_string(), cmd: RawCommand::Limit { side: Side::Buy, price, qty: 1 } }).unwrap(); }
+    wait_for(&ig, "X", 2);
+    let live = ig.book_snapshot("X").unwrap();
+    assert_eq!(live.book.bids.iter().map(|l| l.price).collect::<Vec<_>>(), vec![100, 95]);
+
+    let recovered = MultiIngestor::recover_with_attachments(&snaps, journal, Options::default(), Attachments { symbol_configs: configs, ..Attachments::default() }).unwrap();
+    assert_eq!(recovered.book_snapshot("X").unwrap(), live);
+}
@@ -0,0 +1,28 @@
+use ingestor::{MultiIngestor, PriceCondition, RawCommand};
+use match_engine::{OrderBook, ReferenceValue, Side};
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[test]
+fn injected_index_reaches_book_snapshot_and_triggers() {
+    let books = vec![("BTC-PERP".to_string(), OrderBook::new()), ("BTC".to_string(), OrderBook::new())];
+    let ig = MultiIngestor::start_with_books(books, 16, HashMap::new()).unwrap();
+    let recv_done = |n: usize| { let mut d = 0; while d < n { d += ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().commands; } };
+
+    ig.routes["BTC"].send(RawCommand::Limit { side: Side::Sell, price: 50, qty: 10 }).unwrap();
+    recv_done(1);
+    let hedge = ig.add_trigger("BTC-PERP", PriceCondition::IndexAtOrAbove(30_000), "BTC", RawCommand::Market { side: Side::Buy, qty: 2 }).unwrap();
+
+    ig.routes["BTC-PERP"].send(RawCommand::Reference(ReferenceValue::IndexPrice(29_000))).unwrap();
+    ig.routes["BTC-PERP"].send(RawCommand::Reference(ReferenceValue::FundingRate(12))).unwrap();
+    recv_done(2);
+    assert!(ig.rx_triggered.try_recv().is_err());
+    let md = ig.market_data_snapshot("BTC-PERP", 1, 0).unwrap();
+    assert_eq!((md.reference.index_price, md.reference.funding_rate), (Some(29_000), Some(12)));
+
+    ig.routes["BTC-PERP"].send(RawCommand::Reference(ReferenceValue::IndexPrice(30_500))).unwrap();
+    let fired = ig.rx_triggered.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!((fired.id, fired.observed), (hedge, 30_500));
+    let (symbol, t) = ig.rx_trade.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!((symbol.as_str(), t.price, t.qty), ("BTC", 50, 2));
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::temp_path;
+use ingestor::journal::{compact_journal, read_journal, FileJournal};
+use ingestor::{replay, replay_journal, Attachments, FsyncPolicy, JournalConfig, JournalWriter, MultiIngestor, MultiRawCommand, Options, RawCommand, ReplayError, Replayer, SymbolSnapshot};
+use match_engine::{AccountId, Command, EngineError, OrderBook, OrderId, Side, TimeInForce};
+use std::time::Duration;
+
+fn limit(seq: u64, side: Side, price: u64, qty: u64) -> Command { Command::Limit { seq, side, price, qty, tif: TimeInForce::Gtc, account: AccountId(seq % 3) } }
+
+#[test]
+fn journal_replays_to_the_live_book_and_to_any_point_in_time() {
+    let cfg = JournalConfig::new(temp_path("live"), FsyncPolicy::EveryBatch);
+    let ig = MultiIngestor::start_with_attachments(vec![("BTC".to_string(), OrderBook::new())], Options { batch_size: 3, ..Options::default() }, Attachments { journal: Some(cfg.clone()), ..Attachments::default() }).unwrap();
+    for i in 0..30u64 {
+        let cmd = match i % 5 { 4 => RawCommand::Market { side: Side::Buy, qty: 3 }, n => RawCommand::Limit { side: if n % 2 == 0 { Side::Sell } else { Side::Buy }, price: 100 + i % 4, qty: 2 } };
+        ig.tx_cmd.send(MultiRawCommand { symbol: "BTC".to_string(), cmd }).unwrap();
+    }
+    let mut seen = 0;
+    while seen < 30 { seen += ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().commands; }
+    let live = ig.book_snapshot("BTC").unwrap();
+    let path = cfg.path_for("BTC");
+    assert_eq!(replay_journal(&path, None).unwrap().snapshot(), live.book);
+
+    // Point in time: the book before seq 17, from the log alone or on top of an earlier snapshot
+    let log = read_journal(&path).unwrap();
+    let mut r = Replayer::empty().recording_trades();
+    assert_eq!(r.apply_journal_until(&path, 17).unwrap(), 17);
+    assert_eq!((r.next_seq(), r.book().snapshot()), (Some(17), replay(&log[..17]).unwrap().snapshot()));
+    assert!(!r.take_trades().is_empty());
+    let base = SymbolSnapshot { symbol: "BTC".to_string(), next_seq: 9, book: replay(&log[..9]).unwrap().snapshot() };
+    let mut r = Replayer::from_snapshot(&base);
+    assert_eq!(r.apply(&log).unwrap(), 21);
+    assert_eq!(r.into_book().snapshot(), live.book);
+}
+
+#[test]
+fn gaps_and_missing_bases_are_errors() {
+    let cmds = [limit(5, Side::Sell, 100, 2), limit(6, Side::Buy, 100, 1), limit(8, Side::Buy, 99, 1)];
+    let mut r = Replayer::empty();
+    assert!(matches!(r.apply(&cmds), Err(ReplayError::Gap { expected: 7, found: 8 })));
+    // Everything before the gap is applied and the replay can pick up from there
+    assert_eq!((r.next_seq(), r.book().best_bid()), (Some(7), None));
+    assert_eq!(r.apply(&[limit(7, Side::Buy, 98, 1), cmds[2]]).unwrap(), 2);
+    assert_eq!(r.book().best_bid(), Some((99, 1)));
+
+    let path = temp_path("compacted").with_extension("journal");
+    let mut j = FileJournal::open(&path, FsyncPolicy::EveryBatch).unwrap();
+    j.append(&cmds[..2]).unwrap();
+    drop(j);
+    compact_journal(&path, 6).unwrap();
+    assert!(matches!(replay_journal(&path, None), Err(ReplayError::MissingBase { journal: 6, next_seq: None })));
+    let base = SymbolSnapshot { symbol: "X".to_string(), next_seq: 6, book: replay(&cmds[..1]).unwrap().snapshot() };
+    assert_eq!(replay_journal(&path, Some(&base)).unwrap().snapshot(), replay(&cmds[..2]).unwrap().snapshot());
+}
+
+#[test]
+fn a_rejection_inside_a_batch_replays_like_the_live_worker() {
+    let cfg = JournalConfig::new(temp_path("rejected"), FsyncPolicy::EveryBatch);
+    let opts = Options { coalesce_micros: 20_000, ..Options::default() };
+    let ig = MultiIngestor::start_with_attachments(vec![("X".to_string(), OrderBook::new())], opts, Attachments { journal: Some(cfg.clone()), ..Attachments::default() }).unwrap();
+    let cmds = [RawCommand::Limit { side: Side::Buy, price: 100, qty: 2 }, RawCommand::Cancel { id: OrderId(42), account: None }, RawCommand::Limit { side: Side::Sell, price: 110, qty: 7 }];
+    for cmd in cmds { ig.tx_cmd.send(MultiRawCommand { symbol: "X".to_string(), cmd }).unwrap(); }
+    let mut seen = 0;
+    while seen < 3 { seen += ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().commands; }
+    let live = ig.book_snapshot("X").unwrap();
+    assert_eq!(OrderBook::from_snapshot(&live.book).best_ask(), Some((110, 7)));
+
+    let mut r = Replayer::empty().recording_rejections();
+    assert_eq!(r.apply_journal(&cfg.path_for("X")).unwrap(), 3);
+    assert_eq!(r.book().snapshot(), live.book);
+    assert_eq!(r.take_rejections(), vec![(1, EngineError::UnknownOrder)]);
+}
@@ -0,0 +1,43 @@
+use crossbeam_channel as cb;
+use ingestor::{MultiIngestor, Options, RawCommand, Standby};
+use match_engine::{OrderBook, Side};
+use std::time::{Duration, Instant};
+
+fn books() -> Vec<(String, OrderBook)> { vec![("BTC".to_string(), OrderBook::new()), ("ETH".to_string(), OrderBook::new())] }
+
+fn wait_until(mut f: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !f() {
+        assert!(Instant::now() < deadline, "condition not reached");
+        std::thread::sleep(Duration::from_millis(2));
+    }
+}
+
+#[test]
+fn standby_tracks_primary_and_takes_over() {
+    let (tx_repl, rx_repl) = cb::unbounded();
+    let opts = Options { batch_size: 8, replication_hash_every_commands: 5, ..Options::default() };
+    let primary = MultiIngestor::start_primary(books(), opts, tx_repl);
+    let standby = Standby::start(books(), rx_repl);
+    for i in 0..40u64 {
+        let side = if i.is_multiple_of(2) { Side::Buy } else { Side::Sell };
+        let tx = primary.routes.get(if i % 3 == 0 { "ETH" } else { "BTC" }).unwrap();
+        tx.send(RawCommand::Limit { side, price: 100 + i % 5, qty: 1 + i % 4 }).unwrap();
+    }
+    wait_until(|| {
+        let st = standby.status();
+        st.next_seq.get("BTC") == Some(&26) && st.next_seq.get("ETH") == Some(&14)
+    });
+    let st = standby.status();
+    assert!(st.checkpoints_ok > 0);
+    assert!(st.divergences.is_empty(), "{:?}", st.divergences);
+
+    // Primary dies; the promoted standby continues each symbol's sequence
+    drop(primary);
+    wait_until(|| standby.status().primary_disconnected);
+    let promoted = standby.promote(Options::default());
+    let tx = promoted.routes.get("BTC").unwrap();
+    tx.send(RawCommand::Market { side: Side::Buy, qty: 1 }).unwrap();
+    let (sym, trade) = promoted.rx_trade.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!((sym.as_str(), trade.qty), ("BTC", 1));
+}
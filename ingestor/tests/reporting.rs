@@ -0,0 +1,36 @@
+use ingestor::reporting::{Capacity, TradeReporter};
+use match_engine::{OrderBook, Side};
+use std::fs;
+
+const DAY: u64 = 86_400_000_000;
+
+#[test]
+fn writes_one_closed_file_per_day() {
+    let dir = std::env::temp_dir().join(format!("me-reporting-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let mut ob = OrderBook::new();
+    let mut rep = TradeReporter::create(&dir, "XSIM", Capacity::Agency).unwrap();
+    ob.submit_limit(Side::Buy, 100, 5).unwrap();
+    ob.submit_limit(Side::Sell, 101, 5).unwrap();
+    // 2024-03-01 09:30:00.000250 UTC
+    let day1 = 19_783 * DAY + 34_200_000_250;
+    let (_, trades, _) = ob.submit_market(Side::Sell, 2).unwrap();
+    rep.record("BTC", day1, &trades[0]).unwrap();
+    assert!(dir.join("trades-20240301.psv.partial").exists());
+    let (_, trades, _) = ob.submit_market(Side::Buy, 3).unwrap();
+    rep.record("BTC", day1 + 1, &trades[0]).unwrap();
+    // A trade on the next day closes the previous file
+    rep.record("ETH", day1 + DAY, &trades[0]).unwrap();
+    assert_eq!(rep.closed_files(), &[dir.join("trades-20240301.psv")]);
+    let files = rep.finish().unwrap();
+    assert_eq!(files[1], dir.join("trades-20240302.psv"));
+
+    let text = fs::read_to_string(&files[0]).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "HDR|XSIM|20240301");
+    assert_eq!(lines[2], "2024-03-01T09:30:00.000250Z|XSIM|BTC|20240301-1|1|3|S|A|100|2");
+    assert_eq!(lines[3], "2024-03-01T09:30:00.000251Z|XSIM|BTC|20240301-2|4|2|B|A|101|3");
+    assert_eq!(lines[4], "TRL|2|5");
+    assert!(fs::read_to_string(&files[1]).unwrap().contains("|ETH|20240302-1|"));
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,40 @@
+use crossbeam_channel as cb;
+use ingestor::{FeedBook, FeedError, FeedFanout, FeedMsg, ResendError, Retransmitter};
+use match_engine::Side;
+use std::time::Duration;
+
+fn delta(seq: u64, price: u64) -> FeedMsg {
+    FeedMsg::Delta { symbol: "BTC".to_string(), seq, side: Side::Buy, price, qty: 1, orders: 1 }
+}
+
+#[test]
+fn retains_last_n_per_symbol() {
+    let mut r = Retransmitter::new(3);
+    for seq in 1..=5 { r.record(&delta(seq, 100 - seq)); }
+    assert_eq!(r.resend("BTC", 3, 4).unwrap(), vec![delta(3, 97), delta(4, 96)]);
+    assert_eq!(r.resend("BTC", 2, 4), Err(ResendError::NotRetained { oldest: 3 }));
+    assert_eq!(r.resend("BTC", 5, 6), Err(ResendError::NotPublished { last: 5 }));
+    assert_eq!(r.resend("ETH", 1, 1), Err(ResendError::UnknownSymbol));
+}
+
+#[test]
+fn subscriber_fills_gap_through_fanout() {
+    let (tx, rx) = cb::unbounded();
+    let fanout = FeedFanout::start(rx, 16);
+    let sub = fanout.subscribe();
+    tx.send(FeedMsg::Snapshot { symbol: "BTC".to_string(), seq: 0, bids: Vec::new(), asks: Vec::new() }).unwrap();
+    for seq in 1..=4 { tx.send(delta(seq, 100 - seq)).unwrap(); }
+    drop(tx);
+
+    let mut book = FeedBook::new();
+    for m in sub.iter().filter(|m| m.seq() != 2) { // seq 2 lost in transit
+        if let Err(FeedError::Gap { expected, got }) = book.apply(&m) {
+            for r in fanout.resend("BTC", expected, got - 1).unwrap() { book.apply(&r).unwrap(); }
+            book.apply(&m).unwrap();
+        }
+    }
+    assert_eq!(book.seq(), Some(4));
+    assert_eq!(book.bids().len(), 4);
+    assert!(sub.recv_timeout(Duration::from_millis(10)).is_err());
+    fanout.join();
+}
@@ -0,0 +1,36 @@
+use ingestor::schedule::{now_micros, Scheduler};
+use ingestor::{MultiIngestor, RawCommand, ScheduledCommand};
+use match_engine::{OrderBook, Side};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+#[test]
+fn scheduler_releases_due_commands_first_in_time_order() {
+    let mut s = Scheduler::new();
+    let at = |activate_at, price| RawCommand::At { activate_at, cmd: ScheduledCommand::Limit { side: Side::Buy, price, qty: 1 } };
+    let mut batch = vec![at(300, 3), at(100, 1), RawCommand::Market { side: Side::Sell, qty: 1 }, at(100, 2)];
+    s.admit(&mut batch, 50);
+    assert_eq!(batch.len(), 1);
+    assert_eq!(s.len(), 3);
+    assert_eq!(s.next_wait(50), Some(Duration::from_micros(50)));
+
+    let mut batch = vec![at(0, 9)];
+    s.admit(&mut batch, 100);
+    let prices: Vec<u64> = batch.iter().map(|c| match *c { RawCommand::Limit { price, .. } => price, _ => 0 }).collect();
+    assert_eq!(prices, vec![1, 2, 9]);
+    assert_eq!(s.len(), 1);
+}
+
+#[test]
+fn worker_injects_scheduled_order_at_activation_time() {
+    let ig = MultiIngestor::start_with_books(vec![("BTC".to_string(), OrderBook::new())], 16, HashMap::new()).unwrap();
+    let tx = ig.routes.get("BTC").unwrap();
+    let start = Instant::now();
+    tx.send(RawCommand::Limit { side: Side::Sell, price: 100, qty: 2 }).unwrap();
+    let activate_at = now_micros() + 150_000;
+    tx.send(RawCommand::At { activate_at, cmd: ScheduledCommand::Limit { side: Side::Buy, price: 100, qty: 2 } }).unwrap();
+    let (sym, trade) = ig.rx_trade.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!((sym.as_str(), trade.qty), ("BTC", 2));
+    assert!(now_micros() >= activate_at);
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
@@ -0,0 +1,53 @@
+use crossbeam_channel as cb;
+use ingestor::{spawn_sequencer, MergeQueue, MultiRawCommand, RawCommand, ScheduledCommand, SequenceError};
+use match_engine::Side;
+
+fn limit(price: u64) -> MultiRawCommand {
+    MultiRawCommand { symbol: "BTC".to_string(), cmd: RawCommand::Limit { side: Side::Buy, price, qty: 1 } }
+}
+
+fn price_of(c: &MultiRawCommand) -> u64 {
+    match c.cmd { RawCommand::Limit { price, .. } | RawCommand::Sourced { cmd: ScheduledCommand::Limit { price, .. }, .. } => price, _ => unreachable!() }
+}
+
+#[test]
+fn merge_orders_by_ts_then_producer_and_waits_for_idle_producers() {
+    let mut m = MergeQueue::new(3);
+    m.push(1, 10, limit(1)).unwrap();
+    m.push(0, 10, limit(2)).unwrap();
+    // Producer 2 has said nothing yet and could still send ts 0
+    assert!(m.pop_ready().is_none());
+    m.heartbeat(2, 10).unwrap();
+    // (10, 2) sorts after (10, 0) and (10, 1)
+    assert_eq!(m.pop_ready().map(|s| (s.producer, s.sub_seq)), Some((0, 0)));
+    // Producer 0 could still send another ts-10 command, which would sort first
+    assert!(m.pop_ready().is_none());
+    m.heartbeat(0, 11).unwrap();
+    assert_eq!(m.pop_ready().map(|s| (s.producer, s.sub_seq)), Some((1, 0)));
+    assert_eq!(m.push(1, 9, limit(3)), Err(SequenceError::TimestampRegressed { last: 10, got: 9 }));
+    m.push(1, 12, limit(4)).unwrap();
+    m.close(2);
+    m.heartbeat(0, 20).unwrap();
+    assert_eq!(m.pop_ready().map(|s| (s.producer, s.sub_seq, s.ts)), Some((1, 1, 12)));
+    assert_eq!(m.push(2, 30, limit(5)), Err(SequenceError::ProducerClosed));
+}
+
+#[test]
+fn threaded_producers_yield_reproducible_order() {
+    let run = || {
+        let (tx, rx) = cb::unbounded();
+        let handles = spawn_sequencer(4, tx);
+        let threads: Vec<_> = handles.into_iter().map(|mut h| std::thread::spawn(move || {
+            for i in 0..200u64 {
+                let ts = i / 3 + h.id() as u64 % 2;
+                h.send(ts, limit(h.id() as u64 * 1_000 + i)).unwrap();
+                if i % 17 == 0 { std::thread::yield_now(); }
+            }
+        })).collect();
+        for t in threads { t.join().unwrap(); }
+        rx.iter().take(800).map(|c| price_of(&c)).collect::<Vec<_>>()
+    };
+    let first = run();
+    assert_eq!(first.len(), 800);
+    for _ in 0..5 { assert_eq!(run(), first); }
+}
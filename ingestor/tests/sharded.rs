@@ -0,0 +1,75 @@
+use ingestor::{HashRing, MultiRawCommand, Options, RawCommand, ShardedIngestor};
+use match_engine::{EngineError, ErrorPolicy, FaultAction, OrderBook, OrderRequest, PreMatchHook, Side};
+use std::time::Duration;
+
+fn symbols(n: usize) -> Vec<String> { (0..n).map(|i| format!("SYM{}", i)).collect() }
+
+#[test]
+fn adding_a_worker_moves_only_symbols_it_now_owns() {
+    let mut ring = HashRing::new();
+    for w in 0..4 { ring.add(w); }
+    let before: Vec<usize> = symbols(1000).iter().map(|s| ring.owner(s).unwrap()).collect();
+    for w in 0..4 { assert!(before.iter().filter(|&&o| o == w).count() > 100); }
+    ring.add(4);
+    let after: Vec<usize> = symbols(1000).iter().map(|s| ring.owner(s).unwrap()).collect();
+    let moved = before.iter().zip(&after).filter(|(b, a)| b != a).count();
+    // Roughly a fifth of the keys move, all of them to the new worker
+    assert!(before.iter().zip(&after).all(|(b, a)| b == a || *a == 4));
+    assert!((100..350).contains(&moved), "moved {}", moved);
+}
+
+#[test]
+fn live_migration_keeps_book_and_sequence() {
+    let books = symbols(8).into_iter().map(|s| (s, OrderBook::new())).collect();
+    let ig = ShardedIngestor::start(books, 2, Options { batch_size: 16, ..Options::default() });
+    let send = |sym: &str, cmd| ig.tx_cmd.send(MultiRawCommand { symbol: sym.to_string(), cmd }).unwrap();
+    for s in symbols(8) { send(&s, RawCommand::Limit { side: Side::Sell, price: 100, qty: 5 }); }
+
+    let (new_worker, moved) = ig.add_worker();
+    assert_eq!(new_worker, 2);
+    assert!(moved.iter().all(|m| m.to == 2));
+    // Pin one symbol to a different worker, as relief for an overloaded one
+    let sym = &moved.first().map(|m| m.symbol.clone()).unwrap_or_else(|| "SYM0".into());
+    let target = (ig.assignments().iter().find(|(s, _)| s == sym).unwrap().1 + 1) % 3;
+    assert_eq!(ig.move_symbol(sym, target).unwrap().to, target);
+    assert!(ig.remove_worker(0).is_some());
+    assert!(ig.assignments().iter().all(|&(_, w)| w != 0));
+
+    // Every book still holds its resting ask and keeps counting seq from where it was
+    for s in symbols(8) { send(&s, RawCommand::Market { side: Side::Buy, qty: 2 }); }
+    let mut trades = 0;
+    while trades < 8 {
+        let (_, t) = ig.rx_trade.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!((t.price, t.qty), (100, 2));
+        trades += 1;
+    }
+    let snap = ig.remove_symbol(sym).unwrap();
+    assert_eq!(snap.next_seq, 2);
+    assert_eq!(snap.book.asks[0].orders[0].qty, 3);
+    assert!(ig.remove_symbol(sym).is_none());
+}
+
+// Fails 13-lot orders with an internal error
+struct Faulty;
+
+impl PreMatchHook for Faulty {
+    fn pre_match(&mut self, req: &mut OrderRequest) -> Result<(), EngineError> {
+        if req.qty == 13 { Err(EngineError::Invariant("injected".into())) } else { Ok(()) }
+    }
+}
+
+#[test]
+fn fail_fast_faults_are_reported_with_the_commands_applied() {
+    let mut book = OrderBook::new();
+    book.set_error_policy(ErrorPolicy::FailFast);
+    book.add_pre_match_hook(Box::new(Faulty));
+    let ig = ShardedIngestor::start(vec![("X".to_string(), book)], 1, Options { batch_size: 16, ..Options::default() });
+    for qty in [1, 13, 2] { ig.tx_cmd.send(MultiRawCommand { symbol: "X".to_string(), cmd: RawCommand::Limit { side: Side::Buy, price: 100, qty } }).unwrap(); }
+    // Whatever the batching, the batch that hit the fault reports it and ends at it
+    let p = loop {
+        let p = ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap();
+        if !p.faults.is_empty() { break p; }
+    };
+    assert_eq!((p.faults.len(), p.faults[0].seq, p.faults[0].action), (1, 1, FaultAction::CancelOnly));
+    assert_eq!(p.last_seq, 1);
+}
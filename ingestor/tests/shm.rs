@@ -0,0 +1,73 @@
+#![cfg(feature = "shm")]
+use ingestor::shm::{ShmClient, ShmServer};
+use ingestor::{MultiIngestor, MultiRawCommand, RawCommand};
+use match_engine::{OrderBook, Side};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+fn region_path(name: &str) -> PathBuf { std::env::temp_dir().join(format!("me-shm-{}-{}", name, std::process::id())) }
+
+fn cmd(symbol: &str, cmd: RawCommand) -> MultiRawCommand { MultiRawCommand { symbol: symbol.to_string(), cmd } }
+
+#[test]
+fn registration_backpressure_and_seq_tracking() {
+    let path = region_path("proto");
+    let server = ShmServer::create(&path, 4).unwrap();
+    let mut client = ShmClient::attach(&path).unwrap();
+    assert!(ShmClient::attach(&path).is_err());
+    assert_eq!(server.client_pid(), Some(std::process::id() as u64));
+    for i in 0..4 { assert_eq!(client.publish(&cmd("BTC", RawCommand::Market { side: Side::Buy, qty: i + 1 })).unwrap(), i); }
+    let full = client.publish(&cmd("BTC", RawCommand::Market { side: Side::Buy, qty: 9 }));
+    assert_eq!(full.unwrap_err().kind(), std::io::ErrorKind::WouldBlock);
+    let first = server.poll_command().unwrap();
+    assert_eq!(first.client_seq, 0);
+    assert_eq!(first.cmd.symbol, "BTC");
+    assert!(matches!(first.cmd.cmd, RawCommand::Market { qty: 1, .. }));
+    assert_eq!(client.acked_seq(), 1);
+
+    // Reattach resumes numbering after the commands already queued
+    drop(client);
+    assert_eq!(server.client_pid(), None);
+    let mut client = ShmClient::attach(&path).unwrap();
+    assert_eq!(client.session(), 2);
+    assert_eq!(client.publish(&cmd("BTC", RawCommand::Cancel { id: match_engine::OrderId(7), account: None })).unwrap(), 4);
+    while server.poll_command().is_some() {}
+    assert_eq!((client.acked_seq(), server.gaps()), (5, 0));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn bad_capacities_are_rejected() {
+    let path = region_path("capacity");
+    for capacity in [0, 3] { assert_eq!(ShmServer::create(&path, capacity).err().unwrap().kind(), std::io::ErrorKind::InvalidInput); }
+
+    // A corrupt capacity in an existing region must not wrap the size check
+    drop(ShmServer::create(&path, 4).unwrap());
+    let mut bytes = std::fs::read(&path).unwrap();
+    for capacity in [0u64, 5, 1 << 62] {
+        bytes[16..24].copy_from_slice(&capacity.to_le_bytes());
+        std::fs::write(&path, &bytes).unwrap();
+        assert_eq!(ShmClient::attach(&path).err().unwrap().kind(), std::io::ErrorKind::InvalidData);
+    }
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn bridge_feeds_ingestor_and_returns_trades() {
+    let path = region_path("bridge");
+    let ig = MultiIngestor::start_with_books(vec![("ETH-USD".to_string(), OrderBook::new())], 64, HashMap::new()).unwrap();
+    let server = ShmServer::create(&path, 64).unwrap();
+    let mut client = ShmClient::attach(&path).unwrap();
+    let _bridge = server.bridge(&ig);
+    client.publish(&cmd("ETH-USD", RawCommand::Limit { side: Side::Sell, price: 50, qty: 3 })).unwrap();
+    client.publish(&cmd("ETH-USD", RawCommand::Limit { side: Side::Buy, price: 50, qty: 2 })).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let ev = loop {
+        if let Some(ev) = client.poll_event() { break ev; }
+        assert!(Instant::now() < deadline, "no event over shm");
+        std::thread::sleep(Duration::from_millis(1));
+    };
+    assert_eq!((ev.symbol.as_str(), ev.trade.price, ev.trade.qty), ("ETH-USD", 50, 2));
+    let _ = std::fs::remove_file(&path);
+}
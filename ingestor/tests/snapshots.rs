@@ -0,0 +1,39 @@
+use ingestor::{MemorySnapshotStore, MultiIngestor, Options, RawCommand, SnapshotStore};
+use match_engine::{OrderBook, Side};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn wait_for_seq(store: &MemorySnapshotStore, symbol: &str, next_seq: u64) -> ingestor::SymbolSnapshot {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        if let Some(s) = store.load_latest(symbol).unwrap() {
+            if s.next_seq >= next_seq { return s; }
+        }
+        assert!(Instant::now() < deadline, "snapshot for {} not written", symbol);
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+#[test]
+fn snapshots_every_n_commands() {
+    let store = Arc::new(MemorySnapshotStore::new());
+    let opts = Options { batch_size: 1, snapshot_every_commands: 2, ..Options::default() };
+    let ig = MultiIngestor::start_with_books_with_snapshots(vec![("BTC".to_string(), OrderBook::new())], opts, store.clone());
+    let tx = ig.routes.get("BTC").unwrap();
+    tx.send(RawCommand::Limit { side: Side::Buy, price: 100, qty: 5 }).unwrap();
+    tx.send(RawCommand::Limit { side: Side::Sell, price: 105, qty: 3 }).unwrap();
+    let snap = wait_for_seq(&store, "BTC", 2);
+    assert_eq!(snap.next_seq, 2);
+    assert_eq!(snap.book.order_count(), 2);
+    assert_eq!(OrderBook::from_snapshot(&snap.book).best_ask(), Some((105, 3)));
+}
+
+#[test]
+fn snapshots_on_interval_when_idle() {
+    let store = Arc::new(MemorySnapshotStore::new());
+    let opts = Options { snapshot_interval_millis: 20, ..Options::default() };
+    let ig = MultiIngestor::start_with_books_with_snapshots(vec![("ETH".to_string(), OrderBook::new())], opts, store.clone());
+    ig.tx_cmd.send(ingestor::MultiRawCommand { symbol: "ETH".to_string(), cmd: RawCommand::Limit { side: Side::Sell, price: 50, qty: 1 } }).unwrap();
+    let snap = wait_for_seq(&store, "ETH", 1);
+    assert_eq!(snap.book.asks[0].price, 50);
+}
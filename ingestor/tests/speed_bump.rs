@@ -0,0 +1,51 @@
+use ingestor::speed_bump::is_taker;
+use ingestor::{Attachments, MultiIngestor, Options, RawCommand, SpeedBump};
+use match_engine::{OrderBook, OrderId, OrderRequest, Side, TimeInForce};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+fn book_with_ask() -> OrderBook {
+    let mut book = OrderBook::new();
+    book.submit_limit(Side::Sell, 100, 5).unwrap();
+    book
+}
+
+#[test]
+fn maker_cancel_overtakes_a_bumped_taker() {
+    let books = vec![("BUMP".to_string(), book_with_ask()), ("PLAIN".to_string(), book_with_ask())];
+    let speed_bumps = HashMap::from([("BUMP".to_string(), SpeedBump::fixed(100_000))]);
+    let ig = MultiIngestor::start_with_attachments(books, Options::default(), Attachments { speed_bumps, ..Attachments::default() }).unwrap();
+    let start = Instant::now();
+    for sym in ["BUMP", "PLAIN"] {
+        // The cancel is sent after the market order
+        ig.routes[sym].send(RawCommand::Market { side: Side::Buy, qty: 5 }).unwrap();
+        ig.routes[sym].send(RawCommand::Cancel { id: OrderId(1), account: None }).unwrap();
+    }
+    let mut last_seq = HashMap::new();
+    let mut done = 0;
+    while done < 4 {
+        let p = ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap();
+        done += p.commands;
+        last_seq.insert(p.symbol.clone(), p.last_seq);
+    }
+    // Only the unbumped symbol traded; the bumped market order came after the cancel
+    let trades: Vec<_> = ig.rx_trade.try_iter().collect();
+    assert_eq!(trades.iter().map(|(s, t)| (s.as_str(), t.qty)).collect::<Vec<_>>(), vec![("PLAIN", 5)]);
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert_eq!(last_seq["BUMP"], 1);
+}
+
+#[test]
+fn takers_are_market_ioc_fok_and_marketable_limits() {
+    let book = book_with_ask();
+    let limit = |price| RawCommand::Limit { side: Side::Buy, price, qty: 1 };
+    assert!(is_taker(&book, &RawCommand::Market { side: Side::Sell, qty: 1 }));
+    assert!(is_taker(&book, &limit(100)));
+    assert!(!is_taker(&book, &limit(99)));
+    assert!(is_taker(&book, &RawCommand::Submit(OrderRequest::limit(Side::Buy, 90, 1).with_tif(TimeInForce::Ioc))));
+    assert!(is_taker(&book, &RawCommand::Submit(OrderRequest::limit(Side::Buy, 90, 1).with_tif(TimeInForce::Fok))));
+    assert!(!is_taker(&book, &RawCommand::Submit(OrderRequest::limit(Side::Buy, 100, 1).post_only())));
+    assert!(is_taker(&book, &limit(101).sourced(3, 0)));
+    assert!(!is_taker(&book, &RawCommand::Cancel { id: OrderId(1), account: None }));
+    assert_eq!(SpeedBump::random(500, 100, 7), SpeedBump { min_micros: 500, max_micros: 500, seed: 7 });
+}
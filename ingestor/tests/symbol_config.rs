@@ -0,0 +1,30 @@
+use ingestor::{MultiIngestor, MultiRawCommand, RawCommand};
+use match_engine::{EngineError, OrderBook, Side, SymbolConfig};
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[test]
+fn each_symbol_is_checked_against_its_own_config() {
+    let books = vec![("BTC".to_string(), OrderBook::new()), ("ETH".to_string(), OrderBook::new()), ("SOL".to_string(), OrderBook::new())];
+    let configs = HashMap::from([("BTC".to_string(), SymbolConfig { tick: 10, ..SymbolConfig::default() }), ("ETH".to_string(), SymbolConfig { lot: 5, ..SymbolConfig::default() })]);
+    let ig = MultiIngestor::start_with_books(books, 8, configs).unwrap();
+    for symbol in ["BTC", "ETH", "SOL"] {
+        for (price, qty) in [(105, 3), (110, 5)] { ig.tx_cmd.send(MultiRawCommand { symbol: symbol.to_string(), cmd: RawCommand::Limit { side: Side::Buy, price, qty } }).unwrap(); }
+    }
+    let mut done = 0;
+    while done < 3 {
+        if ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().last_seq == 1 { done += 1; }
+    }
+    let resting = |symbol: &str| -> Vec<(u64, u64)> { ig.book_snapshot(symbol).unwrap().book.bids.iter().flat_map(|l| l.orders.iter().map(move |o| (l.price, o.qty))).collect() };
+    // BTC rejects the 105 price, ETH the 3 lot; SOL has no config
+    assert_eq!(resting("BTC"), vec![(110, 5)]);
+    assert_eq!(resting("ETH"), vec![(110, 5)]);
+    assert_eq!(resting("SOL"), vec![(110, 5), (105, 3)]);
+}
+
+#[test]
+fn a_config_the_book_refuses_fails_the_start() {
+    let configs = HashMap::from([("ETH".to_string(), SymbolConfig { lot: 0, ..SymbolConfig::default() })]);
+    let started = MultiIngestor::start_with_books(vec![("BTC".to_string(), OrderBook::new()), ("ETH".to_string(), OrderBook::new())], 8, configs);
+    assert!(matches!(started, Err(EngineError::InvalidConfig(m)) if m.contains("ETH")));
+}
@@ -0,0 +1,29 @@
+use ingestor::{MetricsRegistry, MultiIngestor, RawCommand};
+use match_engine::{OrderBook, Side};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn stats_track_batches_and_export_to_metrics() {
+    let ig = MultiIngestor::start_with_books(vec![("BTC".to_string(), OrderBook::new()), ("ETH".to_string(), OrderBook::new())], 8, HashMap::new()).unwrap();
+    let tx = ig.routes.get("BTC").unwrap();
+    for i in 0..20u64 { tx.send(RawCommand::Limit { side: Side::Buy, price: 100 + i, qty: 1 }).unwrap(); }
+    let mut done = 0;
+    while done < 20 { done += ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().commands; }
+
+    let stats = ig.stats();
+    assert_eq!(stats.iter().map(|s| s.symbol.as_str()).collect::<Vec<_>>(), vec!["BTC", "ETH"]);
+    let (btc, eth) = (&stats[0], &stats[1]);
+    assert_eq!((btc.commands_total, btc.queue_depth), (20, 0));
+    assert!(btc.batches_total >= 3 && btc.max_batch_size <= 8);
+    assert!(btc.since_last_batch.is_some() && btc.commands_per_sec > 0.0);
+    assert_eq!((eth.commands_total, eth.since_last_batch), (0, None));
+    // Rate is measured between polls
+    assert_eq!(ig.stats()[0].commands_per_sec, 0.0);
+
+    let registry = Arc::new(MetricsRegistry::new());
+    ig.telemetry().export(registry.as_ref());
+    assert_eq!(registry.get("me_worker_commands_total", "BTC"), Some(20.0));
+    assert!(registry.render_prometheus().contains("me_worker_queue_depth{symbol=\"ETH\"} 0"));
+}
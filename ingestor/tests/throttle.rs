@@ -0,0 +1,40 @@
+use ingestor::schedule::now_micros;
+use ingestor::{MultiIngestor, Options, RawCommand, ThrottleEvent, ThrottleOwner};
+use match_engine::{AccountId, EntryThrottle, OrderBook, OrderId, OrderRequest, Side};
+use std::time::Duration;
+
+fn await_commands(ig: &MultiIngestor, n: usize) {
+    let mut done = 0;
+    while done < n { done += ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().commands; }
+}
+
+#[test]
+fn producer_throttle_drops_excess_orders_and_reports_them() {
+    let throttle = EntryThrottle { max_orders: 3, window_micros: 10_000_000 };
+    let ig = MultiIngestor::start_with_books_with_config(vec![("BTC".to_string(), OrderBook::new())], Options { producer_throttle: Some(throttle), ..Options::default() });
+    for i in 0..5 { ig.routes["BTC"].send(RawCommand::Limit { side: Side::Buy, price: 90 + i, qty: 1 }.sourced(7, now_micros())).unwrap(); }
+    // Cancels and other producers' orders pass, as do orders sent without a producer
+    ig.routes["BTC"].send(RawCommand::Cancel { id: OrderId(1), account: None }.sourced(7, now_micros())).unwrap();
+    for _ in 0..2 { ig.routes["BTC"].send(RawCommand::Market { side: Side::Sell, qty: 1 }.sourced(8, now_micros())).unwrap(); }
+    ig.routes["BTC"].send(RawCommand::Limit { side: Side::Buy, price: 80, qty: 1 }).unwrap();
+    await_commands(&ig, 7);
+    let events: Vec<ThrottleEvent> = ig.rx_throttle.try_iter().collect();
+    assert_eq!(events.len(), 2);
+    for e in &events {
+        assert_eq!((e.symbol.as_str(), e.owner, e.rate, e.limit, e.window_micros), ("BTC", ThrottleOwner::Producer(7), 3, 3, 10_000_000));
+        assert!(e.retry_after_micros > 0 && e.retry_after_micros <= 10_000_000);
+    }
+    // Only the first three orders were sequenced; the first was cancelled and the next two sold into
+    assert_eq!(ig.rx_trade.try_iter().map(|(_, t)| t.price).collect::<Vec<_>>(), vec![92, 91]);
+}
+
+#[test]
+fn account_throttle_hits_reach_the_throttle_channel() {
+    let mut book = OrderBook::new();
+    book.set_entry_throttle(Some(EntryThrottle { max_orders: 1, window_micros: 10_000_000 }));
+    let ig = MultiIngestor::start_with_books_with_config(vec![("ETH".to_string(), book)], Options::default());
+    for _ in 0..2 { ig.routes["ETH"].send(RawCommand::Submit(OrderRequest::limit(Side::Buy, 99, 1).with_account(AccountId(5)))).unwrap(); }
+    await_commands(&ig, 1);
+    let event = ig.rx_throttle.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!((event.symbol.as_str(), event.owner, event.rate, event.limit), ("ETH", ThrottleOwner::Account(AccountId(5)), 1, 1));
+}
@@ -0,0 +1,36 @@
+use ingestor::{MultiIngestor, PriceCondition, RawCommand, TriggerError};
+use match_engine::{OrderBook, Side};
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[test]
+fn trade_on_reference_symbol_fires_hedge_leg() {
+    let books = vec![("BTC".to_string(), OrderBook::new()), ("ETH".to_string(), OrderBook::new())];
+    let ig = MultiIngestor::start_with_books(books, 16, HashMap::new()).unwrap();
+    let recv_done = |n: usize| { let mut d = 0; while d < n { d += ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().commands; } };
+    assert_eq!(ig.add_trigger("SOL", PriceCondition::LastAtOrAbove(1), "ETH", RawCommand::Market { side: Side::Buy, qty: 1 }), Err(TriggerError::UnknownSymbol("SOL".into())));
+
+    ig.routes["ETH"].send(RawCommand::Limit { side: Side::Sell, price: 50, qty: 10 }).unwrap();
+    recv_done(1);
+    let hedge = ig.add_trigger("BTC", PriceCondition::LastAtOrAbove(105), "ETH", RawCommand::Market { side: Side::Buy, qty: 4 }).unwrap();
+    let other = ig.add_trigger("BTC", PriceCondition::AskAtOrBelow(90), "ETH", RawCommand::Market { side: Side::Buy, qty: 1 }).unwrap();
+    assert_eq!(ig.armed_triggers(), 2);
+
+    // Trade below the threshold: nothing fires
+    ig.routes["BTC"].send(RawCommand::Limit { side: Side::Sell, price: 100, qty: 1 }).unwrap();
+    ig.routes["BTC"].send(RawCommand::Market { side: Side::Buy, qty: 1 }).unwrap();
+    recv_done(2);
+    assert!(ig.rx_triggered.try_recv().is_err());
+
+    ig.routes["BTC"].send(RawCommand::Limit { side: Side::Sell, price: 106, qty: 1 }).unwrap();
+    ig.routes["BTC"].send(RawCommand::Market { side: Side::Buy, qty: 1 }).unwrap();
+    let fired = ig.rx_triggered.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!((fired.id, fired.target.as_str(), fired.observed), (hedge, "ETH", 106));
+    // The hedge leg trades on ETH
+    let eth = (0..3).map(|_| ig.rx_trade.recv_timeout(Duration::from_secs(5)).unwrap()).find(|(s, _)| s == "ETH").unwrap();
+    assert_eq!((eth.1.price, eth.1.qty), (50, 4));
+
+    assert!(ig.cancel_trigger(other));
+    assert!(!ig.cancel_trigger(hedge));
+    assert_eq!(ig.armed_triggers(), 0);
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::temp_path;
+use ingestor::journal::read_journal;
+use ingestor::{Attachments, FsyncPolicy, Ingestor, JournalConfig, JournalWriter, MultiIngestor, MultiRawCommand, Options, RawCommand};
+use match_engine::{Command, FaultAction, OrderBook, OrderId, Side};
+use std::io;
+use std::time::Duration;
+
+#[test]
+fn workers_journal_every_command_before_applying_it() {
+    let cfg = JournalConfig::new(temp_path("multi"), FsyncPolicy::EveryBatch);
+    let books = vec![("BTC/USDT".to_string(), OrderBook::new()), ("ETH".to_string(), OrderBook::new())];
+    let ig = MultiIngestor::start_with_attachments(books, Options { batch_size: 4, ..Options::default() }, Attachments { journal: Some(cfg.clone()), ..Attachments::default() }).unwrap();
+    for i in 0..20u64 {
+        let side = if i % 2 == 0 { Side::Sell } else { Side::Buy };
+        for symbol in ["BTC/USDT", "ETH"] { ig.tx_cmd.send(MultiRawCommand { symbol: symbol.to_string(), cmd: RawCommand::Limit { side, price: 100 + i % 3, qty: 1 + i % 4 } }).unwrap(); }
+    }
+    ig.tx_cmd.send(MultiRawCommand { symbol: "ETH".to_string(), cmd: RawCommand::Cancel { id: OrderId(1), account: None } }).unwrap();
+    let mut seen = 0;
+    while seen < 41 { seen += ig.rx_progress.recv_timeout(Duration::from_secs(5)).unwrap().commands; }
+
+    // Each symbol has its own log, in seq order with no gaps, and replaying it rebuilds the live book
+    for symbol in ["BTC/USDT", "ETH"] {
+        let log = read_journal(&cfg.path_for(symbol)).unwrap();
+        assert_eq!(log.iter().map(Command::seq).collect::<Vec<_>>(), (0..log.len() as u64).collect::<Vec<_>>());
+        let mut replayed = OrderBook::new();
+        let mut trades = Vec::new();
+        let _ = replayed.process_commands_batch_presorted_unchecked(&log, &mut trades);
+        assert_eq!(replayed.snapshot(), ig.book_snapshot(symbol).unwrap().book);
+    }
+    assert!(cfg.path_for("BTC/USDT").ends_with("BTC%2FUSDT.journal"));
+    assert_eq!(read_journal(&cfg.path_for("ETH")).unwrap().len(), 21);
+}
+
+struct Broken;
+
+impl JournalWriter for Broken {
+    fn append(&mut self, _: &[Command]) -> io::Result<()> { Err(io::Error::other("disk full")) }
+    fn sync(&mut self) -> io::Result<()> { Ok(()) }
+    fn compact(&mut self, _: u64) -> io::Result<ingestor::journal::CompactStats> { Ok(Default::default()) }
+}
+
+#[test]
+fn a_failed_journal_stops_the_worker_before_anything_is_applied() {
+    // Single book: nothing is matched and the sender sees the stopped worker
+    let ig = Ingestor::start_with_book_journaled(OrderBook::new(), 8, Box::new(Broken));
+    let _ = ig.tx_cmd.send(RawCommand::Limit { side: Side::Sell, price: 100, qty: 1 });
+    let _ = ig.tx_cmd.send(RawCommand::Limit { side: Side::Buy, price: 100, qty: 1 });
+    assert!(ig.rx_trade.recv_timeout(Duration::from_secs(5)).is_err());
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while ig.tx_cmd.send(RawCommand::Market { side: Side::Buy, qty: 1 }).is_ok() { assert!(std::time::Instant::now() < deadline); std::thread::yield_now(); }
+
+    // Multi-symbol: a journal that cannot be opened stops that worker with a fault
+    let dir = temp_path("not-a-dir");
+    std::fs::write(&dir, b"").unwrap();
+    let books = vec![("X".to_string(), OrderBook::new())];
+    let ig = MultiIngestor::start_with_attachments(books, Options::default(), Attachments { journal: Some(JournalConfig::new(&dir, FsyncPolicy::Never)), ..Attachments::default() }).unwrap();
+    let (symbol, fault) = ig.rx_fault.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!((symbol.as_str(), fault.seq, fault.action), ("X", 0, FaultAction::Stopped));
+    assert!(ig.book_snapshot("X").is_none());
+}